@@ -13,6 +13,7 @@ mod sh1106;
 
 use defmt::*;
 use embassy_executor::Spawner;
+use embassy_futures::select::{select, Either};
 use embassy_stm32::bind_interrupts;
 use embassy_stm32::exti::ExtiInput;
 use embassy_stm32::gpio::{Input, Pull};
@@ -28,7 +29,12 @@ use {defmt_rtt as _, panic_probe as _};
 
 use crate::encoder::Encoder;
 use crate::sh1106::Sh1106;
-use isochron_protocol::{ControllerCommand, DisplayCommand, FrameParser, InputEvent};
+use isochron_display::input::{DEFAULT_DOUBLE_CLICK_MS, DEFAULT_LONG_PRESS_MS};
+use isochron_display::{ButtonDetector, ButtonTiming};
+use isochron_protocol::{
+    ControllerCommand, DisplayCommand, FrameError, FrameParser, InputEvent, CHANNEL_DISPLAY,
+    DEFAULT_CONTRAST, DEFAULT_HEARTBEAT_MS, DISPLAY_WIDTH_PX,
+};
 
 use embassy_stm32::exti;
 
@@ -38,10 +44,20 @@ bind_interrupts!(struct Irqs {
     EXTI0_1 => exti::InterruptHandler<embassy_stm32::interrupt::typelevel::EXTI0_1>;
 });
 
+/// Attempts at the display init sequence before giving up
+const DISPLAY_INIT_ATTEMPTS: u8 = 3;
+
+/// Delay between display retries (ms)
+const DISPLAY_RETRY_DELAY_MS: u64 = 50;
+
 /// Display state for rendering
 pub struct DisplayState {
     pub lines: [heapless::String<21>; 8],
     pub invert: Option<(u8, u8, u8)>, // row, start, end
+    /// Raw page bytes from Blit commands, drawn over the text
+    pub graphics: [[u8; DISPLAY_WIDTH_PX]; 8],
+    /// Columns of each page holding blitted bytes (start, end)
+    pub blits: [Option<(u8, u8)>; 8],
     pub dirty: bool,
 }
 
@@ -59,6 +75,8 @@ impl DisplayState {
                 heapless::String::new(),
             ],
             invert: None,
+            graphics: [[0; DISPLAY_WIDTH_PX]; 8],
+            blits: [None; 8],
             dirty: true,
         }
     }
@@ -68,6 +86,25 @@ impl DisplayState {
             line.clear();
         }
         self.invert = None;
+        self.blits = [None; 8];
+        self.dirty = true;
+    }
+
+    /// Store blitted bytes; they stay on screen until the next clear
+    pub fn blit(&mut self, page: u8, col: u8, data: &[u8]) {
+        let start = col as usize;
+        let end = (start + data.len()).min(DISPLAY_WIDTH_PX);
+        if page >= 8 || start >= end {
+            return;
+        }
+        self.graphics[page as usize][start..end].copy_from_slice(&data[..end - start]);
+
+        // Grow the page's span to cover the new bytes
+        let span = &mut self.blits[page as usize];
+        *span = Some(match *span {
+            Some((s, e)) => (s.min(start as u8), e.max(end as u8)),
+            None => (start as u8, end as u8),
+        });
         self.dirty = true;
     }
 
@@ -104,8 +141,17 @@ static DISPLAY_REFRESH: Signal<CriticalSectionRawMutex, ()> = Signal::new();
 /// Signal for input events to send to controller
 static INPUT_EVENT: Signal<CriticalSectionRawMutex, InputEvent> = Signal::new();
 
-/// Heartbeat interval
-const HEARTBEAT_MS: u64 = 1000;
+/// Signal carrying a new heartbeat interval from the controller (ms)
+static HEARTBEAT_INTERVAL: Signal<CriticalSectionRawMutex, u16> = Signal::new();
+
+/// Signal carrying a link test result to send to the controller
+static LINK_TEST_RESULT: Signal<CriticalSectionRawMutex, DisplayCommand> = Signal::new();
+
+/// Signal carrying a new display contrast from the controller
+static CONTRAST: Signal<CriticalSectionRawMutex, u8> = Signal::new();
+
+/// Signal carrying the sequence number of the last accepted sequenced frame
+static ACK_SEQ: Signal<CriticalSectionRawMutex, u8> = Signal::new();
 
 #[embassy_executor::main]
 async fn main(spawner: Spawner) {
@@ -123,7 +169,18 @@ async fn main(spawner: Spawner) {
 
     // Initialize OLED display
     let mut display = Sh1106::new(i2c);
-    if let Err(e) = display.init().await {
+    let mut attempt = 1;
+    let init = loop {
+        match display.init().await {
+            Err(e) if e.is_retryable() && attempt < DISPLAY_INIT_ATTEMPTS => {
+                warn!("Display init attempt {} failed: {:?}, retrying", attempt, e);
+                attempt += 1;
+                Timer::after_millis(DISPLAY_RETRY_DELAY_MS).await;
+            }
+            result => break result,
+        }
+    };
+    if let Err(e) = init {
         error!("Failed to initialize display: {:?}", e);
     } else {
         info!("OLED initialized");
@@ -172,14 +229,39 @@ async fn uart_rx_task(mut rx: usart::UartRx<'static, Async>) {
 
     let mut parser = FrameParser::new();
     let mut buf = [0u8; 1];
+    // Report a controller on older firmware once
+    let mut legacy_reported = false;
 
     loop {
         match rx.read(&mut buf).await {
             Ok(()) => {
-                if let Ok(Some(frame)) = parser.feed(buf[0]) {
+                let frame = match parser.feed(buf[0]) {
+                    Err(FrameError::LegacyFrame) if !legacy_reported => {
+                        legacy_reported = true;
+                        error!("Controller sends the legacy frame format");
+                        {
+                            let mut state = DISPLAY_STATE.lock().await;
+                            state.clear();
+                            state.set_text(0, 0, "Controller firmware");
+                            state.set_text(1, 0, "is out of date");
+                        }
+                        DISPLAY_REFRESH.signal(());
+                        continue;
+                    }
+                    result => result,
+                };
+                if let Ok(Some(frame)) = frame {
+                    if frame.channel != CHANNEL_DISPLAY {
+                        // Other traffic sharing the link, not for the display
+                        continue;
+                    }
                     match ControllerCommand::from_frame(&frame) {
                         Ok(cmd) => {
                             handle_controller_command(cmd).await;
+                            // Sequenced frames are resent until acknowledged
+                            if let Some(seq) = frame.seq {
+                                ACK_SEQ.signal(seq);
+                            }
                         }
                         Err(e) => {
                             warn!("Failed to parse command: {:?}", e);
@@ -231,12 +313,42 @@ async fn handle_controller_command(cmd: ControllerCommand) {
             }
             DISPLAY_REFRESH.signal(());
         }
+        ControllerCommand::Blit { page, col, data } => {
+            trace!("Blit page {} col {}: {} bytes", page, col, data.len());
+            {
+                let mut state = DISPLAY_STATE.lock().await;
+                state.blit(page, col, &data);
+            }
+            DISPLAY_REFRESH.signal(());
+        }
+        ControllerCommand::SetContrast(contrast) => {
+            trace!("Contrast {}", contrast);
+            CONTRAST.signal(contrast);
+            DISPLAY_REFRESH.signal(());
+        }
+        ControllerCommand::LinkConfig { heartbeat_ms } => {
+            trace!("Heartbeat interval {} ms", heartbeat_ms);
+            HEARTBEAT_INTERVAL.signal(heartbeat_ms);
+        }
+        ControllerCommand::VersionInfo { config_schema } => {
+            trace!("Controller config schema {}", config_schema);
+        }
+        ControllerCommand::LinkTest { pattern, received } => {
+            let result = pattern.verify(&received);
+            trace!("Link test: {:?}", result);
+            LINK_TEST_RESULT.signal(result);
+        }
+        ControllerCommand::SchedulerState { .. } => {
+            // Host telemetry, not meant for the display
+            trace!("Ignoring scheduler state");
+        }
         ControllerCommand::Reset => {
             info!("Reset requested");
             {
                 let mut state = DISPLAY_STATE.lock().await;
                 state.clear();
             }
+            CONTRAST.signal(DEFAULT_CONTRAST);
             DISPLAY_REFRESH.signal(());
         }
     }
@@ -247,10 +359,20 @@ async fn handle_controller_command(cmd: ControllerCommand) {
 async fn uart_tx_task(mut tx: usart::UartTx<'static, Async>) {
     info!("UART TX task started");
 
-    let mut heartbeat = Ticker::every(Duration::from_millis(HEARTBEAT_MS));
+    // Start at the protocol default until the controller sends its interval
+    let mut interval_ms = DEFAULT_HEARTBEAT_MS;
+    let mut heartbeat = Ticker::every(Duration::from_millis(interval_ms as u64));
     let mut buf = [0u8; 64];
 
     loop {
+        if let Some(ms) = HEARTBEAT_INTERVAL.try_take() {
+            if ms != interval_ms {
+                info!("Heartbeat interval set to {} ms", ms);
+                interval_ms = ms;
+                heartbeat = Ticker::every(Duration::from_millis(interval_ms as u64));
+            }
+        }
+
         // Check for input events (non-blocking)
         if let Some(event) = INPUT_EVENT.try_take() {
             if let Ok(frame) = DisplayCommand::Input(event).to_frame() {
@@ -261,12 +383,24 @@ async fn uart_tx_task(mut tx: usart::UartTx<'static, Async>) {
             }
         }
 
-        // Send periodic heartbeat (PING)
-        heartbeat.next().await;
-        if let Ok(frame) = DisplayCommand::Ping.to_frame() {
+        // Echo the result of the last link test
+        if let Some(result) = LINK_TEST_RESULT.try_take() {
+            if let Ok(frame) = result.to_frame() {
+                if let Ok(len) = frame.encode(&mut buf) {
+                    tx.write(&buf[..len]).await.ok();
+                }
+            }
+        }
+
+        // Send periodic heartbeat (PING), or an ACK as soon as one is due
+        let msg = match select(heartbeat.next(), ACK_SEQ.wait()).await {
+            Either::First(()) => DisplayCommand::Ping,
+            Either::Second(seq) => DisplayCommand::Ack { seq },
+        };
+        if let Ok(frame) = msg.to_frame() {
             if let Ok(len) = frame.encode(&mut buf) {
                 tx.write(&buf[..len]).await.ok();
-                trace!("Sent heartbeat");
+                trace!("Sent {:?}", msg);
             }
         }
     }
@@ -286,44 +420,74 @@ async fn encoder_task(a: Input<'static>, b: Input<'static>) {
     }
 }
 
+/// Button timing: long-press threshold and double-click window
+///
+/// Set `double_click_ms` to 0 to report every click immediately.
+const BUTTON_TIMING: ButtonTiming = ButtonTiming {
+    long_press_ms: DEFAULT_LONG_PRESS_MS,
+    double_click_ms: DEFAULT_DOUBLE_CLICK_MS,
+};
+
+/// Milliseconds since boot (wraps after ~49 days)
+fn uptime_ms() -> u32 {
+    embassy_time::Instant::now().as_millis() as u32
+}
+
 /// Button press task
 #[embassy_executor::task]
 async fn button_task(mut btn: ExtiInput<'static>) {
     info!("Button task started");
 
+    let mut detector = ButtonDetector::new(BUTTON_TIMING);
+
     loop {
-        btn.wait_for_falling_edge().await;
-        let press_start = embassy_time::Instant::now();
-
-        // Debounce
-        Timer::after(Duration::from_millis(20)).await;
-
-        if btn.is_low() {
-            // Wait for release or long press timeout
-            let long_press =
-                embassy_time::with_timeout(Duration::from_millis(500), btn.wait_for_rising_edge())
-                    .await;
-
-            match long_press {
-                Ok(()) => {
-                    // Short press
-                    let duration = press_start.elapsed();
-                    if duration.as_millis() > 50 {
-                        INPUT_EVENT.signal(InputEvent::EncoderClick);
-                        debug!("Button: Click");
-                    }
+        // Wait for the next edge, or until the detector has something due
+        let pressed = detector.is_pressed();
+        let wait_edge = async {
+            if pressed {
+                btn.wait_for_rising_edge().await
+            } else {
+                btn.wait_for_falling_edge().await
+            }
+        };
+        let edge = match detector.next_deadline() {
+            Some(deadline) => {
+                let remaining = (deadline.wrapping_sub(uptime_ms()) as i32).max(0);
+                embassy_time::with_timeout(Duration::from_millis(remaining as u64), wait_edge)
+                    .await
+                    .is_ok()
+            }
+            None => {
+                wait_edge.await;
+                true
+            }
+        };
+
+        let event = if edge {
+            let edge_ms = uptime_ms();
+            if pressed {
+                // Debounce after release
+                Timer::after(Duration::from_millis(50)).await;
+                if btn.is_high() {
+                    detector.release(edge_ms)
+                } else {
+                    None
                 }
-                Err(_) => {
-                    // Long press timeout
-                    INPUT_EVENT.signal(InputEvent::EncoderLongPress);
-                    debug!("Button: LongPress");
-                    // Wait for actual release
-                    btn.wait_for_rising_edge().await;
+            } else {
+                // Debounce
+                Timer::after(Duration::from_millis(20)).await;
+                if btn.is_low() {
+                    detector.press(edge_ms);
                 }
+                None
             }
+        } else {
+            detector.poll(uptime_ms())
+        };
 
-            // Debounce after release
-            Timer::after(Duration::from_millis(50)).await;
+        if let Some(event) = event {
+            INPUT_EVENT.signal(event);
+            debug!("Button: {:?}", event);
         }
     }
 }
@@ -337,6 +501,12 @@ async fn display_task(mut display: Sh1106<I2c<'static, Async, embassy_stm32::i2c
         // Wait for refresh signal
         DISPLAY_REFRESH.wait().await;
 
+        if let Some(contrast) = CONTRAST.try_take() {
+            if let Err(e) = display.set_contrast(contrast).await {
+                warn!("Failed to set contrast: {:?}", e);
+            }
+        }
+
         // Lock state and render
         let state = DISPLAY_STATE.lock().await;
 
@@ -349,13 +519,39 @@ async fn display_task(mut display: Sh1106<I2c<'static, Async, embassy_stm32::i2c
                 }
             }
 
+            // Blitted graphics replace the text beneath them
+            for (page, span) in state.blits.iter().enumerate() {
+                if let Some((start, end)) = *span {
+                    let bytes = &state.graphics[page][start as usize..end as usize];
+                    display.draw_bytes(page as u8, start, bytes).await.ok();
+                }
+            }
+
             // Handle invert region
             if let Some((row, start, end)) = state.invert {
                 display.invert_region(row, start, end).await.ok();
             }
 
-            display.flush().await.ok();
-            trace!("Display updated");
+            match display.flush().await {
+                Ok(()) => trace!("Display updated"),
+                Err(e) if e.is_retryable() => {
+                    // Transient bus fault: try again on a fresh refresh
+                    warn!("Display flush failed: {:?}, retrying", e);
+                    drop(state);
+                    Timer::after_millis(DISPLAY_RETRY_DELAY_MS).await;
+                    DISPLAY_REFRESH.signal(());
+                    continue;
+                }
+                Err(e) => {
+                    // Keep the task alive: re-init the panel and redraw
+                    // on the next refresh from the controller
+                    error!("Display flush failed: {:?}, reinitializing", e);
+                    drop(state);
+                    if let Err(e) = display.init().await {
+                        error!("Display reinit failed: {:?}", e);
+                    }
+                }
+            }
         }
 
         // Note: We don't mark clean here because we don't have mutable access
@@ -3,6 +3,11 @@
 //! Driver for 128x64 SH1106-based OLED displays via I2C.
 //! Optimized for text display with 6x8 font (21 chars x 8 rows).
 
+use embassy_stm32::i2c;
+use embedded_hal::i2c::Error as _;
+use isochron_display::DisplayError;
+use isochron_protocol::DEFAULT_CONTRAST;
+
 use crate::font::FONT_6X8;
 
 /// SH1106 I2C address (typically 0x3C or 0x3D)
@@ -36,9 +41,23 @@ mod cmd {
     pub const SET_CHARGE_PUMP: u8 = 0x8D;
 }
 
+/// Classify a bus error for display recovery
+pub trait ClassifyError {
+    /// Map this error onto the display error taxonomy
+    fn classify(&self) -> DisplayError;
+}
+
+impl ClassifyError for i2c::Error {
+    fn classify(&self) -> DisplayError {
+        DisplayError::from_bus(self.kind(), matches!(self, i2c::Error::Timeout))
+    }
+}
+
 /// SH1106 OLED driver
 pub struct Sh1106<I2C> {
     i2c: I2C,
+    /// Init sequence completed
+    initialized: bool,
     /// Frame buffer (1 bit per pixel, organized as pages)
     buffer: [[u8; WIDTH]; PAGES],
 }
@@ -46,17 +65,25 @@ pub struct Sh1106<I2C> {
 impl<I2C> Sh1106<I2C>
 where
     I2C: embedded_hal_async::i2c::I2c,
+    I2C::Error: ClassifyError,
 {
     /// Create a new SH1106 driver
     pub fn new(i2c: I2C) -> Self {
         Self {
             i2c,
+            initialized: false,
             buffer: [[0; WIDTH]; PAGES],
         }
     }
 
     /// Initialize the display
-    pub async fn init(&mut self) -> Result<(), I2C::Error> {
+    ///
+    /// A NACK on the first command means no display answered its address
+    /// (`NotConnected`); a NACK later in the sequence means the display is
+    /// present but rejected it (`Init`).
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        self.initialized = false;
+
         // Initialization sequence for SH1106
         let init_cmds: &[u8] = &[
             cmd::DISPLAY_OFF,
@@ -74,7 +101,7 @@ where
             cmd::SET_COM_PINS,
             0x12, // Alternative COM config
             cmd::SET_CONTRAST,
-            0xCF, // High contrast
+            DEFAULT_CONTRAST,
             cmd::SET_PRECHARGE,
             0xF1,
             cmd::SET_VCOM_DETECT,
@@ -83,20 +110,32 @@ where
             cmd::DISPLAY_ON,
         ];
 
-        for &c in init_cmds {
-            self.command(c).await?;
+        for (i, &c) in init_cmds.iter().enumerate() {
+            match self.command(c).await {
+                Err(DisplayError::NotConnected) if i > 0 => return Err(DisplayError::Init),
+                result => result?,
+            }
         }
 
+        self.initialized = true;
         Ok(())
     }
 
     /// Send a command to the display
-    async fn command(&mut self, cmd: u8) -> Result<(), I2C::Error> {
-        self.i2c.write(SH1106_ADDR, &[0x00, cmd]).await
+    async fn command(&mut self, cmd: u8) -> Result<(), DisplayError> {
+        self.write(&[0x00, cmd]).await
+    }
+
+    /// Write raw bytes to the display
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), DisplayError> {
+        self.i2c
+            .write(SH1106_ADDR, bytes)
+            .await
+            .map_err(|e| e.classify())
     }
 
     /// Clear the frame buffer
-    pub async fn clear(&mut self) -> Result<(), I2C::Error> {
+    pub async fn clear(&mut self) -> Result<(), DisplayError> {
         for page in self.buffer.iter_mut() {
             page.fill(0);
         }
@@ -104,7 +143,7 @@ where
     }
 
     /// Draw text at the specified position (row 0-7, col 0-20)
-    pub async fn draw_text(&mut self, row: u8, col: u8, text: &str) -> Result<(), I2C::Error> {
+    pub async fn draw_text(&mut self, row: u8, col: u8, text: &str) -> Result<(), DisplayError> {
         if row >= PAGES as u8 {
             return Ok(());
         }
@@ -129,13 +168,29 @@ where
         Ok(())
     }
 
+    /// Write raw page bytes starting at pixel column `col` of `page`
+    ///
+    /// Each byte is 8 vertical pixels, LSB at the top. Bytes past the
+    /// right edge are dropped.
+    pub async fn draw_bytes(&mut self, page: u8, col: u8, data: &[u8]) -> Result<(), DisplayError> {
+        if page >= PAGES as u8 || col as usize >= WIDTH {
+            return Ok(());
+        }
+
+        let start = col as usize;
+        let end = (start + data.len()).min(WIDTH);
+        self.buffer[page as usize][start..end].copy_from_slice(&data[..end - start]);
+
+        Ok(())
+    }
+
     /// Invert a region of a row (for selection highlighting)
     pub async fn invert_region(
         &mut self,
         row: u8,
         start_col: u8,
         end_col: u8,
-    ) -> Result<(), I2C::Error> {
+    ) -> Result<(), DisplayError> {
         if row >= PAGES as u8 {
             return Ok(());
         }
@@ -152,7 +207,11 @@ where
     }
 
     /// Flush the frame buffer to the display
-    pub async fn flush(&mut self) -> Result<(), I2C::Error> {
+    pub async fn flush(&mut self) -> Result<(), DisplayError> {
+        if !self.initialized {
+            return Err(DisplayError::NotInitialized);
+        }
+
         for page in 0..PAGES {
             // Set page address
             self.command(cmd::SET_PAGE_ADDR | (page as u8)).await?;
@@ -164,22 +223,21 @@ where
             let mut data = [0u8; WIDTH + 1];
             data[0] = 0x40; // Data mode
             data[1..].copy_from_slice(&self.buffer[page]);
-            self.i2c.write(SH1106_ADDR, &data).await?;
+            self.write(&data).await?;
         }
 
         Ok(())
     }
 
     /// Set display contrast (0-255)
-    #[allow(dead_code)]
-    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), I2C::Error> {
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError> {
         self.command(cmd::SET_CONTRAST).await?;
         self.command(contrast).await
     }
 
     /// Turn display on/off
     #[allow(dead_code)]
-    pub async fn set_display_on(&mut self, on: bool) -> Result<(), I2C::Error> {
+    pub async fn set_display_on(&mut self, on: bool) -> Result<(), DisplayError> {
         if on {
             self.command(cmd::DISPLAY_ON).await
         } else {
@@ -189,7 +247,7 @@ where
 
     /// Invert display colors
     #[allow(dead_code)]
-    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), I2C::Error> {
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), DisplayError> {
         if inverted {
             self.command(cmd::SET_INVERSE).await
         } else {
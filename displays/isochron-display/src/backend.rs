@@ -2,12 +2,22 @@
 //!
 //! Defines the interface for different display types.
 
+use embedded_hal::i2c::ErrorKind;
+
 /// Display backend errors
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DisplayError {
-    /// Communication error with display
-    Communication,
+    /// Initialization sequence was rejected by the display
+    Init,
+    /// Bus fault (arbitration loss, overrun, corrupted transfer)
+    BusError,
+    /// Bus transfer did not complete in time
+    Timeout,
+    /// Display did not acknowledge its address
+    NotConnected,
+    /// Operation not supported by this display
+    Unsupported,
     /// Invalid coordinates or dimensions
     InvalidCoordinates,
     /// Display not initialized
@@ -16,6 +26,37 @@ pub enum DisplayError {
     BufferOverflow,
 }
 
+impl DisplayError {
+    /// Check if the operation is worth retrying
+    ///
+    /// Bus faults and timeouts are usually transient. A display that does
+    /// not answer, or an unsupported operation, will fail the same way again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, DisplayError::BusError | DisplayError::Timeout)
+    }
+
+    /// Map a bus error, given whether the HAL reported it as a timeout
+    ///
+    /// embedded-hal reports timeouts as `ErrorKind::Other`, so a HAL whose
+    /// errors tell them apart passes that on to keep them distinct.
+    pub fn from_bus(kind: ErrorKind, timed_out: bool) -> Self {
+        if timed_out {
+            DisplayError::Timeout
+        } else {
+            kind.into()
+        }
+    }
+}
+
+impl From<ErrorKind> for DisplayError {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::NoAcknowledge(_) => DisplayError::NotConnected,
+            _ => DisplayError::BusError,
+        }
+    }
+}
+
 /// Display backend trait
 ///
 /// Provides a hardware-agnostic interface for rendering to displays.
@@ -69,3 +110,71 @@ pub trait GraphicsDisplayBackend: DisplayBackend {
     /// Get pixel dimensions
     fn pixel_dimensions(&self) -> (u16, u16);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{Error, NoAcknowledgeSource};
+
+    /// Bus error as reported by a HAL that distinguishes timeouts
+    #[derive(Debug)]
+    enum MockI2cError {
+        Nack,
+        Arbitration,
+        Overrun,
+        Timeout,
+    }
+
+    impl Error for MockI2cError {
+        fn kind(&self) -> ErrorKind {
+            match self {
+                MockI2cError::Nack => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+                MockI2cError::Arbitration => ErrorKind::ArbitrationLoss,
+                MockI2cError::Overrun => ErrorKind::Overrun,
+                MockI2cError::Timeout => ErrorKind::Other,
+            }
+        }
+    }
+
+    /// Mapping a backend applies to its HAL's errors
+    fn classify(e: MockI2cError) -> DisplayError {
+        DisplayError::from_bus(e.kind(), matches!(e, MockI2cError::Timeout))
+    }
+
+    #[test]
+    fn test_i2c_error_kinds() {
+        assert_eq!(classify(MockI2cError::Nack), DisplayError::NotConnected);
+        assert_eq!(classify(MockI2cError::Arbitration), DisplayError::BusError);
+        assert_eq!(classify(MockI2cError::Overrun), DisplayError::BusError);
+        assert_eq!(classify(MockI2cError::Timeout), DisplayError::Timeout);
+
+        let data_nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+        assert_eq!(DisplayError::from(data_nack), DisplayError::NotConnected);
+        assert_eq!(DisplayError::from(ErrorKind::Bus), DisplayError::BusError);
+    }
+
+    #[test]
+    fn test_timeout_distinct_from_nack() {
+        let timeout = classify(MockI2cError::Timeout);
+        let nack = classify(MockI2cError::Nack);
+        assert_ne!(timeout, nack);
+        // A timeout is worth retrying, a missing display is not
+        assert!(timeout.is_retryable());
+        assert!(!nack.is_retryable());
+
+        // A HAL that can't tell timeouts apart gets a plain bus fault
+        assert_eq!(
+            DisplayError::from_bus(ErrorKind::Other, false),
+            DisplayError::BusError
+        );
+    }
+
+    #[test]
+    fn test_retryable() {
+        assert!(classify(MockI2cError::Timeout).is_retryable());
+        assert!(classify(MockI2cError::Overrun).is_retryable());
+        assert!(!classify(MockI2cError::Nack).is_retryable());
+        assert!(!DisplayError::Unsupported.is_retryable());
+        assert!(!DisplayError::Init.is_retryable());
+    }
+}
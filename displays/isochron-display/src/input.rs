@@ -2,6 +2,14 @@
 //!
 //! Provides abstraction for different input methods (encoder, touch, buttons).
 
+use isochron_protocol::InputEvent;
+
+/// Default hold time for a long press (ms)
+pub const DEFAULT_LONG_PRESS_MS: u32 = 500;
+
+/// Default window for the second click of a double-click (ms)
+pub const DEFAULT_DOUBLE_CLICK_MS: u32 = 300;
+
 /// Navigation events from input devices
 ///
 /// These events are hardware-agnostic and can be generated by:
@@ -105,7 +113,7 @@ impl EncoderState {
             // Button released
             self.button_pressed = false;
             let held_ms = current_time_ms.saturating_sub(self.button_press_time);
-            if held_ms > 500 {
+            if held_ms > DEFAULT_LONG_PRESS_MS as u64 {
                 return Some(NavigationEvent::LongSelect);
             } else {
                 return Some(NavigationEvent::Select);
@@ -115,3 +123,230 @@ impl EncoderState {
         event
     }
 }
+
+/// Button timing thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ButtonTiming {
+    /// Hold time that turns a press into a long press (ms)
+    pub long_press_ms: u32,
+    /// Window after a click in which a second click makes a double-click
+    /// (ms, 0 = double-click disabled)
+    pub double_click_ms: u32,
+}
+
+impl Default for ButtonTiming {
+    fn default() -> Self {
+        Self {
+            long_press_ms: DEFAULT_LONG_PRESS_MS,
+            double_click_ms: DEFAULT_DOUBLE_CLICK_MS,
+        }
+    }
+}
+
+/// Click / double-click / long-press discrimination
+///
+/// Fed with debounced press and release edges plus periodic polls, all
+/// stamped with a millisecond clock. A click is held back until the
+/// double-click window has passed, so a double-click never also reports
+/// a click. The long press fires while the button is still held; the
+/// following release produces no event.
+#[derive(Debug, Clone)]
+pub struct ButtonDetector {
+    timing: ButtonTiming,
+    /// When the current press started (None = released)
+    pressed_at: Option<u32>,
+    /// Long press already reported for the current press
+    long_sent: bool,
+    /// Release time of a click waiting for a possible second click
+    pending_click_at: Option<u32>,
+}
+
+impl ButtonDetector {
+    /// Create a detector with the given timing
+    pub const fn new(timing: ButtonTiming) -> Self {
+        Self {
+            timing,
+            pressed_at: None,
+            long_sent: false,
+            pending_click_at: None,
+        }
+    }
+
+    /// Whether the button is currently held
+    pub fn is_pressed(&self) -> bool {
+        self.pressed_at.is_some()
+    }
+
+    /// Button pressed (after debounce)
+    pub fn press(&mut self, now_ms: u32) {
+        if self.pressed_at.is_none() {
+            self.pressed_at = Some(now_ms);
+            self.long_sent = false;
+        }
+    }
+
+    /// Button released (after debounce)
+    pub fn release(&mut self, now_ms: u32) -> Option<InputEvent> {
+        self.pressed_at.take()?;
+
+        if self.long_sent {
+            self.long_sent = false;
+            return None;
+        }
+
+        if self.pending_click_at.take().is_some() {
+            return Some(InputEvent::EncoderDoubleClick);
+        }
+
+        if self.timing.double_click_ms == 0 {
+            Some(InputEvent::EncoderClick)
+        } else {
+            self.pending_click_at = Some(now_ms);
+            None
+        }
+    }
+
+    /// Check for time-based events (long press, expired double-click window)
+    pub fn poll(&mut self, now_ms: u32) -> Option<InputEvent> {
+        if let Some(pressed_at) = self.pressed_at {
+            if !self.long_sent && now_ms.wrapping_sub(pressed_at) >= self.timing.long_press_ms {
+                self.long_sent = true;
+                // A click followed by a long press reports the long press only
+                self.pending_click_at = None;
+                return Some(InputEvent::EncoderLongPress);
+            }
+            return None;
+        }
+
+        let released_at = self.pending_click_at?;
+        if now_ms.wrapping_sub(released_at) >= self.timing.double_click_ms {
+            self.pending_click_at = None;
+            return Some(InputEvent::EncoderClick);
+        }
+        None
+    }
+
+    /// Time at which `poll` next has something to report, if any
+    pub fn next_deadline(&self) -> Option<u32> {
+        match self.pressed_at {
+            Some(pressed_at) if !self.long_sent => {
+                Some(pressed_at.wrapping_add(self.timing.long_press_ms))
+            }
+            Some(_) => None,
+            None => self
+                .pending_click_at
+                .map(|released_at| released_at.wrapping_add(self.timing.double_click_ms)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> ButtonDetector {
+        ButtonDetector::new(ButtonTiming {
+            long_press_ms: 500,
+            double_click_ms: 300,
+        })
+    }
+
+    #[test]
+    fn test_single_click_after_window() {
+        let mut btn = detector();
+        btn.press(1000);
+        assert_eq!(btn.release(1100), None);
+        assert_eq!(btn.next_deadline(), Some(1400));
+
+        assert_eq!(btn.poll(1399), None);
+        assert_eq!(btn.poll(1400), Some(InputEvent::EncoderClick));
+        assert_eq!(btn.poll(2000), None);
+        assert_eq!(btn.next_deadline(), None);
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut btn = detector();
+        btn.press(1000);
+        assert_eq!(btn.release(1100), None);
+        assert_eq!(btn.poll(1200), None);
+
+        btn.press(1250);
+        assert_eq!(btn.release(1350), Some(InputEvent::EncoderDoubleClick));
+
+        // No stray click afterwards
+        assert_eq!(btn.poll(2000), None);
+    }
+
+    #[test]
+    fn test_second_click_too_late() {
+        let mut btn = detector();
+        btn.press(1000);
+        btn.release(1100);
+        assert_eq!(btn.poll(1400), Some(InputEvent::EncoderClick));
+
+        btn.press(1450);
+        assert_eq!(btn.release(1500), None);
+        assert_eq!(btn.poll(1800), Some(InputEvent::EncoderClick));
+    }
+
+    #[test]
+    fn test_long_press() {
+        let mut btn = detector();
+        btn.press(1000);
+        assert_eq!(btn.next_deadline(), Some(1500));
+        assert_eq!(btn.poll(1499), None);
+        assert_eq!(btn.poll(1500), Some(InputEvent::EncoderLongPress));
+
+        // Reported once, release is silent
+        assert_eq!(btn.poll(1600), None);
+        assert_eq!(btn.next_deadline(), None);
+        assert_eq!(btn.release(2000), None);
+        assert_eq!(btn.poll(3000), None);
+    }
+
+    #[test]
+    fn test_click_then_long_press() {
+        let mut btn = detector();
+        btn.press(1000);
+        btn.release(1100);
+        btn.press(1200);
+        assert_eq!(btn.poll(1700), Some(InputEvent::EncoderLongPress));
+        assert_eq!(btn.release(1800), None);
+        assert_eq!(btn.poll(2500), None);
+    }
+
+    #[test]
+    fn test_double_click_disabled() {
+        let mut btn = ButtonDetector::new(ButtonTiming {
+            long_press_ms: 800,
+            double_click_ms: 0,
+        });
+        btn.press(1000);
+        assert_eq!(btn.release(1100), Some(InputEvent::EncoderClick));
+        btn.press(1150);
+        assert_eq!(btn.release(1200), Some(InputEvent::EncoderClick));
+
+        // Configured long-press threshold
+        btn.press(2000);
+        assert_eq!(btn.poll(2500), None);
+        assert_eq!(btn.poll(2800), Some(InputEvent::EncoderLongPress));
+    }
+
+    #[test]
+    fn test_clock_wraparound() {
+        let mut btn = detector();
+        btn.press(u32::MAX - 100);
+        btn.release(u32::MAX - 50);
+        assert_eq!(btn.poll(100), None);
+        assert_eq!(btn.poll(250), Some(InputEvent::EncoderClick));
+    }
+
+    #[test]
+    fn test_release_without_press() {
+        let mut btn = detector();
+        assert_eq!(btn.release(1000), None);
+        assert_eq!(btn.poll(5000), None);
+    }
+}
@@ -29,5 +29,5 @@ pub mod screen;
 
 // Re-export key types
 pub use backend::{DisplayBackend, DisplayError};
-pub use input::{InputSource, NavigationEvent};
+pub use input::{ButtonDetector, ButtonTiming, InputSource, NavigationEvent};
 pub use screen::{Screen, SCREEN_COLS, SCREEN_ROWS};
@@ -6,6 +6,22 @@
 //! - ADC2: GPIO28
 //! - ADC3: GPIO29
 //! - ADC4: Internal temperature sensor
+//!
+//! Sensors name their channel by GPIO number in the config. Every sensor
+//! reads through the one ADC, shared behind a [`SharedAdc`] mutex, on
+//! the channel it was handed by [`AdcPins::channel`].
+
+use core::cell::RefCell;
+
+use embassy_rp::adc::{Adc, Async, Channel};
+use embassy_rp::gpio::Pull;
+use embassy_rp::peripherals::{PIN_26, PIN_27, PIN_28, PIN_29};
+use embassy_rp::Peri;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+
+/// The ADC, shared by every sensor reading a channel on it
+pub type SharedAdc = Mutex<CriticalSectionRawMutex, RefCell<Adc<'static, Async>>>;
 
 /// ADC channel identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -47,6 +63,16 @@ impl AdcChannel {
     }
 }
 
+/// ADC channel allocation error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AdcError {
+    /// GPIO is not one of the analog-capable pins (26-29)
+    NotAnAdcPin(u8),
+    /// Channel is already allocated to another sensor
+    InUse(AdcChannel),
+}
+
 /// ADC allocator
 pub struct AdcAllocator {
     allocated: [bool; 5],
@@ -77,6 +103,14 @@ impl AdcAllocator {
         }
     }
 
+    /// Allocate the ADC channel on a GPIO pin
+    pub fn allocate_gpio(&mut self, gpio: u8) -> Result<AdcChannel, AdcError> {
+        let channel = AdcChannel::from_gpio(gpio).ok_or(AdcError::NotAnAdcPin(gpio))?;
+        self.allocate(channel)
+            .map_err(|_| AdcError::InUse(channel))?;
+        Ok(channel)
+    }
+
     /// Release an ADC channel
     pub fn release(&mut self, channel: AdcChannel) {
         self.allocated[channel as usize] = false;
@@ -87,3 +121,83 @@ impl AdcAllocator {
         self.allocated[channel as usize]
     }
 }
+
+/// Analog-capable pins, handed out as ADC channels by GPIO number
+pub struct AdcPins {
+    allocator: AdcAllocator,
+    gpio26: Option<Peri<'static, PIN_26>>,
+    gpio27: Option<Peri<'static, PIN_27>>,
+    gpio28: Option<Peri<'static, PIN_28>>,
+    gpio29: Option<Peri<'static, PIN_29>>,
+}
+
+impl AdcPins {
+    /// Take ownership of the analog-capable pins
+    pub fn new(
+        gpio26: Peri<'static, PIN_26>,
+        gpio27: Peri<'static, PIN_27>,
+        gpio28: Peri<'static, PIN_28>,
+        gpio29: Peri<'static, PIN_29>,
+    ) -> Self {
+        Self {
+            allocator: AdcAllocator::new(),
+            gpio26: Some(gpio26),
+            gpio27: Some(gpio27),
+            gpio28: Some(gpio28),
+            gpio29: Some(gpio29),
+        }
+    }
+
+    /// Allocate the ADC channel on a GPIO pin, e.g. a sensor's `sensor_pin`
+    pub fn channel(&mut self, gpio: u8) -> Result<Channel<'static>, AdcError> {
+        let channel = self.allocator.allocate_gpio(gpio)?;
+        let pin = match channel {
+            AdcChannel::Adc0 => self.gpio26.take().map(|p| Channel::new_pin(p, Pull::None)),
+            AdcChannel::Adc1 => self.gpio27.take().map(|p| Channel::new_pin(p, Pull::None)),
+            AdcChannel::Adc2 => self.gpio28.take().map(|p| Channel::new_pin(p, Pull::None)),
+            AdcChannel::Adc3 => self.gpio29.take().map(|p| Channel::new_pin(p, Pull::None)),
+            AdcChannel::Temperature => None,
+        };
+        pin.ok_or(AdcError::InUse(channel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct_pins_get_distinct_channels() {
+        let mut alloc = AdcAllocator::new();
+
+        let th0 = alloc.allocate_gpio(27).unwrap();
+        let thb = alloc.allocate_gpio(26).unwrap();
+        assert_eq!(th0, AdcChannel::Adc1);
+        assert_eq!(thb, AdcChannel::Adc0);
+        assert!(alloc.is_allocated(AdcChannel::Adc0));
+        assert!(alloc.is_allocated(AdcChannel::Adc1));
+        assert!(!alloc.is_allocated(AdcChannel::Adc2));
+    }
+
+    #[test]
+    fn test_invalid_adc_pin_rejected() {
+        let mut alloc = AdcAllocator::new();
+
+        assert_eq!(alloc.allocate_gpio(11), Err(AdcError::NotAnAdcPin(11)));
+        assert_eq!(alloc.allocate_gpio(30), Err(AdcError::NotAnAdcPin(30)));
+    }
+
+    #[test]
+    fn test_shared_pin_rejected() {
+        let mut alloc = AdcAllocator::new();
+
+        alloc.allocate_gpio(28).unwrap();
+        assert_eq!(
+            alloc.allocate_gpio(28),
+            Err(AdcError::InUse(AdcChannel::Adc2))
+        );
+
+        alloc.release(AdcChannel::Adc2);
+        assert_eq!(alloc.allocate_gpio(28), Ok(AdcChannel::Adc2));
+    }
+}
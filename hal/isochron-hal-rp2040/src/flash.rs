@@ -14,7 +14,10 @@ use sequential_storage::cache::NoCache;
 use sequential_storage::map;
 
 // Re-export shared types from isochron-hal
-pub use isochron_hal::flash::{FlashError, StorageKey};
+pub use isochron_hal::flash::{
+    active_config_slot, commit_config_slot, open_blob, read_with_retry, seal_blob, stage_config,
+    ChecksumKind, ConfigSlot, FlashError, StorageKey, BLOB_HEADER_LEN,
+};
 
 /// Flash storage configuration
 pub const FLASH_SIZE: usize = 2 * 1024 * 1024; // 2MB flash on SKR Pico
@@ -1,8 +1,12 @@
 //! GPIO allocation and management
 //!
-//! Tracks which GPIO pins are in use to prevent conflicts.
+//! Tracks which GPIO pins are in use to prevent conflicts, and wraps
+//! embassy-rp pins for code written against [`isochron_hal::InputPin`]
+//! and [`isochron_hal::OutputPin`].
 
+use embassy_rp::gpio::{Input, Output, OutputOpenDrain};
 use heapless::FnvIndexSet;
+use isochron_hal::{InputPin, OutputPin};
 
 /// Maximum number of GPIO pins on RP2040
 pub const GPIO_COUNT: usize = 30;
@@ -58,6 +62,93 @@ impl GpioAllocator {
     }
 }
 
+/// GPIO input pin
+pub struct RpInput<'d> {
+    input: Input<'d>,
+}
+
+impl<'d> RpInput<'d> {
+    /// Wrap a configured input
+    pub fn new(input: Input<'d>) -> Self {
+        Self { input }
+    }
+}
+
+impl InputPin for RpInput<'_> {
+    fn is_high(&self) -> bool {
+        self.input.is_high()
+    }
+}
+
+/// GPIO output pin
+pub struct RpOutput<'d> {
+    output: Output<'d>,
+}
+
+impl<'d> RpOutput<'d> {
+    /// Wrap a configured output
+    pub fn new(output: Output<'d>) -> Self {
+        Self { output }
+    }
+}
+
+impl OutputPin for RpOutput<'_> {
+    fn set_high(&mut self) {
+        self.output.set_high();
+    }
+
+    fn set_low(&mut self) {
+        self.output.set_low();
+    }
+
+    fn toggle(&mut self) {
+        self.output.toggle();
+    }
+
+    fn is_set_high(&self) -> bool {
+        self.output.is_set_high()
+    }
+}
+
+/// GPIO open-drain pin
+///
+/// Driving high releases the pin to its pull-up, and reads return the
+/// level on the wire, as a 1-Wire bus needs.
+pub struct RpOpenDrain<'d> {
+    pin: OutputOpenDrain<'d>,
+}
+
+impl<'d> RpOpenDrain<'d> {
+    /// Wrap a configured open-drain output
+    pub fn new(pin: OutputOpenDrain<'d>) -> Self {
+        Self { pin }
+    }
+}
+
+impl OutputPin for RpOpenDrain<'_> {
+    fn set_high(&mut self) {
+        self.pin.set_high();
+    }
+
+    fn set_low(&mut self) {
+        self.pin.set_low();
+    }
+
+    fn toggle(&mut self) {
+        self.pin.toggle();
+    }
+
+    fn is_set_high(&self) -> bool {
+        self.pin.is_set_high()
+    }
+}
+
+impl InputPin for RpOpenDrain<'_> {
+    fn is_high(&self) -> bool {
+        self.pin.is_high()
+    }
+}
+
 /// Parse a pin string from config
 ///
 /// Supports formats:
@@ -0,0 +1,42 @@
+//! I2C bus master
+//!
+//! Wraps an embassy-rp I2C peripheral in blocking mode so drivers written
+//! against [`isochron_hal::I2cBus`] (e.g. digital temperature sensors)
+//! can use it. Transfers are a few bytes at 100-400 kHz, short enough to
+//! block a control loop for.
+
+use embassy_rp::i2c::{Blocking, Error, I2c, Instance};
+use isochron_hal::I2cBus;
+
+/// RP2040 I2C peripheral in blocking mode
+pub struct RpI2c<'d, T: Instance> {
+    i2c: I2c<'d, T, Blocking>,
+}
+
+impl<'d, T: Instance> RpI2c<'d, T> {
+    /// Wrap an I2C peripheral set up with `I2c::new_blocking`
+    pub fn new(i2c: I2c<'d, T, Blocking>) -> Self {
+        Self { i2c }
+    }
+}
+
+impl<'d, T: Instance> I2cBus for RpI2c<'d, T> {
+    type Error = Error;
+
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.blocking_write(address, data)
+    }
+
+    fn read(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.blocking_read(address, buf)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write_data: &[u8],
+        read_buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.i2c.blocking_write_read(address, write_data, read_buf)
+    }
+}
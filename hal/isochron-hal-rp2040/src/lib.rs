@@ -3,22 +3,30 @@
 //! This crate provides RP2040-specific implementations of the shared
 //! `isochron-hal` traits, plus RP2040-specific functionality:
 //!
-//! - GPIO allocation and management
+//! - GPIO allocation and management, and pin wrappers (implement
+//!   `isochron_hal::InputPin` and `isochron_hal::OutputPin`)
 //! - Dynamic pin allocation for config-driven setup
 //! - UART peripheral allocation
 //! - ADC channel management
+//! - Blocking I2C master (implements `isochron_hal::I2cBus`)
 //! - PIO-based step pulse generation
 //! - Flash storage driver (implements `isochron_hal::FlashStorage`)
+//! - Watchdog timer (implements `isochron_hal::Watchdog`)
 
 #![no_std]
 
 pub mod adc;
 pub mod flash;
 pub mod gpio;
+pub mod i2c;
 pub mod pins;
 pub mod pio;
 pub mod stepper;
 pub mod uart;
+pub mod watchdog;
 
 // Re-export shared traits from isochron-hal for convenience
-pub use isochron_hal::{FlashStorage as FlashStorageTrait, StorageKey};
+pub use isochron_hal::{
+    FlashStorage as FlashStorageTrait, InputPin as InputPinTrait, OutputPin as OutputPinTrait,
+    StorageKey, Watchdog as WatchdogTrait,
+};
@@ -10,8 +10,10 @@
 //! share the same PIO program loaded once. The RP2040 has 2 PIO blocks
 //! with 4 SMs each, so we can drive up to 8 steppers (though we only need 4).
 //!
-//! The step frequency is controlled by the PIO clock divider. The pulse
-//! width is fixed at a minimum of 2.5µs (safe for all stepper drivers).
+//! The PIO clock divider is fixed per stepper so that the configured step
+//! pulse width fits in the delay field of the "step high" instruction. The
+//! step frequency is then set by pushing a low-time loop count into the
+//! state machine's TX FIFO.
 
 // Embassy PIO imports for when we implement the actual driver
 // use embassy_rp::pio::{
@@ -22,12 +24,24 @@
 /// System clock frequency (RP2040 default)
 pub const SYS_CLK_HZ: u32 = 125_000_000;
 
-/// Minimum pulse width in nanoseconds (2.5µs is safe for all drivers)
-pub const MIN_PULSE_WIDTH_NS: u32 = 2500;
+/// Default step pulse width in nanoseconds (2.5µs is safe for all drivers)
+pub const DEFAULT_STEP_PULSE_NS: u32 = 2500;
 
-/// Maximum step frequency in Hz (limited by pulse width)
+/// Shortest step pulse width accepted in nanoseconds (TMC2209 minimum)
+pub const MIN_STEP_PULSE_NS: u32 = 100;
+
+/// Maximum step frequency in Hz
 pub const MAX_STEP_FREQ_HZ: u32 = 200_000;
 
+/// Largest delay encodable in a PIO instruction (no side-set bits)
+pub const MAX_PIO_DELAY: u8 = 31;
+
+/// PIO cycles per step outside the pulse delay and the low-time loop
+///
+/// `pull`, `mov x`, `mov y`, `set pins, 1`, `set pins, 0` and the final
+/// (not taken) `jmp y--` iteration.
+pub const STEP_OVERHEAD_CYCLES: u32 = 6;
+
 /// PIO step generator configuration
 #[derive(Debug, Clone)]
 pub struct StepGeneratorConfig {
@@ -41,6 +55,8 @@ pub struct StepGeneratorConfig {
     pub enable_inverted: bool,
     /// Steps per revolution (including microstepping)
     pub steps_per_rev: u32,
+    /// Minimum step pulse high time in nanoseconds
+    pub step_pulse_ns: u32,
 }
 
 impl Default for StepGeneratorConfig {
@@ -51,40 +67,98 @@ impl Default for StepGeneratorConfig {
             enable_pin: 12,
             enable_inverted: true,
             steps_per_rev: 200 * 16, // 200 full steps * 16 microsteps
+            step_pulse_ns: DEFAULT_STEP_PULSE_NS,
         }
     }
 }
 
-/// Calculate the clock divider for a target frequency
-///
-/// The PIO program runs at SYS_CLK / divider Hz.
-/// With 2 instructions per cycle (set high, set low), the step frequency is:
-/// freq = SYS_CLK / (divider * 2)
-///
-/// Therefore: divider = SYS_CLK / (freq * 2)
-///
-/// Returns (integer_part, fractional_part) for the 16.8 fixed-point divider.
-pub fn calc_clock_divider(freq_hz: u32) -> (u16, u8) {
-    if freq_hz == 0 {
-        return (0xFFFF, 0xFF); // Maximum divider = stopped
+/// PIO timing derived from the configured step pulse width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepTiming {
+    /// Integer PIO clock divider (PIO clock = SYS_CLK / divider)
+    pub clock_divider: u16,
+    /// Delay on the "step high" instruction (high time = delay + 1 cycles)
+    pub pulse_delay: u8,
+}
+
+impl StepTiming {
+    /// Derive timing for a requested pulse width
+    ///
+    /// Picks the smallest integer divider whose PIO clock can express the
+    /// pulse within the instruction delay field, then rounds the pulse up
+    /// to whole PIO cycles so it is never shorter than requested.
+    pub fn from_pulse_ns(step_pulse_ns: u32) -> Self {
+        let pulse_ns = step_pulse_ns.max(MIN_STEP_PULSE_NS);
+        let sys_cycles = pulse_cycles(pulse_ns, SYS_CLK_HZ);
+        let max_cycles = MAX_PIO_DELAY as u32 + 1;
+        let divider = sys_cycles.div_ceil(max_cycles).clamp(1, u16::MAX as u32) as u16;
+
+        Self {
+            clock_divider: divider,
+            pulse_delay: pulse_delay_cycles(pulse_ns, SYS_CLK_HZ / divider as u32),
+        }
     }
 
-    // divider = SYS_CLK / (freq * instructions_per_step)
-    // We use 2 instructions per step (high + low)
-    // To get 8-bit fractional precision, multiply by 256 first
-    // divider * 256 = (SYS_CLK * 256) / (freq * 2)
-    let divisor = freq_hz * 2;
-    let divider_x256 = (SYS_CLK_HZ as u64 * 256) / (divisor as u64);
+    /// PIO clock frequency in Hz
+    pub fn pio_clk_hz(&self) -> u32 {
+        SYS_CLK_HZ / self.clock_divider.max(1) as u32
+    }
+
+    /// Actual step pulse width in nanoseconds
+    pub fn pulse_ns(&self) -> u32 {
+        let cycles = self.pulse_delay as u64 + 1;
+        (cycles * 1_000_000_000 / self.pio_clk_hz() as u64) as u32
+    }
+
+    /// Highest step frequency this timing can produce
+    ///
+    /// The low phase is kept at least as long as the high phase.
+    pub fn max_freq_hz(&self) -> u32 {
+        let min_cycles = STEP_OVERHEAD_CYCLES + self.pulse_delay as u32 + self.min_loop_count();
+        (self.pio_clk_hz() / min_cycles).min(MAX_STEP_FREQ_HZ)
+    }
+
+    /// Low-time loop count needed for the low phase to match the high phase
+    fn min_loop_count(&self) -> u32 {
+        // Low phase is `set pins, 0` + loop + `pull` + 2x `mov` = count + 5
+        (self.pulse_delay as u32 + 1).saturating_sub(5)
+    }
+
+    /// Low-time loop count to push into the TX FIFO for a step frequency
+    ///
+    /// Returns `None` for 0 Hz (the state machine should be stopped).
+    pub fn loop_count(&self, freq_hz: u32) -> Option<u32> {
+        if freq_hz == 0 {
+            return None;
+        }
+
+        let freq = freq_hz.min(self.max_freq_hz());
+        let period_cycles = self.pio_clk_hz() / freq;
+        let count = period_cycles.saturating_sub(STEP_OVERHEAD_CYCLES + self.pulse_delay as u32);
+        Some(count.max(self.min_loop_count()))
+    }
+}
 
-    // Split into integer and fractional parts
-    let int_part = (divider_x256 / 256) as u32;
-    let frac_part = (divider_x256 % 256) as u32;
+impl Default for StepTiming {
+    fn default() -> Self {
+        Self::from_pulse_ns(DEFAULT_STEP_PULSE_NS)
+    }
+}
 
-    // Clamp to valid range
-    let int_part = int_part.min(0xFFFF) as u16;
-    let frac_part = frac_part.min(0xFF) as u8;
+/// Number of PIO cycles needed to cover a pulse width (rounded up)
+pub fn pulse_cycles(pulse_ns: u32, pio_clk_hz: u32) -> u32 {
+    let cycles = (pulse_ns as u64 * pio_clk_hz as u64).div_ceil(1_000_000_000);
+    cycles.max(1) as u32
+}
 
-    (int_part, frac_part)
+/// Delay field for the "step high" instruction at a given PIO clock
+///
+/// The pulse is clamped to [`MIN_STEP_PULSE_NS`] and the result to
+/// [`MAX_PIO_DELAY`]; use [`StepTiming::from_pulse_ns`] to pick a clock
+/// where the requested pulse fits.
+pub fn pulse_delay_cycles(pulse_ns: u32, pio_clk_hz: u32) -> u8 {
+    let cycles = pulse_cycles(pulse_ns.max(MIN_STEP_PULSE_NS), pio_clk_hz);
+    (cycles - 1).min(MAX_PIO_DELAY as u32) as u8
 }
 
 /// Convert RPM to step frequency in Hz
@@ -102,23 +176,35 @@ pub fn freq_to_rpm(freq_hz: u32, steps_per_rev: u32) -> u16 {
     ((freq_hz * 60) / steps_per_rev) as u16
 }
 
-/// PIO program for step pulse generation
-///
-/// This is a minimal 2-instruction program that generates a square wave.
-/// The step frequency is controlled by the clock divider.
-#[rustfmt::skip]
-pub const STEP_PROGRAM: &[u16] = &[
-    // .wrap_target
-    0xE001, // set pins, 1      ; Set step pin high
-    0xE000, // set pins, 0      ; Set step pin low
-    // .wrap
-];
-
-/// Assemble the step generator PIO program
+/// Build the step generator PIO program
 ///
-/// Returns the program ready to be loaded into a PIO block.
-pub fn step_program() -> &'static [u16] {
-    STEP_PROGRAM
+/// ```text
+/// .wrap_target
+///     pull noblock        ; take new loop count, or recycle X if FIFO empty
+///     mov x, osr          ; stash loop count for the next noblock pull
+///     mov y, x
+///     set pins, 1 [delay] ; step high for delay + 1 cycles
+///     set pins, 0
+/// low:
+///     jmp y-- low         ; low time
+/// .wrap
+/// ```
+pub fn step_program(pulse_delay: u8) -> pio::Program<32> {
+    use pio::{JmpCondition, MovDestination, MovOperation, MovSource, SetDestination};
+
+    let mut a = pio::Assembler::<32>::new();
+    let mut low = a.label();
+
+    a.pull(false, false);
+    a.mov(MovDestination::X, MovOperation::None, MovSource::OSR);
+    a.mov(MovDestination::Y, MovOperation::None, MovSource::X);
+    a.set_with_delay(SetDestination::PINS, 1, pulse_delay.min(MAX_PIO_DELAY));
+    a.set(SetDestination::PINS, 0);
+    a.bind(&mut low);
+    a.jmp(JmpCondition::YDecNonZero, &mut low);
+
+    // Default wrap is last instruction -> first instruction
+    a.assemble_program()
 }
 
 /// High-level step generator wrapper
@@ -127,6 +213,8 @@ pub fn step_program() -> &'static [u16] {
 /// without directly managing the PIO state machine.
 pub struct StepGenerator {
     config: StepGeneratorConfig,
+    /// PIO timing derived from the configured pulse width
+    timing: StepTiming,
     /// Current frequency in Hz (steps per second)
     current_freq_hz: u32,
     /// Target frequency in Hz
@@ -143,6 +231,7 @@ impl StepGenerator {
     /// Create a new step generator
     pub fn new(config: StepGeneratorConfig) -> Self {
         Self {
+            timing: StepTiming::from_pulse_ns(config.step_pulse_ns),
             config,
             current_freq_hz: 0,
             target_freq_hz: 0,
@@ -157,6 +246,11 @@ impl StepGenerator {
         &self.config
     }
 
+    /// Get the PIO timing
+    pub fn timing(&self) -> &StepTiming {
+        &self.timing
+    }
+
     /// Get current frequency in Hz
     pub fn current_freq(&self) -> u32 {
         self.current_freq_hz
@@ -172,7 +266,7 @@ impl StepGenerator {
     /// Note: This only updates the internal target. The actual PIO
     /// state machine must be updated separately by the driver.
     pub fn set_frequency(&mut self, freq_hz: u32) {
-        self.target_freq_hz = freq_hz.min(MAX_STEP_FREQ_HZ);
+        self.target_freq_hz = freq_hz.min(self.timing.max_freq_hz());
     }
 
     /// Update current frequency to match target
@@ -245,15 +339,79 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_clock_divider() {
-        // At 1kHz step freq, we need clock divider of 62500
-        // (125MHz / (1000 * 2) = 62500)
-        let (int_part, _frac_part) = calc_clock_divider(1000);
-        assert_eq!(int_part, 62500);
+    fn test_pulse_delay_cycles() {
+        // 2.5µs at 12.5MHz = 31.25 cycles -> 32 cycles -> delay 31
+        assert_eq!(pulse_delay_cycles(2500, 12_500_000), 31);
+
+        // 200ns at 125MHz = 25 cycles -> delay 24
+        assert_eq!(pulse_delay_cycles(200, 125_000_000), 24);
 
-        // At 100kHz step freq, we need clock divider of 625
-        let (int_part, _) = calc_clock_divider(100_000);
-        assert_eq!(int_part, 625);
+        // Partial cycles round up so the pulse is never too short
+        // 100ns at 125MHz = 12.5 cycles -> 13 cycles -> delay 12
+        assert_eq!(pulse_delay_cycles(100, 125_000_000), 12);
+    }
+
+    #[test]
+    fn test_pulse_delay_clamps() {
+        // Below the safe minimum clamps to MIN_STEP_PULSE_NS
+        assert_eq!(
+            pulse_delay_cycles(0, 125_000_000),
+            pulse_delay_cycles(MIN_STEP_PULSE_NS, 125_000_000)
+        );
+        assert_eq!(pulse_delay_cycles(10, 125_000_000), 12);
+
+        // Too long for the delay field at this clock clamps to the max delay
+        assert_eq!(pulse_delay_cycles(10_000, 125_000_000), MAX_PIO_DELAY);
+    }
+
+    #[test]
+    fn test_step_timing_from_pulse() {
+        // Default 2.5µs: divider 10 (12.5MHz), 32 cycles high
+        let timing = StepTiming::from_pulse_ns(DEFAULT_STEP_PULSE_NS);
+        assert_eq!(timing.clock_divider, 10);
+        assert_eq!(timing.pulse_delay, 31);
+        assert!(timing.pulse_ns() >= DEFAULT_STEP_PULSE_NS);
+
+        // 1µs (A4988): divider 4 (31.25MHz), 32 cycles = 1024ns
+        let timing = StepTiming::from_pulse_ns(1000);
+        assert_eq!(timing.clock_divider, 4);
+        assert_eq!(timing.pulse_delay, 31);
+        assert_eq!(timing.pulse_ns(), 1024);
+
+        // Below minimum clamps: full system clock, 13 cycles = 104ns
+        let timing = StepTiming::from_pulse_ns(20);
+        assert_eq!(timing.clock_divider, 1);
+        assert_eq!(timing.pulse_delay, 12);
+        assert!(timing.pulse_ns() >= MIN_STEP_PULSE_NS);
+    }
+
+    #[test]
+    fn test_loop_count() {
+        let timing = StepTiming::default();
+
+        // Stopped
+        assert_eq!(timing.loop_count(0), None);
+
+        // 1kHz at 12.5MHz = 12500 cycles, minus 6 overhead and 31 delay
+        assert_eq!(timing.loop_count(1000), Some(12_463));
+
+        // Above the maximum frequency the low phase still matches the pulse
+        let count = timing.loop_count(u32::MAX).unwrap();
+        assert!(count + 5 > timing.pulse_delay as u32);
+        assert!(timing.max_freq_hz() <= MAX_STEP_FREQ_HZ);
+    }
+
+    #[test]
+    fn test_step_program() {
+        let prg = step_program(31);
+        assert_eq!(prg.code.len(), 6);
+
+        // "set pins, 1 [31]" carries the pulse delay
+        assert_eq!(prg.code[3], 0xE001 | (31 << 8));
+        assert_eq!(prg.code[4], 0xE000);
+
+        // Delay is clamped to what fits in the instruction
+        assert_eq!(step_program(200).code[3], 0xE001 | (31 << 8));
     }
 
     #[test]
@@ -286,5 +444,9 @@ mod tests {
 
         gen.sync_frequency();
         assert_eq!(gen.current_freq(), gen.target_freq());
+
+        // Frequency is limited by the configured pulse width
+        gen.set_frequency(u32::MAX);
+        assert_eq!(gen.target_freq(), gen.timing().max_freq_hz());
     }
 }
@@ -2,13 +2,19 @@
 //!
 //! Uses RP2040's Programmable I/O to generate precise step pulses.
 //! Each stepper gets its own state machine for independent control.
+//!
+//! The PIO program does not count pulses, so the driver dead-reckons its
+//! position by integrating the commanded step rate over time.
 
 use embassy_rp::gpio::{Level, Output, Pin};
 use embassy_rp::pio::{Common, Config, Direction as PioDirection, Instance, PioPin, StateMachine};
 use embassy_rp::Peri;
+use embassy_time::Instant;
 use fixed::types::U24F8;
+use isochron_core::motion::{DeadReckoning, StepScale};
+use isochron_core::traits::PositionStatus;
 
-use crate::pio::{calc_clock_divider, StepGeneratorConfig, MAX_STEP_FREQ_HZ};
+use crate::pio::{step_program, StepGeneratorConfig, StepTiming};
 
 /// PIO stepper driver
 ///
@@ -23,12 +29,20 @@ pub struct PioStepper<'d, PIO: Instance, const SM: usize> {
     enable_pin: Output<'d>,
     /// Configuration
     config: StepGeneratorConfig,
+    /// PIO timing derived from the configured pulse width
+    timing: StepTiming,
+    /// State machine config, re-applied on start to jump to the program origin
+    pio_config: Config<'d, PIO>,
     /// Current frequency in Hz
     current_freq_hz: u32,
     /// Is currently running
     running: bool,
     /// Current direction (true = CW)
     direction_cw: bool,
+    /// Commanded steps, CW counted as forward
+    position: DeadReckoning,
+    /// When `position` was last brought up to date
+    position_updated: Instant,
 }
 
 impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
@@ -49,16 +63,12 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
         enable_pin: Peri<'d, EN>,
         config: StepGeneratorConfig,
     ) -> Self {
-        // Load the step pulse program using pio macro
-        // This 2-instruction program generates a square wave on the step pin
-        let prg = pio::pio_asm!(
-            ".wrap_target",
-            "set pins, 1", // Set step pin high
-            "set pins, 0", // Set step pin low
-            ".wrap"
-        );
+        // Load the step pulse program with the high time baked into the
+        // "set pins, 1" delay; the low time comes from the TX FIFO
+        let timing = StepTiming::from_pulse_ns(config.step_pulse_ns);
+        let prg = step_program(timing.pulse_delay);
 
-        let installed = common.load_program(&prg.program);
+        let installed = common.load_program(&prg);
 
         // Create the PIO pin for the step output
         let step_pio_pin = common.make_pio_pin(step_pin);
@@ -68,9 +78,8 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
         cfg.use_program(&installed, &[&step_pio_pin]);
         cfg.set_set_pins(&[&step_pio_pin]);
 
-        // Start with maximum divider (effectively stopped)
-        // FixedU32<U8> has 24 integer bits and 8 fractional bits
-        cfg.clock_divider = U24F8::from_bits(0xFFFF_FF00);
+        // Fixed divider so the pulse width is independent of step rate
+        cfg.clock_divider = U24F8::from_num(timing.clock_divider);
 
         sm.set_config(&cfg);
         sm.set_pin_dirs(PioDirection::Out, &[&step_pio_pin]);
@@ -91,10 +100,28 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
             dir_pin,
             enable_pin,
             config,
+            timing,
+            pio_config: cfg,
             current_freq_hz: 0,
             running: false,
             direction_cw: true,
+            position: DeadReckoning::new(),
+            position_updated: Instant::now(),
+        }
+    }
+
+    /// Add the steps generated since the last update to the position
+    ///
+    /// Called before every change of rate or direction, so each interval
+    /// is integrated at the rate and direction that applied during it.
+    fn update_position(&mut self) {
+        let now = Instant::now();
+        if self.running {
+            let elapsed_us = now.duration_since(self.position_updated).as_micros();
+            self.position
+                .record_run(self.current_freq_hz, elapsed_us, self.direction_cw);
         }
+        self.position_updated = now;
     }
 
     /// Enable the stepper driver
@@ -107,7 +134,12 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
     }
 
     /// Disable the stepper driver
+    ///
+    /// An unpowered motor can be moved by hand, so the position reference
+    /// is dropped until the next home.
     pub fn disable(&mut self) {
+        self.update_position();
+        self.position.invalidate();
         if self.config.enable_inverted {
             self.enable_pin.set_high();
         } else {
@@ -117,6 +149,7 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
 
     /// Set direction
     pub fn set_direction(&mut self, clockwise: bool) {
+        self.update_position();
         self.direction_cw = clockwise;
         if clockwise {
             self.dir_pin.set_low();
@@ -132,23 +165,29 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
 
     /// Set step frequency in Hz
     ///
-    /// This directly updates the PIO clock divider.
+    /// Pushes a new low-time loop count to the state machine, which picks
+    /// it up at the start of the next step.
     pub fn set_frequency(&mut self, freq_hz: u32) {
-        let freq = freq_hz.min(MAX_STEP_FREQ_HZ);
-        self.current_freq_hz = freq;
-
-        if freq == 0 {
+        let Some(count) = self.timing.loop_count(freq_hz) else {
             self.stop();
             return;
-        }
-
-        let (int_div, frac_div) = calc_clock_divider(freq);
-
-        // Convert to U24F8: integer in upper 24 bits, fractional in lower 8 bits
-        let divider_bits = ((int_div as u32) << 8) | (frac_div as u32);
-        self.sm.set_clock_divider(U24F8::from_bits(divider_bits));
+        };
+        self.update_position();
+        self.current_freq_hz = freq_hz.min(self.timing.max_freq_hz());
 
-        if !self.running {
+        if self.running {
+            // Drop any count not yet consumed so the latest speed wins
+            if !self.sm.tx().try_push(count) {
+                self.sm.clear_fifos();
+                self.sm.tx().push(count);
+            }
+        } else {
+            // Count must be queued before enabling, otherwise the first
+            // "pull noblock" recycles a stale X and steps at full rate
+            self.sm.clear_fifos();
+            self.sm.set_config(&self.pio_config);
+            self.sm.restart();
+            self.sm.tx().push(count);
             self.sm.set_enable(true);
             self.running = true;
         }
@@ -175,6 +214,7 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
 
     /// Stop pulse generation
     pub fn stop(&mut self) {
+        self.update_position();
         self.sm.set_enable(false);
         self.running = false;
         self.current_freq_hz = 0;
@@ -189,4 +229,29 @@ impl<'d, PIO: Instance, const SM: usize> PioStepper<'d, PIO, SM> {
     pub fn steps_per_rev(&self) -> u32 {
         self.config.steps_per_rev
     }
+
+    /// Net commanded steps since the last home (CW positive)
+    pub fn position_steps(&mut self) -> i64 {
+        self.update_position();
+        self.position.steps()
+    }
+
+    /// Dead-reckoned position in axis units
+    pub fn position_status(&mut self, scale: &StepScale) -> PositionStatus {
+        self.update_position();
+        self.position.status(scale)
+    }
+
+    /// Reference the position after homing
+    ///
+    /// `position_steps` is the endstop position in steps.
+    pub fn rehome(&mut self, position_steps: i64) {
+        self.update_position();
+        self.position.rehome(position_steps);
+    }
+
+    /// Get the PIO timing (divider and step pulse delay)
+    pub fn timing(&self) -> &StepTiming {
+        &self.timing
+    }
 }
@@ -0,0 +1,63 @@
+//! Watchdog timer
+//!
+//! Wraps the embassy-rp watchdog peripheral so firmware tasks can be
+//! written against [`isochron_hal::Watchdog`]. The scratch registers and
+//! reset reason are passed through: they survive a watchdog reset, which
+//! is how the firmware learns what it was doing when it hung.
+
+use embassy_rp::watchdog::{ResetReason, Watchdog as EmbassyWatchdog};
+use embassy_time::Duration;
+use isochron_hal::Watchdog;
+
+/// Longest timeout the RP2040 watchdog supports (ms)
+///
+/// The 24-bit counter is decremented twice per microsecond tick
+/// (RP2040-E1), halving the range to 0xFFFFFF / 2 µs.
+pub const MAX_TIMEOUT_MS: u32 = 0xFF_FFFF / 2 / 1000;
+
+/// RP2040 watchdog peripheral
+pub struct RpWatchdog {
+    watchdog: EmbassyWatchdog,
+}
+
+impl RpWatchdog {
+    /// Wrap the watchdog peripheral
+    ///
+    /// The watchdog is paused while a debugger halts the core, so
+    /// stepping through code doesn't reset the board.
+    pub fn new(mut watchdog: EmbassyWatchdog) -> Self {
+        watchdog.pause_on_debug(true);
+        Self { watchdog }
+    }
+
+    /// Whether the last reset was caused by this watchdog timing out
+    pub fn reset_by_timeout(&self) -> bool {
+        self.watchdog.reset_reason() == Some(ResetReason::TimedOut)
+    }
+
+    /// Read a scratch register (0-7), preserved across a watchdog reset
+    pub fn scratch(&mut self, index: usize) -> u32 {
+        self.watchdog.get_scratch(index)
+    }
+
+    /// Write a scratch register (0-7)
+    pub fn set_scratch(&mut self, index: usize, value: u32) {
+        self.watchdog.set_scratch(index, value);
+    }
+}
+
+impl Watchdog for RpWatchdog {
+    fn max_timeout_ms(&self) -> u32 {
+        MAX_TIMEOUT_MS
+    }
+
+    fn start(&mut self, timeout_ms: u32) {
+        let timeout_ms = self.clamp_timeout_ms(timeout_ms);
+        self.watchdog
+            .start(Duration::from_millis(timeout_ms as u64));
+    }
+
+    fn feed(&mut self) {
+        self.watchdog.feed();
+    }
+}
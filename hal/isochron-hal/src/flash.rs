@@ -18,10 +18,14 @@ pub enum StorageKey {
     MachineConfigToml = 1,
     /// PID calibration data for heaters
     PidCalibration = 2,
-    /// Reserved for future use
-    Reserved3 = 3,
+    /// User-adjusted profile values
+    ProfileOverrides = 3,
     /// Reserved for future use
     Reserved4 = 4,
+    /// Second binary machine configuration slot (see [`ConfigSlot`])
+    MachineConfigB = 5,
+    /// Which binary machine configuration slot is active
+    ActiveConfigSlot = 6,
 }
 
 impl StorageKey {
@@ -36,8 +40,10 @@ impl StorageKey {
             0 => Some(StorageKey::MachineConfig),
             1 => Some(StorageKey::MachineConfigToml),
             2 => Some(StorageKey::PidCalibration),
-            3 => Some(StorageKey::Reserved3),
+            3 => Some(StorageKey::ProfileOverrides),
             4 => Some(StorageKey::Reserved4),
+            5 => Some(StorageKey::MachineConfigB),
+            6 => Some(StorageKey::ActiveConfigSlot),
             _ => None,
         }
     }
@@ -59,6 +65,254 @@ pub enum FlashError {
     Corrupted,
     /// Storage is full
     Full,
+    /// Blob header uses a checksum algorithm this firmware does not know
+    UnsupportedChecksum,
+}
+
+impl FlashError {
+    /// Whether the operation may succeed if repeated
+    ///
+    /// Only failures of the flash itself qualify; missing or corrupt data
+    /// reads the same every time.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, FlashError::Flash | FlashError::Storage)
+    }
+}
+
+/// Checksum algorithm protecting a stored blob
+///
+/// The id is written into the blob header, so ids must never be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum ChecksumKind {
+    /// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF)
+    Crc16 = 1,
+    /// CRC-32/IEEE (reflected poly 0xEDB88320)
+    #[default]
+    Crc32 = 2,
+}
+
+impl ChecksumKind {
+    /// Get the algorithm id stored in the header
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    /// Look up an algorithm by header id
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(ChecksumKind::Crc16),
+            2 => Some(ChecksumKind::Crc32),
+            _ => None,
+        }
+    }
+
+    /// Compute the checksum of `data`, zero-extended to 32 bits
+    pub fn compute(self, data: &[u8]) -> u32 {
+        match self {
+            ChecksumKind::Crc16 => crc16_ccitt(data) as u32,
+            ChecksumKind::Crc32 => crc32_ieee(data),
+        }
+    }
+}
+
+/// Blob header length: algorithm id plus a 32-bit checksum
+pub const BLOB_HEADER_LEN: usize = 5;
+
+/// Prefix `payload` with a checksum header
+///
+/// Writes `[id][checksum LE u32][payload]` into `out` and returns the
+/// number of bytes used.
+pub fn seal_blob(kind: ChecksumKind, payload: &[u8], out: &mut [u8]) -> Result<usize, FlashError> {
+    let len = BLOB_HEADER_LEN + payload.len();
+    if out.len() < len {
+        return Err(FlashError::BufferTooSmall);
+    }
+    out[0] = kind.id();
+    out[1..BLOB_HEADER_LEN].copy_from_slice(&kind.compute(payload).to_le_bytes());
+    out[BLOB_HEADER_LEN..len].copy_from_slice(payload);
+    Ok(len)
+}
+
+/// Verify a blob written by [`seal_blob`] and return its payload
+///
+/// The algorithm is taken from the header, so blobs written with any
+/// supported checksum can be read regardless of the current setting.
+pub fn open_blob(blob: &[u8]) -> Result<(ChecksumKind, &[u8]), FlashError> {
+    if blob.len() < BLOB_HEADER_LEN {
+        return Err(FlashError::Corrupted);
+    }
+    let kind = ChecksumKind::from_id(blob[0]).ok_or(FlashError::UnsupportedChecksum)?;
+    let stored = u32::from_le_bytes([blob[1], blob[2], blob[3], blob[4]]);
+    let payload = &blob[BLOB_HEADER_LEN..];
+    if kind.compute(payload) != stored {
+        return Err(FlashError::Corrupted);
+    }
+    Ok((kind, payload))
+}
+
+/// Binary machine configuration slot
+///
+/// A new config is staged in the inactive slot and only becomes active
+/// once it has been read back and validated, so an interrupted or bad
+/// update leaves the previous config in use. Slot A is the original
+/// [`StorageKey::MachineConfig`], so a config written before slots
+/// existed stays active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigSlot {
+    /// Stored under [`StorageKey::MachineConfig`]
+    A,
+    /// Stored under [`StorageKey::MachineConfigB`]
+    B,
+}
+
+impl ConfigSlot {
+    /// Storage key holding this slot
+    pub fn key(self) -> StorageKey {
+        match self {
+            ConfigSlot::A => StorageKey::MachineConfig,
+            ConfigSlot::B => StorageKey::MachineConfigB,
+        }
+    }
+
+    /// The other slot
+    pub fn other(self) -> Self {
+        match self {
+            ConfigSlot::A => ConfigSlot::B,
+            ConfigSlot::B => ConfigSlot::A,
+        }
+    }
+}
+
+/// Read the active-slot pointer
+///
+/// Retries transient failures as [`read_with_retry`] does. Slot A if no
+/// pointer has been written yet. A pointer that still can't be read, or
+/// fails its checksum, is an error rather than a guess at slot A, which
+/// could load an older config without notice.
+pub async fn active_config_slot<S: FlashStorage>(
+    storage: &mut S,
+    attempts: u8,
+    wait: impl AsyncFnMut(),
+) -> Result<ConfigSlot, FlashError> {
+    let mut buf = [0u8; BLOB_HEADER_LEN + 1];
+    let len = match read_with_retry(
+        storage,
+        StorageKey::ActiveConfigSlot,
+        &mut buf,
+        attempts,
+        wait,
+    )
+    .await
+    {
+        Err(FlashError::NotFound) => return Ok(ConfigSlot::A),
+        result => result?,
+    };
+    match open_blob(&buf[..len])? {
+        (_, [0]) => Ok(ConfigSlot::A),
+        (_, [1]) => Ok(ConfigSlot::B),
+        _ => Err(FlashError::Corrupted),
+    }
+}
+
+/// Read a value, retrying transient flash failures
+///
+/// Makes up to `attempts` reads, awaiting `wait` between them, while the
+/// read fails with a [retryable](FlashError::is_retryable) error. Any
+/// other result, including [`FlashError::NotFound`], is returned at once,
+/// so a missing config is never mistaken for an unreadable one.
+pub async fn read_with_retry<S: FlashStorage>(
+    storage: &mut S,
+    key: StorageKey,
+    buffer: &mut [u8],
+    attempts: u8,
+    mut wait: impl AsyncFnMut(),
+) -> Result<usize, FlashError> {
+    let mut attempt = 1;
+    loop {
+        match storage.read(key, buffer).await {
+            Err(e) if e.is_retryable() && attempt < attempts => {
+                attempt += 1;
+                wait().await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Write a sealed config blob to the slot other than `active`
+///
+/// The active slot is left untouched; the new config is only used once
+/// [`commit_config_slot`] accepts it. Returns the slot written.
+pub async fn stage_config<S: FlashStorage>(
+    storage: &mut S,
+    active: ConfigSlot,
+    blob: &[u8],
+) -> Result<ConfigSlot, FlashError> {
+    let slot = active.other();
+    storage.write(slot.key(), blob).await?;
+    Ok(slot)
+}
+
+/// Validate a staged slot and make it active
+///
+/// Reads `slot` back into `buffer` and checks the blob checksum, then
+/// hands the payload to `validate`. The active-slot pointer is only
+/// switched if both pass; otherwise [`FlashError::Corrupted`] is
+/// returned and the previous slot stays active.
+pub async fn commit_config_slot<S: FlashStorage>(
+    storage: &mut S,
+    slot: ConfigSlot,
+    buffer: &mut [u8],
+    validate: impl FnOnce(&[u8]) -> bool,
+) -> Result<(), FlashError> {
+    let len = storage.read(slot.key(), buffer).await?;
+    let (_, payload) = open_blob(&buffer[..len])?;
+    if !validate(payload) {
+        return Err(FlashError::Corrupted);
+    }
+
+    let id = match slot {
+        ConfigSlot::A => 0,
+        ConfigSlot::B => 1,
+    };
+    let mut pointer = [0u8; BLOB_HEADER_LEN + 1];
+    let len = seal_blob(ChecksumKind::Crc16, &[id], &mut pointer)?;
+    storage
+        .write(StorageKey::ActiveConfigSlot, &pointer[..len])
+        .await
+}
+
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc32_ieee(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
 }
 
 /// Flash storage trait
@@ -129,3 +383,229 @@ impl sequential_storage::map::Key for StorageKey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"123456789";
+
+    #[test]
+    fn test_check_values() {
+        assert_eq!(ChecksumKind::Crc16.compute(PAYLOAD), 0x29B1);
+        assert_eq!(ChecksumKind::Crc32.compute(PAYLOAD), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_blob_roundtrip_both_algorithms() {
+        for kind in [ChecksumKind::Crc16, ChecksumKind::Crc32] {
+            let mut buf = [0u8; 32];
+            let len = seal_blob(kind, PAYLOAD, &mut buf).unwrap();
+            assert_eq!(len, BLOB_HEADER_LEN + PAYLOAD.len());
+            assert_eq!(buf[0], kind.id());
+            assert_eq!(open_blob(&buf[..len]), Ok((kind, PAYLOAD)));
+        }
+    }
+
+    #[test]
+    fn test_mismatch_detected_both_algorithms() {
+        for kind in [ChecksumKind::Crc16, ChecksumKind::Crc32] {
+            let mut buf = [0u8; 32];
+            let len = seal_blob(kind, PAYLOAD, &mut buf).unwrap();
+
+            let mut flipped = buf;
+            flipped[len - 1] ^= 0x01;
+            assert_eq!(open_blob(&flipped[..len]), Err(FlashError::Corrupted));
+
+            let mut bad_sum = buf;
+            bad_sum[1] ^= 0x80;
+            assert_eq!(open_blob(&bad_sum[..len]), Err(FlashError::Corrupted));
+        }
+    }
+
+    #[test]
+    fn test_unknown_algorithm_and_short_blob() {
+        let mut buf = [0u8; 32];
+        let len = seal_blob(ChecksumKind::Crc32, PAYLOAD, &mut buf).unwrap();
+        buf[0] = 0x7F;
+        assert_eq!(open_blob(&buf[..len]), Err(FlashError::UnsupportedChecksum));
+        assert_eq!(open_blob(&buf[..3]), Err(FlashError::Corrupted));
+    }
+
+    #[test]
+    fn test_seal_buffer_too_small() {
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            seal_blob(ChecksumKind::Crc16, PAYLOAD, &mut buf),
+            Err(FlashError::BufferTooSmall)
+        );
+    }
+
+    #[cfg(feature = "mock")]
+    mod slots {
+        use super::*;
+        use crate::mock::{block_on, MockFlash};
+
+        const OLD: &[u8] = b"old config";
+        const NEW: &[u8] = b"new config";
+
+        /// Storage with `OLD` committed to slot A
+        fn flash_with_old_config() -> MockFlash {
+            let mut buf = [0u8; 32];
+            let len = seal_blob(ChecksumKind::Crc32, OLD, &mut buf).unwrap();
+            MockFlash::new().with_entry(StorageKey::MachineConfig, &buf[..len])
+        }
+
+        fn stage(flash: &mut MockFlash, payload: &[u8]) -> ConfigSlot {
+            let mut blob = [0u8; 32];
+            let len = seal_blob(ChecksumKind::Crc32, payload, &mut blob).unwrap();
+            let active = active(flash);
+            block_on(stage_config(flash, active, &blob[..len])).unwrap()
+        }
+
+        fn commit(flash: &mut MockFlash, slot: ConfigSlot) -> Result<(), FlashError> {
+            let mut buf = [0u8; 32];
+            block_on(commit_config_slot(flash, slot, &mut buf, |p| p == NEW))
+        }
+
+        fn active(flash: &mut MockFlash) -> ConfigSlot {
+            block_on(active_config_slot(flash, 1, async || {})).unwrap()
+        }
+
+        fn assert_active(flash: &mut MockFlash, payload: &[u8]) {
+            let slot = active(flash);
+            assert_eq!(
+                open_blob(flash.get(slot.key()).unwrap()).unwrap().1,
+                payload
+            );
+        }
+
+        #[test]
+        fn test_commit_switches_active_slot() {
+            let mut flash = flash_with_old_config();
+            assert_eq!(active(&mut flash), ConfigSlot::A);
+
+            let slot = stage(&mut flash, NEW);
+            assert_eq!(slot, ConfigSlot::B);
+            assert_eq!(commit(&mut flash, slot), Ok(()));
+            assert_eq!(active(&mut flash), ConfigSlot::B);
+            assert_active(&mut flash, NEW);
+
+            // The next update goes back to A, leaving B active until committed
+            assert_eq!(stage(&mut flash, NEW), ConfigSlot::A);
+            assert_eq!(active(&mut flash), ConfigSlot::B);
+        }
+
+        #[test]
+        fn test_uncommitted_write_keeps_old_config() {
+            let mut flash = flash_with_old_config();
+            stage(&mut flash, NEW);
+
+            // Interrupted before the commit
+            assert_eq!(active(&mut flash), ConfigSlot::A);
+            assert_active(&mut flash, OLD);
+        }
+
+        #[test]
+        fn test_corrupt_slot_rejected() {
+            // Fails validation
+            let mut flash = flash_with_old_config();
+            let slot = stage(&mut flash, b"bad config");
+            assert_eq!(commit(&mut flash, slot), Err(FlashError::Corrupted));
+            assert_active(&mut flash, OLD);
+
+            // Damaged in flash after staging
+            let slot = stage(&mut flash, NEW);
+            let mut blob = [0u8; 32];
+            let len = flash.get(slot.key()).unwrap().len();
+            blob[..len].copy_from_slice(flash.get(slot.key()).unwrap());
+            blob[BLOB_HEADER_LEN] ^= 0x01;
+            let mut flash = flash_with_old_config().with_entry(slot.key(), &blob[..len]);
+            assert_eq!(commit(&mut flash, slot), Err(FlashError::Corrupted));
+            assert_eq!(active(&mut flash), ConfigSlot::A);
+            assert_active(&mut flash, OLD);
+        }
+
+        #[test]
+        fn test_slot_pointer_read_failures() {
+            let mut flash = flash_with_old_config();
+            let slot = stage(&mut flash, NEW);
+            commit(&mut flash, slot).unwrap();
+
+            // A transient failure is retried
+            flash.fail_times(FlashError::Flash, 2);
+            let mut waits = 0;
+            let slot = block_on(active_config_slot(&mut flash, 3, async || waits += 1));
+            assert_eq!((slot, waits), (Ok(ConfigSlot::B), 2));
+
+            // A persistent one is reported, not taken as slot A
+            flash.fail_times(FlashError::Flash, 10);
+            let slot = block_on(active_config_slot(&mut flash, 3, async || {}));
+            assert_eq!(slot, Err(FlashError::Flash));
+            flash.fail_times(FlashError::Flash, 0);
+
+            // So is a damaged pointer
+            let mut pointer = [0u8; BLOB_HEADER_LEN + 1];
+            pointer.copy_from_slice(flash.get(StorageKey::ActiveConfigSlot).unwrap());
+            pointer[BLOB_HEADER_LEN] ^= 0x01;
+            let mut flash = flash.with_entry(StorageKey::ActiveConfigSlot, &pointer);
+            let slot = block_on(active_config_slot(&mut flash, 3, async || {}));
+            assert_eq!(slot, Err(FlashError::Corrupted));
+        }
+    }
+
+    #[cfg(feature = "mock")]
+    mod retry {
+        use super::*;
+        use crate::mock::{block_on, MockFlash};
+
+        const CONFIG: &[u8] = b"stored config";
+
+        /// Read the config, counting the waits between attempts
+        fn read(flash: &mut MockFlash, attempts: u8) -> (Result<usize, FlashError>, u8) {
+            let mut buf = [0u8; 32];
+            let mut waits = 0;
+            let result = block_on(read_with_retry(
+                flash,
+                StorageKey::MachineConfigToml,
+                &mut buf,
+                attempts,
+                async || waits += 1,
+            ));
+            if let Ok(len) = result {
+                assert_eq!(&buf[..len], CONFIG);
+            }
+            (result, waits)
+        }
+
+        #[test]
+        fn test_transient_failure_recovers() {
+            let mut flash = MockFlash::new().with_entry(StorageKey::MachineConfigToml, CONFIG);
+            flash.fail_times(FlashError::Flash, 2);
+            assert_eq!(read(&mut flash, 3), (Ok(CONFIG.len()), 2));
+            assert_eq!(flash.reads(), 3);
+        }
+
+        #[test]
+        fn test_persistent_failure_exhausts_attempts() {
+            let mut flash = MockFlash::new().with_entry(StorageKey::MachineConfigToml, CONFIG);
+            flash.fail_times(FlashError::Storage, 10);
+            assert_eq!(read(&mut flash, 3), (Err(FlashError::Storage), 2));
+            assert_eq!(flash.reads(), 3);
+
+            // A single attempt never waits
+            assert_eq!(read(&mut flash, 1), (Err(FlashError::Storage), 0));
+        }
+
+        #[test]
+        fn test_missing_config_not_retried() {
+            let mut flash = MockFlash::new();
+            assert_eq!(read(&mut flash, 3), (Err(FlashError::NotFound), 0));
+            assert_eq!(flash.reads(), 1);
+
+            let mut flash = MockFlash::new().with_entry(StorageKey::MachineConfigToml, CONFIG);
+            flash.fail_next(FlashError::Corrupted);
+            assert_eq!(read(&mut flash, 3), (Err(FlashError::Corrupted), 0));
+        }
+    }
+}
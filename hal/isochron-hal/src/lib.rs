@@ -27,10 +27,17 @@
 //! # Traits
 //!
 //! - [`gpio::OutputPin`], [`gpio::InputPin`] - Digital I/O
+//! - [`pwm::PwmPin`] - PWM output
 //! - [`uart::UartTx`], [`uart::UartRx`] - Serial communication
 //! - [`i2c::I2cBus`] - I2C bus operations
 //! - [`spi::SpiBus`] - SPI bus operations
 //! - [`flash::FlashStorage`] - Persistent storage
+//! - [`watchdog::Watchdog`] - Hardware watchdog timer
+//!
+//! # Testing
+//!
+//! The `mock` feature adds [`mock`], with recording and scriptable
+//! implementations of every trait for host-side driver tests.
 
 #![no_std]
 #![deny(unsafe_code)]
@@ -38,12 +45,18 @@
 pub mod flash;
 pub mod gpio;
 pub mod i2c;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod pwm;
 pub mod spi;
 pub mod uart;
+pub mod watchdog;
 
 // Re-export key traits at crate root for convenience
-pub use flash::{FlashStorage, StorageKey};
+pub use flash::{ChecksumKind, ConfigSlot, FlashStorage, StorageKey};
 pub use gpio::{InputPin, OutputPin};
 pub use i2c::I2cBus;
+pub use pwm::PwmPin;
 pub use spi::SpiBus;
 pub use uart::{UartRx, UartTx};
+pub use watchdog::Watchdog;
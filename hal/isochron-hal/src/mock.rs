@@ -0,0 +1,811 @@
+//! Mock implementations of the HAL traits for host-side tests
+//!
+//! Enabled by the `mock` feature. The mocks record everything written to
+//! them and replay scripted responses, so driver tests can check the exact
+//! bytes and pin transitions they produce without hardware.
+//!
+//! Bus mocks ([`MockI2c`], [`MockSpi`]) follow an expectation list: each
+//! call must match the next expected transaction, otherwise the test
+//! panics. Call `done()` at the end of a test to check that every
+//! expectation was consumed.
+//!
+//! This module uses `std` and must only be enabled from dev-dependencies.
+
+extern crate std;
+
+use core::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use crate::flash::{FlashError, FlashStorage, StorageKey};
+use crate::gpio::{InputPin, OutputPin};
+use crate::i2c::I2cBus;
+use crate::pwm::PwmPin;
+use crate::spi::SpiBus;
+use crate::uart::{UartRx, UartTx};
+use crate::watchdog::Watchdog;
+
+/// Number of slots in [`MockFlash`] (one per [`StorageKey`])
+const FLASH_SLOTS: usize = 7;
+
+/// Error returned by the mocks when a failure is injected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockError {
+    /// Failure injected by the test
+    Injected,
+    /// Read attempted with no scripted data left
+    NoData,
+}
+
+/// Output pin that records every level it is driven to
+#[derive(Debug, Default)]
+pub struct MockOutputPin {
+    high: bool,
+    history: Vec<bool>,
+}
+
+impl MockOutputPin {
+    /// Create a pin with the given initial level
+    pub fn new(high: bool) -> Self {
+        Self {
+            high,
+            history: Vec::new(),
+        }
+    }
+
+    /// Levels driven since creation or the last `clear_history`
+    pub fn history(&self) -> &[bool] {
+        &self.history
+    }
+
+    /// Forget recorded levels
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
+
+impl OutputPin for MockOutputPin {
+    fn set_high(&mut self) {
+        self.high = true;
+        self.history.push(true);
+    }
+
+    fn set_low(&mut self) {
+        self.high = false;
+        self.history.push(false);
+    }
+
+    fn toggle(&mut self) {
+        if self.high {
+            self.set_low();
+        } else {
+            self.set_high();
+        }
+    }
+
+    fn is_set_high(&self) -> bool {
+        self.high
+    }
+}
+
+/// PWM channel that records every duty cycle it is set to
+#[derive(Debug)]
+pub struct MockPwmPin {
+    max_duty: u16,
+    duty: u16,
+    history: Vec<u16>,
+}
+
+impl MockPwmPin {
+    /// Create a channel with the given full-scale duty, initially at 0
+    pub fn new(max_duty: u16) -> Self {
+        Self {
+            max_duty,
+            duty: 0,
+            history: Vec::new(),
+        }
+    }
+
+    /// Current duty cycle in counts
+    pub fn duty(&self) -> u16 {
+        self.duty
+    }
+
+    /// Duty cycles set since creation
+    pub fn history(&self) -> &[u16] {
+        &self.history
+    }
+}
+
+impl PwmPin for MockPwmPin {
+    fn max_duty(&self) -> u16 {
+        self.max_duty
+    }
+
+    fn set_duty(&mut self, duty: u16) {
+        self.duty = duty.min(self.max_duty);
+        self.history.push(self.duty);
+    }
+}
+
+/// Input pin that replays scripted levels
+///
+/// Each read consumes the next scripted level; once the script is empty
+/// the pin keeps reading the last level.
+#[derive(Debug, Default)]
+pub struct MockInputPin {
+    level: Cell<bool>,
+    script: RefCell<VecDeque<bool>>,
+    reads: Cell<usize>,
+}
+
+impl MockInputPin {
+    /// Create a pin reading a constant level
+    pub fn new(high: bool) -> Self {
+        Self {
+            level: Cell::new(high),
+            ..Default::default()
+        }
+    }
+
+    /// Set the level returned once the script is exhausted
+    pub fn set_level(&self, high: bool) {
+        self.level.set(high);
+    }
+
+    /// Queue levels to be returned by subsequent reads
+    pub fn script(&self, levels: &[bool]) {
+        self.script.borrow_mut().extend(levels.iter().copied());
+    }
+
+    /// Number of reads so far
+    pub fn reads(&self) -> usize {
+        self.reads.get()
+    }
+}
+
+impl InputPin for MockInputPin {
+    fn is_high(&self) -> bool {
+        self.reads.set(self.reads.get() + 1);
+        if let Some(level) = self.script.borrow_mut().pop_front() {
+            self.level.set(level);
+        }
+        self.level.get()
+    }
+}
+
+/// UART that records transmitted bytes and replays queued receive data
+#[derive(Debug, Default)]
+pub struct MockUart {
+    written: Vec<u8>,
+    rx: VecDeque<u8>,
+    flushes: usize,
+    fail_next_write: bool,
+}
+
+impl MockUart {
+    /// Create an empty UART
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue bytes to be returned by subsequent reads
+    pub fn queue_rx(&mut self, data: &[u8]) {
+        self.rx.extend(data.iter().copied());
+    }
+
+    /// All bytes written so far
+    pub fn written(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Take the bytes written so far, clearing the record
+    pub fn take_written(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.written)
+    }
+
+    /// Number of flush calls
+    pub fn flushes(&self) -> usize {
+        self.flushes
+    }
+
+    /// Bytes queued but not yet read
+    pub fn rx_remaining(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Make the next write fail with [`MockError::Injected`]
+    pub fn fail_next_write(&mut self) {
+        self.fail_next_write = true;
+    }
+}
+
+impl UartTx for MockUart {
+    type Error = MockError;
+
+    fn write_blocking(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        if core::mem::take(&mut self.fail_next_write) {
+            return Err(MockError::Injected);
+        }
+        self.written.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flushes += 1;
+        Ok(())
+    }
+}
+
+impl UartRx for MockUart {
+    type Error = MockError;
+
+    fn read_blocking(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.len() > self.rx.len() {
+            return Err(MockError::NoData);
+        }
+        for byte in buf.iter_mut() {
+            *byte = self.rx.pop_front().unwrap_or_default();
+        }
+        Ok(buf.len())
+    }
+}
+
+/// Kind of bus operation in an expected transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusOp {
+    Write,
+    Read,
+    WriteRead,
+    Transfer,
+    TransferInPlace,
+}
+
+/// Expected I2C transaction for [`MockI2c`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct I2cTransaction {
+    op: BusOp,
+    address: u8,
+    write: Vec<u8>,
+    response: Vec<u8>,
+    error: Option<MockError>,
+}
+
+impl I2cTransaction {
+    /// Expect a write of `data` to `address`
+    pub fn write(address: u8, data: &[u8]) -> Self {
+        Self::new(BusOp::Write, address, data, &[])
+    }
+
+    /// Expect a read from `address`, answered with `response`
+    pub fn read(address: u8, response: &[u8]) -> Self {
+        Self::new(BusOp::Read, address, &[], response)
+    }
+
+    /// Expect a write-read to `address`, answered with `response`
+    pub fn write_read(address: u8, write: &[u8], response: &[u8]) -> Self {
+        Self::new(BusOp::WriteRead, address, write, response)
+    }
+
+    /// Fail this transaction with `error` after matching it
+    pub fn with_error(mut self, error: MockError) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    fn new(op: BusOp, address: u8, write: &[u8], response: &[u8]) -> Self {
+        Self {
+            op,
+            address,
+            write: write.to_vec(),
+            response: response.to_vec(),
+            error: None,
+        }
+    }
+}
+
+/// I2C bus that checks calls against an expectation list
+#[derive(Debug, Default)]
+pub struct MockI2c {
+    expected: VecDeque<I2cTransaction>,
+}
+
+impl MockI2c {
+    /// Create a bus expecting `transactions` in order
+    pub fn new(transactions: &[I2cTransaction]) -> Self {
+        Self {
+            expected: transactions.iter().cloned().collect(),
+        }
+    }
+
+    /// Append further expected transactions
+    pub fn expect(&mut self, transactions: &[I2cTransaction]) {
+        self.expected.extend(transactions.iter().cloned());
+    }
+
+    /// Assert that every expected transaction was performed
+    pub fn done(&self) {
+        assert!(
+            self.expected.is_empty(),
+            "MockI2c: {} expected transaction(s) not performed: {:?}",
+            self.expected.len(),
+            self.expected
+        );
+    }
+
+    fn next(
+        &mut self,
+        op: BusOp,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), MockError> {
+        let expected = self
+            .expected
+            .pop_front()
+            .unwrap_or_else(|| panic!("MockI2c: unexpected {:?} to {:#04x}", op, address));
+        assert_eq!(expected.op, op, "MockI2c: wrong operation");
+        assert_eq!(expected.address, address, "MockI2c: wrong address");
+        assert_eq!(expected.write, write, "MockI2c: wrong write data");
+        assert_eq!(
+            expected.response.len(),
+            read.len(),
+            "MockI2c: wrong read length"
+        );
+        read.copy_from_slice(&expected.response);
+        expected.error.map_or(Ok(()), Err)
+    }
+}
+
+impl I2cBus for MockI2c {
+    type Error = MockError;
+
+    fn write(&mut self, address: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.next(BusOp::Write, address, data, &mut [])
+    }
+
+    fn read(&mut self, address: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.next(BusOp::Read, address, &[], buf)
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write_data: &[u8],
+        read_buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.next(BusOp::WriteRead, address, write_data, read_buf)
+    }
+}
+
+/// Expected SPI transaction for [`MockSpi`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpiTransaction {
+    op: BusOp,
+    write: Vec<u8>,
+    response: Vec<u8>,
+    error: Option<MockError>,
+}
+
+impl SpiTransaction {
+    /// Expect a full-duplex transfer of `write`, answered with `response`
+    pub fn transfer(write: &[u8], response: &[u8]) -> Self {
+        Self::new(BusOp::Transfer, write, response)
+    }
+
+    /// Expect an in-place transfer of `write`, answered with `response`
+    pub fn transfer_in_place(write: &[u8], response: &[u8]) -> Self {
+        Self::new(BusOp::TransferInPlace, write, response)
+    }
+
+    /// Expect a write of `data`
+    pub fn write(data: &[u8]) -> Self {
+        Self::new(BusOp::Write, data, &[])
+    }
+
+    /// Expect a read, answered with `response`
+    pub fn read(response: &[u8]) -> Self {
+        Self::new(BusOp::Read, &[], response)
+    }
+
+    /// Fail this transaction with `error` after matching it
+    pub fn with_error(mut self, error: MockError) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    fn new(op: BusOp, write: &[u8], response: &[u8]) -> Self {
+        Self {
+            op,
+            write: write.to_vec(),
+            response: response.to_vec(),
+            error: None,
+        }
+    }
+}
+
+/// SPI bus that checks calls against an expectation list
+#[derive(Debug, Default)]
+pub struct MockSpi {
+    expected: VecDeque<SpiTransaction>,
+}
+
+impl MockSpi {
+    /// Create a bus expecting `transactions` in order
+    pub fn new(transactions: &[SpiTransaction]) -> Self {
+        Self {
+            expected: transactions.iter().cloned().collect(),
+        }
+    }
+
+    /// Append further expected transactions
+    pub fn expect(&mut self, transactions: &[SpiTransaction]) {
+        self.expected.extend(transactions.iter().cloned());
+    }
+
+    /// Assert that every expected transaction was performed
+    pub fn done(&self) {
+        assert!(
+            self.expected.is_empty(),
+            "MockSpi: {} expected transaction(s) not performed: {:?}",
+            self.expected.len(),
+            self.expected
+        );
+    }
+
+    fn next(&mut self, op: BusOp, write: &[u8], read: &mut [u8]) -> Result<(), MockError> {
+        let expected = self
+            .expected
+            .pop_front()
+            .unwrap_or_else(|| panic!("MockSpi: unexpected {:?}", op));
+        assert_eq!(expected.op, op, "MockSpi: wrong operation");
+        assert_eq!(expected.write, write, "MockSpi: wrong write data");
+        assert_eq!(
+            expected.response.len(),
+            read.len(),
+            "MockSpi: wrong read length"
+        );
+        read.copy_from_slice(&expected.response);
+        expected.error.map_or(Ok(()), Err)
+    }
+}
+
+impl SpiBus for MockSpi {
+    type Error = MockError;
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.next(BusOp::Transfer, write, read)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.next(BusOp::Write, data, &mut [])
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.next(BusOp::Read, &[], buf)
+    }
+
+    fn transfer_in_place(&mut self, data: &mut [u8]) -> Result<(), Self::Error> {
+        let write = data.to_vec();
+        self.next(BusOp::TransferInPlace, &write, data)
+    }
+}
+
+/// In-memory flash storage that records writes
+#[derive(Debug, Default)]
+pub struct MockFlash {
+    slots: [Option<Vec<u8>>; FLASH_SLOTS],
+    writes: Vec<(StorageKey, Vec<u8>)>,
+    erases: usize,
+    reads: usize,
+    /// Error for the next operations, and how many of them fail
+    fail: Option<(FlashError, usize)>,
+}
+
+impl MockFlash {
+    /// Create empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-populate `key` with `data`
+    pub fn with_entry(mut self, key: StorageKey, data: &[u8]) -> Self {
+        self.slots[key.as_u8() as usize] = Some(data.to_vec());
+        self
+    }
+
+    /// Current contents of `key`
+    pub fn get(&self, key: StorageKey) -> Option<&[u8]> {
+        self.slots[key.as_u8() as usize].as_deref()
+    }
+
+    /// Every write performed, in order
+    pub fn writes(&self) -> &[(StorageKey, Vec<u8>)] {
+        &self.writes
+    }
+
+    /// Number of `erase_all` calls
+    pub fn erases(&self) -> usize {
+        self.erases
+    }
+
+    /// Number of `read` calls, including failed ones
+    pub fn reads(&self) -> usize {
+        self.reads
+    }
+
+    /// Make the next read, write or erase fail with `error`
+    pub fn fail_next(&mut self, error: FlashError) {
+        self.fail_times(error, 1);
+    }
+
+    /// Make the next `count` reads, writes or erases fail with `error`
+    pub fn fail_times(&mut self, error: FlashError, count: usize) {
+        self.fail = (count > 0).then_some((error, count));
+    }
+
+    /// Take one failure, if any are pending
+    fn take_failure(&mut self) -> Result<(), FlashError> {
+        match self.fail {
+            Some((error, count)) => {
+                self.fail = (count > 1).then_some((error, count - 1));
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl FlashStorage for MockFlash {
+    async fn read(&mut self, key: StorageKey, buffer: &mut [u8]) -> Result<usize, FlashError> {
+        self.reads += 1;
+        self.take_failure()?;
+        let data = self.get(key).ok_or(FlashError::NotFound)?;
+        let dest = buffer
+            .get_mut(..data.len())
+            .ok_or(FlashError::BufferTooSmall)?;
+        dest.copy_from_slice(data);
+        Ok(data.len())
+    }
+
+    async fn write(&mut self, key: StorageKey, data: &[u8]) -> Result<(), FlashError> {
+        self.take_failure()?;
+        self.slots[key.as_u8() as usize] = Some(data.to_vec());
+        self.writes.push((key, data.to_vec()));
+        Ok(())
+    }
+
+    async fn exists(&mut self, key: StorageKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    async fn erase_all(&mut self) -> Result<(), FlashError> {
+        self.take_failure()?;
+        self.slots = Default::default();
+        self.erases += 1;
+        Ok(())
+    }
+}
+
+/// Watchdog running on simulated time
+///
+/// Tests move time with `advance`; the watchdog counts as expired once
+/// more than its timeout passes without a feed.
+#[derive(Debug, Default)]
+pub struct MockWatchdog {
+    max_timeout_ms: u32,
+    timeout_ms: Option<u32>,
+    since_feed_ms: u32,
+    feeds: usize,
+}
+
+impl MockWatchdog {
+    /// Create a stopped watchdog with the given longest timeout
+    pub fn new(max_timeout_ms: u32) -> Self {
+        Self {
+            max_timeout_ms,
+            ..Default::default()
+        }
+    }
+
+    /// Armed timeout, or None while stopped
+    pub fn timeout_ms(&self) -> Option<u32> {
+        self.timeout_ms
+    }
+
+    /// Number of feeds since creation
+    pub fn feeds(&self) -> usize {
+        self.feeds
+    }
+
+    /// Let simulated time pass
+    pub fn advance(&mut self, ms: u32) {
+        self.since_feed_ms = self.since_feed_ms.saturating_add(ms);
+    }
+
+    /// Whether the watchdog would have reset the chip
+    pub fn expired(&self) -> bool {
+        self.timeout_ms
+            .is_some_and(|timeout| self.since_feed_ms > timeout)
+    }
+}
+
+impl Watchdog for MockWatchdog {
+    fn max_timeout_ms(&self) -> u32 {
+        self.max_timeout_ms
+    }
+
+    fn start(&mut self, timeout_ms: u32) {
+        self.timeout_ms = Some(self.clamp_timeout_ms(timeout_ms));
+        self.since_feed_ms = 0;
+    }
+
+    fn feed(&mut self) {
+        self.since_feed_ms = 0;
+        self.feeds += 1;
+    }
+}
+
+/// Run a future to completion on the current thread
+///
+/// The mocks never return `Pending`, so a no-op waker is sufficient for
+/// driving async HAL calls in tests.
+pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    use core::task::{Context, Poll, Waker};
+
+    let mut future = core::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_pin_records_levels() {
+        let mut pin = MockOutputPin::new(false);
+        pin.set_high();
+        pin.toggle();
+        pin.set_state(true);
+
+        assert!(pin.is_set_high());
+        assert_eq!(pin.history(), &[true, false, true]);
+    }
+
+    #[test]
+    fn test_pwm_pin_records_duty() {
+        let mut pwm = MockPwmPin::new(1000);
+        pwm.set_duty_percent(25);
+        pwm.set_duty(2000);
+
+        assert_eq!(pwm.duty(), 1000);
+        assert_eq!(pwm.history(), &[250, 1000]);
+    }
+
+    #[test]
+    fn test_input_pin_replays_script() {
+        let pin = MockInputPin::new(false);
+        pin.script(&[true, true]);
+
+        assert!(pin.is_high());
+        assert!(pin.is_high());
+        // Keeps the last scripted level
+        assert!(pin.is_high());
+        pin.set_level(false);
+        assert!(pin.is_low());
+        assert_eq!(pin.reads(), 4);
+    }
+
+    #[test]
+    fn test_uart_records_and_replays() {
+        let mut uart = MockUart::new();
+        uart.write_blocking(&[1, 2, 3]).unwrap();
+        uart.queue_rx(&[0xAA, 0xBB]);
+
+        let mut buf = [0u8; 2];
+        assert_eq!(uart.read_blocking(&mut buf), Ok(2));
+        assert_eq!(buf, [0xAA, 0xBB]);
+        assert_eq!(uart.read_byte(), Err(MockError::NoData));
+        assert_eq!(uart.take_written(), [1, 2, 3]);
+
+        uart.fail_next_write();
+        assert_eq!(uart.write_blocking(&[4]), Err(MockError::Injected));
+        assert!(uart.written().is_empty());
+    }
+
+    #[test]
+    fn test_i2c_expectations() {
+        let mut i2c = MockI2c::new(&[
+            I2cTransaction::write(0x48, &[0x01, 0x60]),
+            I2cTransaction::write_read(0x48, &[0x00], &[0x19, 0x80]),
+            I2cTransaction::read(0x48, &[0x00]).with_error(MockError::Injected),
+        ]);
+
+        i2c.write(0x48, &[0x01, 0x60]).unwrap();
+        let mut buf = [0u8; 2];
+        i2c.write_read(0x48, &[0x00], &mut buf).unwrap();
+        assert_eq!(buf, [0x19, 0x80]);
+        let mut one = [0u8; 1];
+        assert_eq!(i2c.read(0x48, &mut one), Err(MockError::Injected));
+        i2c.done();
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong address")]
+    fn test_i2c_wrong_address_panics() {
+        let mut i2c = MockI2c::new(&[I2cTransaction::write(0x48, &[0x01])]);
+        let _ = i2c.write(0x49, &[0x01]);
+    }
+
+    #[test]
+    fn test_spi_expectations() {
+        let mut spi = MockSpi::new(&[
+            SpiTransaction::transfer(&[0x6F, 0, 0, 0, 0], &[0x01, 0, 0, 0, 0x2A]),
+            SpiTransaction::transfer_in_place(&[0x80], &[0x00]),
+        ]);
+
+        let mut read = [0u8; 5];
+        spi.transfer(&mut read, &[0x6F, 0, 0, 0, 0]).unwrap();
+        assert_eq!(read[4], 0x2A);
+        let mut data = [0x80];
+        spi.transfer_in_place(&mut data).unwrap();
+        assert_eq!(data, [0x00]);
+        spi.done();
+    }
+
+    #[test]
+    fn test_flash_roundtrip() {
+        let mut flash = MockFlash::new().with_entry(StorageKey::PidCalibration, &[1, 2]);
+
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            block_on(flash.read(StorageKey::PidCalibration, &mut buf)),
+            Ok(2)
+        );
+        assert_eq!(
+            block_on(flash.read(StorageKey::MachineConfig, &mut buf)),
+            Err(FlashError::NotFound)
+        );
+
+        block_on(flash.write(StorageKey::MachineConfig, &[9; 8])).unwrap();
+        assert_eq!(
+            block_on(flash.read(StorageKey::MachineConfig, &mut buf)),
+            Err(FlashError::BufferTooSmall)
+        );
+        assert_eq!(flash.writes().len(), 1);
+
+        flash.fail_next(FlashError::Full);
+        assert_eq!(
+            block_on(flash.write(StorageKey::MachineConfig, &[0])),
+            Err(FlashError::Full)
+        );
+
+        block_on(flash.erase_all()).unwrap();
+        assert!(!block_on(flash.exists(StorageKey::PidCalibration)));
+    }
+
+    #[test]
+    fn test_watchdog_expires_without_feed() {
+        let mut watchdog = MockWatchdog::new(8_000);
+        watchdog.advance(60_000);
+        assert!(!watchdog.expired());
+
+        watchdog.start(10_000);
+        assert_eq!(watchdog.timeout_ms(), Some(8_000));
+        let interval = watchdog.feed_interval_ms(10_000);
+        for _ in 0..10 {
+            watchdog.advance(interval);
+            watchdog.feed();
+        }
+        assert!(!watchdog.expired());
+        assert_eq!(watchdog.feeds(), 10);
+
+        watchdog.advance(8_001);
+        assert!(watchdog.expired());
+    }
+}
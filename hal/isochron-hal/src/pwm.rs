@@ -0,0 +1,25 @@
+//! PWM output abstraction
+//!
+//! Provides a trait for a single PWM channel that can be implemented by
+//! chip-specific HALs.
+
+/// PWM output channel
+///
+/// The duty cycle is in hardware counts, from 0 (always low) to
+/// [`PwmPin::max_duty`] (always high).
+pub trait PwmPin {
+    /// Duty cycle value for 100%
+    fn max_duty(&self) -> u16;
+
+    /// Set the duty cycle in hardware counts
+    ///
+    /// Values above `max_duty` are treated as `max_duty`.
+    fn set_duty(&mut self, duty: u16);
+
+    /// Set the duty cycle as a percentage (clamped to 100)
+    fn set_duty_percent(&mut self, percent: u8) {
+        let percent = percent.min(100) as u32;
+        let duty = self.max_duty() as u32 * percent / 100;
+        self.set_duty(duty as u16);
+    }
+}
@@ -0,0 +1,84 @@
+//! Watchdog timer abstraction
+//!
+//! Provides a trait for a hardware watchdog that resets the chip unless
+//! it is fed within its timeout.
+
+/// Fraction of the timeout between feeds
+///
+/// Feeding four times per timeout leaves room for a feed to run late
+/// behind other work without the watchdog expiring.
+const FEEDS_PER_TIMEOUT: u32 = 4;
+
+/// Hardware watchdog timer
+pub trait Watchdog {
+    /// Longest timeout the hardware supports (ms)
+    fn max_timeout_ms(&self) -> u32;
+
+    /// Arm the watchdog
+    ///
+    /// Timeouts outside 1..=`max_timeout_ms` are clamped to that range;
+    /// [`Watchdog::clamp_timeout_ms`] gives the timeout actually armed.
+    fn start(&mut self, timeout_ms: u32);
+
+    /// Restart the timeout
+    fn feed(&mut self);
+
+    /// Timeout armed by `start` for a requested timeout (ms)
+    fn clamp_timeout_ms(&self, timeout_ms: u32) -> u32 {
+        timeout_ms.clamp(1, self.max_timeout_ms().max(1))
+    }
+
+    /// Interval between feeds that keeps a timeout from expiring (ms)
+    fn feed_interval_ms(&self, timeout_ms: u32) -> u32 {
+        (self.clamp_timeout_ms(timeout_ms) / FEEDS_PER_TIMEOUT).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Watchdog with the RP2040's limit that only records its timeout
+    struct Fixed {
+        max_ms: u32,
+        armed_ms: Option<u32>,
+    }
+
+    impl Watchdog for Fixed {
+        fn max_timeout_ms(&self) -> u32 {
+            self.max_ms
+        }
+
+        fn start(&mut self, timeout_ms: u32) {
+            self.armed_ms = Some(self.clamp_timeout_ms(timeout_ms));
+        }
+
+        fn feed(&mut self) {}
+    }
+
+    #[test]
+    fn test_timeout_arithmetic() {
+        let mut watchdog = Fixed {
+            max_ms: 8_388,
+            armed_ms: None,
+        };
+
+        assert_eq!(watchdog.clamp_timeout_ms(5_000), 5_000);
+        assert_eq!(watchdog.clamp_timeout_ms(10_000), 8_388);
+        assert_eq!(watchdog.clamp_timeout_ms(0), 1);
+        watchdog.start(u32::MAX);
+        assert_eq!(watchdog.armed_ms, Some(8_388));
+
+        // A quarter of the armed timeout, never zero
+        assert_eq!(watchdog.feed_interval_ms(2_000), 500);
+        assert_eq!(watchdog.feed_interval_ms(10_000), 2_097);
+        assert_eq!(watchdog.feed_interval_ms(3), 1);
+
+        // A watchdog reporting no usable limit still arms 1ms
+        let watchdog = Fixed {
+            max_ms: 0,
+            armed_ms: None,
+        };
+        assert_eq!(watchdog.clamp_timeout_ms(5_000), 1);
+    }
+}
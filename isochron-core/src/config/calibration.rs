@@ -215,7 +215,7 @@ impl CalibrationData {
 }
 
 /// Simple CRC32 update function (IEEE 802.3 polynomial)
-fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+pub(crate) fn crc32_update(crc: u32, data: &[u8]) -> u32 {
     const POLY: u32 = 0xEDB88320;
     let mut crc = crc;
 
@@ -9,9 +9,11 @@ use heapless::{String, Vec};
 use serde::{Deserialize, Serialize};
 
 use super::types::{
-    HeaterConfig, JarConfig, ProfileConfig, ProgramConfig, UiConfig, MAX_JARS, MAX_LABEL_LEN,
-    MAX_PROFILES, MAX_PROGRAMS,
+    HeaterConfig, JarConfig, LinkConfig, ParkPosition, ProfileConfig, ProgramConfig, UiConfig,
+    MAX_JARS, MAX_LABEL_LEN, MAX_PROFILES, MAX_PROGRAMS,
 };
+use crate::motion::{RampCurve, RampProfile};
+use crate::scheduler::BalanceConfig;
 
 /// Maximum steppers per config
 pub const MAX_STEPPERS: usize = 4;
@@ -19,12 +21,26 @@ pub const MAX_STEPPERS: usize = 4;
 /// Maximum DC motors per config
 pub const MAX_DC_MOTORS: usize = 4;
 
+/// Maximum points in a custom thermistor table
+pub const MAX_THERMISTOR_POINTS: usize = 24;
+
+/// Thermistor table: (resistance_ohms, temperature_x10) points
+///
+/// Sorted by decreasing resistance, i.e. increasing temperature.
+pub type ThermistorTable = Vec<(u32, i16), MAX_THERMISTOR_POINTS>;
+
+/// Most ADC samples per thermistor read
+pub const MAX_ADC_SAMPLES: usize = 9;
+
 /// Maximum AC motors per config
 pub const MAX_AC_MOTORS: usize = 4;
 
 /// Maximum heaters per config
 pub const MAX_HEATERS: usize = 4;
 
+/// Default spin-off RPM ceiling in quiet mode
+pub const DEFAULT_QUIET_SPINOFF_RPM: u16 = 150;
+
 /// Motor type for the machine
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -39,6 +55,46 @@ pub enum MotorType {
     Ac,
 }
 
+/// What the motor does when RPM drops to zero during a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StopBehavior {
+    /// Release the driver enable; the basket spins down freely
+    #[default]
+    Coast,
+    /// Keep the driver enabled at hold current to stop and hold the basket
+    Brake,
+}
+
+/// Order in which the Z and X axes are homed at boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HomingOrder {
+    /// Lift the basket clear first, then home X
+    #[default]
+    ZThenX,
+    /// Home X first, e.g. to clear a jar rack before Z can move
+    XThenZ,
+    /// Home both axes at once
+    Simultaneous,
+}
+
+/// How an axis finds its home position at boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HomingType {
+    /// Seek the physical endstop switch
+    #[default]
+    Endstop,
+    /// The user jogs the axis home and confirms
+    Manual,
+    /// No homing: the power-on position is taken as home
+    None,
+}
+
 /// Pin configuration with optional inversion
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -79,6 +135,14 @@ impl PinConfig {
             pull_up: true,
         }
     }
+
+    /// Interpret a raw electrical level through the pin's inversion
+    ///
+    /// Returns true when the pin is logically active: high for a normal
+    /// pin, low for an inverted one.
+    pub const fn is_active(&self, level_high: bool) -> bool {
+        level_high != self.inverted
+    }
 }
 
 /// Stepper motor hardware configuration
@@ -100,15 +164,19 @@ pub struct StepperHwConfig {
     pub full_steps_per_rotation: u16,
     /// Microsteps setting
     pub microsteps: u8,
-    /// Rotation distance in mm per motor rotation
-    /// For linear axes (z/lift): leadscrew pitch (e.g., 8mm for T8 leadscrew)
+    /// Rotation distance in µm per motor rotation (written in mm in TOML)
+    /// For linear axes (z/lift): leadscrew pitch (e.g., 8000 for T8 leadscrew)
     /// For rotary axes (x/carousel): arc circumference per rotation
-    /// For basket: typically 360 (treating degrees as mm for RPM calc)
-    pub rotation_distance: u16,
+    /// For basket: typically 360000 (treating degrees as mm for RPM calc)
+    pub rotation_distance_um: u32,
     /// Gear ratio numerator (e.g., 3 for 3:1)
     pub gear_ratio_num: u8,
     /// Gear ratio denominator (e.g., 1 for 3:1)
     pub gear_ratio_den: u8,
+    /// Minimum step pulse width in ns (default: 2500)
+    pub step_pulse_ns: Option<u16>,
+    /// Behavior when stopping between steps (default: coast)
+    pub stop_behavior: StopBehavior,
 
     // === Position control (Klipper-style) ===
     /// Minimum valid position in mm (default: 0)
@@ -124,6 +192,50 @@ pub struct StepperHwConfig {
     /// If true, home in positive direction; if false, home toward zero
     /// Default: auto-detected from position_endstop location
     pub homing_positive_dir: Option<bool>,
+    /// Time allowed for each homing attempt in s (default: 30, 0 = no limit)
+    pub homing_timeout_s: Option<u16>,
+    /// Play taken up when the axis reverses, in motor steps (default: 0)
+    pub backlash_steps: u16,
+    /// How the axis is homed at boot (default: endstop)
+    pub homing_type: HomingType,
+
+    // === Speed ramp (spinning steppers) ===
+    /// Acceleration in RPM/s (default: 0 = speed changes are immediate)
+    pub accel_rpm_per_s: u16,
+    /// Shape of the speed ramp (default: linear)
+    pub ramp_curve: RampCurve,
+    /// Lowest running speed in RPM (default: 0)
+    pub min_rpm: u16,
+    /// Highest speed in RPM (default: unlimited)
+    pub max_rpm: Option<u16>,
+}
+
+impl StepperHwConfig {
+    /// Steps per mm of output travel × 1000
+    ///
+    /// Includes microstepping and the gear ratio. Returns 0 if the rotation
+    /// distance is not set.
+    pub fn steps_per_mm_x1000(&self) -> u32 {
+        let steps = self.full_steps_per_rotation as u64
+            * self.microsteps.max(1) as u64
+            * self.gear_ratio_num.max(1) as u64;
+        let distance_um = self.rotation_distance_um as u64 * self.gear_ratio_den.max(1) as u64;
+        if distance_um == 0 {
+            return 0;
+        }
+        // steps / (distance_um / 1000) mm, scaled by 1000
+        (steps * 1_000_000 / distance_um).min(u32::MAX as u64) as u32
+    }
+
+    /// Speed ramp followed on RPM changes
+    pub fn ramp(&self) -> RampProfile {
+        RampProfile {
+            accel_rate: self.accel_rpm_per_s,
+            curve: self.ramp_curve,
+            min_rpm: self.min_rpm,
+            max_rpm: self.max_rpm.unwrap_or(u16::MAX),
+        }
+    }
 }
 
 /// TMC2209 driver configuration
@@ -149,6 +261,47 @@ pub struct Tmc2209HwConfig {
     pub stealthchop: bool,
     /// DIAG pin for StallGuard (optional)
     pub diag_pin: Option<u8>,
+    /// Sense resistor in milliohms (None = driver default, 110)
+    pub rsense_mohm: Option<u16>,
+    /// Largest SG_RESULT spread tolerated during spin-off (None = no check)
+    pub imbalance_threshold: Option<u16>,
+    /// Keep run current between the steps of slow spins
+    pub spin_hold: bool,
+    /// Ignore DIAG for this long after a speed change (ms, None = 500)
+    pub stall_settle_ms: Option<u16>,
+}
+
+/// Step/dir driver chip, which sets the microstep pin encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StepDirChip {
+    /// Allegro A4988, up to 1/16 microsteps
+    #[default]
+    A4988,
+    /// TI DRV8825, up to 1/32 microsteps
+    Drv8825,
+}
+
+/// A4988-style step/dir driver configuration
+///
+/// These drivers have no serial interface: microstepping is selected by
+/// the MS pins and the current by a trimpot. An MS pin left unset is
+/// taken to be strapped low on the board.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct A4988HwConfig {
+    /// Name of the associated stepper
+    pub stepper_name: String<MAX_LABEL_LEN>,
+    /// Driver chip
+    pub chip: StepDirChip,
+    /// MS1 (DRV8825: MODE0) pin
+    pub ms1_pin: Option<PinConfig>,
+    /// MS2 (DRV8825: MODE1) pin
+    pub ms2_pin: Option<PinConfig>,
+    /// MS3 (DRV8825: MODE2) pin
+    pub ms3_pin: Option<PinConfig>,
 }
 
 /// DC motor driver type
@@ -256,10 +409,141 @@ pub struct HeaterHwConfig {
     pub name: String<MAX_LABEL_LEN>,
     /// Heater output pin (GPIO for SSR/MOSFET)
     pub heater_pin: PinConfig,
+    /// Optional heater enable pin (separate safety relay)
+    /// Asserted while heating is permitted, released on any fault.
+    pub enable_pin: Option<PinConfig>,
     /// Temperature sensor ADC pin
     pub sensor_pin: u8,
     /// Sensor type
     pub sensor_type: SensorType,
+    /// I2C address of a digital sensor (None = the sensor's default)
+    pub sensor_address: Option<u8>,
+    /// Data pin of a 1-Wire sensor
+    pub onewire_pin: Option<PinConfig>,
+    /// Beta-equation thermistor parameters, replacing the lookup table
+    pub beta: Option<BetaConfig>,
+    /// Steinhart-Hart coefficients, replacing the lookup table
+    pub steinhart_hart: Option<SteinhartHartConfig>,
+    /// Custom thermistor table (empty = built-in table)
+    pub temp_table: ThermistorTable,
+    /// Median filtering of thermistor ADC reads
+    pub adc_filter: AdcFilterConfig,
+}
+
+impl HeaterHwConfig {
+    /// Resistance-to-temperature model for the thermistor
+    ///
+    /// A custom table takes precedence, then Steinhart-Hart coefficients,
+    /// then beta parameters; with none configured the built-in lookup
+    /// table is used.
+    pub fn thermistor_model(&self) -> ThermistorModel {
+        if !self.temp_table.is_empty() {
+            ThermistorModel::Custom(self.temp_table.clone())
+        } else if let Some(sh) = self.steinhart_hart {
+            ThermistorModel::SteinhartHart {
+                a: sh.a,
+                b: sh.b,
+                c: sh.c,
+            }
+        } else if let Some(beta) = self.beta {
+            ThermistorModel::Beta {
+                beta: beta.beta,
+                r0_ohms: beta.r0_ohms.unwrap_or(self.sensor_type.nominal_ohms()),
+                t0_c: beta.t0_c,
+            }
+        } else {
+            ThermistorModel::Table
+        }
+    }
+}
+
+/// Beta-equation thermistor parameters
+///
+/// 1/T = 1/T0 + ln(R/R0)/beta, with T in kelvin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BetaConfig {
+    /// Beta coefficient (K)
+    pub beta: u16,
+    /// Resistance at `t0_c` (None = the sensor type's nominal resistance)
+    pub r0_ohms: Option<u32>,
+    /// Reference temperature (°C)
+    pub t0_c: i16,
+}
+
+impl Default for BetaConfig {
+    fn default() -> Self {
+        Self {
+            beta: 3950,
+            r0_ohms: None,
+            t0_c: 25,
+        }
+    }
+}
+
+/// Median filter over a burst of thermistor ADC samples
+///
+/// The burst takes microseconds, so it rejects electrical spikes without
+/// lagging a real temperature change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdcFilterConfig {
+    /// Samples per read (1 to `MAX_ADC_SAMPLES`, 1 = no filtering)
+    pub samples: u8,
+    /// Largest distance from the median a sample may be and still count (ADC counts)
+    pub max_delta: u16,
+}
+
+impl Default for AdcFilterConfig {
+    fn default() -> Self {
+        Self {
+            samples: 5,
+            max_delta: 40,
+        }
+    }
+}
+
+/// Steinhart-Hart thermistor coefficients
+///
+/// 1/T = a + b·ln(R) + c·ln(R)³, with T in kelvin and R in ohms.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SteinhartHartConfig {
+    /// Constant coefficient
+    pub a: f32,
+    /// ln(R) coefficient
+    pub b: f32,
+    /// ln(R)³ coefficient
+    pub c: f32,
+}
+
+/// Check that a thermistor table is usable for interpolation
+///
+/// Needs at least two points, with resistance strictly decreasing and
+/// temperature strictly increasing from one point to the next.
+pub fn thermistor_table_valid(table: &[(u32, i16)]) -> bool {
+    table.len() >= 2
+        && table
+            .windows(2)
+            .all(|pair| pair[1].0 < pair[0].0 && pair[1].1 > pair[0].1)
+}
+
+/// Thermistor resistance-to-temperature model
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ThermistorModel {
+    /// Built-in NTC 100K lookup table
+    #[default]
+    Table,
+    /// Lookup table from the config
+    Custom(ThermistorTable),
+    /// Beta equation
+    Beta { beta: u16, r0_ohms: u32, t0_c: i16 },
+    /// Steinhart-Hart equation
+    SteinhartHart { a: f32, b: f32, c: f32 },
 }
 
 /// Temperature sensor type
@@ -274,6 +558,30 @@ pub enum SensorType {
     Ntc10k,
     /// PT100 RTD (future)
     Pt100,
+    /// TMP117 digital sensor on I2C
+    I2cTmp117,
+    /// DS18B20 digital sensor on 1-Wire
+    Ds18b20,
+}
+
+impl SensorType {
+    /// Check if the sensor is read over I2C rather than the ADC
+    pub fn is_i2c(&self) -> bool {
+        matches!(self, SensorType::I2cTmp117)
+    }
+
+    /// Check if the sensor is read over 1-Wire rather than the ADC
+    pub fn is_onewire(&self) -> bool {
+        matches!(self, SensorType::Ds18b20)
+    }
+
+    /// Thermistor resistance at 25°C (ohms)
+    pub fn nominal_ohms(&self) -> u32 {
+        match self {
+            SensorType::Ntc10k => 10_000,
+            _ => 100_000,
+        }
+    }
 }
 
 /// Display configuration
@@ -289,6 +597,59 @@ pub struct DisplayHwConfig {
     pub baud_rate: u32,
 }
 
+/// Default time the lid switch must hold a new level (ms)
+pub const DEFAULT_LID_DEBOUNCE_MS: u16 = 50;
+
+/// Lid (or door) interlock switch
+///
+/// The basket may not spin while the switch reports the lid open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LidConfig {
+    /// Switch input, active while the lid is open
+    pub pin: PinConfig,
+    /// Time the switch must hold a new level before it counts (ms)
+    pub debounce_ms: u16,
+}
+
+impl Default for LidConfig {
+    fn default() -> Self {
+        Self {
+            pin: PinConfig::default(),
+            debounce_ms: DEFAULT_LID_DEBOUNCE_MS,
+        }
+    }
+}
+
+/// Default ultrasonic on-time limit (s)
+pub const DEFAULT_ULTRASONIC_MAX_ON_S: u16 = 900;
+
+/// Ultrasonic cleaning module switched from a GPIO (directly or via a relay)
+///
+/// Runs while the basket works in a jar that names it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UltrasonicHwConfig {
+    /// Module name, referenced by jars
+    pub name: String<MAX_LABEL_LEN>,
+    /// On/off output, active while the module runs
+    pub pin: PinConfig,
+    /// Continuous on-time before a safety cutoff (s, 0 = no limit)
+    pub max_on_s: u16,
+}
+
+impl Default for UltrasonicHwConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            pin: PinConfig::default(),
+            max_on_s: DEFAULT_ULTRASONIC_MAX_ON_S,
+        }
+    }
+}
+
 /// Complete machine configuration
 ///
 /// This is the top-level configuration structure that contains all
@@ -297,7 +658,9 @@ pub struct DisplayHwConfig {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MachineConfig {
-    /// Configuration version for compatibility checks
+    /// Config schema version this config was written for
+    ///
+    /// See [`CONFIG_SCHEMA_VERSION`](super::CONFIG_SCHEMA_VERSION).
     pub version: u8,
     /// Motor type for this machine
     pub motor_type: MotorType,
@@ -309,12 +672,70 @@ pub struct MachineConfig {
     /// Typically near stepper.z position_min (top of travel).
     /// If not specified, defaults to stepper.z position_min.
     pub safe_z: Option<i32>,
+    /// Highest Z the basket may be at when an X move starts (mm)
+    /// The config parser rejects a travel height (`safe_z`, or the top of
+    /// the Z axis) below it, so a misconfigured `safe_z` cannot drag the
+    /// basket through the jars. Z is measured down from the top of travel,
+    /// so the basket clears when its Z is at or above (numerically <=)
+    /// this value.
+    pub x_move_clearance_z: Option<i32>,
+    /// Move the basket to `park_position` once a program finishes
+    /// Only used on automated machines. Not acted on until X/Z position
+    /// control exists: the move is queued and logged, nothing more.
+    pub park_after_program: bool,
+    /// Where the basket rests after a program (defaults to home)
+    /// Inert for now, like `park_after_program`.
+    pub park_position: ParkPosition,
+    /// Order in which the Z and X axes are homed at boot
+    pub homing_order: HomingOrder,
+    /// Basket orientation between steps on manual machines (degrees)
+    /// Measured from the basket stepper's position at power-up.
+    /// None = leave the basket where it stopped.
+    pub park_angle_deg: Option<u16>,
+    /// Prompt for the first jar on manual machines instead of starting
+    /// straight away, as for every later step
+    pub prompt_first_jar: bool,
+    /// Heat the next jar's heater while the basket is carried over on
+    /// automated machines
+    pub prewarm_next_jar: bool,
+    /// Spin the basket back at the end of a program to cancel its net
+    /// rotation (None = off)
+    pub balance_rotation: Option<BalanceConfig>,
+
+    // === Startup ===
+    /// Program to start automatically once idle (headless operation)
+    pub autostart_program: Option<String<MAX_LABEL_LEN>>,
+    /// Run the embedded config when flash has no valid one
+    /// Only read from the embedded config. When false, a missing or
+    /// invalid flash config faults with a config error instead.
+    pub config_fallback: bool,
+
+    // === Safety ===
+    /// Abort a paused program after this many seconds (0 = never)
+    /// Stops an abandoned run from holding the heater on indefinitely.
+    pub max_pause_s: u16,
+    /// Upper limit for any profile's spin-off RPM (None = no limit)
+    /// Guards against typos such as 1500 instead of 150.
+    pub max_spinoff_rpm: Option<u16>,
+    /// Spin-off RPM ceiling while quiet mode is on
+    pub quiet_spinoff_rpm: u16,
+    /// Reverse the basket briefly on a stall to free a jam before faulting
+    pub stall_reverse_recovery: bool,
+    /// Delay the heater after the motor when both start together (ms, 0 = off)
+    /// Spreads the inrush current on power-limited supplies.
+    pub startup_stagger_ms: u16,
+    /// Send motor and heater commands to their tasks only when they change
+    pub commands_on_change: bool,
+    /// Reset the board if the firmware stops running for this long (ms, 0 = off)
+    pub watchdog_timeout_ms: u16,
 
     // === Hardware ===
     /// Stepper motor configurations (when motor_type = Stepper)
     pub steppers: Vec<StepperHwConfig, MAX_STEPPERS>,
     /// TMC2209 driver configurations (when motor_type = Stepper)
     pub tmc2209s: Vec<Tmc2209HwConfig, MAX_STEPPERS>,
+    /// A4988/DRV8825 driver configurations (when motor_type = Stepper)
+    pub a4988s: Vec<A4988HwConfig, MAX_STEPPERS>,
     /// DC motor configurations (when motor_type = Dc)
     pub dc_motors: Vec<DcMotorHwConfig, MAX_DC_MOTORS>,
     /// AC motor configurations (when motor_type = Ac)
@@ -323,6 +744,10 @@ pub struct MachineConfig {
     pub heater_hw: Vec<HeaterHwConfig, MAX_HEATERS>,
     /// Heater control configurations
     pub heaters: Vec<HeaterConfig, MAX_HEATERS>,
+    /// Lid interlock switch (None = no lid)
+    pub lid: Option<LidConfig>,
+    /// Ultrasonic module (None = none; one module is supported)
+    pub ultrasonic: Option<UltrasonicHwConfig>,
     /// Jar configurations
     pub jars: Vec<JarConfig, MAX_JARS>,
     /// Profile configurations
@@ -333,40 +758,96 @@ pub struct MachineConfig {
     pub display: DisplayHwConfig,
     /// UI configuration
     pub ui: UiConfig,
+    /// Display link timing
+    pub link: LinkConfig,
 }
 
 impl Default for MachineConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: super::CONFIG_SCHEMA_VERSION,
             motor_type: MotorType::default(),
             safe_z: None,
+            x_move_clearance_z: None,
+            park_after_program: false,
+            park_position: ParkPosition::default(),
+            homing_order: HomingOrder::default(),
+            park_angle_deg: None,
+            prompt_first_jar: false,
+            prewarm_next_jar: false,
+            balance_rotation: None,
+            autostart_program: None,
+            config_fallback: true,
+            max_pause_s: 0,
+            max_spinoff_rpm: None,
+            quiet_spinoff_rpm: DEFAULT_QUIET_SPINOFF_RPM,
+            stall_reverse_recovery: false,
+            startup_stagger_ms: 0,
+            commands_on_change: false,
+            watchdog_timeout_ms: 5000,
             steppers: Vec::new(),
             tmc2209s: Vec::new(),
+            a4988s: Vec::new(),
             dc_motors: Vec::new(),
             ac_motors: Vec::new(),
             heater_hw: Vec::new(),
             heaters: Vec::new(),
+            lid: None,
+            ultrasonic: None,
             jars: Vec::new(),
             profiles: Vec::new(),
             programs: Vec::new(),
             display: DisplayHwConfig::default(),
             ui: UiConfig::default(),
+            link: LinkConfig::default(),
         }
     }
 }
 
+/// Where the running configuration came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigSource {
+    /// Valid config loaded from flash
+    Flash,
+    /// Flash had none; running the embedded defaults
+    Embedded,
+    /// Flash had none and fallback is disabled
+    ///
+    /// The embedded config is still returned so the board can bring up
+    /// its display, but the machine must fault instead of running it.
+    Missing,
+}
+
 impl MachineConfig {
     /// Create a new empty configuration
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Pick the config to run from the flash config, if valid
+    ///
+    /// Falls back to `embedded` unless its `config_fallback` is off.
+    pub fn select(flash: Option<Self>, embedded: Self) -> (Self, ConfigSource) {
+        match flash {
+            Some(config) => (config, ConfigSource::Flash),
+            None if embedded.config_fallback => (embedded, ConfigSource::Embedded),
+            None => (embedded, ConfigSource::Missing),
+        }
+    }
+
     /// Find a stepper by name
     pub fn find_stepper(&self, name: &str) -> Option<&StepperHwConfig> {
         self.steppers.iter().find(|s| s.name.as_str() == name)
     }
 
+    /// Find the A4988-style driver for a stepper
+    pub fn find_a4988(&self, stepper_name: &str) -> Option<&A4988HwConfig> {
+        self.a4988s
+            .iter()
+            .find(|a| a.stepper_name.as_str() == stepper_name)
+    }
+
     /// Find a DC motor by name
     pub fn find_dc_motor(&self, name: &str) -> Option<&DcMotorHwConfig> {
         self.dc_motors.iter().find(|m| m.name.as_str() == name)
@@ -382,6 +863,11 @@ impl MachineConfig {
         self.heaters.iter().find(|h| h.name.as_str() == name)
     }
 
+    /// Find heater hardware by name
+    pub fn find_heater_hw(&self, name: &str) -> Option<&HeaterHwConfig> {
+        self.heater_hw.iter().find(|h| h.name.as_str() == name)
+    }
+
     /// Find a jar by name
     pub fn find_jar(&self, name: &str) -> Option<&JarConfig> {
         self.jars.iter().find(|j| j.name.as_str() == name)
@@ -408,6 +894,17 @@ impl MachineConfig {
         }
     }
 
+    /// Z position the basket lifts to for horizontal travel
+    ///
+    /// `safe_z` if set, otherwise the top of the Z axis travel.
+    pub fn travel_z(&self) -> Option<i32> {
+        self.safe_z.or_else(|| match self.motor_type {
+            MotorType::Stepper => self.find_stepper("z").map(|s| s.position_min),
+            MotorType::Dc => self.find_dc_motor("z").map(|m| m.position_min),
+            MotorType::Ac => self.find_ac_motor("z").map(|m| m.position_min),
+        })
+    }
+
     /// Get the basket stepper (for stepper motor machines)
     pub fn basket_stepper(&self) -> Option<&StepperHwConfig> {
         self.find_stepper("basket")
@@ -451,6 +948,72 @@ mod tests {
         assert!(pullup.pull_up);
     }
 
+    #[test]
+    fn test_pin_is_active() {
+        let normal = PinConfig::new(4);
+        assert!(normal.is_active(true));
+        assert!(!normal.is_active(false));
+
+        // Inverted pin is active at the opposite electrical level
+        let inverted = PinConfig::inverted(4);
+        assert!(inverted.is_active(false));
+        assert!(!inverted.is_active(true));
+    }
+
+    #[test]
+    fn test_thermistor_model() {
+        let mut hw = HeaterHwConfig::default();
+        assert_eq!(hw.thermistor_model(), ThermistorModel::Table);
+
+        // The beta reference resistance follows the sensor type
+        hw.sensor_type = SensorType::Ntc10k;
+        hw.beta = Some(BetaConfig::default());
+        assert_eq!(
+            hw.thermistor_model(),
+            ThermistorModel::Beta {
+                beta: 3950,
+                r0_ohms: 10_000,
+                t0_c: 25
+            }
+        );
+
+        // Steinhart-Hart wins over beta
+        hw.steinhart_hart = Some(SteinhartHartConfig {
+            a: 7.0e-4,
+            b: 2.2e-4,
+            c: 9.0e-8,
+        });
+        assert!(matches!(
+            hw.thermistor_model(),
+            ThermistorModel::SteinhartHart { .. }
+        ));
+
+        // A custom table wins over both
+        hw.temp_table = Vec::from_slice(&[(30_000, 100), (3_000, 800)]).unwrap();
+        assert_eq!(
+            hw.thermistor_model(),
+            ThermistorModel::Custom(hw.temp_table.clone())
+        );
+    }
+
+    #[test]
+    fn test_thermistor_table_valid() {
+        assert!(thermistor_table_valid(&[(100_000, 250), (40_000, 450)]));
+        assert!(!thermistor_table_valid(&[(100_000, 250)]));
+        // Resistance must fall as temperature rises
+        assert!(!thermistor_table_valid(&[(40_000, 250), (100_000, 450)]));
+        assert!(!thermistor_table_valid(&[
+            (100_000, 250),
+            (40_000, 450),
+            (40_000, 500)
+        ]));
+        assert!(!thermistor_table_valid(&[
+            (100_000, 250),
+            (40_000, 450),
+            (30_000, 400)
+        ]));
+    }
+
     #[test]
     fn test_empty_config() {
         let config = MachineConfig::new();
@@ -458,4 +1021,63 @@ mod tests {
         assert!(!config.is_automated());
         assert!(config.basket_stepper().is_none());
     }
+
+    #[test]
+    fn test_steps_per_mm() {
+        let mut stepper = StepperHwConfig {
+            full_steps_per_rotation: 200,
+            microsteps: 16,
+            rotation_distance_um: 40_000,
+            gear_ratio_num: 1,
+            gear_ratio_den: 1,
+            ..Default::default()
+        };
+        assert_eq!(stepper.steps_per_mm_x1000(), 80_000);
+
+        // 8.5 mm lead: 3200 / 8.5 = 376.470...
+        stepper.rotation_distance_um = 8_500;
+        assert_eq!(stepper.steps_per_mm_x1000(), 376_470);
+
+        // 3:1 reduction on a 1.25 mm pitch
+        stepper.rotation_distance_um = 1_250;
+        stepper.gear_ratio_num = 3;
+        assert_eq!(stepper.steps_per_mm_x1000(), 7_680_000);
+
+        stepper.rotation_distance_um = 0;
+        assert_eq!(stepper.steps_per_mm_x1000(), 0);
+    }
+
+    #[test]
+    fn test_select_config() {
+        let mut flash = MachineConfig::new();
+        flash.max_pause_s = 60;
+        let (config, source) = MachineConfig::select(Some(flash), MachineConfig::new());
+        assert_eq!(source, ConfigSource::Flash);
+        assert_eq!(config.max_pause_s, 60);
+
+        // Invalid flash: embedded defaults load as before
+        let (_, source) = MachineConfig::select(None, MachineConfig::new());
+        assert_eq!(source, ConfigSource::Embedded);
+
+        // Unless fallback is disabled
+        let mut embedded = MachineConfig::new();
+        embedded.config_fallback = false;
+        let (_, source) = MachineConfig::select(None, embedded);
+        assert_eq!(source, ConfigSource::Missing);
+    }
+
+    #[test]
+    fn test_travel_z() {
+        let mut config = MachineConfig::new();
+        assert_eq!(config.travel_z(), None);
+
+        let mut z = StepperHwConfig::default();
+        z.name.push_str("z").unwrap();
+        z.position_min = 2;
+        config.steppers.push(z).unwrap();
+        assert_eq!(config.travel_z(), Some(2));
+
+        config.safe_z = Some(5);
+        assert_eq!(config.travel_z(), Some(5));
+    }
 }
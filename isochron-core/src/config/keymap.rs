@@ -0,0 +1,187 @@
+//! Encoder button keymap
+//!
+//! Maps each button gesture to an action, per group of machine states,
+//! so button behaviour can be changed from the config instead of in the
+//! controller's input handling. The controller decides what an action
+//! means in the exact state it is in; the keymap only decides which
+//! action a gesture triggers.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::state::State;
+
+/// Encoder button gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Button {
+    /// Single click
+    Click,
+    /// Press and hold
+    LongPress,
+    /// Two quick clicks
+    DoubleClick,
+}
+
+impl Button {
+    const COUNT: usize = 3;
+
+    fn index(self) -> usize {
+        match self {
+            Button::Click => 0,
+            Button::LongPress => 1,
+            Button::DoubleClick => 2,
+        }
+    }
+}
+
+/// Group of states that share button bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StateCategory {
+    /// Program list
+    Idle,
+    /// Program selected
+    Menu,
+    /// Program being edited
+    Edit,
+    /// Running, paused or spinning off
+    Program,
+    /// Between steps: waiting for the user or the next step
+    Transition,
+    /// Program complete or an error shown
+    Finished,
+    /// PID autotune screens
+    Autotune,
+}
+
+impl StateCategory {
+    const COUNT: usize = 7;
+
+    /// Category of a state (None while booting)
+    pub fn of(state: State) -> Option<Self> {
+        Some(match state {
+            State::Boot => return None,
+            State::Idle => StateCategory::Idle,
+            State::ProgramSelected => StateCategory::Menu,
+            State::EditProgram => StateCategory::Edit,
+            State::Running | State::Paused | State::SpinOff => StateCategory::Program,
+            State::AwaitingJar | State::AwaitingSpinOff | State::StepComplete => {
+                StateCategory::Transition
+            }
+            State::ProgramComplete | State::Error(_) => StateCategory::Finished,
+            State::Autotuning => StateCategory::Autotune,
+        })
+    }
+
+    fn index(self) -> usize {
+        match self {
+            StateCategory::Idle => 0,
+            StateCategory::Menu => 1,
+            StateCategory::Edit => 2,
+            StateCategory::Program => 3,
+            StateCategory::Transition => 4,
+            StateCategory::Finished => 5,
+            StateCategory::Autotune => 6,
+        }
+    }
+}
+
+/// Action triggered by a button gesture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyAction {
+    /// Do nothing
+    Ignore,
+    /// The state's main action: select, start, confirm, pause or resume,
+    /// acknowledge
+    Select,
+    /// Step back a screen without side effects
+    Back,
+    /// Abort the program or cancel autotune
+    Abort,
+    /// Switch quiet mode on or off
+    ToggleQuiet,
+}
+
+/// Button bindings for every state category
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Keymap {
+    bindings: [[KeyAction; Button::COUNT]; StateCategory::COUNT],
+}
+
+impl Default for Keymap {
+    /// Click selects everywhere, long press aborts a program (or backs
+    /// out of the program menu) and double-click steps back through menus.
+    /// A long press while editing does nothing, so it can't drop edits.
+    fn default() -> Self {
+        use KeyAction::*;
+        // Columns: click, long press, double-click
+        Self {
+            bindings: [
+                [Select, Ignore, Back],  // Idle
+                [Select, Back, Back],    // Menu
+                [Select, Ignore, Back],  // Edit
+                [Select, Abort, Ignore], // Program
+                [Select, Abort, Ignore], // Transition
+                [Select, Ignore, Back],  // Finished
+                [Select, Abort, Back],   // Autotune
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// Action bound to `button` in `category`
+    pub fn action(&self, category: StateCategory, button: Button) -> KeyAction {
+        self.bindings[category.index()][button.index()]
+    }
+
+    /// Bind `button` in `category` to `action`
+    pub fn bind(&mut self, category: StateCategory, button: Button, action: KeyAction) {
+        self.bindings[category.index()][button.index()] = action;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ErrorKind;
+
+    #[test]
+    fn test_every_state_but_boot_has_a_category() {
+        assert_eq!(StateCategory::of(State::Boot), None);
+        assert_eq!(
+            StateCategory::of(State::Paused),
+            Some(StateCategory::Program)
+        );
+        assert_eq!(
+            StateCategory::of(State::Error(ErrorKind::MotorStall)),
+            Some(StateCategory::Finished)
+        );
+        assert_eq!(
+            StateCategory::of(State::EditProgram),
+            Some(StateCategory::Edit)
+        );
+    }
+
+    #[test]
+    fn test_bind_overrides_one_binding() {
+        let mut keymap = Keymap::default();
+        keymap.bind(StateCategory::Program, Button::LongPress, KeyAction::Back);
+
+        assert_eq!(
+            keymap.action(StateCategory::Program, Button::LongPress),
+            KeyAction::Back
+        );
+        assert_eq!(
+            keymap.action(StateCategory::Transition, Button::LongPress),
+            KeyAction::Abort
+        );
+    }
+}
@@ -4,8 +4,14 @@
 
 pub mod calibration;
 pub mod hardware;
+pub mod keymap;
+pub mod overrides;
+pub mod schema;
 pub mod types;
 
 pub use calibration::*;
 pub use hardware::*;
+pub use keymap::*;
+pub use overrides::*;
+pub use schema::*;
 pub use types::*;
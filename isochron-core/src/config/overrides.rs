@@ -0,0 +1,302 @@
+//! User-adjusted profile values
+//!
+//! Stores profile parameters edited on the machine so they can be
+//! persisted to flash and merged over the loaded configuration on boot.
+
+use heapless::String;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::calibration::crc32_update;
+use super::types::{ProfileConfig, MAX_LABEL_LEN, MAX_PROFILES};
+use crate::scheduler::DirectionMode;
+
+/// Magic number to identify valid profile override data
+pub const PROFILE_OVERRIDES_MAGIC: u32 = 0x50524F46; // "PROF"
+
+/// Current profile override data version
+pub const PROFILE_OVERRIDES_VERSION: u8 = 1;
+
+/// Saved values for a single profile
+///
+/// The label is stored alongside the index so that overrides are not
+/// applied to a different profile after the configuration changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProfileOverride {
+    /// Profile index in the configuration
+    pub profile_index: u8,
+    /// Whether this slot is valid
+    pub valid: bool,
+    /// Label of the profile when it was saved
+    pub label: String<MAX_LABEL_LEN>,
+    /// Target RPM
+    pub rpm: u16,
+    /// Duration in seconds
+    pub time_s: u16,
+    /// Direction mode
+    pub direction: DirectionMode,
+    /// Number of iterations (for Alternate mode)
+    pub iterations: u8,
+    /// Target temperature (°C)
+    pub temperature_c: Option<i16>,
+}
+
+impl ProfileOverride {
+    /// Capture the adjustable values of a profile
+    pub fn from_profile(profile_index: u8, profile: &ProfileConfig) -> Self {
+        Self {
+            profile_index,
+            valid: true,
+            label: profile.label.clone(),
+            rpm: profile.rpm,
+            time_s: profile.time_s,
+            direction: profile.direction,
+            iterations: profile.iterations,
+            temperature_c: profile.temperature_c,
+        }
+    }
+
+    /// Check if this override slot is valid
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Copy the saved values onto a profile
+    pub fn apply_to(&self, profile: &mut ProfileConfig) {
+        profile.rpm = self.rpm;
+        profile.time_s = self.time_s;
+        profile.direction = self.direction;
+        profile.iterations = self.iterations;
+        profile.temperature_c = self.temperature_c;
+    }
+}
+
+/// Complete profile override data stored in flash
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProfileOverrides {
+    /// Magic number for validation
+    pub magic: u32,
+    /// Data format version
+    pub version: u8,
+    /// Per-profile overrides
+    pub profiles: [ProfileOverride; MAX_PROFILES],
+    /// CRC32 checksum (calculated over magic..profiles)
+    pub crc: u32,
+}
+
+impl Default for ProfileOverrides {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProfileOverrides {
+    /// Create empty override data
+    pub fn new() -> Self {
+        Self {
+            magic: PROFILE_OVERRIDES_MAGIC,
+            version: PROFILE_OVERRIDES_VERSION,
+            profiles: Default::default(),
+            crc: 0,
+        }
+    }
+
+    /// Check if the data is valid (magic and version match)
+    pub fn is_valid(&self) -> bool {
+        self.magic == PROFILE_OVERRIDES_MAGIC && self.version == PROFILE_OVERRIDES_VERSION
+    }
+
+    /// Get the override for a profile index
+    pub fn get(&self, profile_index: u8) -> Option<&ProfileOverride> {
+        self.profiles
+            .iter()
+            .find(|o| o.valid && o.profile_index == profile_index)
+    }
+
+    /// Store the current values of a profile
+    ///
+    /// Replaces any existing override for the same index.
+    /// Returns true if successful, false if no slots available.
+    pub fn set(&mut self, profile_index: u8, profile: &ProfileConfig) -> bool {
+        let entry = ProfileOverride::from_profile(profile_index, profile);
+
+        let slot = match self
+            .profiles
+            .iter()
+            .position(|o| o.valid && o.profile_index == profile_index)
+        {
+            Some(i) => Some(i),
+            None => self.profiles.iter().position(|o| !o.valid),
+        };
+
+        match slot {
+            Some(i) => {
+                self.profiles[i] = entry;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove the override for a profile index
+    pub fn clear_profile(&mut self, profile_index: u8) {
+        for slot in &mut self.profiles {
+            if slot.profile_index == profile_index {
+                *slot = ProfileOverride::default();
+            }
+        }
+    }
+
+    /// Merge saved values over loaded profiles
+    ///
+    /// Overrides whose label no longer matches the profile at that index
+    /// are skipped. Returns the number of profiles updated.
+    pub fn apply(&self, profiles: &mut [ProfileConfig]) -> usize {
+        let mut applied = 0;
+        for entry in self.profiles.iter().filter(|o| o.valid) {
+            if let Some(profile) = profiles.get_mut(entry.profile_index as usize) {
+                if profile.label == entry.label {
+                    entry.apply_to(profile);
+                    applied += 1;
+                }
+            }
+        }
+        applied
+    }
+
+    /// Calculate CRC32 for the data (excluding the crc field itself)
+    pub fn calculate_crc(&self) -> u32 {
+        let mut crc: u32 = 0xFFFFFFFF;
+
+        crc = crc32_update(crc, &self.magic.to_le_bytes());
+        crc = crc32_update(crc, &[self.version]);
+
+        for entry in &self.profiles {
+            crc = crc32_update(crc, &[entry.profile_index, entry.valid as u8]);
+            crc = crc32_update(crc, &[entry.label.len() as u8]);
+            crc = crc32_update(crc, entry.label.as_bytes());
+            crc = crc32_update(crc, &entry.rpm.to_le_bytes());
+            crc = crc32_update(crc, &entry.time_s.to_le_bytes());
+            crc = crc32_update(crc, &[entry.direction as u8, entry.iterations]);
+            match entry.temperature_c {
+                Some(temp) => {
+                    crc = crc32_update(crc, &[1]);
+                    crc = crc32_update(crc, &temp.to_le_bytes());
+                }
+                None => crc = crc32_update(crc, &[0]),
+            }
+        }
+
+        !crc
+    }
+
+    /// Update the CRC field
+    pub fn update_crc(&mut self) {
+        self.crc = self.calculate_crc();
+    }
+
+    /// Verify the CRC is correct
+    pub fn verify_crc(&self) -> bool {
+        self.crc == self.calculate_crc()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_profile(name: &str, rpm: u16) -> ProfileConfig {
+        let mut label = String::new();
+        let _ = label.push_str(name);
+        ProfileConfig {
+            label,
+            rpm,
+            ..Default::default()
+        }
+    }
+
+    fn loaded_profiles() -> [ProfileConfig; 2] {
+        [make_profile("Clean", 120), make_profile("Rinse", 100)]
+    }
+
+    #[test]
+    fn test_saved_edit_is_reloaded() {
+        let mut stored = ProfileOverrides::new();
+
+        // User edits Rinse and chooses to save
+        let mut edited = loaded_profiles()[1].clone();
+        edited.rpm = 150;
+        edited.time_s = 240;
+        edited.temperature_c = Some(35);
+        assert!(stored.set(1, &edited));
+        stored.update_crc();
+        assert!(stored.verify_crc());
+
+        // Next boot merges the saved values over the loaded config
+        let mut profiles = loaded_profiles();
+        assert_eq!(stored.apply(&mut profiles), 1);
+        assert_eq!(profiles[1].rpm, 150);
+        assert_eq!(profiles[1].time_s, 240);
+        assert_eq!(profiles[1].temperature_c, Some(35));
+        assert_eq!(profiles[0].rpm, 120);
+    }
+
+    #[test]
+    fn test_declined_edit_leaves_store_unchanged() {
+        let mut stored = ProfileOverrides::new();
+        let mut saved = loaded_profiles()[0].clone();
+        saved.rpm = 90;
+        stored.set(0, &saved);
+        stored.update_crc();
+        let crc_before = stored.crc;
+
+        // User edits again but declines to save: the store is not touched
+        let mut edited = saved.clone();
+        edited.rpm = 200;
+
+        let mut profiles = loaded_profiles();
+        stored.apply(&mut profiles);
+        assert_eq!(profiles[0].rpm, 90);
+        assert_eq!(stored.crc, crc_before);
+        assert!(stored.verify_crc());
+        assert_ne!(edited.rpm, profiles[0].rpm);
+    }
+
+    #[test]
+    fn test_set_replaces_existing_entry() {
+        let mut stored = ProfileOverrides::new();
+        stored.set(0, &make_profile("Clean", 100));
+        stored.set(0, &make_profile("Clean", 110));
+
+        assert_eq!(stored.profiles.iter().filter(|o| o.valid).count(), 1);
+        assert_eq!(stored.get(0).unwrap().rpm, 110);
+
+        stored.clear_profile(0);
+        assert!(stored.get(0).is_none());
+    }
+
+    #[test]
+    fn test_label_mismatch_skipped() {
+        let mut stored = ProfileOverrides::new();
+        stored.set(0, &make_profile("Old", 200));
+
+        let mut profiles = loaded_profiles();
+        assert_eq!(stored.apply(&mut profiles), 0);
+        assert_eq!(profiles[0].rpm, 120);
+    }
+
+    #[test]
+    fn test_crc_detects_change() {
+        let mut stored = ProfileOverrides::new();
+        stored.set(0, &make_profile("Clean", 100));
+        stored.update_crc();
+
+        stored.profiles[0].rpm = 101;
+        assert!(!stored.verify_crc());
+    }
+}
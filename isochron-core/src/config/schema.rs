@@ -0,0 +1,174 @@
+//! Config schema versioning
+//!
+//! [`CONFIG_SCHEMA_VERSION`] is the single source of truth for the layout
+//! of [`MachineConfig`]. It is written in front of binary configs in
+//! flash, checked against the `version` key of TOML configs, and reported
+//! to the display so a companion app can tell which configs a firmware
+//! accepts.
+//!
+//! # Compatibility policy
+//!
+//! - Bump the version whenever a change alters the binary layout or the
+//!   meaning of an existing key. Adding an optional TOML key does not
+//!   need a bump.
+//! - A config with the current version loads as-is.
+//! - A config older than the current version, but not older than
+//!   [`MIN_CONFIG_SCHEMA_VERSION`], is migrated in place by
+//!   [`MachineConfig::migrate_schema`].
+//! - Anything older than that, or newer than the firmware, is rejected
+//!   rather than guessed at, and the embedded fallback config is used.
+//! - A binary config is only read back if its layout is no older than
+//!   [`MIN_BINARY_SCHEMA_VERSION`]; older blobs can't be deserialized
+//!   and are rejected the same way.
+//!
+//! # History
+//!
+//! - 1: initial schema.
+//! - 2: settings for the spin ramp, homing and backlash, the lid and
+//!   ultrasonic modules, thermistor models, jar pre-warming and more.
+//!   Keys schema 1 lacked take their defaults, so a schema 1 TOML config
+//!   migrates without conversion; the binary layout changed.
+
+use super::MachineConfig;
+
+/// Config schema version understood by this firmware
+pub const CONFIG_SCHEMA_VERSION: u8 = 2;
+
+/// Oldest config schema version that can still be migrated
+pub const MIN_CONFIG_SCHEMA_VERSION: u8 = 1;
+
+/// Oldest binary config layout that can still be deserialized
+pub const MIN_BINARY_SCHEMA_VERSION: u8 = 2;
+
+/// Length of the schema header in front of a binary config
+pub const SCHEMA_HEADER_LEN: usize = 1;
+
+/// Config schema version rejected by the compatibility policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SchemaError {
+    /// No schema header present
+    Missing,
+    /// Older than [`MIN_CONFIG_SCHEMA_VERSION`]; no migration exists
+    TooOld(u8),
+    /// Written for newer firmware
+    TooNew(u8),
+}
+
+/// Check a config schema version against the compatibility policy
+///
+/// Returns `Ok(true)` if the config needs migrating first.
+pub fn check_schema_version(version: u8) -> Result<bool, SchemaError> {
+    if version > CONFIG_SCHEMA_VERSION {
+        Err(SchemaError::TooNew(version))
+    } else if version < MIN_CONFIG_SCHEMA_VERSION {
+        Err(SchemaError::TooOld(version))
+    } else {
+        Ok(version < CONFIG_SCHEMA_VERSION)
+    }
+}
+
+/// Write the schema header for a binary config
+///
+/// Returns the header length, or `None` if `out` is empty.
+pub fn write_schema_header(out: &mut [u8]) -> Option<usize> {
+    *out.first_mut()? = CONFIG_SCHEMA_VERSION;
+    Some(SCHEMA_HEADER_LEN)
+}
+
+/// Split a binary config into its schema version and payload
+///
+/// The version is checked, so a payload is only returned if it can be
+/// deserialized (and migrated if the version is older).
+pub fn read_schema_header(blob: &[u8]) -> Result<(u8, &[u8]), SchemaError> {
+    let (&version, payload) = blob.split_first().ok_or(SchemaError::Missing)?;
+    check_schema_version(version)?;
+    if version < MIN_BINARY_SCHEMA_VERSION {
+        return Err(SchemaError::TooOld(version));
+    }
+    Ok((version, payload))
+}
+
+impl MachineConfig {
+    /// Bring a config from an older schema version up to date
+    ///
+    /// Migration steps go here as the schema evolves, one per version.
+    /// Configs that can't be migrated are left untouched.
+    pub fn migrate_schema(&mut self) -> Result<(), SchemaError> {
+        if !check_schema_version(self.version)? {
+            return Ok(());
+        }
+        // 1 -> 2: keys added in 2 keep their defaults
+        self.version = CONFIG_SCHEMA_VERSION;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_version_loads() {
+        let mut blob = [0u8; 4];
+        let len = write_schema_header(&mut blob).unwrap();
+        blob[len..].copy_from_slice(&[7, 8, 9]);
+
+        let (version, payload) = read_schema_header(&blob).unwrap();
+        assert_eq!(version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(payload, &[7, 8, 9]);
+        assert_eq!(check_schema_version(version), Ok(false));
+    }
+
+    #[test]
+    fn test_old_version_rejected() {
+        let old = MIN_CONFIG_SCHEMA_VERSION - 1;
+        assert_eq!(
+            read_schema_header(&[old, 1, 2]),
+            Err(SchemaError::TooOld(old))
+        );
+
+        let mut config = MachineConfig {
+            version: old,
+            ..Default::default()
+        };
+        assert_eq!(config.migrate_schema(), Err(SchemaError::TooOld(old)));
+        assert_eq!(config.version, old);
+    }
+
+    #[test]
+    fn test_newer_version_rejected() {
+        let new = CONFIG_SCHEMA_VERSION + 1;
+        assert_eq!(
+            read_schema_header(&[new, 1, 2]),
+            Err(SchemaError::TooNew(new))
+        );
+        assert_eq!(read_schema_header(&[]), Err(SchemaError::Missing));
+    }
+
+    #[test]
+    fn test_schema_1_migrates_with_defaults() {
+        let mut config = MachineConfig {
+            version: 1,
+            ..Default::default()
+        };
+        assert_eq!(check_schema_version(1), Ok(true));
+        assert_eq!(config.migrate_schema(), Ok(()));
+        assert_eq!(config.version, CONFIG_SCHEMA_VERSION);
+        // Settings schema 1 didn't have keep their defaults
+        assert!(!config.prewarm_next_jar);
+        assert!(config.ultrasonic.is_none());
+        assert!(config.steppers.is_empty());
+
+        // Its binary layout can't be read back
+        assert_eq!(read_schema_header(&[1, 7, 8]), Err(SchemaError::TooOld(1)));
+    }
+
+    #[test]
+    fn test_current_config_needs_no_migration() {
+        let mut config = MachineConfig::default();
+        assert_eq!(config.version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(config.migrate_schema(), Ok(()));
+        assert_eq!(config.version, CONFIG_SCHEMA_VERSION);
+    }
+}
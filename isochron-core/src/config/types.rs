@@ -4,8 +4,10 @@
 //! in flash as postcard-serialized binary data.
 
 use heapless::String;
+use isochron_protocol::LinkTestPattern;
 
-use crate::scheduler::{DirectionMode, SpinOffConfig};
+use super::keymap::Keymap;
+use crate::scheduler::{DirectionMode, PrimeConfig, SoakConfig, SpinOffConfig};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -13,6 +15,9 @@ use serde::{Deserialize, Serialize};
 /// Maximum label length
 pub const MAX_LABEL_LEN: usize = 16;
 
+/// Maximum program notes length (two display rows)
+pub const MAX_NOTES_LEN: usize = 42;
+
 /// Maximum profiles per config
 pub const MAX_PROFILES: usize = 8;
 
@@ -55,8 +60,14 @@ pub struct ProfileConfig {
     pub iterations: u8,
     /// Target temperature for drying (°C)
     pub temperature_c: Option<i16>,
+    /// Profile temperature ceiling (°C), below the heater's hardware max
+    pub max_temp_c: Option<i16>,
     /// Optional spin-off configuration
     pub spinoff: Option<SpinOffConfig>,
+    /// Optional spin/soak cycling, replacing `time_s` and `iterations`
+    pub soak: Option<SoakConfig>,
+    /// Optional unheated mixing spin before the profile
+    pub prime: Option<PrimeConfig>,
 }
 
 impl Default for ProfileConfig {
@@ -69,11 +80,26 @@ impl Default for ProfileConfig {
             direction: DirectionMode::Alternate,
             iterations: 3,
             temperature_c: None,
+            max_temp_c: None,
             spinoff: None,
+            soak: None,
+            prime: None,
         }
     }
 }
 
+impl ProfileConfig {
+    /// Effective heater target for this profile
+    ///
+    /// The requested temperature is clamped to the profile's `max_temp_c`,
+    /// then to `hardware_max_c`, which always acts as the hard ceiling.
+    pub fn effective_temp_c(&self, hardware_max_c: i16) -> Option<i16> {
+        let target = self.temperature_c?;
+        let profile_max = self.max_temp_c.unwrap_or(hardware_max_c);
+        Some(target.min(profile_max).min(hardware_max_c))
+    }
+}
+
 /// Jar position configuration
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -94,6 +120,19 @@ pub struct JarConfig {
     pub lid: Option<String<MAX_LABEL_LEN>>,
 }
 
+/// Basket resting position for automated machines
+///
+/// Positions use the same units as `JarConfig`. The default is home (0, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParkPosition {
+    /// X-axis position (mm from home)
+    pub x_pos: i32,
+    /// Z-axis position (mm down from top)
+    pub z_pos: i32,
+}
+
 /// Program step (jar + profile pair)
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -112,6 +151,8 @@ pub struct ProgramStep {
 pub struct ProgramConfig {
     /// Display label
     pub label: String<MAX_LABEL_LEN>,
+    /// Short description shown on the program detail screen
+    pub notes: Option<String<MAX_NOTES_LEN>>,
     /// Steps in this program
     pub steps: heapless::Vec<ProgramStep, MAX_STEPS_PER_PROGRAM>,
 }
@@ -120,6 +161,7 @@ impl Default for ProgramConfig {
     fn default() -> Self {
         Self {
             label: String::new(),
+            notes: None,
             steps: heapless::Vec::new(),
         }
     }
@@ -137,6 +179,51 @@ pub enum HeaterControlMode {
     Pid,
 }
 
+/// What a heater's temperature sensor fault does to a running program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SensorFaultPolicy {
+    /// Fault and abort the program at once
+    #[default]
+    Abort,
+    /// Switch the heater off and keep agitating, aborting only if the
+    /// fault persists
+    ContinueUnheated,
+}
+
+/// Thermal runaway detection
+///
+/// While the heater is switched on, the temperature must rise by at least
+/// `min_rise_c` within every `window_s`. A heater stuck on without effect,
+/// or a thermistor that has come away from the element and reads flat,
+/// faults instead of heating unwatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThermalRunawayConfig {
+    /// Continuous heating time allowed for each rise (s)
+    pub window_s: u16,
+    /// Rise expected within each window (°C)
+    pub min_rise_c: u8,
+}
+
+impl Default for ThermalRunawayConfig {
+    fn default() -> Self {
+        Self {
+            window_s: 120,
+            min_rise_c: 2,
+        }
+    }
+}
+
+impl ThermalRunawayConfig {
+    /// Heating window (ms)
+    pub fn window_ms(&self) -> u32 {
+        self.window_s as u32 * 1000
+    }
+}
+
 /// Heater configuration
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -159,6 +246,12 @@ pub struct HeaterConfig {
     pub pid_ki_x100: Option<i16>,
     /// PID derivative gain (value × 100, e.g., 50 = 0.50)
     pub pid_kd_x100: Option<i16>,
+    /// Maximum heating rate (°C/min, 0 = no limit)
+    pub max_heat_rate_c_per_min: u16,
+    /// Response to a temperature sensor fault
+    pub fault_policy: SensorFaultPolicy,
+    /// Thermal runaway detection (None = off)
+    pub runaway: Option<ThermalRunawayConfig>,
 }
 
 /// UI configuration
@@ -172,6 +265,22 @@ pub struct UiConfig {
     pub time_step_s: u16,
     /// Temperature adjustment step (°C)
     pub temp_step_c: i16,
+    /// Minimum time between progress-only display renders (ms)
+    pub min_render_interval_ms: u16,
+    /// Reserve the top display row for a persistent status header
+    pub status_header: bool,
+    /// Show whole-program progress on the running screen
+    pub show_overall_progress: bool,
+    /// Return from the complete screen to idle after this long (s, 0 = wait for a click)
+    pub complete_auto_return_s: u16,
+    /// Confirm a manual jar or step prompt after this long (s, 0 = wait for a click)
+    pub auto_advance_s: u16,
+    /// Offer the sensor diagnostics screen in the idle menu
+    pub diagnostics_menu: bool,
+    /// Ignore all input but abort while the machine moves on its own
+    pub lock_during_motion: bool,
+    /// Encoder button bindings
+    pub keymap: Keymap,
 }
 
 impl Default for UiConfig {
@@ -180,10 +289,59 @@ impl Default for UiConfig {
             rpm_step: 10,
             time_step_s: 30,
             temp_step_c: 5,
+            min_render_interval_ms: 250,
+            status_header: false,
+            show_overall_progress: false,
+            complete_auto_return_s: 0,
+            auto_advance_s: 0,
+            diagnostics_menu: false,
+            lock_during_motion: false,
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+/// Display link timing
+///
+/// The display sends a heartbeat every `heartbeat_ms`; the controller
+/// declares the link lost after `timeout_multiplier` intervals without one.
+/// With `auto_clear` set, the lost-link fault clears by itself once
+/// heartbeats return instead of latching until acknowledged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinkConfig {
+    /// Display heartbeat interval (ms)
+    pub heartbeat_ms: u16,
+    /// Heartbeat intervals without a heartbeat before the link is lost
+    pub timeout_multiplier: u8,
+    /// Clear a lost-link fault once heartbeats return
+    pub auto_clear: bool,
+    /// Send this test pattern with every heartbeat to check the link
+    pub test_pattern: Option<LinkTestPattern>,
+    /// Send the scheduler state to the host whenever it changes
+    pub report_scheduler_state: bool,
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_ms: isochron_protocol::DEFAULT_HEARTBEAT_MS,
+            timeout_multiplier: 3,
+            auto_clear: false,
+            test_pattern: None,
+            report_scheduler_state: false,
         }
     }
 }
 
+impl LinkConfig {
+    /// Link-lost timeout (ms)
+    pub fn timeout_ms(&self) -> u32 {
+        self.heartbeat_ms as u32 * self.timeout_multiplier as u32
+    }
+}
+
 /// Machine capabilities (determined from config)
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -197,6 +355,8 @@ pub struct MachineCapabilities {
     pub has_lid: bool,
     /// Number of heaters
     pub heater_count: u8,
+    /// Has a heater; without one, profile temperatures are ignored
+    pub has_heater: bool,
     /// Is an automated machine (has z and x motors)
     pub is_automated: bool,
 }
@@ -209,6 +369,7 @@ impl MachineCapabilities {
             has_x,
             has_lid,
             heater_count,
+            has_heater: heater_count > 0,
             is_automated: has_z && has_x,
         }
     }
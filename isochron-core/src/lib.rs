@@ -9,6 +9,7 @@
 //! - Motion planning (acceleration math)
 //! - Safety monitoring logic
 //! - Configuration type definitions
+//! - Shared utilities (retry/backoff, fixed-point temperature)
 
 #![no_std]
 #![deny(unsafe_code)]
@@ -19,3 +20,4 @@ pub mod safety;
 pub mod scheduler;
 pub mod state;
 pub mod traits;
+pub mod util;
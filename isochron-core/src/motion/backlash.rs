@@ -0,0 +1,268 @@
+//! Backlash compensation for position-controlled axes (z, x)
+//!
+//! Belts and leadscrews have play: after a reversal the motor turns a few
+//! steps before the axis follows, so a position reached from one side
+//! differs from the same position reached from the other. Compensation
+//! adds those steps to every move that reverses direction, so the axis
+//! lands on the commanded position whichever way it arrives.
+//!
+//! Positions here are in the axis's own steps; the extra steps taking up
+//! the play are motor steps only and never change the axis position.
+
+use crate::config::StepperHwConfig;
+
+/// Motor move for a planned axis move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BacklashMove {
+    /// Motor steps to issue, including any backlash take-up
+    pub steps: u32,
+    /// Step in the forward (positive) direction
+    pub forward: bool,
+}
+
+/// Backlash compensation for one axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Backlash {
+    /// Play taken up on a reversal (steps)
+    steps: u16,
+    /// Direction of the last move (None = unknown)
+    last_forward: Option<bool>,
+}
+
+impl Backlash {
+    /// Create a compensator taking up `steps` of play
+    ///
+    /// The first move is not compensated, since the side the play sits
+    /// on is unknown until the axis has moved.
+    pub const fn new(steps: u16) -> Self {
+        Self {
+            steps,
+            last_forward: None,
+        }
+    }
+
+    /// Build the compensator from a stepper configuration
+    pub fn from_stepper(config: &StepperHwConfig) -> Self {
+        Self::new(config.backlash_steps)
+    }
+
+    /// Record the direction the axis last moved in, e.g. after homing
+    pub fn set_direction(&mut self, forward: bool) {
+        self.last_forward = Some(forward);
+    }
+
+    /// Plan a move from `from_steps` to `to_steps`
+    ///
+    /// Returns None when the axis is already there.
+    pub fn plan(&mut self, from_steps: i64, to_steps: i64) -> Option<BacklashMove> {
+        if from_steps == to_steps {
+            return None;
+        }
+        let forward = to_steps > from_steps;
+        let distance = from_steps.abs_diff(to_steps).min(u32::MAX as u64) as u32;
+        let take_up = self.take_up(forward);
+        Some(BacklashMove {
+            steps: distance.saturating_add(take_up as u32),
+            forward,
+        })
+    }
+
+    /// Steps of play to take up before a move in the `forward` direction
+    ///
+    /// For moves that run until told to stop rather than to a planned
+    /// position. Records the direction like [`Backlash::plan`].
+    pub fn take_up(&mut self, forward: bool) -> u16 {
+        let reversed = self.last_forward.is_some_and(|last| last != forward);
+        self.last_forward = Some(forward);
+        if reversed {
+            self.steps
+        } else {
+            0
+        }
+    }
+}
+
+/// Guided backlash measurement
+///
+/// The axis is first moved forward to take up the play on that side.
+/// It then reverses in small increments while the user watches it; the
+/// first increment the user sees it move on marks the end of the play.
+/// The steps reversed before that are the backlash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BacklashCalibration {
+    /// Steps reversed per increment
+    increment: u16,
+    /// Increments reversed without the axis moving
+    still: u16,
+    /// Give up after this many increments
+    max_increments: u16,
+}
+
+impl BacklashCalibration {
+    /// Create a calibration reversing `increment` steps at a time, for at
+    /// most `max_steps` in total
+    pub fn new(increment: u16, max_steps: u16) -> Self {
+        let increment = increment.max(1);
+        Self {
+            increment,
+            still: 0,
+            max_increments: max_steps / increment,
+        }
+    }
+
+    /// Steps to move forward before measuring, taking up the play
+    pub fn preload_steps(&self) -> u32 {
+        self.max_increments as u32 * self.increment as u32
+    }
+
+    /// Steps to reverse for the next increment
+    pub fn increment(&self) -> u16 {
+        self.increment
+    }
+
+    /// Report whether the axis visibly moved on the last increment
+    ///
+    /// Returns the measured backlash, to within one increment, once it
+    /// moved, or the full range if it never did.
+    pub fn observe(&mut self, moved: bool) -> Option<u16> {
+        if !moved {
+            self.still += 1;
+            if self.still < self.max_increments {
+                return None;
+            }
+        }
+        Some(self.still.saturating_mul(self.increment))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Axis with `play` steps of backlash driven by a motor
+    struct SlackAxis {
+        play: i64,
+        motor: i64,
+        position: i64,
+    }
+
+    impl SlackAxis {
+        /// Starts with the play taken up in the forward direction
+        fn new(play: i64) -> Self {
+            Self {
+                play,
+                motor: 0,
+                position: 0,
+            }
+        }
+
+        fn step(&mut self, mv: BacklashMove) {
+            let delta = if mv.forward { 1 } else { -1 };
+            for _ in 0..mv.steps {
+                self.motor += delta;
+                // The axis follows once the motor is past the play
+                if self.motor > self.position {
+                    self.position = self.motor;
+                } else if self.motor < self.position - self.play {
+                    self.position = self.motor + self.play;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reversal_adds_backlash() {
+        let mut backlash = Backlash::new(12);
+        backlash.set_direction(true);
+
+        let mv = backlash.plan(1000, 400).unwrap();
+        assert_eq!(
+            mv,
+            BacklashMove {
+                steps: 612,
+                forward: false
+            }
+        );
+
+        // Reversing back again takes up the play on the other side
+        assert_eq!(backlash.plan(400, 900).unwrap().steps, 512);
+    }
+
+    #[test]
+    fn test_same_direction_not_compensated() {
+        let mut backlash = Backlash::new(12);
+        backlash.set_direction(true);
+        assert_eq!(backlash.plan(0, 500).unwrap().steps, 500);
+        assert_eq!(backlash.plan(500, 800).unwrap().steps, 300);
+        assert_eq!(backlash.plan(800, 800), None);
+
+        // Unknown direction: nothing to take up yet
+        let mut backlash = Backlash::new(12);
+        assert_eq!(backlash.plan(800, 0).unwrap().steps, 800);
+    }
+
+    #[test]
+    fn test_take_up_on_reversal() {
+        let mut backlash = Backlash::new(12);
+        assert_eq!(backlash.take_up(false), 0);
+        assert_eq!(backlash.take_up(false), 0);
+        assert_eq!(backlash.take_up(true), 12);
+        // Shares the direction with planned moves
+        assert_eq!(backlash.plan(100, 0).unwrap().steps, 112);
+        assert_eq!(backlash.take_up(false), 0);
+    }
+
+    #[test]
+    fn test_compensation_lands_on_target() {
+        let mut axis = SlackAxis::new(12);
+        let mut backlash = Backlash::new(12);
+        backlash.set_direction(true);
+
+        let mut position = 0;
+        for target in [800, 300, 300, 650, 100, 900, 20] {
+            if let Some(mv) = backlash.plan(position, target) {
+                axis.step(mv);
+            }
+            position = target;
+            assert_eq!(axis.position, target);
+        }
+
+        // Without compensation the axis falls short after a reversal
+        let mut axis = SlackAxis::new(12);
+        let mut plain = Backlash::new(0);
+        axis.step(plain.plan(0, 800).unwrap());
+        axis.step(plain.plan(800, 300).unwrap());
+        assert_eq!(axis.position, 312);
+    }
+
+    #[test]
+    fn test_guided_calibration() {
+        let mut axis = SlackAxis::new(12);
+        let mut cal = BacklashCalibration::new(4, 100);
+        axis.step(BacklashMove {
+            steps: cal.preload_steps(),
+            forward: true,
+        });
+
+        let result = loop {
+            let before = axis.position;
+            axis.step(BacklashMove {
+                steps: cal.increment() as u32,
+                forward: false,
+            });
+            if let Some(steps) = cal.observe(axis.position != before) {
+                break steps;
+            }
+        };
+        assert_eq!(result, 12);
+
+        // An axis that never moves stops at the limit
+        let mut cal = BacklashCalibration::new(10, 30);
+        assert_eq!(cal.observe(false), None);
+        assert_eq!(cal.observe(false), None);
+        assert_eq!(cal.observe(false), Some(30));
+    }
+}
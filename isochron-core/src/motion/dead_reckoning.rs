@@ -0,0 +1,276 @@
+//! Dead-reckoned position for open-loop steppers
+//!
+//! Without an encoder, the best position estimate is the net number of
+//! steps commanded since the axis was last homed. Missed steps make the
+//! estimate drift, so it is only good enough to check that the basket is
+//! roughly where a move expects it, and is re-zeroed on every home.
+
+use crate::config::StepperHwConfig;
+use crate::traits::PositionStatus;
+
+/// Microseconds per second, the unit of the rate integration remainder
+const US_PER_S: i64 = 1_000_000;
+
+/// Conversion between motor steps and axis units (mm)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StepScale {
+    /// Steps per rotation of the output (full steps × microsteps × gear numerator)
+    steps: u64,
+    /// Distance per rotation of the output in µm (rotation distance × gear denominator)
+    distance_um: u64,
+}
+
+impl StepScale {
+    /// Create a scale of `steps` per `distance_um` µm
+    ///
+    /// Both values are clamped to at least 1.
+    pub const fn new(steps: u64, distance_um: u64) -> Self {
+        Self {
+            steps: if steps == 0 { 1 } else { steps },
+            distance_um: if distance_um == 0 { 1 } else { distance_um },
+        }
+    }
+
+    /// Build the scale from a stepper configuration
+    pub fn from_stepper(config: &StepperHwConfig) -> Self {
+        let steps = config.full_steps_per_rotation as u64
+            * config.microsteps.max(1) as u64
+            * config.gear_ratio_num.max(1) as u64;
+        let distance_um = config.rotation_distance_um as u64 * config.gear_ratio_den.max(1) as u64;
+        Self::new(steps, distance_um)
+    }
+
+    /// Convert steps to mm, rounded to the nearest mm
+    pub fn steps_to_mm(&self, steps: i64) -> i32 {
+        let scaled = steps as i128 * self.distance_um as i128;
+        let per_mm = self.steps as i128 * 1000;
+        let half = per_mm / 2;
+        let rounded = if scaled >= 0 {
+            (scaled + half) / per_mm
+        } else {
+            (scaled - half) / per_mm
+        };
+        rounded.clamp(i32::MIN as i128, i32::MAX as i128) as i32
+    }
+
+    /// Convert mm to steps
+    pub fn mm_to_steps(&self, mm: i32) -> i64 {
+        let steps = mm as i128 * 1000 * self.steps as i128 / self.distance_um as i128;
+        steps.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+}
+
+/// Forward steps that turn a rotary output to `angle_deg`
+///
+/// `position_steps` is the current net step count, where 0 is angle 0.
+/// Always turns forward by less than one revolution, and returns 0 when
+/// the output is already at the angle.
+pub fn steps_to_angle(position_steps: i64, steps_per_rev: u32, angle_deg: u16) -> u32 {
+    let steps_per_rev = steps_per_rev.max(1) as i64;
+    let target = (angle_deg % 360) as i64 * steps_per_rev / 360;
+    let current = position_steps.rem_euclid(steps_per_rev);
+    (target - current).rem_euclid(steps_per_rev) as u32
+}
+
+/// Net commanded step count for one axis
+#[derive(Debug, Clone, Default)]
+pub struct DeadReckoning {
+    /// Net steps, positive in the forward direction
+    steps: i64,
+    /// Partial step carried between rate updates (step·µs)
+    remainder: i64,
+    /// Position has been referenced by a home
+    homed: bool,
+}
+
+impl DeadReckoning {
+    /// Create an unhomed estimate at step zero
+    pub const fn new() -> Self {
+        Self {
+            steps: 0,
+            remainder: 0,
+            homed: false,
+        }
+    }
+
+    /// Record a discrete number of commanded steps
+    pub fn record_steps(&mut self, steps: u32, forward: bool) {
+        let steps = steps as i64;
+        self.steps += if forward { steps } else { -steps };
+    }
+
+    /// Record stepping at `freq_hz` for `elapsed_us`
+    ///
+    /// For step generators that run at a rate rather than counting pulses.
+    /// Fractions of a step are carried into the next update.
+    pub fn record_run(&mut self, freq_hz: u32, elapsed_us: u64, forward: bool) {
+        let step_us = (freq_hz as i64).saturating_mul(elapsed_us.min(i64::MAX as u64) as i64);
+        let total = self.remainder + if forward { step_us } else { -step_us };
+        self.steps += total / US_PER_S;
+        self.remainder = total % US_PER_S;
+    }
+
+    /// Net commanded steps since the last home
+    pub fn steps(&self) -> i64 {
+        self.steps
+    }
+
+    /// Check if the position has been referenced by a home
+    pub fn is_homed(&self) -> bool {
+        self.homed
+    }
+
+    /// Reference the estimate after homing
+    ///
+    /// `position_steps` is the endstop position in steps (usually 0).
+    pub fn rehome(&mut self, position_steps: i64) {
+        self.steps = position_steps;
+        self.remainder = 0;
+        self.homed = true;
+    }
+
+    /// Forget the reference, e.g. after the driver was disabled
+    pub fn invalidate(&mut self) {
+        self.homed = false;
+    }
+
+    /// Position estimate in axis units
+    pub fn status(&self, scale: &StepScale) -> PositionStatus {
+        PositionStatus {
+            position: scale.steps_to_mm(self.steps),
+            homed: self.homed,
+            estimated: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// T8 leadscrew: 200 full steps × 16 microsteps per 8 mm
+    fn t8() -> StepScale {
+        StepScale::new(3200, 8000)
+    }
+
+    #[test]
+    fn test_scale_from_stepper() {
+        let config = StepperHwConfig {
+            full_steps_per_rotation: 200,
+            microsteps: 16,
+            rotation_distance_um: 8000,
+            gear_ratio_num: 3,
+            gear_ratio_den: 1,
+            ..Default::default()
+        };
+        let scale = StepScale::from_stepper(&config);
+        assert_eq!(scale.mm_to_steps(8), 9600);
+        assert_eq!(scale.steps_to_mm(9600), 8);
+
+        // The largest distance the config accepts doesn't overflow
+        let config = StepperHwConfig {
+            rotation_distance_um: u32::MAX,
+            gear_ratio_den: u8::MAX,
+            ..config
+        };
+        let scale = StepScale::from_stepper(&config);
+        assert_eq!(scale.mm_to_steps(1_000_000), 8);
+        assert_eq!(scale.steps_to_mm(i64::MAX), i32::MAX);
+    }
+
+    #[test]
+    fn test_steps_follow_direction() {
+        let mut pos = DeadReckoning::new();
+        pos.record_steps(4000, true);
+        assert_eq!(pos.steps(), 4000);
+        assert_eq!(pos.status(&t8()).position, 10);
+
+        pos.record_steps(1600, false);
+        assert_eq!(pos.steps(), 2400);
+        assert_eq!(pos.status(&t8()).position, 6);
+
+        pos.record_steps(4000, false);
+        assert_eq!(pos.steps(), -1600);
+        assert_eq!(pos.status(&t8()).position, -4);
+    }
+
+    #[test]
+    fn test_run_integrates_rate() {
+        let mut pos = DeadReckoning::new();
+        // 3200 Hz for 2.5 s forward = 8000 steps = 20 mm
+        pos.record_run(3200, 2_500_000, true);
+        assert_eq!(pos.steps(), 8000);
+        assert_eq!(pos.status(&t8()).position, 20);
+
+        // Reverse for 1 s
+        pos.record_run(3200, 1_000_000, false);
+        assert_eq!(pos.steps(), 4800);
+    }
+
+    #[test]
+    fn test_run_carries_partial_steps() {
+        let mut pos = DeadReckoning::new();
+        // 1 kHz in 500 µs updates: half a step each time
+        for _ in 0..10 {
+            pos.record_run(1000, 500, true);
+        }
+        assert_eq!(pos.steps(), 5);
+
+        for _ in 0..10 {
+            pos.record_run(1000, 500, false);
+        }
+        assert_eq!(pos.steps(), 0);
+    }
+
+    #[test]
+    fn test_rehome_zeroes_estimate() {
+        let mut pos = DeadReckoning::new();
+        assert!(!pos.status(&t8()).homed);
+
+        pos.record_steps(12_345, true);
+        pos.record_run(1000, 700, true);
+        pos.rehome(0);
+
+        let status = pos.status(&t8());
+        assert_eq!(pos.steps(), 0);
+        assert_eq!(status.position, 0);
+        assert!(status.homed);
+        assert!(status.estimated);
+
+        // No partial step survives the home
+        pos.record_run(1000, 500, true);
+        assert_eq!(pos.steps(), 0);
+    }
+
+    #[test]
+    fn test_steps_to_angle() {
+        // 3200 steps per revolution: 90° = 800 steps
+        assert_eq!(steps_to_angle(0, 3200, 90), 800);
+        assert_eq!(steps_to_angle(800, 3200, 90), 0);
+
+        // Past the angle: turn on round rather than back
+        assert_eq!(steps_to_angle(1000, 3200, 90), 3000);
+
+        // Several turns in either direction
+        assert_eq!(steps_to_angle(3 * 3200 + 400, 3200, 90), 400);
+        assert_eq!(steps_to_angle(-400, 3200, 0), 400);
+        assert_eq!(steps_to_angle(0, 3200, 450), 800);
+    }
+
+    #[test]
+    fn test_invalidate_drops_reference() {
+        let mut pos = DeadReckoning::new();
+        pos.rehome(0);
+        pos.invalidate();
+        assert!(!pos.is_homed());
+    }
+
+    #[test]
+    fn test_rounding_is_symmetric() {
+        let scale = t8();
+        assert_eq!(scale.steps_to_mm(1000), 3); // 2.5 mm
+        assert_eq!(scale.steps_to_mm(-1000), -3);
+        assert_eq!(scale.steps_to_mm(799), 2); // 1.9975 mm
+    }
+}
@@ -0,0 +1,727 @@
+//! Endstop homing for position-controlled axes (z, x)
+//!
+//! Klipper-style two-pass homing: seek the endstop at homing speed,
+//! retract, then approach again at half speed for a repeatable trigger.
+//! The endstop is always read through its [`PinConfig`], so both
+//! normally-open and normally-closed switches work.
+//!
+//! A cold machine can move slowly enough to miss a tight limit on its
+//! first attempt, so a miss (endstop not found, or the attempt timing
+//! out) gets one grace retry: the axis backs off and approaches again
+//! with a fresh timeout. A second miss fails homing.
+
+use crate::config::{HomingOrder, PinConfig, StepperHwConfig};
+use crate::util::CancelToken;
+
+/// Default homing speed in mm/s
+pub const DEFAULT_HOMING_SPEED: u16 = 5;
+
+/// Default retract distance after first contact in mm
+pub const DEFAULT_HOMING_RETRACT_DIST: u16 = 5;
+
+/// Default time allowed for one homing attempt in s
+pub const DEFAULT_HOMING_TIMEOUT_S: u16 = 30;
+
+/// Extra travel allowed beyond the axis length before giving up (mm)
+const HOMING_TRAVEL_MARGIN_MM: u32 = 10;
+
+/// Endstop switch interpreted through its pin configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Endstop {
+    pin: PinConfig,
+}
+
+impl Endstop {
+    /// Create an endstop from its pin configuration
+    pub const fn new(pin: PinConfig) -> Self {
+        Self { pin }
+    }
+
+    /// Check whether the endstop is triggered at the given electrical level
+    ///
+    /// A normal endstop triggers when the pin reads high; an inverted
+    /// (`!`) endstop triggers when it reads low.
+    pub const fn is_triggered(&self, level_high: bool) -> bool {
+        self.pin.is_active(level_high)
+    }
+
+    /// Whether the input should be configured with an internal pull-up
+    pub const fn pull_up(&self) -> bool {
+        self.pin.pull_up
+    }
+
+    /// GPIO pin number
+    pub const fn pin(&self) -> u8 {
+        self.pin.pin
+    }
+}
+
+/// Homing parameters for a single axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HomingConfig {
+    /// Position assigned to the axis once homed (mm)
+    pub position_endstop: i32,
+    /// Home in the positive direction
+    pub positive_dir: bool,
+    /// First-pass homing speed in mm/s
+    pub speed: u16,
+    /// Retract distance after first contact in mm (0 = single pass)
+    pub retract_dist: u16,
+    /// Maximum travel while seeking before homing fails (mm)
+    pub max_travel: u32,
+    /// Time allowed for each attempt in s (0 = no limit)
+    pub timeout_s: u16,
+}
+
+impl HomingConfig {
+    /// Build homing parameters from a stepper configuration
+    ///
+    /// Returns `None` if the stepper has no endstop or is missing
+    /// `position_endstop` / `position_max`.
+    pub fn from_stepper(config: &StepperHwConfig) -> Option<(Self, Endstop)> {
+        let pin = config.endstop_pin?;
+        let position_endstop = config.position_endstop?;
+        let position_max = config.position_max?;
+        let position_min = config.position_min;
+
+        // Auto-detect direction: home toward whichever limit the endstop is nearer
+        let positive_dir = config.homing_positive_dir.unwrap_or_else(|| {
+            position_endstop.saturating_sub(position_min)
+                > position_max.saturating_sub(position_endstop)
+        });
+
+        let axis_len = position_max.saturating_sub(position_min).unsigned_abs();
+
+        Some((
+            Self {
+                position_endstop,
+                positive_dir,
+                speed: config.homing_speed.unwrap_or(DEFAULT_HOMING_SPEED),
+                retract_dist: config
+                    .homing_retract_dist
+                    .unwrap_or(DEFAULT_HOMING_RETRACT_DIST),
+                max_travel: axis_len + HOMING_TRAVEL_MARGIN_MM,
+                timeout_s: config.homing_timeout_s.unwrap_or(DEFAULT_HOMING_TIMEOUT_S),
+            },
+            Endstop::new(pin),
+        ))
+    }
+}
+
+/// Reason homing failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HomingError {
+    /// Travelled the full axis without the endstop triggering
+    EndstopNotFound,
+    /// Endstop still triggered after retracting
+    EndstopStuck,
+    /// Attempt took longer than the configured timeout
+    Timeout,
+}
+
+/// Homing phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HomingPhase {
+    /// Not homing
+    Idle,
+    /// First pass toward the endstop
+    Seeking,
+    /// Backing off the endstop
+    Retracting,
+    /// Second, slower pass toward the endstop
+    Reseeking,
+    /// Moving away from the endstop before retrying after a miss
+    BackingOff,
+    /// Endstop found, position is valid
+    Homed,
+    /// Homing aborted
+    Failed(HomingError),
+}
+
+/// Motion requested by the homing sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HomingMove {
+    /// Motor should be stopped
+    Stop,
+    /// Move continuously in the given direction
+    Move {
+        /// Move in the positive direction
+        positive: bool,
+        /// Speed in mm/s
+        speed: u16,
+    },
+}
+
+/// Homing sequence state machine
+///
+/// The caller drives the motor according to [`Homing::motion`] and calls
+/// [`Homing::update`] with the raw endstop level and the distance moved
+/// since the previous update.
+#[derive(Debug, Clone)]
+pub struct Homing {
+    config: HomingConfig,
+    endstop: Endstop,
+    phase: HomingPhase,
+    /// Distance travelled in the current phase (µm)
+    travelled_um: u32,
+    /// Time spent on the current attempt (ms)
+    attempt_ms: u32,
+    /// The grace retry has been used
+    retried: bool,
+}
+
+impl Homing {
+    /// Create an idle homing sequence
+    pub fn new(config: HomingConfig, endstop: Endstop) -> Self {
+        Self {
+            config,
+            endstop,
+            phase: HomingPhase::Idle,
+            travelled_um: 0,
+            attempt_ms: 0,
+            retried: false,
+        }
+    }
+
+    /// Get the current phase
+    pub fn phase(&self) -> HomingPhase {
+        self.phase
+    }
+
+    /// Get the endstop this sequence reads
+    pub fn endstop(&self) -> Endstop {
+        self.endstop
+    }
+
+    /// Check if homing completed successfully
+    pub fn is_homed(&self) -> bool {
+        self.phase == HomingPhase::Homed
+    }
+
+    /// Position of the axis once homed
+    pub fn homed_position(&self) -> Option<i32> {
+        self.is_homed().then_some(self.config.position_endstop)
+    }
+
+    /// Start homing
+    ///
+    /// If the endstop is already triggered the axis backs off first.
+    pub fn start(&mut self, level_high: bool) {
+        self.attempt_ms = 0;
+        self.retried = false;
+        let phase = if self.endstop.is_triggered(level_high) {
+            HomingPhase::Retracting
+        } else {
+            HomingPhase::Seeking
+        };
+        self.enter(phase);
+    }
+
+    /// Abort homing and stop the motor
+    pub fn abort(&mut self) {
+        self.enter(HomingPhase::Idle);
+    }
+
+    /// Abort if `token` has been cancelled
+    ///
+    /// Call once per update while homing. Returns `true` if the sequence
+    /// was stopped; the motor must then be halted via [`Homing::motion`].
+    pub fn check_cancel(&mut self, token: &CancelToken) -> bool {
+        if self.is_active() && token.is_cancelled() {
+            self.abort();
+            return true;
+        }
+        false
+    }
+
+    /// Motion the caller should apply for the current phase
+    pub fn motion(&self) -> HomingMove {
+        let toward = self.config.positive_dir;
+        match self.phase {
+            HomingPhase::Seeking => HomingMove::Move {
+                positive: toward,
+                speed: self.config.speed,
+            },
+            HomingPhase::Retracting | HomingPhase::BackingOff => HomingMove::Move {
+                positive: !toward,
+                speed: self.config.speed,
+            },
+            HomingPhase::Reseeking => HomingMove::Move {
+                positive: toward,
+                speed: (self.config.speed / 2).max(1),
+            },
+            HomingPhase::Idle | HomingPhase::Homed | HomingPhase::Failed(_) => HomingMove::Stop,
+        }
+    }
+
+    /// Advance the sequence
+    ///
+    /// # Arguments
+    /// - `level_high`: Raw electrical level of the endstop pin
+    /// - `moved_um`: Distance moved since the last update in µm
+    ///
+    /// # Returns
+    /// The phase after the update
+    pub fn update(&mut self, level_high: bool, moved_um: u32) -> HomingPhase {
+        let triggered = self.endstop.is_triggered(level_high);
+        self.travelled_um = self.travelled_um.saturating_add(moved_um);
+        let retract_um = self.config.retract_dist as u32 * 1000;
+
+        match self.phase {
+            HomingPhase::Seeking => {
+                if triggered {
+                    if self.config.retract_dist == 0 {
+                        self.enter(HomingPhase::Homed);
+                    } else {
+                        self.enter(HomingPhase::Retracting);
+                    }
+                } else if self.travelled_um > self.config.max_travel * 1000 {
+                    self.miss(HomingError::EndstopNotFound);
+                }
+            }
+            HomingPhase::Retracting => {
+                if self.travelled_um >= retract_um {
+                    if triggered {
+                        self.enter(HomingPhase::Failed(HomingError::EndstopStuck));
+                    } else {
+                        self.enter(HomingPhase::Reseeking);
+                    }
+                }
+            }
+            HomingPhase::Reseeking => {
+                if triggered {
+                    self.enter(HomingPhase::Homed);
+                } else if self.travelled_um > retract_um * 2 {
+                    self.miss(HomingError::EndstopNotFound);
+                }
+            }
+            HomingPhase::BackingOff => {
+                if self.travelled_um >= self.backoff_um() {
+                    self.enter(HomingPhase::Seeking);
+                }
+            }
+            HomingPhase::Idle | HomingPhase::Homed | HomingPhase::Failed(_) => {}
+        }
+
+        self.phase
+    }
+
+    /// Count time spent homing
+    ///
+    /// Call periodically while homing with the time since the last call.
+    /// An attempt that runs past the configured timeout counts as a miss.
+    ///
+    /// # Returns
+    /// The phase after the update
+    pub fn update_time(&mut self, elapsed_ms: u32) -> HomingPhase {
+        if !self.is_active() || self.config.timeout_s == 0 {
+            return self.phase;
+        }
+        self.attempt_ms = self.attempt_ms.saturating_add(elapsed_ms);
+        if self.attempt_ms > self.config.timeout_s as u32 * 1000 {
+            self.miss(HomingError::Timeout);
+        }
+        self.phase
+    }
+
+    /// Whether the axis is moving under the sequence's control
+    fn is_active(&self) -> bool {
+        matches!(
+            self.phase,
+            HomingPhase::Seeking
+                | HomingPhase::Retracting
+                | HomingPhase::Reseeking
+                | HomingPhase::BackingOff
+        )
+    }
+
+    /// Distance to back off before the retry (µm)
+    fn backoff_um(&self) -> u32 {
+        let dist = match self.config.retract_dist {
+            0 => DEFAULT_HOMING_RETRACT_DIST,
+            dist => dist,
+        };
+        dist as u32 * 1000
+    }
+
+    /// Retry after the first miss, fail on the second
+    fn miss(&mut self, error: HomingError) {
+        if self.retried {
+            self.enter(HomingPhase::Failed(error));
+        } else {
+            self.retried = true;
+            self.attempt_ms = 0;
+            self.enter(HomingPhase::BackingOff);
+        }
+    }
+
+    fn enter(&mut self, phase: HomingPhase) {
+        self.phase = phase;
+        self.travelled_um = 0;
+    }
+}
+
+/// Position-controlled basket axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Axis {
+    /// Lift
+    Z,
+    /// Jar selection
+    X,
+}
+
+/// Homes the machine's axes in the configured [`HomingOrder`]
+///
+/// Tracks which axes still need homing and which of them may home now;
+/// the per-axis moves are left to each axis' [`Homing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HomingSequence {
+    order: HomingOrder,
+    /// Z still has to be homed
+    z_pending: bool,
+    /// X still has to be homed
+    x_pending: bool,
+}
+
+impl HomingSequence {
+    /// Start homing the axes the machine has
+    pub const fn new(order: HomingOrder, has_z: bool, has_x: bool) -> Self {
+        Self {
+            order,
+            z_pending: has_z,
+            x_pending: has_x,
+        }
+    }
+
+    /// Check whether `axis` should be homing now
+    pub fn is_active(&self, axis: Axis) -> bool {
+        match axis {
+            Axis::Z => self.z_pending && (self.order != HomingOrder::XThenZ || !self.x_pending),
+            Axis::X => self.x_pending && (self.order != HomingOrder::ZThenX || !self.z_pending),
+        }
+    }
+
+    /// Record that `axis` finished homing
+    ///
+    /// Ignored unless the axis was active, so an early report can't skip
+    /// ahead of the configured order.
+    pub fn axis_homed(&mut self, axis: Axis) {
+        if !self.is_active(axis) {
+            return;
+        }
+        match axis {
+            Axis::Z => self.z_pending = false,
+            Axis::X => self.x_pending = false,
+        }
+    }
+
+    /// Check whether every axis has been homed
+    pub fn is_complete(&self) -> bool {
+        !self.z_pending && !self.x_pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> HomingConfig {
+        HomingConfig {
+            position_endstop: 0,
+            positive_dir: false,
+            speed: 10,
+            retract_dist: 5,
+            max_travel: 100,
+            timeout_s: 20,
+        }
+    }
+
+    /// Run a full two-pass homing cycle with the given "pressed" level
+    fn home_with(endstop: Endstop, pressed: bool) -> Homing {
+        let released = !pressed;
+        let mut homing = Homing::new(config(), endstop);
+        homing.start(released);
+        assert_eq!(homing.phase(), HomingPhase::Seeking);
+
+        // Travel without contact
+        assert_eq!(homing.update(released, 20_000), HomingPhase::Seeking);
+        // First contact
+        assert_eq!(homing.update(pressed, 1_000), HomingPhase::Retracting);
+        // Back off the switch
+        assert_eq!(homing.update(released, 5_000), HomingPhase::Reseeking);
+        // Slow approach
+        assert_eq!(homing.update(released, 2_000), HomingPhase::Reseeking);
+        assert_eq!(homing.update(pressed, 2_000), HomingPhase::Homed);
+        homing
+    }
+
+    #[test]
+    fn test_endstop_inverted_is_electrical_opposite() {
+        let normal = Endstop::new(PinConfig::new(4));
+        let inverted = Endstop::new(PinConfig::inverted(4));
+
+        for level in [false, true] {
+            assert_eq!(normal.is_triggered(level), !inverted.is_triggered(level));
+        }
+        assert!(normal.is_triggered(true));
+        assert!(inverted.is_triggered(false));
+    }
+
+    #[test]
+    fn test_endstop_pull_up() {
+        let endstop = Endstop::new(PinConfig::with_pullup(4));
+        assert!(endstop.pull_up());
+        assert!(!Endstop::new(PinConfig::new(4)).pull_up());
+    }
+
+    #[test]
+    fn test_homes_on_high_for_normal_endstop() {
+        let homing = home_with(Endstop::new(PinConfig::new(4)), true);
+        assert!(homing.is_homed());
+        assert_eq!(homing.homed_position(), Some(0));
+    }
+
+    #[test]
+    fn test_homes_on_low_for_inverted_endstop() {
+        let homing = home_with(Endstop::new(PinConfig::inverted(4)), false);
+        assert!(homing.is_homed());
+    }
+
+    #[test]
+    fn test_inverted_endstop_ignores_high_level() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::inverted(4)));
+        homing.start(true);
+        assert_eq!(homing.phase(), HomingPhase::Seeking);
+        assert_eq!(homing.update(true, 1_000), HomingPhase::Seeking);
+        assert_eq!(homing.update(false, 1_000), HomingPhase::Retracting);
+    }
+
+    #[test]
+    fn test_start_on_endstop_backs_off() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(true);
+        assert_eq!(homing.phase(), HomingPhase::Retracting);
+        assert_eq!(
+            homing.motion(),
+            HomingMove::Move {
+                positive: true,
+                speed: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_single_pass_without_retract() {
+        let mut cfg = config();
+        cfg.retract_dist = 0;
+        let mut homing = Homing::new(cfg, Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        assert_eq!(homing.update(true, 1_000), HomingPhase::Homed);
+        assert_eq!(homing.motion(), HomingMove::Stop);
+    }
+
+    #[test]
+    fn test_endstop_not_found() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        homing.update(false, 100_000);
+        assert_eq!(homing.phase(), HomingPhase::Seeking);
+
+        // The first miss backs off for the grace retry
+        assert_eq!(homing.update(false, 1_000), HomingPhase::BackingOff);
+        assert_eq!(homing.update(false, 5_000), HomingPhase::Seeking);
+        homing.update(false, 100_000);
+        assert_eq!(
+            homing.update(false, 1_000),
+            HomingPhase::Failed(HomingError::EndstopNotFound)
+        );
+    }
+
+    #[test]
+    fn test_homes_within_timeout() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+
+        // A slow first move, just inside the 20s limit
+        for _ in 0..19 {
+            assert_eq!(homing.update_time(1_000), HomingPhase::Seeking);
+            homing.update(false, 1_000);
+        }
+        homing.update(true, 500);
+        homing.update(false, 5_000);
+        assert_eq!(homing.update_time(1_000), HomingPhase::Reseeking);
+        assert_eq!(homing.update(true, 1_000), HomingPhase::Homed);
+
+        // Time no longer counts once homed
+        assert_eq!(homing.update_time(60_000), HomingPhase::Homed);
+    }
+
+    #[test]
+    fn test_first_timeout_retries_once() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        homing.update(false, 10_000);
+        assert_eq!(homing.update_time(20_001), HomingPhase::BackingOff);
+
+        // Backs away from the endstop, then approaches again at full speed
+        assert_eq!(
+            homing.motion(),
+            HomingMove::Move {
+                positive: true,
+                speed: 10
+            }
+        );
+        assert_eq!(homing.update(false, 4_000), HomingPhase::BackingOff);
+        assert_eq!(homing.update(false, 1_000), HomingPhase::Seeking);
+        assert_eq!(
+            homing.motion(),
+            HomingMove::Move {
+                positive: false,
+                speed: 10
+            }
+        );
+
+        // The retry gets a fresh timeout and can still home
+        assert_eq!(homing.update_time(15_000), HomingPhase::Seeking);
+        homing.update(true, 1_000);
+        homing.update(false, 5_000);
+        assert_eq!(homing.update(true, 1_000), HomingPhase::Homed);
+    }
+
+    #[test]
+    fn test_second_timeout_fails() {
+        let mut cfg = config();
+        cfg.timeout_s = 5;
+        let mut homing = Homing::new(cfg, Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        assert_eq!(homing.update_time(5_001), HomingPhase::BackingOff);
+        homing.update(false, 5_000);
+
+        // The configured timeout holds for the retry, to the millisecond
+        assert_eq!(homing.update_time(5_000), HomingPhase::Seeking);
+        assert_eq!(
+            homing.update_time(1),
+            HomingPhase::Failed(HomingError::Timeout)
+        );
+        assert_eq!(homing.motion(), HomingMove::Stop);
+
+        // A new start gets the grace retry back
+        homing.start(false);
+        assert_eq!(homing.update_time(5_001), HomingPhase::BackingOff);
+    }
+
+    #[test]
+    fn test_no_timeout_when_zero() {
+        let mut cfg = config();
+        cfg.timeout_s = 0;
+        let mut homing = Homing::new(cfg, Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        assert_eq!(homing.update_time(u32::MAX), HomingPhase::Seeking);
+    }
+
+    #[test]
+    fn test_cancel_stops_homing() {
+        let token = CancelToken::new();
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        homing.update(false, 10_000);
+        assert!(!homing.check_cancel(&token));
+        assert_eq!(homing.phase(), HomingPhase::Seeking);
+
+        token.cancel();
+        assert!(homing.check_cancel(&token));
+        assert_eq!(homing.phase(), HomingPhase::Idle);
+        assert_eq!(homing.motion(), HomingMove::Stop);
+    }
+
+    #[test]
+    fn test_cancel_after_homed_is_ignored() {
+        let token = CancelToken::new();
+        let mut homing = home_with(Endstop::new(PinConfig::new(4)), true);
+        token.cancel();
+        assert!(!homing.check_cancel(&token));
+        assert!(homing.is_homed());
+    }
+
+    #[test]
+    fn test_endstop_stuck_after_retract() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(true);
+        assert_eq!(
+            homing.update(true, 5_000),
+            HomingPhase::Failed(HomingError::EndstopStuck)
+        );
+    }
+
+    #[test]
+    fn test_second_pass_is_slower() {
+        let mut homing = Homing::new(config(), Endstop::new(PinConfig::new(4)));
+        homing.start(false);
+        homing.update(true, 0);
+        homing.update(false, 5_000);
+        assert_eq!(
+            homing.motion(),
+            HomingMove::Move {
+                positive: false,
+                speed: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_from_stepper() {
+        let mut stepper = StepperHwConfig {
+            endstop_pin: Some(PinConfig::inverted(4)),
+            position_min: 0,
+            position_max: Some(150),
+            position_endstop: Some(150),
+            ..Default::default()
+        };
+        let (cfg, endstop) = HomingConfig::from_stepper(&stepper).unwrap();
+        assert!(cfg.positive_dir);
+        assert_eq!(cfg.speed, DEFAULT_HOMING_SPEED);
+        assert_eq!(cfg.retract_dist, DEFAULT_HOMING_RETRACT_DIST);
+        assert_eq!(cfg.timeout_s, DEFAULT_HOMING_TIMEOUT_S);
+        assert!(endstop.is_triggered(false));
+
+        stepper.position_endstop = Some(0);
+        stepper.homing_timeout_s = Some(90);
+        let (cfg, _) = HomingConfig::from_stepper(&stepper).unwrap();
+        assert!(!cfg.positive_dir);
+        assert_eq!(cfg.timeout_s, 90);
+
+        stepper.endstop_pin = None;
+        assert!(HomingConfig::from_stepper(&stepper).is_none());
+    }
+
+    #[test]
+    fn test_sequence_single_axis_machine() {
+        // Only the configured axes are waited for
+        let mut seq = HomingSequence::new(HomingOrder::XThenZ, true, false);
+        assert!(seq.is_active(Axis::Z));
+        assert!(!seq.is_active(Axis::X));
+        seq.axis_homed(Axis::Z);
+        assert!(seq.is_complete());
+
+        assert!(HomingSequence::new(HomingOrder::ZThenX, false, false).is_complete());
+    }
+
+    #[test]
+    fn test_sequence_out_of_order_report_ignored() {
+        let mut seq = HomingSequence::new(HomingOrder::ZThenX, true, true);
+        seq.axis_homed(Axis::X);
+        assert!(!seq.is_active(Axis::X));
+        assert!(seq.is_active(Axis::Z));
+
+        seq.axis_homed(Axis::Z);
+        assert!(seq.is_active(Axis::X));
+        assert!(!seq.is_complete());
+    }
+}
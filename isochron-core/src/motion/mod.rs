@@ -1,7 +1,20 @@
 //! Motion planning
 //!
-//! Acceleration and deceleration profiles for smooth motor control.
+//! Trapezoidal and S-curve acceleration for smooth motor control, a
+//! spin ramp profile shared by every motor type, endstop homing,
+//! backlash compensation and dead-reckoned position for
+//! position-controlled axes.
 
+pub mod backlash;
+pub mod dead_reckoning;
+pub mod homing;
 pub mod planner;
+pub mod ramp;
 
-pub use planner::{MotionPlanner, MotionState};
+pub use backlash::{Backlash, BacklashCalibration, BacklashMove};
+pub use dead_reckoning::{steps_to_angle, DeadReckoning, StepScale};
+pub use homing::{
+    Axis, Endstop, Homing, HomingConfig, HomingError, HomingMove, HomingPhase, HomingSequence,
+};
+pub use planner::{AccelProfile, MotionPlanner, MotionState};
+pub use ramp::{RampCurve, RampProfile, SpeedRamp};
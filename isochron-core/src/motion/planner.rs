@@ -1,6 +1,10 @@
 //! Motion planner for acceleration/deceleration profiles
 //!
 //! Provides smooth speed transitions to minimize fluid shock and vortex formation.
+//!
+//! Ramps are trapezoidal by default: constant acceleration that switches
+//! on and off abruptly. An S-curve profile limits jerk instead, easing the
+//! acceleration in and out so the basket starts and settles without a kick.
 
 /// Default acceleration rate in RPM per second
 pub const DEFAULT_ACCEL_RPM_PER_S: u16 = 50;
@@ -8,6 +12,23 @@ pub const DEFAULT_ACCEL_RPM_PER_S: u16 = 50;
 /// Maximum acceleration rate in RPM per second
 pub const MAX_ACCEL_RPM_PER_S: u16 = 100;
 
+/// S-curve speed resolution (µRPM per RPM)
+const URPM_PER_RPM: i64 = 1_000_000;
+
+/// Shape of the planner's speed ramps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccelProfile {
+    /// Constant acceleration, switched on and off at the ends of a ramp
+    #[default]
+    Trapezoidal,
+    /// Jerk-limited acceleration that ramps in and out
+    SCurve {
+        /// Maximum rate of change of acceleration (RPM/s²)
+        jerk_rpm_per_s2: u16,
+    },
+}
+
 /// Current motion state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -36,6 +57,12 @@ pub struct MotionPlanner {
     accel_rpm_per_s: u16,
     /// Current motion state
     state: MotionState,
+    /// Ramp shape
+    profile: AccelProfile,
+    /// Current speed at full resolution (µRPM)
+    speed_urpm: i64,
+    /// Current acceleration (mRPM/s, negative while slowing)
+    accel_mrpm_per_s: i32,
 }
 
 impl Default for MotionPlanner {
@@ -52,6 +79,9 @@ impl MotionPlanner {
             target_rpm: 0,
             accel_rpm_per_s: DEFAULT_ACCEL_RPM_PER_S,
             state: MotionState::Stopped,
+            profile: AccelProfile::Trapezoidal,
+            speed_urpm: 0,
+            accel_mrpm_per_s: 0,
         }
     }
 
@@ -61,10 +91,28 @@ impl MotionPlanner {
             current_rpm_x10: 0,
             target_rpm: 0,
             accel_rpm_per_s: accel_rpm_per_s.min(MAX_ACCEL_RPM_PER_S),
-            state: MotionState::Stopped,
+            ..Self::new()
         }
     }
 
+    /// Switch to an S-curve profile limiting jerk to `jerk_rpm_per_s2`
+    ///
+    /// The acceleration limit still applies. A jerk of 0 keeps the
+    /// trapezoidal profile.
+    pub fn with_jerk_limit(mut self, jerk_rpm_per_s2: u16) -> Self {
+        self.profile = if jerk_rpm_per_s2 == 0 {
+            AccelProfile::Trapezoidal
+        } else {
+            AccelProfile::SCurve { jerk_rpm_per_s2 }
+        };
+        self
+    }
+
+    /// Get the ramp profile
+    pub fn get_profile(&self) -> AccelProfile {
+        self.profile
+    }
+
     /// Set the target RPM
     pub fn set_target(&mut self, rpm: u16) {
         self.target_rpm = rpm;
@@ -81,6 +129,11 @@ impl MotionPlanner {
         (self.current_rpm_x10 / 10) as u16
     }
 
+    /// Get the current acceleration in mRPM/s (negative while slowing)
+    pub fn get_acceleration(&self) -> i32 {
+        self.accel_mrpm_per_s
+    }
+
     /// Get the current motion state
     pub fn get_state(&self) -> MotionState {
         self.state
@@ -106,11 +159,23 @@ impl MotionPlanner {
     /// # Returns
     /// The current RPM after the update
     pub fn update(&mut self, delta_ms: u32) -> u16 {
+        match self.profile {
+            AccelProfile::Trapezoidal => self.update_trapezoidal(delta_ms),
+            AccelProfile::SCurve { jerk_rpm_per_s2 } => {
+                self.update_s_curve(delta_ms, jerk_rpm_per_s2)
+            }
+        }
+        self.update_state();
+        self.get_current()
+    }
+
+    /// Step the speed at constant acceleration
+    fn update_trapezoidal(&mut self, delta_ms: u32) {
         let target_x10 = (self.target_rpm as u32) * 10;
 
         if self.current_rpm_x10 == target_x10 {
-            self.update_state();
-            return self.get_current();
+            self.accel_mrpm_per_s = 0;
+            return;
         }
 
         // Calculate change in RPM*10 for this time step
@@ -130,26 +195,88 @@ impl MotionPlanner {
             self.current_rpm_x10 = self.current_rpm_x10.max(target_x10);
         }
 
-        self.update_state();
-        self.get_current()
+        self.speed_urpm = self.current_rpm_x10 as i64 * (URPM_PER_RPM / 10);
+        self.accel_mrpm_per_s = if self.current_rpm_x10 == target_x10 {
+            0
+        } else if self.current_rpm_x10 < target_x10 {
+            self.accel_rpm_per_s as i32 * 1000
+        } else {
+            -(self.accel_rpm_per_s as i32 * 1000)
+        };
+    }
+
+    /// Step the speed with the acceleration changing at most `jerk_rpm_per_s2`
+    ///
+    /// Each update picks the largest acceleration that can still be eased
+    /// back to zero by the time the speed reaches the target.
+    fn update_s_curve(&mut self, delta_ms: u32, jerk_rpm_per_s2: u16) {
+        let target = self.target_rpm as i64 * URPM_PER_RPM;
+        let diff = target - self.speed_urpm;
+        if diff == 0 && self.accel_mrpm_per_s == 0 {
+            return;
+        }
+
+        // Work toward the target so one set of limits covers both directions
+        let dir = if diff != 0 {
+            diff.signum()
+        } else {
+            -(self.accel_mrpm_per_s as i64).signum()
+        };
+        let remaining = dir * diff; // µRPM
+        let accel = dir * self.accel_mrpm_per_s as i64; // mRPM/s
+        let max_accel = self.accel_rpm_per_s as i64 * 1000;
+        let jerk = jerk_rpm_per_s2 as i64 * 1000; // mRPM/s²
+        let dt = delta_ms as i64;
+        let step = jerk * dt / 1000; // largest acceleration change this update
+
+        // Close enough to land this update without exceeding the jerk limit
+        if accel.abs() <= step && remaining <= (accel.max(0) + step.min(max_accel)) * dt / 2 {
+            self.speed_urpm = target;
+            self.accel_mrpm_per_s = 0;
+            self.current_rpm_x10 = (target / (URPM_PER_RPM / 10)) as u32;
+            return;
+        }
+
+        // Solve (a + a')·dt/2 + a'²/2j = remaining for the next acceleration a'
+        let discriminant = step * step + 8 * jerk * (remaining / 1000) - 4 * accel * step;
+        let landing = if discriminant > 0 {
+            ((discriminant as u64).isqrt() as i64 - step) / 2
+        } else {
+            -max_accel
+        };
+        let next = landing
+            .min(accel + step)
+            .min(max_accel)
+            .max(accel - step)
+            .max(-max_accel);
+
+        self.speed_urpm = target - dir * (remaining - (accel + next) * dt / 2);
+        self.accel_mrpm_per_s = (dir * next) as i32;
+        if self.speed_urpm < 0 {
+            self.speed_urpm = 0;
+            self.accel_mrpm_per_s = 0;
+        }
+        self.current_rpm_x10 = (self.speed_urpm / (URPM_PER_RPM / 10)) as u32;
     }
 
     /// Immediately stop (emergency stop)
     pub fn emergency_stop(&mut self) {
         self.target_rpm = 0;
         self.current_rpm_x10 = 0;
+        self.speed_urpm = 0;
+        self.accel_mrpm_per_s = 0;
         self.state = MotionState::Stopped;
     }
 
     /// Update the motion state based on current/target RPM
     fn update_state(&mut self) {
-        let target_x10 = (self.target_rpm as u32) * 10;
+        let target = self.target_rpm as i64 * URPM_PER_RPM;
 
-        if self.current_rpm_x10 == 0 && target_x10 == 0 {
+        if self.speed_urpm == 0 && target == 0 {
             self.state = MotionState::Stopped;
-        } else if self.current_rpm_x10 < target_x10 {
+        } else if self.speed_urpm < target {
             self.state = MotionState::Accelerating;
-        } else if self.current_rpm_x10 > target_x10 {
+        } else if self.speed_urpm > target {
             self.state = MotionState::Decelerating;
         } else {
             self.state = MotionState::AtSpeed;
@@ -158,7 +285,8 @@ impl MotionPlanner {
 
     /// Calculate time to reach target from current speed
     ///
-    /// Returns time in milliseconds
+    /// Returns time in milliseconds. For an S-curve this assumes the
+    /// acceleration starts from zero.
     pub fn time_to_target(&self) -> u32 {
         let target_x10 = (self.target_rpm as u32) * 10;
         let diff = self.current_rpm_x10.abs_diff(target_x10);
@@ -167,7 +295,24 @@ impl MotionPlanner {
         if self.accel_rpm_per_s == 0 {
             return u32::MAX;
         }
-        diff * 100 / (self.accel_rpm_per_s as u32)
+        let linear_ms = diff * 100 / (self.accel_rpm_per_s as u32);
+
+        let AccelProfile::SCurve { jerk_rpm_per_s2 } = self.profile else {
+            return linear_ms;
+        };
+        if diff == 0 {
+            return 0;
+        }
+        // Easing in and out takes accel/jerk longer, unless the change is
+        // too small to reach full acceleration
+        let accel = self.accel_rpm_per_s as u64;
+        let jerk = jerk_rpm_per_s2 as u64;
+        if diff as u64 * jerk >= accel * accel * 10 {
+            linear_ms + (accel * 1000 / jerk) as u32
+        } else {
+            // 2·sqrt(diff / jerk) seconds
+            2 * (diff as u64 * 100_000 / jerk).isqrt() as u32
+        }
     }
 }
 
@@ -243,5 +388,86 @@ mod tests {
         planner.set_target(100);
         // Should take ~1000ms to reach target
         assert_eq!(planner.time_to_target(), 1000);
+
+        // Easing in and out adds accel / jerk = 500ms
+        let mut planner = MotionPlanner::with_acceleration(100).with_jerk_limit(200);
+        planner.set_target(100);
+        assert_eq!(planner.time_to_target(), 1500);
+
+        // Too short to reach full acceleration: 2·sqrt(20 / 200) s
+        planner.set_target(20);
+        assert_eq!(planner.time_to_target(), 632);
+    }
+
+    /// Run a move to 100 RPM, starting to stop at `stop_ms`, in 10ms updates
+    ///
+    /// Returns the distance covered (µRPM·ms), the peak speed and every
+    /// acceleration along the way.
+    fn run_move(planner: &mut MotionPlanner, stop_ms: u32) -> (i64, u16, heapless::Vec<i32, 1001>) {
+        let mut distance = 0;
+        let mut peak = 0;
+        let mut accels = heapless::Vec::new();
+        accels.push(planner.get_acceleration()).unwrap();
+        planner.set_target(100);
+        for t in (0..10_000).step_by(10) {
+            if t == stop_ms {
+                planner.set_target(0);
+            }
+            peak = peak.max(planner.update(10));
+            distance += planner.speed_urpm * 10;
+            accels.push(planner.get_acceleration()).unwrap();
+        }
+        assert!(planner.is_stopped());
+        (distance, peak, accels)
+    }
+
+    #[test]
+    fn test_s_curve_matches_trapezoid() {
+        let (trapezoid_distance, trapezoid_peak, _) =
+            run_move(&mut MotionPlanner::with_acceleration(100), 3000);
+        let mut planner = MotionPlanner::with_acceleration(100).with_jerk_limit(200);
+        let (s_curve_distance, s_curve_peak, _) = run_move(&mut planner, 3000);
+
+        // Symmetric ramps cover the same ground when the stop starts together
+        assert_eq!(s_curve_peak, 100);
+        assert_eq!(s_curve_peak, trapezoid_peak);
+        assert!(s_curve_distance.abs_diff(trapezoid_distance) < trapezoid_distance as u64 / 100);
+    }
+
+    #[test]
+    fn test_s_curve_limits_jerk() {
+        // 200 RPM/s² over a 10ms update
+        let max_change = 2_000;
+
+        let mut planner = MotionPlanner::with_acceleration(100).with_jerk_limit(200);
+        assert_eq!(
+            planner.get_profile(),
+            AccelProfile::SCurve {
+                jerk_rpm_per_s2: 200
+            }
+        );
+        let (_, _, accels) = run_move(&mut planner, 3000);
+        assert!(accels.windows(2).all(|w| w[0].abs_diff(w[1]) <= max_change));
+        assert_eq!(accels.iter().max(), Some(&100_000));
+        assert_eq!(accels.iter().min(), Some(&-100_000));
+        assert_eq!(accels.last(), Some(&0));
+
+        // Settles at speed with the acceleration eased out
+        let mut planner = MotionPlanner::with_acceleration(100).with_jerk_limit(200);
+        planner.set_target(50);
+        for _ in 0..200 {
+            planner.update(10);
+        }
+        assert_eq!(planner.get_current(), 50);
+        assert_eq!(planner.get_acceleration(), 0);
+        assert_eq!(planner.get_state(), MotionState::AtSpeed);
+
+        // A trapezoid steps straight to full acceleration and back
+        let (_, _, accels) = run_move(&mut MotionPlanner::with_acceleration(100), 3000);
+        assert_eq!(accels[1], 100_000);
+        assert!(accels.windows(2).any(|w| w[0].abs_diff(w[1]) == 100_000));
+
+        let planner = MotionPlanner::with_acceleration(100).with_jerk_limit(0);
+        assert_eq!(planner.get_profile(), AccelProfile::Trapezoidal);
     }
 }
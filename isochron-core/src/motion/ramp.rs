@@ -0,0 +1,322 @@
+//! Spin ramp profile shared by all motor types
+//!
+//! A ramp profile describes how a motor changes speed: how fast it
+//! accelerates, the shape of the ramp and the speed range it may run in.
+//! DC motors and steppers both consume it, so ramp behaviour is configured
+//! the same way whatever drives the basket.
+//!
+//! Speeds are in RPM. A DC motor without speed feedback is commanded in
+//! percent of full speed, so its profile uses percent with `max_rpm = 100`.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::planner::DEFAULT_ACCEL_RPM_PER_S;
+
+/// Shape of a speed ramp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RampCurve {
+    /// Constant acceleration
+    #[default]
+    Linear,
+    /// Eases in and out of the ramp (smoothstep), same total duration
+    Smooth,
+}
+
+impl RampCurve {
+    /// Shape a ramp fraction (‰) into a speed fraction (‰)
+    fn shape(self, fraction_x1000: u32) -> u32 {
+        match self {
+            RampCurve::Linear => fraction_x1000,
+            RampCurve::Smooth => {
+                let t = fraction_x1000 as u64;
+                (t * t * (3000 - 2 * t) / 1_000_000) as u32
+            }
+        }
+    }
+}
+
+/// Speed ramp for a motor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RampProfile {
+    /// Acceleration and deceleration rate (RPM/s, 0 = instant)
+    pub accel_rate: u16,
+    /// Ramp shape
+    pub curve: RampCurve,
+    /// Lowest running speed; ramps start and stop here (RPM)
+    pub min_rpm: u16,
+    /// Highest speed a ramp may reach (RPM)
+    pub max_rpm: u16,
+}
+
+impl Default for RampProfile {
+    fn default() -> Self {
+        Self {
+            accel_rate: DEFAULT_ACCEL_RPM_PER_S,
+            curve: RampCurve::Linear,
+            min_rpm: 0,
+            max_rpm: u16::MAX,
+        }
+    }
+}
+
+impl RampProfile {
+    /// Linear profile taking `ramp_ms` to go from standstill to `max_rpm`
+    pub fn from_ramp_time(ramp_ms: u16, max_rpm: u16) -> Self {
+        let accel_rate = if ramp_ms == 0 {
+            0
+        } else {
+            (max_rpm as u32 * 1000 / ramp_ms as u32).clamp(1, u16::MAX as u32) as u16
+        };
+        Self {
+            accel_rate,
+            max_rpm,
+            ..Self::default()
+        }
+    }
+
+    /// Clamp a commanded speed into the running range (0 stays stopped)
+    pub fn limit(&self, rpm: u16) -> u16 {
+        if rpm == 0 {
+            0
+        } else {
+            rpm.max(self.min_rpm).min(self.max_rpm)
+        }
+    }
+
+    /// Speeds a ramp actually runs between
+    ///
+    /// Starting from standstill jumps straight to `min_rpm`; stopping ramps
+    /// down to `min_rpm` and then cuts out.
+    fn endpoints(&self, from_rpm: u16, to_rpm: u16) -> (u16, u16) {
+        let floor = self.min_rpm.min(self.max_rpm);
+        let from = if from_rpm == 0 {
+            floor
+        } else {
+            self.limit(from_rpm)
+        };
+        let to = if to_rpm == 0 {
+            floor
+        } else {
+            self.limit(to_rpm)
+        };
+        (from, to)
+    }
+
+    /// Time to ramp from `from_rpm` to `to_rpm` (ms)
+    pub fn ramp_time_ms(&self, from_rpm: u16, to_rpm: u16) -> u32 {
+        if self.accel_rate == 0 {
+            return 0;
+        }
+        let (from, to) = self.endpoints(from_rpm, to_rpm);
+        from.abs_diff(to) as u32 * 1000 / self.accel_rate as u32
+    }
+
+    /// Speed `elapsed_ms` into a ramp from `from_rpm` to `to_rpm`
+    pub fn rpm_at(&self, from_rpm: u16, to_rpm: u16, elapsed_ms: u32) -> u16 {
+        let duration_ms = self.ramp_time_ms(from_rpm, to_rpm);
+        if elapsed_ms >= duration_ms {
+            return self.limit(to_rpm);
+        }
+
+        let (from, to) = self.endpoints(from_rpm, to_rpm);
+        let fraction_x1000 = self
+            .curve
+            .shape((elapsed_ms as u64 * 1000 / duration_ms as u64) as u32);
+        let span = from.abs_diff(to) as u32 * fraction_x1000 / 1000;
+        if to > from {
+            from + span as u16
+        } else {
+            from - span as u16
+        }
+    }
+}
+
+/// A motor's progress along its ramp to the latest target speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SpeedRamp {
+    /// Ramp followed on speed changes
+    profile: RampProfile,
+    /// Speed the current ramp started from
+    start_rpm: u16,
+    /// Speed being ramped to
+    target_rpm: u16,
+    /// Speed reached so far
+    current_rpm: u16,
+    /// Time into the current ramp (ms)
+    elapsed_ms: u32,
+}
+
+impl SpeedRamp {
+    /// Create a stopped ramp following `profile`
+    pub fn new(profile: RampProfile) -> Self {
+        Self {
+            profile,
+            ..Self::default()
+        }
+    }
+
+    /// Ramp profile in use
+    pub fn profile(&self) -> &RampProfile {
+        &self.profile
+    }
+
+    /// Change the ramp profile; takes effect on the next speed change
+    pub fn set_profile(&mut self, profile: RampProfile) {
+        self.profile = profile;
+    }
+
+    /// Ramp to a new target speed, starting from the current speed
+    pub fn set_target(&mut self, rpm: u16) {
+        if rpm != self.target_rpm {
+            self.start_rpm = self.current_rpm;
+            self.elapsed_ms = 0;
+        }
+        self.target_rpm = rpm;
+    }
+
+    /// Speed being ramped to
+    pub fn target(&self) -> u16 {
+        self.target_rpm
+    }
+
+    /// Speed reached so far
+    pub fn current(&self) -> u16 {
+        self.current_rpm
+    }
+
+    /// Check if the ramp has reached its target
+    pub fn is_done(&self) -> bool {
+        self.current_rpm == self.profile.limit(self.target_rpm)
+    }
+
+    /// Advance the ramp by `delta_ms`, returning the speed to run at
+    pub fn update(&mut self, delta_ms: u32) -> u16 {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(delta_ms);
+        self.current_rpm = self
+            .profile
+            .rpm_at(self.start_rpm, self.target_rpm, self.elapsed_ms);
+        self.current_rpm
+    }
+
+    /// Jump straight to the target speed
+    pub fn sync(&mut self) {
+        self.current_rpm = self.target_rpm;
+        self.start_rpm = self.target_rpm;
+    }
+
+    /// Stop at once, e.g. after the motor was moved outside the ramp
+    pub fn reset(&mut self) {
+        *self = Self::new(self.profile);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(accel_rate: u16) -> RampProfile {
+        RampProfile {
+            accel_rate,
+            ..RampProfile::default()
+        }
+    }
+
+    #[test]
+    fn test_linear_ramp_timing() {
+        // 100 RPM/s: 0 to 100 RPM takes a second
+        let ramp = profile(100);
+        assert_eq!(ramp.ramp_time_ms(0, 100), 1000);
+        assert_eq!(ramp.ramp_time_ms(100, 0), 1000);
+        assert_eq!(ramp.rpm_at(0, 100, 0), 0);
+        assert_eq!(ramp.rpm_at(0, 100, 500), 50);
+        assert_eq!(ramp.rpm_at(0, 100, 1000), 100);
+        assert_eq!(ramp.rpm_at(100, 40, 300), 70);
+    }
+
+    #[test]
+    fn test_smooth_ramp_same_duration() {
+        let linear = profile(100);
+        let smooth = RampProfile {
+            curve: RampCurve::Smooth,
+            ..linear
+        };
+
+        assert_eq!(smooth.ramp_time_ms(0, 100), linear.ramp_time_ms(0, 100));
+        // Slower off the mark, level at the midpoint, ahead after it
+        assert!(smooth.rpm_at(0, 100, 200) < linear.rpm_at(0, 100, 200));
+        assert_eq!(smooth.rpm_at(0, 100, 500), 50);
+        assert!(smooth.rpm_at(0, 100, 800) > linear.rpm_at(0, 100, 800));
+        assert_eq!(smooth.rpm_at(0, 100, 1000), 100);
+    }
+
+    #[test]
+    fn test_speed_range() {
+        let ramp = RampProfile {
+            accel_rate: 100,
+            curve: RampCurve::Linear,
+            min_rpm: 20,
+            max_rpm: 200,
+        };
+
+        assert_eq!(ramp.limit(0), 0);
+        assert_eq!(ramp.limit(5), 20);
+        assert_eq!(ramp.limit(300), 200);
+
+        // Starts at min_rpm, so only 80 RPM of ramp
+        assert_eq!(ramp.ramp_time_ms(0, 100), 800);
+        assert_eq!(ramp.rpm_at(0, 100, 0), 20);
+        // Stops by cutting out at min_rpm
+        assert_eq!(ramp.rpm_at(100, 0, 400), 60);
+        assert!(ramp.rpm_at(100, 0, 799) >= 20);
+        assert_eq!(ramp.rpm_at(100, 0, 800), 0);
+        // Never ramps past max_rpm
+        assert_eq!(ramp.rpm_at(0, 300, 10_000), 200);
+    }
+
+    #[test]
+    fn test_zero_rate_is_instant() {
+        let ramp = profile(0);
+        assert_eq!(ramp.ramp_time_ms(0, 100), 0);
+        assert_eq!(ramp.rpm_at(0, 100, 0), 100);
+        assert_eq!(RampProfile::from_ramp_time(0, 100).accel_rate, 0);
+    }
+
+    #[test]
+    fn test_speed_ramp_follows_target() {
+        let mut ramp = SpeedRamp::new(profile(100));
+        ramp.set_target(120);
+        assert_eq!(ramp.update(600), 60);
+        assert!(!ramp.is_done());
+        assert_eq!(ramp.update(600), 120);
+        assert!(ramp.is_done());
+
+        // A new target ramps from where the motor is
+        ramp.set_target(60);
+        assert_eq!(ramp.update(300), 90);
+        ramp.set_target(0);
+        assert_eq!(ramp.update(900), 0);
+        assert!(ramp.is_done());
+
+        // Without a rate every change is immediate
+        let mut instant = SpeedRamp::new(profile(0));
+        instant.set_target(80);
+        assert_eq!(instant.update(0), 80);
+        instant.reset();
+        assert_eq!(instant.current(), 0);
+        assert_eq!(instant.target(), 0);
+    }
+
+    #[test]
+    fn test_from_ramp_time() {
+        let ramp = RampProfile::from_ramp_time(500, 100);
+        assert_eq!(ramp.accel_rate, 200);
+        assert_eq!(ramp.ramp_time_ms(0, 100), 500);
+        assert_eq!(ramp.ramp_time_ms(0, 50), 250);
+    }
+}
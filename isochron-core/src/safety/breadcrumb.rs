@@ -0,0 +1,176 @@
+//! Last-known state kept across a watchdog reset
+//!
+//! The controller records a breadcrumb on every state change. It is packed
+//! into a single word so it fits a register that survives a reset (the
+//! RP2040 watchdog scratch registers) and costs no flash wear. After a
+//! watchdog reset the firmware reads it back to explain what the machine
+//! was doing when it hung.
+
+use crate::state::{DriverFaultKind, ErrorKind, State};
+
+/// Marks a word as a breadcrumb (upper half)
+const MAGIC: u32 = 0xB7C0_0000;
+
+/// Mask for the magic half of the word
+const MAGIC_MASK: u32 = 0xFFFF_0000;
+
+/// Flag bit marking an error state code
+const ERROR_FLAG: u8 = 0x80;
+
+/// Machine state at the time of the last state change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Breadcrumb {
+    /// Machine state
+    pub state: State,
+    /// Program step (1-based, 0 = no program running)
+    pub step: u8,
+}
+
+impl Breadcrumb {
+    /// Create a breadcrumb
+    pub const fn new(state: State, step: u8) -> Self {
+        Self { state, step }
+    }
+
+    /// Pack into a word for a reset-surviving register
+    pub fn to_word(&self) -> u32 {
+        MAGIC | (state_code(self.state) as u32) << 8 | self.step as u32
+    }
+
+    /// Unpack a word written by [`Breadcrumb::to_word`]
+    ///
+    /// Returns `None` for anything else, such as the power-on contents of
+    /// the register.
+    pub fn from_word(word: u32) -> Option<Self> {
+        if word & MAGIC_MASK != MAGIC {
+            return None;
+        }
+        let state = state_from_code((word >> 8) as u8)?;
+        Some(Self::new(state, word as u8))
+    }
+}
+
+/// Shown after the board was reset by the watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecoveryNotice {
+    /// State before the reset, if a breadcrumb was left
+    pub last: Option<Breadcrumb>,
+}
+
+fn state_code(state: State) -> u8 {
+    match state {
+        State::Boot => 0,
+        State::Idle => 1,
+        State::ProgramSelected => 2,
+        State::EditProgram => 3,
+        State::AwaitingJar => 4,
+        State::Running => 5,
+        State::AwaitingSpinOff => 6,
+        State::SpinOff => 7,
+        State::Paused => 8,
+        State::StepComplete => 9,
+        State::ProgramComplete => 10,
+        State::Autotuning => 11,
+        State::Error(kind) => ERROR_FLAG | error_code(kind),
+    }
+}
+
+fn state_from_code(code: u8) -> Option<State> {
+    if code & ERROR_FLAG != 0 {
+        return error_from_code(code & !ERROR_FLAG).map(State::Error);
+    }
+    Some(match code {
+        0 => State::Boot,
+        1 => State::Idle,
+        2 => State::ProgramSelected,
+        3 => State::EditProgram,
+        4 => State::AwaitingJar,
+        5 => State::Running,
+        6 => State::AwaitingSpinOff,
+        7 => State::SpinOff,
+        8 => State::Paused,
+        9 => State::StepComplete,
+        10 => State::ProgramComplete,
+        11 => State::Autotuning,
+        _ => return None,
+    })
+}
+
+fn error_code(kind: ErrorKind) -> u8 {
+    match kind {
+        ErrorKind::ThermistorFault => 0,
+        ErrorKind::OverTemperature => 1,
+        ErrorKind::MotorStall => 2,
+        ErrorKind::DriverFault(DriverFaultKind::OverTemperature) => 3,
+        ErrorKind::DriverFault(DriverFaultKind::ShortCircuit) => 4,
+        ErrorKind::LinkLost => 5,
+        ErrorKind::PositionOutOfBounds => 6,
+        ErrorKind::Imbalance => 7,
+        ErrorKind::ConfigError => 8,
+        ErrorKind::Unknown => 9,
+        ErrorKind::ThermalRunaway => 10,
+        ErrorKind::HomingFailed => 11,
+        ErrorKind::LidOpen => 12,
+    }
+}
+
+fn error_from_code(code: u8) -> Option<ErrorKind> {
+    Some(match code {
+        0 => ErrorKind::ThermistorFault,
+        1 => ErrorKind::OverTemperature,
+        2 => ErrorKind::MotorStall,
+        3 => ErrorKind::DriverFault(DriverFaultKind::OverTemperature),
+        4 => ErrorKind::DriverFault(DriverFaultKind::ShortCircuit),
+        5 => ErrorKind::LinkLost,
+        6 => ErrorKind::PositionOutOfBounds,
+        7 => ErrorKind::Imbalance,
+        8 => ErrorKind::ConfigError,
+        9 => ErrorKind::Unknown,
+        10 => ErrorKind::ThermalRunaway,
+        11 => ErrorKind::HomingFailed,
+        12 => ErrorKind::LidOpen,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let states = [
+            State::Boot,
+            State::Idle,
+            State::ProgramSelected,
+            State::EditProgram,
+            State::AwaitingJar,
+            State::Running,
+            State::AwaitingSpinOff,
+            State::SpinOff,
+            State::Paused,
+            State::StepComplete,
+            State::ProgramComplete,
+            State::Autotuning,
+            State::Error(ErrorKind::MotorStall),
+            State::Error(ErrorKind::DriverFault(DriverFaultKind::ShortCircuit)),
+            State::Error(ErrorKind::Unknown),
+        ];
+        for (i, state) in states.into_iter().enumerate() {
+            let crumb = Breadcrumb::new(state, i as u8);
+            assert_eq!(Breadcrumb::from_word(crumb.to_word()), Some(crumb));
+        }
+    }
+
+    #[test]
+    fn test_foreign_words_rejected() {
+        // Power-on contents and stale values from other firmware
+        assert_eq!(Breadcrumb::from_word(0), None);
+        assert_eq!(Breadcrumb::from_word(0xFFFF_FFFF), None);
+        // Right magic, unknown state code
+        assert_eq!(Breadcrumb::from_word(MAGIC | 0x40 << 8), None);
+        assert_eq!(Breadcrumb::from_word(MAGIC | 0xC0 << 8), None);
+    }
+}
@@ -0,0 +1,114 @@
+//! Basket imbalance detection
+//!
+//! An unevenly loaded basket at spin-off speed makes the motor load swing
+//! with every revolution. StallGuard's `SG_RESULT` follows the load, so a
+//! wide spread between recent readings indicates an imbalance while a
+//! balanced basket reads steadily.
+
+use heapless::Deque;
+
+/// Number of readings the spread is measured over
+pub const IMBALANCE_WINDOW: usize = 4;
+
+/// Detects excessive StallGuard load variation
+#[derive(Debug, Clone)]
+pub struct ImbalanceDetector {
+    /// Maximum allowed spread between readings in the window
+    threshold: u16,
+    /// Most recent readings, oldest first
+    samples: Deque<u16, IMBALANCE_WINDOW>,
+}
+
+impl ImbalanceDetector {
+    /// Create a detector that trips when the spread exceeds `threshold`
+    pub fn new(threshold: u16) -> Self {
+        Self {
+            threshold,
+            samples: Deque::new(),
+        }
+    }
+
+    /// Get the configured threshold
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    /// Discard collected readings
+    ///
+    /// Call whenever the motor leaves spin-off so readings from ramps or
+    /// other phases are never compared.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Spread (max - min) of the window, once it is full
+    pub fn spread(&self) -> Option<u16> {
+        if !self.samples.is_full() {
+            return None;
+        }
+        let max = self.samples.iter().max()?;
+        let min = self.samples.iter().min()?;
+        Some(max - min)
+    }
+
+    /// Add an `SG_RESULT` reading
+    ///
+    /// Returns true if the load variation over the window exceeds the
+    /// threshold.
+    pub fn update(&mut self, sg_result: u16) -> bool {
+        if self.samples.is_full() {
+            self.samples.pop_front();
+        }
+        let _ = self.samples.push_back(sg_result);
+        self.spread().is_some_and(|spread| spread > self.threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steady_readings_pass() {
+        let mut detector = ImbalanceDetector::new(60);
+        for sg in [240, 250, 245, 255, 248, 252, 246, 250] {
+            assert!(!detector.update(sg));
+        }
+        assert!(detector.spread().unwrap() <= 60);
+    }
+
+    #[test]
+    fn test_high_variance_trips() {
+        let mut detector = ImbalanceDetector::new(60);
+        assert!(!detector.update(250));
+        assert!(!detector.update(120));
+        assert!(!detector.update(300));
+        // Window full: spread 180
+        assert!(detector.update(140));
+        assert_eq!(detector.spread(), Some(180));
+    }
+
+    #[test]
+    fn test_old_readings_leave_window() {
+        let mut detector = ImbalanceDetector::new(60);
+        // One outlier early on, then steady
+        detector.update(50);
+        for _ in 0..IMBALANCE_WINDOW - 1 {
+            detector.update(250);
+        }
+        assert!(!detector.update(250));
+        assert_eq!(detector.spread(), Some(0));
+    }
+
+    #[test]
+    fn test_reset_needs_full_window() {
+        let mut detector = ImbalanceDetector::new(60);
+        for sg in [100, 300, 100, 300] {
+            detector.update(sg);
+        }
+        detector.reset();
+        assert_eq!(detector.spread(), None);
+        assert!(!detector.update(100));
+        assert!(!detector.update(300));
+    }
+}
@@ -2,6 +2,12 @@
 //!
 //! Detects fault conditions and triggers error states.
 
+pub mod breadcrumb;
+pub mod imbalance;
 pub mod monitor;
+pub mod watchdog;
 
+pub use breadcrumb::{Breadcrumb, RecoveryNotice};
+pub use imbalance::ImbalanceDetector;
 pub use monitor::{SafetyMonitor, SafetyStatus};
+pub use watchdog::{TickWatch, WatchdogAction};
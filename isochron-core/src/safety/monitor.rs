@@ -1,13 +1,14 @@
 //! Safety monitor implementation
 //!
-//! Monitors temperature, motor stall, and communication link health.
+//! Monitors temperature, thermal runaway, motor stall, driver faults, the
+//! lid interlock, and communication link health.
 
-use crate::state::ErrorKind;
+use crate::config::{LinkConfig, ThermalRunawayConfig, MAX_HEATERS};
+use crate::state::{DriverFaultKind, ErrorKind};
+use crate::util::TemperatureC10;
 
 /// Safety thresholds
 pub const MAX_TEMPERATURE_C: i16 = 55;
-pub const HEARTBEAT_TIMEOUT_MS: u32 = 3000;
-pub const MAX_MISSED_HEARTBEATS: u8 = 3;
 
 /// Safety condition status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,20 +20,59 @@ pub enum SafetyStatus {
     Fault(ErrorKind),
 }
 
+/// Temperature and thermal runaway state of one heater
+#[derive(Debug, Clone, Copy)]
+struct HeaterSafety {
+    /// Current temperature reading
+    last_temp: Option<TemperatureC10>,
+    /// Temperature sensor valid
+    temp_sensor_valid: bool,
+    /// Heater element currently switched on
+    heater_on: bool,
+    /// Temperature at the start of the current heating window
+    runaway_baseline: Option<TemperatureC10>,
+    /// Time heated in the current window without the expected rise (ms)
+    runaway_elapsed_ms: u32,
+}
+
+impl HeaterSafety {
+    const fn new() -> Self {
+        Self {
+            last_temp: None,
+            temp_sensor_valid: true,
+            heater_on: false,
+            runaway_baseline: None,
+            runaway_elapsed_ms: 0,
+        }
+    }
+}
+
 /// Safety monitor for fault detection
 ///
 /// This struct tracks safety-related state and determines
-/// when to trigger error conditions.
+/// when to trigger error conditions. Each heater's temperature is
+/// checked on its own; the methods without a heater index act on
+/// heater 0.
 #[derive(Debug, Clone)]
 pub struct SafetyMonitor {
-    /// Current temperature reading (×10 for 0.1°C resolution)
-    last_temp_x10: Option<i16>,
-    /// Temperature sensor valid
-    temp_sensor_valid: bool,
+    /// Per-heater temperature state, by heater index
+    heaters: [HeaterSafety; MAX_HEATERS],
+    /// Thermal runaway detection (None = off)
+    runaway: Option<ThermalRunawayConfig>,
     /// Motor stall detected
     motor_stalled: bool,
-    /// Missed heartbeat count
-    missed_heartbeats: u8,
+    /// Stepper driver fault reported
+    driver_fault: Option<DriverFaultKind>,
+    /// Spin-off load imbalance detected
+    imbalance: bool,
+    /// Lid interlock reports the lid open
+    lid_open: bool,
+    /// Motor commanded to move
+    motor_commanded: bool,
+    /// Expected heartbeat interval (ms)
+    heartbeat_ms: u32,
+    /// Time without a heartbeat before the link is lost (ms)
+    link_timeout_ms: u32,
     /// Time since last heartbeat (ms)
     time_since_heartbeat_ms: u32,
 }
@@ -46,22 +86,99 @@ impl Default for SafetyMonitor {
 impl SafetyMonitor {
     /// Create a new safety monitor
     pub fn new() -> Self {
+        let link = LinkConfig::default();
         Self {
-            last_temp_x10: None,
-            temp_sensor_valid: true,
+            heaters: [HeaterSafety::new(); MAX_HEATERS],
+            runaway: None,
             motor_stalled: false,
-            missed_heartbeats: 0,
+            driver_fault: None,
+            imbalance: false,
+            lid_open: false,
+            motor_commanded: false,
+            heartbeat_ms: link.heartbeat_ms as u32,
+            link_timeout_ms: link.timeout_ms(),
             time_since_heartbeat_ms: 0,
         }
     }
 
+    /// Set the heartbeat interval and link-lost timeout
+    ///
+    /// The link is lost once no heartbeat has arrived for
+    /// `heartbeat_ms × timeout_multiplier`, so heartbeats that arrive late
+    /// by less than the spare intervals do not trip it.
+    pub fn set_link_config(&mut self, link: &LinkConfig) {
+        self.heartbeat_ms = (link.heartbeat_ms as u32).max(1);
+        self.link_timeout_ms = link.timeout_ms().max(self.heartbeat_ms);
+    }
+
+    /// Link-lost timeout (ms)
+    pub fn link_timeout_ms(&self) -> u32 {
+        self.link_timeout_ms
+    }
+
     /// Update temperature reading
     ///
     /// # Arguments
-    /// - `temp_x10`: Temperature in 0.1°C units, or None if sensor fault
-    pub fn update_temperature(&mut self, temp_x10: Option<i16>) {
-        self.last_temp_x10 = temp_x10;
-        self.temp_sensor_valid = temp_x10.is_some();
+    /// - `temp`: Temperature reading, or None if sensor fault
+    pub fn update_temperature(&mut self, temp: Option<TemperatureC10>) {
+        self.update_temperature_for(0, temp);
+    }
+
+    /// Update one heater's temperature reading
+    ///
+    /// Readings for a heater index past `MAX_HEATERS` are ignored.
+    pub fn update_temperature_for(&mut self, heater: u8, temp: Option<TemperatureC10>) {
+        let runaway = self.runaway;
+        let Some(state) = self.heaters.get_mut(heater as usize) else {
+            return;
+        };
+        state.last_temp = temp;
+        state.temp_sensor_valid = temp.is_some();
+
+        // Each rise by the expected amount starts a new window
+        if let (Some(config), Some(temp)) = (runaway, temp) {
+            let risen = state.runaway_baseline.is_none_or(|baseline| {
+                temp.as_x10() - baseline.as_x10() >= config.min_rise_c as i16 * 10
+            });
+            if state.heater_on && risen {
+                state.runaway_baseline = Some(temp);
+                state.runaway_elapsed_ms = 0;
+            }
+        }
+    }
+
+    /// Enable thermal runaway detection
+    ///
+    /// See [`ThermalRunawayConfig`]. None turns detection off.
+    pub fn set_thermal_runaway(&mut self, config: Option<ThermalRunawayConfig>) {
+        self.runaway = config;
+        for state in self.heaters.iter_mut() {
+            state.runaway_baseline = None;
+            state.runaway_elapsed_ms = 0;
+        }
+    }
+
+    /// Update heater element status
+    ///
+    /// Thermal runaway is timed from the moment the element switches on,
+    /// and starts over each time it switches off.
+    pub fn update_heater_output(&mut self, on: bool) {
+        self.update_heater_output_for(0, on);
+    }
+
+    /// Update one heater element's status
+    pub fn update_heater_output_for(&mut self, heater: u8, on: bool) {
+        let Some(state) = self.heaters.get_mut(heater as usize) else {
+            return;
+        };
+        if on && !state.heater_on {
+            state.runaway_baseline = state.last_temp;
+            state.runaway_elapsed_ms = 0;
+        } else if !on {
+            state.runaway_baseline = None;
+            state.runaway_elapsed_ms = 0;
+        }
+        state.heater_on = on;
     }
 
     /// Update motor stall status
@@ -69,9 +186,30 @@ impl SafetyMonitor {
         self.motor_stalled = stalled;
     }
 
+    /// Update stepper driver fault status
+    pub fn update_driver_fault(&mut self, fault: Option<DriverFaultKind>) {
+        self.driver_fault = fault;
+    }
+
+    /// Update spin-off imbalance status
+    pub fn update_imbalance(&mut self, imbalance: bool) {
+        self.imbalance = imbalance;
+    }
+
+    /// Update lid interlock status
+    pub fn update_lid_open(&mut self, open: bool) {
+        self.lid_open = open;
+    }
+
+    /// Update whether the motor is commanded to move
+    ///
+    /// An open lid only faults while the motor is commanded.
+    pub fn update_motor_commanded(&mut self, commanded: bool) {
+        self.motor_commanded = commanded;
+    }
+
     /// Record a heartbeat received
     pub fn heartbeat_received(&mut self) {
-        self.missed_heartbeats = 0;
         self.time_since_heartbeat_ms = 0;
     }
 
@@ -81,10 +219,10 @@ impl SafetyMonitor {
     /// - `delta_ms`: Time elapsed since last update
     pub fn update_time(&mut self, delta_ms: u32) {
         self.time_since_heartbeat_ms = self.time_since_heartbeat_ms.saturating_add(delta_ms);
-
-        if self.time_since_heartbeat_ms >= HEARTBEAT_TIMEOUT_MS {
-            self.missed_heartbeats = self.missed_heartbeats.saturating_add(1);
-            self.time_since_heartbeat_ms = 0;
+        if self.runaway.is_some() {
+            for state in self.heaters.iter_mut().filter(|s| s.heater_on) {
+                state.runaway_elapsed_ms = state.runaway_elapsed_ms.saturating_add(delta_ms);
+            }
         }
     }
 
@@ -93,14 +231,28 @@ impl SafetyMonitor {
     /// Returns the first fault detected, or Ok if all conditions are normal.
     pub fn check(&self) -> SafetyStatus {
         // Check temperature sensor fault
-        if !self.temp_sensor_valid {
+        if self.heaters.iter().any(|s| !s.temp_sensor_valid) {
             return SafetyStatus::Fault(ErrorKind::ThermistorFault);
         }
 
         // Check over-temperature
-        if let Some(temp_x10) = self.last_temp_x10 {
-            if temp_x10 > MAX_TEMPERATURE_C * 10 {
-                return SafetyStatus::Fault(ErrorKind::OverTemperature);
+        let max = TemperatureC10::from_whole(MAX_TEMPERATURE_C);
+        if self
+            .heaters
+            .iter()
+            .any(|s| s.last_temp.is_some_and(|t| t > max))
+        {
+            return SafetyStatus::Fault(ErrorKind::OverTemperature);
+        }
+
+        // Check thermal runaway
+        if let Some(config) = self.runaway {
+            if self
+                .heaters
+                .iter()
+                .any(|s| s.heater_on && s.runaway_elapsed_ms >= config.window_ms())
+            {
+                return SafetyStatus::Fault(ErrorKind::ThermalRunaway);
             }
         }
 
@@ -109,8 +261,23 @@ impl SafetyMonitor {
             return SafetyStatus::Fault(ErrorKind::MotorStall);
         }
 
+        // Check stepper driver faults
+        if let Some(fault) = self.driver_fault {
+            return SafetyStatus::Fault(ErrorKind::DriverFault(fault));
+        }
+
+        // Check spin-off imbalance
+        if self.imbalance {
+            return SafetyStatus::Fault(ErrorKind::Imbalance);
+        }
+
+        // Check lid interlock
+        if self.lid_open && self.motor_commanded {
+            return SafetyStatus::Fault(ErrorKind::LidOpen);
+        }
+
         // Check link health
-        if self.missed_heartbeats >= MAX_MISSED_HEARTBEATS {
+        if !self.is_link_healthy() {
             return SafetyStatus::Fault(ErrorKind::LinkLost);
         }
 
@@ -119,35 +286,44 @@ impl SafetyMonitor {
 
     /// Get current temperature in whole degrees Celsius
     pub fn get_temperature(&self) -> Option<i16> {
-        self.last_temp_x10.map(|t| t / 10)
+        self.get_temperature_for(0)
+    }
+
+    /// Get one heater's temperature in whole degrees Celsius
+    pub fn get_temperature_for(&self, heater: u8) -> Option<i16> {
+        self.heaters
+            .get(heater as usize)?
+            .last_temp
+            .map(TemperatureC10::to_whole)
     }
 
     /// Check if link is healthy
     pub fn is_link_healthy(&self) -> bool {
-        self.missed_heartbeats < MAX_MISSED_HEARTBEATS
+        self.time_since_heartbeat_ms < self.link_timeout_ms
     }
 
     /// Get number of missed heartbeats
     pub fn get_missed_heartbeats(&self) -> u8 {
-        self.missed_heartbeats
+        (self.time_since_heartbeat_ms / self.heartbeat_ms).min(u8::MAX as u32) as u8
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use isochron_protocol::DEFAULT_HEARTBEAT_MS;
 
     #[test]
     fn test_normal_operation() {
         let mut monitor = SafetyMonitor::new();
-        monitor.update_temperature(Some(450)); // 45.0°C
+        monitor.update_temperature(Some(TemperatureC10::from_x10(450))); // 45.0°C
         assert_eq!(monitor.check(), SafetyStatus::Ok);
     }
 
     #[test]
     fn test_over_temperature() {
         let mut monitor = SafetyMonitor::new();
-        monitor.update_temperature(Some(560)); // 56.0°C > 55°C
+        monitor.update_temperature(Some(TemperatureC10::from_x10(560))); // 56.0°C > 55°C
         assert_eq!(
             monitor.check(),
             SafetyStatus::Fault(ErrorKind::OverTemperature)
@@ -164,22 +340,211 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_over_temperature_per_heater() {
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature(Some(TemperatureC10::from_x10(450)));
+        monitor.update_temperature_for(1, Some(TemperatureC10::from_x10(400)));
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+        assert_eq!(monitor.get_temperature(), Some(45));
+        assert_eq!(monitor.get_temperature_for(1), Some(40));
+
+        // The second heater overheating faults, whatever the first reads
+        monitor.update_temperature_for(1, Some(TemperatureC10::from_x10(560)));
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::OverTemperature)
+        );
+        monitor.update_temperature_for(1, Some(TemperatureC10::from_x10(400)));
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+
+        // So does its sensor failing
+        monitor.update_temperature_for(1, None);
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::ThermistorFault)
+        );
+
+        // Out-of-range heaters are ignored
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature_for(MAX_HEATERS as u8, None);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+        assert_eq!(monitor.get_temperature_for(MAX_HEATERS as u8), None);
+    }
+
+    /// Advance time with the display link healthy
+    fn advance(monitor: &mut SafetyMonitor, delta_ms: u32) {
+        monitor.update_time(delta_ms);
+        monitor.heartbeat_received();
+    }
+
+    fn runaway_monitor() -> SafetyMonitor {
+        let mut monitor = SafetyMonitor::new();
+        monitor.set_thermal_runaway(Some(ThermalRunawayConfig {
+            window_s: 60,
+            min_rise_c: 2,
+        }));
+        monitor.update_temperature(Some(TemperatureC10::from_x10(250)));
+        monitor
+    }
+
+    #[test]
+    fn test_thermal_runaway_flat_while_heating() {
+        let mut monitor = runaway_monitor();
+        monitor.update_heater_output(true);
+
+        // A detached thermistor reads a steady, plausible 25°C
+        for _ in 0..59 {
+            advance(&mut monitor, 1000);
+            monitor.update_temperature(Some(TemperatureC10::from_x10(251)));
+            assert_eq!(monitor.check(), SafetyStatus::Ok);
+        }
+        advance(&mut monitor, 1000);
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::ThermalRunaway)
+        );
+
+        // Switching the element off clears it
+        monitor.update_heater_output(false);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+    }
+
+    #[test]
+    fn test_thermal_runaway_rising_passes() {
+        let mut monitor = runaway_monitor();
+        monitor.update_heater_output(true);
+
+        // 2°C every 50 s keeps restarting the window
+        let mut temp_x10 = 250;
+        for second in 1..=300 {
+            advance(&mut monitor, 1000);
+            if second % 50 == 0 {
+                temp_x10 += 20;
+            }
+            monitor.update_temperature(Some(TemperatureC10::from_x10(temp_x10)));
+            assert_eq!(monitor.check(), SafetyStatus::Ok);
+        }
+
+        // Then stalls
+        for _ in 0..60 {
+            advance(&mut monitor, 1000);
+            monitor.update_temperature(Some(TemperatureC10::from_x10(temp_x10 + 15)));
+        }
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::ThermalRunaway)
+        );
+    }
+
+    #[test]
+    fn test_thermal_runaway_only_while_heating() {
+        // Cycling on and off at target never accumulates a full window
+        let mut monitor = runaway_monitor();
+        for _ in 0..20 {
+            monitor.update_heater_output(true);
+            for _ in 0..30 {
+                advance(&mut monitor, 1000);
+                monitor.update_temperature(Some(TemperatureC10::from_x10(250)));
+            }
+            assert_eq!(monitor.check(), SafetyStatus::Ok);
+            monitor.update_heater_output(false);
+            advance(&mut monitor, 10_000);
+        }
+
+        // Off by default
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature(Some(TemperatureC10::from_x10(250)));
+        monitor.update_heater_output(true);
+        advance(&mut monitor, 3_600_000);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+    }
+
+    #[test]
+    fn test_thermal_runaway_per_heater() {
+        let mut monitor = runaway_monitor();
+        monitor.update_temperature_for(1, Some(TemperatureC10::from_x10(250)));
+        monitor.update_heater_output(true);
+        monitor.update_heater_output_for(1, true);
+
+        // Heater 0 rises, heater 1 stays flat
+        for i in 1..60 {
+            advance(&mut monitor, 1000);
+            monitor.update_temperature(Some(TemperatureC10::from_x10(250 + i)));
+            monitor.update_temperature_for(1, Some(TemperatureC10::from_x10(250)));
+            assert_eq!(monitor.check(), SafetyStatus::Ok);
+        }
+        advance(&mut monitor, 1000);
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::ThermalRunaway)
+        );
+
+        // Only switching heater 1 off clears it
+        monitor.update_heater_output(false);
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::ThermalRunaway)
+        );
+        monitor.update_heater_output_for(1, false);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+    }
+
     #[test]
     fn test_motor_stall() {
         let mut monitor = SafetyMonitor::new();
-        monitor.update_temperature(Some(400));
+        monitor.update_temperature(Some(TemperatureC10::from_x10(400)));
         monitor.update_motor_stall(true);
         assert_eq!(monitor.check(), SafetyStatus::Fault(ErrorKind::MotorStall));
     }
 
+    #[test]
+    fn test_driver_fault() {
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature(Some(TemperatureC10::from_x10(400)));
+        monitor.update_driver_fault(Some(DriverFaultKind::ShortCircuit));
+        assert_eq!(
+            monitor.check(),
+            SafetyStatus::Fault(ErrorKind::DriverFault(DriverFaultKind::ShortCircuit))
+        );
+
+        monitor.update_driver_fault(None);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+    }
+
+    #[test]
+    fn test_imbalance() {
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature(Some(TemperatureC10::from_x10(400)));
+        monitor.update_imbalance(true);
+        assert_eq!(monitor.check(), SafetyStatus::Fault(ErrorKind::Imbalance));
+
+        monitor.update_imbalance(false);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+    }
+
+    #[test]
+    fn test_lid_open_only_faults_while_moving() {
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature(Some(TemperatureC10::from_x10(400)));
+        monitor.update_lid_open(true);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+
+        monitor.update_motor_commanded(true);
+        assert_eq!(monitor.check(), SafetyStatus::Fault(ErrorKind::LidOpen));
+
+        monitor.update_lid_open(false);
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+    }
+
     #[test]
     fn test_link_lost() {
         let mut monitor = SafetyMonitor::new();
-        monitor.update_temperature(Some(400));
+        monitor.update_temperature(Some(TemperatureC10::from_x10(400)));
 
         // Miss 3 heartbeats
         for _ in 0..3 {
-            monitor.update_time(HEARTBEAT_TIMEOUT_MS);
+            monitor.update_time(DEFAULT_HEARTBEAT_MS as u32);
         }
 
         assert_eq!(monitor.check(), SafetyStatus::Fault(ErrorKind::LinkLost));
@@ -190,8 +555,8 @@ mod tests {
         let mut monitor = SafetyMonitor::new();
 
         // Miss 2 heartbeats
-        monitor.update_time(HEARTBEAT_TIMEOUT_MS);
-        monitor.update_time(HEARTBEAT_TIMEOUT_MS);
+        monitor.update_time(DEFAULT_HEARTBEAT_MS as u32);
+        monitor.update_time(DEFAULT_HEARTBEAT_MS as u32);
         assert_eq!(monitor.get_missed_heartbeats(), 2);
 
         // Receive heartbeat
@@ -199,4 +564,61 @@ mod tests {
         assert_eq!(monitor.get_missed_heartbeats(), 0);
         assert!(monitor.is_link_healthy());
     }
+
+    #[test]
+    fn test_link_timeout_is_interval_times_multiplier() {
+        let mut monitor = SafetyMonitor::new();
+        assert_eq!(monitor.link_timeout_ms(), DEFAULT_HEARTBEAT_MS as u32 * 3);
+
+        monitor.set_link_config(&LinkConfig {
+            heartbeat_ms: 2000,
+            timeout_multiplier: 5,
+            auto_clear: false,
+            test_pattern: None,
+            report_scheduler_state: false,
+        });
+        assert_eq!(monitor.link_timeout_ms(), 10_000);
+    }
+
+    fn slow_link_monitor() -> SafetyMonitor {
+        let mut monitor = SafetyMonitor::new();
+        monitor.update_temperature(Some(TemperatureC10::from_x10(400)));
+        monitor.set_link_config(&LinkConfig {
+            heartbeat_ms: 1000,
+            timeout_multiplier: 3,
+            auto_clear: false,
+            test_pattern: None,
+            report_scheduler_state: false,
+        });
+        monitor
+    }
+
+    #[test]
+    fn test_jittery_heartbeats_keep_link() {
+        let mut monitor = slow_link_monitor();
+
+        // Heartbeats arriving up to 1.8 intervals apart
+        for gap_ms in [1400, 600, 1800, 900, 1700, 1100] {
+            for _ in 0..gap_ms / 100 {
+                monitor.update_time(100);
+                assert_eq!(monitor.check(), SafetyStatus::Ok);
+            }
+            monitor.heartbeat_received();
+        }
+        assert!(monitor.is_link_healthy());
+    }
+
+    #[test]
+    fn test_heartbeat_gap_loses_link() {
+        let mut monitor = slow_link_monitor();
+
+        for _ in 0..29 {
+            monitor.update_time(100);
+        }
+        assert_eq!(monitor.check(), SafetyStatus::Ok);
+        assert_eq!(monitor.get_missed_heartbeats(), 2);
+
+        monitor.update_time(100);
+        assert_eq!(monitor.check(), SafetyStatus::Fault(ErrorKind::LinkLost));
+    }
 }
@@ -0,0 +1,107 @@
+//! Watchdog feeding gated on controller health
+//!
+//! Feeding the watchdog from any task that happens to run only catches a
+//! hung executor. A controller stuck waiting on something that never
+//! arrives leaves the executor running, so the watchdog would be fed
+//! forever while the heater holds its last command. [`TickWatch`] feeds
+//! only while the controller keeps finishing its ticks.
+//!
+//! Once the controller stalls the watch never feeds again, even if ticks
+//! resume: the outputs are forced off first, and the reset that follows
+//! is the only way back to a known state.
+
+/// What the watchdog task should do on this poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchdogAction {
+    /// Controller healthy: feed the watchdog
+    Feed,
+    /// Controller just stalled: turn the heater and motor off, don't feed
+    DisableOutputs,
+    /// Controller stalled earlier: keep starving the watchdog
+    Starve,
+}
+
+/// Tracks controller ticks to decide whether to feed the watchdog
+#[derive(Debug, Clone)]
+pub struct TickWatch {
+    /// Longest gap between ticks before the controller counts as stalled (ms)
+    stale_after_ms: u32,
+    /// Time of the last controller tick (ms)
+    last_tick_ms: u32,
+    /// Controller has stalled
+    stalled: bool,
+}
+
+impl TickWatch {
+    /// Create a watch, counting `now_ms` as the first tick
+    ///
+    /// The controller gets `stale_after_ms` to finish booting.
+    pub fn new(stale_after_ms: u32, now_ms: u32) -> Self {
+        Self {
+            stale_after_ms,
+            last_tick_ms: now_ms,
+            stalled: false,
+        }
+    }
+
+    /// Record a completed controller tick
+    pub fn tick(&mut self, now_ms: u32) {
+        self.last_tick_ms = now_ms;
+    }
+
+    /// Whether the controller has stalled
+    pub fn stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Decide whether to feed the watchdog
+    pub fn poll(&mut self, now_ms: u32) -> WatchdogAction {
+        if self.stalled {
+            return WatchdogAction::Starve;
+        }
+        if now_ms.wrapping_sub(self.last_tick_ms) > self.stale_after_ms {
+            self.stalled = true;
+            return WatchdogAction::DisableOutputs;
+        }
+        WatchdogAction::Feed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feeds_while_ticking() {
+        let mut watch = TickWatch::new(1_000, 0);
+        for now in (100..5_000).step_by(100) {
+            watch.tick(now);
+            assert_eq!(watch.poll(now + 50), WatchdogAction::Feed);
+        }
+        // Boot gets the full grace period before the first tick
+        let mut watch = TickWatch::new(1_000, 0);
+        assert_eq!(watch.poll(1_000), WatchdogAction::Feed);
+    }
+
+    #[test]
+    fn test_stall_disables_outputs_once() {
+        let mut watch = TickWatch::new(1_000, 0);
+        watch.tick(500);
+        assert_eq!(watch.poll(1_500), WatchdogAction::Feed);
+        assert_eq!(watch.poll(1_501), WatchdogAction::DisableOutputs);
+        assert!(watch.stalled());
+        assert_eq!(watch.poll(1_600), WatchdogAction::Starve);
+
+        // A controller that recovers doesn't win the watchdog back
+        watch.tick(1_700);
+        assert_eq!(watch.poll(1_700), WatchdogAction::Starve);
+    }
+
+    #[test]
+    fn test_clock_wrap() {
+        let mut watch = TickWatch::new(1_000, u32::MAX - 200);
+        assert_eq!(watch.poll(300), WatchdogAction::Feed);
+        assert_eq!(watch.poll(900), WatchdogAction::DisableOutputs);
+    }
+}
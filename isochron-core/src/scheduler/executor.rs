@@ -5,12 +5,18 @@
 
 use heapless::Vec;
 
-use super::segment::{generate_segments, Segment, SpinOffConfig};
+use super::segment::{
+    balance_segment, generate_segments, generate_soak_segments, prepend_prime, BalanceConfig,
+    DirectionMode, Segment, SegmentError, SpinOffConfig,
+};
 use crate::config::{
-    JarConfig, MachineCapabilities, ProfileConfig, ProgramConfig, MAX_JARS, MAX_PROFILES,
+    HeaterConfig, JarConfig, MachineCapabilities, ProfileConfig, ProgramConfig, ProgramStep,
+    StopBehavior, MAX_HEATERS, MAX_JARS, MAX_PROFILES,
 };
+use crate::safety::monitor::MAX_TEMPERATURE_C;
 use crate::state::events::Event;
 use crate::traits::Direction;
+use crate::util::TemperatureC10;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -37,10 +43,65 @@ pub enum ExecutionPhase {
     Paused,
     /// Step complete, transitioning
     StepComplete,
+    /// Last step done, spinning back to cancel the program's net rotation
+    Balancing,
     /// All steps done
     Complete,
 }
 
+impl ExecutionPhase {
+    /// Convert to wire byte
+    pub fn to_byte(self) -> u8 {
+        match self {
+            ExecutionPhase::Idle => 0,
+            ExecutionPhase::Running => 1,
+            ExecutionPhase::SpinOff => 2,
+            ExecutionPhase::AwaitingSpinOff => 3,
+            ExecutionPhase::AwaitingJar => 4,
+            ExecutionPhase::Paused => 5,
+            ExecutionPhase::StepComplete => 6,
+            ExecutionPhase::Balancing => 7,
+            ExecutionPhase::Complete => 8,
+        }
+    }
+
+    /// Parse from wire byte
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0 => ExecutionPhase::Idle,
+            1 => ExecutionPhase::Running,
+            2 => ExecutionPhase::SpinOff,
+            3 => ExecutionPhase::AwaitingSpinOff,
+            4 => ExecutionPhase::AwaitingJar,
+            5 => ExecutionPhase::Paused,
+            6 => ExecutionPhase::StepComplete,
+            7 => ExecutionPhase::Balancing,
+            8 => ExecutionPhase::Complete,
+            _ => return None,
+        })
+    }
+}
+
+/// Basket movement needed between two program steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StepTransition {
+    /// Same jar and the basket is still lowered: no movement
+    Stay,
+    /// Same jar, but the basket was lifted for spin-off and must be lowered
+    Reposition,
+    /// Different jar: lift, travel and lower
+    ChangeJar,
+}
+
+impl StepTransition {
+    /// Whether the basket has to move (or the user be prompted to move it)
+    pub fn needs_motion(&self) -> bool {
+        *self != Self::Stay
+    }
+}
+
 /// Current motor command from scheduler
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -49,6 +110,8 @@ pub struct MotorCommand {
     pub rpm: u16,
     /// Rotation direction
     pub direction: Direction,
+    /// Keep the driver enabled while stopped (brake)
+    pub hold: bool,
 }
 
 impl MotorCommand {
@@ -57,12 +120,31 @@ impl MotorCommand {
         Self {
             rpm: 0,
             direction: Direction::Clockwise,
+            hold: false,
+        }
+    }
+
+    /// Create a stopped command that holds the motor in place
+    pub const fn holding() -> Self {
+        Self {
+            rpm: 0,
+            direction: Direction::Clockwise,
+            hold: true,
         }
     }
 
     /// Create a running command
     pub const fn running(rpm: u16, direction: Direction) -> Self {
-        Self { rpm, direction }
+        Self {
+            rpm,
+            direction,
+            hold: false,
+        }
+    }
+
+    /// Check if the motor driver should be enabled for this command
+    pub const fn enable_required(&self) -> bool {
+        self.rpm > 0 || self.hold
     }
 }
 
@@ -70,22 +152,73 @@ impl MotorCommand {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct HeaterCommand {
-    /// Target temperature in °C (None = heater off)
-    pub target_temp_c: Option<i16>,
+    /// Heater index, in `[heater]` section order
+    pub heater: u8,
+    /// Target temperature (None = heater off)
+    pub target: Option<TemperatureC10>,
 }
 
 impl HeaterCommand {
-    /// Create an off command
+    /// Create an off command for the first heater
     pub const fn off() -> Self {
         Self {
-            target_temp_c: None,
+            heater: 0,
+            target: None,
         }
     }
 
-    /// Create a heating command
-    pub const fn heating(temp_c: i16) -> Self {
+    /// Create a heating command for the first heater
+    pub const fn heating(target: TemperatureC10) -> Self {
         Self {
-            target_temp_c: Some(temp_c),
+            heater: 0,
+            target: Some(target),
+        }
+    }
+
+    /// The same command for another heater
+    pub const fn for_heater(self, heater: u8) -> Self {
+        Self { heater, ..self }
+    }
+}
+
+/// Heater command for the next jar, issued ahead of the basket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PrewarmCommand<'a> {
+    /// Heater named by the next jar
+    pub heater: &'a str,
+    /// Command for that heater
+    pub command: HeaterCommand,
+}
+
+/// Accessory on/off command from scheduler
+///
+/// Accessories are switched by phase rather than by profile: they run
+/// while the basket is working in the jar and stop for everything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AccessoryCommand {
+    /// Accessory should be running
+    pub on: bool,
+}
+
+impl AccessoryCommand {
+    /// Create an off command
+    pub const fn off() -> Self {
+        Self { on: false }
+    }
+
+    /// Create an on command
+    pub const fn on() -> Self {
+        Self { on: true }
+    }
+
+    /// Command for an execution phase
+    fn for_phase(phase: ExecutionPhase) -> Self {
+        if phase == ExecutionPhase::Running {
+            Self::on()
+        } else {
+            Self::off()
         }
     }
 }
@@ -102,8 +235,12 @@ pub struct StepState {
     pub jar_index: u8,
     /// Profile index for current step
     pub profile_index: u8,
+    /// Heater warming the current jar (None = unheated)
+    pub heater: Option<u8>,
     /// Segments for current step
     pub segments: Vec<Segment, MAX_SEGMENTS>,
+    /// Leading segments that prime the jar with the heater off
+    pub prime_segments: u8,
     /// Current segment index
     pub segment_index: u8,
     /// Elapsed time in current segment (seconds)
@@ -114,6 +251,10 @@ pub struct StepState {
     pub spinoff: Option<SpinOffConfig>,
     /// Spin-off elapsed time (seconds)
     pub spinoff_elapsed_s: u16,
+    /// Basket has been lifted clear of the jar for spin-off
+    pub lift_complete: bool,
+    /// Time waited since the lift completed (seconds)
+    pub spinoff_dwell_s: u16,
 }
 
 impl Default for StepState {
@@ -123,12 +264,16 @@ impl Default for StepState {
             total_steps: 0,
             jar_index: 0,
             profile_index: 0,
+            heater: None,
             segments: Vec::new(),
+            prime_segments: 0,
             segment_index: 0,
             segment_elapsed_s: 0,
             step_elapsed_s: 0,
             spinoff: None,
             spinoff_elapsed_s: 0,
+            lift_complete: false,
+            spinoff_dwell_s: 0,
         }
     }
 }
@@ -140,20 +285,44 @@ impl Default for StepState {
 pub struct Scheduler {
     /// Current execution phase
     phase: ExecutionPhase,
+    /// Phase that was interrupted by the current pause
+    paused_from: ExecutionPhase,
     /// Machine capabilities (determines automated vs manual flow)
     capabilities: MachineCapabilities,
     /// Current step state
     step: StepState,
     /// Current program config (copied for duration of execution)
     program: Option<ProgramConfig>,
+    /// Planned time of the steps already finished (seconds)
+    completed_s: u32,
     /// Available profiles (referenced by name)
     profiles: Vec<ProfileConfig, MAX_PROFILES>,
     /// Available jars (referenced by name)
     jars: Vec<JarConfig, MAX_JARS>,
+    /// Configured heaters, in index order
+    heaters: Vec<HeaterConfig, MAX_HEATERS>,
     /// Motor command state (preserved during pause)
     motor_cmd: MotorCommand,
     /// Heater command state
     heater_cmd: HeaterCommand,
+    /// Hardware heater ceiling (°C)
+    heater_max_c: i16,
+    /// Motor behavior when stopped mid-program
+    stop_behavior: StopBehavior,
+    /// Machine spin-off RPM ceiling
+    max_spinoff_rpm: Option<u16>,
+    /// Warm the next jar's heater while the basket travels to it
+    prewarm: bool,
+    /// Basket orientation between steps on manual machines (degrees)
+    park_angle_deg: Option<u16>,
+    /// Prompt for the first jar on manual machines instead of starting
+    prompt_first_jar: bool,
+    /// Balancing spin at program end (None = off)
+    balance: Option<BalanceConfig>,
+    /// Net rotation of the program so far (RPM-seconds, positive clockwise)
+    net_rpm_s: i32,
+    /// Time left in the balancing spin (seconds)
+    balance_remaining_s: u16,
 }
 
 impl Scheduler {
@@ -161,16 +330,96 @@ impl Scheduler {
     pub fn new(capabilities: MachineCapabilities) -> Self {
         Self {
             phase: ExecutionPhase::Idle,
+            paused_from: ExecutionPhase::Idle,
             capabilities,
             step: StepState::default(),
             program: None,
+            completed_s: 0,
             profiles: Vec::new(),
             jars: Vec::new(),
+            heaters: Vec::new(),
             motor_cmd: MotorCommand::stopped(),
             heater_cmd: HeaterCommand::off(),
+            heater_max_c: MAX_TEMPERATURE_C,
+            stop_behavior: StopBehavior::Coast,
+            max_spinoff_rpm: None,
+            prewarm: false,
+            park_angle_deg: None,
+            prompt_first_jar: false,
+            balance: None,
+            net_rpm_s: 0,
+            balance_remaining_s: 0,
         }
     }
 
+    /// Set how the motor stops between steps
+    pub fn set_stop_behavior(&mut self, behavior: StopBehavior) {
+        self.stop_behavior = behavior;
+    }
+
+    /// Set the hardware heater ceiling
+    ///
+    /// Profile targets are always clamped to this, regardless of the
+    /// profile's own `max_temp_c`.
+    pub fn set_heater_max_temp(&mut self, max_c: i16) {
+        self.heater_max_c = max_c;
+    }
+
+    /// Set the machine spin-off RPM ceiling
+    ///
+    /// Spin-off speeds are always clamped to this, regardless of profile.
+    pub fn set_max_spinoff_rpm(&mut self, max_rpm: Option<u16>) {
+        self.max_spinoff_rpm = max_rpm;
+    }
+
+    /// Enable heater pre-warm for the next jar
+    ///
+    /// See [`Scheduler::prewarm_command`].
+    pub fn set_prewarm(&mut self, enabled: bool) {
+        self.prewarm = enabled;
+    }
+
+    /// Set the basket orientation between steps on manual machines
+    ///
+    /// See [`Scheduler::park_orientation`].
+    pub fn set_park_angle(&mut self, angle_deg: Option<u16>) {
+        self.park_angle_deg = angle_deg;
+    }
+
+    /// Prompt for the first jar on manual machines
+    ///
+    /// When set, a program's first step waits in `AwaitingJar` for the
+    /// user to confirm the basket is in place, like every later step.
+    /// Otherwise the first step starts as soon as the program does.
+    pub fn set_prompt_first_jar(&mut self, enabled: bool) {
+        self.prompt_first_jar = enabled;
+    }
+
+    /// Balance the program's net rotation at its end
+    ///
+    /// See [`BalanceConfig`]. None turns balancing off.
+    pub fn set_balance(&mut self, balance: Option<BalanceConfig>) {
+        self.balance = balance;
+    }
+
+    /// Net rotation of the current program (RPM-seconds, positive clockwise)
+    ///
+    /// Counts every segment, spin-off and balancing spin run so far;
+    /// 60 RPM-seconds is one revolution.
+    pub fn net_rotation_rpm_s(&self) -> i32 {
+        self.net_rpm_s
+    }
+
+    /// Count `seconds` of rotation under `command`
+    fn record_rotation(&mut self, command: MotorCommand, seconds: u16) {
+        let rpm_s = command.rpm as i32 * seconds as i32;
+        let signed = match command.direction {
+            Direction::Clockwise => rpm_s,
+            Direction::CounterClockwise => -rpm_s,
+        };
+        self.net_rpm_s = self.net_rpm_s.saturating_add(signed);
+    }
+
     /// Load available profiles
     pub fn load_profiles(&mut self, profiles: &[ProfileConfig]) {
         self.profiles.clear();
@@ -187,32 +436,135 @@ impl Scheduler {
         }
     }
 
+    /// Load configured heaters
+    ///
+    /// A jar's `heater` field is looked up here; a heater's index is its
+    /// position in `heaters`. Each heater's `max_temp` also clamps the
+    /// profile targets sent to it.
+    pub fn load_heaters(&mut self, heaters: &[HeaterConfig]) {
+        self.heaters.clear();
+        for h in heaters.iter().take(MAX_HEATERS) {
+            let _ = self.heaters.push(h.clone());
+        }
+    }
+
+    /// Machine capabilities this scheduler was created with
+    pub fn capabilities(&self) -> &MachineCapabilities {
+        &self.capabilities
+    }
+
     /// Get current execution phase
     pub fn phase(&self) -> ExecutionPhase {
         self.phase
     }
 
     /// Get current motor command
+    ///
+    /// While a program is active but the motor is stopped (between steps,
+    /// paused, awaiting the user), the command holds the motor if the stop
+    /// behavior is `Brake`. Once idle or complete the motor is released.
     pub fn motor_command(&self) -> MotorCommand {
-        if matches!(
-            self.phase,
-            ExecutionPhase::Running | ExecutionPhase::SpinOff
-        ) {
-            self.motor_cmd
+        match self.phase {
+            ExecutionPhase::Running => self.motor_cmd,
+            ExecutionPhase::SpinOff if self.spinoff_ready() => self.motor_cmd,
+            ExecutionPhase::Balancing => self.motor_cmd,
+            ExecutionPhase::Idle | ExecutionPhase::Complete => MotorCommand::stopped(),
+            _ => self.stop_command(),
+        }
+    }
+
+    /// Stopped command according to the configured stop behavior
+    fn stop_command(&self) -> MotorCommand {
+        match self.stop_behavior {
+            StopBehavior::Coast => MotorCommand::stopped(),
+            StopBehavior::Brake => MotorCommand::holding(),
+        }
+    }
+
+    /// Motor command for a running segment
+    ///
+    /// Zero-RPM soak segments stop according to the stop behavior.
+    fn segment_command(&self, seg: &Segment) -> MotorCommand {
+        if seg.rpm == 0 {
+            self.stop_command()
         } else {
-            MotorCommand::stopped()
+            MotorCommand::running(seg.rpm, seg.direction)
+        }
+    }
+
+    /// Notify that the basket has been lifted clear for spin-off
+    ///
+    /// On automated machines the spin-off motor stays stopped until this
+    /// is called and `pre_spinoff_delay_s` has elapsed afterwards.
+    pub fn lift_complete(&mut self) {
+        if self.phase == ExecutionPhase::SpinOff {
+            self.step.lift_complete = true;
+        }
+    }
+
+    /// Check if the spin-off motor may run
+    ///
+    /// Manual machines rely on the user lifting the basket, so they are
+    /// ready as soon as spin-off is confirmed.
+    fn spinoff_ready(&self) -> bool {
+        if !self.capabilities.is_automated {
+            return true;
         }
+        let delay_s = self
+            .step
+            .spinoff
+            .map(|s| s.pre_spinoff_delay_s)
+            .unwrap_or(0);
+        self.step.lift_complete && self.step.spinoff_dwell_s >= delay_s
     }
 
     /// Get current heater command
+    ///
+    /// Addressed to the current jar's heater. The heater stays off while
+    /// priming.
     pub fn heater_command(&self) -> HeaterCommand {
-        if self.phase == ExecutionPhase::Running {
+        if self.phase == ExecutionPhase::Running && !self.is_priming() {
             self.heater_cmd
         } else {
-            HeaterCommand::off()
+            HeaterCommand::off().for_heater(self.heater_cmd.heater)
+        }
+    }
+
+    /// Get the command for one heater
+    ///
+    /// Heaters other than the current jar's are held off, except the next
+    /// jar's heater while it pre-warms (see [`Scheduler::prewarm_command`]).
+    /// A target for the current jar wins over pre-warming the same heater.
+    pub fn heater_command_for(&self, heater: u8) -> HeaterCommand {
+        let cmd = self.heater_command();
+        let current = cmd.heater == heater;
+        if current && cmd.target.is_some() {
+            return cmd;
+        }
+        match self.prewarm_command() {
+            Some(prewarm) if prewarm.command.heater == heater => prewarm.command,
+            _ if current => cmd,
+            _ => HeaterCommand::off().for_heater(heater),
+        }
+    }
+
+    /// Get the command for the ultrasonic module
+    ///
+    /// On only while the basket works in a jar that references one, and
+    /// off for everything else: other jars, an abort or idle. Sending it
+    /// always therefore switches off a module left running.
+    pub fn ultrasonic_command(&self) -> AccessoryCommand {
+        match self.current_jar() {
+            Some(jar) if jar.ultrasonic.is_some() => AccessoryCommand::for_phase(self.phase),
+            _ => AccessoryCommand::off(),
         }
     }
 
+    /// Check if the current step is running its prime segment
+    pub fn is_priming(&self) -> bool {
+        self.phase == ExecutionPhase::Running && self.step.segment_index < self.step.prime_segments
+    }
+
     /// Get current step state (if running)
     pub fn step_state(&self) -> Option<&StepState> {
         if self.phase != ExecutionPhase::Idle && self.phase != ExecutionPhase::Complete {
@@ -224,13 +576,18 @@ impl Scheduler {
 
     /// Start executing a program
     ///
-    /// Returns the first event to send (or None if start failed)
+    /// Returns the first event to send, or None once running. A program
+    /// with no steps, or with a step that can't run (missing jar or
+    /// profile, or a profile with no runnable segments), is rejected up
+    /// front and the phase stays `Idle`.
     pub fn start_program(&mut self, program: ProgramConfig) -> Option<Event> {
-        if program.steps.is_empty() {
+        if program.steps.is_empty() || !program.steps.iter().all(|s| self.step_runnable(s)) {
             return None;
         }
 
         self.program = Some(program);
+        self.completed_s = 0;
+        self.net_rpm_s = 0;
         self.step = StepState::default();
 
         // Try to start the first step
@@ -247,6 +604,13 @@ impl Scheduler {
             return Some(Event::ProgramFinished);
         }
 
+        // Decided before the step state is replaced below
+        let transition = if step_index > 0 {
+            self.transition_to(step_index)
+        } else {
+            None
+        };
+
         let step = &program.steps[step_index as usize];
 
         // Find profile and jar by name
@@ -254,14 +618,10 @@ impl Scheduler {
         let jar_index = self.find_jar(&step.jar)?;
 
         let profile = &self.profiles[profile_index as usize];
+        let heater = self.jar_heater(&self.jars[jar_index as usize]);
 
         // Generate segments for this profile
-        let segments = generate_segments(
-            profile.rpm,
-            profile.time_s,
-            profile.direction,
-            profile.iterations,
-        )?;
+        let segments = profile_segments(profile).ok()?;
 
         // Setup step state
         self.step = StepState {
@@ -269,36 +629,169 @@ impl Scheduler {
             total_steps: program.steps.len() as u8,
             jar_index,
             profile_index,
+            heater,
             segments,
+            prime_segments: profile.prime.is_some() as u8,
             segment_index: 0,
             segment_elapsed_s: 0,
             step_elapsed_s: 0,
             spinoff: profile.spinoff,
             spinoff_elapsed_s: 0,
+            lift_complete: false,
+            spinoff_dwell_s: 0,
         };
 
         // Setup motor command from first segment
         if let Some(seg) = self.step.segments.first() {
-            self.motor_cmd = MotorCommand::running(seg.rpm, seg.direction);
+            self.motor_cmd = self.segment_command(seg);
         }
 
-        // Setup heater command if profile has temperature target
-        if let Some(temp) = profile.temperature_c {
-            self.heater_cmd = HeaterCommand::heating(temp);
-        } else {
-            self.heater_cmd = HeaterCommand::off();
-        }
+        self.heater_cmd = self.profile_heater_command(profile, heater);
 
         // For manual machines, prompt user to move to jar first
-        if !self.capabilities.is_automated && step_index > 0 {
-            self.phase = ExecutionPhase::AwaitingJar;
-            return Some(Event::PromptNextJar);
+        if !self.capabilities.is_automated {
+            if step_index == 0 && self.prompt_first_jar {
+                self.phase = ExecutionPhase::AwaitingJar;
+                return Some(Event::PromptNextJar);
+            }
+            if let Some(transition) = transition {
+                if transition.needs_motion() {
+                    self.phase = ExecutionPhase::AwaitingJar;
+                    return Some(Event::PromptNextJar);
+                }
+                // Basket is already in place
+                self.phase = ExecutionPhase::Running;
+                return Some(Event::NextStep);
+            }
         }
 
         self.phase = ExecutionPhase::Running;
         None
     }
 
+    /// Resolve the heater warming a jar
+    ///
+    /// A jar names its heater; with no heaters loaded, any name means the
+    /// only heater. A jar that names none uses the only heater on a
+    /// single-heater machine and is unheated on one with several. Machines
+    /// without a heater never heat.
+    fn jar_heater(&self, jar: &JarConfig) -> Option<u8> {
+        if !self.capabilities.has_heater {
+            return None;
+        }
+        match &jar.heater {
+            Some(_) if self.heaters.is_empty() => Some(0),
+            Some(name) => self
+                .heaters
+                .iter()
+                .position(|h| h.name == *name)
+                .map(|i| i as u8),
+            None if self.heaters.len() <= 1 => Some(0),
+            None => None,
+        }
+    }
+
+    /// Heater command for a profile's temperature target
+    ///
+    /// Clamped to both the hardware ceiling and the heater's own
+    /// `max_temp`. Unheated jars ignore profile temperatures.
+    fn profile_heater_command(&self, profile: &ProfileConfig, heater: Option<u8>) -> HeaterCommand {
+        let Some(heater) = heater else {
+            return HeaterCommand::off();
+        };
+        let max_c = self
+            .heaters
+            .get(heater as usize)
+            .map_or(self.heater_max_c, |h| h.max_temp.min(self.heater_max_c));
+        match profile.effective_temp_c(max_c) {
+            Some(temp) => HeaterCommand::heating(TemperatureC10::from_whole(temp)),
+            None => HeaterCommand::off(),
+        }
+        .for_heater(heater)
+    }
+
+    /// Get the pre-warm command for the next jar's heater
+    ///
+    /// On automated machines with pre-warm enabled, the next jar's heater
+    /// starts heating to the next profile's target while the basket is
+    /// lifted for spin-off and carried over, so the jar is up to
+    /// temperature on arrival. Returns `None` if the next step stays in
+    /// the same jar, the next jar has no heater, or the next profile is
+    /// unheated.
+    pub fn prewarm_command(&self) -> Option<PrewarmCommand<'_>> {
+        if !self.prewarm || !self.capabilities.is_automated {
+            return None;
+        }
+        if !matches!(
+            self.phase,
+            ExecutionPhase::SpinOff | ExecutionPhase::StepComplete
+        ) || !self.next_jar_differs()
+        {
+            return None;
+        }
+
+        let next = self
+            .program
+            .as_ref()?
+            .steps
+            .get(self.step.step_index as usize + 1)?;
+        let jar = &self.jars[self.find_jar(&next.jar)? as usize];
+        let heater = jar.heater.as_ref()?;
+        let profile = &self.profiles[self.find_profile(&next.profile)? as usize];
+        let command = self.profile_heater_command(profile, self.jar_heater(jar));
+        command.target?;
+
+        Some(PrewarmCommand {
+            heater: heater.as_str(),
+            command,
+        })
+    }
+
+    /// Get the angle to turn the basket to before the next-jar prompt
+    ///
+    /// On manual machines with a park angle configured, the basket is
+    /// turned to a fixed orientation once a step completes so the user can
+    /// reach the parts. Returns `None` on automated machines, without a
+    /// park angle, or when no step has just completed.
+    pub fn park_orientation(&self) -> Option<u16> {
+        if self.capabilities.is_automated || self.phase != ExecutionPhase::StepComplete {
+            return None;
+        }
+        self.park_angle_deg
+    }
+
+    /// Whether the next program step uses a different jar
+    ///
+    /// False when there is no next step.
+    pub fn next_jar_differs(&self) -> bool {
+        self.next_step_transition() == Some(StepTransition::ChangeJar)
+    }
+
+    /// Basket movement needed to go from the current step to the next
+    ///
+    /// Considers both the jar and whether the current profile lifts the
+    /// basket for spin-off. Returns `None` when there is no next step.
+    pub fn next_step_transition(&self) -> Option<StepTransition> {
+        if self.phase == ExecutionPhase::Idle || self.phase == ExecutionPhase::Complete {
+            return None;
+        }
+        self.transition_to(self.step.step_index + 1)
+    }
+
+    /// Basket movement from the current step to `step_index`
+    fn transition_to(&self, step_index: u8) -> Option<StepTransition> {
+        let program = self.program.as_ref()?;
+        let next = program.steps.get(step_index as usize)?;
+
+        if self.find_jar(&next.jar) != Some(self.step.jar_index) {
+            Some(StepTransition::ChangeJar)
+        } else if self.step.spinoff.is_some() {
+            Some(StepTransition::Reposition)
+        } else {
+            Some(StepTransition::Stay)
+        }
+    }
+
     /// Find profile index by name
     fn find_profile(&self, name: &str) -> Option<u8> {
         self.profiles
@@ -339,17 +832,27 @@ impl Scheduler {
         match self.phase {
             ExecutionPhase::Running => self.tick_running(elapsed_s),
             ExecutionPhase::SpinOff => self.tick_spinoff(elapsed_s),
+            ExecutionPhase::Balancing => self.tick_balancing(elapsed_s),
             _ => None,
         }
     }
 
     /// Tick while in Running phase
     fn tick_running(&mut self, elapsed_s: u16) -> Option<Event> {
+        // Rotation past the end of the segment isn't counted
+        let segment = *self.step.segments.get(self.step.segment_index as usize)?;
+        let remaining_s = segment
+            .duration_s
+            .saturating_sub(self.step.segment_elapsed_s);
+        self.record_rotation(
+            MotorCommand::running(segment.rpm, segment.direction),
+            elapsed_s.min(remaining_s),
+        );
+
         self.step.segment_elapsed_s += elapsed_s;
         self.step.step_elapsed_s += elapsed_s as u32;
 
         // Check if current segment is complete
-        let segment = self.step.segments.get(self.step.segment_index as usize)?;
         if self.step.segment_elapsed_s >= segment.duration_s {
             // Move to next segment
             self.step.segment_index += 1;
@@ -357,7 +860,7 @@ impl Scheduler {
 
             if let Some(next_seg) = self.step.segments.get(self.step.segment_index as usize) {
                 // Update motor command for new segment
-                self.motor_cmd = MotorCommand::running(next_seg.rpm, next_seg.direction);
+                self.motor_cmd = self.segment_command(next_seg);
             } else {
                 // All segments done, check for spin-off
                 return self.finish_profile();
@@ -372,7 +875,11 @@ impl Scheduler {
         // Check if spin-off is configured
         if let Some(spinoff) = self.step.spinoff {
             // Setup for spin-off phase
-            self.motor_cmd = MotorCommand::running(spinoff.rpm, Direction::Clockwise);
+            let rpm = match self.max_spinoff_rpm {
+                Some(max_rpm) => spinoff.rpm.min(max_rpm),
+                None => spinoff.rpm,
+            };
+            self.motor_cmd = MotorCommand::running(rpm, Direction::Clockwise);
             self.heater_cmd = HeaterCommand::off(); // No heating during spin-off
 
             if self.capabilities.is_automated {
@@ -392,6 +899,18 @@ impl Scheduler {
 
     /// Tick while in SpinOff phase
     fn tick_spinoff(&mut self, elapsed_s: u16) -> Option<Event> {
+        if !self.spinoff_ready() {
+            // Waiting for the lift, then dwelling before spinning
+            if self.step.lift_complete {
+                self.step.spinoff_dwell_s = self.step.spinoff_dwell_s.saturating_add(elapsed_s);
+            }
+            return None;
+        }
+
+        if let Some(spinoff) = self.step.spinoff {
+            let remaining_s = spinoff.time_s.saturating_sub(self.step.spinoff_elapsed_s);
+            self.record_rotation(self.motor_cmd, elapsed_s.min(remaining_s));
+        }
         self.step.spinoff_elapsed_s += elapsed_s;
 
         if let Some(spinoff) = self.step.spinoff {
@@ -404,6 +923,19 @@ impl Scheduler {
         None
     }
 
+    /// Tick while in Balancing phase
+    fn tick_balancing(&mut self, elapsed_s: u16) -> Option<Event> {
+        let spun_s = elapsed_s.min(self.balance_remaining_s);
+        self.record_rotation(self.motor_cmd, spun_s);
+        self.balance_remaining_s -= spun_s;
+        if self.balance_remaining_s > 0 {
+            return None;
+        }
+        self.motor_cmd = MotorCommand::stopped();
+        self.phase = ExecutionPhase::Complete;
+        Some(Event::ProgramFinished)
+    }
+
     /// Handle spin-off completion
     fn finish_spinoff(&mut self) -> Option<Event> {
         self.motor_cmd = MotorCommand::stopped();
@@ -414,12 +946,22 @@ impl Scheduler {
     fn finish_step(&mut self) -> Option<Event> {
         self.motor_cmd = MotorCommand::stopped();
         self.heater_cmd = HeaterCommand::off();
+        self.completed_s += self.step_total_s();
 
         let next_step = self.step.step_index + 1;
         let program = self.program.as_ref()?;
 
         if next_step as usize >= program.steps.len() {
-            // All steps complete
+            // All steps complete, once the net rotation is balanced
+            if let Some(seg) = self
+                .balance
+                .and_then(|balance| balance_segment(self.net_rpm_s, balance))
+            {
+                self.motor_cmd = MotorCommand::running(seg.rpm, seg.direction);
+                self.balance_remaining_s = seg.duration_s;
+                self.phase = ExecutionPhase::Balancing;
+                return None;
+            }
             self.phase = ExecutionPhase::Complete;
             return Some(Event::ProgramFinished);
         }
@@ -463,9 +1005,13 @@ impl Scheduler {
 
     /// Pause execution
     ///
-    /// Motor command is preserved for resume.
+    /// Motor command and the interrupted phase are preserved for resume.
     pub fn pause(&mut self) -> bool {
-        if self.phase == ExecutionPhase::Running || self.phase == ExecutionPhase::SpinOff {
+        if matches!(
+            self.phase,
+            ExecutionPhase::Running | ExecutionPhase::SpinOff | ExecutionPhase::Balancing
+        ) {
+            self.paused_from = self.phase;
             self.phase = ExecutionPhase::Paused;
             true
         } else {
@@ -473,15 +1019,10 @@ impl Scheduler {
         }
     }
 
-    /// Resume execution
+    /// Resume execution in the phase that was paused
     pub fn resume(&mut self) -> bool {
         if self.phase == ExecutionPhase::Paused {
-            // Restore to running or spinoff based on whether we have spinoff time
-            if self.step.spinoff.is_some() && self.step.spinoff_elapsed_s > 0 {
-                self.phase = ExecutionPhase::SpinOff;
-            } else {
-                self.phase = ExecutionPhase::Running;
-            }
+            self.phase = self.paused_from;
             true
         } else {
             false
@@ -491,17 +1032,70 @@ impl Scheduler {
     /// Abort execution
     pub fn abort(&mut self) {
         self.phase = ExecutionPhase::Idle;
+        self.paused_from = ExecutionPhase::Idle;
         self.motor_cmd = MotorCommand::stopped();
         self.heater_cmd = HeaterCommand::off();
         self.program = None;
+        self.completed_s = 0;
+        self.net_rpm_s = 0;
+        self.balance_remaining_s = 0;
         self.step = StepState::default();
     }
 
-    /// Get total elapsed time for current program (seconds)
-    pub fn total_elapsed_s(&self) -> u32 {
-        // Sum of completed steps plus current step
-        // (simplified: just return current step elapsed)
-        self.step.step_elapsed_s
+    /// Get elapsed time for the current program (seconds)
+    ///
+    /// Finished steps count with their planned duration, so the value
+    /// never runs ahead of [`program_total_s`](Self::program_total_s)
+    /// because of tick granularity.
+    pub fn program_elapsed_s(&self) -> u32 {
+        if self.program.is_none() {
+            return 0;
+        }
+        if matches!(
+            self.phase,
+            ExecutionPhase::StepComplete | ExecutionPhase::Balancing | ExecutionPhase::Complete
+        ) {
+            // The finished step is already in `completed_s`
+            return self.completed_s;
+        }
+        let step_elapsed = self.step.step_elapsed_s + self.step.spinoff_elapsed_s as u32;
+        self.completed_s + step_elapsed.min(self.step_total_s())
+    }
+
+    /// Get planned total time for the current program (seconds)
+    ///
+    /// Sum of every step's profile and spin-off time. Steps whose profile
+    /// can't be found count as zero.
+    pub fn program_total_s(&self) -> u32 {
+        self.program
+            .as_ref()
+            .map(|p| p.steps.iter().map(|s| self.planned_step_s(s)).sum())
+            .unwrap_or(0)
+    }
+
+    /// Check that a program step's jar and profile exist and the profile
+    /// produces segments
+    fn step_runnable(&self, step: &ProgramStep) -> bool {
+        self.find_jar(&step.jar).is_some()
+            && self
+                .find_profile(&step.profile)
+                .and_then(|i| self.profiles.get(i as usize))
+                .is_some_and(|p| profile_segments(p).is_ok())
+    }
+
+    /// Planned time for one program step (seconds)
+    fn planned_step_s(&self, step: &ProgramStep) -> u32 {
+        let Some(profile) = self
+            .find_profile(&step.profile)
+            .and_then(|i| self.profiles.get(i as usize))
+        else {
+            return 0;
+        };
+        let profile_time: u32 = profile_segments(profile)
+            .map(|segs| segs.iter().map(|s| s.duration_s as u32).sum())
+            .unwrap_or(0);
+        let spinoff_time = profile.spinoff.map(|s| s.time_s as u32).unwrap_or(0);
+        profile_time + spinoff_time
     }
 
     /// Get remaining time for current segment (seconds)
@@ -519,6 +1113,31 @@ impl Scheduler {
         let spinoff_time = self.step.spinoff.map(|s| s.time_s as u32).unwrap_or(0);
         profile_time + spinoff_time
     }
+
+    /// Get the current iteration of the step's profile and the total
+    ///
+    /// Returns (current, total), 1-based. An alternating profile iterates
+    /// once per CW/CCW pair and a soak profile once per spin/soak cycle;
+    /// a continuous profile is a single iteration. The prime counts as
+    /// part of the first. (0, 0) when no program is running.
+    pub fn current_iteration(&self) -> (u8, u8) {
+        let Some(profile) = self.current_profile() else {
+            return (0, 0);
+        };
+        let per_iteration =
+            if profile.soak.is_some() || profile.direction == DirectionMode::Alternate {
+                2
+            } else {
+                1
+            };
+        let segments = self.step.segments.len() as u8 - self.step.prime_segments;
+        let total = (segments / per_iteration).max(1);
+        let index = self
+            .step
+            .segment_index
+            .saturating_sub(self.step.prime_segments);
+        ((index / per_iteration + 1).min(total), total)
+    }
 }
 
 impl Default for Scheduler {
@@ -527,9 +1146,37 @@ impl Default for Scheduler {
     }
 }
 
+/// Generate the segments a profile runs, prime first
+///
+/// A profile that would run for zero seconds in total is rejected, so a
+/// step can never complete the moment it starts.
+pub fn profile_segments(
+    profile: &ProfileConfig,
+) -> Result<Vec<Segment, MAX_SEGMENTS>, SegmentError> {
+    let mut segments = match profile.soak {
+        Some(soak) => generate_soak_segments(profile.rpm, profile.direction, soak)?,
+        None => generate_segments(
+            profile.rpm,
+            profile.time_s,
+            profile.direction,
+            profile.iterations,
+        )?,
+    };
+    if let Some(prime) = profile.prime {
+        prepend_prime(&mut segments, profile.rpm, prime)?;
+    }
+    if segments.iter().all(|s| s.duration_s == 0) {
+        return Err(SegmentError::TooShort {
+            duration_s: 0,
+            min_s: 1,
+        });
+    }
+    Ok(segments)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::super::segment::DirectionMode;
+    use super::super::segment::{DirectionMode, PrimeConfig, SoakConfig};
     use super::*;
     use heapless::String;
 
@@ -546,6 +1193,10 @@ mod tests {
         }
     }
 
+    fn heated_machine() -> MachineCapabilities {
+        MachineCapabilities::from_config(false, false, false, 1)
+    }
+
     fn make_jar(name: &str) -> JarConfig {
         let mut jar_name = String::new();
         let _ = jar_name.push_str(name);
@@ -572,6 +1223,7 @@ mod tests {
         }
         ProgramConfig {
             label,
+            notes: None,
             steps: step_vec,
         }
     }
@@ -632,39 +1284,207 @@ mod tests {
         assert_eq!(sched.phase(), ExecutionPhase::Complete);
     }
 
+    /// Tick one second at a time until the program finishes
+    fn run_to_end(sched: &mut Scheduler) {
+        for _ in 0..10_000 {
+            match sched.tick(1) {
+                Some(Event::NextStep) => {
+                    sched.advance_step();
+                }
+                Some(Event::StartSpinOff) => sched.lift_complete(),
+                Some(Event::ProgramFinished) => return,
+                _ => {}
+            }
+        }
+        panic!("program never finished");
+    }
+
     #[test]
-    fn test_pause_resume() {
+    fn test_balancing_cancels_net_rotation() {
         let mut sched = Scheduler::new(MachineCapabilities {
             is_automated: true,
             ..Default::default()
         });
-
-        let profiles = [make_profile("Clean", 120, 60, DirectionMode::Clockwise)];
-        let jars = [make_jar("clean")];
+        let mut rinse = make_profile("Rinse", 60, 30, DirectionMode::CounterClockwise);
+        rinse.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 10,
+            pre_spinoff_delay_s: 0,
+        });
+        let profiles = [
+            make_profile("Clean", 120, 60, DirectionMode::Clockwise),
+            make_profile("Mix", 90, 120, DirectionMode::Alternate),
+            rinse,
+        ];
+        let jars = [make_jar("clean"), make_jar("rinse")];
         sched.load_profiles(&profiles);
         sched.load_jars(&jars);
+        sched.set_balance(Some(BalanceConfig {
+            rpm: 60,
+            min_revs: 1,
+        }));
+        sched.start_program(make_program(
+            "Test",
+            &[("clean", "Clean"), ("clean", "Mix"), ("rinse", "Rinse")],
+        ));
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
 
-        let program = make_program("Test", &[("clean", "Clean")]);
-        sched.start_program(program);
+        // Tick through the last step's spin-off
+        for _ in 0..10_000 {
+            match sched.tick(1) {
+                Some(Event::NextStep) => {
+                    sched.advance_step();
+                }
+                Some(Event::StartSpinOff) => sched.lift_complete(),
+                Some(event) => panic!("unexpected {:?}", event),
+                None if sched.phase() == ExecutionPhase::Balancing => break,
+                None => {}
+            }
+        }
+        assert_eq!(sched.phase(), ExecutionPhase::Balancing);
 
-        // Pause
-        assert!(sched.pause());
-        assert_eq!(sched.phase(), ExecutionPhase::Paused);
+        // 120 RPM x 60 s + 150 RPM x 10 s clockwise, less 60 RPM x 30 s:
+        // 6900 RPM-seconds (115 revolutions) of clockwise bias
+        assert_eq!(sched.net_rotation_rpm_s(), 6_900);
+        assert_eq!(
+            sched.motor_command(),
+            MotorCommand::running(60, Direction::CounterClockwise)
+        );
+
+        run_to_end(&mut sched);
+        assert_eq!(sched.phase(), ExecutionPhase::Complete);
         assert_eq!(sched.motor_command(), MotorCommand::stopped());
+        // Cancelled to within a second of the balancing spin
+        assert!(sched.net_rotation_rpm_s().abs() < 60);
+    }
 
-        // Resume
-        assert!(sched.resume());
-        assert_eq!(sched.phase(), ExecutionPhase::Running);
-        assert_eq!(sched.motor_command().rpm, 120);
+    #[test]
+    fn test_phase_byte_roundtrip() {
+        for byte in 0..9 {
+            let phase = ExecutionPhase::from_byte(byte).unwrap();
+            assert_eq!(phase.to_byte(), byte);
+        }
+        assert_eq!(ExecutionPhase::from_byte(9), None);
     }
 
     #[test]
-    fn test_manual_machine_flow() {
+    fn test_balancing_skipped() {
+        let profiles = [
+            make_profile("Clean", 120, 60, DirectionMode::Clockwise),
+            make_profile("Back", 120, 60, DirectionMode::CounterClockwise),
+        ];
+        let jars = [make_jar("clean")];
         let mut sched = Scheduler::new(MachineCapabilities {
-            is_automated: false, // Manual machine
+            is_automated: true,
             ..Default::default()
         });
-
+        sched.load_profiles(&profiles);
+        sched.load_jars(&jars);
+
+        // Off by default
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        assert_eq!(sched.tick(60), Some(Event::ProgramFinished));
+        assert_eq!(sched.net_rotation_rpm_s(), 7_200);
+
+        // Already balanced: nothing to do
+        sched.set_balance(Some(BalanceConfig::default()));
+        sched.start_program(make_program(
+            "Test",
+            &[("clean", "Clean"), ("clean", "Back")],
+        ));
+        assert_eq!(sched.net_rotation_rpm_s(), 0);
+        assert_eq!(sched.tick(60), Some(Event::NextStep));
+        sched.advance_step();
+        assert_eq!(sched.tick(60), Some(Event::ProgramFinished));
+        assert_eq!(sched.net_rotation_rpm_s(), 0);
+    }
+
+    #[test]
+    fn test_program_time_tracking() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            has_z: true,
+            ..Default::default()
+        });
+
+        let mut rinse = make_profile("Rinse", 120, 20, DirectionMode::Clockwise);
+        rinse.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        let profiles = [
+            make_profile("Clean", 120, 30, DirectionMode::Clockwise),
+            rinse,
+        ];
+        let jars = [make_jar("clean"), make_jar("rinse")];
+        sched.load_profiles(&profiles);
+        sched.load_jars(&jars);
+        assert_eq!(sched.program_total_s(), 0);
+
+        sched.start_program(make_program(
+            "Test",
+            &[("clean", "Clean"), ("rinse", "Rinse")],
+        ));
+        assert_eq!(sched.program_total_s(), 55);
+        assert_eq!(sched.program_elapsed_s(), 0);
+
+        sched.tick(10);
+        assert_eq!(sched.program_elapsed_s(), 10);
+
+        // Overshooting a step counts only its planned time
+        assert_eq!(sched.tick(25), Some(Event::NextStep));
+        assert_eq!(sched.program_elapsed_s(), 30);
+        sched.advance_step();
+
+        sched.tick(20);
+        sched.lift_complete();
+        sched.tick(2);
+        assert_eq!(sched.program_elapsed_s(), 52);
+
+        assert_eq!(sched.tick(5), Some(Event::ProgramFinished));
+        assert_eq!(sched.program_elapsed_s(), 55);
+
+        sched.abort();
+        assert_eq!(sched.program_elapsed_s(), 0);
+        assert_eq!(sched.program_total_s(), 0);
+    }
+
+    #[test]
+    fn test_pause_resume() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+
+        let profiles = [make_profile("Clean", 120, 60, DirectionMode::Clockwise)];
+        let jars = [make_jar("clean")];
+        sched.load_profiles(&profiles);
+        sched.load_jars(&jars);
+
+        let program = make_program("Test", &[("clean", "Clean")]);
+        sched.start_program(program);
+
+        // Pause
+        assert!(sched.pause());
+        assert_eq!(sched.phase(), ExecutionPhase::Paused);
+        assert_eq!(sched.motor_command(), MotorCommand::stopped());
+
+        // Resume
+        assert!(sched.resume());
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        assert_eq!(sched.motor_command().rpm, 120);
+    }
+
+    #[test]
+    fn test_manual_machine_flow() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: false, // Manual machine
+            ..Default::default()
+        });
+
         let profiles = [
             make_profile("Clean", 120, 10, DirectionMode::Clockwise),
             make_profile("Rinse", 100, 10, DirectionMode::Clockwise),
@@ -684,6 +1504,397 @@ mod tests {
         assert_eq!(event, Some(Event::PromptNextJar));
     }
 
+    fn first_jar_scheduler(is_automated: bool, prompt_first_jar: bool) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated,
+            ..Default::default()
+        });
+        sched.set_prompt_first_jar(prompt_first_jar);
+        sched.load_profiles(&[make_profile("Clean", 120, 10, DirectionMode::Clockwise)]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched
+    }
+
+    #[test]
+    fn test_prompt_first_jar() {
+        let mut sched = first_jar_scheduler(false, true);
+
+        let event = sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        assert_eq!(event, Some(Event::PromptNextJar));
+        assert_eq!(sched.phase(), ExecutionPhase::AwaitingJar);
+        assert_eq!(sched.motor_command().rpm, 0);
+
+        // Confirming the jar starts the step
+        sched.user_confirm();
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        assert_eq!(sched.motor_command().rpm, 120);
+    }
+
+    #[test]
+    fn test_first_jar_starts_without_prompt() {
+        let mut sched = first_jar_scheduler(false, false);
+        let event = sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        assert_eq!(event, None);
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+
+        // Automated machines move the basket themselves
+        let mut sched = first_jar_scheduler(true, true);
+        let event = sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        assert_eq!(event, None);
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+    }
+
+    fn transition_scheduler(is_automated: bool, steps: &[(&str, &str)]) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated,
+            ..Default::default()
+        });
+
+        let mut dry = make_profile("Dry", 150, 10, DirectionMode::Clockwise);
+        dry.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        let profiles = [
+            make_profile("Clean", 120, 10, DirectionMode::Clockwise),
+            make_profile("Rinse", 100, 10, DirectionMode::Clockwise),
+            dry,
+        ];
+        let jars = [make_jar("clean"), make_jar("rinse")];
+        sched.load_profiles(&profiles);
+        sched.load_jars(&jars);
+        sched.start_program(make_program("Test", steps));
+        sched
+    }
+
+    #[test]
+    fn test_transition_different_jar() {
+        let sched = transition_scheduler(true, &[("clean", "Clean"), ("rinse", "Clean")]);
+        assert_eq!(
+            sched.next_step_transition(),
+            Some(StepTransition::ChangeJar)
+        );
+        assert!(sched.next_jar_differs());
+    }
+
+    #[test]
+    fn test_transition_same_jar_different_profile() {
+        let sched = transition_scheduler(true, &[("clean", "Clean"), ("clean", "Rinse")]);
+        assert_eq!(sched.next_step_transition(), Some(StepTransition::Stay));
+        assert!(!sched.next_jar_differs());
+    }
+
+    #[test]
+    fn test_transition_same_jar_after_spinoff() {
+        // Spin-off lifts the basket, so it has to go back down
+        let sched = transition_scheduler(true, &[("clean", "Dry"), ("clean", "Clean")]);
+        assert_eq!(
+            sched.next_step_transition(),
+            Some(StepTransition::Reposition)
+        );
+        assert!(!sched.next_jar_differs());
+    }
+
+    #[test]
+    fn test_transition_last_step() {
+        let sched = transition_scheduler(true, &[("clean", "Clean")]);
+        assert_eq!(sched.next_step_transition(), None);
+        assert!(!sched.next_jar_differs());
+
+        let idle = Scheduler::new(MachineCapabilities::default());
+        assert_eq!(idle.next_step_transition(), None);
+    }
+
+    #[test]
+    fn test_manual_same_jar_skips_prompt() {
+        let mut sched = transition_scheduler(false, &[("clean", "Clean"), ("clean", "Rinse")]);
+        assert_eq!(sched.tick(15), Some(Event::PromptNextJar));
+        assert_eq!(sched.phase(), ExecutionPhase::StepComplete);
+
+        assert_eq!(sched.advance_step(), Some(Event::NextStep));
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+    }
+
+    #[test]
+    fn test_manual_different_jar_prompts() {
+        let mut sched = transition_scheduler(false, &[("clean", "Clean"), ("rinse", "Rinse")]);
+        sched.tick(15);
+
+        assert_eq!(sched.advance_step(), Some(Event::PromptNextJar));
+        assert_eq!(sched.phase(), ExecutionPhase::AwaitingJar);
+    }
+
+    #[test]
+    fn test_park_orientation_on_manual_step_complete() {
+        let mut sched = transition_scheduler(false, &[("clean", "Clean"), ("rinse", "Rinse")]);
+        sched.set_park_angle(Some(90));
+        assert_eq!(sched.park_orientation(), None);
+
+        assert_eq!(sched.tick(15), Some(Event::PromptNextJar));
+        assert_eq!(sched.park_orientation(), Some(90));
+
+        sched.advance_step();
+        assert_eq!(sched.park_orientation(), None);
+
+        // Disabled
+        let mut sched = transition_scheduler(false, &[("clean", "Clean"), ("rinse", "Rinse")]);
+        sched.tick(15);
+        assert_eq!(sched.park_orientation(), None);
+
+        // Automated machines never stop to orient the basket
+        let mut sched = transition_scheduler(true, &[("clean", "Clean"), ("rinse", "Rinse")]);
+        sched.set_park_angle(Some(90));
+        assert_eq!(sched.tick(15), Some(Event::NextStep));
+        assert_eq!(sched.park_orientation(), None);
+    }
+
+    /// Automated, heated scheduler where only the "warm" jar has a heater
+    fn prewarm_scheduler(steps: &[(&str, &str)]) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities::from_config(true, true, false, 1));
+        sched.set_prewarm(true);
+
+        let mut dry = make_profile("Dry", 150, 10, DirectionMode::Clockwise);
+        dry.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        let mut heated = make_profile("Heated", 120, 10, DirectionMode::Clockwise);
+        heated.temperature_c = Some(40);
+        let mut warm = make_jar("warm");
+        let mut heater = String::new();
+        let _ = heater.push_str("jar_heater");
+        warm.heater = Some(heater);
+
+        sched.load_profiles(&[dry, heated]);
+        sched.load_jars(&[make_jar("clean"), warm, make_jar("rinse")]);
+        sched.start_program(make_program("Test", steps));
+        sched
+    }
+
+    #[test]
+    fn test_prewarm_next_jar_during_transition() {
+        let mut sched = prewarm_scheduler(&[("clean", "Dry"), ("warm", "Heated")]);
+        let heating = HeaterCommand::heating(TemperatureC10::from_whole(40));
+        assert_eq!(sched.prewarm_command(), None);
+
+        // Lifted for spin-off: the next jar starts warming
+        assert_eq!(sched.tick(10), Some(Event::StartSpinOff));
+        let prewarm = sched.prewarm_command().unwrap();
+        assert_eq!(prewarm.heater, "jar_heater");
+        assert_eq!(prewarm.command, heating);
+        // The current step's own heater command is unaffected, but the
+        // heater is sent the pre-warm target
+        assert_eq!(sched.heater_command(), HeaterCommand::off());
+        assert_eq!(sched.heater_command_for(0), heating);
+
+        // Still warming while the basket moves over
+        sched.lift_complete();
+        assert_eq!(sched.tick(5), Some(Event::NextStep));
+        assert_eq!(sched.phase(), ExecutionPhase::StepComplete);
+        assert_eq!(sched.prewarm_command().map(|p| p.command), Some(heating));
+
+        // Arrived: the step's heater command takes over
+        sched.advance_step();
+        assert_eq!(sched.prewarm_command(), None);
+        assert_eq!(sched.heater_command(), heating);
+    }
+
+    #[test]
+    fn test_prewarm_suppressed_without_heater() {
+        // Next jar has no heater
+        let mut sched = prewarm_scheduler(&[("clean", "Dry"), ("rinse", "Heated")]);
+        sched.tick(10);
+        assert_eq!(sched.phase(), ExecutionPhase::SpinOff);
+        assert_eq!(sched.prewarm_command(), None);
+
+        // Next profile is unheated
+        let mut sched = prewarm_scheduler(&[("clean", "Dry"), ("warm", "Dry")]);
+        sched.tick(10);
+        assert_eq!(sched.prewarm_command(), None);
+
+        // Pre-warm disabled
+        let mut sched = prewarm_scheduler(&[("clean", "Dry"), ("warm", "Heated")]);
+        sched.set_prewarm(false);
+        sched.tick(10);
+        assert_eq!(sched.prewarm_command(), None);
+    }
+
+    fn make_heater(name: &str, max_temp: i16) -> HeaterConfig {
+        let mut heater_name = String::new();
+        let _ = heater_name.push_str(name);
+        HeaterConfig {
+            name: heater_name,
+            max_temp,
+            ..Default::default()
+        }
+    }
+
+    fn heated_jar(name: &str, heater: &str) -> JarConfig {
+        let mut jar = make_jar(name);
+        let mut heater_name = String::new();
+        let _ = heater_name.push_str(heater);
+        jar.heater = Some(heater_name);
+        jar
+    }
+
+    /// Automated machine with a jar heater (0) and a dryer (1)
+    fn two_heater_scheduler(steps: &[(&str, &str)]) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities::from_config(true, true, false, 2));
+        let mut clean = make_profile("Clean", 120, 10, DirectionMode::Clockwise);
+        clean.temperature_c = Some(40);
+        let mut dry = make_profile("Dry", 150, 10, DirectionMode::Clockwise);
+        dry.temperature_c = Some(50);
+
+        sched.load_profiles(&[clean, dry]);
+        sched.load_jars(&[
+            heated_jar("clean", "jar_heater"),
+            make_jar("rinse"),
+            heated_jar("dry", "dryer"),
+            heated_jar("spare", "missing"),
+        ]);
+        sched.load_heaters(&[make_heater("jar_heater", 55), make_heater("dryer", 45)]);
+        sched.start_program(make_program("Test", steps));
+        sched
+    }
+
+    #[test]
+    fn test_two_heater_program() {
+        let mut sched =
+            two_heater_scheduler(&[("clean", "Clean"), ("rinse", "Clean"), ("dry", "Dry")]);
+        let jar_heating = HeaterCommand::heating(TemperatureC10::from_whole(40));
+
+        // The clean jar's heater warms, the dryer stays off
+        assert_eq!(sched.step_state().unwrap().heater, Some(0));
+        assert_eq!(sched.heater_command(), jar_heating);
+        assert_eq!(sched.heater_command_for(0), jar_heating);
+        assert_eq!(
+            sched.heater_command_for(1),
+            HeaterCommand::off().for_heater(1)
+        );
+
+        // A jar naming no heater is unheated when there are several
+        assert_eq!(sched.tick(10), Some(Event::NextStep));
+        sched.advance_step();
+        assert_eq!(sched.step_state().unwrap().heater, None);
+        assert_eq!(sched.heater_command().target, None);
+        assert_eq!(sched.heater_command_for(0), HeaterCommand::off());
+        assert_eq!(
+            sched.heater_command_for(1),
+            HeaterCommand::off().for_heater(1)
+        );
+
+        // The dryer takes over, clamped to its own ceiling
+        assert_eq!(sched.tick(10), Some(Event::NextStep));
+        sched.advance_step();
+        let dryer_heating = HeaterCommand::heating(TemperatureC10::from_whole(45)).for_heater(1);
+        assert_eq!(sched.step_state().unwrap().heater, Some(1));
+        assert_eq!(sched.heater_command(), dryer_heating);
+        assert_eq!(sched.heater_command_for(0), HeaterCommand::off());
+        assert_eq!(sched.heater_command_for(1), dryer_heating);
+
+        // Everything is off once the program ends
+        assert_eq!(sched.tick(10), Some(Event::ProgramFinished));
+        assert_eq!(sched.heater_command_for(0).target, None);
+        assert_eq!(sched.heater_command_for(1).target, None);
+    }
+
+    #[test]
+    fn test_unknown_heater_unheated() {
+        let sched = two_heater_scheduler(&[("spare", "Clean")]);
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        assert_eq!(sched.step_state().unwrap().heater, None);
+        assert_eq!(sched.heater_command().target, None);
+
+        // With a single heater, jars that name none still use it
+        let mut sched = Scheduler::new(heated_machine());
+        let mut clean = make_profile("Clean", 120, 10, DirectionMode::Clockwise);
+        clean.temperature_c = Some(40);
+        sched.load_profiles(&[clean]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.load_heaters(&[make_heater("dryer", 55)]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        assert_eq!(sched.step_state().unwrap().heater, Some(0));
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(40))
+        );
+    }
+
+    /// Automated scheduler where the "clean" jar has an ultrasonic module
+    fn ultrasonic_scheduler(steps: &[(&str, &str)]) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+
+        let mut dry = make_profile("Dry", 150, 10, DirectionMode::Clockwise);
+        dry.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        let mut clean = make_jar("clean");
+        let mut ultrasonic = String::new();
+        let _ = ultrasonic.push_str("us1");
+        clean.ultrasonic = Some(ultrasonic);
+
+        sched.load_profiles(&[
+            make_profile("Clean", 120, 10, DirectionMode::Clockwise),
+            dry,
+        ]);
+        sched.load_jars(&[clean, make_jar("rinse")]);
+        sched.start_program(make_program("Test", steps));
+        sched
+    }
+
+    #[test]
+    fn test_ultrasonic_follows_phase() {
+        let mut sched = ultrasonic_scheduler(&[("clean", "Dry"), ("clean", "Clean")]);
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::on());
+
+        assert_eq!(sched.tick(10), Some(Event::StartSpinOff));
+        assert_eq!(sched.phase(), ExecutionPhase::SpinOff);
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+
+        sched.lift_complete();
+        assert_eq!(sched.tick(5), Some(Event::NextStep));
+        assert_eq!(sched.phase(), ExecutionPhase::StepComplete);
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+
+        sched.advance_step();
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::on());
+
+        assert!(sched.pause());
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+        assert!(sched.resume());
+
+        assert_eq!(sched.tick(10), Some(Event::ProgramFinished));
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+    }
+
+    #[test]
+    fn test_no_ultrasonic_without_reference() {
+        let mut sched = ultrasonic_scheduler(&[("rinse", "Clean"), ("clean", "Clean")]);
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+
+        assert_eq!(sched.tick(10), Some(Event::NextStep));
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+
+        // Reaching the jar with a module switches it on
+        sched.advance_step();
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::on());
+
+        sched.abort();
+        assert_eq!(sched.ultrasonic_command(), AccessoryCommand::off());
+    }
+
     #[test]
     fn test_spinoff_flow() {
         let mut sched = Scheduler::new(MachineCapabilities {
@@ -697,6 +1908,7 @@ mod tests {
             lift_mm: 20,
             rpm: 150,
             time_s: 5,
+            pre_spinoff_delay_s: 0,
         });
 
         let profiles = [profile];
@@ -711,6 +1923,7 @@ mod tests {
         let event = sched.tick(15);
         assert_eq!(event, Some(Event::StartSpinOff));
         assert_eq!(sched.phase(), ExecutionPhase::SpinOff);
+        sched.lift_complete();
 
         // Motor should be at spinoff RPM
         assert_eq!(sched.motor_command().rpm, 150);
@@ -720,6 +1933,401 @@ mod tests {
         assert_eq!(event, Some(Event::ProgramFinished));
     }
 
+    #[test]
+    fn test_resume_returns_to_paused_phase() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            has_z: true,
+            ..Default::default()
+        });
+
+        let mut profile = make_profile("Clean", 120, 10, DirectionMode::Clockwise);
+        profile.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program(
+            "Test",
+            &[("clean", "Clean"), ("clean", "Clean")],
+        ));
+
+        // Pause during spin-off, still lifting: no spin-off time recorded yet
+        assert_eq!(sched.tick(15), Some(Event::StartSpinOff));
+        assert_eq!(sched.step_state().unwrap().spinoff_elapsed_s, 0);
+        assert!(sched.pause());
+        assert!(sched.resume());
+        assert_eq!(sched.phase(), ExecutionPhase::SpinOff);
+
+        // Pause again with spin-off time elapsed
+        sched.lift_complete();
+        sched.tick(2);
+        assert!(sched.pause());
+        assert!(sched.resume());
+        assert_eq!(sched.phase(), ExecutionPhase::SpinOff);
+        assert_eq!(sched.motor_command().rpm, 150);
+
+        // Next step: a pause while running resumes running
+        assert_eq!(sched.tick(3), Some(Event::NextStep));
+        sched.advance_step();
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        sched.tick(4);
+        assert!(sched.pause());
+        assert!(sched.resume());
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        assert_eq!(sched.motor_command().rpm, 120);
+
+        // And its spin-off still resumes as spin-off
+        assert_eq!(sched.tick(10), Some(Event::StartSpinOff));
+        assert!(sched.pause());
+        assert!(sched.resume());
+        assert_eq!(sched.phase(), ExecutionPhase::SpinOff);
+    }
+
+    #[test]
+    fn test_spinoff_waits_for_lift_and_delay() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            has_z: true,
+            ..Default::default()
+        });
+
+        let mut profile = make_profile("Clean", 120, 10, DirectionMode::Clockwise);
+        profile.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 3,
+        });
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+
+        assert_eq!(sched.tick(15), Some(Event::StartSpinOff));
+
+        // Lift still in progress: motor stopped, time doesn't count
+        assert_eq!(sched.tick(10), None);
+        assert_eq!(sched.motor_command().rpm, 0);
+
+        // Lift done, dwell not yet elapsed
+        sched.lift_complete();
+        assert_eq!(sched.motor_command().rpm, 0);
+        sched.tick(2);
+        assert_eq!(sched.motor_command().rpm, 0);
+
+        // Dwell elapsed: spin-off motor starts
+        sched.tick(1);
+        assert_eq!(sched.motor_command().rpm, 150);
+        assert_eq!(sched.step_state().unwrap().spinoff_elapsed_s, 0);
+
+        // Full spin-off time still runs after the dwell
+        assert_eq!(sched.tick(4), None);
+        assert_eq!(sched.tick(1), Some(Event::ProgramFinished));
+    }
+
+    #[test]
+    fn test_spinoff_rpm_clamped_to_machine_max() {
+        for (rpm, expected) in [(1500, 300), (300, 300), (150, 150)] {
+            let mut sched = Scheduler::new(MachineCapabilities {
+                is_automated: true,
+                has_z: true,
+                ..Default::default()
+            });
+            sched.set_max_spinoff_rpm(Some(300));
+
+            let mut profile = make_profile("Clean", 120, 10, DirectionMode::Clockwise);
+            profile.spinoff = Some(SpinOffConfig {
+                lift_mm: 20,
+                rpm,
+                time_s: 5,
+                pre_spinoff_delay_s: 0,
+            });
+
+            sched.load_profiles(&[profile]);
+            sched.load_jars(&[make_jar("clean")]);
+            sched.start_program(make_program("Test", &[("clean", "Clean")]));
+
+            assert_eq!(sched.tick(15), Some(Event::StartSpinOff));
+            sched.lift_complete();
+            assert_eq!(sched.motor_command().rpm, expected);
+        }
+    }
+
+    #[test]
+    fn test_manual_spinoff_ignores_delay() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: false,
+            ..Default::default()
+        });
+
+        let mut profile = make_profile("Clean", 120, 10, DirectionMode::Clockwise);
+        profile.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 3,
+        });
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+
+        assert_eq!(sched.tick(15), Some(Event::PromptSpinOff));
+        sched.user_confirm();
+        assert_eq!(sched.motor_command().rpm, 150);
+    }
+
+    #[test]
+    fn test_profile_max_temp_clamps_target() {
+        let mut sched = Scheduler::new(heated_machine());
+        sched.set_heater_max_temp(55);
+
+        let mut profile = make_profile("Dry", 60, 60, DirectionMode::Clockwise);
+        profile.temperature_c = Some(50);
+        profile.max_temp_c = Some(40);
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("dry")]);
+        sched.start_program(make_program("Test", &[("dry", "Dry")]));
+
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(40))
+        );
+    }
+
+    #[test]
+    fn test_hardware_max_overrides_profile_max() {
+        let mut sched = Scheduler::new(heated_machine());
+        sched.set_heater_max_temp(45);
+
+        let mut profile = make_profile("Dry", 60, 60, DirectionMode::Clockwise);
+        profile.temperature_c = Some(60);
+        profile.max_temp_c = Some(50);
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("dry")]);
+        sched.start_program(make_program("Test", &[("dry", "Dry")]));
+
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(45))
+        );
+    }
+
+    #[test]
+    fn test_target_below_max_unchanged() {
+        let mut sched = Scheduler::new(heated_machine());
+
+        let mut profile = make_profile("Dry", 60, 60, DirectionMode::Clockwise);
+        profile.temperature_c = Some(35);
+        profile.max_temp_c = Some(40);
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("dry")]);
+        sched.start_program(make_program("Test", &[("dry", "Dry")]));
+
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(35))
+        );
+    }
+
+    fn heated_clean_scheduler(prime: Option<PrimeConfig>) -> Scheduler {
+        let mut sched = Scheduler::new(heated_machine());
+        let mut profile = make_profile("Clean", 120, 60, DirectionMode::Clockwise);
+        profile.temperature_c = Some(40);
+        profile.prime = prime;
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        sched
+    }
+
+    #[test]
+    fn test_prime_runs_unheated_before_profile() {
+        let mut sched = heated_clean_scheduler(Some(PrimeConfig {
+            time_s: 15,
+            rpm: Some(80),
+        }));
+        let heating = HeaterCommand::heating(TemperatureC10::from_whole(40));
+
+        assert!(sched.is_priming());
+        assert_eq!(
+            sched.motor_command(),
+            MotorCommand::running(80, Direction::Clockwise)
+        );
+        assert_eq!(sched.heater_command(), HeaterCommand::off());
+        assert_eq!(sched.step_total_s(), 75);
+
+        assert_eq!(sched.tick(14), None);
+        assert_eq!(sched.heater_command(), HeaterCommand::off());
+
+        // Heated profile segment begins once the prime has run
+        assert_eq!(sched.tick(1), None);
+        assert!(!sched.is_priming());
+        assert_eq!(
+            sched.motor_command(),
+            MotorCommand::running(120, Direction::Clockwise)
+        );
+        assert_eq!(sched.heater_command(), heating);
+
+        assert_eq!(sched.tick(60), Some(Event::ProgramFinished));
+    }
+
+    #[test]
+    fn test_zero_time_profile_rejected() {
+        let mut sched = Scheduler::new(MachineCapabilities::default());
+        let profile = make_profile("Clean", 120, 0, DirectionMode::Clockwise);
+        assert!(profile_segments(&profile).is_err());
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        assert_eq!(
+            sched.start_program(make_program("Test", &[("clean", "Clean")])),
+            None
+        );
+
+        // Never runs, rather than completing straight away
+        assert_eq!(sched.phase(), ExecutionPhase::Idle);
+        assert_eq!(sched.tick(1), None);
+        assert_eq!(sched.phase(), ExecutionPhase::Idle);
+        assert_eq!(sched.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_unrunnable_later_step_rejects_program() {
+        let mut sched = Scheduler::new(MachineCapabilities::default());
+        sched.load_profiles(&[
+            make_profile("Clean", 120, 60, DirectionMode::Clockwise),
+            make_profile("Empty", 120, 0, DirectionMode::Clockwise),
+        ]);
+        sched.load_jars(&[make_jar("clean")]);
+
+        let program = make_program("Test", &[("clean", "Clean"), ("clean", "Empty")]);
+        assert_eq!(sched.start_program(program), None);
+        assert_eq!(sched.phase(), ExecutionPhase::Idle);
+    }
+
+    #[test]
+    fn test_minimal_profile_runs_single_segment() {
+        let mut sched = Scheduler::new(MachineCapabilities::default());
+        let profile = make_profile("Clean", 120, 1, DirectionMode::Clockwise);
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        let segments = &sched.step_state().unwrap().segments;
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].duration_s, 1);
+        assert_eq!(sched.tick(1), Some(Event::ProgramFinished));
+    }
+
+    #[test]
+    fn test_no_prime_heats_from_start() {
+        let sched = heated_clean_scheduler(None);
+
+        assert!(!sched.is_priming());
+        assert_eq!(sched.step_state().unwrap().segments.len(), 1);
+        assert_eq!(sched.step_total_s(), 60);
+        assert_eq!(
+            sched.motor_command(),
+            MotorCommand::running(120, Direction::Clockwise)
+        );
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(40))
+        );
+    }
+
+    #[test]
+    fn test_no_heater_ignores_profile_temperature() {
+        let mut sched = Scheduler::new(MachineCapabilities::from_config(false, false, false, 0));
+
+        let mut profile = make_profile("Dry", 60, 60, DirectionMode::Clockwise);
+        profile.temperature_c = Some(40);
+
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("dry")]);
+        sched.start_program(make_program("Test", &[("dry", "Dry")]));
+
+        assert_eq!(sched.phase(), ExecutionPhase::Running);
+        while sched.phase() == ExecutionPhase::Running {
+            assert_eq!(sched.heater_command(), HeaterCommand::off());
+            sched.tick(10);
+        }
+    }
+
+    #[test]
+    fn test_enable_required() {
+        assert!(!MotorCommand::stopped().enable_required());
+        assert!(MotorCommand::holding().enable_required());
+        assert!(MotorCommand::running(100, Direction::Clockwise).enable_required());
+    }
+
+    fn run_to_step_complete(behavior: StopBehavior) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+        sched.set_stop_behavior(behavior);
+
+        let profiles = [
+            make_profile("Clean", 120, 10, DirectionMode::Clockwise),
+            make_profile("Rinse", 100, 10, DirectionMode::Clockwise),
+        ];
+        sched.load_profiles(&profiles);
+        sched.load_jars(&[make_jar("clean"), make_jar("rinse")]);
+        sched.start_program(make_program(
+            "Test",
+            &[("clean", "Clean"), ("rinse", "Rinse")],
+        ));
+
+        assert_eq!(sched.tick(10), Some(Event::NextStep));
+        assert_eq!(sched.phase(), ExecutionPhase::StepComplete);
+        sched
+    }
+
+    #[test]
+    fn test_coast_releases_on_stop() {
+        let sched = run_to_step_complete(StopBehavior::Coast);
+        let cmd = sched.motor_command();
+        assert_eq!(cmd.rpm, 0);
+        assert!(!cmd.hold);
+        assert!(!cmd.enable_required());
+    }
+
+    #[test]
+    fn test_brake_holds_on_stop() {
+        let mut sched = run_to_step_complete(StopBehavior::Brake);
+        let cmd = sched.motor_command();
+        assert_eq!(cmd.rpm, 0);
+        assert!(cmd.hold);
+        assert!(cmd.enable_required());
+
+        // Released once the program ends
+        sched.abort();
+        assert_eq!(sched.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_brake_holds_while_paused() {
+        let mut sched = Scheduler::new(MachineCapabilities::default());
+        sched.set_stop_behavior(StopBehavior::Brake);
+        sched.load_profiles(&[make_profile("Clean", 120, 60, DirectionMode::Clockwise)]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+
+        assert!(sched.pause());
+        assert_eq!(sched.motor_command(), MotorCommand::holding());
+    }
+
     #[test]
     fn test_segment_tracking() {
         let mut sched = Scheduler::new(MachineCapabilities {
@@ -746,6 +2354,131 @@ mod tests {
         assert_eq!(state.segments[1].direction, Direction::CounterClockwise);
     }
 
+    #[test]
+    fn test_current_iteration_advances_per_direction_pair() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+        assert_eq!(sched.current_iteration(), (0, 0));
+
+        // 3 iterations of 10s CW + 10s CCW
+        let profiles = [make_profile("Clean", 120, 60, DirectionMode::Alternate)];
+        sched.load_profiles(&profiles);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+
+        let mut seen = [(0, 0); 6];
+        for iteration in seen.iter_mut() {
+            *iteration = sched.current_iteration();
+            sched.tick(10);
+        }
+        assert_eq!(seen, [(1, 3), (1, 3), (2, 3), (2, 3), (3, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn test_current_iteration_with_prime_and_continuous() {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+        let mut primed = make_profile("Clean", 120, 60, DirectionMode::Alternate);
+        primed.prime = Some(PrimeConfig {
+            time_s: 5,
+            rpm: None,
+        });
+        let profiles = [
+            primed,
+            make_profile("Rinse", 120, 60, DirectionMode::Clockwise),
+        ];
+        sched.load_profiles(&profiles);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program(
+            "Test",
+            &[("clean", "Clean"), ("clean", "Rinse")],
+        ));
+
+        // The prime belongs to the first iteration
+        assert_eq!(sched.current_iteration(), (1, 3));
+        sched.tick(5);
+        assert_eq!(sched.current_iteration(), (1, 3));
+        sched.tick(10);
+        sched.tick(10);
+        assert_eq!(sched.current_iteration(), (2, 3));
+
+        // A continuous profile is one iteration
+        for _ in 0..4 {
+            sched.tick(10);
+        }
+        assert_eq!(sched.advance_step(), None);
+        assert_eq!(sched.step_state().unwrap().step_index, 1);
+        assert_eq!(sched.current_iteration(), (1, 1));
+    }
+
+    fn soak_scheduler(behavior: StopBehavior) -> Scheduler {
+        let mut sched = Scheduler::new(MachineCapabilities {
+            is_automated: true,
+            ..heated_machine()
+        });
+        sched.set_stop_behavior(behavior);
+
+        let mut profile = make_profile("Clean", 150, 60, DirectionMode::Clockwise);
+        profile.temperature_c = Some(40);
+        profile.soak = Some(SoakConfig {
+            spin_s: 20,
+            soak_s: 40,
+            cycles: 3,
+        });
+        sched.load_profiles(&[profile]);
+        sched.load_jars(&[make_jar("clean")]);
+        sched.start_program(make_program("Test", &[("clean", "Clean")]));
+        sched
+    }
+
+    #[test]
+    fn test_soak_segments_alternate() {
+        let mut sched = soak_scheduler(StopBehavior::Coast);
+
+        let state = sched.step_state().unwrap();
+        assert_eq!(state.segments.len(), 6);
+        assert_eq!(sched.step_total_s(), 3 * (20 + 40));
+
+        for _ in 0..3 {
+            assert_eq!(sched.motor_command().rpm, 150);
+            assert_eq!(sched.tick(20), None);
+            assert_eq!(sched.motor_command(), MotorCommand::stopped());
+            assert_eq!(sched.phase(), ExecutionPhase::Running);
+            let event = sched.tick(40);
+            if sched.phase() == ExecutionPhase::Running {
+                assert_eq!(event, None);
+            }
+        }
+        assert_eq!(sched.phase(), ExecutionPhase::Complete);
+    }
+
+    #[test]
+    fn test_heater_stays_on_during_soak() {
+        let mut sched = soak_scheduler(StopBehavior::Coast);
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(40))
+        );
+
+        sched.tick(20);
+        assert_eq!(sched.motor_command().rpm, 0);
+        assert_eq!(
+            sched.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(40))
+        );
+    }
+
+    #[test]
+    fn test_brake_holds_during_soak() {
+        let mut sched = soak_scheduler(StopBehavior::Brake);
+        sched.tick(20);
+        assert_eq!(sched.motor_command(), MotorCommand::holding());
+    }
+
     #[test]
     fn test_abort() {
         let mut sched = Scheduler::new(MachineCapabilities::default());
@@ -7,6 +7,10 @@ pub mod executor;
 pub mod segment;
 
 pub use executor::{
-    ExecutionPhase, HeaterCommand, MotorCommand, Scheduler, StepState, MAX_SEGMENTS,
+    profile_segments, AccessoryCommand, ExecutionPhase, HeaterCommand, MotorCommand,
+    PrewarmCommand, Scheduler, StepState, StepTransition, MAX_SEGMENTS,
+};
+pub use segment::{
+    balance_segment, generate_segments, generate_soak_segments, prepend_prime, BalanceConfig,
+    DirectionMode, PrimeConfig, Segment, SegmentError, SoakConfig, SpinOffConfig,
 };
-pub use segment::{generate_segments, DirectionMode, Segment, SpinOffConfig};
@@ -1,5 +1,6 @@
 //! Execution segments generated from profiles
 
+use super::executor::MAX_SEGMENTS;
 use crate::traits::Direction;
 
 #[cfg(feature = "serde")]
@@ -32,6 +33,75 @@ pub struct SpinOffConfig {
     pub rpm: u16,
     /// Spin-off duration (seconds)
     pub time_s: u16,
+    /// Dwell after the lift completes before spinning (seconds, automated only)
+    pub pre_spinoff_delay_s: u16,
+}
+
+/// Spin-then-soak cycling for a profile
+///
+/// Each cycle spins at the profile RPM for `spin_s`, then rests at 0 RPM
+/// for `soak_s` so the parts soak in the fluid. The heater stays on
+/// throughout; this is independent of `iterations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SoakConfig {
+    /// Spin duration per cycle (seconds)
+    pub spin_s: u16,
+    /// Soak duration per cycle (seconds)
+    pub soak_s: u16,
+    /// Number of spin/soak cycles
+    pub cycles: u8,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            spin_s: 0,
+            soak_s: 0,
+            cycles: 1,
+        }
+    }
+}
+
+/// Unheated mixing spin before a profile
+///
+/// Spins the basket with the heater off so settled solution is mixed
+/// before heating starts. It runs as a leading segment of the profile,
+/// in the direction of the profile's first segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrimeConfig {
+    /// Prime duration (seconds)
+    pub time_s: u16,
+    /// Prime speed (None = the profile RPM)
+    pub rpm: Option<u16>,
+}
+
+/// Balancing spin at the end of a program
+///
+/// Profiles that spin mostly one way leave the basket turned many times
+/// over in that direction by the end of a program, which can work basket
+/// lids loose. The balancing spin runs the other way at `rpm` for long
+/// enough to bring the net rotation back near zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BalanceConfig {
+    /// Balancing spin speed
+    pub rpm: u16,
+    /// Net rotation left unbalanced (revolutions)
+    pub min_revs: u16,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        Self {
+            rpm: 60,
+            min_revs: 1,
+        }
+    }
 }
 
 /// Direction mode for profiles
@@ -49,8 +119,31 @@ pub enum DirectionMode {
 }
 
 /// Minimum segment duration in seconds
+///
+/// Applies to every segment that is followed by a reversal or a soak.
+/// A continuous profile is a single segment and only needs a non-zero time.
 pub const MIN_SEGMENT_DURATION_S: u16 = 10;
 
+/// Reason a profile could not be split into segments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SegmentError {
+    /// Alternate mode with zero iterations, or soak with zero cycles
+    NoIterations,
+    /// More segments than the scheduler can hold
+    TooManySegments,
+    /// A segment would be shorter than the allowed minimum
+    TooShort {
+        /// Duration the segment would have had (seconds)
+        duration_s: u16,
+        /// Minimum allowed duration (seconds)
+        min_s: u16,
+    },
+}
+
+/// Maximum spin/soak cycles (each cycle uses two segments)
+pub const MAX_SOAK_CYCLES: u8 = 8;
+
 /// Generate segments from profile parameters
 ///
 /// # Arguments
@@ -60,47 +153,55 @@ pub const MIN_SEGMENT_DURATION_S: u16 = 10;
 /// - `iterations`: Number of alternations (only used for Alternate mode)
 ///
 /// # Returns
-/// A vector of segments, or None if validation fails
+/// A vector of segments, or the reason the profile cannot be run
 pub fn generate_segments(
     rpm: u16,
     total_time_s: u16,
     direction: DirectionMode,
     iterations: u8,
-) -> Option<heapless::Vec<Segment, 16>> {
+) -> Result<heapless::Vec<Segment, MAX_SEGMENTS>, SegmentError> {
     use heapless::Vec;
 
     let mut segments = Vec::new();
 
     match direction {
-        DirectionMode::Clockwise => {
-            segments
-                .push(Segment {
-                    direction: Direction::Clockwise,
-                    duration_s: total_time_s,
-                    rpm,
-                })
-                .ok()?;
-        }
-        DirectionMode::CounterClockwise => {
+        DirectionMode::Clockwise | DirectionMode::CounterClockwise => {
+            if total_time_s == 0 {
+                return Err(SegmentError::TooShort {
+                    duration_s: 0,
+                    min_s: 1,
+                });
+            }
+
+            let direction = if direction == DirectionMode::Clockwise {
+                Direction::Clockwise
+            } else {
+                Direction::CounterClockwise
+            };
             segments
                 .push(Segment {
-                    direction: Direction::CounterClockwise,
+                    direction,
                     duration_s: total_time_s,
                     rpm,
                 })
-                .ok()?;
+                .map_err(|_| SegmentError::TooManySegments)?;
         }
         DirectionMode::Alternate => {
             if iterations == 0 {
-                return None;
+                return Err(SegmentError::NoIterations);
             }
 
             let num_segments = (iterations as u16) * 2;
-            let segment_duration = total_time_s / num_segments;
+            if num_segments as usize > MAX_SEGMENTS {
+                return Err(SegmentError::TooManySegments);
+            }
 
-            // Validate minimum segment duration
+            let segment_duration = total_time_s / num_segments;
             if segment_duration < MIN_SEGMENT_DURATION_S {
-                return None;
+                return Err(SegmentError::TooShort {
+                    duration_s: segment_duration,
+                    min_s: MIN_SEGMENT_DURATION_S,
+                });
             }
 
             let mut current_dir = Direction::Clockwise;
@@ -111,19 +212,182 @@ pub fn generate_segments(
                         duration_s: segment_duration,
                         rpm,
                     })
-                    .ok()?;
+                    .map_err(|_| SegmentError::TooManySegments)?;
                 current_dir = current_dir.opposite();
             }
         }
     }
 
-    Some(segments)
+    Ok(segments)
+}
+
+/// Generate alternating spin and soak segments
+///
+/// Spin segments run at `rpm`; with `DirectionMode::Alternate` each spin
+/// reverses the previous one. Soak segments have 0 RPM.
+///
+/// # Returns
+/// A vector of segments, or the reason the profile cannot be run
+pub fn generate_soak_segments(
+    rpm: u16,
+    direction: DirectionMode,
+    soak: SoakConfig,
+) -> Result<heapless::Vec<Segment, MAX_SEGMENTS>, SegmentError> {
+    use heapless::Vec;
+
+    if soak.cycles == 0 {
+        return Err(SegmentError::NoIterations);
+    }
+    if soak.cycles > MAX_SOAK_CYCLES {
+        return Err(SegmentError::TooManySegments);
+    }
+    if soak.spin_s < MIN_SEGMENT_DURATION_S {
+        return Err(SegmentError::TooShort {
+            duration_s: soak.spin_s,
+            min_s: MIN_SEGMENT_DURATION_S,
+        });
+    }
+    if soak.soak_s == 0 {
+        return Err(SegmentError::TooShort {
+            duration_s: 0,
+            min_s: 1,
+        });
+    }
+
+    let mut current_dir = match direction {
+        DirectionMode::CounterClockwise => Direction::CounterClockwise,
+        DirectionMode::Clockwise | DirectionMode::Alternate => Direction::Clockwise,
+    };
+
+    let mut segments = Vec::new();
+    for _ in 0..soak.cycles {
+        segments
+            .push(Segment {
+                direction: current_dir,
+                duration_s: soak.spin_s,
+                rpm,
+            })
+            .map_err(|_| SegmentError::TooManySegments)?;
+        segments
+            .push(Segment {
+                direction: current_dir,
+                duration_s: soak.soak_s,
+                rpm: 0,
+            })
+            .map_err(|_| SegmentError::TooManySegments)?;
+        if direction == DirectionMode::Alternate {
+            current_dir = current_dir.opposite();
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Insert a prime segment in front of a profile's segments
+///
+/// `rpm` is the profile RPM, used unless the prime sets its own.
+pub fn prepend_prime(
+    segments: &mut heapless::Vec<Segment, MAX_SEGMENTS>,
+    rpm: u16,
+    prime: PrimeConfig,
+) -> Result<(), SegmentError> {
+    if prime.time_s == 0 {
+        return Err(SegmentError::TooShort {
+            duration_s: 0,
+            min_s: 1,
+        });
+    }
+    let direction = segments
+        .first()
+        .map(|s| s.direction)
+        .unwrap_or(Direction::Clockwise);
+    segments
+        .insert(
+            0,
+            Segment {
+                direction,
+                duration_s: prime.time_s,
+                rpm: prime.rpm.unwrap_or(rpm),
+            },
+        )
+        .map_err(|_| SegmentError::TooManySegments)
+}
+
+/// Segment cancelling a net rotation
+///
+/// `net_rpm_s` is the net rotation in RPM-seconds (60 per revolution),
+/// positive clockwise. The duration is rounded to the nearest second.
+/// Returns None when the net rotation is below `min_revs` or too small
+/// to balance at the configured speed.
+pub fn balance_segment(net_rpm_s: i32, config: BalanceConfig) -> Option<Segment> {
+    let magnitude = net_rpm_s.unsigned_abs();
+    if config.rpm == 0 || magnitude < config.min_revs as u32 * 60 {
+        return None;
+    }
+    let rpm = config.rpm as u32;
+    let duration_s = ((magnitude + rpm / 2) / rpm).min(u16::MAX as u32) as u16;
+    if duration_s == 0 {
+        return None;
+    }
+    let direction = if net_rpm_s > 0 {
+        Direction::CounterClockwise
+    } else {
+        Direction::Clockwise
+    };
+    Some(Segment {
+        direction,
+        duration_s,
+        rpm: config.rpm,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_balance_segment() {
+        let config = BalanceConfig {
+            rpm: 60,
+            min_revs: 2,
+        };
+        // 10 clockwise revolutions: 10 s counter-clockwise at 60 RPM
+        assert_eq!(
+            balance_segment(600, config),
+            Some(Segment {
+                direction: Direction::CounterClockwise,
+                duration_s: 10,
+                rpm: 60
+            })
+        );
+        // Counter-clockwise bias, rounded to the nearest second
+        let seg = balance_segment(-190, config).unwrap();
+        assert_eq!((seg.direction, seg.duration_s), (Direction::Clockwise, 3));
+
+        // Under the threshold, or nothing to balance
+        assert_eq!(balance_segment(119, config), None);
+        assert_eq!(
+            balance_segment(
+                0,
+                BalanceConfig {
+                    rpm: 60,
+                    min_revs: 0
+                }
+            ),
+            None
+        );
+        assert_eq!(
+            balance_segment(
+                600,
+                BalanceConfig {
+                    rpm: 0,
+                    min_revs: 0
+                }
+            ),
+            None
+        );
+    }
+
     #[test]
     fn test_single_direction() {
         let segments = generate_segments(120, 180, DirectionMode::Clockwise, 0).unwrap();
@@ -153,13 +417,146 @@ mod tests {
     #[test]
     fn test_alternate_zero_iterations() {
         let result = generate_segments(120, 180, DirectionMode::Alternate, 0);
-        assert!(result.is_none());
+        assert_eq!(result, Err(SegmentError::NoIterations));
     }
 
     #[test]
     fn test_segment_too_short() {
         // 60 seconds / 8 segments = 7.5 seconds < MIN_SEGMENT_DURATION_S
         let result = generate_segments(120, 60, DirectionMode::Alternate, 4);
-        assert!(result.is_none());
+        assert_eq!(
+            result,
+            Err(SegmentError::TooShort {
+                duration_s: 7,
+                min_s: MIN_SEGMENT_DURATION_S,
+            })
+        );
+    }
+
+    #[test]
+    fn test_many_iterations_on_short_profile_rejected() {
+        // 30 seconds / 16 segments would round down to 1 second each
+        let result = generate_segments(120, 30, DirectionMode::Alternate, 8);
+        assert!(matches!(
+            result,
+            Err(SegmentError::TooShort { duration_s: 1, .. })
+        ));
+
+        // More reversals than the scheduler holds
+        let result = generate_segments(120, 3600, DirectionMode::Alternate, 9);
+        assert_eq!(result, Err(SegmentError::TooManySegments));
+    }
+
+    #[test]
+    fn test_segment_at_minimum_allowed() {
+        let segments = generate_segments(120, 40, DirectionMode::Alternate, 2).unwrap();
+        assert_eq!(segments.len(), 4);
+        assert!(segments
+            .iter()
+            .all(|s| s.duration_s == MIN_SEGMENT_DURATION_S));
+    }
+
+    #[test]
+    fn test_zero_time_continuous_rejected() {
+        let result = generate_segments(120, 0, DirectionMode::Clockwise, 0);
+        assert!(matches!(
+            result,
+            Err(SegmentError::TooShort { duration_s: 0, .. })
+        ));
+
+        // Short continuous profiles are a single segment and stay valid
+        let segments = generate_segments(120, 5, DirectionMode::CounterClockwise, 0).unwrap();
+        assert_eq!(segments[0].duration_s, 5);
+    }
+
+    fn soak(spin_s: u16, soak_s: u16, cycles: u8) -> SoakConfig {
+        SoakConfig {
+            spin_s,
+            soak_s,
+            cycles,
+        }
+    }
+
+    #[test]
+    fn test_soak_segments_alternate_spin_and_rest() {
+        let segments =
+            generate_soak_segments(150, DirectionMode::Clockwise, soak(30, 90, 3)).unwrap();
+
+        assert_eq!(segments.len(), 6);
+        for pair in segments.chunks(2) {
+            assert_eq!(pair[0].rpm, 150);
+            assert_eq!(pair[0].duration_s, 30);
+            assert_eq!(pair[0].direction, Direction::Clockwise);
+            assert_eq!(pair[1].rpm, 0);
+            assert_eq!(pair[1].duration_s, 90);
+        }
+    }
+
+    #[test]
+    fn test_soak_segments_alternate_direction_per_spin() {
+        let segments =
+            generate_soak_segments(150, DirectionMode::Alternate, soak(20, 40, 3)).unwrap();
+
+        assert_eq!(segments[0].direction, Direction::Clockwise);
+        assert_eq!(segments[2].direction, Direction::CounterClockwise);
+        assert_eq!(segments[4].direction, Direction::Clockwise);
+    }
+
+    #[test]
+    fn test_soak_segments_invalid() {
+        let dir = DirectionMode::Clockwise;
+        assert_eq!(
+            generate_soak_segments(150, dir, soak(30, 90, 0)),
+            Err(SegmentError::NoIterations)
+        );
+        assert_eq!(
+            generate_soak_segments(150, dir, soak(30, 90, MAX_SOAK_CYCLES + 1)),
+            Err(SegmentError::TooManySegments)
+        );
+        assert!(matches!(
+            generate_soak_segments(150, dir, soak(5, 90, 2)),
+            Err(SegmentError::TooShort { duration_s: 5, .. })
+        ));
+        assert!(generate_soak_segments(150, dir, soak(30, 0, 2)).is_err());
+        assert!(generate_soak_segments(150, dir, soak(30, 90, MAX_SOAK_CYCLES)).is_ok());
+    }
+
+    #[test]
+    fn test_prepend_prime() {
+        let mut segments = generate_segments(120, 180, DirectionMode::CounterClockwise, 0).unwrap();
+        let prime = PrimeConfig {
+            time_s: 20,
+            rpm: Some(60),
+        };
+        prepend_prime(&mut segments, 120, prime).unwrap();
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].rpm, 60);
+        assert_eq!(segments[0].duration_s, 20);
+        assert_eq!(segments[0].direction, Direction::CounterClockwise);
+        assert_eq!(segments[1].duration_s, 180);
+
+        // Prime speed defaults to the profile RPM
+        let mut segments = generate_segments(120, 180, DirectionMode::Clockwise, 0).unwrap();
+        prepend_prime(
+            &mut segments,
+            120,
+            PrimeConfig {
+                time_s: 20,
+                rpm: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(segments[0].rpm, 120);
+
+        let mut segments = generate_segments(120, 180, DirectionMode::Clockwise, 0).unwrap();
+        assert!(prepend_prime(&mut segments, 120, PrimeConfig::default()).is_err());
+
+        // No room left behind a full alternate profile
+        let mut segments = generate_segments(120, 3200, DirectionMode::Alternate, 8).unwrap();
+        assert_eq!(
+            prepend_prime(&mut segments, 120, prime),
+            Err(SegmentError::TooManySegments)
+        );
     }
 }
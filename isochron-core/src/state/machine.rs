@@ -45,17 +45,69 @@ pub enum ErrorKind {
     ThermistorFault,
     /// Temperature exceeded safe limit
     OverTemperature,
+    /// Temperature failed to rise while the heater was on
+    ThermalRunaway,
     /// Motor stall detected
     MotorStall,
+    /// Stepper driver reported a fault and shut down its outputs
+    DriverFault(DriverFaultKind),
     /// Display communication lost
     LinkLost,
+    /// Axis move refused because the basket was out of position
+    PositionOutOfBounds,
+    /// Axis failed to find its endstop, even after the grace retry
+    HomingFailed,
+    /// Lid opened while the basket was commanded to move
+    LidOpen,
+    /// Basket load unbalanced during spin-off
+    Imbalance,
     /// Configuration error
     ConfigError,
     /// Unknown/generic error
     Unknown,
 }
 
+/// Stepper driver fault causes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DriverFaultKind {
+    /// Driver over-temperature shutdown
+    OverTemperature,
+    /// Phase shorted to ground or supply
+    ShortCircuit,
+}
+
+impl ErrorKind {
+    /// Check if the fault can clear by itself once its cause goes away
+    ///
+    /// Only a lost display link qualifies: the machine was stopped safely
+    /// and nothing about it needs inspecting once heartbeats return.
+    /// Everything else stays latched until acknowledged.
+    pub fn recoverable(&self) -> bool {
+        matches!(self, ErrorKind::LinkLost)
+    }
+}
+
 impl State {
+    /// Short human-readable name
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Boot => "Starting",
+            State::Idle => "Ready",
+            State::ProgramSelected => "Program",
+            State::EditProgram => "Edit Program",
+            State::AwaitingJar => "Awaiting Jar",
+            State::Running => "Running",
+            State::AwaitingSpinOff => "Awaiting Spin-off",
+            State::SpinOff => "Spin-off",
+            State::Paused => "Paused",
+            State::StepComplete => "Step Complete",
+            State::ProgramComplete => "Complete",
+            State::Autotuning => "Autotune",
+            State::Error(_) => "Error",
+        }
+    }
+
     /// Check if this state allows motor operation
     pub fn motor_allowed(&self) -> bool {
         matches!(self, State::Running | State::SpinOff)
@@ -65,6 +117,8 @@ impl State {
     pub fn heater_allowed(&self) -> bool {
         // Heater allowed during Running and Autotuning states
         // Not during SpinOff (basket is out of solution)
+        // During Autotuning the autotune relay owns the heater, not the
+        // scheduler's heater command
         matches!(self, State::Running | State::Autotuning)
     }
 
@@ -98,6 +152,7 @@ impl State {
             // ProgramSelected transitions
             (ProgramSelected, EditParameter) => EditProgram,
             (ProgramSelected, Start) => Running,
+            (ProgramSelected, PromptNextJar) => AwaitingJar, // Manual machines
             (ProgramSelected, Back) => Idle,
             (ProgramSelected, ErrorDetected(kind)) => Error(kind),
 
@@ -119,6 +174,9 @@ impl State {
                 // For now, we go to StepComplete; caller handles spin-off
                 StepComplete
             }
+            (Running, ProgramFinished) => ProgramComplete, // Last step, no spin-off
+            (Running, NextStep) => Running,                // Automated machines
+            (Running, PromptNextJar) => StepComplete,      // Manual machines
             (Running, StartSpinOff) => SpinOff,
             (Running, PromptSpinOff) => AwaitingSpinOff, // Manual machines
             (Running, Abort) => Idle,
@@ -131,6 +189,9 @@ impl State {
 
             // SpinOff transitions
             (SpinOff, SpinOffFinished) => StepComplete,
+            (SpinOff, NextStep) => Running, // Automated machines
+            (SpinOff, PromptNextJar) => StepComplete, // Manual machines
+            (SpinOff, ProgramFinished) => ProgramComplete,
             (SpinOff, Abort) => Idle,
             (SpinOff, ErrorDetected(kind)) => Error(kind),
 
@@ -143,6 +204,7 @@ impl State {
             (StepComplete, NextStep) => Running,
             (StepComplete, PromptNextJar) => AwaitingJar, // Manual machines
             (StepComplete, ProgramFinished) => ProgramComplete,
+            (StepComplete, Abort) => Idle,
             (StepComplete, ErrorDetected(kind)) => Error(kind),
 
             // ProgramComplete transitions
@@ -198,6 +260,8 @@ mod tests {
             State::Paused,
             State::SpinOff,
             State::AwaitingJar,
+            State::AwaitingSpinOff,
+            State::StepComplete,
         ];
 
         for state in states {
@@ -236,6 +300,23 @@ mod tests {
         assert_eq!(complete, State::StepComplete);
     }
 
+    #[test]
+    fn test_last_step_finishes_program() {
+        // The scheduler reports the end of the last step directly
+        let done = State::Running.transition(Event::ProgramFinished);
+        assert_eq!(done, State::ProgramComplete);
+
+        let done = State::SpinOff.transition(Event::ProgramFinished);
+        assert_eq!(done, State::ProgramComplete);
+    }
+
+    #[test]
+    fn test_automated_machine_moves_on() {
+        // The scheduler starts the next step itself, with or without a spin-off
+        assert_eq!(State::Running.transition(Event::NextStep), State::Running);
+        assert_eq!(State::SpinOff.transition(Event::NextStep), State::Running);
+    }
+
     #[test]
     fn test_manual_machine_flow() {
         // Manual machine: user prompted to lift basket
@@ -246,6 +327,17 @@ mod tests {
         // User confirms lift
         let spinoff = awaiting.transition(Event::UserConfirm);
         assert_eq!(spinoff, State::SpinOff);
+
+        // Step done: user prompted to move the basket to the next jar
+        let complete = spinoff.transition(Event::PromptNextJar);
+        assert_eq!(complete, State::StepComplete);
+        let awaiting = complete.transition(Event::PromptNextJar);
+        assert_eq!(awaiting, State::AwaitingJar);
+        assert_eq!(awaiting.transition(Event::UserConfirm), State::Running);
+
+        // Optionally prompted for the first jar too
+        let awaiting = State::ProgramSelected.transition(Event::PromptNextJar);
+        assert_eq!(awaiting, State::AwaitingJar);
     }
 
     #[test]
@@ -265,6 +357,14 @@ mod tests {
         assert!(!State::Paused.heater_allowed());
     }
 
+    #[test]
+    fn test_only_link_loss_recoverable() {
+        assert!(ErrorKind::LinkLost.recoverable());
+        assert!(!ErrorKind::OverTemperature.recoverable());
+        assert!(!ErrorKind::ThermistorFault.recoverable());
+        assert!(!ErrorKind::DriverFault(DriverFaultKind::ShortCircuit).recoverable());
+    }
+
     #[test]
     fn test_autotune_flow() {
         // Start autotune from idle
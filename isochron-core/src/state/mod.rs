@@ -7,4 +7,4 @@ pub mod events;
 pub mod machine;
 
 pub use events::Event;
-pub use machine::{ErrorKind, State};
+pub use machine::{DriverFaultKind, ErrorKind, State};
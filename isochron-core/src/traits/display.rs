@@ -47,6 +47,11 @@ pub trait DisplayDriver {
     /// Reset the display to boot state
     fn reset(&mut self) -> Result<(), DisplayError>;
 
+    /// Set the display contrast, e.g. to dim it while idle
+    ///
+    /// Levels below `isochron_protocol::MIN_CONTRAST` are raised to it.
+    fn set_contrast(&mut self, contrast: u8) -> Result<(), DisplayError>;
+
     /// Poll for incoming input events
     ///
     /// Returns `Ok(Some(event))` if an input event is available,
@@ -1,5 +1,7 @@
 //! Heater and temperature sensor traits
 
+use crate::util::TemperatureC10;
+
 /// Errors that can occur with temperature sensing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -12,6 +14,21 @@ pub enum SensorError {
     OutOfRange,
     /// ADC conversion error
     ConversionError,
+    /// Digital sensor data failed its checksum
+    ChecksumError,
+}
+
+/// Raw values behind a thermistor reading, for diagnosing wiring and
+/// sensor-type problems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SensorRaw {
+    /// ADC count
+    pub adc: u16,
+    /// Computed thermistor resistance (ohms), None if open or shorted
+    pub resistance_ohms: Option<u32>,
+    /// Resulting temperature (°C × 10), None if outside the table
+    pub temp_x10: Option<i16>,
 }
 
 /// Trait for temperature sensors
@@ -27,6 +44,11 @@ pub trait TemperatureSensor {
     /// Takes `&mut self` because ADC reads typically require mutable access.
     fn read_celsius_x10(&mut self) -> Result<i16, SensorError>;
 
+    /// Read the current temperature as a [`TemperatureC10`]
+    fn read_temperature(&mut self) -> Result<TemperatureC10, SensorError> {
+        self.read_celsius_x10().map(TemperatureC10::from_x10)
+    }
+
     /// Read the current temperature in whole degrees Celsius
     fn read_celsius(&mut self) -> Result<i16, SensorError> {
         self.read_celsius_x10().map(|t| t / 10)
@@ -36,6 +58,26 @@ pub trait TemperatureSensor {
     fn is_valid(&mut self) -> bool {
         self.read_celsius_x10().is_ok()
     }
+
+    /// Raw values behind the last reading
+    ///
+    /// None for sensors without an analog front end, or before the first
+    /// reading.
+    fn raw_reading(&self) -> Option<SensorRaw> {
+        None
+    }
+}
+
+/// Lets a heater controller borrow a sensor, including a
+/// `&mut dyn TemperatureSensor` chosen at runtime
+impl<T: TemperatureSensor + ?Sized> TemperatureSensor for &mut T {
+    fn read_celsius_x10(&mut self) -> Result<i16, SensorError> {
+        (**self).read_celsius_x10()
+    }
+
+    fn raw_reading(&self) -> Option<SensorRaw> {
+        (**self).raw_reading()
+    }
 }
 
 /// Trait for heater output control
@@ -9,9 +9,9 @@ pub mod motor;
 pub mod stepper;
 
 pub use display::DisplayDriver;
-pub use heater::{HeaterController, HeaterOutput, SensorError, TemperatureSensor};
+pub use heater::{HeaterController, HeaterOutput, SensorError, SensorRaw, TemperatureSensor};
 pub use motor::{
     AcMotorDriver, AcMotorState, AcRelayType, DcDriverType, DcMotorDriver, DcMotorState,
     MotorDriver, MotorError,
 };
-pub use stepper::{Direction, PositionStepperDriver, StepperDriver, StepperError};
+pub use stepper::{Direction, PositionStatus, PositionStepperDriver, StepperDriver, StepperError};
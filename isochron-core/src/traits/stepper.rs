@@ -89,6 +89,33 @@ pub trait StepperDriver {
     }
 }
 
+/// Reported position of a position-controlled stepper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PositionStatus {
+    /// Position in configured units (mm or degrees)
+    pub position: i32,
+    /// Position is referenced to a completed home
+    pub homed: bool,
+    /// Position is dead-reckoned from commanded steps, not measured
+    pub estimated: bool,
+}
+
+impl PositionStatus {
+    /// Distance between the reported and the expected position
+    pub fn deviation(&self, expected: i32) -> u32 {
+        self.position.abs_diff(expected)
+    }
+
+    /// Check the axis is homed and within `tolerance` of `expected`
+    ///
+    /// Used before a transition that depends on the axis position; an
+    /// unhomed axis never passes.
+    pub fn is_near(&self, expected: i32, tolerance: u32) -> bool {
+        self.homed && self.deviation(expected) <= tolerance
+    }
+}
+
 /// Extended trait for position-controlled steppers (z, x, lid)
 pub trait PositionStepperDriver: StepperDriver {
     /// Move to an absolute position
@@ -108,4 +135,16 @@ pub trait PositionStepperDriver: StepperDriver {
 
     /// Check if a move is in progress
     fn is_moving(&self) -> bool;
+
+    /// Get the position together with how it was obtained
+    ///
+    /// Open-loop drivers should override this to report their
+    /// dead-reckoned estimate.
+    fn position_status(&self) -> PositionStatus {
+        PositionStatus {
+            position: self.get_position(),
+            homed: self.is_homed(),
+            estimated: false,
+        }
+    }
 }
@@ -0,0 +1,64 @@
+//! Cooperative cancellation
+//!
+//! Long operations (autotune, homing, long moves) check a [`CancelToken`]
+//! between steps and stop on their own terms: outputs de-energised and
+//! state reset, rather than being torn down mid-step. Only atomic loads and
+//! stores are used, so a token works as a `static` on Cortex-M0.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared cancellation flag
+#[derive(Debug)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelToken {
+    /// Create a token that is not cancelled
+    pub const fn new() -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Request cancellation of the current operation
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Check if cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Clear the flag
+    ///
+    /// Call when starting an operation so a cancellation aimed at an
+    /// earlier one is not applied to it.
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_flag() {
+        let token = CancelToken::new();
+        assert!(!token.is_cancelled());
+
+        token.cancel();
+        assert!(token.is_cancelled());
+
+        token.reset();
+        assert!(!token.is_cancelled());
+    }
+}
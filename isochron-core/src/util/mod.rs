@@ -0,0 +1,11 @@
+//! Shared utilities
+//!
+//! Small helpers used across tasks that don't belong to a specific subsystem.
+
+pub mod cancel;
+pub mod retry;
+pub mod temperature;
+
+pub use cancel::CancelToken;
+pub use retry::{retry_async, Backoff};
+pub use temperature::TemperatureC10;
@@ -0,0 +1,149 @@
+//! Retry with exponential backoff
+//!
+//! Bus operations (UART, I2C, flash) occasionally fail transiently.
+//! [`retry_async`] re-runs a fallible async operation a bounded number of
+//! times, waiting between attempts. The delay is supplied by the caller so
+//! this stays independent of any particular timer implementation.
+
+/// Exponential backoff schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Backoff {
+    /// Delay before the first retry in ms
+    pub initial_ms: u32,
+    /// Upper bound on any single delay in ms
+    pub max_ms: u32,
+}
+
+impl Backoff {
+    /// Create a backoff schedule
+    pub const fn new(initial_ms: u32, max_ms: u32) -> Self {
+        Self { initial_ms, max_ms }
+    }
+
+    /// Delay before the given retry (0 = first retry)
+    ///
+    /// Doubles with each retry, capped at `max_ms`.
+    pub fn delay_ms(&self, retry: u8) -> u32 {
+        let factor = 1u32.checked_shl(retry as u32).unwrap_or(u32::MAX);
+        self.initial_ms.saturating_mul(factor).min(self.max_ms)
+    }
+}
+
+/// Run `op` up to `attempts` times, waiting with `delay` between failures
+///
+/// Returns the first `Ok`, or the last error once all attempts are used.
+/// An `attempts` of 0 is treated as 1.
+///
+/// # Arguments
+/// - `attempts`: Maximum number of times to run `op`
+/// - `backoff`: Delay schedule between attempts
+/// - `delay`: Async sleep, called with the delay in ms
+/// - `op`: The fallible operation
+pub async fn retry_async<T, E, F, D>(
+    attempts: u8,
+    backoff: Backoff,
+    mut delay: D,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: AsyncFnMut() -> Result<T, E>,
+    D: AsyncFnMut(u32),
+{
+    let attempts = attempts.max(1);
+    let mut retry = 0;
+
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if retry + 1 >= attempts => return Err(e),
+            Err(_) => {
+                delay(backoff.delay_ms(retry)).await;
+                retry += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    /// Minimal executor: the futures under test never actually pend
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    /// Run `retry_async` against an op that fails `failures` times
+    ///
+    /// Returns (result, calls made, delays requested)
+    fn run(attempts: u8, failures: u8) -> (Result<u8, u8>, u8, heapless::Vec<u32, 8>) {
+        let mut calls = 0u8;
+        let mut delays = heapless::Vec::new();
+        let result = block_on(retry_async(
+            attempts,
+            Backoff::new(10, 50),
+            async |ms| delays.push(ms).unwrap(),
+            async || {
+                calls += 1;
+                if calls <= failures {
+                    Err(calls)
+                } else {
+                    Ok(calls)
+                }
+            },
+        ));
+        (result, calls, delays)
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let backoff = Backoff::new(10, 50);
+        assert_eq!(backoff.delay_ms(0), 10);
+        assert_eq!(backoff.delay_ms(1), 20);
+        assert_eq!(backoff.delay_ms(2), 40);
+        assert_eq!(backoff.delay_ms(3), 50);
+        assert_eq!(backoff.delay_ms(40), 50);
+    }
+
+    #[test]
+    fn test_succeeds_on_last_attempt() {
+        let (result, calls, delays) = run(4, 3);
+        assert_eq!(result, Ok(4));
+        assert_eq!(calls, 4);
+        assert_eq!(delays.as_slice(), &[10, 20, 40]);
+    }
+
+    #[test]
+    fn test_first_success_does_not_wait() {
+        let (result, calls, delays) = run(3, 0);
+        assert_eq!(result, Ok(1));
+        assert_eq!(calls, 1);
+        assert!(delays.is_empty());
+    }
+
+    #[test]
+    fn test_exhausted_returns_last_error() {
+        let (result, calls, delays) = run(3, 5);
+        assert_eq!(result, Err(3));
+        assert_eq!(calls, 3);
+        // No wait after the final failure
+        assert_eq!(delays.len(), 2);
+    }
+
+    #[test]
+    fn test_zero_attempts_runs_once() {
+        let (result, calls, _) = run(0, 5);
+        assert_eq!(result, Err(1));
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,156 @@
+//! Fixed-point temperature
+//!
+//! Temperatures are carried in 0.1°C steps so sensor readings keep their
+//! resolution without floating point. [`TemperatureC10`] wraps the scaled
+//! value so it can't be mixed up with whole degrees.
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Temperature in 0.1°C units (e.g., 452 = 45.2°C)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TemperatureC10(i16);
+
+impl TemperatureC10 {
+    /// 0.0°C
+    pub const ZERO: Self = Self(0);
+
+    /// Create from a value in 0.1°C units
+    pub const fn from_x10(x10: i16) -> Self {
+        Self(x10)
+    }
+
+    /// Create from whole degrees Celsius (saturates at the i16 range)
+    pub const fn from_whole(celsius: i16) -> Self {
+        Self(celsius.saturating_mul(10))
+    }
+
+    /// Value in 0.1°C units
+    pub const fn as_x10(self) -> i16 {
+        self.0
+    }
+
+    /// Whole degrees Celsius, truncated toward zero
+    pub const fn to_whole(self) -> i16 {
+        self.0 / 10
+    }
+
+    /// Tenths digit (always positive)
+    pub const fn tenths(self) -> u8 {
+        (self.0 % 10).unsigned_abs() as u8
+    }
+}
+
+impl Add for TemperatureC10 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for TemperatureC10 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl AddAssign for TemperatureC10 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for TemperatureC10 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Formats as degrees with one decimal, e.g. `45.2` or `-0.5`
+impl fmt::Display for TemperatureC10 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // -0.5 truncates to 0 whole degrees, so the sign is written separately
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(
+            f,
+            "{}{}.{}",
+            sign,
+            self.to_whole().unsigned_abs(),
+            self.tenths()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for TemperatureC10 {
+    fn format(&self, f: defmt::Formatter) {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        defmt::write!(
+            f,
+            "{=str}{}.{}",
+            sign,
+            self.to_whole().unsigned_abs(),
+            self.tenths()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    fn display(t: TemperatureC10) -> heapless::String<8> {
+        let mut s = heapless::String::new();
+        write!(s, "{}", t).unwrap();
+        s
+    }
+
+    #[test]
+    fn test_conversions() {
+        let t = TemperatureC10::from_whole(45);
+        assert_eq!(t.as_x10(), 450);
+        assert_eq!(t.to_whole(), 45);
+
+        let t = TemperatureC10::from_x10(457);
+        assert_eq!(t.to_whole(), 45);
+        assert_eq!(t.tenths(), 7);
+
+        let t = TemperatureC10::from_x10(-15);
+        assert_eq!(t.to_whole(), -1);
+        assert_eq!(t.tenths(), 5);
+
+        assert_eq!(TemperatureC10::from_whole(i16::MAX).as_x10(), i16::MAX);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut t = TemperatureC10::from_whole(40) + TemperatureC10::from_x10(25);
+        assert_eq!(t.as_x10(), 425);
+
+        t -= TemperatureC10::from_whole(50);
+        assert_eq!(t.as_x10(), -75);
+
+        t += TemperatureC10::from_x10(75);
+        assert_eq!(t, TemperatureC10::ZERO);
+
+        let hot = TemperatureC10::from_x10(i16::MAX);
+        assert_eq!((hot + hot).as_x10(), i16::MAX);
+        assert!(TemperatureC10::from_x10(551) > TemperatureC10::from_whole(55));
+    }
+
+    #[test]
+    fn test_formatting() {
+        assert_eq!(display(TemperatureC10::from_x10(452)), "45.2");
+        assert_eq!(display(TemperatureC10::from_whole(40)), "40.0");
+        assert_eq!(display(TemperatureC10::from_x10(-5)), "-0.5");
+        assert_eq!(display(TemperatureC10::from_x10(-123)), "-12.3");
+    }
+}
@@ -2,9 +2,10 @@
 //!
 //! Optional accessories like ultrasonic cleaners, neopixels, fans, etc.
 
+pub mod ultrasonic;
+
 // These will be implemented in later phases
 
-// pub mod ultrasonic;
 // pub mod neopixel;
 // pub mod fan;
 // pub mod speaker;
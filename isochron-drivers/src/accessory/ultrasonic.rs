@@ -0,0 +1,256 @@
+//! Ultrasonic transducer driver
+//!
+//! Switches an ultrasonic cleaning module from the scheduler's
+//! [`AccessoryCommand`]. Simple modules have an on/off input driven from a
+//! GPIO (directly or via a relay); others accept a PWM signal whose duty
+//! sets the power.
+//!
+//! Running a transducer in a jar that has been drained or for too long
+//! overheats it, so the driver cuts it off after a maximum continuous
+//! on-time regardless of the command. It stays off until the command goes
+//! off again, so a stuck "on" can't restart it.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! let mut ultrasonic = Ultrasonic::new(PwmOutput(pwm), UltrasonicConfig::default());
+//!
+//! // Whenever the scheduler's command changes or on every tick:
+//! ultrasonic.apply(scheduler.ultrasonic_command(), now_ms);
+//! ultrasonic.update(now_ms);
+//! ```
+
+use isochron_core::scheduler::AccessoryCommand;
+use isochron_hal::{OutputPin, PwmPin};
+
+/// Transducer drive output
+///
+/// `duty_percent` is 0 when off; outputs without intensity control treat
+/// any other value as on.
+pub trait TransducerOutput {
+    /// Drive the transducer at `duty_percent` (0-100)
+    fn set_duty_percent(&mut self, duty_percent: u8);
+}
+
+/// On/off transducer on a GPIO pin (active high)
+pub struct SwitchedOutput<P>(pub P);
+
+impl<P: OutputPin> TransducerOutput for SwitchedOutput<P> {
+    fn set_duty_percent(&mut self, duty_percent: u8) {
+        self.0.set_state(duty_percent > 0);
+    }
+}
+
+/// Transducer with a PWM intensity input
+pub struct PwmOutput<P>(pub P);
+
+impl<P: PwmPin> TransducerOutput for PwmOutput<P> {
+    fn set_duty_percent(&mut self, duty_percent: u8) {
+        self.0.set_duty_percent(duty_percent);
+    }
+}
+
+/// Ultrasonic transducer configuration
+#[derive(Debug, Clone)]
+pub struct UltrasonicConfig {
+    /// Intensity when on (0-100%)
+    pub intensity: u8,
+    /// PWM duty at the lowest intensity (%)
+    ///
+    /// Many modules don't oscillate below a certain drive level.
+    pub min_duty_percent: u8,
+    /// PWM duty at full intensity (%)
+    pub max_duty_percent: u8,
+    /// Maximum continuous on-time before a safety cutoff (ms, 0 = no limit)
+    pub max_on_ms: u32,
+}
+
+impl Default for UltrasonicConfig {
+    fn default() -> Self {
+        Self {
+            intensity: 100,
+            min_duty_percent: 0,
+            max_duty_percent: 100,
+            max_on_ms: 15 * 60 * 1000,
+        }
+    }
+}
+
+impl UltrasonicConfig {
+    /// Duty cycle for the configured intensity
+    ///
+    /// Maps 1-100% intensity onto `min_duty_percent..=max_duty_percent`;
+    /// 0% intensity is off.
+    pub fn duty_percent(&self) -> u8 {
+        if self.intensity == 0 {
+            return 0;
+        }
+        let max = self.max_duty_percent.min(100) as u32;
+        let min = (self.min_duty_percent as u32).min(max);
+        let intensity = self.intensity.min(100) as u32;
+        (min + (max - min) * intensity / 100) as u8
+    }
+}
+
+/// Ultrasonic transducer
+pub struct Ultrasonic<O> {
+    output: O,
+    config: UltrasonicConfig,
+    /// When the transducer was switched on (ms), if it is on
+    on_since_ms: Option<u32>,
+    /// Cut off by the on-time limit, waiting for an off command
+    cut_off: bool,
+}
+
+impl<O: TransducerOutput> Ultrasonic<O> {
+    /// Create a new driver with the transducer off
+    pub fn new(output: O, config: UltrasonicConfig) -> Self {
+        let mut ultrasonic = Self {
+            output,
+            config,
+            on_since_ms: None,
+            cut_off: false,
+        };
+        ultrasonic.output.set_duty_percent(0);
+        ultrasonic
+    }
+
+    /// Apply a scheduler command
+    ///
+    /// Repeating an "on" command doesn't restart the on-time limit.
+    pub fn apply(&mut self, command: AccessoryCommand, now_ms: u32) {
+        if !command.on {
+            self.cut_off = false;
+            self.switch_off();
+        } else if self.on_since_ms.is_none() && !self.cut_off {
+            self.output.set_duty_percent(self.config.duty_percent());
+            self.on_since_ms = Some(now_ms);
+        }
+    }
+
+    /// Enforce the maximum on-time
+    ///
+    /// Call periodically while the transducer may be on.
+    pub fn update(&mut self, now_ms: u32) {
+        let Some(since) = self.on_since_ms else {
+            return;
+        };
+        let max_on_ms = self.config.max_on_ms;
+        if max_on_ms > 0 && now_ms.wrapping_sub(since) >= max_on_ms {
+            self.cut_off = true;
+            self.switch_off();
+        }
+    }
+
+    /// Switch off immediately, e.g. on a fault
+    pub fn shutdown(&mut self) {
+        self.switch_off();
+    }
+
+    /// Check if the transducer is on
+    pub fn is_on(&self) -> bool {
+        self.on_since_ms.is_some()
+    }
+
+    /// Check if the on-time limit has cut the transducer off
+    pub fn is_cut_off(&self) -> bool {
+        self.cut_off
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &UltrasonicConfig {
+        &self.config
+    }
+
+    /// Release the output
+    pub fn release(self) -> O {
+        self.output
+    }
+
+    fn switch_off(&mut self) {
+        self.output.set_duty_percent(0);
+        self.on_since_ms = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isochron_hal::mock::{MockOutputPin, MockPwmPin};
+
+    fn config(max_on_ms: u32) -> UltrasonicConfig {
+        UltrasonicConfig {
+            max_on_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_follows_command() {
+        let mut us = Ultrasonic::new(SwitchedOutput(MockOutputPin::new(false)), config(0));
+        assert!(!us.is_on());
+
+        us.apply(AccessoryCommand::on(), 0);
+        us.apply(AccessoryCommand::on(), 100);
+        assert!(us.is_on());
+
+        us.apply(AccessoryCommand::off(), 200);
+        assert!(!us.is_on());
+
+        let pin = us.release().0;
+        assert_eq!(pin.history(), &[false, true, false]);
+    }
+
+    #[test]
+    fn test_max_on_time_cuts_off() {
+        let mut us = Ultrasonic::new(SwitchedOutput(MockOutputPin::new(false)), config(1000));
+        us.apply(AccessoryCommand::on(), 500);
+
+        // Repeated commands don't extend the limit
+        us.apply(AccessoryCommand::on(), 1000);
+        us.update(1499);
+        assert!(us.is_on());
+        us.update(1500);
+        assert!(!us.is_on());
+        assert!(us.is_cut_off());
+
+        // Stays off while the command is still on
+        us.apply(AccessoryCommand::on(), 1600);
+        assert!(!us.is_on());
+
+        // Re-armed by an off command
+        us.apply(AccessoryCommand::off(), 1700);
+        assert!(!us.is_cut_off());
+        us.apply(AccessoryCommand::on(), 1800);
+        assert!(us.is_on());
+
+        let pin = us.release().0;
+        assert!(pin.is_set_high());
+    }
+
+    #[test]
+    fn test_intensity_maps_to_duty_range() {
+        let mut config = UltrasonicConfig {
+            intensity: 50,
+            min_duty_percent: 40,
+            max_duty_percent: 80,
+            max_on_ms: 0,
+        };
+        assert_eq!(config.duty_percent(), 60);
+
+        config.intensity = 100;
+        assert_eq!(config.duty_percent(), 80);
+        config.intensity = 1;
+        assert_eq!(config.duty_percent(), 40);
+        config.intensity = 0;
+        assert_eq!(config.duty_percent(), 0);
+
+        config.intensity = 50;
+        let mut us = Ultrasonic::new(PwmOutput(MockPwmPin::new(1000)), config);
+        us.apply(AccessoryCommand::on(), 0);
+        us.apply(AccessoryCommand::off(), 10);
+
+        let pwm = us.release().0;
+        assert_eq!(pwm.history(), &[0, 600, 0]);
+    }
+}
@@ -18,6 +18,18 @@ const MAX_PEAKS: usize = 24;
 /// Maximum autotune duration in ticks (20 minutes at 500ms = 2400 ticks)
 const MAX_TICKS: u32 = 2400;
 
+/// Smallest oscillation amplitude (°C × 10) considered measurable
+pub const MIN_AMPLITUDE_X10: i32 = 5;
+
+/// Shortest oscillation period (ticks) considered measurable
+pub const MIN_PERIOD_TICKS: u32 = 4;
+
+/// Largest gain (×100) accepted from a tune
+///
+/// Gains are stored as `i16`, so anything above this cannot be represented
+/// and indicates a bad measurement rather than a real plant.
+pub const MAX_GAIN_X100: i64 = i16::MAX as i64;
+
 /// Autotune state machine
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -111,6 +123,68 @@ pub struct AutotuneResult {
     pub amplitude_x10: i16,
 }
 
+/// Gains produced by the Ziegler-Nichols rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ZieglerNichols {
+    /// Ultimate gain (Ku × 100)
+    pub ku_x100: i32,
+    /// Proportional gain (× 100)
+    pub kp_x100: i16,
+    /// Integral gain (× 100)
+    pub ki_x100: i16,
+    /// Derivative gain (× 100)
+    pub kd_x100: i16,
+}
+
+/// Calculate PID gains from a relay oscillation
+///
+/// Uses i64 intermediates so no combination of inputs can overflow.
+/// Returns `None` when the oscillation is too small or too fast to
+/// measure, or when the resulting gains fall outside what can be stored
+/// (Kp and Ki must be at least 0.01, all gains at most `MAX_GAIN_X100`).
+/// Implausible gains are rejected rather than clamped so they are never
+/// saved.
+pub fn ziegler_nichols(
+    relay_output: u8,
+    amplitude_x10: i32,
+    tu_ticks: u32,
+) -> Option<ZieglerNichols> {
+    if amplitude_x10 < MIN_AMPLITUDE_X10 || tu_ticks < MIN_PERIOD_TICKS {
+        return None;
+    }
+
+    // Ku = 4 * d / (π * a), d = relay output, using π ≈ 314/100
+    //
+    // Ku_x100 = (4 * d * 100 * 100) / (314 * amplitude)
+    let d = relay_output as i64;
+    let amplitude = amplitude_x10 as i64;
+    let tu = tu_ticks as i64;
+    let ku_x100 = (4 * d * 10000) / (314 * amplitude);
+
+    // Ziegler-Nichols PID:
+    // Kp = 0.6 * Ku
+    // Ki = 1.2 * Ku / Tu
+    // Kd = 0.075 * Ku * Tu
+    let kp_x100 = (60 * ku_x100) / 100;
+    let ki_x100 = (120 * ku_x100) / (100 * tu);
+    let kd_x100 = (75 * ku_x100 * tu) / 10000;
+
+    if kp_x100 < 1 || ki_x100 < 1 || kd_x100 < 0 {
+        return None;
+    }
+    if kp_x100 > MAX_GAIN_X100 || ki_x100 > MAX_GAIN_X100 || kd_x100 > MAX_GAIN_X100 {
+        return None;
+    }
+
+    Some(ZieglerNichols {
+        ku_x100: ku_x100 as i32,
+        kp_x100: kp_x100 as i16,
+        ki_x100: ki_x100 as i16,
+        kd_x100: kd_x100 as i16,
+    })
+}
+
 /// Autotuner state machine
 pub struct Autotuner<S, H> {
     sensor: S,
@@ -320,53 +394,25 @@ impl<S: TemperatureSensor, H: HeaterOutput> Autotuner<S, H> {
             }
         };
 
-        // Check for meaningful oscillation
-        if amplitude_x10 < 5 || period_ticks < 4 {
-            self.state = AutotuneState::Failed(AutotuneError::NoOscillation);
-            return;
-        }
-
-        // Calculate ultimate gain Ku
-        // Ku = 4 * d / (π * a)
-        // where d = relay output (255), a = amplitude
-        //
-        // Using fixed point: Ku_x100 = (4 * 255 * 100) / (π * amplitude)
-        // π ≈ 314/100 for fixed-point approximation
-        //
-        // Ku = (4 * 255) / (3.14159 * amplitude)
-        // Ku_x100 = (4 * 255 * 100 * 100) / (314 * amplitude)
-        let d = self.config.relay_output as i32;
-        let ku_x100 = (4 * d * 10000) / (314 * amplitude_x10 as i32);
-
-        // Ultimate period Tu = period_ticks (in control loop ticks)
-        let tu_ticks = period_ticks;
-
-        // Ziegler-Nichols PID tuning:
-        // Kp = 0.6 * Ku
-        // Ki = 1.2 * Ku / Tu
-        // Kd = 0.075 * Ku * Tu
-        //
-        // As x100 values:
-        // Kp_x100 = 60 * Ku_x100 / 100
-        // Ki_x100 = 120 * Ku_x100 / (100 * Tu)
-        // Kd_x100 = 75 * Ku_x100 * Tu / 10000
-
-        let kp_x100 = (60 * ku_x100) / 100;
-        let ki_x100 = (120 * ku_x100) / (100 * tu_ticks as i32);
-        let kd_x100 = (75 * ku_x100 * tu_ticks as i32) / 10000;
-
-        // Clamp to i16 range for storage
-        let kp_x100 = kp_x100.clamp(0, i16::MAX as i32) as i16;
-        let ki_x100 = ki_x100.clamp(0, i16::MAX as i32) as i16;
-        let kd_x100 = kd_x100.clamp(0, i16::MAX as i32) as i16;
+        let gains =
+            match ziegler_nichols(self.config.relay_output, amplitude_x10 as i32, period_ticks) {
+                Some(gains) => gains,
+                None => {
+                    self.state = AutotuneState::Failed(AutotuneError::NoOscillation);
+                    return;
+                }
+            };
 
-        let coefficients =
-            PidCoefficients::from_scaled_100(kp_x100 as i32, ki_x100 as i32, kd_x100 as i32);
+        let coefficients = PidCoefficients::from_scaled_100(
+            gains.kp_x100 as i32,
+            gains.ki_x100 as i32,
+            gains.kd_x100 as i32,
+        );
 
         self.result = Some(AutotuneResult {
             coefficients,
-            ku_x100,
-            tu_ticks,
+            ku_x100: gains.ku_x100,
+            tu_ticks: period_ticks,
             amplitude_x10,
         });
 
@@ -554,4 +600,44 @@ mod tests {
         let kd_x100 = (75 * ku_x100 * tu_ticks as i32) / 10000;
         assert_eq!(kd_x100, 150); // Note: 1500/10 due to scaling
     }
+
+    #[test]
+    fn test_ziegler_nichols_typical() {
+        // 1.0°C amplitude, 40 tick period
+        let gains = ziegler_nichols(255, 10, 40).unwrap();
+        assert_eq!(gains.ku_x100, 3248);
+        assert_eq!(gains.kp_x100, 1948);
+        assert_eq!(gains.ki_x100, 97);
+        assert_eq!(gains.kd_x100, 974);
+    }
+
+    #[test]
+    fn test_ziegler_nichols_rejects_tiny_oscillation() {
+        assert_eq!(ziegler_nichols(255, 4, 40), None);
+        assert_eq!(ziegler_nichols(255, 0, 40), None);
+        assert_eq!(ziegler_nichols(255, -10, 40), None);
+        assert_eq!(ziegler_nichols(255, 10, 3), None);
+        assert_eq!(ziegler_nichols(255, 10, 0), None);
+    }
+
+    #[test]
+    fn test_ziegler_nichols_large_values_do_not_overflow() {
+        // Huge amplitude: Ku rounds towards zero, gains vanish
+        assert_eq!(ziegler_nichols(255, i32::MAX, 40), None);
+        // Huge period: Kd would far exceed i16 range
+        assert_eq!(ziegler_nichols(255, 10, u32::MAX), None);
+        assert_eq!(ziegler_nichols(255, i32::MAX, u32::MAX), None);
+    }
+
+    #[test]
+    fn test_ziegler_nichols_rejects_implausible_gains() {
+        // Smallest amplitude is still plausible on its own...
+        assert!(ziegler_nichols(255, MIN_AMPLITUDE_X10, MIN_PERIOD_TICKS).is_some());
+        // ...but paired with a slow period Kd cannot be stored
+        assert_eq!(ziegler_nichols(255, MIN_AMPLITUDE_X10, 1000), None);
+        // Long period drives Ki to zero
+        assert_eq!(ziegler_nichols(255, 50, 2000), None);
+        // No relay output means no gain at all
+        assert_eq!(ziegler_nichols(0, 10, 40), None);
+    }
 }
@@ -1,6 +1,7 @@
 //! GPIO heater output
 //!
-//! Simple heater control using a GPIO pin (directly or via SSR/MOSFET).
+//! Simple heater control using a GPIO pin (directly or via SSR/MOSFET),
+//! with an optional enable pin for a separate safety relay.
 
 use isochron_core::traits::HeaterOutput;
 
@@ -16,16 +17,63 @@ pub trait OutputPin {
     fn is_set_high(&self) -> bool;
 }
 
+/// Heater enable output
+///
+/// Drives a safety relay that must be on for the heater to work. It is
+/// asserted while heating is permitted, independent of the heater pin
+/// switching for regulation.
+pub struct EnablePin<P> {
+    pin: P,
+    /// If true, enabled = pin LOW
+    inverted: bool,
+    /// Current logical state (true = asserted)
+    enabled: bool,
+}
+
+impl<P: OutputPin> EnablePin<P> {
+    /// Create a new enable output, initially de-asserted
+    pub fn new(pin: P, inverted: bool) -> Self {
+        let mut enable = Self {
+            pin,
+            inverted,
+            enabled: false,
+        };
+        enable.set_enabled(false);
+        enable
+    }
+
+    /// Assert or de-assert the enable output
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if enabled != self.inverted {
+            self.pin.set_high();
+        } else {
+            self.pin.set_low();
+        }
+    }
+
+    /// Check if the enable output is asserted
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
 /// GPIO heater output
 ///
 /// Controls a heater via a GPIO pin. The pin can be configured as
 /// active-high (default) or active-low.
+///
+/// If an enable pin is attached, it is asserted before the heater is
+/// switched on and only de-asserted by [`GpioHeater::shutdown`].
 pub struct GpioHeater<P> {
     pin: P,
     /// If true, heater ON = pin LOW
     inverted: bool,
     /// Current logical state (true = heater on)
     on: bool,
+    /// Optional heater enable (safety relay) output
+    enable: Option<EnablePin<P>>,
 }
 
 impl<P: OutputPin> GpioHeater<P> {
@@ -39,6 +87,7 @@ impl<P: OutputPin> GpioHeater<P> {
             pin,
             inverted,
             on: false,
+            enable: None,
         };
         // Ensure heater starts off
         heater.set_on(false);
@@ -54,10 +103,37 @@ impl<P: OutputPin> GpioHeater<P> {
     pub fn new_active_low(pin: P) -> Self {
         Self::new(pin, true)
     }
+
+    /// Attach a heater enable pin, initially de-asserted
+    pub fn with_enable_pin(mut self, pin: P, inverted: bool) -> Self {
+        self.enable = Some(EnablePin::new(pin, inverted));
+        self
+    }
+
+    /// Whether the enable pin is asserted (None if no enable pin)
+    pub fn is_enabled(&self) -> Option<bool> {
+        self.enable.as_ref().map(EnablePin::is_enabled)
+    }
+
+    /// Turn the heater off and de-assert the enable pin
+    ///
+    /// Use on faults and when heating is no longer permitted. Plain
+    /// `set_on(false)` leaves the enable pin asserted for regulation.
+    pub fn shutdown(&mut self) {
+        self.set_on(false);
+        if let Some(enable) = &mut self.enable {
+            enable.set_enabled(false);
+        }
+    }
 }
 
 impl<P: OutputPin> HeaterOutput for GpioHeater<P> {
     fn set_on(&mut self, on: bool) {
+        if on {
+            if let Some(enable) = &mut self.enable {
+                enable.set_enabled(true);
+            }
+        }
         self.on = on;
 
         if on != self.inverted {
@@ -157,4 +233,55 @@ mod tests {
 
         check_heater(&mut heater);
     }
+
+    #[test]
+    fn test_enable_pin_follows_heating() {
+        let mut heater =
+            GpioHeater::new_active_high(MockPin::new()).with_enable_pin(MockPin::new(), false);
+        assert_eq!(heater.is_enabled(), Some(false));
+
+        // Heating asserts the enable pin
+        heater.set_on(true);
+        assert_eq!(heater.is_enabled(), Some(true));
+        assert!(heater.enable.as_ref().unwrap().pin.is_set_high());
+
+        // Regulating off keeps it asserted
+        heater.set_on(false);
+        assert_eq!(heater.is_enabled(), Some(true));
+
+        // Shutdown de-asserts both
+        heater.set_on(true);
+        heater.shutdown();
+        assert!(!heater.is_on());
+        assert!(!heater.pin.is_set_high());
+        assert_eq!(heater.is_enabled(), Some(false));
+        assert!(!heater.enable.as_ref().unwrap().pin.is_set_high());
+    }
+
+    #[test]
+    fn test_inverted_enable_pin() {
+        let mut heater =
+            GpioHeater::new_active_high(MockPin::new()).with_enable_pin(MockPin::new(), true);
+
+        // De-asserted active-low enable idles high
+        assert!(heater.enable.as_ref().unwrap().pin.is_set_high());
+
+        heater.set_on(true);
+        assert!(!heater.enable.as_ref().unwrap().pin.is_set_high());
+
+        heater.shutdown();
+        assert!(heater.enable.as_ref().unwrap().pin.is_set_high());
+    }
+
+    #[test]
+    fn test_no_enable_pin_unchanged() {
+        let mut heater = GpioHeater::new_active_high(MockPin::new());
+        assert_eq!(heater.is_enabled(), None);
+
+        heater.set_on(true);
+        assert!(heater.pin.is_set_high());
+        heater.shutdown();
+        assert!(!heater.pin.is_set_high());
+        assert_eq!(heater.is_enabled(), None);
+    }
 }
@@ -5,9 +5,14 @@ pub mod bang_bang;
 pub mod fixed;
 pub mod gpio;
 pub mod pid;
+pub mod ramp;
 
-pub use autotune::{AutotuneConfig, AutotuneError, AutotuneResult, AutotuneState, Autotuner};
+pub use autotune::{
+    ziegler_nichols, AutotuneConfig, AutotuneError, AutotuneResult, AutotuneState, Autotuner,
+    ZieglerNichols,
+};
 pub use bang_bang::{BangBangConfig, BangBangController};
 pub use fixed::Fixed32;
-pub use gpio::{GpioHeater, OutputPin};
+pub use gpio::{EnablePin, GpioHeater, OutputPin};
 pub use pid::{PidCoefficients, PidConfig, PidController};
+pub use ramp::SetpointRamp;
@@ -0,0 +1,196 @@
+//! Heating rate limit
+//!
+//! Raising a cold jar straight to its target at full power can crack
+//! delicate parts. The ramp limits how fast the setpoint handed to the
+//! controller (bang-bang or PID) rises: it starts at the temperature the
+//! jar is at when a new target arrives and climbs at a fixed rate until it
+//! reaches the target, then holds there. If the jar warms faster than the
+//! ramp (a preheated jar, or residual heat after an overshoot) by more
+//! than [`RESTART_MARGIN_X10`], the ramp restarts from the measured
+//! temperature rather than holding the heater off until the setpoint
+//! catches up. The margin keeps sensor noise around a tracked setpoint
+//! from ratcheting it up faster than the rate limit. Cooling is never
+//! limited, since the heater can only slow it down anyway.
+//!
+//! The ramp only shapes the setpoint; the heater's max-temp cutoff still
+//! applies to the measured temperature.
+
+/// How far the measured temperature must be above the ramped setpoint to
+/// restart the ramp from it (°C × 10)
+pub const RESTART_MARGIN_X10: i16 = 10;
+
+/// Setpoint ramp for a heating rate limit
+#[derive(Debug, Clone)]
+pub struct SetpointRamp {
+    /// Maximum heating rate (°C/min, 0 = no limit)
+    rate_c_per_min: u16,
+    /// Target the ramp is heading for (°C × 10)
+    target_x10: Option<i16>,
+    /// Setpoint the ramp started from (°C × 10)
+    start_x10: i16,
+    /// Time since the ramp started (ms)
+    elapsed_ms: u32,
+}
+
+impl SetpointRamp {
+    /// Create a ramp limiting heating to `rate_c_per_min`
+    pub fn new(rate_c_per_min: u16) -> Self {
+        Self {
+            rate_c_per_min,
+            target_x10: None,
+            start_x10: 0,
+            elapsed_ms: 0,
+        }
+    }
+
+    /// Effective setpoint after another `dt_ms` (°C × 10)
+    ///
+    /// A target different from the last one restarts the ramp from
+    /// `current_x10`, the measured temperature, as does a measured
+    /// temperature at least [`RESTART_MARGIN_X10`] above the ramped
+    /// setpoint.
+    pub fn update(&mut self, target_x10: i16, current_x10: i16, dt_ms: u32) -> i16 {
+        if self.rate_c_per_min == 0 {
+            return target_x10;
+        }
+
+        if self.target_x10 != Some(target_x10) {
+            self.target_x10 = Some(target_x10);
+            self.start_x10 = current_x10;
+            self.elapsed_ms = 0;
+        } else {
+            self.elapsed_ms = self.elapsed_ms.saturating_add(dt_ms);
+        }
+
+        if target_x10 <= self.start_x10 {
+            return target_x10;
+        }
+        let rise_x10 = self.rate_c_per_min as u64 * 10 * self.elapsed_ms as u64 / 60_000;
+        let span_x10 = (target_x10 - self.start_x10) as u64;
+        let setpoint_x10 = self.start_x10 + rise_x10.min(span_x10) as i16;
+
+        if current_x10 >= setpoint_x10.saturating_add(RESTART_MARGIN_X10) {
+            self.start_x10 = current_x10.min(target_x10);
+            self.elapsed_ms = 0;
+            return self.start_x10;
+        }
+        setpoint_x10
+    }
+
+    /// Forget the current ramp, e.g. when the heater is switched off
+    pub fn reset(&mut self) {
+        self.target_x10 = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setpoint_rises_at_rate_limit() {
+        // 2°C/min from 20°C towards 45°C, updated every 500ms
+        let mut ramp = SetpointRamp::new(2);
+        let mut prev = ramp.update(450, 200, 500);
+        assert_eq!(prev, 200);
+
+        for tick in 1..=120 {
+            let setpoint = ramp.update(450, 200, 500);
+            assert!(setpoint >= prev);
+            // Never ahead of 2°C/min (0.2°C × 10 per 6s)
+            assert!((setpoint - 200) as u32 * 60_000 <= 2 * 10 * tick * 500);
+            prev = setpoint;
+        }
+        // One minute in: 2°C above the start
+        assert_eq!(prev, 220);
+    }
+
+    #[test]
+    fn test_setpoint_holds_at_target() {
+        let mut ramp = SetpointRamp::new(5);
+        ramp.update(300, 250, 500);
+
+        // 5°C to go at 5°C/min: there after a minute, and stays there
+        let mut setpoint = 0;
+        for _ in 0..120 {
+            setpoint = ramp.update(300, 280, 500);
+        }
+        assert_eq!(setpoint, 300);
+        for _ in 0..120 {
+            assert_eq!(ramp.update(300, 300, 500), 300);
+        }
+    }
+
+    #[test]
+    fn test_new_target_restarts_from_current_temperature() {
+        let mut ramp = SetpointRamp::new(1);
+        ramp.update(400, 200, 500);
+        for _ in 0..240 {
+            ramp.update(400, 210, 500);
+        }
+
+        // Cooling is not limited
+        assert_eq!(ramp.update(150, 260, 500), 150);
+
+        ramp.reset();
+        assert_eq!(ramp.update(400, 260, 500), 260);
+    }
+
+    #[test]
+    fn test_ramp_restarts_below_current_temperature() {
+        // 1°C/min from 20°C; after a minute the setpoint is at 21°C
+        let mut ramp = SetpointRamp::new(1);
+        ramp.update(450, 200, 500);
+        for _ in 0..120 {
+            ramp.update(450, 200, 500);
+        }
+        assert_eq!(ramp.update(450, 205, 0), 210);
+
+        // The jar has run ahead to 30°C: climb on from there
+        assert_eq!(ramp.update(450, 300, 500), 300);
+        for _ in 0..120 {
+            ramp.update(450, 290, 500);
+        }
+        assert_eq!(ramp.update(450, 290, 0), 310);
+
+        // Already past the target: hold the target
+        assert_eq!(ramp.update(450, 470, 500), 450);
+        assert_eq!(ramp.update(450, 440, 500), 450);
+    }
+
+    #[test]
+    fn test_noise_does_not_speed_up_ramp() {
+        // 2°C/min from 20°C, readings scattered just under 1°C either side
+        // of the setpoint as a PID loop tracking it would give
+        let noise = [9, -9, 5, 9, -3, 8, -9, 9];
+        let mut ramp = SetpointRamp::new(2);
+        let mut setpoint = ramp.update(450, 200, 500);
+        for tick in 1..=240u32 {
+            let reading = setpoint + noise[tick as usize % noise.len()];
+            setpoint = ramp.update(450, reading, 500);
+            assert!((setpoint - 200) as u32 * 60_000 <= 2 * 10 * tick * 500);
+        }
+        // Two minutes in: still only 4°C above the start
+        assert_eq!(setpoint, 240);
+    }
+
+    #[test]
+    fn test_ramp_reaches_target_in_closed_loop() {
+        // A jar that tracks the setpoint with a lag still ends at the target
+        let mut ramp = SetpointRamp::new(3);
+        let mut temp = 200;
+        let mut setpoint = 0;
+        for _ in 0..2_000 {
+            setpoint = ramp.update(450, temp, 500);
+            temp += (setpoint - temp).signum();
+        }
+        assert_eq!(setpoint, 450);
+        assert_eq!(temp, 450);
+    }
+
+    #[test]
+    fn test_zero_rate_is_unlimited() {
+        let mut ramp = SetpointRamp::new(0);
+        assert_eq!(ramp.update(450, 200, 500), 450);
+    }
+}
@@ -6,7 +6,7 @@
 //! - Motor drivers (DC PWM, AC relay)
 //! - Stepper drivers (TMC2209, TMC2130, A4988)
 //! - Heater controllers (bang-bang, PID)
-//! - Temperature sensors (NTC thermistor)
+//! - Temperature sensors (NTC thermistor, TMP117 over I2C, DS18B20 over 1-Wire)
 //! - Accessories (ultrasonic, neopixel, fan, speaker)
 
 #![no_std]
@@ -2,7 +2,8 @@
 //!
 //! This driver provides:
 //! - PWM duty cycle control (0-100%)
-//! - Soft start/stop ramping for smooth acceleration
+//! - Soft start/stop ramping for smooth acceleration, following a shared
+//!   [`RampProfile`] in percent of full speed
 //! - Direction control for H-bridge drivers
 //! - Minimum duty cycle handling (below which motor won't start)
 //!
@@ -22,6 +23,7 @@
 //! pwm.set_duty(duty);
 //! ```
 
+use isochron_core::motion::RampProfile;
 use isochron_core::traits::{DcMotorDriver, DcMotorState, Direction, MotorDriver, MotorError};
 
 /// DC motor driver configuration
@@ -29,10 +31,10 @@ use isochron_core::traits::{DcMotorDriver, DcMotorState, Direction, MotorDriver,
 pub struct DcMotorConfig {
     /// Minimum duty cycle percentage (below this the motor won't start)
     pub min_duty: u8,
-    /// Soft start ramp time in ms (0 = instant)
-    pub soft_start_ms: u16,
-    /// Soft stop ramp time in ms (0 = instant)
-    pub soft_stop_ms: u16,
+    /// Soft start ramp (speeds in percent)
+    pub ramp: RampProfile,
+    /// Soft stop ramp (speeds in percent)
+    pub stop_ramp: RampProfile,
     /// Whether direction control is available
     pub has_direction: bool,
 }
@@ -41,8 +43,8 @@ impl Default for DcMotorConfig {
     fn default() -> Self {
         Self {
             min_duty: 20,
-            soft_start_ms: 500,
-            soft_stop_ms: 300,
+            ramp: RampProfile::from_ramp_time(500, 100),
+            stop_ramp: RampProfile::from_ramp_time(300, 100),
             has_direction: true,
         }
     }
@@ -130,24 +132,13 @@ impl DcMotor {
     }
 
     /// Calculate the ramped speed for the current time
-    fn calculate_ramp_speed(&self, ramp_time_total_ms: u16) -> u8 {
-        if ramp_time_total_ms == 0 {
-            // No ramping, instant change
-            return self.ramp_end_speed;
-        }
-
-        let progress = (self.ramp_time_ms * 100) / ramp_time_total_ms as u32;
-        let progress = progress.min(100) as u8;
-
-        if self.ramp_start_speed < self.ramp_end_speed {
-            // Ramping up
-            let delta = self.ramp_end_speed - self.ramp_start_speed;
-            self.ramp_start_speed + (delta as u32 * progress as u32 / 100) as u8
-        } else {
-            // Ramping down
-            let delta = self.ramp_start_speed - self.ramp_end_speed;
-            self.ramp_start_speed - (delta as u32 * progress as u32 / 100) as u8
-        }
+    fn calculate_ramp_speed(&self, ramp: &RampProfile) -> u8 {
+        let speed = ramp.rpm_at(
+            self.ramp_start_speed as u16,
+            self.ramp_end_speed as u16,
+            self.ramp_time_ms,
+        );
+        speed.min(100) as u8
     }
 
     /// Start the ramp to a new target speed
@@ -173,7 +164,7 @@ impl DcMotor {
             }
             DcMotorState::Starting => {
                 self.ramp_time_ms += delta_ms;
-                self.actual_speed = self.calculate_ramp_speed(self.config.soft_start_ms);
+                self.actual_speed = self.calculate_ramp_speed(&self.config.ramp);
 
                 if self.actual_speed >= self.target_speed {
                     self.actual_speed = self.target_speed;
@@ -198,13 +189,13 @@ impl DcMotor {
             }
             DcMotorState::Stopping => {
                 self.ramp_time_ms += delta_ms;
-                self.actual_speed = self.calculate_ramp_speed(self.config.soft_stop_ms);
+                self.actual_speed = self.calculate_ramp_speed(&self.config.stop_ramp);
 
                 if self.actual_speed == 0 {
                     self.state = DcMotorState::Stopped;
-                } else if self.ramp_time_ms >= self.config.soft_stop_ms as u32 {
-                    self.actual_speed = 0;
-                    self.state = DcMotorState::Stopped;
+                } else if self.actual_speed == self.ramp_end_speed {
+                    // Slowed to a lower running speed
+                    self.state = DcMotorState::Running;
                 }
             }
         }
@@ -350,8 +341,8 @@ mod tests {
     fn test_soft_start() {
         let config = DcMotorConfig {
             min_duty: 0,
-            soft_start_ms: 100,
-            soft_stop_ms: 100,
+            ramp: RampProfile::from_ramp_time(100, 100),
+            stop_ramp: RampProfile::from_ramp_time(100, 100),
             has_direction: true,
         };
         let mut motor = DcMotor::new(config);
@@ -381,8 +372,8 @@ mod tests {
     fn test_soft_stop() {
         let config = DcMotorConfig {
             min_duty: 0,
-            soft_start_ms: 0, // Instant start
-            soft_stop_ms: 100,
+            ramp: RampProfile::from_ramp_time(0, 100), // Instant start
+            stop_ramp: RampProfile::from_ramp_time(100, 100),
             has_direction: true,
         };
         let mut motor = DcMotor::new(config);
@@ -416,8 +407,8 @@ mod tests {
     fn test_instant_start_stop() {
         let config = DcMotorConfig {
             min_duty: 0,
-            soft_start_ms: 0,
-            soft_stop_ms: 0,
+            ramp: RampProfile::from_ramp_time(0, 100),
+            stop_ramp: RampProfile::from_ramp_time(0, 100),
             has_direction: true,
         };
         let mut motor = DcMotor::new(config);
@@ -443,8 +434,8 @@ mod tests {
     fn test_duty_scaling() {
         let config = DcMotorConfig {
             min_duty: 20,
-            soft_start_ms: 0,
-            soft_stop_ms: 0,
+            ramp: RampProfile::from_ramp_time(0, 100),
+            stop_ramp: RampProfile::from_ramp_time(0, 100),
             has_direction: true,
         };
         let mut motor = DcMotor::new(config);
@@ -499,4 +490,47 @@ mod tests {
         motor.set_direction(Direction::Clockwise);
         assert_eq!(motor.get_direction(), Direction::Clockwise);
     }
+
+    #[test]
+    fn test_ramp_matches_stepper() {
+        use crate::stepper::{Tmc2209Config, Tmc2209Driver};
+        use isochron_core::motion::RampCurve;
+        use isochron_core::traits::StepperDriver;
+
+        // One profile for both: full speed in half a second, eased
+        let ramp = RampProfile {
+            accel_rate: 200,
+            curve: RampCurve::Smooth,
+            min_rpm: 0,
+            max_rpm: 100,
+        };
+
+        let mut motor = DcMotor::new(DcMotorConfig {
+            min_duty: 0,
+            ramp,
+            stop_ramp: ramp,
+            has_direction: true,
+        });
+        let mut stepper = Tmc2209Driver::new(Tmc2209Config::default());
+        stepper.set_ramp(ramp);
+
+        // Duty percent and RPM track each other through start and stop
+        motor.enable(true);
+        motor.set_speed(100);
+        motor.start().unwrap();
+        stepper.set_rpm(100);
+        for _ in 0..ramp.ramp_time_ms(0, 100) {
+            assert_eq!(motor.update(), stepper.update(1) as u8);
+        }
+        assert!(motor.is_at_speed());
+        assert!(stepper.is_at_speed());
+
+        motor.stop();
+        stepper.set_rpm(0);
+        for _ in 0..ramp.ramp_time_ms(100, 0) {
+            assert_eq!(motor.update(), stepper.update(1) as u8);
+        }
+        assert_eq!(motor.state(), DcMotorState::Stopped);
+        assert_eq!(stepper.current_rpm(), 0);
+    }
 }
@@ -0,0 +1,374 @@
+//! DS18B20 1-Wire digital temperature sensor
+//!
+//! Waterproof probes built around the DS18B20 can sit in the cleaning
+//! fluid itself. The sensor is the only device on its bus (addressed with
+//! Skip ROM) and must be powered from VDD; parasite power is not supported.
+//!
+//! A 12-bit conversion takes up to 750 ms, longer than the heater loop, so
+//! conversions run in the background: each read collects the finished
+//! conversion and starts the next, and while one is still running the
+//! previous temperature is reported.
+//!
+//! The bus is bit-banged with interrupts enabled, so an occasional
+//! scratchpad fails its CRC. It is read again a few times, and if the
+//! conversion stays unreadable the previous temperature is reported for
+//! up to [`MAX_FAILED_CONVERSIONS`] conversions before the sensor faults.
+
+use embedded_hal::delay::DelayNs;
+use isochron_core::traits::{SensorError, TemperatureSensor};
+use isochron_hal::{InputPin, OutputPin};
+
+use super::onewire::{crc8, OneWire, OneWireError};
+
+/// Function commands
+mod cmd {
+    /// Address the only device on the bus
+    pub const SKIP_ROM: u8 = 0xCC;
+    /// Start a temperature conversion
+    pub const CONVERT_T: u8 = 0x44;
+    /// Read the 9-byte scratchpad
+    pub const READ_SCRATCHPAD: u8 = 0xBE;
+}
+
+/// Scratchpad length, CRC byte included
+pub const SCRATCHPAD_LEN: usize = 9;
+
+/// Longest 12-bit conversion, with margin (ms)
+const CONVERSION_TIMEOUT_MS: u32 = 800;
+
+/// Reads in a row a conversion may still be running before it counts as failed
+const MAX_BUSY_READS: u8 = 4;
+
+/// Reads of one scratchpad before its conversion counts as failed
+const SCRATCHPAD_ATTEMPTS: u8 = 3;
+
+/// Failed conversions in a row covered by the previous temperature
+pub const MAX_FAILED_CONVERSIONS: u8 = 3;
+
+impl From<OneWireError> for SensorError {
+    fn from(err: OneWireError) -> Self {
+        match err {
+            OneWireError::NoPresence => SensorError::OpenCircuit,
+            OneWireError::BusShorted => SensorError::ShortCircuit,
+        }
+    }
+}
+
+/// DS18B20 sensor on a 1-Wire bus
+///
+/// A missing probe reads as [`SensorError::OpenCircuit`], a shorted bus
+/// as [`SensorError::ShortCircuit`], and scratchpads that keep failing
+/// their CRC as [`SensorError::ChecksumError`].
+pub struct Ds18b20Sensor<P, D> {
+    bus: OneWire<P, D>,
+    /// A conversion has been started and not yet collected
+    converting: bool,
+    /// Reads that found the conversion still running
+    busy_reads: u8,
+    /// Conversions in a row whose scratchpad failed its CRC
+    failed_conversions: u8,
+    /// Last good temperature (°C × 10)
+    last: Option<i16>,
+}
+
+impl<P: OutputPin + InputPin, D: DelayNs> Ds18b20Sensor<P, D> {
+    /// Create a sensor on `bus`
+    pub fn new(bus: OneWire<P, D>) -> Self {
+        Self {
+            bus,
+            converting: false,
+            busy_reads: 0,
+            failed_conversions: 0,
+            last: None,
+        }
+    }
+
+    /// Check that a sensor answers and take the first reading
+    ///
+    /// Blocks for one conversion (up to 750 ms), so call it at start-up.
+    pub fn probe(&mut self) -> Result<i16, SensorError> {
+        self.start_conversion()?;
+        let mut waited_ms = 0;
+        while !self.bus.read_bit() {
+            if waited_ms >= CONVERSION_TIMEOUT_MS {
+                self.converting = false;
+                return Err(SensorError::ConversionError);
+            }
+            self.bus.delay_us(1_000);
+            waited_ms += 1;
+        }
+        self.finish_conversion()
+    }
+
+    /// Convert a raw temperature register value to 0.1°C units
+    ///
+    /// At 12-bit resolution 1 LSB = 1/16 °C.
+    pub fn raw_to_temp_x10(raw: i16) -> i16 {
+        (raw as i32 * 10 / 16) as i16
+    }
+
+    /// Check a scratchpad's CRC and extract the temperature (°C × 10)
+    pub fn parse_scratchpad(scratchpad: &[u8; SCRATCHPAD_LEN]) -> Result<i16, SensorError> {
+        if crc8(scratchpad) != 0 {
+            return Err(SensorError::ChecksumError);
+        }
+        let raw = i16::from_le_bytes([scratchpad[0], scratchpad[1]]);
+        Ok(Self::raw_to_temp_x10(raw))
+    }
+
+    /// Release the bus
+    pub fn release(self) -> OneWire<P, D> {
+        self.bus
+    }
+
+    fn start_conversion(&mut self) -> Result<(), SensorError> {
+        self.bus.reset()?;
+        self.bus.write_byte(cmd::SKIP_ROM);
+        self.bus.write_byte(cmd::CONVERT_T);
+        self.converting = true;
+        self.busy_reads = 0;
+        Ok(())
+    }
+
+    /// Collect a finished conversion and start the next
+    fn finish_conversion(&mut self) -> Result<i16, SensorError> {
+        self.converting = false;
+        let mut result = Err(SensorError::ChecksumError);
+        for _ in 0..SCRATCHPAD_ATTEMPTS {
+            result = self
+                .read_scratchpad()
+                .and_then(|scratchpad| Self::parse_scratchpad(&scratchpad));
+            if result != Err(SensorError::ChecksumError) {
+                break;
+            }
+        }
+
+        match result {
+            Ok(temp_x10) => {
+                self.last = Some(temp_x10);
+                self.failed_conversions = 0;
+            }
+            Err(SensorError::ChecksumError) => {
+                self.failed_conversions = self.failed_conversions.saturating_add(1);
+                if self.failed_conversions > MAX_FAILED_CONVERSIONS {
+                    self.last = None;
+                }
+            }
+            Err(err) => {
+                self.last = None;
+                return Err(err);
+            }
+        }
+        self.start_conversion()?;
+        result.or(self.last.ok_or(SensorError::ChecksumError))
+    }
+
+    fn read_scratchpad(&mut self) -> Result<[u8; SCRATCHPAD_LEN], SensorError> {
+        self.bus.reset()?;
+        self.bus.write_byte(cmd::SKIP_ROM);
+        self.bus.write_byte(cmd::READ_SCRATCHPAD);
+        let mut scratchpad = [0u8; SCRATCHPAD_LEN];
+        for byte in scratchpad.iter_mut() {
+            *byte = self.bus.read_byte();
+        }
+        Ok(scratchpad)
+    }
+}
+
+impl<P: OutputPin + InputPin, D: DelayNs> TemperatureSensor for Ds18b20Sensor<P, D> {
+    fn read_celsius_x10(&mut self) -> Result<i16, SensorError> {
+        if !self.converting {
+            // The last read failed before the next conversion started
+            self.start_conversion()?;
+        }
+        // The sensor holds read slots low until the conversion is done
+        if !self.bus.read_bit() {
+            self.busy_reads += 1;
+            if self.busy_reads > MAX_BUSY_READS {
+                self.converting = false;
+                self.last = None;
+            }
+            return self.last.ok_or(SensorError::ConversionError);
+        }
+        self.finish_conversion()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isochron_hal::mock::{MockInputPin, MockOutputPin};
+
+    /// 25.0625°C at 12-bit resolution
+    const SCRATCHPAD_25C: [u8; SCRATCHPAD_LEN] =
+        [0x91, 0x01, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0x70];
+
+    /// Bus levels for a reset answered by a sensor
+    const PRESENCE: [bool; 2] = [false, true];
+
+    type Sensor<'a> = Ds18b20Sensor<MockBus<'a>, NoDelay>;
+
+    /// Open-drain bus: writes are recorded, reads replay a script
+    struct MockBus<'a> {
+        out: MockOutputPin,
+        levels: &'a MockInputPin,
+    }
+
+    impl OutputPin for MockBus<'_> {
+        fn set_high(&mut self) {
+            self.out.set_high();
+        }
+
+        fn set_low(&mut self) {
+            self.out.set_low();
+        }
+
+        fn toggle(&mut self) {
+            self.out.toggle();
+        }
+
+        fn is_set_high(&self) -> bool {
+            self.out.is_set_high()
+        }
+    }
+
+    impl InputPin for MockBus<'_> {
+        fn is_high(&self) -> bool {
+            self.levels.is_high()
+        }
+    }
+
+    struct NoDelay;
+
+    impl DelayNs for NoDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    fn sensor(levels: &MockInputPin) -> Sensor<'_> {
+        let bus = MockBus {
+            out: MockOutputPin::new(true),
+            levels,
+        };
+        Ds18b20Sensor::new(OneWire::new(bus, NoDelay))
+    }
+
+    /// Bus levels for reading `bytes`
+    fn bits(bytes: &[u8]) -> heapless::Vec<bool, 128> {
+        bytes
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| byte & (1 << i) != 0))
+            .collect()
+    }
+
+    /// Script a finished conversion, its scratchpad, and the next start
+    fn script_conversion(levels: &MockInputPin, scratchpad: &[u8]) {
+        levels.script(&[true]);
+        levels.script(&PRESENCE);
+        levels.script(&bits(scratchpad));
+        levels.script(&PRESENCE);
+    }
+
+    #[test]
+    fn test_raw_conversion() {
+        // Datasheet table, 12-bit resolution
+        assert_eq!(Sensor::raw_to_temp_x10(0x07D0), 1250);
+        assert_eq!(Sensor::raw_to_temp_x10(0x0550), 850);
+        assert_eq!(Sensor::raw_to_temp_x10(0x0191), 250);
+        assert_eq!(Sensor::raw_to_temp_x10(0x0008), 5);
+        assert_eq!(Sensor::raw_to_temp_x10(0x0000), 0);
+        assert_eq!(Sensor::raw_to_temp_x10(0xFFF8_u16 as i16), -5);
+        assert_eq!(Sensor::raw_to_temp_x10(0xFF5E_u16 as i16), -101);
+        assert_eq!(Sensor::raw_to_temp_x10(0xFC90_u16 as i16), -550);
+    }
+
+    #[test]
+    fn test_scratchpad_crc() {
+        assert_eq!(Sensor::parse_scratchpad(&SCRATCHPAD_25C), Ok(250));
+
+        // A flipped bit anywhere fails the CRC
+        let mut corrupt = SCRATCHPAD_25C;
+        corrupt[0] ^= 0x04;
+        assert_eq!(
+            Sensor::parse_scratchpad(&corrupt),
+            Err(SensorError::ChecksumError)
+        );
+        let mut corrupt = SCRATCHPAD_25C;
+        corrupt[8] ^= 0x80;
+        assert_eq!(
+            Sensor::parse_scratchpad(&corrupt),
+            Err(SensorError::ChecksumError)
+        );
+    }
+
+    #[test]
+    fn test_background_conversions() {
+        let levels = MockInputPin::new(true);
+        let mut sensor = sensor(&levels);
+        levels.script(&PRESENCE);
+        script_conversion(&levels, &SCRATCHPAD_25C);
+        assert_eq!(sensor.probe(), Ok(250));
+
+        // Still converting: the last reading stands in
+        levels.script(&[false]);
+        assert_eq!(sensor.read_celsius_x10(), Ok(250));
+
+        script_conversion(&levels, &SCRATCHPAD_25C);
+        assert_eq!(sensor.read_celsius_x10(), Ok(250));
+    }
+
+    #[test]
+    fn test_crc_glitches_tolerated() {
+        let levels = MockInputPin::new(true);
+        let mut sensor = sensor(&levels);
+        levels.script(&PRESENCE);
+        script_conversion(&levels, &SCRATCHPAD_25C);
+        assert_eq!(sensor.probe(), Ok(250));
+
+        let mut corrupt = SCRATCHPAD_25C;
+        corrupt[1] ^= 0x01;
+
+        // A corrupted read is retried: the second one counts
+        levels.script(&[true]);
+        levels.script(&PRESENCE);
+        levels.script(&bits(&corrupt));
+        levels.script(&PRESENCE);
+        levels.script(&bits(&SCRATCHPAD_25C));
+        levels.script(&PRESENCE);
+        assert_eq!(sensor.read_celsius_x10(), Ok(250));
+
+        // Unreadable conversions: the last temperature stands in, for a while
+        for _ in 0..=MAX_FAILED_CONVERSIONS {
+            levels.script(&[true]);
+            for _ in 0..SCRATCHPAD_ATTEMPTS {
+                levels.script(&PRESENCE);
+                levels.script(&bits(&corrupt));
+            }
+            levels.script(&PRESENCE);
+        }
+        for _ in 0..MAX_FAILED_CONVERSIONS {
+            assert_eq!(sensor.read_celsius_x10(), Ok(250));
+        }
+        assert_eq!(sensor.read_celsius_x10(), Err(SensorError::ChecksumError));
+
+        // A good conversion recovers
+        script_conversion(&levels, &SCRATCHPAD_25C);
+        assert_eq!(sensor.read_celsius_x10(), Ok(250));
+    }
+
+    #[test]
+    fn test_bus_faults() {
+        // Nothing pulls the bus low: no probe connected
+        let levels = MockInputPin::new(true);
+        assert_eq!(sensor(&levels).probe(), Err(SensorError::OpenCircuit));
+
+        // Bus stuck low
+        let levels = MockInputPin::new(false);
+        assert_eq!(sensor(&levels).probe(), Err(SensorError::ShortCircuit));
+
+        // A conversion that never finishes
+        let levels = MockInputPin::new(true);
+        levels.script(&PRESENCE);
+        levels.script(&[false]);
+        assert_eq!(sensor(&levels).probe(), Err(SensorError::ConversionError));
+    }
+}
@@ -0,0 +1,130 @@
+//! Median filtering of ADC reads
+//!
+//! Stepper switching couples occasional spikes into the thermistor input.
+//! [`AdcFilter`] takes a short burst of samples, drops any that sit too far
+//! from the burst's median, and averages the rest. The burst is over in
+//! microseconds, so a real temperature change (even a fast autotune ramp)
+//! passes through unsmoothed.
+
+use isochron_core::config::{AdcFilterConfig, MAX_ADC_SAMPLES};
+
+/// Filter a burst of samples
+///
+/// Sorts `samples` in place and returns the rounded mean of those within
+/// `max_delta` of the median, or None if there are no samples. The median
+/// itself always counts, so a `max_delta` of 0 gives the plain median.
+pub fn filter_samples(samples: &mut [u16], max_delta: u16) -> Option<u16> {
+    samples.sort_unstable();
+    let median = *samples.get(samples.len() / 2)?;
+    let (sum, count) = samples
+        .iter()
+        .filter(|&&sample| sample.abs_diff(median) <= max_delta)
+        .fold((0u32, 0u32), |(sum, count), &sample| {
+            (sum + sample as u32, count + 1)
+        });
+    Some(((sum + count / 2) / count) as u16)
+}
+
+/// Median-of-N ADC sampler
+#[derive(Debug, Clone, Copy)]
+pub struct AdcFilter {
+    samples: usize,
+    max_delta: u16,
+}
+
+impl AdcFilter {
+    /// Create a filter, clamping the window to 1..=`MAX_ADC_SAMPLES`
+    pub fn new(config: AdcFilterConfig) -> Self {
+        Self {
+            samples: (config.samples as usize).clamp(1, MAX_ADC_SAMPLES),
+            max_delta: config.max_delta,
+        }
+    }
+
+    /// Take a burst of samples from `read` and filter them
+    ///
+    /// Fails with the first read error.
+    pub fn read<E>(&self, mut read: impl FnMut() -> Result<u16, E>) -> Result<u16, E> {
+        let mut buf = [0u16; MAX_ADC_SAMPLES];
+        let burst = &mut buf[..self.samples];
+        for sample in burst.iter_mut() {
+            *sample = read()?;
+        }
+        // The window is never empty
+        Ok(filter_samples(burst, self.max_delta).unwrap_or(0))
+    }
+}
+
+impl Default for AdcFilter {
+    fn default() -> Self {
+        Self::new(AdcFilterConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spikes_rejected() {
+        // One spike each way around a steady 2000
+        let mut samples = [2001, 3900, 1998, 2000, 12];
+        assert_eq!(filter_samples(&mut samples, 40), Some(2000));
+
+        // Noise within the delta is averaged, not discarded
+        let mut samples = [2010, 1990, 2004, 1996, 2000];
+        assert_eq!(filter_samples(&mut samples, 40), Some(2000));
+
+        // A zero delta is the plain median
+        let mut samples = [2010, 1990, 2004, 1996, 2000];
+        assert_eq!(filter_samples(&mut samples, 0), Some(2000));
+
+        assert_eq!(filter_samples(&mut [], 40), None);
+    }
+
+    #[test]
+    fn test_ramp_not_smoothed() {
+        // A steady climb within the delta keeps its midpoint
+        let mut samples = [1980, 1990, 2000, 2010, 2020];
+        assert_eq!(filter_samples(&mut samples, 40), Some(2000));
+
+        // Wider than the delta, it falls back to the median
+        let mut samples = [1900, 1950, 2000, 2050, 2100];
+        assert_eq!(filter_samples(&mut samples, 40), Some(2000));
+    }
+
+    #[test]
+    fn test_filter_reads() {
+        let mut noisy = [2002, 4095, 1999, 2000, 2001].into_iter();
+        let filter = AdcFilter::default();
+        assert_eq!(filter.read(|| noisy.next().ok_or(())), Ok(2001));
+
+        // A single sample window reads once
+        let filter = AdcFilter::new(AdcFilterConfig {
+            samples: 1,
+            max_delta: 40,
+        });
+        let mut reads = 0;
+        let value = filter.read(|| {
+            reads += 1;
+            Ok::<_, ()>(4000)
+        });
+        assert_eq!((value, reads), (Ok(4000), 1));
+
+        // Oversized windows are clamped
+        let filter = AdcFilter::new(AdcFilterConfig {
+            samples: 200,
+            max_delta: 40,
+        });
+        let mut reads = 0;
+        let _ = filter.read(|| {
+            reads += 1;
+            Ok::<_, ()>(2000)
+        });
+        assert_eq!(reads, MAX_ADC_SAMPLES);
+
+        // Read errors are passed on
+        let mut failing = [Ok(2000), Err("adc")].into_iter();
+        assert_eq!(filter.read(|| failing.next().unwrap()), Err("adc"));
+    }
+}
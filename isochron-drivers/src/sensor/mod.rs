@@ -1,5 +1,14 @@
 //! Temperature sensor implementations
 
+pub mod ds18b20;
+pub mod median;
 pub mod ntc100k;
+pub mod onewire;
+pub mod thermistor;
+pub mod tmp117;
 
+pub use ds18b20::Ds18b20Sensor;
+pub use median::AdcFilter;
 pub use ntc100k::{AdcReader, Ntc100kSensor};
+pub use onewire::OneWire;
+pub use tmp117::{Tmp117Sensor, TMP117_DEFAULT_ADDRESS};
@@ -3,8 +3,11 @@
 //! Common thermistor used in 3D printing for temperature sensing.
 //! Uses a lookup table for integer-only temperature calculation.
 
+use isochron_core::config::ThermistorModel;
 use isochron_core::traits::{SensorError, TemperatureSensor};
 
+use super::thermistor;
+
 /// NTC 100K thermistor temperature lookup table
 ///
 /// Table format: (resistance_ohms, temperature_x10)
@@ -43,7 +46,8 @@ pub trait AdcReader {
 
 /// NTC 100K thermistor with B=3950
 ///
-/// Uses lookup table with linear interpolation for temperature calculation.
+/// Uses lookup table with linear interpolation for temperature calculation,
+/// unless a beta or Steinhart-Hart model is configured with `with_model`.
 pub struct Ntc100kSensor<ADC> {
     adc: ADC,
     /// ADC reference voltage in mV (stored for potential future use)
@@ -53,6 +57,8 @@ pub struct Ntc100kSensor<ADC> {
     pullup_ohms: u32,
     /// ADC resolution (typically 4096 for 12-bit)
     adc_max: u16,
+    /// Resistance-to-temperature model
+    model: ThermistorModel,
 }
 
 impl<ADC> Ntc100kSensor<ADC> {
@@ -68,9 +74,16 @@ impl<ADC> Ntc100kSensor<ADC> {
             vref_mv,
             pullup_ohms,
             adc_max: 4096, // 12-bit ADC
+            model: ThermistorModel::Table,
         }
     }
 
+    /// Convert with a beta or Steinhart-Hart model instead of the table
+    pub fn with_model(mut self, model: ThermistorModel) -> Self {
+        self.model = model;
+        self
+    }
+
     /// Convert ADC reading to resistance
     ///
     /// Circuit: VCC -- pullup -- ADC_PIN -- NTC -- GND
@@ -99,37 +112,7 @@ impl<ADC> Ntc100kSensor<ADC> {
     /// Returns temperature in 0.1°C units (e.g., 250 = 25.0°C).
     /// Uses linear interpolation between table entries.
     pub fn resistance_to_temp_x10(resistance: u32) -> Result<i16, SensorError> {
-        // Check for out-of-range values
-        if resistance > TEMP_TABLE[0].0 {
-            // Resistance too high = too cold or open
-            return Err(SensorError::OutOfRange);
-        }
-
-        if resistance < TEMP_TABLE[TEMP_TABLE.len() - 1].0 {
-            // Resistance too low = too hot or shorted
-            return Err(SensorError::OutOfRange);
-        }
-
-        // Find the two table entries to interpolate between
-        // Table is sorted by decreasing resistance (increasing temperature)
-        for i in 0..TEMP_TABLE.len() - 1 {
-            let (r_high, t_low) = TEMP_TABLE[i];
-            let (r_low, t_high) = TEMP_TABLE[i + 1];
-
-            if resistance <= r_high && resistance >= r_low {
-                // Linear interpolation
-                // temp = t_low + (t_high - t_low) * (r_high - r) / (r_high - r_low)
-                let r_range = r_high - r_low;
-                let t_range = t_high - t_low;
-                let r_offset = r_high - resistance;
-
-                let temp = t_low + (t_range as i32 * r_offset as i32 / r_range as i32) as i16;
-                return Ok(temp);
-            }
-        }
-
-        // Shouldn't reach here, but just in case
-        Err(SensorError::OutOfRange)
+        thermistor::table_to_temp_x10(TEMP_TABLE, resistance)
     }
 }
 
@@ -142,7 +125,7 @@ impl<ADC: AdcReader> TemperatureSensor for Ntc100kSensor<ADC> {
         let resistance = self.adc_to_resistance(adc_value)?;
 
         // Convert to temperature
-        Self::resistance_to_temp_x10(resistance)
+        thermistor::resistance_to_temp_x10(&self.model, resistance)
     }
 }
 
@@ -176,6 +159,20 @@ mod tests {
         assert!((temp - 550).abs() < 50);
     }
 
+    #[test]
+    fn test_configured_model() {
+        // 4.7K pullup, 100K thermistor at 25°C
+        let adc = (4096u32 * 100_000 / 104_700) as u16;
+        let mut sensor =
+            Ntc100kSensor::new(DummyAdc(adc), 3300, 4700).with_model(ThermistorModel::Beta {
+                beta: 3950,
+                r0_ohms: 100_000,
+                t0_c: 25,
+            });
+        let temp = sensor.read_celsius_x10().unwrap();
+        assert!((temp - 250).abs() <= 2);
+    }
+
     #[test]
     fn test_adc_to_resistance() {
         // With 4.7K pullup and 12-bit ADC:
@@ -0,0 +1,147 @@
+//! 1-Wire bus master, bit-banged on a single open-drain pin
+//!
+//! Standard-speed slot timing from Maxim application note 126. The pin
+//! must be open-drain: `set_low` pulls the bus low, `set_high` releases it
+//! to the pull-up, and `is_high` reads the bus itself. Slots are timed with
+//! a busy-wait delay, so an interrupt landing in a read slot can corrupt a
+//! bit; check data against its [`crc8`].
+
+use embedded_hal::delay::DelayNs;
+use isochron_hal::{InputPin, OutputPin};
+
+/// Slot timing (µs)
+mod timing {
+    /// Reset pulse
+    pub const RESET_LOW: u32 = 480;
+    /// Release to presence sample
+    pub const PRESENCE_SAMPLE: u32 = 70;
+    /// Presence sample to end of reset
+    pub const RESET_RECOVERY: u32 = 410;
+    /// Low time writing a 1, or starting a read
+    pub const SHORT_LOW: u32 = 6;
+    /// Low time writing a 0
+    pub const WRITE_0_LOW: u32 = 60;
+    /// Release to sample in a read slot
+    pub const READ_SAMPLE: u32 = 9;
+    /// Rest of a read slot after sampling
+    pub const READ_RECOVERY: u32 = 55;
+    /// Slot length, low time included
+    pub const SLOT: u32 = 70;
+}
+
+/// 1-Wire bus errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OneWireError {
+    /// No device answered the reset pulse
+    NoPresence,
+    /// Bus still low after the reset (shorted to ground or no pull-up)
+    BusShorted,
+}
+
+/// 1-Wire bus master
+pub struct OneWire<P, D> {
+    pin: P,
+    delay: D,
+}
+
+impl<P: OutputPin + InputPin, D: DelayNs> OneWire<P, D> {
+    /// Create a bus master, releasing the bus
+    pub fn new(mut pin: P, delay: D) -> Self {
+        pin.set_high();
+        Self { pin, delay }
+    }
+
+    /// Reset the bus and check that a device answers
+    pub fn reset(&mut self) -> Result<(), OneWireError> {
+        self.pin.set_low();
+        self.delay.delay_us(timing::RESET_LOW);
+        self.pin.set_high();
+        self.delay.delay_us(timing::PRESENCE_SAMPLE);
+        let present = self.pin.is_low();
+        self.delay.delay_us(timing::RESET_RECOVERY);
+
+        if self.pin.is_low() {
+            Err(OneWireError::BusShorted)
+        } else if !present {
+            Err(OneWireError::NoPresence)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write one bit
+    pub fn write_bit(&mut self, bit: bool) {
+        let low = if bit {
+            timing::SHORT_LOW
+        } else {
+            timing::WRITE_0_LOW
+        };
+        self.pin.set_low();
+        self.delay.delay_us(low);
+        self.pin.set_high();
+        self.delay.delay_us(timing::SLOT - low);
+    }
+
+    /// Read one bit
+    pub fn read_bit(&mut self) -> bool {
+        self.pin.set_low();
+        self.delay.delay_us(timing::SHORT_LOW);
+        self.pin.set_high();
+        self.delay.delay_us(timing::READ_SAMPLE);
+        let bit = self.pin.is_high();
+        self.delay.delay_us(timing::READ_RECOVERY);
+        bit
+    }
+
+    /// Write a byte, least significant bit first
+    pub fn write_byte(&mut self, byte: u8) {
+        for i in 0..8 {
+            self.write_bit(byte & (1 << i) != 0);
+        }
+    }
+
+    /// Read a byte, least significant bit first
+    pub fn read_byte(&mut self) -> u8 {
+        (0..8).fold(0, |byte, i| byte | (self.read_bit() as u8) << i)
+    }
+
+    /// Busy-wait with the bus's delay
+    pub fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us);
+    }
+
+    /// Release the pin and delay
+    pub fn release(self) -> (P, D) {
+        (self.pin, self.delay)
+    }
+}
+
+/// Dallas/Maxim CRC-8 (x^8 + x^5 + x^4 + 1, reflected)
+///
+/// A block followed by its own CRC byte sums to 0.
+pub fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8C
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc8() {
+        // ROM code example from Maxim application note 27
+        let rom = [0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00];
+        assert_eq!(crc8(&rom), 0xA2);
+        assert_eq!(crc8(&[0x02, 0x1C, 0xB8, 0x01, 0x00, 0x00, 0x00, 0xA2]), 0);
+        assert_eq!(crc8(&[]), 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! Thermistor resistance-to-temperature models
+//!
+//! The built-in lookup table only describes one NTC 100K curve. Other
+//! thermistors are converted with a table from the config, or with their
+//! own equation from a datasheet beta value or fitted Steinhart-Hart
+//! coefficients:
+//!
+//! - Beta: 1/T = 1/T0 + ln(R/R0)/beta
+//! - Steinhart-Hart: 1/T = a + b·ln(R) + c·ln(R)³
+//!
+//! with T in kelvin. Both are evaluated in `f32`, which resolves well
+//! under 0.1°C over the range a thermistor reads.
+
+use isochron_core::config::ThermistorModel;
+use isochron_core::traits::SensorError;
+
+use super::ntc100k::Ntc100kSensor;
+
+/// Lowest temperature an equation model reports (0.1°C units)
+const MIN_TEMP_X10: i16 = -400;
+
+/// Highest temperature an equation model reports (0.1°C units)
+const MAX_TEMP_X10: i16 = 3000;
+
+/// 0°C in kelvin
+const ZERO_C_K: f32 = 273.15;
+
+/// Convert a thermistor resistance to temperature with the configured model
+///
+/// Returns temperature in 0.1°C units (e.g., 250 = 25.0°C).
+pub fn resistance_to_temp_x10(
+    model: &ThermistorModel,
+    resistance: u32,
+) -> Result<i16, SensorError> {
+    match model {
+        ThermistorModel::Table => Ntc100kSensor::<()>::resistance_to_temp_x10(resistance),
+        ThermistorModel::Custom(table) => table_to_temp_x10(table, resistance),
+        &ThermistorModel::Beta {
+            beta,
+            r0_ohms,
+            t0_c,
+        } => beta_to_temp_x10(resistance, beta, r0_ohms, t0_c),
+        &ThermistorModel::SteinhartHart { a, b, c } => {
+            steinhart_hart_to_temp_x10(resistance, a, b, c)
+        }
+    }
+}
+
+/// Temperature by linear interpolation in a table
+///
+/// `table` holds (resistance_ohms, temperature_x10) points sorted by
+/// decreasing resistance. Resistances outside the table are out of range.
+pub fn table_to_temp_x10(table: &[(u32, i16)], resistance: u32) -> Result<i16, SensorError> {
+    table
+        .windows(2)
+        .find(|pair| resistance <= pair[0].0 && resistance >= pair[1].0)
+        .map(|pair| {
+            let ((r_high, t_low), (r_low, t_high)) = (pair[0], pair[1]);
+            // Widened: a span of more than 3276.7°C overflows an i16
+            let r_range = (r_high - r_low) as i64;
+            let t_range = t_high as i32 - t_low as i32;
+            let r_offset = (r_high - resistance) as i64;
+            (t_low as i32 + (t_range as i64 * r_offset / r_range.max(1)) as i32) as i16
+        })
+        .ok_or(SensorError::OutOfRange)
+}
+
+/// Temperature from the beta equation
+pub fn beta_to_temp_x10(
+    resistance: u32,
+    beta: u16,
+    r0_ohms: u32,
+    t0_c: i16,
+) -> Result<i16, SensorError> {
+    if resistance == 0 || r0_ohms == 0 || beta == 0 {
+        return Err(SensorError::OutOfRange);
+    }
+    let t0_k = t0_c as f32 + ZERO_C_K;
+    let inv_t = 1.0 / t0_k + ln(resistance as f32 / r0_ohms as f32) / beta as f32;
+    kelvin_to_c_x10(inv_t)
+}
+
+/// Temperature from the Steinhart-Hart equation
+pub fn steinhart_hart_to_temp_x10(
+    resistance: u32,
+    a: f32,
+    b: f32,
+    c: f32,
+) -> Result<i16, SensorError> {
+    if resistance == 0 {
+        return Err(SensorError::OutOfRange);
+    }
+    let ln_r = ln(resistance as f32);
+    let inv_t = a + b * ln_r + c * ln_r * ln_r * ln_r;
+    kelvin_to_c_x10(inv_t)
+}
+
+/// Convert 1/T (1/K) to 0.1°C, rejecting temperatures no thermistor reads
+fn kelvin_to_c_x10(inv_t: f32) -> Result<i16, SensorError> {
+    if inv_t.is_nan() || inv_t <= 0.0 {
+        return Err(SensorError::OutOfRange);
+    }
+    let temp_x10 = (1.0 / inv_t - ZERO_C_K) * 10.0;
+    if !(MIN_TEMP_X10 as f32..=MAX_TEMP_X10 as f32).contains(&temp_x10) {
+        return Err(SensorError::OutOfRange);
+    }
+    // Round to nearest
+    let rounded = if temp_x10 < 0.0 {
+        temp_x10 - 0.5
+    } else {
+        temp_x10 + 0.5
+    };
+    Ok(rounded as i16)
+}
+
+/// Natural logarithm for positive, finite `x`
+///
+/// Splits `x` into m·2^e with m in [√½, √2), then sums the series
+/// ln(m) = 2·atanh((m-1)/(m+1)), which converges to f32 precision in
+/// five terms over that interval.
+fn ln(x: f32) -> f32 {
+    const LN_2: f32 = core::f32::consts::LN_2;
+    const SQRT_2: f32 = core::f32::consts::SQRT_2;
+
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 23) & 0xFF) as i32 - 127;
+    let mut mantissa = f32::from_bits((bits & 0x007F_FFFF) | 0x3F80_0000);
+    if mantissa > SQRT_2 {
+        mantissa *= 0.5;
+        exponent += 1;
+    }
+
+    let s = (mantissa - 1.0) / (mantissa + 1.0);
+    let s2 = s * s;
+    let series =
+        s * (2.0 + s2 * (2.0 / 3.0 + s2 * (2.0 / 5.0 + s2 * (2.0 / 7.0 + s2 * (2.0 / 9.0)))));
+    exponent as f32 * LN_2 + series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Common NTC 100K datasheet values, the curve the table was built from
+    const NTC100K_BETA: ThermistorModel = ThermistorModel::Beta {
+        beta: 3950,
+        r0_ohms: 100_000,
+        t0_c: 25,
+    };
+
+    #[test]
+    fn test_ln() {
+        for (x, expected) in [
+            (1.0f32, 0.0f32),
+            (core::f32::consts::E, 1.0),
+            (0.5, -core::f32::consts::LN_2),
+            (1_000.0, 6.907_755),
+            (100_000.0, 11.512_925),
+            (1.0e-6, -13.815_511),
+        ] {
+            assert!((ln(x) - expected).abs() < 1.0e-5, "ln({})", x);
+        }
+    }
+
+    #[test]
+    fn test_beta_against_table() {
+        // Exact at the reference point
+        assert_eq!(resistance_to_temp_x10(&NTC100K_BETA, 100_000), Ok(250));
+
+        // The table points drift from the beta curve away from 25°C;
+        // over the drying range they stay within 5°C of it
+        for (resistance, table_x10) in [
+            (100_000, 250),
+            (80_000, 300),
+            (55_000, 400),
+            (40_000, 450),
+            (30_000, 500),
+            (25_000, 550),
+        ] {
+            let table = resistance_to_temp_x10(&ThermistorModel::Table, resistance).unwrap();
+            assert_eq!(table, table_x10);
+            let beta = resistance_to_temp_x10(&NTC100K_BETA, resistance).unwrap();
+            assert!(
+                (beta - table).abs() <= 50,
+                "{} ohms: beta {} table {}",
+                resistance,
+                beta,
+                table
+            );
+        }
+
+        // Higher up they disagree by far more, which is why a configured
+        // curve matters for anything hotter
+        let table = resistance_to_temp_x10(&ThermistorModel::Table, 4_000).unwrap();
+        let beta = resistance_to_temp_x10(&NTC100K_BETA, 4_000).unwrap();
+        assert!(beta - table > 150);
+    }
+
+    #[test]
+    fn test_beta_curve_points() {
+        // Reference values from the beta equation evaluated in f64
+        for (resistance, expected_x10) in [(30_000, 548), (12_000, 818), (1_000_000, -191)] {
+            let temp = resistance_to_temp_x10(&NTC100K_BETA, resistance).unwrap();
+            assert!(
+                (temp - expected_x10).abs() <= 1,
+                "{} ohms: {}",
+                resistance,
+                temp
+            );
+        }
+
+        // A 10K part with its own reference
+        let ntc10k = ThermistorModel::Beta {
+            beta: 3435,
+            r0_ohms: 10_000,
+            t0_c: 25,
+        };
+        assert_eq!(resistance_to_temp_x10(&ntc10k, 10_000), Ok(250));
+    }
+
+    #[test]
+    fn test_custom_table() {
+        // A 10K thermistor: the built-in 100K table reads it far too hot
+        let table = ThermistorModel::Custom(
+            heapless::Vec::from_slice(&[(32_650, 0), (10_000, 250), (3_600, 500), (1_250, 850)])
+                .unwrap(),
+        );
+        assert_eq!(resistance_to_temp_x10(&table, 10_000), Ok(250));
+        assert_eq!(resistance_to_temp_x10(&table, 6_800), Ok(375));
+        assert_eq!(resistance_to_temp_x10(&table, 1_250), Ok(850));
+        assert_eq!(
+            resistance_to_temp_x10(&table, 40_000),
+            Err(SensorError::OutOfRange)
+        );
+        assert_eq!(
+            resistance_to_temp_x10(&table, 1_000),
+            Err(SensorError::OutOfRange)
+        );
+        assert!(resistance_to_temp_x10(&ThermistorModel::Table, 10_000).unwrap() > 700);
+
+        // Spans wider than an i16 interpolate without overflowing
+        let wide = [(100_000, -20_000), (1_000, 20_000)];
+        assert_eq!(table_to_temp_x10(&wide, 50_500), Ok(0));
+        assert_eq!(table_to_temp_x10(&wide, 1_000), Ok(20_000));
+    }
+
+    #[test]
+    fn test_steinhart_hart_reduces_to_beta() {
+        // a = 1/T0 - ln(R0)/beta, b = 1/beta, c = 0 is the beta equation
+        let b = 1.0 / 3950.0;
+        let a = 1.0 / (25.0 + ZERO_C_K) - ln(100_000.0) * b;
+        let sh = ThermistorModel::SteinhartHart { a, b, c: 0.0 };
+
+        for resistance in [1_000_000, 100_000, 40_000, 25_000, 4_000, 1_000] {
+            let beta = resistance_to_temp_x10(&NTC100K_BETA, resistance).unwrap();
+            let temp = resistance_to_temp_x10(&sh, resistance).unwrap();
+            assert!((temp - beta).abs() <= 1, "{} ohms", resistance);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!(
+            resistance_to_temp_x10(&NTC100K_BETA, 0),
+            Err(SensorError::OutOfRange)
+        );
+        // Shorted and open thermistors land far outside the reporting range
+        assert_eq!(
+            resistance_to_temp_x10(&NTC100K_BETA, 5),
+            Err(SensorError::OutOfRange)
+        );
+        assert_eq!(
+            resistance_to_temp_x10(&NTC100K_BETA, 100_000_000),
+            Err(SensorError::OutOfRange)
+        );
+
+        // Unconfigured coefficients never yield a temperature
+        let empty = ThermistorModel::SteinhartHart {
+            a: 0.0,
+            b: 0.0,
+            c: 0.0,
+        };
+        assert_eq!(
+            resistance_to_temp_x10(&empty, 100_000),
+            Err(SensorError::OutOfRange)
+        );
+    }
+}
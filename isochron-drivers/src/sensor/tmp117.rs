@@ -0,0 +1,188 @@
+//! TMP117 digital temperature sensor
+//!
+//! High-accuracy (±0.1°C) sensor on I2C. It converts continuously after
+//! power-up, so reading the temperature is a single register read with
+//! no configuration needed.
+
+use isochron_core::traits::{SensorError, TemperatureSensor};
+use isochron_hal::I2cBus;
+
+/// Default 7-bit address (ADD0 tied to GND)
+pub const TMP117_DEFAULT_ADDRESS: u8 = 0x48;
+
+/// Register addresses
+mod reg {
+    /// Temperature result
+    pub const TEMP_RESULT: u8 = 0x00;
+    /// Device ID
+    pub const DEVICE_ID: u8 = 0x0F;
+}
+
+/// Device ID reported by a TMP117 (revision bits masked off)
+const DEVICE_ID: u16 = 0x0117;
+
+/// Result register value before the first conversion completes
+const TEMP_NOT_READY: i16 = i16::MIN;
+
+/// TMP117 sensor on an I2C bus
+///
+/// The result register has a resolution of 1/128 °C. Bus errors are
+/// reported as [`SensorError::ConversionError`], the same as a failed
+/// ADC read, so the heater faults the same way as with a thermistor.
+pub struct Tmp117Sensor<B> {
+    bus: B,
+    address: u8,
+}
+
+impl<B: I2cBus> Tmp117Sensor<B> {
+    /// Create a sensor at `address` on `bus`
+    pub fn new(bus: B, address: u8) -> Self {
+        Self { bus, address }
+    }
+
+    /// Check that a TMP117 answers at the configured address
+    pub fn probe(&mut self) -> Result<(), SensorError> {
+        let id = self.read_register(reg::DEVICE_ID)? as u16;
+        if id & 0x0FFF == DEVICE_ID {
+            Ok(())
+        } else {
+            Err(SensorError::OpenCircuit)
+        }
+    }
+
+    /// Convert a raw result register value to 0.1°C units
+    pub fn raw_to_temp_x10(raw: i16) -> Result<i16, SensorError> {
+        if raw == TEMP_NOT_READY {
+            return Err(SensorError::ConversionError);
+        }
+        // 1 LSB = 1/128 °C
+        Ok((raw as i32 * 10 / 128) as i16)
+    }
+
+    /// Release the bus
+    pub fn release(self) -> B {
+        self.bus
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<i16, SensorError> {
+        let mut buf = [0u8; 2];
+        self.bus
+            .write_read(self.address, &[register], &mut buf)
+            .map_err(|_| SensorError::ConversionError)?;
+        Ok(i16::from_be_bytes(buf))
+    }
+}
+
+impl<B: I2cBus> TemperatureSensor for Tmp117Sensor<B> {
+    fn read_celsius_x10(&mut self) -> Result<i16, SensorError> {
+        let raw = self.read_register(reg::TEMP_RESULT)?;
+        Self::raw_to_temp_x10(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heater::{BangBangConfig, BangBangController};
+    use crate::sensor::ntc100k::{DummyAdc, Ntc100kSensor};
+    use isochron_core::traits::{HeaterController, HeaterOutput};
+    use isochron_hal::mock::{I2cTransaction, MockError, MockI2c};
+
+    const ADDR: u8 = TMP117_DEFAULT_ADDRESS;
+
+    fn temp_read(raw: i16) -> I2cTransaction {
+        I2cTransaction::write_read(ADDR, &[reg::TEMP_RESULT], &raw.to_be_bytes())
+    }
+
+    struct MockHeater {
+        on: bool,
+    }
+
+    impl HeaterOutput for MockHeater {
+        fn set_on(&mut self, on: bool) {
+            self.on = on;
+        }
+
+        fn is_on(&self) -> bool {
+            self.on
+        }
+    }
+
+    /// Run one bang-bang update at a 45°C target and report the heater state
+    fn heater_on_after_update(sensor: &mut dyn TemperatureSensor) -> Result<bool, SensorError> {
+        let mut controller =
+            BangBangController::new(sensor, MockHeater { on: false }, BangBangConfig::default());
+        controller.set_target(45);
+        controller.enable(true);
+        controller.update()?;
+        Ok(controller.heater().is_on())
+    }
+
+    #[test]
+    fn test_read_temperature() {
+        // 25°C = 3200 LSB, -10°C = -1280 LSB
+        let mut sensor =
+            Tmp117Sensor::new(MockI2c::new(&[temp_read(3200), temp_read(-1280)]), ADDR);
+
+        assert_eq!(sensor.read_celsius_x10(), Ok(250));
+        assert_eq!(sensor.read_celsius_x10(), Ok(-100));
+        sensor.release().done();
+    }
+
+    #[test]
+    fn test_not_ready_and_bus_errors() {
+        let mut sensor = Tmp117Sensor::new(
+            MockI2c::new(&[
+                temp_read(TEMP_NOT_READY),
+                temp_read(0).with_error(MockError::Injected),
+            ]),
+            ADDR,
+        );
+
+        assert_eq!(sensor.read_celsius_x10(), Err(SensorError::ConversionError));
+        assert_eq!(sensor.read_celsius_x10(), Err(SensorError::ConversionError));
+        sensor.release().done();
+    }
+
+    #[test]
+    fn test_probe() {
+        let mut sensor = Tmp117Sensor::new(
+            MockI2c::new(&[
+                I2cTransaction::write_read(0x49, &[reg::DEVICE_ID], &[0x01, 0x17]),
+                I2cTransaction::write_read(0x49, &[reg::DEVICE_ID], &[0x00, 0x75]),
+            ]),
+            0x49,
+        );
+
+        assert_eq!(sensor.probe(), Ok(()));
+        assert_eq!(sensor.probe(), Err(SensorError::OpenCircuit));
+        sensor.release().done();
+    }
+
+    #[test]
+    fn test_heater_reacts_as_with_adc() {
+        // 40°C: below the hysteresis band, heater on
+        let mut i2c = Tmp117Sensor::new(MockI2c::new(&[temp_read(5120)]), ADDR);
+        let mut adc = Ntc100kSensor::new(DummyAdc(3773), 3300, 4700);
+        assert_eq!(heater_on_after_update(&mut i2c), Ok(true));
+        assert_eq!(heater_on_after_update(&mut adc), Ok(true));
+        i2c.release().done();
+
+        // 50°C: above the hysteresis band, heater stays off
+        let mut i2c = Tmp117Sensor::new(MockI2c::new(&[temp_read(6400)]), ADDR);
+        let mut adc = Ntc100kSensor::new(DummyAdc(3541), 3300, 4700);
+        assert_eq!(heater_on_after_update(&mut i2c), Ok(false));
+        assert_eq!(heater_on_after_update(&mut adc), Ok(false));
+        i2c.release().done();
+
+        // A failed read faults the update either way
+        let mut i2c = Tmp117Sensor::new(
+            MockI2c::new(&[temp_read(0).with_error(MockError::Injected)]),
+            ADDR,
+        );
+        let mut adc = Ntc100kSensor::new(DummyAdc(4095), 3300, 4700);
+        assert!(heater_on_after_update(&mut i2c).is_err());
+        assert!(heater_on_after_update(&mut adc).is_err());
+        i2c.release().done();
+    }
+}
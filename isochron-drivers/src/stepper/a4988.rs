@@ -0,0 +1,325 @@
+//! A4988 / DRV8825 stepper driver (step/dir only)
+//!
+//! Plain step/dir drivers have no serial interface. Microstepping is
+//! selected by the MS1-MS3 pins (MODE0-MODE2 on the DRV8825), the motor
+//! current by a trimpot on the board, and there is no stall or fault
+//! feedback: [`StepperDriver::is_stalled`] is always false.
+//!
+//! # Microstep Pins
+//!
+//! | Microsteps | A4988 MS1 MS2 MS3 | DRV8825 MODE0 MODE1 MODE2 |
+//! |-----------:|:-----------------:|:-------------------------:|
+//! |          1 | L L L             | L L L                     |
+//! |          2 | H L L             | H L L                     |
+//! |          4 | L H L             | L H L                     |
+//! |          8 | H H L             | H H L                     |
+//! |         16 | H H H             | L L H                     |
+//! |         32 | -                 | H L H                     |
+//!
+//! Speed changes follow a shared [`RampProfile`], advanced by
+//! [`A4988Driver::update`].
+
+pub use isochron_core::config::StepDirChip;
+use isochron_core::motion::RampProfile;
+use isochron_core::traits::{Direction, StepperDriver};
+use isochron_hal::OutputPin;
+
+/// MS pin levels (MS1, MS2, MS3) selecting `microsteps` on `chip`
+///
+/// Returns None if the chip can't run at that resolution.
+pub fn microstep_pins(chip: StepDirChip, microsteps: u16) -> Option<[bool; 3]> {
+    match (chip, microsteps) {
+        (_, 1) => Some([false, false, false]),
+        (_, 2) => Some([true, false, false]),
+        (_, 4) => Some([false, true, false]),
+        (_, 8) => Some([true, true, false]),
+        (StepDirChip::A4988, 16) => Some([true, true, true]),
+        (StepDirChip::Drv8825, 16) => Some([false, false, true]),
+        (StepDirChip::Drv8825, 32) => Some([true, false, true]),
+        _ => None,
+    }
+}
+
+/// A4988 configuration errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum A4988Error {
+    /// The chip has no pin setting for this microstep resolution
+    UnsupportedMicrosteps,
+    /// An MS pin that must be high is not connected (MS1 = 0)
+    MissingPin(u8),
+}
+
+/// A4988 driver configuration
+#[derive(Debug, Clone)]
+pub struct A4988Config {
+    /// Driver chip
+    pub chip: StepDirChip,
+    /// Microstepping (1-16, or 1-32 on the DRV8825)
+    pub microsteps: u16,
+}
+
+impl Default for A4988Config {
+    fn default() -> Self {
+        Self {
+            chip: StepDirChip::A4988,
+            microsteps: 16,
+        }
+    }
+}
+
+/// MS pin output, active high unless inverted
+pub struct MsPin<P> {
+    /// Output pin
+    pub pin: P,
+    /// Pin is driven through an inverting stage
+    pub inverted: bool,
+}
+
+/// A4988 driver state
+///
+/// Owns the MS pins so they keep their levels for as long as the driver
+/// lives.
+pub struct A4988Driver<P> {
+    config: A4988Config,
+    ms_pins: [Option<MsPin<P>>; 3],
+    current_rpm: u16,
+    target_rpm: u16,
+    /// Speed ramp for RPM changes
+    ramp: RampProfile,
+    /// Speed the current ramp started from
+    ramp_start_rpm: u16,
+    /// Time into the current ramp (ms)
+    ramp_elapsed_ms: u32,
+    direction: Direction,
+    enabled: bool,
+}
+
+impl<P: OutputPin> A4988Driver<P> {
+    /// Create a driver and set the MS pins for the configured microsteps
+    ///
+    /// A missing pin is taken to be strapped low, so it is only an error
+    /// if the resolution needs it high.
+    pub fn new(
+        config: A4988Config,
+        mut ms_pins: [Option<MsPin<P>>; 3],
+    ) -> Result<Self, A4988Error> {
+        let levels = microstep_pins(config.chip, config.microsteps)
+            .ok_or(A4988Error::UnsupportedMicrosteps)?;
+        for (index, (ms, high)) in ms_pins.iter_mut().zip(levels).enumerate() {
+            match ms {
+                Some(ms) => ms.pin.set_state(high != ms.inverted),
+                None if high => return Err(A4988Error::MissingPin(index as u8)),
+                None => {}
+            }
+        }
+
+        Ok(Self {
+            config,
+            ms_pins,
+            current_rpm: 0,
+            target_rpm: 0,
+            ramp: RampProfile::default(),
+            ramp_start_rpm: 0,
+            ramp_elapsed_ms: 0,
+            direction: Direction::Clockwise,
+            enabled: false,
+        })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &A4988Config {
+        &self.config
+    }
+
+    /// Set the speed ramp for RPM changes
+    pub fn set_ramp(&mut self, ramp: RampProfile) {
+        self.ramp = ramp;
+    }
+
+    /// Current RPM along the ramp
+    pub fn current_rpm(&self) -> u16 {
+        self.current_rpm
+    }
+
+    /// Advance the speed ramp by `delta_ms`
+    ///
+    /// Returns the RPM to step at.
+    pub fn update(&mut self, delta_ms: u32) -> u16 {
+        self.ramp_elapsed_ms = self.ramp_elapsed_ms.saturating_add(delta_ms);
+        self.current_rpm =
+            self.ramp
+                .rpm_at(self.ramp_start_rpm, self.target_rpm, self.ramp_elapsed_ms);
+        self.current_rpm
+    }
+
+    /// Release the MS pins
+    pub fn release(self) -> [Option<MsPin<P>>; 3] {
+        self.ms_pins
+    }
+}
+
+impl<P: OutputPin> StepperDriver for A4988Driver<P> {
+    fn set_rpm(&mut self, rpm: u16) {
+        if rpm != self.target_rpm {
+            self.ramp_start_rpm = self.current_rpm;
+            self.ramp_elapsed_ms = 0;
+        }
+        self.target_rpm = rpm;
+    }
+
+    fn get_rpm(&self) -> u16 {
+        self.target_rpm
+    }
+
+    fn set_direction(&mut self, dir: Direction) {
+        self.direction = dir;
+    }
+
+    fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn enable(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn is_stalled(&self) -> bool {
+        // No load feedback on a step/dir driver
+        false
+    }
+
+    fn clear_stall(&mut self) {}
+
+    fn is_at_speed(&self) -> bool {
+        self.current_rpm == self.target_rpm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isochron_hal::mock::MockOutputPin;
+
+    fn pins() -> [Option<MsPin<MockOutputPin>>; 3] {
+        [0, 1, 2].map(|_| {
+            Some(MsPin {
+                pin: MockOutputPin::new(false),
+                inverted: false,
+            })
+        })
+    }
+
+    /// Levels driven on the MS pins (None = pin not connected)
+    fn levels(driver: A4988Driver<MockOutputPin>) -> [Option<bool>; 3] {
+        driver.release().map(|ms| ms.map(|ms| ms.pin.is_set_high()))
+    }
+
+    fn new_driver(
+        chip: StepDirChip,
+        microsteps: u16,
+    ) -> Result<A4988Driver<MockOutputPin>, A4988Error> {
+        A4988Driver::new(A4988Config { chip, microsteps }, pins())
+    }
+
+    #[test]
+    fn test_microstep_table() {
+        use StepDirChip::{Drv8825, A4988};
+        let table = [
+            (1, [false, false, false], [false, false, false]),
+            (2, [true, false, false], [true, false, false]),
+            (4, [false, true, false], [false, true, false]),
+            (8, [true, true, false], [true, true, false]),
+            (16, [true, true, true], [false, false, true]),
+        ];
+        for (microsteps, a4988, drv8825) in table {
+            assert_eq!(microstep_pins(A4988, microsteps), Some(a4988));
+            assert_eq!(microstep_pins(Drv8825, microsteps), Some(drv8825));
+        }
+
+        // Only the DRV8825 reaches 1/32
+        assert_eq!(microstep_pins(Drv8825, 32), Some([true, false, true]));
+        assert_eq!(microstep_pins(A4988, 32), None);
+        for microsteps in [0, 3, 64, 256] {
+            assert_eq!(microstep_pins(A4988, microsteps), None);
+            assert_eq!(microstep_pins(Drv8825, microsteps), None);
+        }
+    }
+
+    #[test]
+    fn test_new_sets_ms_pins() {
+        let driver = new_driver(StepDirChip::A4988, 8).unwrap();
+        assert_eq!(levels(driver), [Some(true), Some(true), Some(false)]);
+
+        let driver = new_driver(StepDirChip::Drv8825, 16).unwrap();
+        assert_eq!(levels(driver), [Some(false), Some(false), Some(true)]);
+
+        assert_eq!(
+            new_driver(StepDirChip::A4988, 32).err(),
+            Some(A4988Error::UnsupportedMicrosteps)
+        );
+    }
+
+    #[test]
+    fn test_missing_and_inverted_pins() {
+        // Full steps need no pin high, so none have to be connected
+        let driver = A4988Driver::<MockOutputPin>::new(
+            A4988Config {
+                microsteps: 1,
+                ..Default::default()
+            },
+            [None, None, None],
+        );
+        assert!(driver.is_ok());
+
+        // MS3 strapped low on the board can't give 1/16 on an A4988
+        let [ms1, ms2, _] = pins();
+        let driver = A4988Driver::new(A4988Config::default(), [ms1, ms2, None]);
+        assert_eq!(driver.err(), Some(A4988Error::MissingPin(2)));
+
+        // ... but 1/8 works
+        let [ms1, ms2, _] = pins();
+        let config = A4988Config {
+            microsteps: 8,
+            ..Default::default()
+        };
+        let driver = A4988Driver::new(config, [ms1, ms2, None]).unwrap();
+        assert_eq!(levels(driver), [Some(true), Some(true), None]);
+
+        // Inverted pins are driven to the opposite level
+        let mut ms = pins();
+        for pin in ms.iter_mut().flatten() {
+            pin.inverted = true;
+        }
+        let driver = A4988Driver::new(A4988Config::default(), ms).unwrap();
+        assert_eq!(levels(driver), [Some(false), Some(false), Some(false)]);
+    }
+
+    #[test]
+    fn test_driver_state() {
+        let mut driver = new_driver(StepDirChip::A4988, 16).unwrap();
+        driver.set_ramp(RampProfile {
+            accel_rate: 100,
+            ..RampProfile::default()
+        });
+
+        assert!(!driver.is_enabled());
+        driver.enable(true);
+        assert!(driver.is_enabled());
+
+        driver.set_rpm(120);
+        assert_eq!(driver.update(600), 60);
+        assert!(!driver.is_at_speed());
+        assert_eq!(driver.update(600), 120);
+        assert!(driver.is_at_speed());
+
+        // Never reports a stall
+        assert!(!driver.is_stalled());
+        driver.clear_stall();
+        assert!(!driver.is_stalled());
+    }
+}
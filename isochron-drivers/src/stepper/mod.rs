@@ -1,7 +1,9 @@
 //! Stepper driver implementations
 
+pub mod a4988;
+pub mod tmc2130;
 pub mod tmc2209;
-// pub mod tmc2130;  // Future
-// pub mod a4988;    // Future
 
+pub use a4988::{A4988Config, A4988Driver};
+pub use tmc2130::{Tmc2130Config, Tmc2130Driver};
 pub use tmc2209::{Tmc2209Config, Tmc2209Driver};
@@ -0,0 +1,704 @@
+//! TMC2130 stepper driver (SPI mode)
+//!
+//! The TMC2130 offers the same StealthChop, StallGuard and CoolStep
+//! features as the TMC2209, configured over SPI instead of single-wire
+//! UART. Register layouts mostly match; the differences that matter here
+//! are that StealthChop is enabled in GCONF rather than disabled, and the
+//! StallGuard threshold is the signed SGT field in COOLCONF.
+//!
+//! # SPI Protocol
+//!
+//! SPI mode 3, each datagram 40 bits framed by its own chip select:
+//! - Register address, bit 7 set for a write
+//! - Data (4 bytes, big-endian)
+//!
+//! Every reply starts with the SPI_STATUS byte. The data in a reply is
+//! the register addressed by the *previous* datagram, so reading a
+//! register takes two transfers.
+//!
+//! Speed changes follow a shared [`RampProfile`], advanced by
+//! [`Tmc2130Driver::update`].
+
+use isochron_core::motion::RampProfile;
+use isochron_core::traits::{Direction, StepperDriver};
+use isochron_hal::spi::{Phase, Polarity, SpiConfig};
+use isochron_hal::SpiBus;
+
+use super::tmc2209::DrvStatus;
+
+/// TMC2130 Register addresses
+pub mod reg {
+    /// General configuration
+    pub const GCONF: u8 = 0x00;
+    /// Global status flags
+    pub const GSTAT: u8 = 0x01;
+    /// Input pin states
+    pub const IOIN: u8 = 0x04;
+    /// Hold/run current settings
+    pub const IHOLD_IRUN: u8 = 0x10;
+    /// Power down delay
+    pub const TPOWERDOWN: u8 = 0x11;
+    /// Measured time between steps
+    pub const TSTEP: u8 = 0x12;
+    /// Upper velocity for StealthChop
+    pub const TPWMTHRS: u8 = 0x13;
+    /// Lower velocity for CoolStep/StallGuard
+    pub const TCOOLTHRS: u8 = 0x14;
+    /// Microstep counter
+    pub const MSCNT: u8 = 0x6A;
+    /// Chopper configuration
+    pub const CHOPCONF: u8 = 0x6C;
+    /// CoolStep and StallGuard configuration
+    pub const COOLCONF: u8 = 0x6D;
+    /// Driver status
+    pub const DRV_STATUS: u8 = 0x6F;
+    /// StealthChop PWM configuration
+    pub const PWMCONF: u8 = 0x70;
+}
+
+/// Write bit in the address byte
+const WRITE_BIT: u8 = 0x80;
+
+/// SPI clock, within the 4MHz limit when running from the internal clock
+pub const SPI_FREQUENCY_HZ: u32 = 2_000_000;
+
+/// Default sense resistor (mΩ), typical for TMC2130 breakout boards
+pub const DEFAULT_RSENSE_MOHM: u16 = 110;
+
+/// Default TPOWERDOWN (×2^18 clocks, about 0.4s)
+pub const DEFAULT_TPOWERDOWN: u8 = 20;
+
+/// StallGuard2 threshold range (SGT)
+pub const SGT_MIN: i8 = -64;
+/// StallGuard2 threshold range (SGT)
+pub const SGT_MAX: i8 = 63;
+
+/// Bus settings for a TMC2130
+pub fn spi_config() -> SpiConfig {
+    SpiConfig {
+        frequency: SPI_FREQUENCY_HZ,
+        polarity: Polarity::IdleHigh,
+        phase: Phase::CaptureOnSecondTransition,
+    }
+}
+
+/// TMC2130 driver configuration
+///
+/// Fields shared with [`Tmc2209Config`](super::Tmc2209Config) mean the
+/// same; there is no bus address, as each driver has its own chip select.
+#[derive(Debug, Clone)]
+pub struct Tmc2130Config {
+    /// Run current in mA (100-2000)
+    pub run_current_ma: u16,
+    /// Hold current in mA (typically 50% of run current)
+    pub hold_current_ma: u16,
+    /// Enable StealthChop mode (quiet operation)
+    pub stealthchop: bool,
+    /// StallGuard2 threshold (-64 to 63, lower = more sensitive)
+    pub stallguard_threshold: i8,
+    /// Enable CoolStep current scaling
+    pub coolstep: bool,
+    /// Microstepping (1, 2, 4, 8, 16, 32, 64, 128, 256)
+    pub microsteps: u16,
+    /// Sense resistor value in milliohms (board specific)
+    pub rsense_mohm: u16,
+}
+
+impl Default for Tmc2130Config {
+    fn default() -> Self {
+        Self {
+            run_current_ma: 800,
+            hold_current_ma: 400,
+            stealthchop: true,
+            stallguard_threshold: 0,
+            coolstep: false,
+            microsteps: 16,
+            rsense_mohm: DEFAULT_RSENSE_MOHM,
+        }
+    }
+}
+
+impl Tmc2130Config {
+    /// Convert microsteps to MRES register value
+    pub fn mres(&self) -> u8 {
+        match self.microsteps {
+            1..=256 if self.microsteps.is_power_of_two() => {
+                8 - self.microsteps.trailing_zeros() as u8
+            }
+            _ => 4, // Default to 16 microsteps
+        }
+    }
+
+    /// Convert current in mA to IRUN/IHOLD register value (0-31)
+    ///
+    /// Uses the datasheet formula with VSENSE = 0 (0.32V full scale),
+    /// which adds 20mΩ of internal resistance to the sense resistor.
+    pub fn current_to_cs(current_ma: u16, rsense_mohm: u16) -> u8 {
+        // CS = I_rms * 32 * 1.41 * (Rsense + 0.02) / 0.32 - 1
+        // For milliamps and milliohms:
+        // CS = I_mA * (R_mΩ + 20) * 141421 / 10^9 - 1
+        // For 800mA at 0.11Ω: CS ≈ 14.7 - 1 = 13
+        let scaled = (current_ma as u64) * (rsense_mohm as u64 + 20) * 141_421 / 1_000_000_000;
+        (scaled.saturating_sub(1).min(31)) as u8
+    }
+}
+
+/// SPI_STATUS byte returned at the start of every reply
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SpiStatus {
+    /// Driver was reset since GSTAT was last cleared
+    pub reset_flag: bool,
+    /// Driver shut down on over-temperature or a short
+    pub driver_error: bool,
+    /// StallGuard2 detected a stall
+    pub stallguard: bool,
+    /// Motor standstill
+    pub standstill: bool,
+}
+
+impl SpiStatus {
+    /// Parse from the SPI_STATUS byte
+    pub fn from_byte(value: u8) -> Self {
+        Self {
+            reset_flag: value & (1 << 0) != 0,
+            driver_error: value & (1 << 1) != 0,
+            stallguard: value & (1 << 2) != 0,
+            standstill: value & (1 << 3) != 0,
+        }
+    }
+}
+
+/// Build a write datagram for TMC2130
+pub fn build_write_datagram(reg: u8, data: u32) -> [u8; 5] {
+    let mut datagram = [0u8; 5];
+    datagram[0] = reg | WRITE_BIT;
+    datagram[1..].copy_from_slice(&data.to_be_bytes());
+    datagram
+}
+
+/// Build a read datagram for TMC2130
+///
+/// The register's value arrives in the reply to the next datagram.
+pub fn build_read_datagram(reg: u8) -> [u8; 5] {
+    [reg & !WRITE_BIT, 0, 0, 0, 0]
+}
+
+/// Parse a reply into its status and data
+pub fn parse_reply(reply: &[u8; 5]) -> (SpiStatus, u32) {
+    let data = u32::from_be_bytes([reply[1], reply[2], reply[3], reply[4]]);
+    (SpiStatus::from_byte(reply[0]), data)
+}
+
+/// Exchange one datagram
+pub fn transfer<S: SpiBus>(spi: &mut S, datagram: &[u8; 5]) -> Result<(SpiStatus, u32), S::Error> {
+    let mut reply = [0u8; 5];
+    spi.transfer(&mut reply, datagram)?;
+    Ok(parse_reply(&reply))
+}
+
+/// Write a register, returning the driver status
+pub fn write_register<S: SpiBus>(spi: &mut S, reg: u8, data: u32) -> Result<SpiStatus, S::Error> {
+    transfer(spi, &build_write_datagram(reg, data)).map(|(status, _)| status)
+}
+
+/// Read a register
+///
+/// Sends the read datagram twice: the first reply carries whatever the
+/// previous datagram addressed, the second the requested register.
+pub fn read_register<S: SpiBus>(spi: &mut S, reg: u8) -> Result<(SpiStatus, u32), S::Error> {
+    let datagram = build_read_datagram(reg);
+    transfer(spi, &datagram)?;
+    transfer(spi, &datagram)
+}
+
+/// Parse a TMC2130 DRV_STATUS register
+///
+/// The TMC2130 packs its flags differently from the TMC2209 and has no
+/// short-to-supply, StealthChop or temperature threshold flags; those are
+/// left clear so faults are classified the same way for both drivers.
+/// Unlike the TMC2209 it carries SG_RESULT in bits 0-9, which
+/// [`sg_result_from_register`](super::tmc2209::sg_result_from_register)
+/// extracts.
+pub fn parse_drv_status(value: u32) -> DrvStatus {
+    DrvStatus {
+        standstill: (value & (1 << 31)) != 0,
+        ot_prewarning: (value & (1 << 26)) != 0,
+        ot_shutdown: (value & (1 << 25)) != 0,
+        s2ga: (value & (1 << 27)) != 0,
+        s2gb: (value & (1 << 28)) != 0,
+        s2vsa: false,
+        s2vsb: false,
+        ola: (value & (1 << 29)) != 0,
+        olb: (value & (1 << 30)) != 0,
+        t120: false,
+        t143: false,
+        t150: false,
+        t157: false,
+        stealth: false,
+        cs_actual: ((value >> 16) & 0x1F) as u8,
+    }
+}
+
+/// TMC2130 driver state
+///
+/// This struct manages the driver state and provides methods for
+/// configuring the TMC2130 over SPI.
+pub struct Tmc2130Driver {
+    config: Tmc2130Config,
+    current_rpm: u16,
+    target_rpm: u16,
+    /// Speed ramp for RPM changes
+    ramp: RampProfile,
+    /// Speed the current ramp started from
+    ramp_start_rpm: u16,
+    /// Time into the current ramp (ms)
+    ramp_elapsed_ms: u32,
+    direction: Direction,
+    enabled: bool,
+    stalled: bool,
+    initialized: bool,
+}
+
+impl Tmc2130Driver {
+    /// Create a new TMC2130 driver
+    pub fn new(config: Tmc2130Config) -> Self {
+        Self {
+            config,
+            current_rpm: 0,
+            target_rpm: 0,
+            ramp: RampProfile::default(),
+            ramp_start_rpm: 0,
+            ramp_elapsed_ms: 0,
+            direction: Direction::Clockwise,
+            enabled: false,
+            stalled: false,
+            initialized: false,
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &Tmc2130Config {
+        &self.config
+    }
+
+    /// Set the speed ramp for RPM changes
+    pub fn set_ramp(&mut self, ramp: RampProfile) {
+        self.ramp = ramp;
+    }
+
+    /// Current RPM along the ramp
+    pub fn current_rpm(&self) -> u16 {
+        self.current_rpm
+    }
+
+    /// Advance the speed ramp by `delta_ms`
+    ///
+    /// Returns the RPM to step at.
+    pub fn update(&mut self, delta_ms: u32) -> u16 {
+        self.ramp_elapsed_ms = self.ramp_elapsed_ms.saturating_add(delta_ms);
+        self.current_rpm =
+            self.ramp
+                .rpm_at(self.ramp_start_rpm, self.target_rpm, self.ramp_elapsed_ms);
+        self.current_rpm
+    }
+
+    /// Build GCONF register value
+    fn build_gconf(&self, stealthchop: bool) -> u32 {
+        let mut gconf = 0u32;
+
+        // Bit 0: I_scale_analog = 0 (use internal reference)
+        // Bit 1: internal_Rsense = 0 (external sense resistors)
+        // Bit 2: en_pwm_mode = stealthchop
+        if stealthchop {
+            gconf |= 1 << 2;
+        }
+        // Bit 4: shaft = 0 (normal direction)
+        // Bit 8: diag1_stall = 1 (StallGuard on DIAG1 for sensorless homing)
+        gconf |= 1 << 8;
+        // Bit 13: diag1_pushpull = 0 (open collector, active low)
+
+        gconf
+    }
+
+    /// Build CHOPCONF register value
+    fn build_chopconf(&self) -> u32 {
+        let mut chopconf = 0u32;
+
+        // TOFF = 5 (off time, must be > 0 for driver to work)
+        chopconf |= 5;
+        // HSTRT = 4 (hysteresis start)
+        chopconf |= 4 << 4;
+        // HEND = 0 (hysteresis end)
+        // chm = 0 (SpreadCycle)
+        // TBL = 2 (blanking time)
+        chopconf |= 2 << 15;
+        // vsense = 0 (0.32V full scale, see current_to_cs)
+        // MRES = microstep resolution
+        chopconf |= (self.config.mres() as u32) << 24;
+        // intpol = 1 (interpolate to 256 microsteps)
+        chopconf |= 1 << 28;
+        // dedge = 0 (step on rising edge only)
+        // diss2g = 0 (short to GND protection on)
+
+        chopconf
+    }
+
+    /// Build IHOLD_IRUN register value
+    fn build_ihold_irun(&self, run_ma: u16, hold_ma: u16) -> u32 {
+        let rsense = self.config.rsense_mohm;
+        let ihold = Tmc2130Config::current_to_cs(hold_ma, rsense);
+        let irun = Tmc2130Config::current_to_cs(run_ma, rsense);
+        let iholddelay = 6u32; // Delay before reducing to hold current
+
+        ((iholddelay & 0x0F) << 16) | ((irun as u32 & 0x1F) << 8) | (ihold as u32 & 0x1F)
+    }
+
+    /// Build COOLCONF register value
+    fn build_coolconf(&self, threshold: i8) -> u32 {
+        let mut coolconf = 0u32;
+
+        if self.config.coolstep {
+            // SEMIN = 5 (raise current below SG 5 × 32, 0 disables CoolStep)
+            coolconf |= 5;
+            // SEUP = 1 (current up in steps of 2)
+            coolconf |= 1 << 5;
+            // SEMAX = 2 (lower current above SG (5 + 2 + 1) × 32)
+            coolconf |= 2 << 8;
+            // SEDN = 0 (current down by one every 32 readings)
+            // SEIMIN = 0 (down to half the run current)
+        }
+        // SGT = StallGuard2 threshold, 7-bit two's complement
+        let sgt = threshold.clamp(SGT_MIN, SGT_MAX) as u8 & 0x7F;
+        coolconf |= (sgt as u32) << 16;
+        // sfilt = 0 (unfiltered, a reading every fullstep)
+
+        coolconf
+    }
+
+    /// Build PWMCONF register value for StealthChop
+    fn build_pwmconf(&self) -> u32 {
+        let mut pwmconf = 0u32;
+
+        // PWM_AMPL = 128 (amplitude limit)
+        pwmconf |= 128;
+        // PWM_GRAD = 4 (amplitude gradient)
+        pwmconf |= 4 << 8;
+        // pwm_freq = 1 (2/683 fCLK, about 35kHz)
+        pwmconf |= 1 << 16;
+        // pwm_autoscale = 1
+        pwmconf |= 1 << 18;
+        // pwm_symmetric = 0
+        // freewheel = 0 (normal operation)
+
+        pwmconf
+    }
+
+    /// Get register write datagrams for initialization
+    ///
+    /// Returns an array of datagrams to send over SPI, one per chip select.
+    pub fn init_datagrams(&self) -> [[u8; 5]; 6] {
+        [
+            // GCONF - general configuration
+            self.gconf_datagram(false),
+            // CHOPCONF - chopper configuration + microsteps
+            build_write_datagram(reg::CHOPCONF, self.build_chopconf()),
+            // IHOLD_IRUN - current settings
+            self.set_current_datagram(self.config.run_current_ma, self.config.hold_current_ma),
+            // TPOWERDOWN - power down delay
+            build_write_datagram(reg::TPOWERDOWN, DEFAULT_TPOWERDOWN as u32),
+            // PWMCONF - StealthChop configuration
+            build_write_datagram(reg::PWMCONF, self.build_pwmconf()),
+            // COOLCONF - CoolStep and StallGuard threshold
+            self.set_stallguard_datagram(self.config.stallguard_threshold),
+        ]
+    }
+
+    /// Mark as initialized
+    pub fn set_initialized(&mut self) {
+        self.initialized = true;
+    }
+
+    /// Check if initialized
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Update stall status
+    pub fn set_stalled(&mut self, stalled: bool) {
+        self.stalled = stalled;
+    }
+
+    /// Sync current RPM with target (called when acceleration complete)
+    pub fn sync_rpm(&mut self) {
+        self.current_rpm = self.target_rpm;
+        self.ramp_start_rpm = self.target_rpm;
+    }
+
+    /// Get read datagram for DRV_STATUS register
+    pub fn read_status_datagram(&self) -> [u8; 5] {
+        build_read_datagram(reg::DRV_STATUS)
+    }
+
+    /// Build a datagram to update run current
+    pub fn set_current_datagram(&self, run_ma: u16, hold_ma: u16) -> [u8; 5] {
+        build_write_datagram(reg::IHOLD_IRUN, self.build_ihold_irun(run_ma, hold_ma))
+    }
+
+    /// Build a datagram rewriting GCONF
+    ///
+    /// With `force_stealthchop` the driver runs in StealthChop even when
+    /// configured for SpreadCycle, e.g. for quiet mode; without it the
+    /// configured chopper mode is restored.
+    pub fn gconf_datagram(&self, force_stealthchop: bool) -> [u8; 5] {
+        let stealthchop = force_stealthchop || self.config.stealthchop;
+        build_write_datagram(reg::GCONF, self.build_gconf(stealthchop))
+    }
+
+    /// Build a datagram to update the StallGuard threshold
+    ///
+    /// The threshold shares COOLCONF with the CoolStep settings, which
+    /// are rewritten from the configuration.
+    pub fn set_stallguard_datagram(&self, threshold: i8) -> [u8; 5] {
+        build_write_datagram(reg::COOLCONF, self.build_coolconf(threshold))
+    }
+}
+
+impl StepperDriver for Tmc2130Driver {
+    fn set_rpm(&mut self, rpm: u16) {
+        if rpm != self.target_rpm {
+            self.ramp_start_rpm = self.current_rpm;
+            self.ramp_elapsed_ms = 0;
+        }
+        self.target_rpm = rpm;
+    }
+
+    fn get_rpm(&self) -> u16 {
+        self.target_rpm
+    }
+
+    fn set_direction(&mut self, dir: Direction) {
+        self.direction = dir;
+    }
+
+    fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn enable(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    fn clear_stall(&mut self) {
+        self.stalled = false;
+    }
+
+    fn is_at_speed(&self) -> bool {
+        self.current_rpm == self.target_rpm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stepper::tmc2209::sg_result_from_register;
+    use isochron_core::state::DriverFaultKind;
+    use isochron_hal::mock::{MockError, MockSpi, SpiTransaction};
+
+    #[test]
+    fn test_mres_conversion() {
+        for (microsteps, mres) in [(256, 0), (16, 4), (2, 7), (1, 8), (0, 4), (12, 4), (512, 4)] {
+            let config = Tmc2130Config {
+                microsteps,
+                ..Default::default()
+            };
+            assert_eq!(config.mres(), mres, "{} microsteps", microsteps);
+        }
+    }
+
+    #[test]
+    fn test_current_conversion() {
+        // 800mA with 0.11Ω + 0.02Ω internal: CS = 13
+        assert_eq!(Tmc2130Config::current_to_cs(800, DEFAULT_RSENSE_MOHM), 13);
+        assert_eq!(Tmc2130Config::current_to_cs(400, DEFAULT_RSENSE_MOHM), 6);
+
+        // Extremes saturate instead of overflowing
+        assert_eq!(Tmc2130Config::current_to_cs(u16::MAX, u16::MAX), 31);
+        assert_eq!(Tmc2130Config::current_to_cs(0, DEFAULT_RSENSE_MOHM), 0);
+    }
+
+    #[test]
+    fn test_write_datagram() {
+        let datagram = build_write_datagram(reg::CHOPCONF, 0x1401_0045);
+        assert_eq!(datagram, [reg::CHOPCONF | 0x80, 0x14, 0x01, 0x00, 0x45]);
+
+        // Reads clear the write bit and send no data
+        assert_eq!(build_read_datagram(reg::DRV_STATUS), [0x6F, 0, 0, 0, 0]);
+        assert_eq!(build_read_datagram(reg::GCONF | 0x80), [0x00, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_parse_reply_status() {
+        let (status, data) = parse_reply(&[0x0A, 0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(data, 0x1234_5678);
+        assert_eq!(
+            status,
+            SpiStatus {
+                reset_flag: false,
+                driver_error: true,
+                stallguard: false,
+                standstill: true,
+            }
+        );
+
+        let status = SpiStatus::from_byte(0x05);
+        assert!(status.reset_flag && status.stallguard);
+        assert!(!status.driver_error && !status.standstill);
+        assert_eq!(SpiStatus::from_byte(0xF0), SpiStatus::default());
+    }
+
+    #[test]
+    fn test_register_values() {
+        let driver = Tmc2130Driver::new(Tmc2130Config::default());
+
+        // StealthChop is enabled in GCONF, unlike the TMC2209
+        assert_eq!(driver.build_gconf(true), 0x0000_0104);
+        assert_eq!(driver.build_gconf(false), 0x0000_0100);
+        assert_eq!(driver.build_chopconf(), 0x1401_0045);
+        assert_eq!(driver.build_ihold_irun(800, 400), 0x0006_0D06);
+        assert_eq!(driver.build_pwmconf(), 0x0005_0480);
+    }
+
+    #[test]
+    fn test_coolconf_threshold() {
+        let driver = Tmc2130Driver::new(Tmc2130Config::default());
+        assert_eq!(driver.build_coolconf(0), 0);
+        assert_eq!(driver.build_coolconf(10), 10 << 16);
+        // Negative thresholds are 7-bit two's complement
+        assert_eq!(driver.build_coolconf(-1), 0x7F << 16);
+        assert_eq!(driver.build_coolconf(-64), 0x40 << 16);
+        // Out of range values clamp
+        assert_eq!(driver.build_coolconf(100), 63 << 16);
+        assert_eq!(driver.build_coolconf(-100), 0x40 << 16);
+
+        let driver = Tmc2130Driver::new(Tmc2130Config {
+            coolstep: true,
+            ..Default::default()
+        });
+        assert_eq!(driver.build_coolconf(-1), 0x007F_0225);
+
+        let datagram = driver.set_stallguard_datagram(5);
+        assert_eq!(datagram, [reg::COOLCONF | 0x80, 0x00, 0x05, 0x02, 0x25]);
+    }
+
+    #[test]
+    fn test_init_over_spi() {
+        let mut driver = Tmc2130Driver::new(Tmc2130Config::default());
+        let datagrams = driver.init_datagrams();
+        assert!(datagrams.iter().all(|dg| dg[0] & 0x80 != 0));
+
+        // The first reply reports the reset since power-up
+        let mut expected = [0x01, 0, 0, 0, 0];
+        let mut spi = MockSpi::default();
+        for datagram in &datagrams {
+            spi.expect(&[SpiTransaction::transfer(datagram, &expected)]);
+            expected = [0x08, 0, 0, 0, 0];
+        }
+
+        let status = write_register(&mut spi, datagrams[0][0] & 0x7F, 0x104).unwrap();
+        assert!(status.reset_flag);
+        for datagram in &datagrams[1..] {
+            let (status, _) = transfer(&mut spi, datagram).unwrap();
+            assert!(status.standstill && !status.reset_flag);
+        }
+        driver.set_initialized();
+        assert!(driver.is_initialized());
+        spi.done();
+    }
+
+    #[test]
+    fn test_read_register_is_pipelined() {
+        let request = build_read_datagram(reg::DRV_STATUS);
+        let mut spi = MockSpi::new(&[
+            // The first reply holds the previously addressed register
+            SpiTransaction::transfer(&request, &[0x08, 0xDE, 0xAD, 0xBE, 0xEF]),
+            SpiTransaction::transfer(&request, &[0x0A, 0x02, 0x00, 0x00, 0x00]),
+        ]);
+
+        let (status, value) = read_register(&mut spi, reg::DRV_STATUS).unwrap();
+        assert!(status.driver_error);
+        assert_eq!(
+            parse_drv_status(value).fault_kind(),
+            Some(DriverFaultKind::OverTemperature)
+        );
+        spi.done();
+
+        // A bus error on either transfer fails the read
+        let mut spi = MockSpi::new(&[
+            SpiTransaction::transfer(&request, &[0; 5]).with_error(MockError::Injected)
+        ]);
+        assert_eq!(
+            read_register(&mut spi, reg::DRV_STATUS),
+            Err(MockError::Injected)
+        );
+        spi.done();
+    }
+
+    #[test]
+    fn test_drv_status_parsing() {
+        let status = parse_drv_status(0x8000_0000);
+        assert!(status.standstill);
+        assert!(!status.has_fault());
+
+        let status = parse_drv_status(0x001F_01FF);
+        assert_eq!(sg_result_from_register(0x001F_01FF), 0x1FF);
+        assert_eq!(status.cs_actual, 31);
+
+        // Warnings alone are not faults
+        let warning = parse_drv_status((1 << 26) | (1 << 29) | (1 << 30));
+        assert!(warning.has_warning());
+        assert_eq!(warning.fault_kind(), None);
+
+        for bit in [27, 28] {
+            assert_eq!(
+                parse_drv_status(1 << bit).fault_kind(),
+                Some(DriverFaultKind::ShortCircuit)
+            );
+        }
+        // Bits that mean a short on the TMC2209 are SG_RESULT here
+        assert_eq!(parse_drv_status(0x3C).fault_kind(), None);
+    }
+
+    #[test]
+    fn test_driver_state() {
+        let mut driver = Tmc2130Driver::new(Tmc2130Config::default());
+        driver.set_ramp(RampProfile {
+            accel_rate: 100,
+            ..RampProfile::default()
+        });
+
+        assert!(!driver.is_enabled());
+        driver.enable(true);
+        driver.set_direction(Direction::CounterClockwise);
+        assert_eq!(driver.get_direction(), Direction::CounterClockwise);
+
+        driver.set_rpm(120);
+        assert_eq!(driver.update(600), 60);
+        assert!(!driver.is_at_speed());
+        assert_eq!(driver.update(600), 120);
+        assert!(driver.is_at_speed());
+
+        driver.set_stalled(true);
+        assert!(driver.is_stalled());
+        driver.clear_stall();
+        assert!(!driver.is_stalled());
+    }
+}
@@ -17,7 +17,12 @@
 //! - StealthChop: Quiet operation mode using voltage chopping
 //! - StallGuard: Load-based stall detection without physical endstops
 //! - CoolStep: Dynamic current scaling based on load (optional)
+//!
+//! Speed changes follow a shared [`RampProfile`], advanced by
+//! [`Tmc2209Driver::update`].
 
+use isochron_core::motion::{RampProfile, SpeedRamp};
+use isochron_core::state::DriverFaultKind;
 use isochron_core::traits::{Direction, StepperDriver};
 
 /// TMC2209 Register addresses
@@ -57,6 +62,21 @@ pub mod reg {
 /// UART sync byte for TMC2209
 const SYNC_BYTE: u8 = 0x05;
 
+/// Default sense resistor (mΩ), typical for TMC2209 breakout boards
+pub const DEFAULT_RSENSE_MOHM: u16 = 110;
+
+/// Default TPOWERDOWN (×2^18 clocks, about 0.44s)
+pub const DEFAULT_TPOWERDOWN: u8 = 20;
+
+/// TMC2209 internal clock (Hz)
+const FCLK_HZ: u64 = 12_000_000;
+
+/// Clocks per TPOWERDOWN unit
+const TPOWERDOWN_UNIT_CLOCKS: u64 = 1 << 18;
+
+/// Clocks without a step pulse before the driver detects standstill
+const STANDSTILL_CLOCKS: u64 = 1 << 20;
+
 /// TMC2209 driver configuration
 #[derive(Debug, Clone)]
 pub struct Tmc2209Config {
@@ -72,6 +92,12 @@ pub struct Tmc2209Config {
     pub stallguard_threshold: u8,
     /// Microstepping (1, 2, 4, 8, 16, 32, 64, 128, 256)
     pub microsteps: u16,
+    /// Sense resistor value in milliohms (board specific)
+    pub rsense_mohm: u16,
+    /// Microsteps per output revolution, gearing included
+    pub steps_per_rev: u32,
+    /// Keep run current between the steps of a slow spin
+    pub spin_hold: bool,
 }
 
 impl Default for Tmc2209Config {
@@ -83,6 +109,9 @@ impl Default for Tmc2209Config {
             stealthchop: true,
             stallguard_threshold: 80,
             microsteps: 16,
+            rsense_mohm: DEFAULT_RSENSE_MOHM,
+            steps_per_rev: 3200,
+            spin_hold: false,
         }
     }
 }
@@ -105,16 +134,21 @@ impl Tmc2209Config {
     }
 
     /// Convert current in mA to IRUN/IHOLD register value (0-31)
-    /// Based on Rsense = 0.11 ohm (typical for TMC2209 breakout boards)
-    pub fn current_to_cs(current_ma: u16) -> u8 {
+    ///
+    /// `rsense_mohm` is the board's sense resistor; a higher resistance
+    /// needs a lower CS for the same current.
+    pub fn current_to_cs(current_ma: u16, rsense_mohm: u16) -> u8 {
         // CS = (I_rms * 32 * 1.41 * Rsense) / Vref - 1
         // With Rsense = 0.11, Vref = 0.325 (internal)
         // CS ≈ (I_rms * 32 * 1.41 * 0.11) / 0.325 - 1
         // CS ≈ I_rms * 15.34 - 1
         // For milliamps: CS = (I_mA * 1534 / 100000) - 1
         // For 800mA: CS ≈ (800 * 1534 / 100000) - 1 = 12 - 1 = 11
-        let cs = ((current_ma as u32) * 1534 / 100000).saturating_sub(1);
-        (cs.min(31)) as u8
+        //
+        // Scaled by Rsense / 0.11 for other boards; u64 avoids overflow
+        let scaled = (current_ma as u64) * (rsense_mohm as u64) * 1534
+            / (DEFAULT_RSENSE_MOHM as u64 * 100000);
+        (scaled.saturating_sub(1).min(31)) as u8
     }
 }
 
@@ -192,6 +226,15 @@ pub fn parse_read_response(response: &[u8; 8]) -> Result<u32, Tmc2209Error> {
     Ok(data)
 }
 
+/// Extract the StallGuard load measurement from an SG_RESULT value
+///
+/// SG_RESULT holds the 10-bit result (0-510, lower = higher load); the
+/// TMC2209 reports it only here, not in DRV_STATUS. The TMC2130 packs the
+/// same field into the low bits of its DRV_STATUS.
+pub fn sg_result_from_register(value: u32) -> u16 {
+    (value & 0x3FF) as u16
+}
+
 /// TMC2209 communication errors
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -205,11 +248,12 @@ pub enum Tmc2209Error {
 }
 
 /// Parsed DRV_STATUS register
+///
+/// StallGuard isn't part of DRV_STATUS on the TMC2209; it has its own
+/// SG_RESULT register (see [`sg_result_from_register`]).
 #[derive(Debug, Clone, Copy, Default)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DrvStatus {
-    /// StallGuard result (0-510)
-    pub sg_result: u16,
     /// Motor standstill indicator
     pub standstill: bool,
     /// Overtemperature pre-warning (120°C)
@@ -228,6 +272,14 @@ pub struct DrvStatus {
     pub ola: bool,
     /// Open load on phase B
     pub olb: bool,
+    /// Temperature above 120°C
+    pub t120: bool,
+    /// Temperature above 143°C
+    pub t143: bool,
+    /// Temperature above 150°C
+    pub t150: bool,
+    /// Temperature above 157°C
+    pub t157: bool,
     /// StealthChop active
     pub stealth: bool,
     /// Current scaling (0-31)
@@ -236,19 +288,27 @@ pub struct DrvStatus {
 
 impl DrvStatus {
     /// Parse from raw DRV_STATUS register value
+    ///
+    /// TMC2209 layout: otpw, ot, s2ga, s2gb, s2vsa, s2vsb, ola, olb in
+    /// bits 0-7, t120/t143/t150/t157 in bits 8-11, CS_ACTUAL in bits
+    /// 16-20, stealth in bit 30 and stst in bit 31.
     pub fn from_register(value: u32) -> Self {
+        let bit = |n: u32| (value & (1 << n)) != 0;
         Self {
-            sg_result: (value & 0x3FF) as u16,
-            standstill: (value & (1 << 31)) != 0,
-            ot_prewarning: (value & (1 << 26)) != 0,
-            ot_shutdown: (value & (1 << 25)) != 0,
-            s2ga: (value & (1 << 24)) != 0,
-            s2gb: (value & (1 << 23)) != 0,
-            s2vsa: (value & (1 << 12)) != 0,
-            s2vsb: (value & (1 << 11)) != 0,
-            ola: (value & (1 << 29)) != 0,
-            olb: (value & (1 << 30)) != 0,
-            stealth: (value & (1 << 14)) != 0,
+            standstill: bit(31),
+            ot_prewarning: bit(0),
+            ot_shutdown: bit(1),
+            s2ga: bit(2),
+            s2gb: bit(3),
+            s2vsa: bit(4),
+            s2vsb: bit(5),
+            ola: bit(6),
+            olb: bit(7),
+            t120: bit(8),
+            t143: bit(9),
+            t150: bit(10),
+            t157: bit(11),
+            stealth: bit(30),
             cs_actual: ((value >> 16) & 0x1F) as u8,
         }
     }
@@ -258,6 +318,19 @@ impl DrvStatus {
         self.ot_shutdown || self.s2ga || self.s2gb || self.s2vsa || self.s2vsb
     }
 
+    /// Fault to report to the controller, if any
+    ///
+    /// Over-temperature shutdown takes precedence over short detection.
+    pub fn fault_kind(&self) -> Option<DriverFaultKind> {
+        if self.ot_shutdown {
+            Some(DriverFaultKind::OverTemperature)
+        } else if self.s2ga || self.s2gb || self.s2vsa || self.s2vsb {
+            Some(DriverFaultKind::ShortCircuit)
+        } else {
+            None
+        }
+    }
+
     /// Check if driver is in warning state
     pub fn has_warning(&self) -> bool {
         self.ot_prewarning || self.ola || self.olb
@@ -270,8 +343,8 @@ impl DrvStatus {
 /// configuring the TMC2209 over UART.
 pub struct Tmc2209Driver {
     config: Tmc2209Config,
-    current_rpm: u16,
-    target_rpm: u16,
+    /// Speed ramp for RPM changes
+    ramp: SpeedRamp,
     direction: Direction,
     enabled: bool,
     stalled: bool,
@@ -283,8 +356,7 @@ impl Tmc2209Driver {
     pub fn new(config: Tmc2209Config) -> Self {
         Self {
             config,
-            current_rpm: 0,
-            target_rpm: 0,
+            ramp: SpeedRamp::default(),
             direction: Direction::Clockwise,
             enabled: false,
             stalled: false,
@@ -297,14 +369,31 @@ impl Tmc2209Driver {
         &self.config
     }
 
+    /// Set the speed ramp for RPM changes
+    pub fn set_ramp(&mut self, ramp: RampProfile) {
+        self.ramp.set_profile(ramp);
+    }
+
+    /// Current RPM along the ramp
+    pub fn current_rpm(&self) -> u16 {
+        self.ramp.current()
+    }
+
+    /// Advance the speed ramp by `delta_ms`
+    ///
+    /// Returns the RPM to step at.
+    pub fn update(&mut self, delta_ms: u32) -> u16 {
+        self.ramp.update(delta_ms)
+    }
+
     /// Build GCONF register value
-    fn build_gconf(&self) -> u32 {
+    fn build_gconf(&self, stealthchop: bool) -> u32 {
         let mut gconf = 0u32;
 
         // Bit 0: I_scale_analog = 0 (use internal reference)
         // Bit 1: internal_Rsense = 0 (external sense resistors)
         // Bit 2: en_spreadcycle = !stealthchop
-        if !self.config.stealthchop {
+        if !stealthchop {
             gconf |= 1 << 2;
         }
         // Bit 3: shaft = 0 (normal direction)
@@ -344,8 +433,9 @@ impl Tmc2209Driver {
 
     /// Build IHOLD_IRUN register value
     fn build_ihold_irun(&self) -> u32 {
-        let ihold = Tmc2209Config::current_to_cs(self.config.hold_current_ma);
-        let irun = Tmc2209Config::current_to_cs(self.config.run_current_ma);
+        let rsense = self.config.rsense_mohm;
+        let ihold = Tmc2209Config::current_to_cs(self.config.hold_current_ma, rsense);
+        let irun = Tmc2209Config::current_to_cs(self.config.run_current_ma, rsense);
         let iholddelay = 6u32; // Delay before reducing to hold current
 
         ((iholddelay & 0x0F) << 16) | ((irun as u32 & 0x1F) << 8) | (ihold as u32 & 0x1F)
@@ -382,13 +472,13 @@ impl Tmc2209Driver {
 
         [
             // GCONF - general configuration
-            build_write_datagram(addr, reg::GCONF, self.build_gconf()),
+            self.gconf_datagram(false),
             // CHOPCONF - chopper configuration + microsteps
             build_write_datagram(addr, reg::CHOPCONF, self.build_chopconf()),
             // IHOLD_IRUN - current settings
             build_write_datagram(addr, reg::IHOLD_IRUN, self.build_ihold_irun()),
             // TPOWERDOWN - power down delay
-            build_write_datagram(addr, reg::TPOWERDOWN, 20),
+            build_write_datagram(addr, reg::TPOWERDOWN, DEFAULT_TPOWERDOWN as u32),
             // PWMCONF - StealthChop configuration
             build_write_datagram(addr, reg::PWMCONF, self.build_pwmconf()),
             // SGTHRS - StallGuard threshold
@@ -413,7 +503,7 @@ impl Tmc2209Driver {
 
     /// Sync current RPM with target (called when acceleration complete)
     pub fn sync_rpm(&mut self) {
-        self.current_rpm = self.target_rpm;
+        self.ramp.sync();
     }
 
     /// Get read request for DRV_STATUS register
@@ -421,6 +511,11 @@ impl Tmc2209Driver {
         build_read_request(self.config.uart_address, reg::DRV_STATUS)
     }
 
+    /// Get read request for SG_RESULT register (StallGuard load)
+    pub fn read_sg_result_request(&self) -> [u8; 4] {
+        build_read_request(self.config.uart_address, reg::SG_RESULT)
+    }
+
     /// Get read request for TSTEP register (measures actual step rate)
     pub fn read_tstep_request(&self) -> [u8; 4] {
         build_read_request(self.config.uart_address, reg::TSTEP)
@@ -433,14 +528,62 @@ impl Tmc2209Driver {
 
     /// Build a datagram to update run current
     pub fn set_current_datagram(&self, run_ma: u16, hold_ma: u16) -> [u8; 8] {
-        let ihold = Tmc2209Config::current_to_cs(hold_ma);
-        let irun = Tmc2209Config::current_to_cs(run_ma);
+        let rsense = self.config.rsense_mohm;
+        let ihold = Tmc2209Config::current_to_cs(hold_ma, rsense);
+        let irun = Tmc2209Config::current_to_cs(run_ma, rsense);
         let iholddelay = 6u32;
         let value =
             ((iholddelay & 0x0F) << 16) | ((irun as u32 & 0x1F) << 8) | (ihold as u32 & 0x1F);
         build_write_datagram(self.config.uart_address, reg::IHOLD_IRUN, value)
     }
 
+    /// TPOWERDOWN that keeps run current while spinning at `rpm`
+    ///
+    /// The driver treats 2^20 clocks (~87ms) without a step pulse as
+    /// standstill and drops to hold current TPOWERDOWN later. At a very
+    /// slow spin the gap between steps can be long enough for that to
+    /// happen mid-spin, so with `spin_hold` the delay is stretched to
+    /// twice the step interval. Stopped (or with `spin_hold` off) the
+    /// default delay applies and the motor rests at hold current.
+    pub fn power_down_delay(&self, rpm: u16) -> u8 {
+        if !self.config.spin_hold || rpm == 0 {
+            return DEFAULT_TPOWERDOWN;
+        }
+
+        let steps_per_min = rpm as u64 * self.config.steps_per_rev.max(1) as u64;
+        let step_clocks = FCLK_HZ * 60 / steps_per_min;
+        if step_clocks < STANDSTILL_CLOCKS {
+            return DEFAULT_TPOWERDOWN;
+        }
+        let delay = step_clocks.div_ceil(TPOWERDOWN_UNIT_CLOCKS) * 2;
+        delay.clamp(DEFAULT_TPOWERDOWN as u64, u8::MAX as u64) as u8
+    }
+
+    /// Build a datagram applying the power-down delay for `rpm`
+    ///
+    /// See [`Tmc2209Driver::power_down_delay`].
+    pub fn power_down_datagram(&self, rpm: u16) -> [u8; 8] {
+        build_write_datagram(
+            self.config.uart_address,
+            reg::TPOWERDOWN,
+            self.power_down_delay(rpm) as u32,
+        )
+    }
+
+    /// Build a datagram rewriting GCONF
+    ///
+    /// With `force_stealthchop` the driver runs in StealthChop even when
+    /// configured for SpreadCycle, e.g. for quiet mode; without it the
+    /// configured chopper mode is restored.
+    pub fn gconf_datagram(&self, force_stealthchop: bool) -> [u8; 8] {
+        let stealthchop = force_stealthchop || self.config.stealthchop;
+        build_write_datagram(
+            self.config.uart_address,
+            reg::GCONF,
+            self.build_gconf(stealthchop),
+        )
+    }
+
     /// Build a datagram to update StallGuard threshold
     pub fn set_stallguard_datagram(&self, threshold: u8) -> [u8; 8] {
         build_write_datagram(self.config.uart_address, reg::SGTHRS, threshold as u32)
@@ -449,11 +592,11 @@ impl Tmc2209Driver {
 
 impl StepperDriver for Tmc2209Driver {
     fn set_rpm(&mut self, rpm: u16) {
-        self.target_rpm = rpm;
+        self.ramp.set_target(rpm);
     }
 
     fn get_rpm(&self) -> u16 {
-        self.target_rpm
+        self.ramp.target()
     }
 
     fn set_direction(&mut self, dir: Direction) {
@@ -481,7 +624,7 @@ impl StepperDriver for Tmc2209Driver {
     }
 
     fn is_at_speed(&self) -> bool {
-        self.current_rpm == self.target_rpm
+        self.ramp.current() == self.ramp.target()
     }
 }
 
@@ -513,14 +656,51 @@ mod tests {
     #[test]
     fn test_current_conversion() {
         // 800mA should give roughly CS=11
-        let cs = Tmc2209Config::current_to_cs(800);
+        let cs = Tmc2209Config::current_to_cs(800, DEFAULT_RSENSE_MOHM);
         assert!((10..=13).contains(&cs));
 
         // 400mA should be lower
-        let cs_low = Tmc2209Config::current_to_cs(400);
+        let cs_low = Tmc2209Config::current_to_cs(400, DEFAULT_RSENSE_MOHM);
         assert!(cs_low < cs);
     }
 
+    #[test]
+    fn test_current_conversion_default_rsense_unchanged() {
+        // Matches the original fixed 0.11 ohm formula
+        for current_ma in [0u16, 100, 400, 800, 1200, 2000, u16::MAX] {
+            let expected = ((current_ma as u32) * 1534 / 100000)
+                .saturating_sub(1)
+                .min(31) as u8;
+            assert_eq!(
+                Tmc2209Config::current_to_cs(current_ma, DEFAULT_RSENSE_MOHM),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_current_conversion_rsense() {
+        let cs_075 = Tmc2209Config::current_to_cs(800, 75);
+        let cs_110 = Tmc2209Config::current_to_cs(800, 110);
+        let cs_150 = Tmc2209Config::current_to_cs(800, 150);
+        assert_eq!((cs_075, cs_110, cs_150), (7, 11, 15));
+
+        // Extremes saturate instead of overflowing
+        assert_eq!(Tmc2209Config::current_to_cs(u16::MAX, u16::MAX), 31);
+        assert_eq!(Tmc2209Config::current_to_cs(800, 0), 0);
+    }
+
+    #[test]
+    fn test_ihold_irun_uses_rsense() {
+        let driver = Tmc2209Driver::new(Tmc2209Config {
+            rsense_mohm: 150,
+            ..Default::default()
+        });
+        let value = driver.build_ihold_irun();
+        assert_eq!((value >> 8) & 0x1F, 15); // IRUN for 800mA
+        assert_eq!(value & 0x1F, 7); // IHOLD for 400mA
+    }
+
     #[test]
     fn test_crc8() {
         // Test with known values
@@ -560,6 +740,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_init_over_uart() {
+        use isochron_hal::mock::MockUart;
+        use isochron_hal::{UartRx, UartTx};
+
+        let mut driver = Tmc2209Driver::new(Tmc2209Config::default());
+        let mut uart = MockUart::new();
+
+        for datagram in driver.init_datagrams() {
+            uart.write_blocking(&datagram).unwrap();
+        }
+        driver.set_initialized();
+
+        let written = uart.take_written();
+        assert_eq!(written.len(), 6 * 8);
+        // GCONF
+        assert_eq!(written[..8], [0x05, 0x00, 0x80, 0, 0, 0x01, 0xC0, 0x66]);
+        // TPOWERDOWN = 20
+        assert_eq!(written[24..32], [0x05, 0x00, 0x91, 0, 0, 0, 0x14, 0x01]);
+
+        // IFCNT read-back over the same link
+        uart.write_blocking(&driver.read_ifcnt_request()).unwrap();
+        let mut response = [SYNC_BYTE, 0xFF, reg::IFCNT, 0, 0, 0, 6, 0];
+        response[7] = crc8(&response[..7]);
+        uart.queue_rx(&response);
+
+        let mut reply = [0u8; 8];
+        uart.read_blocking(&mut reply).unwrap();
+        assert_eq!(parse_read_response(&reply), Ok(6));
+        assert_eq!(uart.written(), driver.read_ifcnt_request());
+    }
+
     #[test]
     fn test_driver_state() {
         let config = Tmc2209Config::default();
@@ -579,6 +791,27 @@ mod tests {
         assert_eq!(driver.get_direction(), Direction::CounterClockwise);
     }
 
+    #[test]
+    fn test_rpm_follows_ramp() {
+        let mut driver = Tmc2209Driver::new(Tmc2209Config::default());
+        driver.set_ramp(RampProfile {
+            accel_rate: 100,
+            ..RampProfile::default()
+        });
+
+        driver.set_rpm(120);
+        assert_eq!(driver.update(600), 60);
+        assert!(!driver.is_at_speed());
+        assert_eq!(driver.update(600), 120);
+        assert!(driver.is_at_speed());
+
+        // A new target ramps from where the motor is
+        driver.set_rpm(60);
+        assert_eq!(driver.update(300), 90);
+        driver.sync_rpm();
+        assert_eq!(driver.update(10), 60);
+    }
+
     #[test]
     fn test_drv_status_parsing() {
         // Test standstill flag (bit 31)
@@ -586,17 +819,26 @@ mod tests {
         assert!(status.standstill);
         assert!(!status.has_fault());
 
-        // Test StallGuard result (bits 0-9)
-        let status = DrvStatus::from_register(0x000001FF);
-        assert_eq!(status.sg_result, 0x1FF);
-
-        // Test overtemperature shutdown (bit 25)
-        let status = DrvStatus::from_register(1 << 25);
+        // Test overtemperature pre-warning (bit 0) and shutdown (bit 1)
+        let status = DrvStatus::from_register(1 << 0);
+        assert!(status.ot_prewarning && !status.ot_shutdown);
+        assert!(!status.has_fault() && status.has_warning());
+        let status = DrvStatus::from_register(1 << 1);
         assert!(status.ot_shutdown);
         assert!(status.has_fault());
 
-        // Test StealthChop active (bit 14)
-        let status = DrvStatus::from_register(1 << 14);
+        // Test short and open load flags (bits 2-7)
+        let status = DrvStatus::from_register(0xFC);
+        assert!(status.s2ga && status.s2gb && status.s2vsa && status.s2vsb);
+        assert!(status.ola && status.olb);
+
+        // Test temperature thresholds (bits 8-11)
+        let status = DrvStatus::from_register(0xF00);
+        assert!(status.t120 && status.t143 && status.t150 && status.t157);
+        assert!(!status.has_fault());
+
+        // Test StealthChop active (bit 30)
+        let status = DrvStatus::from_register(1 << 30);
         assert!(status.stealth);
 
         // Test CS_ACTUAL (bits 16-20)
@@ -604,6 +846,50 @@ mod tests {
         assert_eq!(status.cs_actual, 31);
     }
 
+    #[test]
+    fn test_drv_status_fault_kind() {
+        // Clean status: standstill, StealthChop, some current, warm
+        let clean = DrvStatus::from_register(0xC010_0F00);
+        assert_eq!(clean.fault_kind(), None);
+
+        // Warnings alone are not faults
+        let warning = DrvStatus::from_register((1 << 0) | (1 << 6) | (1 << 7));
+        assert_eq!(warning.fault_kind(), None);
+        assert!(warning.has_warning());
+
+        let ot = DrvStatus::from_register(1 << 1);
+        assert_eq!(ot.fault_kind(), Some(DriverFaultKind::OverTemperature));
+
+        for bit in [2, 3, 4, 5] {
+            let short = DrvStatus::from_register(1 << bit);
+            assert_eq!(short.fault_kind(), Some(DriverFaultKind::ShortCircuit));
+        }
+
+        // Shutdown wins when both are reported
+        let both = DrvStatus::from_register((1 << 1) | (1 << 2));
+        assert_eq!(both.fault_kind(), Some(DriverFaultKind::OverTemperature));
+    }
+
+    #[test]
+    fn test_drv_status_fault_from_response() {
+        let mut response = [SYNC_BYTE, 0xFF, reg::DRV_STATUS, 0, 0, 0, 0x02, 0];
+        response[7] = crc8(&response[..7]);
+        let status = DrvStatus::from_register(parse_read_response(&response).unwrap());
+        assert_eq!(status.fault_kind(), Some(DriverFaultKind::OverTemperature));
+    }
+
+    #[test]
+    fn test_sg_result_from_response() {
+        let driver = Tmc2209Driver::new(Tmc2209Config::default());
+        assert_eq!(driver.read_sg_result_request()[2], reg::SG_RESULT);
+
+        // Only the low 10 bits are the result
+        let mut response = [SYNC_BYTE, 0xFF, reg::SG_RESULT, 0xFF, 0xFF, 0x01, 0xFE, 0];
+        response[7] = crc8(&response[..7]);
+        let value = parse_read_response(&response).unwrap();
+        assert_eq!(sg_result_from_register(value), 510);
+    }
+
     #[test]
     fn test_read_request() {
         let request = build_read_request(0, reg::DRV_STATUS);
@@ -659,4 +945,64 @@ mod tests {
         assert_eq!(datagram[0], SYNC_BYTE);
         assert_eq!(datagram[2], reg::IHOLD_IRUN | 0x80); // write bit set
     }
+
+    /// Full-step, direct-drive basket: 200 steps per revolution
+    fn spin_hold_driver() -> Tmc2209Driver {
+        Tmc2209Driver::new(Tmc2209Config {
+            microsteps: 1,
+            steps_per_rev: 200,
+            spin_hold: true,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_power_down_delay_holds_slow_spin() {
+        let driver = spin_hold_driver();
+
+        // 1 RPM: a step every 300ms, well past standstill detection
+        let delay = driver.power_down_delay(1);
+        assert!(delay > DEFAULT_TPOWERDOWN);
+        let step_clocks = FCLK_HZ * 60 / 200;
+        assert!(delay as u64 * TPOWERDOWN_UNIT_CLOCKS > step_clocks);
+
+        // Faster spins need less, down to the default
+        assert!(driver.power_down_delay(2) < delay);
+        assert_eq!(driver.power_down_delay(60), DEFAULT_TPOWERDOWN);
+
+        let datagram = driver.power_down_datagram(1);
+        assert_eq!(datagram[2], reg::TPOWERDOWN | 0x80);
+        assert_eq!(datagram[6], delay);
+    }
+
+    #[test]
+    fn test_power_down_delay_allows_standstill() {
+        // Stopped: the motor should drop to hold current as usual
+        assert_eq!(spin_hold_driver().power_down_delay(0), DEFAULT_TPOWERDOWN);
+
+        // Without spin hold the delay never changes
+        let driver = Tmc2209Driver::new(Tmc2209Config {
+            microsteps: 1,
+            steps_per_rev: 200,
+            ..Default::default()
+        });
+        assert_eq!(driver.power_down_delay(1), DEFAULT_TPOWERDOWN);
+    }
+
+    #[test]
+    fn test_gconf_datagram_forces_stealthchop() {
+        let driver = Tmc2209Driver::new(Tmc2209Config {
+            stealthchop: false,
+            ..Default::default()
+        });
+        let en_spreadcycle = 1 << 2;
+
+        let configured = driver.gconf_datagram(false);
+        assert_eq!(configured[2], reg::GCONF | 0x80);
+        assert_eq!(configured, driver.init_datagrams()[0]);
+        assert_ne!(configured[6] & en_spreadcycle, 0);
+
+        let quiet = driver.gconf_datagram(true);
+        assert_eq!(quiet[6] & en_spreadcycle, 0);
+    }
 }
@@ -231,6 +231,26 @@ fn validate_machine_section(config: &toml::Value) {
         }
     }
 
+    // Validate autostart_program names an existing program
+    if let Some(toml::Value::String(name)) = machine.get("autostart_program") {
+        let exists = config
+            .get("program")
+            .and_then(|p| p.as_table())
+            .map(|programs| {
+                programs.iter().any(|(key, program)| {
+                    key == name
+                        || program.get("label").and_then(|l| l.as_str()) == Some(name.as_str())
+                })
+            })
+            .unwrap_or(false);
+        if !exists {
+            errors.push(format!(
+                "autostart_program '{}' does not match any program",
+                name
+            ));
+        }
+    }
+
     if !errors.is_empty() {
         panic!(
             "\n\
@@ -257,6 +277,11 @@ fn validate_profiles(config: &toml::Value) {
 
     let mut errors = Vec::new();
 
+    let max_spinoff_rpm = config
+        .get("machine")
+        .and_then(|m| m.get("max_spinoff_rpm"))
+        .and_then(|v| v.as_integer());
+
     for (name, profile) in profiles {
         let profile = match profile {
             toml::Value::Table(t) => t,
@@ -296,6 +321,26 @@ fn validate_profiles(config: &toml::Value) {
                 errors.push(format!("[profile.{}] rpm must be 0-1000", name));
             }
         }
+        // Spin/soak profiles don't use time_s
+        if let Some(toml::Value::Integer(time_s)) = profile.get("time_s") {
+            if *time_s <= 0 && profile.get("spin_s").is_none() {
+                errors.push(format!("[profile.{}] time_s must be greater than 0", name));
+            }
+        }
+
+        // Spin-off RPM must respect the machine limit
+        let spinoff_rpm = profile
+            .get("spinoff")
+            .and_then(|s| s.get("rpm"))
+            .and_then(|v| v.as_integer());
+        if let (Some(rpm), Some(max_rpm)) = (spinoff_rpm, max_spinoff_rpm) {
+            if rpm > max_rpm {
+                errors.push(format!(
+                    "[profile.{}] spinoff rpm {} exceeds max_spinoff_rpm {}",
+                    name, rpm, max_rpm
+                ));
+            }
+        }
     }
 
     if !errors.is_empty() {
@@ -455,6 +500,11 @@ fn validate_jars(config: &toml::Value) {
         .and_then(|h| h.as_table())
         .map(|t| t.keys().cloned().collect())
         .unwrap_or_default();
+    let ultrasonics: Vec<String> = config
+        .get("ultrasonic")
+        .and_then(|u| u.as_table())
+        .map(|t| t.keys().cloned().collect())
+        .unwrap_or_default();
 
     // Get position limits from stepper configurations (Klipper-style)
     // Only steppers support position control for automated jar movement
@@ -462,6 +512,9 @@ fn validate_jars(config: &toml::Value) {
     let z_limits = get_stepper_position_limits(config, "z");
 
     let mut errors = Vec::new();
+    if ultrasonics.len() > 1 {
+        errors.push("only one [ultrasonic] module is supported".to_string());
+    }
 
     for (name, jar) in jars {
         let jar = match jar {
@@ -518,6 +571,16 @@ fn validate_jars(config: &toml::Value) {
                 ));
             }
         }
+
+        // Validate ultrasonic reference against a configured module
+        if let Some(toml::Value::String(module)) = jar.get("ultrasonic") {
+            if !ultrasonics.is_empty() && !ultrasonics.contains(module) {
+                errors.push(format!(
+                    "[jar.{}] references unknown ultrasonic '{}'",
+                    name, module
+                ));
+            }
+        }
     }
 
     if !errors.is_empty() {
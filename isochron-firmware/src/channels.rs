@@ -7,9 +7,14 @@ use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::channel::Channel;
 use embassy_sync::signal::Signal;
 
-use isochron_core::scheduler::{HeaterCommand, MotorCommand};
-use isochron_core::state::Event;
-use isochron_protocol::InputEvent;
+use isochron_core::config::{ProfileConfig, MAX_HEATERS};
+use isochron_core::motion::{Axis, HomingMove};
+use isochron_core::safety::{Breadcrumb, RecoveryNotice};
+use isochron_core::scheduler::{AccessoryCommand, HeaterCommand, MotorCommand};
+use isochron_core::state::{DriverFaultKind, Event};
+use isochron_core::traits::SensorRaw;
+use isochron_core::util::{CancelToken, TemperatureC10};
+use isochron_protocol::{InputEvent, PicoMessage};
 
 /// Channel capacity for input events from display
 const INPUT_CHANNEL_SIZE: usize = 8;
@@ -17,6 +22,9 @@ const INPUT_CHANNEL_SIZE: usize = 8;
 /// Channel capacity for state events
 const EVENT_CHANNEL_SIZE: usize = 8;
 
+/// Channel capacity for axis reports
+const AXIS_REPORT_SIZE: usize = 4;
+
 /// Input events from the V0 Display (encoder rotation, button presses)
 pub static INPUT_CHANNEL: Channel<CriticalSectionRawMutex, InputEvent, INPUT_CHANNEL_SIZE> =
     Channel::new();
@@ -28,31 +36,136 @@ pub static EVENT_CHANNEL: Channel<CriticalSectionRawMutex, Event, EVENT_CHANNEL_
 /// Signal that a screen update is ready to be sent
 pub static SCREEN_UPDATE: Signal<CriticalSectionRawMutex, ()> = Signal::new();
 
+/// Latest breadcrumb (updated by controller on state changes)
+///
+/// The watchdog task writes it to a watchdog scratch register, which
+/// survives a watchdog reset.
+pub static BREADCRUMB: Signal<CriticalSectionRawMutex, Breadcrumb> = Signal::new();
+
+/// Signal that the controller finished a tick (watchdog task feeds on it)
+pub static CONTROLLER_TICK: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Set at boot if the board was reset by the watchdog
+pub static RECOVERY_NOTICE: Signal<CriticalSectionRawMutex, RecoveryNotice> = Signal::new();
+
 /// Signal that a heartbeat (PING) was received from display
 pub static HEARTBEAT_RECEIVED: Signal<CriticalSectionRawMutex, ()> = Signal::new();
 
 /// Motor command signal (updated by controller)
 pub static MOTOR_CMD: Signal<CriticalSectionRawMutex, MotorCommand> = Signal::new();
 
-/// Heater command signal (updated by controller)
-pub static HEATER_CMD: Signal<CriticalSectionRawMutex, HeaterCommand> = Signal::new();
+/// Basket orientation request in degrees (updated by controller)
+/// The stepper task turns the basket forward to this angle, then
+/// signals `ORIENT_DONE`. A motor command cancels the move.
+pub static ORIENT_CMD: Signal<CriticalSectionRawMutex, u16> = Signal::new();
+
+/// End of a move from `ORIENT_CMD` (updated by stepper task)
+/// True once the basket reached the angle, false if a motor command
+/// stopped it first.
+pub static ORIENT_DONE: Signal<CriticalSectionRawMutex, bool> = Signal::new();
+
+/// Motion request for a Z or X axis (from controller to axis task)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AxisCommand {
+    /// Homing motion: stop, or move until the next command
+    Homing(HomingMove),
+    /// Move by this many mm at homing speed (manual homing)
+    Jog(i32),
+}
+
+/// Endstop level and travel of a Z or X axis (from axis task to controller)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AxisReport {
+    /// Reporting axis
+    pub axis: Axis,
+    /// Raw electrical level of the endstop pin
+    pub endstop_high: bool,
+    /// Distance moved since the previous report (µm)
+    pub moved_um: u32,
+}
+
+/// Axis motion requests, by `Axis as usize` (updated by controller)
+pub static AXIS_CMD: [Signal<CriticalSectionRawMutex, AxisCommand>; 2] =
+    [const { Signal::new() }; 2];
+
+/// Axis reports (from axis tasks, drained by the controller every tick)
+pub static AXIS_REPORT: Channel<CriticalSectionRawMutex, AxisReport, AXIS_REPORT_SIZE> =
+    Channel::new();
+
+/// Basket stepper speed in RPM (updated by stepper task)
+/// Lets the TMC task keep run current during slow spins.
+pub static STEPPER_RPM: Signal<CriticalSectionRawMutex, u16> = Signal::new();
+
+/// Basket stepper speed changed (updated by stepper task)
+/// Opens the stall monitor's settle window while the motor accelerates.
+pub static STALL_SPEED_CHANGE: Signal<CriticalSectionRawMutex, ()> = Signal::new();
 
-/// Temperature reading signal (updated by heater task)
-/// Value is temperature in 0.1°C units (e.g., 450 = 45.0°C), or None for sensor fault
-pub static TEMP_READING: Signal<CriticalSectionRawMutex, Option<i16>> = Signal::new();
+/// Quiet mode switched on or off (updated by controller)
+/// Lets the TMC task force StealthChop while quiet.
+pub static QUIET_MODE: Signal<CriticalSectionRawMutex, bool> = Signal::new();
+
+/// Heater command signals, by heater index (updated by controller)
+///
+/// Ignored by the heater task while autotuning; the autotune relay owns
+/// the heater until it completes, fails or is cancelled.
+pub static HEATER_CMD: [Signal<CriticalSectionRawMutex, HeaterCommand>; MAX_HEATERS] =
+    [const { Signal::new() }; MAX_HEATERS];
+
+/// Ultrasonic module command (updated by controller)
+pub static ULTRASONIC_CMD: Signal<CriticalSectionRawMutex, AccessoryCommand> = Signal::new();
+
+/// Temperature reading signals, by heater index (updated by heater tasks)
+/// None signals a sensor fault
+pub static TEMP_READING: [Signal<CriticalSectionRawMutex, Option<TemperatureC10>>; MAX_HEATERS] =
+    [const { Signal::new() }; MAX_HEATERS];
+
+/// Heater element states, by heater index (updated by heater tasks)
+/// True while the element is switched on, for thermal runaway detection
+pub static HEATER_OUTPUT: [Signal<CriticalSectionRawMutex, bool>; MAX_HEATERS] =
+    [const { Signal::new() }; MAX_HEATERS];
+
+/// Raw thermistor values behind each reading (updated by heater 0's task)
+/// Shown on the diagnostics screen
+pub static SENSOR_RAW: Signal<CriticalSectionRawMutex, SensorRaw> = Signal::new();
+
+/// Lid interlock signal (updated by lid task)
+/// True while the lid is open
+pub static LID_OPEN: Signal<CriticalSectionRawMutex, bool> = Signal::new();
 
 /// Motor stall signal (updated by TMC monitoring task)
 /// True if motor stall detected via StallGuard
 pub static MOTOR_STALL: Signal<CriticalSectionRawMutex, bool> = Signal::new();
 
+/// Stepper driver fault signal (updated by TMC task)
+/// Some when DRV_STATUS reports over-temperature shutdown or a phase short
+pub static DRIVER_FAULT: Signal<CriticalSectionRawMutex, Option<DriverFaultKind>> = Signal::new();
+
+/// StallGuard reading signal (updated by TMC task)
+/// Latest SG_RESULT register reading, polled every tick for imbalance
+/// detection
+pub static STALLGUARD_READING: Signal<CriticalSectionRawMutex, u16> = Signal::new();
+
+/// Scheduler state request from the host (from display RX task)
+pub static SCHEDULER_STATE_REQUEST: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Scheduler state to send to the host (from controller)
+/// Sent on the telemetry channel by the display TX task
+pub static SCHEDULER_STATE: Signal<CriticalSectionRawMutex, PicoMessage<'static>> = Signal::new();
+
+/// Soft reset request (from display RX task)
+/// The controller task only honours it while idle.
+pub static SOFT_RESET_REQUEST: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
 /// Autotune command types
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AutotuneCommand {
     /// Start autotune with target temperature (°C × 10)
+    ///
+    /// Cancellation goes through [`OPERATION_CANCEL`].
     Start { target_x10: i16 },
-    /// Cancel ongoing autotune
-    Cancel,
 }
 
 /// Autotune status updates
@@ -92,6 +205,12 @@ pub enum AutotuneFailure {
 /// Autotune command signal (from controller to heater task)
 pub static AUTOTUNE_CMD: Signal<CriticalSectionRawMutex, AutotuneCommand> = Signal::new();
 
+/// Cancellation for long operations (autotune, homing)
+///
+/// The controller resets it before starting an operation and cancels it on
+/// abort; the task running the operation polls it and shuts down cleanly.
+pub static OPERATION_CANCEL: CancelToken = CancelToken::new();
+
 /// Autotune status signal (from heater task to controller)
 pub static AUTOTUNE_STATUS: Signal<CriticalSectionRawMutex, AutotuneStatus> = Signal::new();
 
@@ -112,3 +231,19 @@ pub struct CalibrationSaveRequest {
 /// Calibration save request signal (from controller to calibration task)
 pub static CALIBRATION_SAVE: Signal<CriticalSectionRawMutex, CalibrationSaveRequest> =
     Signal::new();
+
+/// Calibration save result signal (from calibration task to controller)
+/// True if the calibration was written to flash
+pub static CALIBRATION_SAVED: Signal<CriticalSectionRawMutex, bool> = Signal::new();
+
+/// Confirmed profile edit to save
+#[derive(Debug, Clone)]
+pub struct ProfileSaveRequest {
+    /// Index of the profile in the configuration
+    pub profile_index: u8,
+    /// Profile with the user's changes
+    pub profile: ProfileConfig,
+}
+
+/// Profile save request signal (from controller to calibration task)
+pub static PROFILE_SAVE: Signal<CriticalSectionRawMutex, ProfileSaveRequest> = Signal::new();
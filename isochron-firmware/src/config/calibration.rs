@@ -3,14 +3,22 @@
 //! Loads and saves PID calibration data to flash storage.
 
 use defmt::*;
+use embassy_time::Timer;
 
 use isochron_core::config::{CalibrationData, HeaterCalibration};
+use isochron_core::util::{retry_async, Backoff};
 use isochron_hal_rp2040::flash::{FlashError, FlashStorage, StorageKey};
 use isochron_hal_rp2040::FlashStorageTrait;
 
 /// Maximum serialized calibration size
 const MAX_CALIBRATION_SIZE: usize = 256;
 
+/// Attempts for a flash write before reporting failure
+const WRITE_ATTEMPTS: u8 = 3;
+
+/// Backoff between failed flash writes
+const WRITE_BACKOFF: Backoff = Backoff::new(20, 100);
+
 /// Calibration persistence errors
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -100,10 +108,14 @@ pub async fn save_calibration(
 
     debug!("Saving {} bytes of calibration to flash", bytes.len());
 
-    storage
-        .write(StorageKey::PidCalibration, bytes)
-        .await
-        .map_err(CalibrationError::Flash)?;
+    retry_async(
+        WRITE_ATTEMPTS,
+        WRITE_BACKOFF,
+        async |ms| Timer::after_millis(ms as u64).await,
+        async || storage.write(StorageKey::PidCalibration, bytes).await,
+    )
+    .await
+    .map_err(CalibrationError::Flash)?;
 
     info!("Saved PID calibration to flash");
     log_calibration_summary(data);
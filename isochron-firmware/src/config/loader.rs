@@ -2,16 +2,35 @@
 //!
 //! Loads machine configuration from flash storage.
 //! Falls back to embedded defaults if flash is empty.
+//!
+//! Binary configs are stored behind a checksum header that records the
+//! algorithm used, so blobs written with either CRC16 or CRC32 stay
+//! readable when the default changes. Inside the checksummed payload, a
+//! schema header records `CONFIG_SCHEMA_VERSION` so a config from other
+//! firmware is migrated or rejected before it is deserialized.
+//!
+//! The binary config lives in one of two flash slots (A/B); the one the
+//! active-slot pointer names is loaded. Writers stage a new config in the
+//! inactive slot and only switch the pointer once it reads back, so an
+//! interrupted or bad update leaves the old config in place.
+//!
+//! Reads that fail in the flash itself (e.g. while the supply recovers
+//! from a brown-out) are retried a few times before the stored config is
+//! given up on; a config that is simply absent is not retried. The same
+//! goes for the active-slot pointer, which is never guessed when it can't
+//! be read.
 
 extern crate alloc;
 
 use core::str;
 use defmt::*;
 
-use isochron_core::config::MachineConfig;
-use isochron_hal_rp2040::flash::{FlashError, FlashStorage, StorageKey};
-// Import the FlashStorage trait to bring methods into scope
-use isochron_hal_rp2040::FlashStorageTrait;
+use embassy_time::Timer;
+use isochron_core::config::{read_schema_header, MachineConfig, SchemaError, SCHEMA_HEADER_LEN};
+use isochron_hal_rp2040::flash::{
+    active_config_slot, open_blob, read_with_retry, ConfigSlot, FlashError, FlashStorage,
+    StorageKey, BLOB_HEADER_LEN,
+};
 
 use super::toml::parse_config;
 
@@ -21,6 +40,12 @@ const MAX_CONFIG_SIZE: usize = 2048;
 /// Maximum TOML config size
 const MAX_TOML_SIZE: usize = 8192;
 
+/// Attempts at reading a stored config
+const READ_ATTEMPTS: u8 = 3;
+
+/// Delay between config read attempts (ms)
+const READ_RETRY_MS: u64 = 20;
+
 /// Configuration persistence errors
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -33,8 +58,16 @@ pub enum ConfigError {
     TomlParse,
     /// Invalid UTF-8 in TOML data
     InvalidUtf8,
-    /// Config version mismatch
-    VersionMismatch,
+    /// Config schema version rejected
+    VersionMismatch(SchemaError),
+}
+
+impl ConfigError {
+    /// Whether the stored config could not be read, as opposed to being
+    /// absent or invalid
+    pub fn is_read_error(&self) -> bool {
+        matches!(self, ConfigError::Flash(e) if e.is_retryable())
+    }
 }
 
 impl From<FlashError> for ConfigError {
@@ -43,6 +76,12 @@ impl From<FlashError> for ConfigError {
     }
 }
 
+impl From<SchemaError> for ConfigError {
+    fn from(e: SchemaError) -> Self {
+        ConfigError::VersionMismatch(e)
+    }
+}
+
 /// Configuration persistence manager
 ///
 /// Handles loading machine configuration from flash storage.
@@ -68,25 +107,44 @@ impl<'d> ConfigPersistence<'d> {
     ///
     /// Tries to load TOML config first, falls back to binary postcard format.
     /// Returns the loaded config, or an error if not found or invalid.
+    /// A TOML config that could not be read is reported as a read error
+    /// rather than hidden behind a missing binary config.
     pub async fn load(&mut self) -> Result<MachineConfig, ConfigError> {
         info!("Loading configuration from flash...");
 
         // Try TOML first
-        match self.load_toml().await {
+        let toml_error = match self.load_toml().await {
             Ok(config) => {
                 info!("Loaded configuration from TOML");
                 return Ok(config);
             }
             Err(ConfigError::Flash(FlashError::NotFound)) => {
                 debug!("No TOML config found, trying binary format");
+                None
             }
             Err(e) => {
                 warn!("Failed to load TOML config: {:?}, trying binary", e);
+                Some(e)
             }
-        }
+        };
 
         // Fall back to binary postcard format
-        self.load_binary().await
+        match (self.load_binary().await, toml_error) {
+            (Err(ConfigError::Flash(FlashError::NotFound)), Some(e)) if e.is_read_error() => Err(e),
+            (result, _) => result,
+        }
+    }
+
+    /// Read a stored value, retrying transient flash failures
+    async fn read(&mut self, key: StorageKey, buffer: &mut [u8]) -> Result<usize, FlashError> {
+        read_with_retry(&mut self.storage, key, buffer, READ_ATTEMPTS, retry_wait).await
+    }
+
+    /// Read the active-slot pointer, retrying transient flash failures
+    async fn active_slot(&mut self) -> Result<ConfigSlot, FlashError> {
+        active_config_slot(&mut self.storage, READ_ATTEMPTS, retry_wait)
+            .await
+            .inspect_err(|e| warn!("Active config slot unreadable: {:?}", e))
     }
 
     /// Load configuration from TOML format
@@ -94,7 +152,6 @@ impl<'d> ConfigPersistence<'d> {
         // Read raw TOML data from flash
         let mut buffer = [0u8; MAX_TOML_SIZE];
         let len = self
-            .storage
             .read(StorageKey::MachineConfigToml, &mut buffer)
             .await?;
 
@@ -103,7 +160,7 @@ impl<'d> ConfigPersistence<'d> {
         // Convert to string
         let toml_str = str::from_utf8(&buffer[..len]).map_err(|_| ConfigError::InvalidUtf8)?;
 
-        // Parse TOML
+        // Parse TOML (checks the schema version)
         let config = parse_config(toml_str).map_err(|e| {
             warn!("TOML parse error: {:?}", defmt::Debug2Format(&e));
             ConfigError::TomlParse
@@ -115,38 +172,52 @@ impl<'d> ConfigPersistence<'d> {
 
     /// Load configuration from binary postcard format
     async fn load_binary(&mut self) -> Result<MachineConfig, ConfigError> {
-        // Read raw data from flash
-        let mut buffer = [0u8; MAX_CONFIG_SIZE];
-        let len = self
-            .storage
-            .read(StorageKey::MachineConfig, &mut buffer)
-            .await?;
-
-        debug!("Read {} bytes of binary config from flash", len);
+        // Read raw data from the active slot
+        let slot = self.active_slot().await?;
+        let mut buffer = [0u8; BLOB_HEADER_LEN + SCHEMA_HEADER_LEN + MAX_CONFIG_SIZE];
+        let len = self.read(slot.key(), &mut buffer).await?;
 
-        // Deserialize with postcard
-        let config: MachineConfig =
-            postcard::from_bytes(&buffer[..len]).map_err(|_| ConfigError::Deserialize)?;
+        debug!("Read {} bytes of binary config from slot {:?}", len, slot);
 
-        // Version check
-        if config.version != 1 {
-            warn!(
-                "Config version mismatch: found {}, expected 1",
-                config.version
-            );
-            return Err(ConfigError::VersionMismatch);
-        }
+        let (checksum, payload) = open_blob(&buffer[..len]).inspect_err(|e| {
+            warn!("Binary config failed integrity check: {:?}", e);
+        })?;
+        debug!("Binary config checksum {:?} OK", checksum);
 
+        let config = decode_binary(payload)?;
         log_config_summary(&config);
         Ok(config)
     }
 }
 
+/// Wait between config read attempts
+async fn retry_wait() {
+    warn!("Config read failed, retrying in {} ms", READ_RETRY_MS);
+    Timer::after_millis(READ_RETRY_MS).await
+}
+
+/// Decode a binary config payload (after the checksum header)
+fn decode_binary(payload: &[u8]) -> Result<MachineConfig, ConfigError> {
+    // Schema check before deserializing: a different layout would
+    // either fail to parse or, worse, parse into the wrong fields
+    let (version, payload) = read_schema_header(payload).inspect_err(|e| {
+        warn!("Binary config schema rejected: {:?}", e);
+    })?;
+
+    // Deserialize with postcard
+    let mut config: MachineConfig =
+        postcard::from_bytes(payload).map_err(|_| ConfigError::Deserialize)?;
+    config.version = version;
+    config.migrate_schema()?;
+    Ok(config)
+}
+
 /// Log a summary of the loaded configuration
 fn log_config_summary(config: &MachineConfig) {
     info!("Configuration loaded successfully");
     debug!("  {} steppers", config.steppers.len());
     debug!("  {} TMC2209 drivers", config.tmc2209s.len());
+    debug!("  {} A4988 drivers", config.a4988s.len());
     debug!("  {} heaters", config.heaters.len());
     debug!("  {} jars", config.jars.len());
     debug!("  {} profiles", config.profiles.len());
@@ -5,8 +5,10 @@
 
 pub mod calibration;
 pub mod loader;
+pub mod profiles;
 pub mod toml;
 
 pub use calibration::load_calibration;
 pub use loader::ConfigPersistence;
+pub use profiles::load_profile_overrides;
 pub use toml::parse_config;
@@ -0,0 +1,125 @@
+//! Profile override persistence
+//!
+//! Loads and saves user-adjusted profile values to flash storage.
+
+use defmt::*;
+use embassy_time::Timer;
+
+use isochron_core::config::{ProfileConfig, ProfileOverrides};
+use isochron_core::util::{retry_async, Backoff};
+use isochron_hal_rp2040::flash::{FlashError, FlashStorage, StorageKey};
+use isochron_hal_rp2040::FlashStorageTrait;
+
+/// Maximum serialized profile override size
+const MAX_OVERRIDES_SIZE: usize = 512;
+
+/// Attempts for a flash write before reporting failure
+const WRITE_ATTEMPTS: u8 = 3;
+
+/// Backoff between failed flash writes
+const WRITE_BACKOFF: Backoff = Backoff::new(20, 100);
+
+/// Profile override persistence errors
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProfileStoreError {
+    /// Flash operation failed
+    Flash(FlashError),
+    /// Deserialization failed
+    Deserialize,
+    /// Serialization failed
+    Serialize,
+    /// CRC check failed
+    CrcMismatch,
+    /// Invalid magic or version
+    InvalidFormat,
+    /// No free override slot
+    Full,
+}
+
+impl From<FlashError> for ProfileStoreError {
+    fn from(e: FlashError) -> Self {
+        ProfileStoreError::Flash(e)
+    }
+}
+
+/// Load profile overrides from flash
+///
+/// Returns the stored overrides, or empty overrides if none are stored
+/// or the data is invalid.
+pub async fn load_profile_overrides(storage: &mut FlashStorage<'_>) -> ProfileOverrides {
+    match load_profile_overrides_inner(storage).await {
+        Ok(data) => {
+            info!("Loaded profile overrides from flash");
+            data
+        }
+        Err(ProfileStoreError::Flash(FlashError::NotFound)) => {
+            debug!("No profile overrides in flash");
+            ProfileOverrides::new()
+        }
+        Err(e) => {
+            warn!("Failed to load profile overrides: {:?}, ignoring", e);
+            ProfileOverrides::new()
+        }
+    }
+}
+
+/// Inner function that returns errors
+async fn load_profile_overrides_inner(
+    storage: &mut FlashStorage<'_>,
+) -> Result<ProfileOverrides, ProfileStoreError> {
+    let mut buffer = [0u8; MAX_OVERRIDES_SIZE];
+    let len = storage
+        .read(StorageKey::ProfileOverrides, &mut buffer)
+        .await?;
+
+    debug!("Read {} bytes of profile overrides from flash", len);
+
+    let data: ProfileOverrides =
+        postcard::from_bytes(&buffer[..len]).map_err(|_| ProfileStoreError::Deserialize)?;
+
+    if !data.is_valid() {
+        return Err(ProfileStoreError::InvalidFormat);
+    }
+
+    if !data.verify_crc() {
+        warn!("Profile override CRC mismatch");
+        return Err(ProfileStoreError::CrcMismatch);
+    }
+
+    Ok(data)
+}
+
+/// Save an edited profile
+///
+/// Loads existing overrides, replaces the entry for `profile_index` and
+/// writes them back. Only call this once the user has confirmed the save;
+/// declined edits must leave flash untouched.
+pub async fn save_profile(
+    storage: &mut FlashStorage<'_>,
+    profile_index: u8,
+    profile: &ProfileConfig,
+) -> Result<(), ProfileStoreError> {
+    let mut data = load_profile_overrides(storage).await;
+    if !data.set(profile_index, profile) {
+        return Err(ProfileStoreError::Full);
+    }
+    data.update_crc();
+
+    let mut buffer = [0u8; MAX_OVERRIDES_SIZE];
+    let bytes = postcard::to_slice(&data, &mut buffer).map_err(|_| ProfileStoreError::Serialize)?;
+
+    debug!("Saving {} bytes of profile overrides to flash", bytes.len());
+
+    retry_async(
+        WRITE_ATTEMPTS,
+        WRITE_BACKOFF,
+        async |ms| Timer::after_millis(ms as u64).await,
+        async || storage.write(StorageKey::ProfileOverrides, bytes).await,
+    )
+    .await
+    .map_err(ProfileStoreError::Flash)?;
+
+    info!("Saved profile {} to flash", profile_index);
+    Ok(())
+}
@@ -20,11 +20,18 @@ use alloc::vec::Vec;
 use heapless::String as HString;
 
 use isochron_core::config::{
-    DisplayHwConfig, HeaterConfig, HeaterControlMode, HeaterHwConfig, JarConfig, MachineConfig,
-    PinConfig, ProfileConfig, ProfileType, ProgramConfig, ProgramStep, SensorType, StepperHwConfig,
-    Tmc2209HwConfig, UiConfig, MAX_LABEL_LEN,
+    thermistor_table_valid, A4988HwConfig, BetaConfig, Button, DisplayHwConfig, HeaterConfig,
+    HeaterControlMode, HeaterHwConfig, HomingOrder, HomingType, JarConfig, KeyAction, LidConfig,
+    LinkConfig, MachineConfig, PinConfig, ProfileConfig, ProfileType, ProgramConfig, ProgramStep,
+    SensorFaultPolicy, SensorType, StateCategory, SteinhartHartConfig, StepDirChip,
+    StepperHwConfig, StopBehavior, ThermalRunawayConfig, ThermistorTable, Tmc2209HwConfig,
+    UiConfig, UltrasonicHwConfig, MAX_ADC_SAMPLES, MAX_LABEL_LEN, MAX_PROGRAMS,
 };
-use isochron_core::scheduler::{DirectionMode, SpinOffConfig};
+use isochron_core::motion::RampCurve;
+use isochron_core::scheduler::{
+    profile_segments, BalanceConfig, DirectionMode, PrimeConfig, SoakConfig, SpinOffConfig,
+};
+use isochron_protocol::LinkTestPattern;
 
 /// Parse error
 #[derive(Debug, Clone)]
@@ -38,6 +45,8 @@ pub enum ParseError {
     TooManyItems,
     /// Invalid pin string
     InvalidPin,
+    /// Config written for an unsupported schema version
+    UnsupportedVersion,
 }
 
 /// Current parsing context
@@ -45,8 +54,10 @@ pub enum ParseError {
 #[allow(dead_code)] // ProfileSpinoff name field reserved for future use
 enum Section {
     Root,
+    Machine,
     Stepper(HString<MAX_LABEL_LEN>),
     Tmc2209(HString<MAX_LABEL_LEN>),
+    A4988(HString<MAX_LABEL_LEN>),
     Heater(HString<MAX_LABEL_LEN>),
     HeaterHw(HString<MAX_LABEL_LEN>),
     Jar(HString<MAX_LABEL_LEN>),
@@ -55,6 +66,9 @@ enum Section {
     Program(HString<MAX_LABEL_LEN>),
     Display,
     Ui,
+    Link,
+    Lid,
+    Ultrasonic(HString<MAX_LABEL_LEN>),
 }
 
 /// Parse TOML configuration into MachineConfig
@@ -65,12 +79,16 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
     // Temporary storage for current section being built
     let mut current_stepper: Option<StepperHwConfig> = None;
     let mut current_tmc: Option<Tmc2209HwConfig> = None;
+    let mut current_a4988: Option<A4988HwConfig> = None;
     let mut current_heater: Option<HeaterConfig> = None;
     let mut current_heater_hw: Option<HeaterHwConfig> = None;
     let mut current_jar: Option<JarConfig> = None;
     let mut current_profile: Option<ProfileConfig> = None;
     let mut current_spinoff: Option<SpinOffConfig> = None;
     let mut current_program: Option<ProgramConfig> = None;
+    // Section key of each program, in the order they are pushed
+    let mut program_keys: heapless::Vec<HString<MAX_LABEL_LEN>, MAX_PROGRAMS> =
+        heapless::Vec::new();
 
     for line in input.lines() {
         let line = line.trim();
@@ -88,6 +106,7 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
                 &mut config,
                 &mut current_stepper,
                 &mut current_tmc,
+                &mut current_a4988,
                 &mut current_heater,
                 &mut current_heater_hw,
                 &mut current_jar,
@@ -111,6 +130,12 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
                     t.stepper_name = name.clone();
                     current_tmc = Some(t);
                 }
+                Section::A4988(name) => {
+                    current_a4988 = Some(A4988HwConfig {
+                        stepper_name: name.clone(),
+                        ..Default::default()
+                    });
+                }
                 Section::Heater(name) => {
                     let mut h = HeaterConfig::default();
                     h.name = name.clone();
@@ -137,12 +162,15 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
                         lift_mm: 20,
                         rpm: 150,
                         time_s: 10,
+                        pre_spinoff_delay_s: 0,
                     });
                 }
                 Section::Program(name) => {
                     let mut p = ProgramConfig::default();
                     p.label = name.clone();
                     current_program = Some(p);
+                    // Overflow is caught when the program is pushed
+                    let _ = program_keys.push(name.clone());
                 }
                 Section::Display => {
                     config.display = DisplayHwConfig::default();
@@ -150,7 +178,10 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
                 Section::Ui => {
                     config.ui = UiConfig::default();
                 }
-                Section::Root => {}
+                Section::Link => {
+                    config.link = LinkConfig::default();
+                }
+                Section::Machine | Section::Lid | Section::Ultrasonic(_) | Section::Root => {}
             }
             continue;
         }
@@ -164,6 +195,7 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
                 &mut config,
                 &mut current_stepper,
                 &mut current_tmc,
+                &mut current_a4988,
                 &mut current_heater,
                 &mut current_heater_hw,
                 &mut current_jar,
@@ -180,6 +212,7 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
         &mut config,
         &mut current_stepper,
         &mut current_tmc,
+        &mut current_a4988,
         &mut current_heater,
         &mut current_heater_hw,
         &mut current_jar,
@@ -188,9 +221,141 @@ pub fn parse_config(input: &str) -> Result<MachineConfig, ParseError> {
         &mut current_program,
     )?;
 
+    validate_spinoff_rpm(&config)?;
+    validate_profile_times(&config)?;
+    validate_onewire_pins(&config)?;
+    validate_steinhart_hart(&config)?;
+    validate_x_move_clearance(&config)?;
+    validate_ultrasonic_refs(&config)?;
+    resolve_autostart_program(&mut config, &program_keys);
+
+    // Reject configs written for another schema; older ones are migrated
+    config
+        .migrate_schema()
+        .map_err(|_| ParseError::UnsupportedVersion)?;
+
     Ok(config)
 }
 
+/// Turn an `autostart_program` naming a program's key into its label
+///
+/// Like the build-time check, the program may be named by its section
+/// key or its label; the controller looks programs up by label.
+fn resolve_autostart_program(config: &mut MachineConfig, program_keys: &[HString<MAX_LABEL_LEN>]) {
+    let Some(name) = config.autostart_program.as_ref() else {
+        return;
+    };
+    if config.programs.iter().any(|p| p.label == *name) {
+        return;
+    }
+    let program = program_keys
+        .iter()
+        .position(|key| key == name)
+        .and_then(|i| config.programs.get(i));
+    if let Some(program) = program {
+        config.autostart_program = Some(program.label.clone());
+    }
+}
+
+/// Reject profiles whose spin-off RPM exceeds the machine limit
+///
+/// The scheduler clamps at runtime as well; this catches typos early.
+fn validate_spinoff_rpm(config: &MachineConfig) -> Result<(), ParseError> {
+    let Some(max_rpm) = config.max_spinoff_rpm else {
+        return Ok(());
+    };
+    let too_fast = config
+        .profiles
+        .iter()
+        .filter_map(|p| p.spinoff)
+        .any(|s| s.rpm > max_rpm);
+    if too_fast {
+        return Err(ParseError::InvalidValue);
+    }
+    Ok(())
+}
+
+/// Reject profiles that can't be split into runnable segments
+///
+/// Covers a zero `time_s` as well as alternate iterations too short to
+/// reverse, which the scheduler would otherwise refuse at step start.
+fn validate_profile_times(config: &MachineConfig) -> Result<(), ParseError> {
+    if config.profiles.iter().all(|p| profile_segments(p).is_ok()) {
+        Ok(())
+    } else {
+        Err(ParseError::InvalidValue)
+    }
+}
+
+/// Reject a travel height that doesn't clear `x_move_clearance_z`
+///
+/// Every X move travels at that height, so one below the clearance would
+/// drag the basket through the jar rims.
+fn validate_x_move_clearance(config: &MachineConfig) -> Result<(), ParseError> {
+    match (config.travel_z(), config.x_move_clearance_z) {
+        // Z is measured down from the top, so a larger value is lower
+        (Some(travel_z), Some(clearance_z)) if travel_z > clearance_z => {
+            Err(ParseError::InvalidValue)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reject jars naming an ultrasonic module other than the configured one
+///
+/// Without an `[ultrasonic]` section the references are left unwired.
+fn validate_ultrasonic_refs(config: &MachineConfig) -> Result<(), ParseError> {
+    let Some(module) = &config.ultrasonic else {
+        return Ok(());
+    };
+    let unknown = config
+        .jars
+        .iter()
+        .filter_map(|j| j.ultrasonic.as_ref())
+        .any(|name| *name != module.name);
+    if unknown {
+        return Err(ParseError::InvalidValue);
+    }
+    Ok(())
+}
+
+/// Reject 1-Wire sensors without a data pin of their own
+///
+/// A missing or clashing pin would otherwise only show up at boot as a
+/// heater stuck in a sensor fault.
+fn validate_onewire_pins(config: &MachineConfig) -> Result<(), ParseError> {
+    let unwired = config
+        .heater_hw
+        .iter()
+        .filter(|hw| hw.sensor_type.is_onewire())
+        .any(|hw| match hw.onewire_pin {
+            None => true,
+            Some(pin) => {
+                pin.pin == hw.heater_pin.pin || hw.enable_pin.is_some_and(|e| e.pin == pin.pin)
+            }
+        });
+    if unwired {
+        return Err(ParseError::InvalidValue);
+    }
+    Ok(())
+}
+
+/// Reject a Steinhart-Hart model missing one of its coefficients
+///
+/// Unset coefficients default to zero, which would silently turn a typo
+/// or a forgotten key into a wildly wrong temperature curve.
+fn validate_steinhart_hart(config: &MachineConfig) -> Result<(), ParseError> {
+    let partial = config
+        .heater_hw
+        .iter()
+        .filter_map(|hw| hw.steinhart_hart)
+        .any(|sh| sh.a == 0.0 || sh.b == 0.0 || sh.c == 0.0);
+    if partial {
+        return Err(ParseError::InvalidValue);
+    }
+    Ok(())
+}
+
 /// Parse section header like "stepper basket", "stepper.basket" or "profile.clean.spinoff"
 fn parse_section_header(header: &str) -> Result<Section, ParseError> {
     let header = header.trim();
@@ -214,11 +379,13 @@ fn parse_section_header(header: &str) -> Result<Section, ParseError> {
             return match section_type {
                 "stepper" => Ok(Section::Stepper(name)),
                 "tmc2209" => Ok(Section::Tmc2209(name)),
+                "a4988" => Ok(Section::A4988(name)),
                 "heater" => Ok(Section::HeaterHw(name)),
                 "heater_control" => Ok(Section::Heater(name)),
                 "jar" => Ok(Section::Jar(name)),
                 "profile" => Ok(Section::Profile(name)),
                 "program" => Ok(Section::Program(name)),
+                "ultrasonic" => Ok(Section::Ultrasonic(name)),
                 _ => Err(ParseError::InvalidSection),
             };
         }
@@ -242,6 +409,11 @@ fn parse_section_header(header: &str) -> Result<Section, ParseError> {
             let name = HString::try_from(name).map_err(|_| ParseError::InvalidSection)?;
             Ok(Section::Tmc2209(name))
         }
+        "a4988" => {
+            let name = name.ok_or(ParseError::InvalidSection)?;
+            let name = HString::try_from(name).map_err(|_| ParseError::InvalidSection)?;
+            Ok(Section::A4988(name))
+        }
         "heater" => {
             let name = name.ok_or(ParseError::InvalidSection)?;
             let name = HString::try_from(name).map_err(|_| ParseError::InvalidSection)?;
@@ -269,8 +441,16 @@ fn parse_section_header(header: &str) -> Result<Section, ParseError> {
             let name = HString::try_from(name).map_err(|_| ParseError::InvalidSection)?;
             Ok(Section::Program(name))
         }
+        "ultrasonic" => {
+            let name = name.ok_or(ParseError::InvalidSection)?;
+            let name = HString::try_from(name).map_err(|_| ParseError::InvalidSection)?;
+            Ok(Section::Ultrasonic(name))
+        }
+        "machine" => Ok(Section::Machine),
         "display" => Ok(Section::Display),
         "ui" => Ok(Section::Ui),
+        "link" => Ok(Section::Link),
+        "lid" => Ok(Section::Lid),
         _ => Err(ParseError::InvalidSection),
     }
 }
@@ -381,6 +561,108 @@ fn parse_profile_type(value: &str) -> Result<ProfileType, ParseError> {
     }
 }
 
+/// Parse motor stop behavior
+fn parse_stop_behavior(value: &str) -> Result<StopBehavior, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "coast" | "Coast" => Ok(StopBehavior::Coast),
+        "brake" | "Brake" => Ok(StopBehavior::Brake),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
+/// Parse speed ramp curve
+fn parse_ramp_curve(value: &str) -> Result<RampCurve, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "linear" => Ok(RampCurve::Linear),
+        "smooth" => Ok(RampCurve::Smooth),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
+/// Parse step/dir driver chip
+fn parse_step_dir_chip(value: &str) -> Result<StepDirChip, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "a4988" | "A4988" => Ok(StepDirChip::A4988),
+        "drv8825" | "DRV8825" => Ok(StepDirChip::Drv8825),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
+/// Parse homing order
+fn parse_homing_order(value: &str) -> Result<HomingOrder, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "z_then_x" => Ok(HomingOrder::ZThenX),
+        "x_then_z" => Ok(HomingOrder::XThenZ),
+        "simultaneous" => Ok(HomingOrder::Simultaneous),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
+/// Parse homing type
+fn parse_homing_type(value: &str) -> Result<HomingType, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "endstop" => Ok(HomingType::Endstop),
+        "manual" => Ok(HomingType::Manual),
+        "none" => Ok(HomingType::None),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
+/// Parse a keymap key like "program_long_press"
+///
+/// Returns None for keys that aren't a `<category>_<button>` binding.
+fn parse_keymap_key(key: &str) -> Option<(StateCategory, Button)> {
+    // "_double_click" also ends in "_click", so check it first
+    let (category, button) = if let Some(c) = key.strip_suffix("_double_click") {
+        (c, Button::DoubleClick)
+    } else if let Some(c) = key.strip_suffix("_long_press") {
+        (c, Button::LongPress)
+    } else if let Some(c) = key.strip_suffix("_click") {
+        (c, Button::Click)
+    } else {
+        return None;
+    };
+    let category = match category {
+        "idle" => StateCategory::Idle,
+        "menu" => StateCategory::Menu,
+        "edit" => StateCategory::Edit,
+        "program" => StateCategory::Program,
+        "transition" => StateCategory::Transition,
+        "finished" => StateCategory::Finished,
+        "autotune" => StateCategory::Autotune,
+        _ => return None,
+    };
+    Some((category, button))
+}
+
+/// Parse a keymap action
+fn parse_key_action(value: &str) -> Result<KeyAction, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "ignore" => Ok(KeyAction::Ignore),
+        "select" => Ok(KeyAction::Select),
+        "back" => Ok(KeyAction::Back),
+        "abort" => Ok(KeyAction::Abort),
+        "quiet" => Ok(KeyAction::ToggleQuiet),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
+/// Parse a display link test pattern
+fn parse_link_test_pattern(value: &str) -> Result<LinkTestPattern, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "incrementing" => Ok(LinkTestPattern::Incrementing),
+        "checkerboard" => Ok(LinkTestPattern::Checkerboard),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
 /// Parse sensor type
 fn parse_sensor_type(value: &str) -> Result<SensorType, ParseError> {
     let value = parse_string(value)?;
@@ -388,10 +670,84 @@ fn parse_sensor_type(value: &str) -> Result<SensorType, ParseError> {
         "ntc100k" | "NTC100K" => Ok(SensorType::Ntc100k),
         "ntc10k" | "NTC10K" => Ok(SensorType::Ntc10k),
         "pt100" | "PT100" => Ok(SensorType::Pt100),
+        "i2c_tmp117" => Ok(SensorType::I2cTmp117),
+        "ds18b20" => Ok(SensorType::Ds18b20),
         _ => Err(ParseError::InvalidValue),
     }
 }
 
+/// Parse a finite floating-point value, e.g. a Steinhart-Hart coefficient
+fn parse_float(value: &str) -> Result<f32, ParseError> {
+    let f: f32 = value.trim().parse().map_err(|_| ParseError::InvalidValue)?;
+    if f.is_finite() {
+        Ok(f)
+    } else {
+        Err(ParseError::InvalidValue)
+    }
+}
+
+/// Parse a thermistor table like [[100000, 25], [40000, 45.5]]
+///
+/// Points are (resistance in ohms, temperature in °C). They may be listed
+/// from cold to hot or hot to cold, but must be monotonic: a table where
+/// resistance and temperature don't move in opposite directions
+/// throughout is rejected, as is one with fewer than two points.
+fn parse_temp_table(value: &str) -> Result<ThermistorTable, ParseError> {
+    let inner = value
+        .trim()
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .ok_or(ParseError::InvalidValue)?;
+
+    let mut table = ThermistorTable::new();
+    for point in inner.split(']') {
+        let point = point.trim().trim_start_matches(',').trim();
+        if point.is_empty() {
+            continue;
+        }
+        let (resistance, temp) = point
+            .strip_prefix('[')
+            .and_then(|p| p.split_once(','))
+            .ok_or(ParseError::InvalidValue)?;
+        let temp_x10 = parse_float(temp)? * 10.0;
+        if !(i16::MIN as f32..=i16::MAX as f32).contains(&temp_x10) {
+            return Err(ParseError::InvalidValue);
+        }
+        // Round to the nearest 0.1°C
+        let temp_x10 = if temp_x10 < 0.0 {
+            temp_x10 - 0.5
+        } else {
+            temp_x10 + 0.5
+        } as i16;
+        table
+            .push((parse_int(resistance.trim())?, temp_x10))
+            .map_err(|_| ParseError::TooManyItems)?;
+    }
+
+    // Stored hot to cold: decreasing resistance
+    if table.first().map(|p| p.0) < table.last().map(|p| p.0) {
+        table.reverse();
+    }
+    if !thermistor_table_valid(&table) {
+        return Err(ParseError::InvalidValue);
+    }
+    Ok(table)
+}
+
+/// Parse a 7-bit I2C address, decimal or "0x" hex
+fn parse_i2c_address(value: &str) -> Result<u8, ParseError> {
+    let address = match value.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).map_err(|_| ParseError::InvalidValue)?,
+        None => parse_int(value)?,
+    };
+    // 0x00-0x07 and 0x78-0x7F are reserved
+    if (0x08..=0x77).contains(&address) {
+        Ok(address)
+    } else {
+        Err(ParseError::InvalidValue)
+    }
+}
+
 /// Parse heater control mode
 fn parse_control_mode(value: &str) -> Result<HeaterControlMode, ParseError> {
     let value = parse_string(value)?;
@@ -402,6 +758,16 @@ fn parse_control_mode(value: &str) -> Result<HeaterControlMode, ParseError> {
     }
 }
 
+/// Parse heater sensor fault policy
+fn parse_fault_policy(value: &str) -> Result<SensorFaultPolicy, ParseError> {
+    let value = parse_string(value)?;
+    match value {
+        "abort" => Ok(SensorFaultPolicy::Abort),
+        "continue_unheated" => Ok(SensorFaultPolicy::ContinueUnheated),
+        _ => Err(ParseError::InvalidValue),
+    }
+}
+
 /// Parse PID coefficient value
 ///
 /// Accepts either:
@@ -423,6 +789,30 @@ fn parse_pid_value(value: &str) -> Result<i16, ParseError> {
     }
 }
 
+/// Parse a distance in mm with up to three decimals into µm
+///
+/// Parsed exactly as decimal text ("8.125" -> 8125), so no precision is
+/// lost to floating point. More than three decimals is rejected rather
+/// than rounded.
+fn parse_mm_as_um(value: &str) -> Result<u32, ParseError> {
+    let value = value.trim();
+    let (whole, frac) = value.split_once('.').unwrap_or((value, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::InvalidValue);
+    }
+
+    let whole: u32 = parse_int(whole)?;
+    let mut frac_um = 0u32;
+    for (i, digit) in frac.bytes().enumerate() {
+        frac_um += (digit - b'0') as u32 * [100, 10, 1][i];
+    }
+
+    whole
+        .checked_mul(1000)
+        .and_then(|um| um.checked_add(frac_um))
+        .ok_or(ParseError::InvalidValue)
+}
+
 /// Parse gear ratio string like "3:1"
 fn parse_gear_ratio(value: &str) -> Result<(u8, u8), ParseError> {
     let value = parse_string(value)?;
@@ -512,6 +902,7 @@ fn apply_value(
     config: &mut MachineConfig,
     current_stepper: &mut Option<StepperHwConfig>,
     current_tmc: &mut Option<Tmc2209HwConfig>,
+    current_a4988: &mut Option<A4988HwConfig>,
     current_heater: &mut Option<HeaterConfig>,
     current_heater_hw: &mut Option<HeaterHwConfig>,
     current_jar: &mut Option<JarConfig>,
@@ -529,12 +920,14 @@ fn apply_value(
                 "endstop_pin" => s.endstop_pin = Some(parse_pin(value)?),
                 "full_steps_per_rotation" => s.full_steps_per_rotation = parse_int(value)?,
                 "microsteps" => s.microsteps = parse_int(value)?,
-                "rotation_distance" => s.rotation_distance = parse_int(value)?,
+                "rotation_distance" => s.rotation_distance_um = parse_mm_as_um(value)?,
                 "gear_ratio" => {
                     let (num, den) = parse_gear_ratio(value)?;
                     s.gear_ratio_num = num;
                     s.gear_ratio_den = den;
                 }
+                "step_pulse_ns" => s.step_pulse_ns = Some(parse_int(value)?),
+                "stop_behavior" => s.stop_behavior = parse_stop_behavior(value)?,
                 // Position control (Klipper-style)
                 "position_min" => s.position_min = parse_int(value)?,
                 "position_max" => s.position_max = Some(parse_int(value)?),
@@ -542,6 +935,14 @@ fn apply_value(
                 "homing_speed" => s.homing_speed = Some(parse_int(value)?),
                 "homing_retract_dist" => s.homing_retract_dist = Some(parse_int(value)?),
                 "homing_positive_dir" => s.homing_positive_dir = Some(parse_bool(value)?),
+                "homing_timeout_s" => s.homing_timeout_s = Some(parse_int(value)?),
+                "backlash_steps" => s.backlash_steps = parse_int(value)?,
+                "homing_type" => s.homing_type = parse_homing_type(value)?,
+                // Speed ramp
+                "accel_rpm_per_s" => s.accel_rpm_per_s = parse_int(value)?,
+                "ramp_curve" => s.ramp_curve = parse_ramp_curve(value)?,
+                "min_rpm" => s.min_rpm = parse_int(value)?,
+                "max_rpm" => s.max_rpm = Some(parse_int(value)?),
                 _ => {} // Ignore unknown keys
             }
         }
@@ -575,7 +976,23 @@ fn apply_value(
                     }
                 }
                 "stealthchop" => t.stealthchop = parse_bool(value)?,
+                "sense_resistor" | "sense_resistor_mohm" => {
+                    // Support both decimal ohms and integer mΩ
+                    let mohm = if value.contains('.') {
+                        let ohms: f32 = value.parse().map_err(|_| ParseError::InvalidValue)?;
+                        (ohms * 1000.0 + 0.5) as u16
+                    } else {
+                        parse_int(value)?
+                    };
+                    if mohm == 0 {
+                        return Err(ParseError::InvalidValue);
+                    }
+                    t.rsense_mohm = Some(mohm);
+                }
                 "stallguard_threshold" | "stall_threshold" => t.stall_threshold = parse_int(value)?,
+                "imbalance_threshold" => t.imbalance_threshold = Some(parse_int(value)?),
+                "spin_hold" => t.spin_hold = parse_bool(value)?,
+                "stall_settle_ms" => t.stall_settle_ms = Some(parse_int(value)?),
                 "diag_pin" => {
                     let pin = parse_pin(value)?;
                     t.diag_pin = Some(pin.pin);
@@ -583,6 +1000,16 @@ fn apply_value(
                 _ => {}
             }
         }
+        Section::A4988(_) => {
+            let a = current_a4988.as_mut().ok_or(ParseError::InvalidSection)?;
+            match key {
+                "chip" => a.chip = parse_step_dir_chip(value)?,
+                "ms1_pin" => a.ms1_pin = Some(parse_pin(value)?),
+                "ms2_pin" => a.ms2_pin = Some(parse_pin(value)?),
+                "ms3_pin" => a.ms3_pin = Some(parse_pin(value)?),
+                _ => {}
+            }
+        }
         Section::Heater(_) => {
             let h = current_heater.as_mut().ok_or(ParseError::InvalidSection)?;
             match key {
@@ -592,6 +1019,18 @@ fn apply_value(
                 "pid_kp" => h.pid_kp_x100 = Some(parse_pid_value(value)?),
                 "pid_ki" => h.pid_ki_x100 = Some(parse_pid_value(value)?),
                 "pid_kd" => h.pid_kd_x100 = Some(parse_pid_value(value)?),
+                "max_heat_rate_c_per_min" => h.max_heat_rate_c_per_min = parse_int(value)?,
+                "fault_policy" => h.fault_policy = parse_fault_policy(value)?,
+                "runaway_window_s" => {
+                    h.runaway
+                        .get_or_insert_with(ThermalRunawayConfig::default)
+                        .window_s = parse_int(value)?
+                }
+                "runaway_min_rise_c" => {
+                    h.runaway
+                        .get_or_insert_with(ThermalRunawayConfig::default)
+                        .min_rise_c = parse_int(value)?
+                }
                 _ => {}
             }
         }
@@ -601,11 +1040,47 @@ fn apply_value(
                 .ok_or(ParseError::InvalidSection)?;
             match key {
                 "heater_pin" => h.heater_pin = parse_pin(value)?,
+                "enable_pin" => h.enable_pin = Some(parse_pin(value)?),
                 "sensor_pin" => {
                     let pin = parse_pin(value)?;
                     h.sensor_pin = pin.pin;
                 }
                 "sensor_type" => h.sensor_type = parse_sensor_type(value)?,
+                "sensor_address" => h.sensor_address = Some(parse_i2c_address(value)?),
+                "onewire_pin" => h.onewire_pin = Some(parse_pin(value)?),
+                "temp_table" => h.temp_table = parse_temp_table(value)?,
+                "adc_samples" => {
+                    let samples: u8 = parse_int(value)?;
+                    if samples == 0 || samples as usize > MAX_ADC_SAMPLES {
+                        return Err(ParseError::InvalidValue);
+                    }
+                    h.adc_filter.samples = samples;
+                }
+                "adc_max_delta" => h.adc_filter.max_delta = parse_int(value)?,
+                "thermistor_beta" => {
+                    h.beta.get_or_insert_with(BetaConfig::default).beta = parse_int(value)?
+                }
+                "thermistor_r0" => {
+                    h.beta.get_or_insert_with(BetaConfig::default).r0_ohms = Some(parse_int(value)?)
+                }
+                "thermistor_t0" => {
+                    h.beta.get_or_insert_with(BetaConfig::default).t0_c = parse_int(value)?
+                }
+                "steinhart_hart_a" => {
+                    h.steinhart_hart
+                        .get_or_insert_with(SteinhartHartConfig::default)
+                        .a = parse_float(value)?
+                }
+                "steinhart_hart_b" => {
+                    h.steinhart_hart
+                        .get_or_insert_with(SteinhartHartConfig::default)
+                        .b = parse_float(value)?
+                }
+                "steinhart_hart_c" => {
+                    h.steinhart_hart
+                        .get_or_insert_with(SteinhartHartConfig::default)
+                        .c = parse_float(value)?
+                }
                 // Also handle control params in hardware section
                 "control" | "max_temp" | "hysteresis" => {
                     // These belong to HeaterConfig, but users might put them here
@@ -648,6 +1123,22 @@ fn apply_value(
                 "direction" => p.direction = parse_direction(value)?,
                 "iterations" => p.iterations = parse_int(value)?,
                 "temperature_c" => p.temperature_c = Some(parse_int(value)?),
+                "max_temp_c" => p.max_temp_c = Some(parse_int(value)?),
+                "spin_s" => {
+                    p.soak.get_or_insert_with(SoakConfig::default).spin_s = parse_int(value)?
+                }
+                "soak_s" => {
+                    p.soak.get_or_insert_with(SoakConfig::default).soak_s = parse_int(value)?
+                }
+                "cycles" => {
+                    p.soak.get_or_insert_with(SoakConfig::default).cycles = parse_int(value)?
+                }
+                "prime_s" => {
+                    p.prime.get_or_insert_with(PrimeConfig::default).time_s = parse_int(value)?
+                }
+                "prime_rpm" => {
+                    p.prime.get_or_insert_with(PrimeConfig::default).rpm = Some(parse_int(value)?)
+                }
                 _ => {}
             }
         }
@@ -657,6 +1148,7 @@ fn apply_value(
                 "lift_mm" => s.lift_mm = parse_int(value)?,
                 "rpm" => s.rpm = parse_int(value)?,
                 "time_s" => s.time_s = parse_int(value)?,
+                "pre_spinoff_delay_s" => s.pre_spinoff_delay_s = parse_int(value)?,
                 _ => {}
             }
         }
@@ -667,12 +1159,58 @@ fn apply_value(
                     let label = parse_string(value)?;
                     p.label = HString::try_from(label).map_err(|_| ParseError::InvalidValue)?;
                 }
+                "notes" => {
+                    let notes = parse_string(value)?;
+                    p.notes = Some(HString::try_from(notes).map_err(|_| ParseError::InvalidValue)?);
+                }
                 "steps" => {
                     p.steps = parse_steps(value)?;
                 }
                 _ => {}
             }
         }
+        Section::Machine => match key {
+            "version" => config.version = parse_int(value)?,
+            "safe_z" => config.safe_z = Some(parse_int(value)?),
+            "x_move_clearance_z" => config.x_move_clearance_z = Some(parse_int(value)?),
+            "park_after_program" => config.park_after_program = parse_bool(value)?,
+            "park_x" => config.park_position.x_pos = parse_int(value)?,
+            "park_z" => config.park_position.z_pos = parse_int(value)?,
+            "park_angle_deg" => config.park_angle_deg = Some(parse_int(value)?),
+            "prompt_first_jar" => config.prompt_first_jar = parse_bool(value)?,
+            "prewarm_next_jar" => config.prewarm_next_jar = parse_bool(value)?,
+            "balance_rotation" => {
+                config.balance_rotation =
+                    parse_bool(value)?.then(|| config.balance_rotation.unwrap_or_default())
+            }
+            "balance_rpm" => {
+                config
+                    .balance_rotation
+                    .get_or_insert_with(BalanceConfig::default)
+                    .rpm = parse_int(value)?
+            }
+            "balance_min_revs" => {
+                config
+                    .balance_rotation
+                    .get_or_insert_with(BalanceConfig::default)
+                    .min_revs = parse_int(value)?
+            }
+            "autostart_program" => {
+                let name = parse_string(value)?;
+                config.autostart_program =
+                    Some(HString::try_from(name).map_err(|_| ParseError::InvalidValue)?);
+            }
+            "config_fallback" => config.config_fallback = parse_bool(value)?,
+            "max_pause_s" => config.max_pause_s = parse_int(value)?,
+            "max_spinoff_rpm" => config.max_spinoff_rpm = Some(parse_int(value)?),
+            "quiet_spinoff_rpm" => config.quiet_spinoff_rpm = parse_int(value)?,
+            "stall_reverse_recovery" => config.stall_reverse_recovery = parse_bool(value)?,
+            "startup_stagger_ms" => config.startup_stagger_ms = parse_int(value)?,
+            "commands_on_change" => config.commands_on_change = parse_bool(value)?,
+            "watchdog_timeout_ms" => config.watchdog_timeout_ms = parse_int(value)?,
+            "homing_order" => config.homing_order = parse_homing_order(value)?,
+            _ => {}
+        },
         Section::Display => match key {
             "uart_tx_pin" | "tx_pin" => {
                 let pin = parse_pin(value)?;
@@ -689,8 +1227,63 @@ fn apply_value(
             "rpm_step" => config.ui.rpm_step = parse_int(value)?,
             "time_step_s" => config.ui.time_step_s = parse_int(value)?,
             "temp_step_c" => config.ui.temp_step_c = parse_int(value)?,
+            "min_render_interval_ms" => config.ui.min_render_interval_ms = parse_int(value)?,
+            "status_header" => config.ui.status_header = parse_bool(value)?,
+            "show_overall_progress" => config.ui.show_overall_progress = parse_bool(value)?,
+            "complete_auto_return_s" => config.ui.complete_auto_return_s = parse_int(value)?,
+            "auto_advance_s" => config.ui.auto_advance_s = parse_int(value)?,
+            "diagnostics_menu" => config.ui.diagnostics_menu = parse_bool(value)?,
+            "lock_during_motion" => config.ui.lock_during_motion = parse_bool(value)?,
+            _ => {
+                if let Some((category, button)) = parse_keymap_key(key) {
+                    let action = parse_key_action(value)?;
+                    config.ui.keymap.bind(category, button, action);
+                }
+            }
+        },
+        Section::Link => match key {
+            "heartbeat_ms" => {
+                config.link.heartbeat_ms = parse_int(value)?;
+                if config.link.heartbeat_ms == 0 {
+                    return Err(ParseError::InvalidValue);
+                }
+            }
+            "timeout_multiplier" => {
+                config.link.timeout_multiplier = parse_int(value)?;
+                if config.link.timeout_multiplier == 0 {
+                    return Err(ParseError::InvalidValue);
+                }
+            }
+            "auto_clear" => config.link.auto_clear = parse_bool(value)?,
+            "test_pattern" => config.link.test_pattern = Some(parse_link_test_pattern(value)?),
+            "report_scheduler_state" => config.link.report_scheduler_state = parse_bool(value)?,
             _ => {}
         },
+        Section::Lid => match key {
+            "pin" => config.lid.get_or_insert_with(LidConfig::default).pin = parse_pin(value)?,
+            "debounce_ms" => {
+                config
+                    .lid
+                    .get_or_insert_with(LidConfig::default)
+                    .debounce_ms = parse_int(value)?
+            }
+            _ => {}
+        },
+        Section::Ultrasonic(name) => {
+            let u = config.ultrasonic.get_or_insert_with(|| UltrasonicHwConfig {
+                name: name.clone(),
+                ..Default::default()
+            });
+            // Only one module is supported
+            if u.name != *name {
+                return Err(ParseError::TooManyItems);
+            }
+            match key {
+                "pin" => u.pin = parse_pin(value)?,
+                "max_on_s" => u.max_on_s = parse_int(value)?,
+                _ => {}
+            }
+        }
         Section::Root => {
             // Handle root-level keys if any
         }
@@ -706,6 +1299,7 @@ fn save_section(
     config: &mut MachineConfig,
     current_stepper: &mut Option<StepperHwConfig>,
     current_tmc: &mut Option<Tmc2209HwConfig>,
+    current_a4988: &mut Option<A4988HwConfig>,
     current_heater: &mut Option<HeaterConfig>,
     current_heater_hw: &mut Option<HeaterHwConfig>,
     current_jar: &mut Option<JarConfig>,
@@ -730,6 +1324,14 @@ fn save_section(
                     .map_err(|_| ParseError::TooManyItems)?;
             }
         }
+        Section::A4988(_) => {
+            if let Some(a) = current_a4988.take() {
+                config
+                    .a4988s
+                    .push(a)
+                    .map_err(|_| ParseError::TooManyItems)?;
+            }
+        }
         Section::Heater(_) => {
             if let Some(h) = current_heater.take() {
                 config
@@ -763,23 +1365,21 @@ fn save_section(
                 config.jars.push(j).map_err(|_| ParseError::TooManyItems)?;
             }
         }
-        Section::Profile(_) | Section::ProfileSpinoff(_) => {
-            // Attach spinoff to profile if present
+        Section::Profile(_) => {
+            if let Some(p) = current_profile.take() {
+                config
+                    .profiles
+                    .push(p)
+                    .map_err(|_| ParseError::TooManyItems)?;
+            }
+        }
+        Section::ProfileSpinoff(_) => {
+            // The parent profile was saved when its own section ended
             if let Some(spinoff) = current_spinoff.take() {
-                if let Some(ref mut p) = current_profile {
+                if let Some(p) = config.profiles.last_mut() {
                     p.spinoff = Some(spinoff);
                 }
             }
-
-            // Save profile when moving to new section (not spinoff subsection)
-            if !matches!(section, Section::ProfileSpinoff(_)) {
-                if let Some(p) = current_profile.take() {
-                    config
-                        .profiles
-                        .push(p)
-                        .map_err(|_| ParseError::TooManyItems)?;
-                }
-            }
         }
         Section::Program(_) => {
             if let Some(p) = current_program.take() {
@@ -789,7 +1389,13 @@ fn save_section(
                     .map_err(|_| ParseError::TooManyItems)?;
             }
         }
-        Section::Display | Section::Ui | Section::Root => {
+        Section::Machine
+        | Section::Display
+        | Section::Ui
+        | Section::Link
+        | Section::Lid
+        | Section::Ultrasonic(_)
+        | Section::Root => {
             // These are stored directly in config, nothing to save
         }
     }
@@ -822,6 +1428,73 @@ mod tests {
         assert!(pin.pull_up);
     }
 
+    #[test]
+    fn test_parse_mm_as_um() {
+        assert_eq!(parse_mm_as_um("360").ok(), Some(360_000));
+        assert_eq!(parse_mm_as_um("8.5").ok(), Some(8_500));
+        assert_eq!(parse_mm_as_um("1.25").ok(), Some(1_250));
+        assert_eq!(parse_mm_as_um("0.008").ok(), Some(8));
+        assert!(parse_mm_as_um("1.2345").is_err());
+        assert!(parse_mm_as_um("-8").is_err());
+        assert!(parse_mm_as_um("8.x").is_err());
+    }
+
+    #[test]
+    fn test_fractional_rotation_distance() {
+        let config = parse_config(
+            r#"
+[stepper z]
+full_steps_per_rotation = 200
+microsteps = 16
+rotation_distance = 8.125
+gear_ratio = "1:1"
+backlash_steps = 24
+homing_type = "manual"
+homing_timeout_s = 45
+"#,
+        )
+        .unwrap();
+
+        let z = config.find_stepper("z").unwrap();
+        assert_eq!(z.rotation_distance_um, 8_125);
+        assert_eq!(z.backlash_steps, 24);
+        assert_eq!(z.homing_type, HomingType::Manual);
+        assert_eq!(z.homing_timeout_s, Some(45));
+
+        let config = parse_config("[stepper z]\nhoming_type = \"none\"\n").unwrap();
+        assert_eq!(config.steppers[0].homing_type, HomingType::None);
+        assert!(parse_config("[stepper z]\nhoming_type = \"switch\"\n").is_err());
+        // 3200 / 8.125 = 393.846...
+        assert_eq!(z.steps_per_mm_x1000(), 393_846);
+    }
+
+    #[test]
+    fn test_parse_stepper_ramp() {
+        let config = parse_config(
+            r#"
+[stepper basket]
+accel_rpm_per_s = 40
+ramp_curve = "smooth"
+min_rpm = 10
+max_rpm = 300
+"#,
+        )
+        .unwrap();
+
+        let ramp = config.steppers[0].ramp();
+        assert_eq!(ramp.accel_rate, 40);
+        assert_eq!(ramp.curve, RampCurve::Smooth);
+        assert_eq!(ramp.min_rpm, 10);
+        assert_eq!(ramp.max_rpm, 300);
+
+        // Unset: immediate speed changes over the full range
+        let config = parse_config("[stepper basket]\n").unwrap();
+        let ramp = config.steppers[0].ramp();
+        assert_eq!(ramp.accel_rate, 0);
+        assert_eq!(ramp.max_rpm, u16::MAX);
+        assert!(parse_config("[stepper basket]\nramp_curve = \"s\"\n").is_err());
+    }
+
     #[test]
     fn test_parse_section_header() {
         match parse_section_header("stepper basket").unwrap() {
@@ -838,6 +1511,444 @@ mod tests {
             Section::Display => {}
             _ => panic!("Wrong section type"),
         }
+
+        match parse_section_header("machine").unwrap() {
+            Section::Machine => {}
+            _ => panic!("Wrong section type"),
+        }
+    }
+
+    #[test]
+    fn test_schema_version_checked() {
+        use core::fmt::Write;
+        use isochron_core::config::CONFIG_SCHEMA_VERSION;
+
+        // Omitting the version assumes the current schema
+        let config = parse_config("[machine]\n").unwrap();
+        assert_eq!(config.version, CONFIG_SCHEMA_VERSION);
+
+        let mut newer: HString<32> = HString::new();
+        write!(
+            newer,
+            "[machine]\nversion = {}\n",
+            CONFIG_SCHEMA_VERSION + 1
+        )
+        .unwrap();
+        assert!(parse_config(&newer).is_err());
+        assert!(parse_config("[machine]\nversion = 0\n").is_err());
+
+        // A schema 1 file is migrated, its missing keys defaulted
+        let config = parse_config("[machine]\nversion = 1\n[stepper basket]\n").unwrap();
+        assert_eq!(config.version, CONFIG_SCHEMA_VERSION);
+        assert!(!config.prewarm_next_jar);
+        assert!(config.ultrasonic.is_none());
+        assert_eq!(config.steppers[0].accel_rpm_per_s, 0);
+    }
+
+    #[test]
+    fn test_travel_below_x_move_clearance() {
+        let too_low = "[machine]\nsafe_z = 10\nx_move_clearance_z = 5\n";
+        assert!(matches!(
+            parse_config(too_low),
+            Err(ParseError::InvalidValue)
+        ));
+
+        // Without safe_z the top of the Z axis travel is used
+        let z_axis = r#"
+[stepper z]
+position_min = 8
+
+[machine]
+x_move_clearance_z = 5
+"#;
+        assert!(matches!(
+            parse_config(z_axis),
+            Err(ParseError::InvalidValue)
+        ));
+
+        let clear = "[machine]\nsafe_z = 5\nx_move_clearance_z = 5\n";
+        assert!(parse_config(clear).is_ok());
+        assert!(parse_config("[machine]\nx_move_clearance_z = 5\n").is_ok());
+    }
+
+    #[test]
+    fn test_parse_machine_section() {
+        let config_str = r#"
+[machine]
+version = 1
+safe_z = 5
+x_move_clearance_z = 8
+autostart_program = "full"
+max_pause_s = 600
+stall_reverse_recovery = true
+startup_stagger_ms = 300
+commands_on_change = true
+quiet_spinoff_rpm = 120
+watchdog_timeout_ms = 2000
+park_after_program = true
+park_x = 10
+park_z = 2
+homing_order = "simultaneous"
+park_angle_deg = 90
+prompt_first_jar = true
+prewarm_next_jar = true
+config_fallback = false
+balance_rpm = 90
+"#;
+
+        let config = parse_config(config_str).unwrap();
+        assert_eq!(config.version, 2);
+        assert_eq!(config.safe_z, Some(5));
+        assert_eq!(config.x_move_clearance_z, Some(8));
+        assert_eq!(config.autostart_program.as_deref(), Some("full"));
+        assert_eq!(config.max_pause_s, 600);
+        assert!(config.stall_reverse_recovery);
+        assert_eq!(config.startup_stagger_ms, 300);
+        assert!(config.commands_on_change);
+        assert_eq!(config.quiet_spinoff_rpm, 120);
+        assert_eq!(config.watchdog_timeout_ms, 2000);
+        assert!(config.park_after_program);
+        assert_eq!(config.park_position.x_pos, 10);
+        assert_eq!(config.park_position.z_pos, 2);
+        assert_eq!(config.homing_order, HomingOrder::Simultaneous);
+        assert_eq!(config.park_angle_deg, Some(90));
+        assert!(config.prompt_first_jar);
+        assert!(config.prewarm_next_jar);
+        assert!(!config.config_fallback);
+        assert_eq!(
+            config.balance_rotation,
+            Some(BalanceConfig {
+                rpm: 90,
+                min_revs: 1
+            })
+        );
+
+        let config = parse_config("[machine]\nversion = 1\n").unwrap();
+        assert!(config.autostart_program.is_none());
+        assert_eq!(config.max_pause_s, 0);
+        assert!(!config.stall_reverse_recovery);
+        assert_eq!(config.startup_stagger_ms, 0);
+        assert!(!config.commands_on_change);
+        assert_eq!(config.watchdog_timeout_ms, 5000);
+        assert!(!config.park_after_program);
+        assert_eq!(config.park_position.x_pos, 0);
+        assert_eq!(config.homing_order, HomingOrder::ZThenX);
+        assert_eq!(config.park_angle_deg, None);
+        assert!(!config.prompt_first_jar);
+        assert!(!config.prewarm_next_jar);
+        assert!(config.config_fallback);
+        assert_eq!(config.balance_rotation, None);
+
+        let config = parse_config("[machine]\nbalance_rotation = true\n").unwrap();
+        assert_eq!(config.balance_rotation, Some(BalanceConfig::default()));
+
+        assert!(parse_config("[machine]\nhoming_order = \"x_first\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_sense_resistor() {
+        let config = parse_config("[tmc2209 basket]\nsense_resistor = 0.150\n").unwrap();
+        assert_eq!(config.tmc2209s[0].rsense_mohm, Some(150));
+
+        let config = parse_config("[tmc2209 basket]\nsense_resistor_mohm = 75\n").unwrap();
+        assert_eq!(config.tmc2209s[0].rsense_mohm, Some(75));
+
+        let config = parse_config("[tmc2209 basket]\nuart_address = 0\n").unwrap();
+        assert_eq!(config.tmc2209s[0].rsense_mohm, None);
+
+        assert!(parse_config("[tmc2209 basket]\nsense_resistor = 0\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_stall_settle() {
+        let config = parse_config("[tmc2209 basket]\nstall_settle_ms = 800\n").unwrap();
+        assert_eq!(config.tmc2209s[0].stall_settle_ms, Some(800));
+
+        let config = parse_config("[tmc2209 basket]\nuart_address = 0\n").unwrap();
+        assert_eq!(config.tmc2209s[0].stall_settle_ms, None);
+    }
+
+    #[test]
+    fn test_parse_spin_hold() {
+        let config = parse_config("[tmc2209 basket]\nspin_hold = true\n").unwrap();
+        assert!(config.tmc2209s[0].spin_hold);
+
+        let config = parse_config("[tmc2209 basket]\nuart_address = 0\n").unwrap();
+        assert!(!config.tmc2209s[0].spin_hold);
+    }
+
+    #[test]
+    fn test_parse_a4988_section() {
+        let config = parse_config(
+            "[a4988.basket]\nchip = \"drv8825\"\nms1_pin = \"gpio2\"\nms2_pin = \"!gpio3\"\n",
+        )
+        .unwrap();
+        let a4988 = config.find_a4988("basket").unwrap();
+        assert_eq!(a4988.chip, StepDirChip::Drv8825);
+        assert_eq!(a4988.ms1_pin.map(|p| p.pin), Some(2));
+        assert!(a4988.ms2_pin.unwrap().inverted);
+        assert!(a4988.ms3_pin.is_none());
+        assert!(config.tmc2209s.is_empty());
+
+        let config = parse_config("[a4988 basket]\nms3_pin = \"gpio4\"\n").unwrap();
+        assert_eq!(config.a4988s[0].chip, StepDirChip::A4988);
+
+        assert!(parse_config("[a4988 basket]\nchip = \"tmc2209\"\n").is_err());
+        assert!(parse_config("[a4988]\nchip = \"a4988\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_ui_section() {
+        let config = parse_config(
+            "[ui]\nmin_render_interval_ms = 500\nstatus_header = true\nshow_overall_progress = true\ncomplete_auto_return_s = 30\nauto_advance_s = 20\ndiagnostics_menu = true\nlock_during_motion = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.ui.min_render_interval_ms, 500);
+        assert!(config.ui.status_header);
+        assert!(config.ui.show_overall_progress);
+        assert_eq!(config.ui.complete_auto_return_s, 30);
+        assert_eq!(config.ui.auto_advance_s, 20);
+        assert!(config.ui.diagnostics_menu);
+        assert!(config.ui.lock_during_motion);
+
+        let config = parse_config("[ui]\n").unwrap();
+        assert!(!config.ui.status_header);
+        assert!(!config.ui.show_overall_progress);
+        assert_eq!(config.ui.complete_auto_return_s, 0);
+        assert_eq!(config.ui.auto_advance_s, 0);
+        assert!(!config.ui.diagnostics_menu);
+        assert!(!config.ui.lock_during_motion);
+    }
+
+    #[test]
+    fn test_parse_ui_keymap() {
+        let config = parse_config(
+            "[ui]
+program_long_press = \"back\"
+menu_double_click = \"ignore\"
+idle_click = \"select\"
+idle_long_press = \"quiet\"
+edit_long_press = \"back\"
+",
+        )
+        .unwrap();
+        let keymap = &config.ui.keymap;
+        assert_eq!(
+            keymap.action(StateCategory::Program, Button::LongPress),
+            KeyAction::Back
+        );
+        assert_eq!(
+            keymap.action(StateCategory::Menu, Button::DoubleClick),
+            KeyAction::Ignore
+        );
+        assert_eq!(
+            keymap.action(StateCategory::Idle, Button::LongPress),
+            KeyAction::ToggleQuiet
+        );
+        assert_eq!(
+            keymap.action(StateCategory::Edit, Button::LongPress),
+            KeyAction::Back
+        );
+        // Unbound gestures keep their defaults
+        assert_eq!(
+            keymap.action(StateCategory::Transition, Button::LongPress),
+            KeyAction::Abort
+        );
+
+        assert!(parse_config(
+            "[ui]
+program_click = \"pause\"
+"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_profile_soak() {
+        let config = parse_config(
+            "[profile Soak]
+rpm = 150
+spin_s = 30
+soak_s = 90
+cycles = 4
+",
+        )
+        .unwrap();
+        let soak = config.profiles[0].soak.unwrap();
+        assert_eq!(soak.spin_s, 30);
+        assert_eq!(soak.soak_s, 90);
+        assert_eq!(soak.cycles, 4);
+
+        let config = parse_config(
+            "[profile Soak]
+spin_s = 30
+soak_s = 90
+",
+        )
+        .unwrap();
+        assert_eq!(config.profiles[0].soak.unwrap().cycles, 1);
+
+        let config = parse_config(
+            "[profile Clean]
+rpm = 120
+",
+        )
+        .unwrap();
+        assert!(config.profiles[0].soak.is_none());
+    }
+
+    #[test]
+    fn test_zero_time_profile_rejected() {
+        assert!(parse_config("[profile Clean]\nrpm = 120\ntime_s = 0\n").is_err());
+        // Four 7-second half-cycles are too short to reverse between
+        assert!(parse_config("[profile Clean]\ntime_s = 60\niterations = 4\n").is_err());
+
+        let config =
+            parse_config("[profile Clean]\nrpm = 120\ntime_s = 1\ndirection = \"cw\"\n").unwrap();
+        let segments = profile_segments(&config.profiles[0]).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].duration_s, 1);
+    }
+
+    #[test]
+    fn test_parse_profile_prime() {
+        let config = parse_config(
+            "[profile Clean]
+rpm = 120
+temperature_c = 40
+prime_s = 20
+prime_rpm = 80
+",
+        )
+        .unwrap();
+        let prime = config.profiles[0].prime.unwrap();
+        assert_eq!(prime.time_s, 20);
+        assert_eq!(prime.rpm, Some(80));
+
+        let config = parse_config("[profile Clean]\nprime_s = 20\n").unwrap();
+        assert_eq!(config.profiles[0].prime.unwrap().rpm, None);
+
+        let config = parse_config("[profile Clean]\nrpm = 120\n").unwrap();
+        assert!(config.profiles[0].prime.is_none());
+    }
+
+    #[test]
+    fn test_spinoff_rpm_above_machine_max() {
+        // The limit applies regardless of section order
+        let too_fast = "[profile Clean]
+rpm = 120
+
+[profile.Clean.spinoff]
+rpm = 1500
+
+[machine]
+max_spinoff_rpm = 300
+";
+        assert!(matches!(
+            parse_config(too_fast),
+            Err(ParseError::InvalidValue)
+        ));
+
+        // Without a machine limit any spin-off RPM is accepted
+        let uncapped = "[profile Clean]
+rpm = 120
+
+[profile.Clean.spinoff]
+rpm = 1500
+
+[machine]
+";
+        let config = parse_config(uncapped).unwrap();
+        assert_eq!(config.max_spinoff_rpm, None);
+        assert_eq!(config.profiles[0].spinoff.unwrap().rpm, 1500);
+
+        let within = "[profile Clean]
+rpm = 120
+
+[profile.Clean.spinoff]
+rpm = 300
+
+[machine]
+max_spinoff_rpm = 300
+";
+        let config = parse_config(within).unwrap();
+        assert_eq!(config.max_spinoff_rpm, Some(300));
+        assert_eq!(config.profiles[0].spinoff.unwrap().rpm, 300);
+    }
+
+    #[test]
+    fn test_parse_link_section() {
+        let config = parse_config("[link]\nheartbeat_ms = 2000\ntimeout_multiplier = 5\n").unwrap();
+        assert_eq!(config.link.heartbeat_ms, 2000);
+        assert_eq!(config.link.timeout_multiplier, 5);
+        assert_eq!(config.link.timeout_ms(), 10_000);
+        assert!(!config.link.auto_clear);
+
+        let config = parse_config("[link]\nauto_clear = true\n").unwrap();
+        assert!(config.link.auto_clear);
+
+        let config = parse_config("[link]\ntest_pattern = \"checkerboard\"\n").unwrap();
+        assert_eq!(
+            config.link.test_pattern,
+            Some(LinkTestPattern::Checkerboard)
+        );
+        assert!(parse_config("[link]\ntest_pattern = \"zigzag\"\n").is_err());
+
+        let config = parse_config("[link]\nreport_scheduler_state = true\n").unwrap();
+        assert!(config.link.report_scheduler_state);
+
+        let config = parse_config("[machine]\nversion = 1\n").unwrap();
+        assert_eq!(config.link, LinkConfig::default());
+
+        assert!(parse_config("[link]\nheartbeat_ms = 0\n").is_err());
+        assert!(parse_config("[link]\ntimeout_multiplier = 0\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_lid_section() {
+        let config = parse_config("[lid]\npin = \"!^gpio14\"\ndebounce_ms = 20\n").unwrap();
+        let lid = config.lid.unwrap();
+        assert_eq!(lid.pin.pin, 14);
+        assert!(lid.pin.inverted && lid.pin.pull_up);
+        assert_eq!(lid.debounce_ms, 20);
+
+        let config = parse_config("[lid]\npin = \"gpio14\"\n").unwrap();
+        assert_eq!(config.lid.unwrap().debounce_ms, 50);
+
+        assert!(parse_config("[lid]\n").unwrap().lid.is_none());
+        assert!(parse_config("[lid]\npin = \"pin14\"\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_ultrasonic_section() {
+        let module = "[ultrasonic us_clean]\npin = \"!gpio16\"\nmax_on_s = 600\n";
+        let config = parse_config(module).unwrap();
+        let ultrasonic = config.ultrasonic.unwrap();
+        assert_eq!(ultrasonic.name.as_str(), "us_clean");
+        assert_eq!(ultrasonic.pin.pin, 16);
+        assert!(ultrasonic.pin.inverted);
+        assert_eq!(ultrasonic.max_on_s, 600);
+
+        let config = parse_config("[ultrasonic.us_clean]\npin = \"gpio16\"\n").unwrap();
+        assert_eq!(config.ultrasonic.unwrap().max_on_s, 900);
+        assert!(parse_config("[ultrasonic us_clean]\n")
+            .unwrap()
+            .ultrasonic
+            .is_none());
+
+        // Jars may only name the configured module
+        let jar = |name: &str| {
+            let jar = alloc::format!("[jar clean]\nultrasonic = \"{}\"\n", name);
+            parse_config(&(jar + module))
+        };
+        assert!(jar("us_clean").is_ok());
+        assert!(jar("us_other").is_err());
+        // Without a module, references are left unwired
+        assert!(parse_config("[jar clean]\nultrasonic = \"us_other\"\n").is_ok());
+
+        // Only one module
+        let two = alloc::format!("{}[ultrasonic us_dry]\npin = \"gpio17\"\n", module);
+        assert!(parse_config(&two).is_err());
     }
 
     #[test]
@@ -863,6 +1974,46 @@ mod tests {
         assert_eq!(steps[1].profile.as_str(), "Rinse");
     }
 
+    #[test]
+    fn test_parse_program_notes() {
+        let config = parse_config(
+            r#"
+[program gold]
+label = "Gold"
+notes = "For gold-plated movements"
+
+[program full]
+label = "Full"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.programs[0].notes.as_deref(),
+            Some("For gold-plated movements")
+        );
+        assert_eq!(config.programs[1].notes, None);
+
+        // Notes longer than two display rows are rejected
+        let long =
+            "[program gold]\nnotes = \"This description is much too long to fit the screen\"\n";
+        assert!(parse_config(long).is_err());
+    }
+
+    #[test]
+    fn test_autostart_program_by_key_or_label() {
+        let programs = "[program gold]\nlabel = \"Gold\"\n[program full]\nlabel = \"Full\"\n";
+        let with = |name: &str| {
+            let machine = alloc::format!("[machine]\nautostart_program = \"{}\"\n", name);
+            parse_config(&(machine + programs)).unwrap()
+        };
+
+        // The key resolves to the program's label
+        assert_eq!(with("full").autostart_program.as_deref(), Some("Full"));
+        assert_eq!(with("Gold").autostart_program.as_deref(), Some("Gold"));
+        // Unknown names are left for the controller to reject
+        assert_eq!(with("other").autostart_program.as_deref(), Some("other"));
+    }
+
     #[test]
     fn test_parse_minimal_config() {
         let config_str = r#"
@@ -881,9 +2032,24 @@ uart_rx_pin = "gpio1"
         assert_eq!(config.steppers[0].name.as_str(), "basket");
         assert_eq!(config.steppers[0].step_pin.pin, 11);
         assert!(config.steppers[0].enable_pin.inverted);
+        assert_eq!(config.steppers[0].step_pulse_ns, None);
+        assert_eq!(config.steppers[0].stop_behavior, StopBehavior::Coast);
         assert_eq!(config.display.uart_tx_pin, 0);
     }
 
+    #[test]
+    fn test_parse_stop_behavior() {
+        assert_eq!(
+            parse_stop_behavior("\"coast\"").unwrap(),
+            StopBehavior::Coast
+        );
+        assert_eq!(
+            parse_stop_behavior("\"brake\"").unwrap(),
+            StopBehavior::Brake
+        );
+        assert!(parse_stop_behavior("\"hold\"").is_err());
+    }
+
     #[test]
     fn test_parse_pid_value() {
         // Float format
@@ -906,6 +2072,9 @@ max_temp = 55
 pid_kp = 1.5
 pid_ki = 0.1
 pid_kd = 0.5
+max_heat_rate_c_per_min = 3
+fault_policy = "continue_unheated"
+runaway_window_s = 90
 "#;
 
         let config = parse_config(config_str).unwrap();
@@ -915,5 +2084,221 @@ pid_kd = 0.5
         assert_eq!(config.heaters[0].pid_kp_x100, Some(150));
         assert_eq!(config.heaters[0].pid_ki_x100, Some(10));
         assert_eq!(config.heaters[0].pid_kd_x100, Some(50));
+        assert_eq!(config.heaters[0].max_heat_rate_c_per_min, 3);
+        assert_eq!(
+            config.heaters[0].fault_policy,
+            SensorFaultPolicy::ContinueUnheated
+        );
+        assert_eq!(
+            config.heaters[0].runaway,
+            Some(ThermalRunawayConfig {
+                window_s: 90,
+                min_rise_c: 2
+            })
+        );
+
+        let config = parse_config("[heater_control dryer]\ncontrol = \"pid\"\n").unwrap();
+        assert_eq!(config.heaters[0].runaway, None);
+    }
+
+    #[test]
+    fn test_parse_heater_enable_pin() {
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+enable_pin = "!gpio24"
+sensor_pin = "gpio27"
+"#;
+
+        let config = parse_config(config_str).unwrap();
+        let enable = config.heater_hw[0].enable_pin.unwrap();
+        assert_eq!(enable.pin, 24);
+        assert!(enable.inverted);
+
+        let config = parse_config("[heater dryer]\nheater_pin = \"gpio23\"\n").unwrap();
+        assert!(config.heater_hw[0].enable_pin.is_none());
+    }
+
+    #[test]
+    fn test_parse_i2c_sensor() {
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+sensor_type = "i2c_tmp117"
+sensor_address = 0x49
+"#;
+
+        let config = parse_config(config_str).unwrap();
+        let hw = &config.heater_hw[0];
+        assert_eq!(hw.sensor_type, SensorType::I2cTmp117);
+        assert!(hw.sensor_type.is_i2c());
+        assert_eq!(hw.sensor_address, Some(0x49));
+
+        assert_eq!(parse_i2c_address("72").ok(), Some(0x48));
+        assert!(parse_i2c_address("0x78").is_err());
+        assert!(parse_i2c_address("0xZZ").is_err());
+    }
+
+    #[test]
+    fn test_parse_onewire_sensor() {
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+sensor_type = "ds18b20"
+onewire_pin = "^gpio22"
+"#;
+
+        let config = parse_config(config_str).unwrap();
+        let hw = &config.heater_hw[0];
+        assert_eq!(hw.sensor_type, SensorType::Ds18b20);
+        assert!(hw.sensor_type.is_onewire());
+        assert!(!hw.sensor_type.is_i2c());
+        let pin = hw.onewire_pin.unwrap();
+        assert_eq!(pin.pin, 22);
+        assert!(pin.pull_up);
+    }
+
+    #[test]
+    fn test_onewire_sensor_needs_own_pin() {
+        let no_pin = r#"
+[heater dryer]
+heater_pin = "gpio23"
+sensor_type = "ds18b20"
+"#;
+        assert!(matches!(
+            parse_config(no_pin),
+            Err(ParseError::InvalidValue)
+        ));
+
+        let shared_pin = r#"
+[heater dryer]
+heater_pin = "gpio23"
+enable_pin = "gpio22"
+sensor_type = "ds18b20"
+onewire_pin = "gpio22"
+"#;
+        assert!(matches!(
+            parse_config(shared_pin),
+            Err(ParseError::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn test_parse_thermistor_model() {
+        use isochron_core::config::ThermistorModel;
+
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+sensor_type = "ntc10k"
+thermistor_beta = 3435
+"#;
+        let config = parse_config(config_str).unwrap();
+        assert_eq!(
+            config.heater_hw[0].thermistor_model(),
+            ThermistorModel::Beta {
+                beta: 3435,
+                r0_ohms: 10_000,
+                t0_c: 25
+            }
+        );
+
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+steinhart_hart_a = 0.000722
+steinhart_hart_b = 2.17e-4
+steinhart_hart_c = 8.7e-8
+"#;
+        let config = parse_config(config_str).unwrap();
+        assert_eq!(
+            config.heater_hw[0].thermistor_model(),
+            ThermistorModel::SteinhartHart {
+                a: 0.000722,
+                b: 2.17e-4,
+                c: 8.7e-8
+            }
+        );
+
+        // All three coefficients or none
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+steinhart_hart_a = 0.000722
+steinhart_hart_b = 2.17e-4
+"#;
+        assert!(matches!(
+            parse_config(config_str),
+            Err(ParseError::InvalidValue)
+        ));
+
+        assert!(parse_float("inf").is_err());
+        assert!(parse_float("1e-4x").is_err());
+    }
+
+    #[test]
+    fn test_parse_temp_table() {
+        use isochron_core::config::ThermistorModel;
+
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+sensor_type = "ntc10k"
+temp_table = [[32650, 0], [10000, 25], [4917, 42.5], [3600, 50]]
+"#;
+        let config = parse_config(config_str).unwrap();
+        let hw = &config.heater_hw[0];
+        assert_eq!(
+            hw.temp_table.as_slice(),
+            &[(32_650, 0), (10_000, 250), (4_917, 425), (3_600, 500)]
+        );
+        assert!(matches!(hw.thermistor_model(), ThermistorModel::Custom(_)));
+
+        // Listed cold resistance last: stored in the same order
+        let table = parse_temp_table("[[3600, 50], [10000, 25], [32650, 0]]").unwrap();
+        assert_eq!(
+            table.as_slice(),
+            &[(32_650, 0), (10_000, 250), (3_600, 500)]
+        );
+
+        // Not monotonic
+        assert!(parse_temp_table("[[32650, 0], [10000, 25], [12000, 40]]").is_err());
+        assert!(parse_temp_table("[[32650, 0], [10000, 25], [3600, 20]]").is_err());
+        assert!(parse_temp_table("[[32650, 0], [32650, 25]]").is_err());
+        // Too short or malformed
+        assert!(parse_temp_table("[[10000, 25]]").is_err());
+        assert!(parse_temp_table("[[10000 25], [3600, 50]]").is_err());
+        assert!(parse_temp_table("[]").is_err());
+
+        // Without a table the built-in one is used
+        let config = parse_config("[heater dryer]\nheater_pin = \"gpio23\"\n").unwrap();
+        assert!(config.heater_hw[0].temp_table.is_empty());
+    }
+
+    #[test]
+    fn test_parse_adc_filter() {
+        use isochron_core::config::AdcFilterConfig;
+
+        let config_str = r#"
+[heater dryer]
+heater_pin = "gpio23"
+adc_samples = 7
+adc_max_delta = 25
+"#;
+        let config = parse_config(config_str).unwrap();
+        assert_eq!(
+            config.heater_hw[0].adc_filter,
+            AdcFilterConfig {
+                samples: 7,
+                max_delta: 25
+            }
+        );
+
+        let config = parse_config("[heater dryer]\nheater_pin = \"gpio23\"\n").unwrap();
+        assert_eq!(config.heater_hw[0].adc_filter, AdcFilterConfig::default());
+
+        // The window must hold 1 to MAX_ADC_SAMPLES samples
+        assert!(parse_config("[heater dryer]\nadc_samples = 0\n").is_err());
+        assert!(parse_config("[heater dryer]\nadc_samples = 10\n").is_err());
     }
 }
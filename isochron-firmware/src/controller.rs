@@ -8,28 +8,56 @@
 //! - Generates display updates
 
 use isochron_core::config::{
-    JarConfig, MachineCapabilities, ProfileConfig, ProgramConfig, MAX_JARS, MAX_PROFILES,
-    MAX_PROGRAMS,
+    Button, CalibrationData, HeaterConfig, HomingOrder, HomingType, JarConfig, KeyAction, Keymap,
+    LinkConfig, MachineCapabilities, ParkPosition, ProfileConfig, ProgramConfig, SensorFaultPolicy,
+    StateCategory, StopBehavior, ThermalRunawayConfig, DEFAULT_QUIET_SPINOFF_RPM, MAX_HEATERS,
+    MAX_JARS, MAX_PROFILES, MAX_PROGRAMS,
 };
-use isochron_core::safety::{SafetyMonitor, SafetyStatus};
-use isochron_core::scheduler::{HeaterCommand, MotorCommand, Scheduler};
-use isochron_core::state::{Event, State};
-use isochron_protocol::InputEvent;
+use isochron_core::motion::{
+    Axis, Endstop, Homing, HomingConfig, HomingMove, HomingPhase, HomingSequence,
+};
+use isochron_core::safety::{
+    Breadcrumb, ImbalanceDetector, RecoveryNotice, SafetyMonitor, SafetyStatus,
+};
+use isochron_core::scheduler::{
+    AccessoryCommand, BalanceConfig, ExecutionPhase, HeaterCommand, MotorCommand, Scheduler,
+};
+use isochron_core::state::{DriverFaultKind, ErrorKind, Event, State};
+use isochron_core::traits::SensorRaw;
+use isochron_core::util::{CancelToken, TemperatureC10};
+use isochron_protocol::{InputEvent, PicoMessage};
 
 use heapless::Vec;
 
 /// Special menu item index for autotune (after programs)
 const AUTOTUNE_MENU_INDEX: u8 = 254;
 
+/// Special menu item index for sensor diagnostics (after autotune)
+const DIAGNOSTICS_MENU_INDEX: u8 = 253;
+
 /// Default autotune target temperature (°C × 10)
 const AUTOTUNE_TARGET_X10: i16 = 450; // 45.0°C
 
+/// Heater index used for autotune and calibration storage (the first heater)
+pub const AUTOTUNE_HEATER_INDEX: u8 = 0;
+
+/// How long the basket reverses to free a jam (ms)
+const STALL_REVERSE_MS: u32 = 500;
+
+/// Reversals tried per phase before a stall faults
+const MAX_STALL_REVERSALS: u8 = 3;
+
+/// How long a sensor fault may last before continuing unheated gives up (ms)
+const SUSTAINED_SENSOR_FAULT_MS: u32 = 30_000;
+
 /// Autotune UI phase (sub-state within Autotuning state)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum AutotunePhase {
     /// Showing confirmation screen, waiting for user to confirm
     #[default]
     Confirming,
+    /// Calibration already stored, waiting for user to confirm overwrite
+    ConfirmOverwrite,
     /// Autotune is running, showing progress
     Running,
     /// Autotune completed successfully, showing result
@@ -61,6 +89,42 @@ impl AutotuneFailureReason {
     }
 }
 
+/// RPM change per encoder detent on the edit screen
+const EDIT_RPM_STEP: u16 = 10;
+
+/// Duration change per encoder detent on the edit screen (s)
+const EDIT_TIME_STEP_S: u16 = 10;
+
+/// Profile value adjusted on the edit screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditField {
+    /// Target RPM
+    Rpm,
+    /// Duration
+    Time,
+}
+
+/// Profile edit in progress (sub-state within EditProgram state)
+///
+/// The edit screen walks the selected program's steps, one profile at a
+/// time. Changes stay in a working copy until the user answers the
+/// "Save changes?" prompt.
+#[derive(Debug, Clone)]
+pub struct ProfileEdit {
+    /// Program step whose profile is shown
+    pub step: u8,
+    /// Index of the profile in the configuration
+    pub profile_index: u8,
+    /// Working copy with the user's changes
+    pub profile: ProfileConfig,
+    /// Value the encoder adjusts
+    pub field: EditField,
+    /// "Save changes?" prompt shown, with Yes highlighted when true
+    pub save_prompt: Option<bool>,
+    /// Leave the edit screen once the prompt is answered
+    exit_after: bool,
+}
+
 /// Controller state for coordinating subsystems
 pub struct Controller {
     /// Current machine state
@@ -75,8 +139,17 @@ pub struct Controller {
     profiles: Vec<ProfileConfig, MAX_PROFILES>,
     /// Available jars
     jars: Vec<JarConfig, MAX_JARS>,
-    /// Currently selected program index (AUTOTUNE_MENU_INDEX for autotune)
+    /// Currently selected program index (AUTOTUNE_MENU_INDEX for autotune,
+    /// DIAGNOSTICS_MENU_INDEX for diagnostics)
     selected_program: u8,
+    /// Offer the diagnostics screen in the idle menu
+    diagnostics_menu: bool,
+    /// Diagnostics screen shown over the idle menu
+    diagnostics: bool,
+    /// Latest raw thermistor values, for the diagnostics screen
+    sensor_raw: Option<SensorRaw>,
+    /// Ignore all input but abort while the machine moves on its own
+    lock_during_motion: bool,
     /// Last tick timestamp (ms)
     last_tick_ms: u32,
     /// Autotune UI phase
@@ -88,6 +161,98 @@ pub struct Controller {
     autotune_result: Option<(i16, i16, i16)>,
     /// Autotune failure reason (when failed)
     autotune_failure: Option<AutotuneFailureReason>,
+    /// PID coefficients from stored calibration (or last autotune)
+    active_pid: Option<(i16, i16, i16)>,
+    /// Result of saving the last autotune to flash (None while pending)
+    calibration_saved: Option<bool>,
+    /// Profile edit in progress
+    profile_edit: Option<ProfileEdit>,
+    /// Confirmed profile edit not yet picked up for saving
+    pending_profile_save: Option<(u8, ProfileConfig)>,
+    /// Program to start automatically once idle (headless operation)
+    autostart_program: Option<u8>,
+    /// Abort a paused program after this long (ms, 0 = never)
+    max_pause_ms: u32,
+    /// Clear recoverable faults once their cause goes away
+    auto_clear_faults: bool,
+    /// Scheduler state last reported to the host (None = reporting off)
+    reported_scheduler_state: Option<PicoMessage<'static>>,
+    /// Time spent in the current pause (ms)
+    paused_ms: u32,
+    /// Return from the complete screen to idle after this long (ms, 0 = wait for a click)
+    complete_auto_return_ms: u32,
+    /// Time spent on the complete screen (ms)
+    complete_ms: u32,
+    /// Confirm a manual jar or step prompt after this long (ms, 0 = wait for a click)
+    auto_advance_ms: u32,
+    /// Time spent on the current jar or step prompt (ms)
+    prompt_ms: u32,
+    /// Where to park the basket after a program (None = leave it in place)
+    park_position: Option<ParkPosition>,
+    /// Park move requested but not yet picked up
+    pending_park: Option<ParkPosition>,
+    /// Basket orientation requested but not yet picked up (degrees)
+    pending_orient: Option<u16>,
+    /// Next-jar prompt held back until the basket reaches its park angle
+    orienting: bool,
+    /// Spin-off imbalance detection from StallGuard (None = disabled)
+    imbalance: Option<ImbalanceDetector>,
+    /// Reverse briefly on a stall instead of faulting straight away
+    stall_reverse_recovery: bool,
+    /// Time left in the current jam-clearing reversal (ms)
+    stall_reverse_ms: Option<u32>,
+    /// Reversals tried in the current phase
+    stall_reversals: u8,
+    /// Delay between motor and heater start when both start together (ms)
+    startup_stagger_ms: u32,
+    /// Time left before the heater may switch on (ms)
+    stagger_remaining_ms: Option<u32>,
+    /// Motor was running after the last transition
+    motor_was_on: bool,
+    /// Heater was commanded on after the last transition
+    heater_was_on: bool,
+    /// Motor or heater command changed outside a state transition
+    command_update: bool,
+    /// Hand out motor and heater commands only when they change
+    commands_on_change: bool,
+    /// Motor command last handed out for sending
+    sent_motor: Option<MotorCommand>,
+    /// Heater commands last handed out for sending, by heater index
+    sent_heater: [Option<HeaterCommand>; MAX_HEATERS],
+    /// Ultrasonic command last handed out for sending
+    sent_ultrasonic: Option<AccessoryCommand>,
+    /// Response to a heater temperature sensor fault
+    sensor_fault_policy: SensorFaultPolicy,
+    /// How long each heater's sensor has been faulty while continuing unheated (ms)
+    sensor_fault_ms: [Option<u32>; MAX_HEATERS],
+    /// Configured spin-off RPM ceiling
+    max_spinoff_rpm: Option<u16>,
+    /// Quiet mode: cap spin-off speed and keep the driver in StealthChop
+    quiet_mode: bool,
+    /// Spin-off RPM ceiling while quiet
+    quiet_spinoff_rpm: u16,
+    /// Quiet mode toggled since last taken
+    quiet_changed: bool,
+    /// Watchdog reset notice, shown on the idle screen until clicked away
+    recovery: Option<RecoveryNotice>,
+    /// Breadcrumb last handed out for persisting
+    recorded_breadcrumb: Option<Breadcrumb>,
+    /// Encoder button bindings
+    keymap: Keymap,
+    /// Order in which the Z and X axes home at boot
+    homing_order: HomingOrder,
+    /// Homing in progress (None = not homing)
+    homing: Option<HomingSequence>,
+    /// Endstop homing of each axis, by `Axis as usize` (None = no endstop)
+    axis_homing: [Option<Homing>; 2],
+    /// Homing motion last handed out for each axis
+    sent_homing_move: [HomingMove; 2],
+    /// How the Z axis homes
+    z_homing: HomingType,
+    /// How the X axis homes
+    x_homing: HomingType,
+    /// Manual homing jog not yet picked up (axis, mm)
+    pending_jog: Option<(Axis, i32)>,
 }
 
 impl Controller {
@@ -107,6 +272,56 @@ impl Controller {
             autotune_elapsed_ticks: 0,
             autotune_result: None,
             autotune_failure: None,
+            active_pid: None,
+            calibration_saved: None,
+            profile_edit: None,
+            pending_profile_save: None,
+            autostart_program: None,
+            max_pause_ms: 0,
+            auto_clear_faults: false,
+            reported_scheduler_state: None,
+            paused_ms: 0,
+            complete_auto_return_ms: 0,
+            complete_ms: 0,
+            auto_advance_ms: 0,
+            prompt_ms: 0,
+            park_position: None,
+            pending_park: None,
+            pending_orient: None,
+            orienting: false,
+            imbalance: None,
+            stall_reverse_recovery: false,
+            stall_reverse_ms: None,
+            stall_reversals: 0,
+            startup_stagger_ms: 0,
+            stagger_remaining_ms: None,
+            motor_was_on: false,
+            heater_was_on: false,
+            command_update: false,
+            commands_on_change: false,
+            sent_motor: None,
+            sent_heater: [None; MAX_HEATERS],
+            sent_ultrasonic: None,
+            sensor_fault_policy: SensorFaultPolicy::Abort,
+            sensor_fault_ms: [None; MAX_HEATERS],
+            max_spinoff_rpm: None,
+            quiet_mode: false,
+            quiet_spinoff_rpm: DEFAULT_QUIET_SPINOFF_RPM,
+            quiet_changed: false,
+            recovery: None,
+            recorded_breadcrumb: None,
+            keymap: Keymap::default(),
+            homing_order: HomingOrder::default(),
+            homing: None,
+            axis_homing: [None, None],
+            sent_homing_move: [HomingMove::Stop; 2],
+            z_homing: HomingType::default(),
+            x_homing: HomingType::default(),
+            pending_jog: None,
+            diagnostics_menu: false,
+            diagnostics: false,
+            sensor_raw: None,
+            lock_during_motion: false,
         }
     }
 
@@ -136,9 +351,649 @@ impl Controller {
         self.scheduler.load_jars(jars);
     }
 
+    /// Load configured heaters, in heater index order
+    ///
+    /// Jars pick their heater from these by name.
+    pub fn load_heaters(&mut self, heaters: &[HeaterConfig]) {
+        self.scheduler.load_heaters(heaters);
+    }
+
+    /// Number of heaters the machine has
+    pub fn heater_count(&self) -> u8 {
+        self.scheduler.capabilities().heater_count
+    }
+
+    /// Load stored PID calibration for the autotuned heater
+    pub fn load_calibration(&mut self, calibration: &CalibrationData) {
+        self.active_pid = calibration
+            .get(AUTOTUNE_HEATER_INDEX)
+            .map(|c| (c.kp_x100, c.ki_x100, c.kd_x100));
+    }
+
+    /// Configure a program to start automatically once idle
+    ///
+    /// Must be called after `load_config`. Returns false if no program
+    /// with that label exists, in which case autostart stays disabled.
+    pub fn set_autostart_program(&mut self, label: &str) -> bool {
+        self.autostart_program = self
+            .programs
+            .iter()
+            .position(|p| p.label.as_str() == label)
+            .map(|i| i as u8);
+        self.autostart_program.is_some()
+    }
+
+    /// Fault because the config can't be run
+    ///
+    /// Flash held no valid config and fallback is disabled, or the config
+    /// names hardware that couldn't be set up. Call before `boot_complete`.
+    /// Unlike other faults a config error can't be acknowledged: only a
+    /// valid config and a restart clear it.
+    pub fn fault_config_error(&mut self) {
+        self.transition(Event::ErrorDetected(ErrorKind::ConfigError));
+    }
+
     /// Complete boot sequence
-    pub fn boot_complete(&mut self) {
+    ///
+    /// If an autostart program is configured it is selected and started
+    /// straight away; the returned event is the one from starting it.
+    pub fn boot_complete(&mut self) -> Option<Event> {
         self.transition(Event::BootComplete);
+        self.autostart()
+    }
+
+    /// Start the autostart program (once) if the machine is idle
+    ///
+    /// Skipped after a watchdog reset, so a program that hung the board
+    /// isn't restarted unattended.
+    fn autostart(&mut self) -> Option<Event> {
+        if self.state != State::Idle {
+            return None;
+        }
+        if self.recovery.is_some() {
+            self.autostart_program = None;
+            return None;
+        }
+        let index = self.autostart_program.take()?;
+        self.selected_program = index;
+        self.transition(Event::SelectProgram);
+        self.start_program()
+    }
+
+    /// Set the order in which the Z and X axes home
+    pub fn set_homing_order(&mut self, order: HomingOrder) {
+        self.homing_order = order;
+    }
+
+    /// Set how each axis homes
+    pub fn set_homing_types(&mut self, z: HomingType, x: HomingType) {
+        self.z_homing = z;
+        self.x_homing = x;
+    }
+
+    /// How `axis` homes
+    fn homing_type(&self, axis: Axis) -> HomingType {
+        match axis {
+            Axis::Z => self.z_homing,
+            Axis::X => self.x_homing,
+        }
+    }
+
+    /// Start homing the configured axes
+    ///
+    /// Only valid during boot. Axes with homing type `none` are taken as
+    /// homed where they are. Returns false if there is nothing left to
+    /// home, in which case `boot_complete` can be called straight away.
+    pub fn start_homing(&mut self) -> bool {
+        if self.state != State::Boot {
+            return false;
+        }
+        let caps = self.scheduler.capabilities();
+        let mut sequence = HomingSequence::new(self.homing_order, caps.has_z, caps.has_x);
+        self.skip_unhomed_axes(&mut sequence);
+        if sequence.is_complete() {
+            return false;
+        }
+        self.homing = Some(sequence);
+        true
+    }
+
+    /// Mark active axes without homing as homed, in order
+    fn skip_unhomed_axes(&self, sequence: &mut HomingSequence) {
+        loop {
+            let skip = [Axis::Z, Axis::X].into_iter().find(|&axis| {
+                sequence.is_active(axis) && self.homing_type(axis) == HomingType::None
+            });
+            match skip {
+                Some(axis) => sequence.axis_homed(axis),
+                None => break,
+            }
+        }
+    }
+
+    /// Set up endstop homing for `axis`
+    pub fn set_axis_homing(&mut self, axis: Axis, config: HomingConfig, endstop: Endstop) {
+        self.axis_homing[axis as usize] = Some(Homing::new(config, endstop));
+    }
+
+    /// Whether `axis` should be seeking its endstop now
+    pub fn is_homing(&self, axis: Axis) -> bool {
+        self.homing.is_some_and(|h| h.is_active(axis))
+            && self.homing_type(axis) == HomingType::Endstop
+    }
+
+    /// Axis waiting for the user to jog it home and confirm
+    pub fn manual_homing_axis(&self) -> Option<Axis> {
+        let homing = self.homing?;
+        [Axis::Z, Axis::X]
+            .into_iter()
+            .find(|&axis| homing.is_active(axis) && self.homing_type(axis) == HomingType::Manual)
+    }
+
+    /// Take the pending manual homing jog (axis, mm)
+    pub fn take_homing_jog(&mut self) -> Option<(Axis, i32)> {
+        self.pending_jog.take()
+    }
+
+    /// Handle an endstop report from an axis while homing
+    ///
+    /// `moved_um` is the distance the axis moved since its last report.
+    /// The first report from an axis seeking its endstop starts its
+    /// homing sequence. Once every configured axis has homed, boot
+    /// completes and the event from `boot_complete` is returned
+    /// (`BootComplete` if no program was autostarted); an axis giving up
+    /// returns the `HomingFailed` fault event. Reports from axes that
+    /// aren't seeking their endstop yet are ignored.
+    pub fn handle_axis_report(
+        &mut self,
+        axis: Axis,
+        endstop_high: bool,
+        moved_um: u32,
+    ) -> Option<Event> {
+        if !self.is_homing(axis) {
+            return None;
+        }
+        let homing = self.axis_homing[axis as usize].as_mut()?;
+        let phase = if homing.phase() == HomingPhase::Idle {
+            homing.start(endstop_high);
+            homing.phase()
+        } else {
+            homing.update(endstop_high, moved_um)
+        };
+        self.homing_progress(axis, phase)
+    }
+
+    /// Move on once `axis` has homed or given up
+    fn homing_progress(&mut self, axis: Axis, phase: HomingPhase) -> Option<Event> {
+        match phase {
+            HomingPhase::Homed => self.axis_homed(axis),
+            HomingPhase::Failed(_) => self.homing_failed(axis),
+            _ => None,
+        }
+    }
+
+    /// Count time spent homing by the axes seeking their endstop
+    fn tick_homing(&mut self, delta_ms: u32) -> Option<Event> {
+        for axis in [Axis::Z, Axis::X] {
+            if !self.is_homing(axis) {
+                continue;
+            }
+            let Some(homing) = self.axis_homing[axis as usize].as_mut() else {
+                continue;
+            };
+            let phase = homing.update_time(delta_ms);
+            if let Some(event) = self.homing_progress(axis, phase) {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    /// Motion `axis` should make for homing now
+    pub fn homing_move(&self, axis: Axis) -> HomingMove {
+        match &self.axis_homing[axis as usize] {
+            Some(homing) if self.is_homing(axis) => homing.motion(),
+            _ => HomingMove::Stop,
+        }
+    }
+
+    /// Take the homing motion for `axis` if it changed since last taken
+    pub fn take_homing_move(&mut self, axis: Axis) -> Option<HomingMove> {
+        let motion = self.homing_move(axis);
+        let sent = &mut self.sent_homing_move[axis as usize];
+        if *sent == motion {
+            return None;
+        }
+        *sent = motion;
+        Some(motion)
+    }
+
+    /// Handle an axis that gave up homing after its grace retry
+    ///
+    /// Homing stops and the machine faults with `HomingFailed`; the fault
+    /// event is returned. Failures from axes that aren't seeking their
+    /// endstop are ignored.
+    fn homing_failed(&mut self, axis: Axis) -> Option<Event> {
+        if !self.is_homing(axis) {
+            return None;
+        }
+        self.homing = None;
+        let event = Event::ErrorDetected(ErrorKind::HomingFailed);
+        self.transition(event);
+        Some(event)
+    }
+
+    /// Stop homing if `token` has been cancelled
+    ///
+    /// Call every tick while booting. Cancelling leaves the axes unhomed,
+    /// so the machine faults with `HomingFailed`; the fault event is
+    /// returned.
+    pub fn check_homing_cancel(&mut self, token: &CancelToken) -> Option<Event> {
+        if self.homing.is_none() || !token.is_cancelled() {
+            return None;
+        }
+        for homing in self.axis_homing.iter_mut().flatten() {
+            homing.check_cancel(token);
+        }
+        self.homing = None;
+        self.pending_jog = None;
+        let event = Event::ErrorDetected(ErrorKind::HomingFailed);
+        self.transition(event);
+        Some(event)
+    }
+
+    /// Record that `axis` is home and move on to the next one
+    fn axis_homed(&mut self, axis: Axis) -> Option<Event> {
+        let mut homing = self.homing?;
+        homing.axis_homed(axis);
+        self.skip_unhomed_axes(&mut homing);
+        if !homing.is_complete() {
+            self.homing = Some(homing);
+            return None;
+        }
+        self.homing = None;
+        self.pending_jog = None;
+        self.boot_complete().or(Some(Event::BootComplete))
+    }
+
+    /// Handle input while booting: jog and confirm a manually homed axis
+    ///
+    /// A long press while homing returns `Abort`; the caller cancels the
+    /// operation and homing stops at the next `check_homing_cancel`.
+    fn handle_homing_input(&mut self, input: InputEvent) -> Option<Event> {
+        if self.homing.is_some() && input == InputEvent::EncoderLongPress {
+            return Some(Event::Abort);
+        }
+        let axis = self.manual_homing_axis()?;
+        let step_mm = match input {
+            InputEvent::EncoderCw => 1,
+            InputEvent::EncoderCcw => -1,
+            InputEvent::EncoderClick => {
+                // Confirmed: the axis is home
+                self.pending_jog = None;
+                return self.axis_homed(axis);
+            }
+            _ => return None,
+        };
+        let jog_mm = match self.pending_jog {
+            Some((jog_axis, mm)) if jog_axis == axis => mm + step_mm,
+            _ => step_mm,
+        };
+        self.pending_jog = Some((axis, jog_mm));
+        None
+    }
+
+    /// Report that the board was reset by the watchdog
+    ///
+    /// Call before `boot_complete`. The idle screen shows the notice until
+    /// the user clicks it away.
+    pub fn set_recovery_notice(&mut self, notice: RecoveryNotice) {
+        self.recovery = Some(notice);
+    }
+
+    /// Watchdog reset notice to show instead of the program menu
+    pub fn recovery_notice(&self) -> Option<&RecoveryNotice> {
+        if self.state == State::Idle {
+            self.recovery.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Take the current breadcrumb if it changed since the last call
+    ///
+    /// The breadcrumb is the state and program step, persisted so the
+    /// recovery screen can show what the machine was doing after a reset.
+    pub fn take_breadcrumb(&mut self) -> Option<Breadcrumb> {
+        let crumb = Breadcrumb::new(self.state, self.current_step_num());
+        if self.recorded_breadcrumb == Some(crumb) {
+            return None;
+        }
+        self.recorded_breadcrumb = Some(crumb);
+        Some(crumb)
+    }
+
+    /// Set the encoder button bindings
+    pub fn set_keymap(&mut self, keymap: &Keymap) {
+        self.keymap = keymap.clone();
+    }
+
+    /// Set how the motor stops between program steps
+    pub fn set_stop_behavior(&mut self, behavior: StopBehavior) {
+        self.scheduler.set_stop_behavior(behavior);
+    }
+
+    /// Set the machine spin-off RPM ceiling (None = no limit)
+    pub fn set_max_spinoff_rpm(&mut self, max_rpm: Option<u16>) {
+        self.max_spinoff_rpm = max_rpm;
+        self.apply_spinoff_limit();
+    }
+
+    /// Set the spin-off RPM ceiling applied while quiet mode is on
+    pub fn set_quiet_spinoff_rpm(&mut self, rpm: u16) {
+        self.quiet_spinoff_rpm = rpm;
+        self.apply_spinoff_limit();
+    }
+
+    /// Switch quiet mode on or off
+    ///
+    /// Quiet mode caps spin-off speed at the quiet ceiling from the next
+    /// spin-off on, and asks the stepper driver to stay in StealthChop.
+    pub fn set_quiet_mode(&mut self, enabled: bool) {
+        if enabled != self.quiet_mode {
+            self.quiet_mode = enabled;
+            self.quiet_changed = true;
+            self.apply_spinoff_limit();
+        }
+    }
+
+    /// Get whether quiet mode is on
+    pub fn quiet_mode(&self) -> bool {
+        self.quiet_mode
+    }
+
+    /// Offer the sensor diagnostics screen in the idle menu
+    pub fn set_diagnostics_menu(&mut self, enabled: bool) {
+        self.diagnostics_menu = enabled;
+    }
+
+    /// Check if the idle menu offers the diagnostics screen
+    pub fn diagnostics_menu(&self) -> bool {
+        self.diagnostics_menu
+    }
+
+    /// Lock the UI while the machine moves on its own
+    ///
+    /// See `ui_locked` for the phases covered.
+    pub fn set_lock_during_motion(&mut self, enabled: bool) {
+        self.lock_during_motion = enabled;
+    }
+
+    /// Check if input is locked out
+    ///
+    /// With the lock enabled, the UI is locked while an axis homes against
+    /// an endstop or stall, and while the basket turns to its park angle.
+    /// Only the abort binding is honoured meanwhile. Prompts that wait for
+    /// a click to continue are never locked.
+    pub fn ui_locked(&self) -> bool {
+        if !self.lock_during_motion {
+            return false;
+        }
+        match self.state {
+            // Manual homing needs the encoder to jog the axis
+            State::Boot => self.homing.is_some() && self.manual_homing_axis().is_none(),
+            State::AwaitingJar
+            | State::AwaitingSpinOff
+            | State::StepComplete
+            | State::ProgramComplete
+            | State::Error(_) => false,
+            _ => self.orienting,
+        }
+    }
+
+    /// Check if the diagnostics screen is shown
+    pub fn diagnostics_shown(&self) -> bool {
+        self.diagnostics
+    }
+
+    /// Check if diagnostics is selected in the menu
+    pub fn is_diagnostics_selected(&self) -> bool {
+        self.selected_program == DIAGNOSTICS_MENU_INDEX
+    }
+
+    /// Latest raw thermistor values
+    pub fn sensor_raw(&self) -> Option<SensorRaw> {
+        self.sensor_raw
+    }
+
+    /// Record raw thermistor values from the heater task
+    ///
+    /// Returns true if the diagnostics screen shows them and needs a redraw.
+    pub fn update_sensor_raw(&mut self, raw: SensorRaw) -> bool {
+        let changed = self.sensor_raw != Some(raw);
+        self.sensor_raw = Some(raw);
+        changed && self.diagnostics && self.state == State::Idle
+    }
+
+    /// Take the new quiet mode setting if it changed since the last call
+    pub fn take_quiet_change(&mut self) -> Option<bool> {
+        core::mem::take(&mut self.quiet_changed).then_some(self.quiet_mode)
+    }
+
+    /// Hand the scheduler the lower of the machine and quiet ceilings
+    fn apply_spinoff_limit(&mut self) {
+        let limit = match (self.max_spinoff_rpm, self.quiet_mode) {
+            (Some(max_rpm), true) => Some(max_rpm.min(self.quiet_spinoff_rpm)),
+            (None, true) => Some(self.quiet_spinoff_rpm),
+            (max_rpm, false) => max_rpm,
+        };
+        self.scheduler.set_max_spinoff_rpm(limit);
+    }
+
+    /// Enable spin-off imbalance detection (None = disabled)
+    ///
+    /// `threshold` is the largest `SG_RESULT` spread tolerated between
+    /// recent readings while spinning off.
+    pub fn set_imbalance_threshold(&mut self, threshold: Option<u16>) {
+        self.imbalance = threshold.map(ImbalanceDetector::new);
+    }
+
+    /// Reverse the basket to free a jam when it stalls while running
+    ///
+    /// A stall then reverses for `STALL_REVERSE_MS` before resuming the
+    /// commanded direction. Only after `MAX_STALL_REVERSALS` attempts in
+    /// one phase does the stall fault. When disabled a stall faults
+    /// immediately.
+    pub fn set_stall_reverse_recovery(&mut self, enabled: bool) {
+        self.stall_reverse_recovery = enabled;
+    }
+
+    /// Stagger heater and motor start-up by `stagger_ms` (0 = off)
+    ///
+    /// When a transition starts both at once, the motor starts first and
+    /// the heater follows once the stagger has elapsed, so their inrush
+    /// currents don't add up on a weak supply.
+    pub fn set_startup_stagger(&mut self, stagger_ms: u16) {
+        self.startup_stagger_ms = stagger_ms as u32;
+    }
+
+    /// Set what a heater temperature sensor fault does
+    ///
+    /// With `ContinueUnheated`, a sensor fault switches the heater off
+    /// while agitation carries on, and only faults the machine once it
+    /// has lasted `SUSTAINED_SENSOR_FAULT_MS`.
+    pub fn set_sensor_fault_policy(&mut self, policy: SensorFaultPolicy) {
+        self.sensor_fault_policy = policy;
+    }
+
+    /// Fault when the heater is on but the temperature fails to rise
+    ///
+    /// See [`ThermalRunawayConfig`]. None turns detection off.
+    pub fn set_thermal_runaway(&mut self, config: Option<ThermalRunawayConfig>) {
+        self.safety.set_thermal_runaway(config);
+    }
+
+    /// Send motor and heater commands only when they change
+    ///
+    /// Otherwise every input, tick and status update re-sends both, even
+    /// when nothing changed.
+    pub fn set_commands_on_change(&mut self, enabled: bool) {
+        self.commands_on_change = enabled;
+    }
+
+    /// Set how long a program may stay paused before aborting (0 = never)
+    pub fn set_max_pause(&mut self, max_pause_s: u16) {
+        self.max_pause_ms = max_pause_s as u32 * 1000;
+    }
+
+    /// Set how long the complete screen stays up before returning to idle
+    ///
+    /// 0 keeps it up until the user clicks.
+    pub fn set_complete_auto_return(&mut self, auto_return_s: u16) {
+        self.complete_auto_return_ms = auto_return_s as u32 * 1000;
+    }
+
+    /// Set how long a manual jar or step prompt waits before confirming itself
+    ///
+    /// 0 waits for a click.
+    pub fn set_auto_advance(&mut self, auto_advance_s: u16) {
+        self.auto_advance_ms = auto_advance_s as u32 * 1000;
+    }
+
+    /// Seconds left before the current prompt confirms itself
+    ///
+    /// None when auto-advance is off or no jar or step prompt is shown.
+    pub fn auto_advance_remaining_s(&self) -> Option<u32> {
+        let prompting = matches!(self.state, State::AwaitingJar | State::StepComplete);
+        if !prompting || self.auto_advance_ms == 0 {
+            return None;
+        }
+        let remaining_ms = self.auto_advance_ms.saturating_sub(self.prompt_ms);
+        Some(remaining_ms.div_ceil(1000))
+    }
+
+    /// Park the basket at `position` after each program
+    ///
+    /// Only takes effect on automated machines. `None` leaves the basket
+    /// wherever the last step left it. The move is only queued for
+    /// [`take_park_move`](Self::take_park_move); nothing drives it yet.
+    pub fn set_park_position(&mut self, position: Option<ParkPosition>) {
+        self.park_position = position;
+    }
+
+    /// Turn the basket to `angle_deg` between steps on manual machines
+    ///
+    /// None leaves the basket where it stopped.
+    pub fn set_park_angle(&mut self, angle_deg: Option<u16>) {
+        self.scheduler.set_park_angle(angle_deg);
+    }
+
+    /// Prompt for the first jar when a program starts on manual machines
+    pub fn set_prompt_first_jar(&mut self, enabled: bool) {
+        self.scheduler.set_prompt_first_jar(enabled);
+    }
+
+    /// Heat the next jar while the basket is carried over (automated only)
+    pub fn set_prewarm(&mut self, enabled: bool) {
+        self.scheduler.set_prewarm(enabled);
+    }
+
+    /// Cancel each program's net basket rotation with a spin at its end
+    pub fn set_balance_rotation(&mut self, balance: Option<BalanceConfig>) {
+        self.scheduler.set_balance(balance);
+    }
+
+    /// Take the pending basket orientation move (degrees)
+    ///
+    /// Requested when a manual step completes with a park angle set. The
+    /// next-jar prompt waits for `orientation_complete`.
+    pub fn take_orient_move(&mut self) -> Option<u16> {
+        self.pending_orient.take()
+    }
+
+    /// Report that a motor command stopped the basket short of its angle
+    ///
+    /// If the turn is still wanted it is requested again through
+    /// `take_orient_move`, to carry on from where the basket stopped.
+    /// Returns whether it was; after an abort it isn't.
+    pub fn orientation_interrupted(&mut self) -> bool {
+        if !self.orienting {
+            return false;
+        }
+        self.pending_orient = self.scheduler.park_orientation();
+        self.pending_orient.is_some()
+    }
+
+    /// Report that the basket reached its park angle
+    ///
+    /// Releases the next-jar prompt, which is returned.
+    pub fn orientation_complete(&mut self) -> Option<Event> {
+        if !self.orienting {
+            return None;
+        }
+        self.orienting = false;
+        self.transition(Event::PromptNextJar);
+        Some(Event::PromptNextJar)
+    }
+
+    /// Whether this machine has a heater
+    ///
+    /// Without one, profile temperatures are ignored and not displayed.
+    pub fn has_heater(&self) -> bool {
+        self.scheduler.capabilities().has_heater
+    }
+
+    /// Whether a remote soft reset may proceed
+    ///
+    /// Only allowed while idle, so a reset never interrupts a program,
+    /// autotune or an unacknowledged error.
+    pub fn soft_reset_allowed(&self) -> bool {
+        self.state == State::Idle
+    }
+
+    /// Take the pending park move, if a program just finished
+    pub fn take_park_move(&mut self) -> Option<ParkPosition> {
+        self.pending_park.take()
+    }
+
+    /// Set display heartbeat timing for link-loss detection
+    pub fn set_link_config(&mut self, link: &LinkConfig) {
+        self.safety.set_link_config(link);
+        self.auto_clear_faults = link.auto_clear;
+        self.reported_scheduler_state = link.report_scheduler_state.then(|| self.scheduler_state());
+    }
+
+    /// Scheduler state for a host dashboard
+    ///
+    /// Step, segment and elapsed time are zero while no program runs.
+    pub fn scheduler_state(&self) -> PicoMessage<'static> {
+        let step = self.scheduler.step_state();
+        PicoMessage::SchedulerState {
+            phase: self.scheduler.phase().to_byte(),
+            step: step.map_or(0, |step| step.step_index),
+            segment: step.map_or(0, |step| step.segment_index),
+            rpm: self.motor_command().rpm,
+            temp: self.heater_command().target.map(TemperatureC10::as_x10),
+            elapsed_s: step.map_or(0, |step| step.step_elapsed_s),
+        }
+    }
+
+    /// Take the scheduler state if it changed since last reported
+    ///
+    /// Always None unless the link config reports scheduler state.
+    pub fn take_scheduler_state_change(&mut self) -> Option<PicoMessage<'static>> {
+        let state = self.scheduler_state();
+        let last = self.reported_scheduler_state.as_mut()?;
+        if *last == state {
+            return None;
+        }
+        *last = state.clone();
+        Some(state)
+    }
+
+    /// Check if the current fault will clear by itself
+    pub fn fault_auto_clears(&self) -> bool {
+        matches!(self.state, State::Error(kind) if kind.recoverable() && self.auto_clear_faults)
     }
 
     /// Get current state
@@ -147,13 +1002,84 @@ impl Controller {
     }
 
     /// Get current motor command
+    ///
+    /// Reversed while a stall recovery is freeing a jam.
     pub fn motor_command(&self) -> MotorCommand {
-        self.scheduler.motor_command()
+        let cmd = self.scheduler.motor_command();
+        if self.stall_reverse_ms.is_some() {
+            MotorCommand::running(cmd.rpm, cmd.direction.opposite())
+        } else {
+            cmd
+        }
+    }
+
+    /// Take a pending motor or heater command change
+    ///
+    /// Returns true once after a stall recovery starts or ends, or the
+    /// start-up stagger releases the heater, so the new commands can be
+    /// sent without waiting for a state change.
+    pub fn take_command_update(&mut self) -> bool {
+        core::mem::take(&mut self.command_update)
     }
 
     /// Get current heater command
+    ///
+    /// For the current jar's heater; see [`Controller::heater_command_for`].
     pub fn heater_command(&self) -> HeaterCommand {
-        self.scheduler.heater_command()
+        self.heater_command_for(self.scheduler.heater_command().heater)
+    }
+
+    /// Get the command for one heater
+    ///
+    /// Held off while the start-up stagger is running or the heater's
+    /// heating is suspended by a sensor fault.
+    pub fn heater_command_for(&self, heater: u8) -> HeaterCommand {
+        if self.stagger_remaining_ms.is_some() || self.heating_suspended_for(heater) {
+            HeaterCommand::off().for_heater(heater)
+        } else {
+            self.scheduler.heater_command_for(heater)
+        }
+    }
+
+    /// Take the motor command to send
+    ///
+    /// With commands sent only on change, returns None while the command
+    /// matches the one last taken.
+    pub fn take_motor_command(&mut self) -> Option<MotorCommand> {
+        let cmd = self.motor_command();
+        if self.commands_on_change && self.sent_motor == Some(cmd) {
+            return None;
+        }
+        self.sent_motor = Some(cmd);
+        Some(cmd)
+    }
+
+    /// Take the command to send to one heater
+    ///
+    /// With commands sent only on change, returns None while the command
+    /// matches the one last taken for that heater.
+    pub fn take_heater_command(&mut self, heater: u8) -> Option<HeaterCommand> {
+        let cmd = self.heater_command_for(heater);
+        let sent = self.sent_heater.get_mut(heater as usize)?;
+        if self.commands_on_change && *sent == Some(cmd) {
+            return None;
+        }
+        *sent = Some(cmd);
+        Some(cmd)
+    }
+
+    /// Take the command to send to the ultrasonic module
+    ///
+    /// Off whenever the current step isn't in an ultrasonic jar. With
+    /// commands sent only on change, returns None while the command
+    /// matches the one last taken.
+    pub fn take_ultrasonic_command(&mut self) -> Option<AccessoryCommand> {
+        let cmd = self.scheduler.ultrasonic_command();
+        if self.commands_on_change && self.sent_ultrasonic == Some(cmd) {
+            return None;
+        }
+        self.sent_ultrasonic = Some(cmd);
+        Some(cmd)
     }
 
     /// Get selected program index
@@ -183,24 +1109,67 @@ impl Controller {
 
     /// Process an input event from the display
     pub fn process_input(&mut self, input: InputEvent) -> Option<Event> {
+        if self.ui_locked() {
+            return self.handle_locked_input(input);
+        }
+        if self.state == State::Boot {
+            return self.handle_homing_input(input);
+        }
         match input {
             InputEvent::EncoderCw => self.handle_encoder_cw(),
             InputEvent::EncoderCcw => self.handle_encoder_ccw(),
-            InputEvent::EncoderClick => self.handle_button_click(),
-            InputEvent::EncoderLongPress => self.handle_button_long_press(),
+            InputEvent::EncoderClick => self.handle_button(Button::Click),
+            InputEvent::EncoderLongPress => self.handle_button(Button::LongPress),
             InputEvent::EncoderRelease => None,
+            InputEvent::EncoderDoubleClick => self.handle_button(Button::DoubleClick),
         }
     }
 
-    /// Handle encoder clockwise rotation
-    fn handle_encoder_cw(&mut self) -> Option<Event> {
-        match self.state {
-            State::Idle => {
-                // Navigate program list (programs + autotune item)
-                if self.selected_program == AUTOTUNE_MENU_INDEX {
-                    // Wrap from autotune to first program
-                    self.selected_program = 0;
-                } else if self.selected_program >= self.programs.len() as u8 - 1 {
+    /// Handle input while the UI is locked: only an abort gets through
+    fn handle_locked_input(&mut self, input: InputEvent) -> Option<Event> {
+        let button = match input {
+            InputEvent::EncoderClick => Button::Click,
+            InputEvent::EncoderLongPress => Button::LongPress,
+            InputEvent::EncoderDoubleClick => Button::DoubleClick,
+            _ => return None,
+        };
+        let category = StateCategory::of(self.state)?;
+        match self.keymap.action(category, button) {
+            KeyAction::Abort => self.abort_action(),
+            _ => None,
+        }
+    }
+
+    /// Handle a button gesture through the keymap
+    fn handle_button(&mut self, button: Button) -> Option<Event> {
+        let category = StateCategory::of(self.state)?;
+        match self.keymap.action(category, button) {
+            KeyAction::Ignore => None,
+            KeyAction::Select => self.select_action(),
+            KeyAction::Back => self.back_action(),
+            KeyAction::Abort => self.abort_action(),
+            KeyAction::ToggleQuiet => {
+                self.set_quiet_mode(!self.quiet_mode);
+                None
+            }
+        }
+    }
+
+    /// Handle encoder clockwise rotation
+    fn handle_encoder_cw(&mut self) -> Option<Event> {
+        match self.state {
+            State::Idle if self.diagnostics => None,
+            State::Idle => {
+                // Navigate program list (programs + autotune and diagnostics items)
+                if self.selected_program == AUTOTUNE_MENU_INDEX && self.diagnostics_menu {
+                    // Move to diagnostics
+                    self.selected_program = DIAGNOSTICS_MENU_INDEX;
+                } else if self.selected_program == AUTOTUNE_MENU_INDEX
+                    || self.selected_program == DIAGNOSTICS_MENU_INDEX
+                {
+                    // Wrap from the last item to first program
+                    self.selected_program = 0;
+                } else if self.selected_program >= self.programs.len() as u8 - 1 {
                     // Move to autotune
                     self.selected_program = AUTOTUNE_MENU_INDEX;
                 } else {
@@ -208,8 +1177,9 @@ impl Controller {
                 }
                 None
             }
-            State::ProgramSelected | State::EditProgram => {
-                // Could adjust parameters here
+            State::ProgramSelected => self.open_profile_edit(),
+            State::EditProgram => {
+                self.adjust_profile_edit(true);
                 None
             }
             _ => None,
@@ -219,13 +1189,20 @@ impl Controller {
     /// Handle encoder counter-clockwise rotation
     fn handle_encoder_ccw(&mut self) -> Option<Event> {
         match self.state {
+            State::Idle if self.diagnostics => None,
             State::Idle => {
-                // Navigate program list backwards (programs + autotune item)
-                if self.selected_program == AUTOTUNE_MENU_INDEX {
+                // Navigate program list backwards (programs + autotune and diagnostics items)
+                if self.selected_program == DIAGNOSTICS_MENU_INDEX {
+                    // Move from diagnostics to autotune
+                    self.selected_program = AUTOTUNE_MENU_INDEX;
+                } else if self.selected_program == AUTOTUNE_MENU_INDEX {
                     // Move from autotune to last program
                     if !self.programs.is_empty() {
                         self.selected_program = (self.programs.len() - 1) as u8;
                     }
+                } else if self.selected_program == 0 && self.diagnostics_menu {
+                    // Wrap to diagnostics
+                    self.selected_program = DIAGNOSTICS_MENU_INDEX;
                 } else if self.selected_program == 0 {
                     // Wrap to autotune
                     self.selected_program = AUTOTUNE_MENU_INDEX;
@@ -234,8 +1211,9 @@ impl Controller {
                 }
                 None
             }
-            State::ProgramSelected | State::EditProgram => {
-                // Could adjust parameters here
+            State::ProgramSelected => self.open_profile_edit(),
+            State::EditProgram => {
+                self.adjust_profile_edit(false);
                 None
             }
             _ => None,
@@ -243,10 +1221,21 @@ impl Controller {
     }
 
     /// Handle button click
-    fn handle_button_click(&mut self) -> Option<Event> {
+    fn select_action(&mut self) -> Option<Event> {
+        if self.orienting {
+            // The step is over; its prompt follows once the basket is turned
+            return None;
+        }
         match self.state {
             State::Idle => {
-                if self.selected_program == AUTOTUNE_MENU_INDEX {
+                if self.recovery.take().is_some() {
+                    // Dismiss the recovery notice, back to the menu
+                    None
+                } else if self.selected_program == DIAGNOSTICS_MENU_INDEX {
+                    // Open or close the diagnostics screen
+                    self.diagnostics = !self.diagnostics;
+                    None
+                } else if self.selected_program == AUTOTUNE_MENU_INDEX {
                     // Show autotune confirmation screen
                     self.autotune_phase = AutotunePhase::Confirming;
                     self.autotune_peaks = 0;
@@ -266,6 +1255,18 @@ impl Controller {
                 // Start program
                 self.start_program()
             }
+            State::EditProgram => match self.profile_edit.as_mut() {
+                Some(edit) => match edit.save_prompt {
+                    Some(save) => self.answer_save_prompt(save),
+                    None if edit.field == EditField::Rpm => {
+                        edit.field = EditField::Time;
+                        None
+                    }
+                    // Done with this profile, on to the next step
+                    None => self.finish_profile_edit(false),
+                },
+                None => None,
+            },
             State::AwaitingJar | State::AwaitingSpinOff => {
                 // User confirms basket position
                 self.scheduler.user_confirm();
@@ -275,6 +1276,7 @@ impl Controller {
             State::Running => {
                 // Pause
                 self.scheduler.pause();
+                self.paused_ms = 0;
                 self.transition(Event::Pause);
                 Some(Event::Pause)
             }
@@ -298,6 +1300,8 @@ impl Controller {
                 self.transition(Event::Back);
                 Some(Event::Back)
             }
+            // Needs a valid config and a restart
+            State::Error(ErrorKind::ConfigError) => None,
             State::Error(_) => {
                 // Acknowledge error
                 self.transition(Event::AcknowledgeError);
@@ -305,7 +1309,12 @@ impl Controller {
             }
             State::Autotuning => {
                 match self.autotune_phase {
-                    AutotunePhase::Confirming => {
+                    AutotunePhase::Confirming if self.active_pid.is_some() => {
+                        // Stored calibration would be replaced - ask again
+                        self.autotune_phase = AutotunePhase::ConfirmOverwrite;
+                        None
+                    }
+                    AutotunePhase::Confirming | AutotunePhase::ConfirmOverwrite => {
                         // User confirmed - actually start autotune
                         self.autotune_phase = AutotunePhase::Running;
                         Some(Event::StartAutotune)
@@ -334,23 +1343,26 @@ impl Controller {
         }
     }
 
-    /// Handle button long press
-    fn handle_button_long_press(&mut self) -> Option<Event> {
+    /// Abort the program or cancel autotune (long press by default)
+    fn abort_action(&mut self) -> Option<Event> {
         match self.state {
-            State::Running | State::Paused | State::SpinOff => {
-                // Abort
+            State::Running
+            | State::Paused
+            | State::SpinOff
+            | State::AwaitingJar
+            | State::AwaitingSpinOff
+            | State::StepComplete => {
+                // Abort - also covers the transitions between jars, where a
+                // click confirms the basket move or advances the step
+                // instead. Aborting the scheduler stops the motor and heater
+                // so the basket is left parked where it is.
                 self.scheduler.abort();
                 self.transition(Event::Abort);
                 Some(Event::Abort)
             }
-            State::ProgramSelected => {
-                // Back to idle
-                self.transition(Event::Back);
-                Some(Event::Back)
-            }
             State::Autotuning => {
                 match self.autotune_phase {
-                    AutotunePhase::Confirming => {
+                    AutotunePhase::Confirming | AutotunePhase::ConfirmOverwrite => {
                         // Go back to idle without starting
                         self.autotune_phase = AutotunePhase::Confirming;
                         self.transition(Event::CancelAutotune);
@@ -376,6 +1388,162 @@ impl Controller {
         }
     }
 
+    /// Step back a screen (double-click by default)
+    ///
+    /// Only steps back through menus; never pauses or aborts a program.
+    fn back_action(&mut self) -> Option<Event> {
+        match self.state {
+            State::Idle if self.diagnostics => {
+                // Close diagnostics, back to its menu entry
+                self.diagnostics = false;
+                None
+            }
+            State::Idle => {
+                // Jump back to the top of the program list
+                self.selected_program = 0;
+                None
+            }
+            State::EditProgram => match self.profile_edit.as_mut() {
+                Some(edit) if edit.save_prompt.is_some() => {
+                    // Back to editing without answering
+                    edit.save_prompt = None;
+                    None
+                }
+                _ => self.finish_profile_edit(true),
+            },
+            State::ProgramSelected | State::ProgramComplete => {
+                self.transition(Event::Back);
+                Some(Event::Back)
+            }
+            State::Autotuning => match self.autotune_phase {
+                AutotunePhase::Confirming | AutotunePhase::ConfirmOverwrite => {
+                    // Leave the confirmation screen without starting
+                    self.autotune_phase = AutotunePhase::Confirming;
+                    self.transition(Event::CancelAutotune);
+                    None
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Open the edit screen on the selected program's first profile
+    fn open_profile_edit(&mut self) -> Option<Event> {
+        self.profile_edit = Some(self.profile_edit_for(0)?);
+        self.transition(Event::EditParameter);
+        Some(Event::EditParameter)
+    }
+
+    /// Working copy of the profile used by `step` of the selected program
+    fn profile_edit_for(&self, step: u8) -> Option<ProfileEdit> {
+        let program = self.programs.get(self.selected_program as usize)?;
+        let name = &program.steps.get(step as usize)?.profile;
+        let profile_index = self.profiles.iter().position(|p| &p.label == name)?;
+        Some(ProfileEdit {
+            step,
+            profile_index: profile_index as u8,
+            profile: self.profiles[profile_index].clone(),
+            field: EditField::Rpm,
+            save_prompt: None,
+            exit_after: false,
+        })
+    }
+
+    /// Turn the encoder on the edit screen
+    ///
+    /// Adjusts the current value, or moves between Yes and No while the
+    /// save prompt is shown.
+    fn adjust_profile_edit(&mut self, up: bool) {
+        let Some(edit) = self.profile_edit.as_mut() else {
+            return;
+        };
+        if let Some(save) = edit.save_prompt.as_mut() {
+            *save = !*save;
+            return;
+        }
+        let (value, step) = match edit.field {
+            EditField::Rpm => (&mut edit.profile.rpm, EDIT_RPM_STEP),
+            EditField::Time => (&mut edit.profile.time_s, EDIT_TIME_STEP_S),
+        };
+        *value = if up {
+            value.saturating_add(step)
+        } else {
+            value.saturating_sub(step).max(step)
+        };
+    }
+
+    /// Check if the working copy differs from the loaded profile
+    fn profile_edit_dirty(&self) -> bool {
+        self.profile_edit.as_ref().is_some_and(|edit| {
+            let stored = &self.profiles[edit.profile_index as usize];
+            edit.profile.rpm != stored.rpm || edit.profile.time_s != stored.time_s
+        })
+    }
+
+    /// Leave the current profile, asking first if it was changed
+    ///
+    /// Moves on to the next step's profile, or back to the program screen
+    /// after the last step or when `exit` is set.
+    fn finish_profile_edit(&mut self, exit: bool) -> Option<Event> {
+        if self.profile_edit_dirty() {
+            if let Some(edit) = self.profile_edit.as_mut() {
+                edit.save_prompt = Some(true);
+                edit.exit_after = exit;
+            }
+            return None;
+        }
+        self.next_profile_edit(exit)
+    }
+
+    /// Answer the "Save changes?" prompt
+    ///
+    /// Yes applies the edit and queues it for saving to flash; No drops
+    /// the working copy, leaving both the loaded and stored profile as
+    /// they were.
+    fn answer_save_prompt(&mut self, save: bool) -> Option<Event> {
+        let edit = self.profile_edit.as_ref()?;
+        let exit = edit.exit_after;
+        if save {
+            let index = edit.profile_index;
+            let profile = edit.profile.clone();
+            self.profiles[index as usize] = profile.clone();
+            self.scheduler.load_profiles(&self.profiles);
+            self.pending_profile_save = Some((index, profile));
+        }
+        self.next_profile_edit(exit)
+    }
+
+    /// Show the next step's profile, or leave the edit screen
+    fn next_profile_edit(&mut self, exit: bool) -> Option<Event> {
+        let next = self.profile_edit.as_ref().map_or(0, |edit| edit.step + 1);
+        self.profile_edit = if exit {
+            None
+        } else {
+            self.profile_edit_for(next)
+        };
+        if self.profile_edit.is_some() {
+            return None;
+        }
+        let event = if exit {
+            Event::Back
+        } else {
+            Event::ConfirmEdit
+        };
+        self.transition(event);
+        Some(event)
+    }
+
+    /// Get the profile edit in progress
+    pub fn profile_edit(&self) -> Option<&ProfileEdit> {
+        self.profile_edit.as_ref()
+    }
+
+    /// Take a confirmed profile edit for saving to flash
+    pub fn take_profile_save(&mut self) -> Option<(u8, ProfileConfig)> {
+        self.pending_profile_save.take()
+    }
+
     /// Start the currently selected program
     fn start_program(&mut self) -> Option<Event> {
         if let Some(program) = self.programs.get(self.selected_program as usize) {
@@ -383,6 +1551,10 @@ impl Controller {
                 self.transition(event);
                 return Some(event);
             }
+            if self.scheduler.phase() == ExecutionPhase::Idle {
+                // Rejected, e.g. a step with a zero-length profile
+                return None;
+            }
             // Program started successfully
             self.transition(Event::Start);
             Some(Event::Start)
@@ -391,16 +1563,89 @@ impl Controller {
         }
     }
 
-    /// Update safety with temperature reading
-    pub fn update_temperature(&mut self, temp_x10: Option<i16>) {
-        self.safety.update_temperature(temp_x10);
+    /// Update safety with one heater's temperature reading
+    ///
+    /// A missing reading is a sensor fault. Continuing unheated, it holds
+    /// that heater off until readings return instead of faulting.
+    pub fn update_temperature_for(&mut self, heater: u8, temp: Option<TemperatureC10>) {
+        let Some(fault_ms) = self.sensor_fault_ms.get_mut(heater as usize) else {
+            return;
+        };
+        if temp.is_none() && self.sensor_fault_policy == SensorFaultPolicy::ContinueUnheated {
+            if fault_ms.is_none() {
+                *fault_ms = Some(0);
+                self.command_update = true;
+            }
+            return;
+        }
+        if temp.is_some() && fault_ms.take().is_some() {
+            self.command_update = true;
+        }
+        self.safety.update_temperature_for(heater, temp);
+    }
+
+    /// Update safety with one heater element's on/off state
+    pub fn update_heater_output_for(&mut self, heater: u8, on: bool) {
+        self.safety.update_heater_output_for(heater, on);
+    }
+
+    /// Check if a sensor fault has one heater off
+    pub fn heating_suspended_for(&self, heater: u8) -> bool {
+        self.sensor_fault_ms
+            .get(heater as usize)
+            .is_some_and(Option::is_some)
     }
 
     /// Update safety with motor stall status
+    ///
+    /// With stall reverse recovery enabled, a stall while running starts
+    /// a reversal instead of faulting, until the attempts run out.
     pub fn update_motor_stall(&mut self, stalled: bool) {
+        let recoverable = self.stall_reverse_recovery
+            && self.state == State::Running
+            && self.stall_reverse_ms.is_none()
+            && self.stall_reversals < MAX_STALL_REVERSALS
+            && self.scheduler.motor_command().rpm > 0;
+        if stalled && recoverable {
+            self.stall_reverse_ms = Some(STALL_REVERSE_MS);
+            self.stall_reversals += 1;
+            self.command_update = true;
+            return;
+        }
         self.safety.update_motor_stall(stalled);
     }
 
+    /// Feed a StallGuard `SG_RESULT` reading from the basket driver
+    ///
+    /// Readings only count while the basket is spinning off; at any other
+    /// time the detector starts over. An imbalance faults on the next tick.
+    pub fn update_stallguard(&mut self, sg_result: u16) {
+        let spinning_off = self.state == State::SpinOff && self.motor_command().rpm > 0;
+        let Some(detector) = self.imbalance.as_mut() else {
+            return;
+        };
+        if !spinning_off {
+            detector.reset();
+        } else if detector.update(sg_result) {
+            self.safety.update_imbalance(true);
+        }
+    }
+
+    /// Update safety with the lid interlock status
+    ///
+    /// Ignored on machines without a lid. An open lid faults with
+    /// `LidOpen` on the next tick while the motor is commanded to move.
+    pub fn update_lid_open(&mut self, open: bool) {
+        if self.scheduler.capabilities().has_lid {
+            self.safety.update_lid_open(open);
+        }
+    }
+
+    /// Update safety with stepper driver fault status
+    pub fn update_driver_fault(&mut self, fault: Option<DriverFaultKind>) {
+        self.safety.update_driver_fault(fault);
+    }
+
     /// Record heartbeat from display
     pub fn heartbeat_received(&mut self) {
         self.safety.heartbeat_received();
@@ -416,14 +1661,92 @@ impl Controller {
 
         // Update safety monitor time tracking
         self.safety.update_time(delta_ms);
+        self.safety
+            .update_motor_commanded(self.motor_command().rpm > 0);
+
+        // A sensor fault that outlasts the grace period faults after all
+        for heater in 0..MAX_HEATERS {
+            if let Some(fault_ms) = self.sensor_fault_ms[heater] {
+                let fault_ms = fault_ms.saturating_add(delta_ms);
+                if fault_ms >= SUSTAINED_SENSOR_FAULT_MS {
+                    self.sensor_fault_ms[heater] = None;
+                    self.safety.update_temperature_for(heater as u8, None);
+                } else {
+                    self.sensor_fault_ms[heater] = Some(fault_ms);
+                }
+            }
+        }
 
         // Check safety conditions
-        if let SafetyStatus::Fault(kind) = self.safety.check() {
-            // Only transition to error if not already in error state
-            if !self.state.is_error() {
+        match self.safety.check() {
+            SafetyStatus::Fault(kind) => {
+                // Only transition to error if not already in error state
+                if !self.state.is_error() {
+                    self.scheduler.abort();
+                    self.transition(Event::ErrorDetected(kind));
+                    return Some(Event::ErrorDetected(kind));
+                }
+            }
+            SafetyStatus::Ok => {
+                // The program was aborted with the fault, so go back to idle
+                if self.fault_auto_clears() {
+                    self.transition(Event::AcknowledgeError);
+                    return Some(Event::AcknowledgeError);
+                }
+            }
+        }
+
+        // Time the homing attempts: one that runs out retries, then faults
+        if let Some(event) = self.tick_homing(delta_ms) {
+            return Some(event);
+        }
+
+        // Release the heater once the start-up stagger has elapsed
+        if let Some(remaining_ms) = self.stagger_remaining_ms {
+            let remaining_ms = remaining_ms.saturating_sub(delta_ms);
+            if remaining_ms == 0 {
+                self.stagger_remaining_ms = None;
+                self.command_update = true;
+            } else {
+                self.stagger_remaining_ms = Some(remaining_ms);
+            }
+        }
+
+        // Resume the commanded direction once a jam-clearing reversal ends
+        if let Some(remaining_ms) = self.stall_reverse_ms {
+            let remaining_ms = remaining_ms.saturating_sub(delta_ms);
+            if remaining_ms == 0 {
+                self.stall_reverse_ms = None;
+                self.command_update = true;
+            } else {
+                self.stall_reverse_ms = Some(remaining_ms);
+            }
+        }
+
+        // Abort runs left paused for too long
+        if self.state == State::Paused && self.max_pause_ms > 0 {
+            self.paused_ms = self.paused_ms.saturating_add(delta_ms);
+            if self.paused_ms >= self.max_pause_ms {
                 self.scheduler.abort();
-                self.transition(Event::ErrorDetected(kind));
-                return Some(Event::ErrorDetected(kind));
+                self.transition(Event::Abort);
+                return Some(Event::Abort);
+            }
+        }
+
+        // Leave the complete screen on its own if configured
+        if self.state == State::ProgramComplete && self.complete_auto_return_ms > 0 {
+            self.complete_ms = self.complete_ms.saturating_add(delta_ms);
+            if self.complete_ms >= self.complete_auto_return_ms {
+                self.transition(Event::Back);
+                return Some(Event::Back);
+            }
+        }
+
+        // Confirm a manual prompt on its own if configured
+        if self.auto_advance_remaining_s().is_some() {
+            self.prompt_ms = self.prompt_ms.saturating_add(delta_ms);
+            if self.prompt_ms >= self.auto_advance_ms {
+                return self.select_action();
             }
         }
 
@@ -432,10 +1755,39 @@ impl Controller {
             // Convert delta to seconds for scheduler (rough, accumulates error)
             let delta_s = (delta_ms / 1000) as u16;
             if delta_s > 0 {
+                let commands = (
+                    self.scheduler.motor_command(),
+                    self.scheduler.heater_command(),
+                );
                 if let Some(event) = self.scheduler.tick(delta_s) {
+                    if event == Event::StartSpinOff {
+                        // No Z axis motion yet: the lift is treated as instant
+                        self.scheduler.lift_complete();
+                    }
+                    if event == Event::NextStep {
+                        // Automated machines go on to the next jar unprompted
+                        self.scheduler.advance_step();
+                    }
+                    if event == Event::PromptNextJar {
+                        if let Some(angle) = self.scheduler.park_orientation() {
+                            // Prompt once the basket is turned for loading
+                            self.pending_orient = Some(angle);
+                            self.orienting = true;
+                            return None;
+                        }
+                    }
                     self.transition(event);
                     return Some(event);
                 }
+                // A new segment (reversal, end of prime) changes commands without an event
+                if commands
+                    != (
+                        self.scheduler.motor_command(),
+                        self.scheduler.heater_command(),
+                    )
+                {
+                    self.command_update = true;
+                }
             }
         }
 
@@ -445,6 +1797,43 @@ impl Controller {
     /// Perform state transition
     fn transition(&mut self, event: Event) {
         self.state = self.state.transition(event);
+
+        // Diagnostics only overlay the idle menu
+        if self.state != State::Idle {
+            self.diagnostics = false;
+        }
+
+        // A reversal never outlives the phase it started in, and each
+        // new phase gets a fresh set of attempts (pausing keeps them)
+        self.stall_reverse_ms = None;
+        if self.state != State::Paused && self.state != State::Running {
+            self.stall_reversals = 0;
+        }
+
+        // Stagger start-up when motor and heater would switch on together
+        let motor_on = self.scheduler.motor_command().rpm > 0;
+        let heater_on = self.scheduler.heater_command().target.is_some();
+        if !heater_on {
+            self.stagger_remaining_ms = None;
+        } else if motor_on
+            && !self.motor_was_on
+            && !self.heater_was_on
+            && self.startup_stagger_ms > 0
+        {
+            self.stagger_remaining_ms = Some(self.startup_stagger_ms);
+        }
+        self.motor_was_on = motor_on;
+        self.heater_was_on = heater_on;
+
+        // An abort or fault cancels a basket orientation in progress
+        self.pending_orient = None;
+        self.orienting = false;
+
+        if event == Event::ProgramFinished && self.scheduler.capabilities().is_automated {
+            self.pending_park = self.park_position;
+        }
+        self.complete_ms = 0;
+        self.prompt_ms = 0;
     }
 
     /// Get elapsed time in current step (seconds)
@@ -460,6 +1849,21 @@ impl Controller {
         self.scheduler.step_total_s()
     }
 
+    /// Get current and total iterations of the current profile
+    pub fn current_iteration(&self) -> (u8, u8) {
+        self.scheduler.current_iteration()
+    }
+
+    /// Get elapsed time for the whole program (seconds)
+    pub fn program_elapsed_s(&self) -> u32 {
+        self.scheduler.program_elapsed_s()
+    }
+
+    /// Get planned total time for the whole program (seconds)
+    pub fn program_total_s(&self) -> u32 {
+        self.scheduler.program_total_s()
+    }
+
     /// Get current step number (1-indexed)
     pub fn current_step_num(&self) -> u8 {
         self.scheduler
@@ -477,8 +1881,19 @@ impl Controller {
     }
 
     /// Get current temperature in whole degrees (if available)
+    ///
+    /// Read from the current jar's heater, or the first heater when the
+    /// step is unheated or nothing is running.
     pub fn current_temp_c(&self) -> Option<i16> {
-        self.safety.get_temperature()
+        let heater = self
+            .scheduler
+            .step_state()
+            .and_then(|step| step.heater)
+            .unwrap_or(0);
+        if self.heating_suspended_for(heater) {
+            return None;
+        }
+        self.safety.get_temperature_for(heater)
     }
 
     // === Autotune methods ===
@@ -510,11 +1925,36 @@ impl Controller {
     }
 
     /// Set autotune result when complete (from heater task)
+    ///
+    /// The heater task switches to the new coefficients immediately, so
+    /// they also become the active PID values.
     pub fn set_autotune_complete(&mut self, kp_x100: i16, ki_x100: i16, kd_x100: i16) {
         self.autotune_result = Some((kp_x100, ki_x100, kd_x100));
+        self.active_pid = self.autotune_result;
+        self.calibration_saved = None;
         self.autotune_phase = AutotunePhase::Complete;
     }
 
+    /// Record the outcome of saving calibration to flash
+    pub fn set_calibration_saved(&mut self, ok: bool) {
+        self.calibration_saved = Some(ok);
+    }
+
+    /// Get the result of the last calibration save (None while pending)
+    pub fn calibration_saved(&self) -> Option<bool> {
+        self.calibration_saved
+    }
+
+    /// Get the active PID coefficients (×100), if calibrated
+    pub fn active_pid(&self) -> Option<(i16, i16, i16)> {
+        self.active_pid
+    }
+
+    /// Heater index autotune results are stored under
+    pub fn autotune_heater_index(&self) -> u8 {
+        AUTOTUNE_HEATER_INDEX
+    }
+
     /// Set autotune failure (from heater task)
     pub fn set_autotune_failed(&mut self, reason: AutotuneFailureReason) {
         self.autotune_failure = Some(reason);
@@ -541,7 +1981,7 @@ impl Controller {
 mod tests {
     use super::*;
     use heapless::String;
-    use isochron_core::scheduler::DirectionMode;
+    use isochron_core::scheduler::{DirectionMode, PrimeConfig, SpinOffConfig};
 
     fn make_profile(name: &str, rpm: u16, time_s: u16) -> ProfileConfig {
         let mut label = String::new();
@@ -579,6 +2019,7 @@ mod tests {
         }
         ProgramConfig {
             label,
+            notes: None,
             steps: step_vec,
         }
     }
@@ -614,6 +2055,117 @@ mod tests {
         assert_eq!(ctrl.state(), State::Running);
     }
 
+    /// Idle machine with a two-step program, on its program screen
+    fn edit_controller() -> Controller {
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+        let profiles = [
+            make_profile("Clean", 120, 60),
+            make_profile("Rinse", 100, 30),
+        ];
+        let jars = [make_jar("clean"), make_jar("rinse")];
+        let programs = [make_program(
+            "Test",
+            &[("clean", "Clean"), ("rinse", "Rinse")],
+        )];
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl
+    }
+
+    #[test]
+    fn test_profile_edit_save_prompt() {
+        let mut ctrl = edit_controller();
+
+        // Turning the encoder opens the first step's profile
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderCw),
+            Some(Event::EditParameter)
+        );
+        assert_eq!(ctrl.state(), State::EditProgram);
+        ctrl.process_input(InputEvent::EncoderCw);
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.profile_edit().unwrap().profile.rpm, 140);
+
+        // Leaving a changed profile asks first and saves nothing yet
+        assert_eq!(ctrl.process_input(InputEvent::EncoderDoubleClick), None);
+        assert_eq!(ctrl.state(), State::EditProgram);
+        assert_eq!(ctrl.profile_edit().unwrap().save_prompt, Some(true));
+        assert!(ctrl.take_profile_save().is_none());
+
+        // Yes queues the edit for flash and applies it to the next run
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::Back)
+        );
+        assert_eq!(ctrl.state(), State::ProgramSelected);
+        let (index, profile) = ctrl.take_profile_save().unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(profile.rpm, 140);
+        assert_eq!(profile.time_s, 60);
+        assert!(ctrl.take_profile_save().is_none());
+
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.current_profile().unwrap().rpm, 140);
+    }
+
+    #[test]
+    fn test_profile_edit_declined() {
+        let mut ctrl = edit_controller();
+        ctrl.process_input(InputEvent::EncoderCw); // Edit
+        ctrl.process_input(InputEvent::EncoderClick); // On to the time
+        ctrl.process_input(InputEvent::EncoderCcw);
+        assert_eq!(ctrl.profile_edit().unwrap().profile.time_s, 50);
+
+        // Clicking past the last value asks before the next step
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.profile_edit().unwrap().save_prompt, Some(true));
+
+        // No drops the change and moves on to the second step
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.profile_edit().unwrap().save_prompt, Some(false));
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        let edit = ctrl.profile_edit().unwrap();
+        assert_eq!((edit.step, edit.profile.label.as_str()), (1, "Rinse"));
+        assert!(ctrl.take_profile_save().is_none());
+
+        // Unchanged profiles leave without a prompt
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::ConfirmEdit)
+        );
+        assert_eq!(ctrl.state(), State::ProgramSelected);
+        assert!(ctrl.take_profile_save().is_none());
+
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.current_profile().unwrap().time_s, 60);
+    }
+
+    #[test]
+    fn test_agitation_only_without_heater() {
+        let mut ctrl = Controller::new(MachineCapabilities::from_config(false, false, false, 0));
+        assert!(!ctrl.has_heater());
+
+        let mut profile = make_profile("Clean", 120, 60);
+        profile.temperature_c = Some(40);
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &[profile], &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+
+        assert_eq!(ctrl.state(), State::Running);
+        assert!(ctrl.motor_command().rpm > 0);
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+    }
+
     #[test]
     fn test_pause_resume() {
         let mut ctrl = Controller::new(MachineCapabilities {
@@ -642,7 +2194,7 @@ mod tests {
     }
 
     #[test]
-    fn test_safety_override() {
+    fn test_double_click_goes_back() {
         let mut ctrl = Controller::new(MachineCapabilities {
             is_automated: true,
             ..Default::default()
@@ -650,64 +2202,142 @@ mod tests {
 
         let profiles = [make_profile("Clean", 120, 60)];
         let jars = [make_jar("clean")];
-        let programs = [make_program("Test", &[("clean", "Clean")])];
+        let programs = [
+            make_program("First", &[("clean", "Clean")]),
+            make_program("Second", &[("clean", "Clean")]),
+        ];
 
         ctrl.load_config(&programs, &profiles, &jars);
         ctrl.boot_complete();
-        ctrl.process_input(InputEvent::EncoderClick); // Select
-        ctrl.process_input(InputEvent::EncoderClick); // Start
-
-        assert_eq!(ctrl.state(), State::Running);
 
-        // Simulate over-temperature
-        ctrl.update_temperature(Some(560)); // 56°C > 55°C max
+        // Idle: jump back to the first program
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.selected_program(), 1);
+        assert_eq!(ctrl.process_input(InputEvent::EncoderDoubleClick), None);
+        assert_eq!(ctrl.selected_program(), 0);
+        assert_eq!(ctrl.state(), State::Idle);
 
-        // Tick should detect the fault
-        let event = ctrl.tick(100);
+        // Program detail: back to the menu
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.state(), State::ProgramSelected);
         assert_eq!(
-            event,
-            Some(Event::ErrorDetected(ErrorKind::OverTemperature))
+            ctrl.process_input(InputEvent::EncoderDoubleClick),
+            Some(Event::Back)
         );
-        assert!(matches!(
-            ctrl.state(),
-            State::Error(ErrorKind::OverTemperature)
-        ));
+        assert_eq!(ctrl.state(), State::Idle);
     }
 
     #[test]
-    fn test_encoder_navigation() {
-        let mut ctrl = Controller::new(MachineCapabilities::default());
+    fn test_double_click_ignored_while_running() {
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
 
-        let profiles = [
-            make_profile("Clean", 120, 60),
-            make_profile("Rinse", 100, 30),
-        ];
-        let jars = [make_jar("clean"), make_jar("rinse")];
-        let programs = [
-            make_program("Full", &[("clean", "Clean"), ("rinse", "Rinse")]),
-            make_program("Quick", &[("clean", "Clean")]),
-        ];
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
 
         ctrl.load_config(&programs, &profiles, &jars);
         ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
 
-        assert_eq!(ctrl.selected_program(), 0);
-
-        // Navigate CW
-        ctrl.process_input(InputEvent::EncoderCw);
-        assert_eq!(ctrl.selected_program(), 1);
+        assert_eq!(ctrl.process_input(InputEvent::EncoderDoubleClick), None);
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.motor_command().rpm, 120);
+    }
 
-        // Navigate CW wraps around
-        ctrl.process_input(InputEvent::EncoderCw);
-        assert_eq!(ctrl.selected_program(), 0);
+    fn paused_controller(max_pause_s: u16) -> Controller {
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
 
-        // Navigate CCW
-        ctrl.process_input(InputEvent::EncoderCcw);
-        assert_eq!(ctrl.selected_program(), 1);
+        let profiles = [make_profile("Clean", 120, 600)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.set_max_pause(max_pause_s);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        ctrl.process_input(InputEvent::EncoderClick); // Pause
+        assert_eq!(ctrl.state(), State::Paused);
+        ctrl
+    }
+
+    /// Tick once per second for `seconds`, keeping the display link alive
+    fn tick_seconds(ctrl: &mut Controller, now_ms: &mut u32, seconds: u32) -> Option<Event> {
+        let mut last = None;
+        for _ in 0..seconds {
+            *now_ms += 1000;
+            ctrl.heartbeat_received();
+            last = ctrl.tick(*now_ms).or(last);
+        }
+        last
     }
 
     #[test]
-    fn test_long_press_abort() {
+    fn test_unrunnable_program_not_started() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 0)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None); // Start
+        assert_eq!(ctrl.state(), State::ProgramSelected);
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_pause_timeout_aborts() {
+        let mut ctrl = paused_controller(5);
+        let mut now_ms = 0;
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 4), None);
+        assert_eq!(ctrl.state(), State::Paused);
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 1), Some(Event::Abort));
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+    }
+
+    #[test]
+    fn test_resume_cancels_pause_timeout() {
+        let mut ctrl = paused_controller(5);
+        let mut now_ms = 0;
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 4), None);
+        ctrl.process_input(InputEvent::EncoderClick); // Resume
+        assert_eq!(ctrl.state(), State::Running);
+
+        // Running past the limit is fine
+        tick_seconds(&mut ctrl, &mut now_ms, 10);
+        assert_eq!(ctrl.state(), State::Running);
+
+        // A new pause starts a fresh timer
+        ctrl.process_input(InputEvent::EncoderClick); // Pause
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 4), None);
+        assert_eq!(ctrl.state(), State::Paused);
+    }
+
+    #[test]
+    fn test_pause_timeout_disabled() {
+        let mut ctrl = paused_controller(0);
+        let mut now_ms = 0;
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 60), None);
+        assert_eq!(ctrl.state(), State::Paused);
+    }
+
+    #[test]
+    fn test_safety_override() {
         let mut ctrl = Controller::new(MachineCapabilities {
             is_automated: true,
             ..Default::default()
@@ -724,8 +2354,2036 @@ mod tests {
 
         assert_eq!(ctrl.state(), State::Running);
 
-        // Long press to abort
-        ctrl.process_input(InputEvent::EncoderLongPress);
-        assert_eq!(ctrl.state(), State::Idle);
+        // Simulate over-temperature
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(560))); // 56°C > 55°C max
+
+        // Tick should detect the fault
+        let event = ctrl.tick(100);
+        assert_eq!(
+            event,
+            Some(Event::ErrorDetected(ErrorKind::OverTemperature))
+        );
+        assert!(matches!(
+            ctrl.state(),
+            State::Error(ErrorKind::OverTemperature)
+        ));
+    }
+
+    /// Machine booted to the idle menu with a single-step "Test" program
+    /// running `profile` in `jar`
+    ///
+    /// `setup` configures the controller before the config is loaded.
+    fn booted_controller(
+        capabilities: MachineCapabilities,
+        profile: ProfileConfig,
+        jar: JarConfig,
+        setup: impl FnOnce(&mut Controller),
+    ) -> Controller {
+        let mut ctrl = Controller::new(capabilities);
+        setup(&mut ctrl);
+        let programs = [make_program(
+            "Test",
+            &[(jar.name.as_str(), profile.label.as_str())],
+        )];
+
+        ctrl.load_config(&programs, &[profile], &[jar]);
+        ctrl.boot_complete();
+        ctrl
+    }
+
+    /// Select and start the program on the idle menu
+    fn start_program(ctrl: &mut Controller) {
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.state(), State::Running);
+    }
+
+    /// Heated machine at 40°C running `profile`, after `setup`
+    fn started_controller(
+        capabilities: MachineCapabilities,
+        profile: ProfileConfig,
+        setup: impl FnOnce(&mut Controller),
+    ) -> Controller {
+        let mut ctrl = booted_controller(capabilities, profile, make_jar("clean"), setup);
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(400)));
+        start_program(&mut ctrl);
+        ctrl
+    }
+
+    fn running_controller() -> Controller {
+        started_controller(
+            MachineCapabilities::default(),
+            make_profile("Clean", 120, 60),
+            |_| {},
+        )
+    }
+
+    /// Heated machine starting `profile` with a start-up stagger of 500 ms
+    fn staggered_controller(profile: ProfileConfig) -> Controller {
+        started_controller(
+            MachineCapabilities::from_config(false, false, false, 1),
+            profile,
+            |ctrl| ctrl.set_startup_stagger(500),
+        )
+    }
+
+    /// Heated machine running a 45°C, 10 minute profile
+    fn heated_controller(policy: SensorFaultPolicy) -> Controller {
+        let mut profile = make_profile("Clean", 120, 600);
+        profile.temperature_c = Some(45);
+        let ctrl = started_controller(
+            MachineCapabilities::from_config(false, false, false, 1),
+            profile,
+            |ctrl| ctrl.set_sensor_fault_policy(policy),
+        );
+        assert!(ctrl.heater_command().target.is_some());
+        ctrl
+    }
+
+    #[test]
+    fn test_sensor_fault_aborts_by_default() {
+        let mut ctrl = heated_controller(SensorFaultPolicy::Abort);
+
+        ctrl.update_temperature_for(0, None);
+        assert_eq!(
+            ctrl.tick(100),
+            Some(Event::ErrorDetected(ErrorKind::ThermistorFault))
+        );
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+    }
+
+    #[test]
+    fn test_sensor_fault_continues_unheated() {
+        let mut ctrl = heated_controller(SensorFaultPolicy::ContinueUnheated);
+
+        ctrl.update_temperature_for(0, None);
+        assert!(ctrl.heating_suspended_for(0));
+        assert!(ctrl.take_command_update());
+        assert_eq!(ctrl.tick(100), None);
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.motor_command().rpm, 120);
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+        assert_eq!(ctrl.current_temp_c(), None);
+
+        // A glitch that clears resumes heating
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(410)));
+        assert!(!ctrl.heating_suspended_for(0));
+        assert!(ctrl.take_command_update());
+        assert!(ctrl.heater_command().target.is_some());
+    }
+
+    #[test]
+    fn test_sustained_sensor_fault_aborts() {
+        let mut ctrl = heated_controller(SensorFaultPolicy::ContinueUnheated);
+        ctrl.update_temperature_for(0, None);
+
+        let mut now = 0;
+        while now < SUSTAINED_SENSOR_FAULT_MS - 100 {
+            now += 100;
+            ctrl.heartbeat_received();
+            assert_eq!(ctrl.tick(now), None);
+        }
+        assert_eq!(ctrl.state(), State::Running);
+
+        ctrl.heartbeat_received();
+        assert_eq!(
+            ctrl.tick(now + 100),
+            Some(Event::ErrorDetected(ErrorKind::ThermistorFault))
+        );
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    /// Machine with a jar heater (0) and a dryer (1), heating in the dryer
+    fn dryer_controller(policy: SensorFaultPolicy) -> Controller {
+        let mut profile = make_profile("Dry", 120, 600);
+        profile.temperature_c = Some(45);
+        let mut jar = make_jar("dry");
+        jar.heater = Some(String::try_from("dryer").unwrap());
+        let heaters = ["jar_heater", "dryer"].map(|name| HeaterConfig {
+            name: String::try_from(name).unwrap(),
+            max_temp: 55,
+            ..Default::default()
+        });
+
+        let mut ctrl = booted_controller(
+            MachineCapabilities::from_config(false, false, false, 2),
+            profile,
+            jar,
+            |ctrl| {
+                ctrl.set_sensor_fault_policy(policy);
+                ctrl.load_heaters(&heaters);
+            },
+        );
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(250)));
+        ctrl.update_temperature_for(1, Some(TemperatureC10::from_x10(400)));
+        start_program(&mut ctrl);
+        ctrl
+    }
+
+    #[test]
+    fn test_second_heater_commanded() {
+        let mut ctrl = dryer_controller(SensorFaultPolicy::Abort);
+        let heating = HeaterCommand::heating(TemperatureC10::from_whole(45)).for_heater(1);
+        assert_eq!(ctrl.heater_count(), 2);
+        assert_eq!(ctrl.heater_command(), heating);
+        assert_eq!(ctrl.take_heater_command(0), Some(HeaterCommand::off()));
+        assert_eq!(ctrl.take_heater_command(1), Some(heating));
+        assert_eq!(ctrl.take_heater_command(MAX_HEATERS as u8), None);
+
+        // The display follows the dryer
+        assert_eq!(ctrl.current_temp_c(), Some(40));
+
+        // The dryer overheating faults, though the jar heater reads cool
+        ctrl.update_temperature_for(1, Some(TemperatureC10::from_x10(560)));
+        assert_eq!(
+            ctrl.tick(100),
+            Some(Event::ErrorDetected(ErrorKind::OverTemperature))
+        );
+        assert_eq!(
+            ctrl.heater_command_for(1),
+            HeaterCommand::off().for_heater(1)
+        );
+    }
+
+    #[test]
+    fn test_sensor_fault_suspends_its_heater() {
+        let mut ctrl = dryer_controller(SensorFaultPolicy::ContinueUnheated);
+
+        // A fault on the idle jar heater leaves the dryer running
+        ctrl.update_temperature_for(0, None);
+        assert!(ctrl.heating_suspended_for(0));
+        assert!(!ctrl.heating_suspended_for(1));
+        assert!(ctrl.heater_command_for(1).target.is_some());
+        assert_eq!(ctrl.current_temp_c(), Some(40));
+
+        // A fault on the dryer holds it off too
+        ctrl.update_temperature_for(1, None);
+        assert_eq!(
+            ctrl.heater_command_for(1),
+            HeaterCommand::off().for_heater(1)
+        );
+        assert_eq!(ctrl.current_temp_c(), None);
+        assert_eq!(ctrl.tick(100), None);
+        assert_eq!(ctrl.state(), State::Running);
+
+        // Each heater resumes on its own readings
+        ctrl.update_temperature_for(1, Some(TemperatureC10::from_x10(410)));
+        assert!(ctrl.heater_command_for(1).target.is_some());
+        assert!(ctrl.heating_suspended_for(0));
+    }
+
+    #[test]
+    fn test_startup_stagger_delays_heater() {
+        let mut profile = make_profile("Clean", 120, 60);
+        profile.temperature_c = Some(45);
+        let mut ctrl = staggered_controller(profile);
+
+        // Motor starts straight away, heater waits out the stagger
+        assert_eq!(ctrl.motor_command().rpm, 120);
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+
+        assert_eq!(ctrl.tick(499), None);
+        assert!(!ctrl.take_command_update());
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+
+        assert_eq!(ctrl.tick(500), None);
+        assert!(ctrl.take_command_update());
+        assert_eq!(
+            ctrl.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(45))
+        );
+
+        // Resuming from a pause starts both again, so it staggers too
+        ctrl.process_input(InputEvent::EncoderClick); // Pause
+        ctrl.process_input(InputEvent::EncoderClick); // Resume
+        assert_eq!(ctrl.motor_command().rpm, 120);
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+        ctrl.tick(1000);
+        assert!(ctrl.heater_command().target.is_some());
+    }
+
+    /// Commands sent after each input or tick, as the controller task does
+    #[derive(Default)]
+    struct EmissionLog {
+        motor: Vec<MotorCommand, 8>,
+        heater: Vec<HeaterCommand, 8>,
+    }
+
+    impl EmissionLog {
+        fn send(&mut self, ctrl: &mut Controller) {
+            if let Some(cmd) = ctrl.take_motor_command() {
+                self.motor.push(cmd).unwrap();
+            }
+            if let Some(cmd) = ctrl.take_heater_command(0) {
+                self.heater.push(cmd).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_commands_sent_once_when_unchanged() {
+        let mut profile = make_profile("Clean", 120, 60);
+        profile.temperature_c = Some(45);
+        let mut ctrl = staggered_controller(profile);
+        ctrl.set_commands_on_change(true);
+        let mut log = EmissionLog::default();
+
+        for s in 0..3 {
+            ctrl.heartbeat_received();
+            ctrl.tick(s * 100);
+            log.send(&mut ctrl);
+        }
+        assert_eq!(log.motor.len(), 1);
+        assert_eq!(log.motor[0].rpm, 120);
+        assert_eq!(log.heater, [HeaterCommand::off()]);
+
+        // The stagger releasing the heater is a change; the motor is not
+        ctrl.tick(500);
+        log.send(&mut ctrl);
+        ctrl.tick(600);
+        log.send(&mut ctrl);
+        assert_eq!(log.motor.len(), 1);
+        assert_eq!(
+            log.heater,
+            [
+                HeaterCommand::off(),
+                HeaterCommand::heating(TemperatureC10::from_whole(45))
+            ]
+        );
+
+        // Pausing changes both
+        ctrl.process_input(InputEvent::EncoderClick);
+        log.send(&mut ctrl);
+        log.send(&mut ctrl);
+        assert_eq!(log.motor.len(), 2);
+        assert_eq!(log.motor[1].rpm, 0);
+        assert_eq!(log.heater.len(), 3);
+    }
+
+    #[test]
+    fn test_ultrasonic_off_after_abort() {
+        use isochron_core::scheduler::AccessoryCommand;
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let mut jar = make_jar("clean");
+        jar.ultrasonic = Some(String::try_from("us_clean").unwrap());
+        let profiles = [make_profile("Clean", 120, 60)];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+        ctrl.load_config(&programs, &profiles, &[jar]);
+        ctrl.set_commands_on_change(true);
+        ctrl.boot_complete();
+        assert_eq!(
+            ctrl.take_ultrasonic_command(),
+            Some(AccessoryCommand::off())
+        );
+
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.take_ultrasonic_command(), Some(AccessoryCommand::on()));
+        assert_eq!(ctrl.take_ultrasonic_command(), None);
+
+        // Aborting switches the module off rather than leaving it alone
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Abort)
+        );
+        assert_eq!(
+            ctrl.take_ultrasonic_command(),
+            Some(AccessoryCommand::off())
+        );
+    }
+
+    #[test]
+    fn test_commands_sent_every_time_by_default() {
+        let mut ctrl = staggered_controller(make_profile("Clean", 120, 60));
+        let mut log = EmissionLog::default();
+
+        for s in 0..3 {
+            ctrl.heartbeat_received();
+            ctrl.tick(s * 100);
+            log.send(&mut ctrl);
+        }
+        assert_eq!(log.motor.len(), 3);
+        assert_eq!(log.heater.len(), 3);
+    }
+
+    #[test]
+    fn test_startup_stagger_motor_only_unaffected() {
+        let mut ctrl = staggered_controller(make_profile("Clean", 120, 60));
+
+        assert_eq!(ctrl.motor_command().rpm, 120);
+        assert_eq!(ctrl.tick(100), None);
+        assert!(!ctrl.take_command_update());
+        assert_eq!(ctrl.motor_command().rpm, 120);
+    }
+
+    #[test]
+    fn test_prime_end_updates_heater() {
+        let mut profile = make_profile("Clean", 120, 60);
+        profile.temperature_c = Some(45);
+        profile.prime = Some(PrimeConfig {
+            time_s: 10,
+            rpm: Some(60),
+        });
+        let mut ctrl = staggered_controller(profile);
+
+        // Priming runs the motor alone
+        assert_eq!(ctrl.motor_command().rpm, 60);
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+        for s in 1..10 {
+            ctrl.heartbeat_received();
+            assert_eq!(ctrl.tick(s * 1000), None);
+        }
+        assert!(!ctrl.take_command_update());
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+
+        // The heated segment has no event of its own, so commands are re-sent
+        ctrl.heartbeat_received();
+        assert_eq!(ctrl.tick(10_000), None);
+        assert!(ctrl.take_command_update());
+        assert_eq!(ctrl.motor_command().rpm, 120);
+        assert_eq!(
+            ctrl.heater_command(),
+            HeaterCommand::heating(TemperatureC10::from_whole(45))
+        );
+    }
+
+    #[test]
+    fn test_stall_reverses_then_resumes() {
+        use isochron_core::traits::Direction;
+
+        let mut ctrl = running_controller();
+        ctrl.set_stall_reverse_recovery(true);
+        assert_eq!(ctrl.motor_command().direction, Direction::Clockwise);
+
+        ctrl.update_motor_stall(true);
+        assert!(ctrl.take_command_update());
+        assert!(!ctrl.take_command_update());
+        assert_eq!(
+            ctrl.motor_command(),
+            MotorCommand::running(120, Direction::CounterClockwise)
+        );
+
+        // Still reversing part-way through, without faulting
+        assert_eq!(ctrl.tick(300), None);
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.motor_command().direction, Direction::CounterClockwise);
+
+        ctrl.update_motor_stall(false);
+        assert_eq!(ctrl.tick(600), None);
+        assert!(ctrl.take_command_update());
+        assert_eq!(
+            ctrl.motor_command(),
+            MotorCommand::running(120, Direction::Clockwise)
+        );
+        assert_eq!(ctrl.state(), State::Running);
+    }
+
+    #[test]
+    fn test_stall_without_recovery_faults() {
+        let mut ctrl = running_controller();
+
+        ctrl.update_motor_stall(true);
+        assert!(!ctrl.take_command_update());
+        assert_eq!(
+            ctrl.tick(100),
+            Some(Event::ErrorDetected(ErrorKind::MotorStall))
+        );
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_stall_recovery_gives_up() {
+        let mut ctrl = running_controller();
+        ctrl.set_stall_reverse_recovery(true);
+
+        let mut now_ms = 0;
+        for _ in 0..MAX_STALL_REVERSALS {
+            ctrl.update_motor_stall(true);
+            now_ms += STALL_REVERSE_MS;
+            assert_eq!(ctrl.tick(now_ms), None);
+            ctrl.update_motor_stall(false);
+        }
+
+        // The jam didn't clear: the next stall faults
+        ctrl.update_motor_stall(true);
+        assert_eq!(
+            ctrl.tick(now_ms + 100),
+            Some(Event::ErrorDetected(ErrorKind::MotorStall))
+        );
+    }
+
+    /// Machine with a lid interlock, booted to the idle menu
+    fn lid_controller() -> Controller {
+        booted_controller(
+            MachineCapabilities::from_config(false, false, true, 0),
+            make_profile("Clean", 120, 60),
+            make_jar("clean"),
+            |_| {},
+        )
+    }
+
+    #[test]
+    fn test_lid_open_while_running_faults() {
+        let mut ctrl = lid_controller();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.tick(100), None);
+
+        ctrl.update_lid_open(true);
+        let fault = Event::ErrorDetected(ErrorKind::LidOpen);
+        assert_eq!(ctrl.tick(200), Some(fault));
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::LidOpen));
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_lid_open_ignored_when_idle() {
+        let mut ctrl = lid_controller();
+        ctrl.update_lid_open(true);
+        assert_eq!(ctrl.tick(100), None);
+        assert_eq!(ctrl.state(), State::Idle);
+
+        // Starting with the lid open faults once the basket is to spin
+        ctrl.process_input(InputEvent::EncoderClick);
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(
+            ctrl.tick(200),
+            Some(Event::ErrorDetected(ErrorKind::LidOpen))
+        );
+    }
+
+    #[test]
+    fn test_lid_ignored_without_lid() {
+        let mut ctrl = running_controller();
+        ctrl.update_lid_open(true);
+        assert_eq!(ctrl.tick(100), None);
+        assert_eq!(ctrl.state(), State::Running);
+    }
+
+    #[test]
+    fn test_driver_overtemp_faults() {
+        use isochron_drivers::stepper::tmc2209::DrvStatus;
+
+        let mut ctrl = running_controller();
+        let status = DrvStatus::from_register(1 << 1);
+        ctrl.update_driver_fault(status.fault_kind());
+
+        let kind = ErrorKind::DriverFault(DriverFaultKind::OverTemperature);
+        assert_eq!(ctrl.tick(100), Some(Event::ErrorDetected(kind)));
+        assert_eq!(ctrl.state(), State::Error(kind));
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_clean_driver_status_keeps_running() {
+        use isochron_drivers::stepper::tmc2209::DrvStatus;
+
+        let mut ctrl = running_controller();
+        let status = DrvStatus::from_register(0xC010_0000);
+        ctrl.update_driver_fault(status.fault_kind());
+
+        assert_eq!(ctrl.tick(100), None);
+        assert_eq!(ctrl.state(), State::Running);
+    }
+
+    #[test]
+    fn test_link_timeout_from_config() {
+        let mut ctrl = running_controller();
+        ctrl.set_link_config(&LinkConfig {
+            heartbeat_ms: 2000,
+            timeout_multiplier: 2,
+            auto_clear: false,
+            test_pattern: None,
+            report_scheduler_state: false,
+        });
+
+        // Late heartbeats inside the 4 s window keep the program running
+        let mut now_ms = 0;
+        for _ in 0..5 {
+            for _ in 0..35 {
+                now_ms += 100;
+                assert_eq!(ctrl.tick(now_ms), None);
+            }
+            ctrl.heartbeat_received();
+        }
+        assert_eq!(ctrl.state(), State::Running);
+
+        // A genuine gap loses the link
+        for _ in 0..40 {
+            now_ms += 100;
+            ctrl.tick(now_ms);
+        }
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::LinkLost));
+    }
+
+    fn auto_clear_controller() -> Controller {
+        let mut ctrl = running_controller();
+        ctrl.set_link_config(&LinkConfig {
+            auto_clear: true,
+            ..LinkConfig::default()
+        });
+        ctrl
+    }
+
+    #[test]
+    fn test_recovered_link_auto_clears() {
+        let mut ctrl = auto_clear_controller();
+
+        let mut now_ms = 0;
+        while ctrl.state() == State::Running {
+            now_ms += 100;
+            ctrl.tick(now_ms);
+        }
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::LinkLost));
+        assert!(ctrl.fault_auto_clears());
+
+        // Still latched while the link stays down
+        now_ms += 100;
+        assert_eq!(ctrl.tick(now_ms), None);
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::LinkLost));
+
+        ctrl.heartbeat_received();
+        now_ms += 100;
+        assert_eq!(ctrl.tick(now_ms), Some(Event::AcknowledgeError));
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_over_temperature_stays_latched() {
+        let mut ctrl = auto_clear_controller();
+
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(560)));
+        assert_eq!(
+            ctrl.tick(100),
+            Some(Event::ErrorDetected(ErrorKind::OverTemperature))
+        );
+        assert!(!ctrl.fault_auto_clears());
+
+        // Cooled down with a healthy link: still waits for the user
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(300)));
+        ctrl.heartbeat_received();
+        assert_eq!(ctrl.tick(200), None);
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::OverTemperature));
+
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_thermal_runaway_faults() {
+        let mut ctrl = running_controller();
+        ctrl.set_thermal_runaway(Some(ThermalRunawayConfig {
+            window_s: 10,
+            min_rise_c: 2,
+        }));
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(300)));
+        ctrl.update_heater_output_for(0, true);
+
+        // Heating with the reading stuck at 30°C
+        for now_ms in (1..10).map(|s| s * 1000) {
+            ctrl.heartbeat_received();
+            assert_eq!(ctrl.tick(now_ms), None);
+        }
+        ctrl.heartbeat_received();
+        assert_eq!(
+            ctrl.tick(10_000),
+            Some(Event::ErrorDetected(ErrorKind::ThermalRunaway))
+        );
+        assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+    }
+
+    #[test]
+    fn test_scheduler_state_matches_step_state() {
+        let mut ctrl = running_controller();
+        for now_ms in (1..=25).map(|s| s * 1000) {
+            ctrl.heartbeat_received();
+            ctrl.tick(now_ms);
+        }
+
+        let step = ctrl.scheduler.step_state().unwrap();
+        let PicoMessage::SchedulerState {
+            phase,
+            step: step_index,
+            segment,
+            rpm,
+            temp,
+            elapsed_s,
+        } = ctrl.scheduler_state()
+        else {
+            panic!("expected scheduler state");
+        };
+        assert_eq!(
+            ExecutionPhase::from_byte(phase),
+            Some(ExecutionPhase::Running)
+        );
+        assert_eq!(step_index, step.step_index);
+        assert_eq!(segment, step.segment_index);
+        assert_eq!(rpm, 120);
+        assert_eq!(temp, None);
+        assert_eq!(elapsed_s, step.step_elapsed_s);
+        assert_eq!(elapsed_s, 25);
+    }
+
+    #[test]
+    fn test_scheduler_state_reported_on_change() {
+        let mut ctrl = running_controller();
+        assert_eq!(ctrl.take_scheduler_state_change(), None);
+
+        ctrl.set_link_config(&LinkConfig {
+            report_scheduler_state: true,
+            ..LinkConfig::default()
+        });
+        assert_eq!(ctrl.take_scheduler_state_change(), None);
+
+        ctrl.heartbeat_received();
+        ctrl.tick(1000);
+        let state = ctrl.take_scheduler_state_change().unwrap();
+        assert!(matches!(
+            state,
+            PicoMessage::SchedulerState { elapsed_s: 1, .. }
+        ));
+        assert_eq!(ctrl.take_scheduler_state_change(), None);
+    }
+
+    #[test]
+    fn test_soft_reset_only_when_idle() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        assert!(!ctrl.soft_reset_allowed()); // Still booting
+
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        assert!(ctrl.soft_reset_allowed());
+
+        let ctrl = running_controller();
+        assert!(!ctrl.soft_reset_allowed());
+    }
+
+    fn finish_automated_program(park: Option<ParkPosition>) -> Controller {
+        let mut ctrl = Controller::new(MachineCapabilities::from_config(true, true, false, 1));
+        let profiles = [make_profile("Clean", 120, 1)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.set_park_position(park);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.take_park_move(), None);
+
+        assert_eq!(ctrl.tick(1000), Some(Event::ProgramFinished));
+        assert_eq!(ctrl.state(), State::ProgramComplete);
+        ctrl
+    }
+
+    /// Manual machine showing the complete screen at `now_ms` = 1000
+    fn completed_manual_program(auto_return_s: u16) -> Controller {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 1)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.set_complete_auto_return(auto_return_s);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+
+        ctrl.heartbeat_received();
+        assert_eq!(ctrl.tick(1000), Some(Event::ProgramFinished));
+        assert_eq!(ctrl.state(), State::ProgramComplete);
+        ctrl
+    }
+
+    #[test]
+    fn test_complete_auto_returns_to_idle() {
+        let mut ctrl = completed_manual_program(10);
+        let mut now_ms = 1000;
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 9), None);
+        assert_eq!(ctrl.state(), State::ProgramComplete);
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 1), Some(Event::Back));
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_complete_waits_for_click() {
+        let mut ctrl = completed_manual_program(0);
+        let mut now_ms = 1000;
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 600), None);
+        assert_eq!(ctrl.state(), State::ProgramComplete);
+
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::Back)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_park_after_program() {
+        let park = ParkPosition { x_pos: 0, z_pos: 5 };
+        let mut ctrl = finish_automated_program(Some(park));
+
+        assert_eq!(ctrl.take_park_move(), Some(park));
+        assert_eq!(ctrl.take_park_move(), None);
+
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_no_park_when_disabled() {
+        let mut ctrl = finish_automated_program(None);
+        assert_eq!(ctrl.take_park_move(), None);
+    }
+
+    #[test]
+    fn test_no_park_on_manual_machine() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 1)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.set_park_position(Some(ParkPosition::default()));
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        ctrl.tick(1000);
+
+        assert_eq!(ctrl.take_park_move(), None);
+    }
+
+    /// Manual machine running the first of two steps in different jars
+    fn manual_two_step_controller(park_angle: Option<u16>) -> Controller {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 2), make_profile("Rinse", 120, 2)];
+        let jars = [make_jar("clean"), make_jar("rinse")];
+        let programs = [make_program(
+            "Test",
+            &[("clean", "Clean"), ("rinse", "Rinse")],
+        )];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.set_park_angle(park_angle);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        ctrl
+    }
+
+    #[test]
+    fn test_orient_basket_before_next_jar_prompt() {
+        let mut ctrl = manual_two_step_controller(Some(90));
+        let mut now_ms = 0;
+
+        // Step done: the basket turns first, the prompt is held back
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 2), None);
+        assert_eq!(ctrl.take_orient_move(), Some(90));
+        assert_eq!(ctrl.take_orient_move(), None);
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+
+        // Clicks don't pause or skip ahead while it turns
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        let state = ctrl.state();
+
+        assert_eq!(ctrl.orientation_complete(), Some(Event::PromptNextJar));
+        assert_eq!(ctrl.state(), state.transition(Event::PromptNextJar));
+        assert_eq!(ctrl.orientation_complete(), None);
+    }
+
+    #[test]
+    fn test_interrupted_orientation_resumes() {
+        let mut ctrl = manual_two_step_controller(Some(90));
+        let mut now_ms = 0;
+        tick_seconds(&mut ctrl, &mut now_ms, 2);
+        assert_eq!(ctrl.take_orient_move(), Some(90));
+
+        // A motor command stopped the turn: it is requested again
+        assert!(ctrl.orientation_interrupted());
+        assert_eq!(ctrl.take_orient_move(), Some(90));
+        assert_eq!(ctrl.orientation_complete(), Some(Event::PromptNextJar));
+
+        // After an abort the interrupted turn is dropped
+        let mut ctrl = manual_two_step_controller(Some(90));
+        let mut now_ms = 0;
+        tick_seconds(&mut ctrl, &mut now_ms, 2);
+        assert_eq!(ctrl.take_orient_move(), Some(90));
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Abort)
+        );
+        assert!(!ctrl.orientation_interrupted());
+        assert_eq!(ctrl.take_orient_move(), None);
+        assert_eq!(ctrl.orientation_complete(), None);
+    }
+
+    #[test]
+    fn test_no_orient_when_disabled() {
+        let mut ctrl = manual_two_step_controller(None);
+        let mut now_ms = 0;
+
+        assert_eq!(
+            tick_seconds(&mut ctrl, &mut now_ms, 2),
+            Some(Event::PromptNextJar)
+        );
+        assert_eq!(ctrl.take_orient_move(), None);
+        assert_eq!(ctrl.orientation_complete(), None);
+    }
+
+    #[test]
+    fn test_abort_cancels_orientation() {
+        let mut ctrl = manual_two_step_controller(Some(180));
+        let mut now_ms = 0;
+        tick_seconds(&mut ctrl, &mut now_ms, 2);
+
+        ctrl.process_input(InputEvent::EncoderLongPress);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.take_orient_move(), None);
+        assert_eq!(ctrl.orientation_complete(), None);
+    }
+
+    #[test]
+    fn test_prompt_first_jar() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+        ctrl.load_config(
+            &programs,
+            &[make_profile("Clean", 120, 2)],
+            &[make_jar("clean")],
+        );
+        ctrl.set_prompt_first_jar(true);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+
+        assert_eq!(ctrl.state(), State::AwaitingJar);
+        assert_eq!(ctrl.motor_command().rpm, 0);
+
+        ctrl.process_input(InputEvent::EncoderClick); // Basket in place
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.motor_command().rpm, 120);
+    }
+
+    #[test]
+    fn test_manual_prompts_auto_advance() {
+        let mut ctrl = manual_two_step_controller(None);
+        ctrl.set_auto_advance(5);
+        let mut now_ms = 0;
+
+        assert_eq!(
+            tick_seconds(&mut ctrl, &mut now_ms, 2),
+            Some(Event::PromptNextJar)
+        );
+        assert_eq!(ctrl.state(), State::StepComplete);
+        assert_eq!(ctrl.auto_advance_remaining_s(), Some(5));
+
+        // Counts down, then moves on to the next jar's prompt
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 4), None);
+        assert_eq!(ctrl.auto_advance_remaining_s(), Some(1));
+        assert_eq!(
+            tick_seconds(&mut ctrl, &mut now_ms, 1),
+            Some(Event::PromptNextJar)
+        );
+        assert_eq!(ctrl.state(), State::AwaitingJar);
+        assert_eq!(ctrl.auto_advance_remaining_s(), Some(5));
+
+        // The jar prompt gets a fresh countdown and confirms itself too
+        assert_eq!(
+            tick_seconds(&mut ctrl, &mut now_ms, 5),
+            Some(Event::UserConfirm)
+        );
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.auto_advance_remaining_s(), None);
+        assert_eq!(ctrl.motor_command().rpm, 120);
+    }
+
+    #[test]
+    fn test_manual_prompts_wait_for_click() {
+        let mut ctrl = manual_two_step_controller(None);
+        let mut now_ms = 0;
+        tick_seconds(&mut ctrl, &mut now_ms, 2);
+        assert_eq!(ctrl.state(), State::StepComplete);
+        assert_eq!(ctrl.auto_advance_remaining_s(), None);
+
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 600), None);
+        assert_eq!(ctrl.state(), State::StepComplete);
+
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::PromptNextJar)
+        );
+        assert_eq!(ctrl.state(), State::AwaitingJar);
+    }
+
+    #[test]
+    fn test_encoder_navigation() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+
+        let profiles = [
+            make_profile("Clean", 120, 60),
+            make_profile("Rinse", 100, 30),
+        ];
+        let jars = [make_jar("clean"), make_jar("rinse")];
+        let programs = [
+            make_program("Full", &[("clean", "Clean"), ("rinse", "Rinse")]),
+            make_program("Quick", &[("clean", "Clean")]),
+        ];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+
+        assert_eq!(ctrl.selected_program(), 0);
+
+        // Navigate CW
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.selected_program(), 1);
+
+        // Navigate CW wraps around
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.selected_program(), 0);
+
+        // Navigate CCW
+        ctrl.process_input(InputEvent::EncoderCcw);
+        assert_eq!(ctrl.selected_program(), 1);
+    }
+
+    #[test]
+    fn test_long_press_abort() {
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+
+        assert_eq!(ctrl.state(), State::Running);
+
+        // Long press to abort
+        ctrl.process_input(InputEvent::EncoderLongPress);
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    fn keymapped_controller(keymap: &Keymap) -> Controller {
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.set_keymap(keymap);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.state(), State::Running);
+        ctrl
+    }
+
+    #[test]
+    fn test_default_keymap_bindings() {
+        let mut ctrl = keymapped_controller(&Keymap::default());
+
+        // Double-click doesn't touch a running program
+        assert_eq!(ctrl.process_input(InputEvent::EncoderDoubleClick), None);
+        assert_eq!(ctrl.state(), State::Running);
+
+        // Click pauses and resumes
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::Pause)
+        );
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::Resume)
+        );
+
+        // Long press aborts
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Abort)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+
+        // Long press backs out of the program detail screen
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.state(), State::ProgramSelected);
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Back)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+
+        // Long press does nothing on the program list
+        assert_eq!(ctrl.process_input(InputEvent::EncoderLongPress), None);
+        assert_eq!(ctrl.state(), State::Idle);
+
+        // Long press doesn't leave the editor; double-click does
+        ctrl.process_input(InputEvent::EncoderClick);
+        ctrl.transition(Event::EditParameter);
+        assert_eq!(ctrl.state(), State::EditProgram);
+        assert_eq!(ctrl.process_input(InputEvent::EncoderLongPress), None);
+        assert_eq!(ctrl.state(), State::EditProgram);
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderDoubleClick),
+            Some(Event::Back)
+        );
+        assert_eq!(ctrl.state(), State::ProgramSelected);
+    }
+
+    #[test]
+    fn test_custom_keymap_remaps_actions() {
+        let mut keymap = Keymap::default();
+        keymap.bind(StateCategory::Program, Button::LongPress, KeyAction::Select);
+        keymap.bind(
+            StateCategory::Program,
+            Button::DoubleClick,
+            KeyAction::Abort,
+        );
+        let mut ctrl = keymapped_controller(&keymap);
+
+        // Long press now pauses instead of aborting
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Pause)
+        );
+        assert_eq!(ctrl.state(), State::Paused);
+
+        // Double-click aborts
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderDoubleClick),
+            Some(Event::Abort)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_long_press_aborts_transient_states() {
+        let mut now_ms = 0;
+
+        // Waiting for the basket in the first jar
+        let mut awaiting_jar = Controller::new(MachineCapabilities::default());
+        awaiting_jar.load_config(
+            &[make_program("Test", &[("clean", "Clean")])],
+            &[make_profile("Clean", 120, 60)],
+            &[make_jar("clean")],
+        );
+        awaiting_jar.set_prompt_first_jar(true);
+        awaiting_jar.boot_complete();
+        awaiting_jar.process_input(InputEvent::EncoderClick); // Select
+        awaiting_jar.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(awaiting_jar.state(), State::AwaitingJar);
+
+        // Waiting for the basket to be lifted for spin-off
+        let mut profile = make_profile("Clean", 120, 1);
+        profile.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        let mut awaiting_spinoff = Controller::new(MachineCapabilities::default());
+        awaiting_spinoff.load_config(
+            &[make_program("Test", &[("clean", "Clean")])],
+            &[profile],
+            &[make_jar("clean")],
+        );
+        awaiting_spinoff.boot_complete();
+        awaiting_spinoff.process_input(InputEvent::EncoderClick); // Select
+        awaiting_spinoff.process_input(InputEvent::EncoderClick); // Start
+        tick_seconds(&mut awaiting_spinoff, &mut now_ms, 1);
+        assert_eq!(awaiting_spinoff.state(), State::AwaitingSpinOff);
+
+        // Between jars
+        let mut step_complete = manual_two_step_controller(None);
+        tick_seconds(&mut step_complete, &mut now_ms, 2);
+        assert_eq!(step_complete.state(), State::StepComplete);
+
+        for mut ctrl in [awaiting_jar, awaiting_spinoff, step_complete] {
+            // Long press aborts to a safe idle state
+            let event = ctrl.process_input(InputEvent::EncoderLongPress);
+            assert_eq!(event, Some(Event::Abort));
+            assert_eq!(ctrl.state(), State::Idle);
+            assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+            assert_eq!(ctrl.heater_command(), HeaterCommand::off());
+        }
+    }
+
+    /// Manual machine spinning off at 150 RPM
+    fn spinoff_controller() -> Controller {
+        let ctrl = start_spinoff(Controller::new(MachineCapabilities::default()));
+        assert_eq!(ctrl.motor_command().rpm, 150);
+        ctrl
+    }
+
+    /// Run a program with a 150 RPM spin-off up to the spin-off
+    fn start_spinoff(mut ctrl: Controller) -> Controller {
+        let mut profile = make_profile("Clean", 120, 1);
+        profile.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 60,
+            pre_spinoff_delay_s: 0,
+        });
+        let profiles = [profile];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_x10(400)));
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+        assert_eq!(ctrl.tick(1000), Some(Event::PromptSpinOff));
+        ctrl.process_input(InputEvent::EncoderClick); // Basket lifted
+        assert_eq!(ctrl.state(), State::SpinOff);
+        ctrl
+    }
+
+    #[test]
+    fn test_quiet_mode_caps_spinoff() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        ctrl.set_quiet_spinoff_rpm(100);
+        ctrl.set_quiet_mode(true);
+        assert_eq!(ctrl.take_quiet_change(), Some(true));
+        assert_eq!(ctrl.take_quiet_change(), None);
+
+        let ctrl = start_spinoff(ctrl);
+        assert_eq!(ctrl.motor_command().rpm, 100);
+
+        // The machine ceiling still applies when it is lower
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        ctrl.set_max_spinoff_rpm(Some(80));
+        ctrl.set_quiet_spinoff_rpm(100);
+        ctrl.set_quiet_mode(true);
+        let ctrl = start_spinoff(ctrl);
+        assert_eq!(ctrl.motor_command().rpm, 80);
+    }
+
+    #[test]
+    fn test_quiet_mode_off_restores_full_spinoff() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        ctrl.set_max_spinoff_rpm(Some(300));
+        ctrl.set_quiet_spinoff_rpm(100);
+        ctrl.set_quiet_mode(true);
+        ctrl.set_quiet_mode(false);
+        assert!(!ctrl.quiet_mode());
+
+        let ctrl = start_spinoff(ctrl);
+        assert_eq!(ctrl.motor_command().rpm, 150);
+    }
+
+    #[test]
+    fn test_ui_locked_while_basket_moves() {
+        let mut ctrl = manual_two_step_controller(Some(90));
+        ctrl.set_lock_during_motion(true);
+        let mut now_ms = 0;
+        assert!(!ctrl.ui_locked());
+
+        // Turning to the park angle: the click would otherwise pause
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 2), None);
+        assert_eq!(ctrl.take_orient_move(), Some(90));
+        assert!(ctrl.ui_locked());
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        assert_eq!(ctrl.process_input(InputEvent::EncoderDoubleClick), None);
+        assert_eq!(ctrl.process_input(InputEvent::EncoderCw), None);
+        assert_eq!(ctrl.state(), State::Running);
+
+        // Abort still gets through
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Abort)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+        assert!(!ctrl.ui_locked());
+    }
+
+    #[test]
+    fn test_ui_lock_automated_program_advances() {
+        let profiles = [make_profile("Clean", 120, 2), make_profile("Rinse", 120, 2)];
+        let jars = [make_jar("clean"), make_jar("rinse")];
+        let programs = [make_program(
+            "Test",
+            &[("clean", "Clean"), ("rinse", "Rinse")],
+        )];
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+        ctrl.set_lock_during_motion(true);
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+
+        // Step done: on to the next jar without a click
+        let mut now_ms = 0;
+        assert_eq!(
+            tick_seconds(&mut ctrl, &mut now_ms, 2),
+            Some(Event::NextStep)
+        );
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.scheduler.phase(), ExecutionPhase::Running);
+        assert!(!ctrl.ui_locked());
+
+        assert_eq!(
+            tick_seconds(&mut ctrl, &mut now_ms, 2),
+            Some(Event::ProgramFinished)
+        );
+        assert_eq!(ctrl.state(), State::ProgramComplete);
+        assert!(!ctrl.ui_locked());
+    }
+
+    #[test]
+    fn test_ui_lock_off_or_manual_machine() {
+        // A manual machine waits at the step prompt for the user
+        let mut ctrl = manual_two_step_controller(Some(90));
+        ctrl.set_lock_during_motion(true);
+        let mut now_ms = 0;
+        tick_seconds(&mut ctrl, &mut now_ms, 2);
+        assert_eq!(ctrl.orientation_complete(), Some(Event::PromptNextJar));
+        assert_eq!(ctrl.state(), State::StepComplete);
+        assert!(!ctrl.ui_locked());
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::PromptNextJar)
+        );
+
+        // Without the option nothing locks
+        let mut ctrl = manual_two_step_controller(Some(90));
+        let mut now_ms = 0;
+        tick_seconds(&mut ctrl, &mut now_ms, 2);
+        assert_eq!(ctrl.take_orient_move(), Some(90));
+        assert!(!ctrl.ui_locked());
+        assert_eq!(ctrl.orientation_complete(), Some(Event::PromptNextJar));
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::PromptNextJar)
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_menu_entry() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Full", &[("clean", "Clean")])];
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+
+        // Hidden unless enabled: autotune wraps straight back to the programs
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert!(ctrl.is_autotune_selected());
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.selected_program(), 0);
+
+        ctrl.set_diagnostics_menu(true);
+        ctrl.process_input(InputEvent::EncoderCcw);
+        assert!(ctrl.is_diagnostics_selected());
+        ctrl.process_input(InputEvent::EncoderCcw);
+        assert!(ctrl.is_autotune_selected());
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert!(ctrl.is_diagnostics_selected());
+
+        // Click opens the screen; the encoder no longer moves the selection
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert!(ctrl.diagnostics_shown());
+        assert_eq!(ctrl.state(), State::Idle);
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert!(ctrl.is_diagnostics_selected());
+
+        // Click again, or step back, returns to the menu
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert!(!ctrl.diagnostics_shown());
+        ctrl.process_input(InputEvent::EncoderClick);
+        ctrl.process_input(InputEvent::EncoderDoubleClick);
+        assert!(!ctrl.diagnostics_shown());
+        assert!(ctrl.is_diagnostics_selected());
+    }
+
+    #[test]
+    fn test_sensor_raw_redraws_diagnostics() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        ctrl.set_diagnostics_menu(true);
+        ctrl.boot_complete();
+        let raw = SensorRaw {
+            adc: 3913,
+            resistance_ohms: Some(100_497),
+            temp_x10: Some(249),
+        };
+
+        // Recorded, but nothing to redraw until the screen is open
+        assert!(!ctrl.update_sensor_raw(raw));
+        assert_eq!(ctrl.sensor_raw(), Some(raw));
+
+        ctrl.process_input(InputEvent::EncoderCcw);
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert!(ctrl.diagnostics_shown());
+        assert!(!ctrl.update_sensor_raw(raw));
+        assert!(ctrl.update_sensor_raw(SensorRaw { adc: 3900, ..raw }));
+    }
+
+    #[test]
+    fn test_keymap_toggles_quiet_mode() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let mut keymap = Keymap::default();
+        keymap.bind(
+            StateCategory::Idle,
+            Button::LongPress,
+            KeyAction::ToggleQuiet,
+        );
+        ctrl.set_keymap(&keymap);
+        ctrl.boot_complete();
+
+        assert_eq!(ctrl.process_input(InputEvent::EncoderLongPress), None);
+        assert!(ctrl.quiet_mode());
+        assert_eq!(ctrl.state(), State::Idle);
+        ctrl.process_input(InputEvent::EncoderLongPress);
+        assert!(!ctrl.quiet_mode());
+        assert_eq!(ctrl.take_quiet_change(), Some(false));
+    }
+
+    #[test]
+    fn test_spinoff_imbalance_faults() {
+        let mut ctrl = spinoff_controller();
+        ctrl.set_imbalance_threshold(Some(60));
+
+        for sg in [250, 110, 290, 130] {
+            ctrl.update_stallguard(sg);
+        }
+
+        let fault = Event::ErrorDetected(ErrorKind::Imbalance);
+        assert_eq!(ctrl.tick(1100), Some(fault));
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::Imbalance));
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_spinoff_steady_load_continues() {
+        let mut ctrl = spinoff_controller();
+        ctrl.set_imbalance_threshold(Some(60));
+
+        for sg in [250, 240, 262, 255, 247, 251, 258, 244] {
+            ctrl.update_stallguard(sg);
+        }
+
+        assert_eq!(ctrl.tick(1100), None);
+        assert_eq!(ctrl.state(), State::SpinOff);
+    }
+
+    #[test]
+    fn test_imbalance_ignored_outside_spinoff() {
+        let mut ctrl = running_controller();
+        ctrl.set_imbalance_threshold(Some(60));
+
+        // Load swings while washing are normal agitation
+        for sg in [250, 110, 290, 130] {
+            ctrl.update_stallguard(sg);
+        }
+
+        assert_eq!(ctrl.tick(100), None);
+        assert_eq!(ctrl.state(), State::Running);
+    }
+
+    #[test]
+    fn test_long_press_aborts_manual_spinoff_prompt() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+
+        let mut profile = make_profile("Clean", 120, 1);
+        profile.spinoff = Some(SpinOffConfig {
+            lift_mm: 20,
+            rpm: 150,
+            time_s: 5,
+            pre_spinoff_delay_s: 0,
+        });
+        let profiles = [profile];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick); // Select
+        ctrl.process_input(InputEvent::EncoderClick); // Start
+
+        assert_eq!(ctrl.tick(1000), Some(Event::PromptSpinOff));
+        assert_eq!(ctrl.state(), State::AwaitingSpinOff);
+
+        ctrl.process_input(InputEvent::EncoderLongPress);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_autostart_program() {
+        let mut ctrl = Controller::new(MachineCapabilities {
+            is_automated: true,
+            ..Default::default()
+        });
+
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [
+            make_program("Quick", &[("clean", "Clean")]),
+            make_program("Full", &[("clean", "Clean")]),
+        ];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        assert!(ctrl.set_autostart_program("Full"));
+        assert_eq!(ctrl.state(), State::Boot);
+
+        // Boot -> Idle -> Running with no input
+        assert_eq!(ctrl.boot_complete(), Some(Event::Start));
+        assert_eq!(ctrl.state(), State::Running);
+        assert_eq!(ctrl.selected_program(), 1);
+        assert_eq!(ctrl.motor_command().rpm, 120);
+
+        // Only starts once: aborting returns to an idle menu
+        ctrl.process_input(InputEvent::EncoderLongPress);
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_no_autostart_stays_idle() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Quick", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        assert!(!ctrl.set_autostart_program("Missing"));
+
+        assert_eq!(ctrl.boot_complete(), None);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_autostart_blocked_by_fault() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Quick", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        assert!(ctrl.set_autostart_program("Quick"));
+
+        // A fault during boot keeps the machine out of Idle
+        ctrl.transition(Event::ErrorDetected(ErrorKind::ThermistorFault));
+        assert_eq!(ctrl.boot_complete(), None);
+        assert!(ctrl.state().is_error());
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_missing_config_faults() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let programs = [make_program("Quick", &[("clean", "Clean")])];
+        ctrl.load_config(
+            &programs,
+            &[make_profile("Clean", 120, 60)],
+            &[make_jar("clean")],
+        );
+        assert!(ctrl.set_autostart_program("Quick"));
+
+        ctrl.fault_config_error();
+        assert_eq!(ctrl.boot_complete(), None);
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::ConfigError));
+
+        // Stays latched: clicking doesn't run the embedded defaults
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::ConfigError));
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_breadcrumb_follows_state() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Quick", &[("clean", "Clean")])];
+        ctrl.load_config(&programs, &profiles, &jars);
+
+        assert_eq!(
+            ctrl.take_breadcrumb(),
+            Some(Breadcrumb::new(State::Boot, 0))
+        );
+        assert_eq!(ctrl.take_breadcrumb(), None);
+
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderClick);
+        ctrl.process_input(InputEvent::EncoderClick);
+        let crumb = ctrl.take_breadcrumb().unwrap();
+        assert_eq!(crumb, Breadcrumb::new(State::Running, 1));
+
+        // What a watchdog reset leaves behind for the next boot
+        assert_eq!(Breadcrumb::from_word(crumb.to_word()), Some(crumb));
+    }
+
+    #[test]
+    fn test_watchdog_reset_shows_recovery() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Quick", &[("clean", "Clean")])];
+        ctrl.load_config(&programs, &profiles, &jars);
+        assert!(ctrl.set_autostart_program("Quick"));
+
+        let notice = RecoveryNotice {
+            last: Some(Breadcrumb::new(State::Running, 1)),
+        };
+        ctrl.set_recovery_notice(notice);
+        assert_eq!(ctrl.recovery_notice(), None);
+
+        // No autostart after a hang; the notice replaces the menu
+        assert_eq!(ctrl.boot_complete(), None);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.recovery_notice(), Some(&notice));
+
+        // A click dismisses it without selecting a program
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.recovery_notice(), None);
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderClick),
+            Some(Event::SelectProgram)
+        );
+    }
+
+    /// Single-pass endstop homing: one trigger homes the axis
+    fn single_pass_homing() -> HomingConfig {
+        HomingConfig {
+            position_endstop: 0,
+            positive_dir: false,
+            speed: 5,
+            retract_dist: 0,
+            max_travel: 160,
+            timeout_s: 10,
+        }
+    }
+
+    /// Controller for a machine with Z and X endstops, not yet homing
+    fn endstop_controller() -> Controller {
+        use isochron_core::config::PinConfig;
+
+        let mut ctrl = Controller::new(MachineCapabilities::from_config(true, true, false, 0));
+        for axis in [Axis::Z, Axis::X] {
+            ctrl.set_axis_homing(axis, single_pass_homing(), Endstop::new(PinConfig::new(4)));
+        }
+        ctrl
+    }
+
+    fn homing_controller(order: HomingOrder) -> Controller {
+        let mut ctrl = endstop_controller();
+        ctrl.set_homing_order(order);
+        assert!(ctrl.start_homing());
+        ctrl
+    }
+
+    /// Report the endstop of `axis` released, then reached
+    fn trigger_endstop(ctrl: &mut Controller, axis: Axis) -> Option<Event> {
+        ctrl.handle_axis_report(axis, false, 0);
+        ctrl.handle_axis_report(axis, true, 1_000)
+    }
+
+    #[test]
+    fn test_homing_z_then_x() {
+        let mut ctrl = homing_controller(HomingOrder::ZThenX);
+        assert!(ctrl.is_homing(Axis::Z));
+        assert!(!ctrl.is_homing(Axis::X));
+
+        // A report from an axis still waiting its turn is ignored
+        assert_eq!(trigger_endstop(&mut ctrl, Axis::X), None);
+        assert!(!ctrl.is_homing(Axis::X));
+        assert_eq!(ctrl.homing_move(Axis::X), HomingMove::Stop);
+
+        assert_eq!(trigger_endstop(&mut ctrl, Axis::Z), None);
+        assert!(ctrl.is_homing(Axis::X));
+        assert_eq!(ctrl.state(), State::Boot);
+
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::X),
+            Some(Event::BootComplete)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_homing_x_then_z() {
+        let mut ctrl = homing_controller(HomingOrder::XThenZ);
+        assert!(ctrl.is_homing(Axis::X));
+        assert!(!ctrl.is_homing(Axis::Z));
+
+        assert_eq!(trigger_endstop(&mut ctrl, Axis::X), None);
+        assert!(ctrl.is_homing(Axis::Z));
+        assert_eq!(ctrl.state(), State::Boot);
+
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::Z),
+            Some(Event::BootComplete)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_homing_simultaneous() {
+        let mut ctrl = homing_controller(HomingOrder::Simultaneous);
+        assert!(ctrl.is_homing(Axis::Z));
+        assert!(ctrl.is_homing(Axis::X));
+
+        // Travel without reaching the endstop doesn't count
+        assert_eq!(ctrl.handle_axis_report(Axis::X, false, 0), None);
+        assert_eq!(ctrl.handle_axis_report(Axis::X, false, 20_000), None);
+        assert!(ctrl.is_homing(Axis::X));
+
+        assert_eq!(ctrl.handle_axis_report(Axis::X, true, 1_000), None);
+        assert!(!ctrl.is_homing(Axis::X));
+        assert_eq!(ctrl.state(), State::Boot);
+
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::Z),
+            Some(Event::BootComplete)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_homing_moves_follow_sequence() {
+        let mut ctrl = homing_controller(HomingOrder::ZThenX);
+
+        // Nothing moves until the axis has reported its endstop
+        assert_eq!(ctrl.take_homing_move(Axis::Z), None);
+
+        ctrl.handle_axis_report(Axis::Z, false, 0);
+        let seek = HomingMove::Move {
+            positive: false,
+            speed: 5,
+        };
+        assert_eq!(ctrl.take_homing_move(Axis::Z), Some(seek));
+        assert_eq!(ctrl.take_homing_move(Axis::Z), None);
+        assert_eq!(ctrl.take_homing_move(Axis::X), None);
+
+        // Z home: it stops, and X starts once it reports
+        ctrl.handle_axis_report(Axis::Z, true, 1_000);
+        assert_eq!(ctrl.take_homing_move(Axis::Z), Some(HomingMove::Stop));
+        ctrl.handle_axis_report(Axis::X, false, 0);
+        assert_eq!(ctrl.take_homing_move(Axis::X), Some(seek));
+    }
+
+    #[test]
+    fn test_homing_endstop_not_found_faults() {
+        let mut ctrl = homing_controller(HomingOrder::ZThenX);
+        ctrl.handle_axis_report(Axis::Z, false, 0);
+
+        // Full travel without the endstop, twice: the grace retry backs
+        // off between the attempts
+        assert_eq!(ctrl.handle_axis_report(Axis::Z, false, 170_000), None);
+        assert_eq!(ctrl.handle_axis_report(Axis::Z, false, 5_000), None);
+
+        let fault = Event::ErrorDetected(ErrorKind::HomingFailed);
+        assert_eq!(
+            ctrl.handle_axis_report(Axis::Z, false, 170_000),
+            Some(fault)
+        );
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::HomingFailed));
+        assert!(!ctrl.is_homing(Axis::X));
+        assert_eq!(ctrl.homing_move(Axis::Z), HomingMove::Stop);
+    }
+
+    #[test]
+    fn test_homing_timeout_faults() {
+        let mut ctrl = homing_controller(HomingOrder::ZThenX);
+        let mut now_ms = 0;
+
+        // Not timed until Z has reported its endstop and started moving
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 20), None);
+        assert_eq!(ctrl.state(), State::Boot);
+        ctrl.handle_axis_report(Axis::Z, false, 0);
+
+        // The first attempt runs out: Z backs off for a second try
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 11), None);
+        assert_eq!(
+            ctrl.homing_move(Axis::Z),
+            HomingMove::Move {
+                positive: true,
+                speed: 5
+            }
+        );
+        ctrl.handle_axis_report(Axis::Z, false, 5_000);
+        assert_eq!(ctrl.state(), State::Boot);
+
+        // The retry runs out too
+        let fault = Event::ErrorDetected(ErrorKind::HomingFailed);
+        assert_eq!(tick_seconds(&mut ctrl, &mut now_ms, 11), Some(fault));
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::HomingFailed));
+        assert!(!ctrl.is_homing(Axis::X));
+        assert_eq!(ctrl.homing_move(Axis::Z), HomingMove::Stop);
+        assert_eq!(ctrl.motor_command(), MotorCommand::stopped());
+    }
+
+    #[test]
+    fn test_homing_cancel_stops_axes() {
+        let token = CancelToken::new();
+        let mut ctrl = homing_controller(HomingOrder::ZThenX);
+        ctrl.handle_axis_report(Axis::Z, false, 0);
+        assert!(ctrl.take_homing_move(Axis::Z).is_some());
+        assert_eq!(ctrl.check_homing_cancel(&token), None);
+        assert!(ctrl.is_homing(Axis::Z));
+
+        // A long press asks for the cancel
+        assert_eq!(
+            ctrl.process_input(InputEvent::EncoderLongPress),
+            Some(Event::Abort)
+        );
+        token.cancel();
+        let fault = Event::ErrorDetected(ErrorKind::HomingFailed);
+        assert_eq!(ctrl.check_homing_cancel(&token), Some(fault));
+        assert_eq!(ctrl.state(), State::Error(ErrorKind::HomingFailed));
+        assert_eq!(ctrl.take_homing_move(Axis::Z), Some(HomingMove::Stop));
+        assert!(!ctrl.is_homing(Axis::X));
+
+        // Once homing is over the token no longer matters
+        assert_eq!(ctrl.check_homing_cancel(&token), None);
+    }
+
+    #[test]
+    fn test_homing_skipped_without_axes() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        assert!(!ctrl.start_homing());
+        assert_eq!(trigger_endstop(&mut ctrl, Axis::Z), None);
+        assert_eq!(ctrl.state(), State::Boot);
+
+        // Z-only machine waits for Z alone
+        let mut ctrl = Controller::new(MachineCapabilities::from_config(true, false, false, 0));
+        ctrl.set_axis_homing(
+            Axis::Z,
+            single_pass_homing(),
+            Endstop::new(isochron_core::config::PinConfig::new(4)),
+        );
+        ctrl.set_homing_order(HomingOrder::XThenZ);
+        assert!(ctrl.start_homing());
+        assert!(ctrl.is_homing(Axis::Z));
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::Z),
+            Some(Event::BootComplete)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+        assert!(!ctrl.start_homing());
+    }
+
+    fn typed_homing_controller(z: HomingType, x: HomingType) -> Controller {
+        let mut ctrl = endstop_controller();
+        ctrl.set_homing_types(z, x);
+        ctrl
+    }
+
+    #[test]
+    fn test_homing_type_none_assumes_home() {
+        let mut ctrl = typed_homing_controller(HomingType::None, HomingType::None);
+        assert!(!ctrl.start_homing());
+        assert!(!ctrl.is_homing(Axis::Z));
+        assert_eq!(ctrl.boot_complete(), None);
+        assert_eq!(ctrl.state(), State::Idle);
+
+        // Z skipped, X still seeks its endstop
+        let mut ctrl = typed_homing_controller(HomingType::None, HomingType::Endstop);
+        assert!(ctrl.start_homing());
+        assert!(!ctrl.is_homing(Axis::Z));
+        assert!(ctrl.is_homing(Axis::X));
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::X),
+            Some(Event::BootComplete)
+        );
+    }
+
+    #[test]
+    fn test_ui_locked_while_homing() {
+        let mut ctrl = typed_homing_controller(HomingType::Endstop, HomingType::Endstop);
+        ctrl.set_lock_during_motion(true);
+        assert!(ctrl.start_homing());
+        assert!(ctrl.ui_locked());
+        for input in [
+            InputEvent::EncoderCw,
+            InputEvent::EncoderClick,
+            InputEvent::EncoderLongPress,
+        ] {
+            assert_eq!(ctrl.process_input(input), None);
+        }
+        assert_eq!(ctrl.state(), State::Boot);
+
+        // Unlocked once homing is done
+        trigger_endstop(&mut ctrl, Axis::Z);
+        trigger_endstop(&mut ctrl, Axis::X);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert!(!ctrl.ui_locked());
+
+        // Manual homing needs the encoder, so it never locks
+        let mut ctrl = typed_homing_controller(HomingType::Manual, HomingType::Endstop);
+        ctrl.set_lock_during_motion(true);
+        ctrl.start_homing();
+        assert!(!ctrl.ui_locked());
+        ctrl.process_input(InputEvent::EncoderCw);
+        assert_eq!(ctrl.take_homing_jog(), Some((Axis::Z, 1)));
+    }
+
+    #[test]
+    fn test_homing_type_manual_waits_for_confirm() {
+        let mut ctrl = typed_homing_controller(HomingType::Manual, HomingType::Endstop);
+        assert!(ctrl.start_homing());
+        assert_eq!(ctrl.manual_homing_axis(), Some(Axis::Z));
+        assert!(!ctrl.is_homing(Axis::Z));
+
+        // Endstop reports don't home a manual axis
+        assert_eq!(trigger_endstop(&mut ctrl, Axis::Z), None);
+        assert_eq!(ctrl.manual_homing_axis(), Some(Axis::Z));
+
+        // Jog with the encoder
+        ctrl.process_input(InputEvent::EncoderCw);
+        ctrl.process_input(InputEvent::EncoderCw);
+        ctrl.process_input(InputEvent::EncoderCcw);
+        assert_eq!(ctrl.take_homing_jog(), Some((Axis::Z, 1)));
+        assert_eq!(ctrl.take_homing_jog(), None);
+        ctrl.process_input(InputEvent::EncoderCcw);
+
+        // Click confirms; X then homes on its endstop
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        assert_eq!(ctrl.take_homing_jog(), None);
+        assert_eq!(ctrl.manual_homing_axis(), None);
+        assert!(ctrl.is_homing(Axis::X));
+        assert_eq!(ctrl.state(), State::Boot);
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::X),
+            Some(Event::BootComplete)
+        );
+        assert_eq!(ctrl.state(), State::Idle);
+    }
+
+    #[test]
+    fn test_homing_type_endstop_uses_switch() {
+        let mut ctrl = typed_homing_controller(HomingType::Endstop, HomingType::Endstop);
+        assert!(ctrl.start_homing());
+        assert!(ctrl.is_homing(Axis::Z));
+        assert_eq!(ctrl.manual_homing_axis(), None);
+
+        // Clicking doesn't skip a physical home
+        assert_eq!(ctrl.process_input(InputEvent::EncoderClick), None);
+        assert!(ctrl.is_homing(Axis::Z));
+
+        assert_eq!(trigger_endstop(&mut ctrl, Axis::Z), None);
+        assert_eq!(
+            trigger_endstop(&mut ctrl, Axis::X),
+            Some(Event::BootComplete)
+        );
+    }
+
+    fn enter_autotune_confirm(ctrl: &mut Controller) {
+        let profiles = [make_profile("Clean", 120, 60)];
+        let jars = [make_jar("clean")];
+        let programs = [make_program("Test", &[("clean", "Clean")])];
+
+        ctrl.load_config(&programs, &profiles, &jars);
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderCcw); // Wrap to autotune item
+        assert!(ctrl.is_autotune_selected());
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.state(), State::Autotuning);
+        assert_eq!(ctrl.autotune_phase(), AutotunePhase::Confirming);
+    }
+
+    #[test]
+    fn test_active_pid_from_calibration() {
+        use isochron_core::config::HeaterCalibration;
+
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        assert_eq!(ctrl.active_pid(), None);
+
+        let mut calibration = CalibrationData::new();
+        calibration.set(HeaterCalibration::new(0, 150, 10, 50));
+        ctrl.load_calibration(&calibration);
+        assert_eq!(ctrl.active_pid(), Some((150, 10, 50)));
+    }
+
+    #[test]
+    fn test_autotune_starts_without_calibration() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        enter_autotune_confirm(&mut ctrl);
+
+        let event = ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(event, Some(Event::StartAutotune));
+        assert_eq!(ctrl.autotune_phase(), AutotunePhase::Running);
+    }
+
+    #[test]
+    fn test_autotune_overwrite_requires_confirmation() {
+        use isochron_core::config::HeaterCalibration;
+
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let mut calibration = CalibrationData::new();
+        calibration.set(HeaterCalibration::new(0, 150, 10, 50));
+        ctrl.load_calibration(&calibration);
+        enter_autotune_confirm(&mut ctrl);
+
+        // First click only asks to confirm the overwrite
+        let event = ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(event, None);
+        assert_eq!(ctrl.autotune_phase(), AutotunePhase::ConfirmOverwrite);
+
+        // Second click starts autotune
+        let event = ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(event, Some(Event::StartAutotune));
+        assert_eq!(ctrl.autotune_phase(), AutotunePhase::Running);
+    }
+
+    #[test]
+    fn test_autotune_overwrite_back_out() {
+        use isochron_core::config::HeaterCalibration;
+
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        let mut calibration = CalibrationData::new();
+        calibration.set(HeaterCalibration::new(0, 150, 10, 50));
+        ctrl.load_calibration(&calibration);
+        enter_autotune_confirm(&mut ctrl);
+
+        ctrl.process_input(InputEvent::EncoderClick);
+        assert_eq!(ctrl.autotune_phase(), AutotunePhase::ConfirmOverwrite);
+
+        // Long press leaves calibration untouched
+        let event = ctrl.process_input(InputEvent::EncoderLongPress);
+        assert_eq!(event, None);
+        assert_eq!(ctrl.state(), State::Idle);
+        assert_eq!(ctrl.active_pid(), Some((150, 10, 50)));
+    }
+
+    #[test]
+    fn test_autotune_complete_updates_active_pid() {
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        enter_autotune_confirm(&mut ctrl);
+        ctrl.process_input(InputEvent::EncoderClick);
+
+        ctrl.set_autotune_complete(200, 20, 80);
+        assert_eq!(ctrl.active_pid(), Some((200, 20, 80)));
+        assert_eq!(ctrl.calibration_saved(), None);
+
+        ctrl.set_calibration_saved(true);
+        assert_eq!(ctrl.calibration_saved(), Some(true));
     }
 }
@@ -16,5 +16,9 @@
 
 pub mod protocol;
 pub mod renderer;
+pub mod screens;
+pub mod throttle;
 
 pub use renderer::{Renderer, Screen};
+pub use screens::render_state;
+pub use throttle::{RenderPass, RenderRequest, RenderThrottle};
@@ -2,103 +2,102 @@
 //!
 //! Provides convenience functions for encoding and sending display commands.
 
-use isochron_protocol::{Frame, FrameError, PicoMessage};
+use isochron_core::config::CONFIG_SCHEMA_VERSION;
+use isochron_protocol::{Frame, FrameError, LinkTestPattern, PicoMessage, CHANNEL_TELEMETRY};
 
-/// Encode a screen to a series of frames
+use super::Screen;
+
+/// Empty screen, the display's state right after a clear
+static BLANK_SCREEN: Screen = Screen::new();
+
+/// Encode the frames turning the display's `prev` screen into `screen`
 ///
-/// Returns frames for:
+/// A full redraw returns frames for:
 /// 1. Clear screen
 /// 2. Text for each non-empty line
 /// 3. Invert command if a row is selected
-pub fn encode_screen(screen: &super::Screen) -> impl Iterator<Item = Frame> + '_ {
-    ScreenEncoder::new(screen)
+///
+/// Otherwise only rows that changed are sent, plus an invert command when the
+/// inverted row moved. Without a previous screen, or when the inverted
+/// row is removed (which only a clear undoes on the display), the screen
+/// is cleared and redrawn in full.
+pub fn encode_screen_update<'a>(
+    screen: &'a Screen,
+    prev: Option<&'a Screen>,
+) -> impl Iterator<Item = Frame> + 'a {
+    let prev = prev.filter(|prev| prev.inverted_row().is_none() || screen.inverted_row().is_some());
+    let (clear, base) = match prev {
+        Some(prev) => (None, prev),
+        None => (PicoMessage::Clear.to_frame().ok(), &BLANK_SCREEN),
+    };
+    let invert = screen
+        .inverted_row()
+        .filter(|&row| base.inverted_row() != Some(row))
+        .and_then(|row| {
+            PicoMessage::Invert {
+                row,
+                start_col: 0,
+                end_col: 20,
+            }
+            .to_frame()
+            .ok()
+        });
+
+    let lines = screen
+        .diff(base)
+        .filter_map(|(row, text)| PicoMessage::Text { row, col: 0, text }.to_frame().ok());
+    clear.into_iter().chain(lines).chain(invert)
 }
 
-/// Iterator that encodes a screen into frames
-struct ScreenEncoder<'a> {
-    screen: &'a super::Screen,
-    state: EncoderState,
-    current_row: u8,
+/// Build a PONG response frame
+pub fn pong_frame() -> Result<Frame, FrameError> {
+    PicoMessage::Pong.to_frame()
 }
 
-#[derive(Clone, Copy)]
-enum EncoderState {
-    Clear,
-    Lines,
-    Selection,
-    Done,
+/// Build a link configuration frame carrying the heartbeat interval
+pub fn link_config_frame(heartbeat_ms: u16) -> Result<Frame, FrameError> {
+    PicoMessage::LinkConfig { heartbeat_ms }.to_frame()
 }
 
-impl<'a> ScreenEncoder<'a> {
-    fn new(screen: &'a super::Screen) -> Self {
-        Self {
-            screen,
-            state: EncoderState::Clear,
-            current_row: 0,
-        }
-    }
+/// Build a link test frame carrying a known pattern
+pub fn link_test_frame(pattern: LinkTestPattern) -> Result<Frame, FrameError> {
+    PicoMessage::LinkTest { pattern }.to_frame()
 }
 
-impl<'a> Iterator for ScreenEncoder<'a> {
-    type Item = Frame;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.state {
-                EncoderState::Clear => {
-                    self.state = EncoderState::Lines;
-                    return PicoMessage::Clear.to_frame().ok();
-                }
-                EncoderState::Lines => {
-                    while self.current_row < 8 {
-                        let row = self.current_row;
-                        self.current_row += 1;
-
-                        let line = self.screen.get_line(row);
-                        if !line.is_empty() {
-                            let msg = PicoMessage::Text {
-                                row,
-                                col: 0,
-                                text: line,
-                            };
-                            return msg.to_frame().ok();
-                        }
-                    }
-                    self.state = EncoderState::Selection;
-                }
-                EncoderState::Selection => {
-                    self.state = EncoderState::Done;
-                    if let Some(row) = self.screen.selected_row() {
-                        if self.screen.invert_selection() {
-                            let msg = PicoMessage::Invert {
-                                row,
-                                start_col: 0,
-                                end_col: 20,
-                            };
-                            return msg.to_frame().ok();
-                        }
-                    }
-                }
-                EncoderState::Done => return None,
-            }
-        }
-    }
+/// Build a frame carrying scheduler state to the host
+pub fn scheduler_state_frame(state: &PicoMessage) -> Result<Frame, FrameError> {
+    Ok(state.to_frame()?.on_channel(CHANNEL_TELEMETRY))
 }
 
-/// Build a PONG response frame
-pub fn pong_frame() -> Result<Frame, FrameError> {
-    PicoMessage::Pong.to_frame()
+/// Build a version info frame reporting the config schema version
+pub fn version_info_frame() -> Result<Frame, FrameError> {
+    PicoMessage::VersionInfo {
+        config_schema: CONFIG_SCHEMA_VERSION,
+    }
+    .to_frame()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::display::Screen;
+    use isochron_protocol::messages::{MSG_CLEAR, MSG_INVERT};
+    use isochron_protocol::ControllerCommand;
+
+    #[test]
+    fn test_version_info_reports_schema() {
+        let frame = version_info_frame().unwrap();
+        assert_eq!(
+            ControllerCommand::from_frame(&frame).unwrap(),
+            ControllerCommand::VersionInfo {
+                config_schema: CONFIG_SCHEMA_VERSION
+            }
+        );
+    }
 
     #[test]
     fn test_encode_empty_screen() {
         let screen = Screen::new();
-        let frames: Vec<_> = encode_screen(&screen).collect();
+        let frames: Vec<_> = encode_screen_update(&screen, None).collect();
 
         // Should just be a clear command
         assert_eq!(frames.len(), 1);
@@ -110,7 +109,7 @@ mod tests {
         screen.set_line(0, "Hello");
         screen.set_line(2, "World");
 
-        let frames: Vec<_> = encode_screen(&screen).collect();
+        let frames: Vec<_> = encode_screen_update(&screen, None).collect();
 
         // Clear + 2 text commands
         assert_eq!(frames.len(), 3);
@@ -122,9 +121,99 @@ mod tests {
         screen.set_line(1, "Selected");
         screen.set_selection(1, true);
 
-        let frames: Vec<_> = encode_screen(&screen).collect();
+        let frames: Vec<_> = encode_screen_update(&screen, None).collect();
 
         // Clear + text + invert
         assert_eq!(frames.len(), 3);
     }
+
+    /// Rows of the text commands among `frames`
+    fn text_rows(frames: &[Frame]) -> Vec<u8> {
+        frames
+            .iter()
+            .filter_map(|frame| match ControllerCommand::from_frame(frame) {
+                Ok(ControllerCommand::Text { row, .. }) => Some(row),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_update_sends_changed_rows_only() {
+        let mut prev = Screen::new();
+        prev.set_line(0, "Full Clean");
+        prev.set_line(3, "Motor: 120 RPM");
+        prev.set_line(5, "[####      ]");
+
+        let mut screen = prev.clone();
+        screen.set_line(5, "[#####     ]");
+        screen.set_line(3, "");
+
+        let frames: Vec<_> = encode_screen_update(&screen, Some(&prev)).collect();
+        assert!(frames.iter().all(|f| f.msg_type != MSG_CLEAR));
+        assert_eq!(text_rows(&frames), [3, 5]);
+
+        // A cleared row is blanked on the display
+        assert_eq!(
+            ControllerCommand::from_frame(&frames[0]).unwrap(),
+            ControllerCommand::Text {
+                row: 3,
+                col: 0,
+                text: heapless::String::new()
+            }
+        );
+
+        // Nothing changed, nothing sent
+        assert_eq!(encode_screen_update(&screen, Some(&screen)).count(), 0);
+    }
+
+    #[test]
+    fn test_update_moves_selection() {
+        let mut prev = Screen::new();
+        prev.set_line(1, "> Full Clean");
+        prev.set_line(2, "  Quick Clean");
+        prev.set_selection(1, true);
+
+        let mut screen = Screen::new();
+        screen.set_line(1, "  Full Clean");
+        screen.set_line(2, "> Quick Clean");
+        screen.set_selection(2, true);
+
+        let frames: Vec<_> = encode_screen_update(&screen, Some(&prev)).collect();
+        assert_eq!(text_rows(&frames), [1, 2]);
+        assert_eq!(
+            ControllerCommand::from_frame(frames.last().unwrap()).unwrap(),
+            ControllerCommand::Invert {
+                row: 2,
+                start_col: 0,
+                end_col: 20
+            }
+        );
+
+        // Only the highlight moved: just the invert command
+        let mut moved = prev.clone();
+        moved.set_selection(2, true);
+        let frames: Vec<_> = encode_screen_update(&moved, Some(&prev)).collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].msg_type, MSG_INVERT);
+    }
+
+    #[test]
+    fn test_update_redraws_when_selection_removed() {
+        let mut prev = Screen::new();
+        prev.set_line(0, "=== SELECT PROGRAM ===");
+        prev.set_line(1, "> Full Clean");
+        prev.set_selection(1, true);
+
+        let mut screen = prev.clone();
+        screen.clear();
+        screen.set_line(0, "=== SELECT PROGRAM ===");
+        screen.set_line(1, "Starting...");
+
+        // The display can't un-invert a row without a clear, after which
+        // every row has to be sent again, unchanged or not
+        let frames: Vec<_> = encode_screen_update(&screen, Some(&prev)).collect();
+        assert_eq!(frames[0].msg_type, MSG_CLEAR);
+        assert_eq!(text_rows(&frames), [0, 1]);
+    }
 }
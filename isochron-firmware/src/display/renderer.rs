@@ -5,10 +5,14 @@
 //! The V0 Display has an 128x64 OLED with 8 rows of 21 characters.
 //! We use a simple text-based UI with inverted regions for selection.
 
-use heapless::String;
+use heapless::{String, Vec};
+use isochron_core::state::State;
+use isochron_core::traits::SensorRaw;
+use isochron_core::util::TemperatureC10;
 use isochron_protocol::messages::{DISPLAY_COLS, DISPLAY_ROWS};
 
 /// A screen buffer that can be sent to the display
+#[derive(Clone)]
 pub struct Screen {
     /// Lines of text (8 rows max)
     lines: [String<22>; 8],
@@ -50,8 +54,7 @@ impl Screen {
     pub fn set_line(&mut self, row: u8, text: &str) {
         if (row as usize) < self.lines.len() {
             self.lines[row as usize].clear();
-            let _ =
-                self.lines[row as usize].push_str(&text[..text.len().min(DISPLAY_COLS as usize)]);
+            let _ = self.lines[row as usize].push_str(truncate(text, DISPLAY_COLS as usize));
         }
     }
 
@@ -81,6 +84,23 @@ impl Screen {
     pub fn invert_selection(&self) -> bool {
         self.invert_selection
     }
+
+    /// Row shown inverted on the display, if any
+    pub fn inverted_row(&self) -> Option<u8> {
+        self.selected_row.filter(|_| self.invert_selection)
+    }
+
+    /// Rows whose text differs from `prev`, with their new text
+    ///
+    /// A row cleared since `prev` comes out as an empty string.
+    pub fn diff<'a>(&'a self, prev: &'a Screen) -> impl Iterator<Item = (u8, &'a str)> + 'a {
+        self.lines
+            .iter()
+            .zip(prev.lines.iter())
+            .enumerate()
+            .filter(|(_, (line, prev_line))| line != prev_line)
+            .map(|(row, (line, _))| (row as u8, line.as_str()))
+    }
 }
 
 impl Default for Screen {
@@ -92,6 +112,12 @@ impl Default for Screen {
 /// Screen renderer for different UI states
 pub struct Renderer {
     screen: Screen,
+    /// Reserve row 0 for a persistent status header
+    status_header: bool,
+    /// Show whole-program progress on the running screen
+    overall_progress: bool,
+    /// Mark the program list while quiet mode is on
+    quiet: bool,
 }
 
 impl Renderer {
@@ -99,7 +125,61 @@ impl Renderer {
     pub const fn new() -> Self {
         Self {
             screen: Screen::new(),
+            status_header: false,
+            overall_progress: false,
+            quiet: false,
+        }
+    }
+
+    /// Reserve row 0 for a persistent status header
+    ///
+    /// Screens that support the header draw it on row 0 and lay out
+    /// their content below it.
+    pub fn set_status_header(&mut self, enabled: bool) {
+        self.status_header = enabled;
+    }
+
+    /// Show whole-program progress on the running screen
+    ///
+    /// Adds an overall elapsed/total line and a second progress bar
+    /// below the step progress. Motor and temperature share a row to
+    /// make room.
+    pub fn set_overall_progress(&mut self, enabled: bool) {
+        self.overall_progress = enabled;
+    }
+
+    /// Show that quiet mode is on in the program list title
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// First row available for screen content
+    fn content_top(&self) -> u8 {
+        if self.status_header {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Draw the status header: label on the left, elapsed time on the right
+    fn render_header(&mut self, label: &str, elapsed_s: Option<u32>) {
+        let mut time: String<22> = String::new();
+        if let Some(elapsed) = elapsed_s {
+            let _ = write_to_string(
+                &mut time,
+                format_args!("{}:{:02}", elapsed / 60, elapsed % 60),
+            );
         }
+
+        let cols = DISPLAY_COLS as usize;
+        let mut header: String<22> = String::new();
+        let _ = header.push_str(truncate(label, cols.saturating_sub(time.len() + 1)));
+        while header.len() + time.len() < cols {
+            let _ = header.push(' ');
+        }
+        let _ = header.push_str(&time);
+        self.screen.set_line(0, &header);
     }
 
     /// Get the current screen buffer
@@ -115,6 +195,21 @@ impl Renderer {
         self.screen.set_line(6, " Connecting...");
     }
 
+    /// Render the manual homing prompt
+    ///
+    /// The user jogs `axis` to its home position with the encoder and
+    /// clicks once it is there.
+    pub fn render_manual_homing(&mut self, axis: &str) {
+        self.screen.clear();
+        self.screen.set_line(0, "====== HOMING =======");
+
+        let mut axis_line: String<22> = String::new();
+        let _ = write_to_string(&mut axis_line, format_args!("Move {} axis home", axis));
+        self.screen.set_line(2, &axis_line);
+        self.screen.set_line(4, "Turn: jog 1mm");
+        self.screen.set_line(7, "CLICK when home");
+    }
+
     /// Render the main menu
     ///
     /// # Arguments
@@ -122,10 +217,19 @@ impl Renderer {
     /// - `selected`: Currently selected index
     pub fn render_menu(&mut self, programs: &[&str], selected: usize) {
         self.screen.clear();
-        self.screen.set_line(0, "=== SELECT PROGRAM ===");
+        let top = self.content_top();
+        if self.status_header {
+            self.render_header("Ready", None);
+        }
+        let title = if self.quiet {
+            "=== SELECT (QUIET) ==="
+        } else {
+            "=== SELECT PROGRAM ==="
+        };
+        self.screen.set_line(top, title);
 
         for (i, program) in programs.iter().take(6).enumerate() {
-            let row = (i + 1) as u8;
+            let row = top + 1 + i as u8;
             let mut line: String<22> = String::new();
 
             // Add selection indicator
@@ -140,7 +244,7 @@ impl Renderer {
         }
 
         if selected < 6 {
-            self.screen.set_selection((selected + 1) as u8, true);
+            self.screen.set_selection(top + 1 + selected as u8, true);
         }
     }
 
@@ -148,21 +252,35 @@ impl Renderer {
     ///
     /// # Arguments
     /// - `name`: Program name
+    /// - `notes`: Program description, word-wrapped below the header
     /// - `steps`: List of step descriptions (jar + profile)
     /// - `total_time_s`: Total estimated time in seconds
-    pub fn render_program_detail(&mut self, name: &str, steps: &[&str], total_time_s: u32) {
+    pub fn render_program_detail(
+        &mut self,
+        name: &str,
+        notes: Option<&str>,
+        steps: &[&str],
+        total_time_s: u32,
+    ) {
         self.screen.clear();
 
         // Header
         let mut header: String<22> = String::new();
         let _ = header.push_str("= ");
-        let _ = header.push_str(&name[..name.len().min(17)]);
+        let _ = header.push_str(truncate(name, 17));
         let _ = header.push_str(" =");
         self.screen.set_line(0, &header);
 
+        // Notes take rows from the step list
+        let notes = notes.map(|text| wrap_text(text, DISPLAY_COLS as usize));
+        let note_rows = notes.as_ref().map_or(0, |rows| rows.len());
+        for (i, line) in notes.iter().flatten().enumerate() {
+            self.screen.set_line((i + 1) as u8, line);
+        }
+
         // Steps
-        for (i, step) in steps.iter().take(5).enumerate() {
-            let row = (i + 1) as u8;
+        for (i, step) in steps.iter().take(5 - note_rows).enumerate() {
+            let row = (note_rows + i + 1) as u8;
             let mut line: String<22> = String::new();
             let _ = write_to_string(&mut line, format_args!("{}. {}", i + 1, step));
             self.screen.set_line(row, &line);
@@ -190,6 +308,9 @@ impl Renderer {
     /// - `rpm`: Current motor RPM
     /// - `elapsed_s`: Elapsed time in seconds
     /// - `total_s`: Total time for this step in seconds
+    /// - `program_elapsed_s`: Elapsed time for the whole program in seconds
+    /// - `program_total_s`: Total time for the whole program in seconds
+    /// - `cycle`: Current and total iterations of the profile
     /// - `temp_c`: Current temperature (None if no heater)
     /// - `target_c`: Target temperature (None if no heater)
     #[allow(clippy::too_many_arguments)]
@@ -200,16 +321,23 @@ impl Renderer {
         total_steps: u8,
         jar_name: &str,
         profile_name: &str,
+        cycle: (u8, u8),
         rpm: u16,
         elapsed_s: u32,
         total_s: u32,
+        program_elapsed_s: u32,
+        program_total_s: u32,
         temp_c: Option<i16>,
         target_c: Option<i16>,
     ) {
         self.screen.clear();
 
-        // Header: program name
-        self.screen.set_line(0, program_name);
+        // Header: program name, plus elapsed time when the status header is on
+        if self.status_header {
+            self.render_header(program_name, Some(elapsed_s));
+        } else {
+            self.screen.set_line(0, program_name);
+        }
 
         // Step info
         let mut step_line: String<22> = String::new();
@@ -219,43 +347,59 @@ impl Renderer {
         );
         self.screen.set_line(1, &step_line);
 
-        // Profile
+        // Profile, with the cycle for multi-iteration profiles
         let mut profile_line: String<22> = String::new();
-        let _ = write_to_string(&mut profile_line, format_args!("Profile: {}", profile_name));
+        let (cycle_num, total_cycles) = cycle;
+        let _ = if total_cycles > 1 {
+            write_to_string(
+                &mut profile_line,
+                format_args!("Cycle {}/{}: {}", cycle_num, total_cycles, profile_name),
+            )
+        } else {
+            write_to_string(&mut profile_line, format_args!("Profile: {}", profile_name))
+        };
         self.screen.set_line(2, &profile_line);
 
         // Motor status
         let mut motor_line: String<22> = String::new();
         let _ = write_to_string(&mut motor_line, format_args!("Motor: {} RPM", rpm));
-        self.screen.set_line(3, &motor_line);
-
-        // Temperature (if applicable)
-        if let (Some(current), Some(target)) = (temp_c, target_c) {
-            let mut temp_line: String<22> = String::new();
-            let _ = write_to_string(
-                &mut temp_line,
-                format_args!("Temp: {}C / {}C", current, target),
-            );
-            self.screen.set_line(4, &temp_line);
-        }
 
-        // Progress bar
-        let progress = if total_s > 0 {
-            ((elapsed_s * 20) / total_s).min(20) as usize
+        // Step progress moves up a row when overall progress is shown
+        let step_row = if self.overall_progress {
+            // Temperature (if applicable) shares the motor row
+            match (temp_c, target_c) {
+                (Some(current), Some(target)) => {
+                    let _ =
+                        write_to_string(&mut motor_line, format_args!(" {}/{}C", current, target));
+                }
+                (None, Some(target)) => {
+                    let _ = write_to_string(&mut motor_line, format_args!(" --/{}C", target));
+                }
+                _ => {}
+            }
+            4
         } else {
-            0
-        };
-        let mut bar: String<22> = String::new();
-        let _ = bar.push('[');
-        for i in 0..20 {
-            if i < progress {
-                let _ = bar.push('#');
-            } else {
-                let _ = bar.push('-');
+            // Temperature (if applicable), dashed without a reading
+            if let Some(target) = target_c {
+                let mut temp_line: String<22> = String::new();
+                let _ = match temp_c {
+                    Some(current) => write_to_string(
+                        &mut temp_line,
+                        format_args!("Temp: {}C / {}C", current, target),
+                    ),
+                    None => {
+                        write_to_string(&mut temp_line, format_args!("Temp: --C / {}C", target))
+                    }
+                };
+                self.screen.set_line(4, &temp_line);
             }
-        }
-        let _ = bar.push(']');
-        self.screen.set_line(5, &bar);
+            5
+        };
+        self.screen.set_line(3, &motor_line);
+
+        // Progress bar
+        self.screen
+            .set_line(step_row, &progress_bar(elapsed_s, total_s));
 
         // Time remaining
         let remaining = total_s.saturating_sub(elapsed_s);
@@ -266,10 +410,28 @@ impl Renderer {
             &mut time_line,
             format_args!("Remaining: {}:{:02}", mins, secs),
         );
-        self.screen.set_line(6, &time_line);
+        self.screen.set_line(step_row + 1, &time_line);
 
-        // Instructions
-        self.screen.set_line(7, "CLICK=Pause");
+        if self.overall_progress {
+            // Whole-program progress
+            let mut overall_line: String<22> = String::new();
+            let _ = write_to_string(
+                &mut overall_line,
+                format_args!(
+                    "Overall: {}:{:02}/{}:{:02}",
+                    program_elapsed_s / 60,
+                    program_elapsed_s % 60,
+                    program_total_s / 60,
+                    program_total_s % 60
+                ),
+            );
+            self.screen.set_line(6, &overall_line);
+            self.screen
+                .set_line(7, &progress_bar(program_elapsed_s, program_total_s));
+        } else {
+            // Instructions
+            self.screen.set_line(7, "CLICK=Pause");
+        }
     }
 
     /// Render the paused screen
@@ -289,7 +451,10 @@ impl Renderer {
     }
 
     /// Render the step complete screen (for manual machines)
-    pub fn render_step_complete(&mut self, next_jar: &str) {
+    ///
+    /// `auto_advance_s` is the countdown before the screen confirms itself,
+    /// if auto-advance is on.
+    pub fn render_step_complete(&mut self, next_jar: &str, auto_advance_s: Option<u32>) {
         self.screen.clear();
         self.screen.set_line(2, "  Step Complete!");
         self.screen.set_line(4, "Move basket to:");
@@ -298,7 +463,15 @@ impl Renderer {
         let _ = write_to_string(&mut jar_line, format_args!("  -> {}", next_jar));
         self.screen.set_line(5, &jar_line);
 
-        self.screen.set_line(7, "CLICK when ready");
+        self.render_ready_hint(auto_advance_s);
+    }
+
+    /// Mark the current screen as locked to input
+    ///
+    /// Drawn over the bottom row after the state's own screen, replacing
+    /// any click hint there, since clicks are ignored while locked.
+    pub fn render_lock_indicator(&mut self) {
+        self.screen.set_line(7, "      [LOCKED]");
     }
 
     /// Render the program complete screen
@@ -319,8 +492,34 @@ impl Renderer {
         self.screen.set_line(7, "CLICK to continue");
     }
 
+    /// Render the notice shown after a watchdog reset
+    ///
+    /// `last_state` and `step` come from the breadcrumb, if one survived;
+    /// step 0 means no program was running.
+    pub fn render_recovered(&mut self, last_state: Option<State>, step: u8) {
+        self.screen.clear();
+        self.screen.set_line(0, "Recovered from fault");
+        self.screen.set_line(2, "Watchdog reset");
+
+        if let Some(state) = last_state {
+            let mut state_line: String<22> = String::new();
+            let _ = write_to_string(&mut state_line, format_args!("Was: {}", state.name()));
+            self.screen.set_line(4, &state_line);
+        }
+        if step > 0 {
+            let mut step_line: String<22> = String::new();
+            let _ = write_to_string(&mut step_line, format_args!("Step {}", step));
+            self.screen.set_line(5, &step_line);
+        }
+
+        self.screen.set_line(7, "CLICK to continue");
+    }
+
     /// Render an error screen
-    pub fn render_error(&mut self, error_type: &str, details: &str) {
+    ///
+    /// `auto_clears` marks a fault that clears by itself once resolved,
+    /// which needs no power cycle.
+    pub fn render_error(&mut self, error_type: &str, details: &str, auto_clears: bool) {
         self.screen.clear();
         self.screen.set_line(0, "!!! ERROR !!!");
         self.screen.set_line(2, error_type);
@@ -333,11 +532,34 @@ impl Renderer {
             }
         }
 
-        self.screen.set_line(7, "Power cycle required");
+        if auto_clears {
+            self.screen.set_line(7, "Clears on recovery");
+        } else {
+            self.screen.set_line(7, "Power cycle required");
+        }
+    }
+
+    /// Render a placeholder for a state without a usable screen
+    ///
+    /// Shows the state name so the display never looks frozen on the
+    /// previous screen.
+    pub fn render_fallback(&mut self, state: State) {
+        self.screen.clear();
+        let mut line: String<22> = String::new();
+        let _ = write_to_string(&mut line, format_args!("{}...", state.name()));
+        self.screen.set_line(3, &line);
     }
 
     /// Render awaiting jar screen (manual machine waiting for user)
-    pub fn render_awaiting_jar(&mut self, jar_name: &str, action: &str) {
+    ///
+    /// `auto_advance_s` is the countdown before the screen confirms itself,
+    /// if auto-advance is on.
+    pub fn render_awaiting_jar(
+        &mut self,
+        jar_name: &str,
+        action: &str,
+        auto_advance_s: Option<u32>,
+    ) {
         self.screen.clear();
         self.screen.set_line(2, action);
 
@@ -345,17 +567,57 @@ impl Renderer {
         let _ = write_to_string(&mut jar_line, format_args!("  -> {}", jar_name));
         self.screen.set_line(4, &jar_line);
 
-        self.screen.set_line(7, "CLICK when ready");
+        self.render_ready_hint(auto_advance_s);
+    }
+
+    /// Bottom-row prompt for a manual step, with the auto-advance countdown
+    fn render_ready_hint(&mut self, auto_advance_s: Option<u32>) {
+        match auto_advance_s {
+            Some(remaining_s) => {
+                let mut hint: String<22> = String::new();
+                let _ =
+                    write_to_string(&mut hint, format_args!("CLICK or auto in {}s", remaining_s));
+                self.screen.set_line(7, &hint);
+            }
+            None => self.screen.set_line(7, "CLICK when ready"),
+        }
     }
 
     /// Render autotune confirmation screen
     ///
-    /// Shows target temperature and asks for confirmation.
-    pub fn render_autotune_confirm(&mut self, target_c: i16) {
+    /// Shows target temperature and asks for confirmation. If the heater is
+    /// already calibrated, the active PID coefficients are shown instead of
+    /// the introduction text.
+    pub fn render_autotune_confirm(&mut self, target_c: i16, active_pid: Option<(i16, i16, i16)>) {
         self.screen.clear();
         self.screen.set_line(0, "=== HEATER AUTOTUNE ==");
-        self.screen.set_line(2, "This will calibrate");
-        self.screen.set_line(3, "PID coefficients.");
+
+        if let Some((kp_x100, ki_x100, kd_x100)) = active_pid {
+            self.screen.set_line(2, "Active PID values:");
+
+            let mut pi_line: String<22> = String::new();
+            let _ = write_to_string(
+                &mut pi_line,
+                format_args!(
+                    "Kp:{}.{:02} Ki:{}.{:02}",
+                    kp_x100 / 100,
+                    (kp_x100 % 100).abs(),
+                    ki_x100 / 100,
+                    (ki_x100 % 100).abs()
+                ),
+            );
+            self.screen.set_line(3, &pi_line);
+
+            let mut d_line: String<22> = String::new();
+            let _ = write_to_string(
+                &mut d_line,
+                format_args!("Kd:{}.{:02}", kd_x100 / 100, (kd_x100 % 100).abs()),
+            );
+            self.screen.set_line(4, &d_line);
+        } else {
+            self.screen.set_line(2, "This will calibrate");
+            self.screen.set_line(3, "PID coefficients.");
+        }
 
         let mut temp_line: String<22> = String::new();
         let _ = write_to_string(&mut temp_line, format_args!("Target: {}C", target_c));
@@ -364,6 +626,80 @@ impl Renderer {
         self.screen.set_line(7, "CLICK=Start HOLD=Back");
     }
 
+    /// Render autotune overwrite confirmation screen
+    ///
+    /// Shown when a stored calibration would be replaced by a new autotune.
+    pub fn render_autotune_overwrite(&mut self) {
+        self.screen.clear();
+        self.screen.set_line(0, "=== HEATER AUTOTUNE ==");
+        self.screen.set_line(2, "Calibration stored.");
+        self.screen.set_line(3, "Autotune will");
+        self.screen.set_line(4, "overwrite it.");
+        self.screen.set_line(7, "CLICK=Yes  HOLD=No");
+    }
+
+    /// Render the profile edit screen
+    ///
+    /// The value the encoder adjusts is marked with an arrow.
+    pub fn render_profile_edit(
+        &mut self,
+        label: &str,
+        step_num: u8,
+        total_steps: u8,
+        rpm: u16,
+        time_s: u16,
+        editing_time: bool,
+    ) {
+        self.screen.clear();
+        self.screen.set_line(0, "=== EDIT PROFILE ===");
+
+        let mut step_line: String<22> = String::new();
+        let _ = write_to_string(
+            &mut step_line,
+            format_args!("Step {}/{}: {}", step_num, total_steps, truncate(label, 11)),
+        );
+        self.screen.set_line(1, &step_line);
+
+        let marker = |selected: bool| if selected { '>' } else { ' ' };
+        let mut rpm_line: String<22> = String::new();
+        let _ = write_to_string(
+            &mut rpm_line,
+            format_args!("{} Speed: {} RPM", marker(!editing_time), rpm),
+        );
+        self.screen.set_line(3, &rpm_line);
+
+        let mut time_line: String<22> = String::new();
+        let _ = write_to_string(
+            &mut time_line,
+            format_args!(
+                "{} Time:  {}:{:02}",
+                marker(editing_time),
+                time_s / 60,
+                time_s % 60
+            ),
+        );
+        self.screen.set_line(4, &time_line);
+
+        self.screen.set_line(7, "CLICK=Next <>=Done");
+    }
+
+    /// Render the "Save changes?" prompt after editing a profile
+    pub fn render_save_prompt(&mut self, label: &str, yes: bool) {
+        self.screen.clear();
+        self.screen.set_line(0, "=== EDIT PROFILE ===");
+        self.screen.set_line(2, label);
+        self.screen.set_line(3, "Save changes?");
+        self.screen.set_line(
+            5,
+            if yes {
+                "  > Yes     No"
+            } else {
+                "    Yes   > No"
+            },
+        );
+        self.screen.set_line(7, "CLICK=Confirm");
+    }
+
     /// Render autotune progress screen
     ///
     /// Shows oscillation count and elapsed time.
@@ -405,8 +741,15 @@ impl Renderer {
 
     /// Render autotune complete screen
     ///
-    /// Shows calculated PID coefficients.
-    pub fn render_autotune_complete(&mut self, kp_x100: i16, ki_x100: i16, kd_x100: i16) {
+    /// Shows calculated PID coefficients and whether they were stored
+    /// (`saved` is None while the flash write is pending).
+    pub fn render_autotune_complete(
+        &mut self,
+        kp_x100: i16,
+        ki_x100: i16,
+        kd_x100: i16,
+        saved: Option<bool>,
+    ) {
         self.screen.clear();
         self.screen.set_line(0, " AUTOTUNE COMPLETE");
 
@@ -431,7 +774,12 @@ impl Renderer {
         );
         self.screen.set_line(4, &kd_line);
 
-        self.screen.set_line(6, "Coefficients saved!");
+        let status = match saved {
+            None => "Saving...",
+            Some(true) => "Coefficients saved!",
+            Some(false) => "Save failed!",
+        };
+        self.screen.set_line(6, status);
         self.screen.set_line(7, "CLICK to continue");
     }
 
@@ -442,6 +790,43 @@ impl Renderer {
         self.screen.set_line(3, reason);
         self.screen.set_line(7, "CLICK to continue");
     }
+
+    /// Render sensor diagnostics screen
+    ///
+    /// Shows the raw ADC count behind the heater temperature, the
+    /// resistance computed from it and the resulting temperature. Values
+    /// that could not be computed show as `--`.
+    pub fn render_diagnostics(&mut self, raw: Option<SensorRaw>) {
+        self.screen.clear();
+        self.screen.set_line(0, "=== DIAGNOSTICS ===");
+
+        if let Some(raw) = raw {
+            let mut adc_line: String<22> = String::new();
+            let _ = write_to_string(&mut adc_line, format_args!("ADC:  {}", raw.adc));
+            self.screen.set_line(2, &adc_line);
+
+            let mut r_line: String<22> = String::new();
+            let _ = match raw.resistance_ohms {
+                Some(ohms) => write_to_string(&mut r_line, format_args!("R:    {} ohm", ohms)),
+                None => write_to_string(&mut r_line, format_args!("R:    --")),
+            };
+            self.screen.set_line(3, &r_line);
+
+            let mut temp_line: String<22> = String::new();
+            let _ = match raw.temp_x10 {
+                Some(t) => write_to_string(
+                    &mut temp_line,
+                    format_args!("Temp: {}C", TemperatureC10::from_x10(t)),
+                ),
+                None => write_to_string(&mut temp_line, format_args!("Temp: --")),
+            };
+            self.screen.set_line(4, &temp_line);
+        } else {
+            self.screen.set_line(3, "No sensor reading");
+        }
+
+        self.screen.set_line(7, "CLICK=Back");
+    }
 }
 
 impl Default for Renderer {
@@ -450,6 +835,66 @@ impl Default for Renderer {
     }
 }
 
+/// Build a 20-segment progress bar for `elapsed_s` of `total_s`
+fn progress_bar(elapsed_s: u32, total_s: u32) -> String<22> {
+    let progress = if total_s > 0 {
+        ((elapsed_s * 20) / total_s).min(20) as usize
+    } else {
+        0
+    };
+    let mut bar: String<22> = String::new();
+    let _ = bar.push('[');
+    for i in 0..20 {
+        if i < progress {
+            let _ = bar.push('#');
+        } else {
+            let _ = bar.push('-');
+        }
+    }
+    let _ = bar.push(']');
+    bar
+}
+
+/// Most display rows program notes are wrapped onto
+const MAX_NOTE_ROWS: usize = 2;
+
+/// Word-wrap `text` into rows of at most `width` characters
+///
+/// Words longer than a row are split; text beyond `MAX_NOTE_ROWS` rows
+/// is dropped.
+fn wrap_text(text: &str, width: usize) -> Vec<&str, MAX_NOTE_ROWS> {
+    let mut rows = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let (row, next) = match rest.char_indices().nth(width) {
+            None => (rest, ""),
+            Some((limit, _)) => {
+                // Break at the last space that fits, if any
+                let split = if rest[limit..].starts_with(' ') {
+                    limit
+                } else {
+                    rest[..limit].rfind(' ').unwrap_or(limit)
+                };
+                rest.split_at(split)
+            }
+        };
+        if rows.push(row.trim_end()).is_err() {
+            break;
+        }
+        rest = next.trim_start();
+    }
+    rows
+}
+
+/// First `max_len` bytes of `text`, cut back to a character boundary
+fn truncate(text: &str, max_len: usize) -> &str {
+    let mut len = text.len().min(max_len);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    &text[..len]
+}
+
 /// Helper to write formatted output to a heapless String
 fn write_to_string(s: &mut String<22>, args: core::fmt::Arguments<'_>) -> core::fmt::Result {
     use core::fmt::Write;
@@ -477,6 +922,50 @@ mod tests {
         assert!(screen.selected_row().is_none());
     }
 
+    #[test]
+    fn test_render_diagnostics() {
+        let mut renderer = Renderer::new();
+        renderer.render_diagnostics(Some(SensorRaw {
+            adc: 3913,
+            resistance_ohms: Some(100_497),
+            temp_x10: Some(249),
+        }));
+        assert_eq!(renderer.screen().get_line(2), "ADC:  3913");
+        assert_eq!(renderer.screen().get_line(3), "R:    100497 ohm");
+        assert_eq!(renderer.screen().get_line(4), "Temp: 24.9C");
+
+        // An open sensor keeps its ADC count
+        renderer.render_diagnostics(Some(SensorRaw {
+            adc: 4095,
+            resistance_ohms: None,
+            temp_x10: None,
+        }));
+        assert_eq!(renderer.screen().get_line(2), "ADC:  4095");
+        assert_eq!(renderer.screen().get_line(3), "R:    --");
+        assert_eq!(renderer.screen().get_line(4), "Temp: --");
+
+        renderer.render_diagnostics(None);
+        assert_eq!(renderer.screen().get_line(3), "No sensor reading");
+    }
+
+    #[test]
+    fn test_render_diagnostics_fits_display() {
+        let mut renderer = Renderer::new();
+        renderer.render_diagnostics(Some(SensorRaw {
+            adc: u16::MAX,
+            resistance_ohms: Some(u32::MAX),
+            temp_x10: Some(i16::MIN),
+        }));
+
+        // Widest values are shown whole, not cut off at the edge
+        assert_eq!(renderer.screen().get_line(2), "ADC:  65535");
+        assert_eq!(renderer.screen().get_line(3), "R:    4294967295 ohm");
+        assert_eq!(renderer.screen().get_line(4), "Temp: -3276.8C");
+        for row in 0..DISPLAY_ROWS {
+            assert!(renderer.screen().get_line(row).len() <= DISPLAY_COLS as usize);
+        }
+    }
+
     #[test]
     fn test_render_boot() {
         let mut renderer = Renderer::new();
@@ -484,6 +973,17 @@ mod tests {
         assert!(renderer.screen().get_line(2).contains("ISOCHRON"));
     }
 
+    #[test]
+    fn test_render_manual_homing() {
+        let mut renderer = Renderer::new();
+        renderer.render_manual_homing("Z");
+        assert_eq!(renderer.screen().get_line(2), "Move Z axis home");
+        assert_eq!(renderer.screen().get_line(7), "CLICK when home");
+        for row in 0..DISPLAY_ROWS {
+            assert!(renderer.screen().get_line(row).len() <= DISPLAY_COLS as usize);
+        }
+    }
+
     #[test]
     fn test_render_menu() {
         let mut renderer = Renderer::new();
@@ -493,6 +993,78 @@ mod tests {
         // Selected item should have indicator
         assert!(renderer.screen().get_line(2).starts_with(">"));
         assert_eq!(renderer.screen().selected_row(), Some(2));
+        assert!(!renderer.screen().get_line(0).contains("QUIET"));
+
+        renderer.set_quiet(true);
+        renderer.render_menu(&programs, 1);
+        assert!(renderer.screen().get_line(0).contains("QUIET"));
+    }
+
+    #[test]
+    fn test_render_lock_indicator() {
+        let mut renderer = Renderer::new();
+        renderer.render_step_complete("rinse", None);
+        assert!(renderer.screen().get_line(7).contains("CLICK"));
+
+        renderer.render_lock_indicator();
+        assert!(renderer.screen().get_line(7).contains("LOCKED"));
+        assert!(renderer.screen().get_line(5).contains("rinse"));
+    }
+
+    #[test]
+    fn test_render_program_detail_notes() {
+        let mut renderer = Renderer::new();
+        renderer.render_program_detail(
+            "Gold",
+            Some("For gold-plated movements and dials"),
+            &["clean", "rinse", "dry"],
+            900,
+        );
+
+        let screen = renderer.screen();
+        assert_eq!(screen.get_line(1), "For gold-plated");
+        assert_eq!(screen.get_line(2), "movements and dials");
+        assert_eq!(screen.get_line(3), "1. clean");
+        assert_eq!(screen.get_line(5), "3. dry");
+        assert_eq!(screen.get_line(6), "Total: 15:00");
+    }
+
+    #[test]
+    fn test_render_program_detail_without_notes() {
+        let mut renderer = Renderer::new();
+        renderer.render_program_detail("Full", None, &["clean", "rinse"], 600);
+
+        // Steps follow the header directly
+        assert_eq!(renderer.screen().get_line(1), "1. clean");
+        assert_eq!(renderer.screen().get_line(2), "2. rinse");
+    }
+
+    #[test]
+    fn test_render_profile_edit() {
+        let mut renderer = Renderer::new();
+        renderer.render_profile_edit("Clean", 1, 3, 140, 90, true);
+        assert_eq!(renderer.screen().get_line(1), "Step 1/3: Clean");
+        assert_eq!(renderer.screen().get_line(3), "  Speed: 140 RPM");
+        assert_eq!(renderer.screen().get_line(4), "> Time:  1:30");
+
+        renderer.render_save_prompt("Clean", false);
+        assert_eq!(renderer.screen().get_line(3), "Save changes?");
+        assert_eq!(renderer.screen().get_line(5), "    Yes   > No");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("short", 21).as_slice(), ["short"]);
+        assert_eq!(
+            wrap_text("split at the last space that fits", 21).as_slice(),
+            ["split at the last", "space that fits"]
+        );
+        // Long words are broken, and overflow past two rows dropped
+        assert_eq!(
+            wrap_text("abcdefghij klm", 6).as_slice(),
+            ["abcdef", "ghij"]
+        );
+        assert!(wrap_text("   ", 21).is_empty());
     }
 
     #[test]
@@ -504,23 +1076,344 @@ mod tests {
             4,
             "clean",
             "Clean",
+            (1, 1),
             120,
             30,
             180,
+            30,
+            720,
             Some(42),
             Some(45),
         );
 
         assert!(renderer.screen().get_line(0).contains("Full Clean"));
+        assert_eq!(renderer.screen().get_line(2), "Profile: Clean");
         assert!(renderer.screen().get_line(3).contains("120 RPM"));
     }
 
+    #[test]
+    fn test_render_running_cycle() {
+        let mut renderer = Renderer::new();
+        renderer.render_running(
+            "Full Clean",
+            1,
+            4,
+            "clean",
+            "Clean",
+            (2, 3),
+            120,
+            90,
+            180,
+            90,
+            720,
+            None,
+            None,
+        );
+
+        assert_eq!(renderer.screen().get_line(2), "Cycle 2/3: Clean");
+    }
+
+    #[test]
+    fn test_render_running_without_heater() {
+        let mut renderer = Renderer::new();
+        renderer.render_running(
+            "Full Clean",
+            1,
+            4,
+            "clean",
+            "Clean",
+            (1, 1),
+            120,
+            30,
+            180,
+            30,
+            720,
+            None,
+            None,
+        );
+
+        assert!(renderer.screen().get_line(3).contains("120 RPM"));
+        assert!(!renderer.screen().get_line(4).contains("Temp"));
+    }
+
+    #[test]
+    fn test_render_running_without_reading() {
+        let mut renderer = Renderer::new();
+        renderer.render_running(
+            "Full Clean",
+            1,
+            4,
+            "clean",
+            "Clean",
+            (1, 1),
+            120,
+            30,
+            180,
+            30,
+            720,
+            None,
+            Some(45),
+        );
+
+        assert_eq!(renderer.screen().get_line(4), "Temp: --C / 45C");
+    }
+
+    #[test]
+    fn test_overall_progress() {
+        let mut renderer = Renderer::new();
+        renderer.set_overall_progress(true);
+        renderer.render_running(
+            "Full Clean",
+            2,
+            4,
+            "rinse",
+            "Rinse",
+            (1, 1),
+            120,
+            90,
+            180,
+            750,
+            2700,
+            Some(42),
+            Some(45),
+        );
+
+        let screen = renderer.screen();
+        assert_eq!(screen.get_line(3), "Motor: 120 RPM 42/45C");
+        assert!(screen.get_line(4).starts_with("[##########-"));
+        assert_eq!(screen.get_line(5), "Remaining: 1:30");
+        assert_eq!(screen.get_line(6), "Overall: 12:30/45:00");
+        // 750 of 2700 s fills 5 of 20 segments
+        assert!(screen.get_line(7).starts_with("[#####-"));
+    }
+
+    #[test]
+    fn test_overall_progress_disabled() {
+        let mut renderer = Renderer::new();
+        renderer.render_running(
+            "Full Clean",
+            2,
+            4,
+            "rinse",
+            "Rinse",
+            (1, 1),
+            120,
+            90,
+            180,
+            750,
+            2700,
+            Some(42),
+            Some(45),
+        );
+
+        let screen = renderer.screen();
+        assert!(screen.get_line(4).starts_with("Temp:"));
+        assert!(screen.get_line(5).starts_with("[##########-"));
+        assert_eq!(screen.get_line(6), "Remaining: 1:30");
+        assert_eq!(screen.get_line(7), "CLICK=Pause");
+        for row in 0..DISPLAY_ROWS {
+            assert!(!screen.get_line(row).contains("Overall"));
+        }
+    }
+
+    #[test]
+    fn test_status_header_running() {
+        let mut renderer = Renderer::new();
+        renderer.set_status_header(true);
+        renderer.render_running(
+            "Full Clean",
+            1,
+            4,
+            "clean",
+            "Clean",
+            (1, 1),
+            120,
+            95,
+            180,
+            95,
+            720,
+            None,
+            None,
+        );
+
+        let header = renderer.screen().get_line(0);
+        assert!(header.starts_with("Full Clean"));
+        assert!(header.ends_with("1:35"));
+        assert_eq!(header.len(), DISPLAY_COLS as usize);
+        assert!(renderer.screen().get_line(1).starts_with("Step 1/4"));
+    }
+
+    #[test]
+    fn test_status_header_truncates_long_name() {
+        let mut renderer = Renderer::new();
+        renderer.set_status_header(true);
+        renderer.render_running(
+            "A Very Long Program Name",
+            1,
+            1,
+            "clean",
+            "Clean",
+            (1, 1),
+            120,
+            600,
+            900,
+            600,
+            900,
+            None,
+            None,
+        );
+
+        let header = renderer.screen().get_line(0);
+        assert!(header.ends_with(" 10:00"));
+        assert_eq!(header.len(), DISPLAY_COLS as usize);
+    }
+
+    #[test]
+    fn test_truncate_on_char_boundary() {
+        assert_eq!(truncate("Clean", 3), "Cle");
+        assert_eq!(truncate("Clean", 10), "Clean");
+        // "é" is two bytes; a cut through it drops the whole character
+        assert_eq!(truncate("Café", 4), "Caf");
+        assert_eq!(truncate("Café", 5), "Café");
+
+        let mut renderer = Renderer::new();
+        renderer.set_status_header(true);
+        renderer.render_running(
+            "Nettoyage rapiécé",
+            1,
+            1,
+            "clean",
+            "Clean",
+            (1, 1),
+            120,
+            600,
+            900,
+            600,
+            900,
+            None,
+            None,
+        );
+        let header = renderer.screen().get_line(0);
+        assert!(header.starts_with("Nettoyage rapi "));
+        assert!(header.ends_with(" 10:00"));
+    }
+
+    #[test]
+    fn test_status_header_menu() {
+        let mut renderer = Renderer::new();
+        renderer.set_status_header(true);
+        let programs = ["Full Clean", "Quick Clean", "Dry Only"];
+        renderer.render_menu(&programs, 1);
+
+        assert!(renderer.screen().get_line(0).starts_with("Ready"));
+        assert!(renderer.screen().get_line(1).contains("SELECT PROGRAM"));
+        assert!(renderer.screen().get_line(3).starts_with(">"));
+        assert_eq!(renderer.screen().selected_row(), Some(3));
+
+        // Disabling restores the full-height layout
+        renderer.set_status_header(false);
+        renderer.render_menu(&programs, 1);
+        assert!(renderer.screen().get_line(0).contains("SELECT PROGRAM"));
+        assert!(renderer.screen().get_line(2).starts_with(">"));
+        assert_eq!(renderer.screen().selected_row(), Some(2));
+    }
+
+    #[test]
+    fn test_render_auto_advance_countdown() {
+        let mut renderer = Renderer::new();
+        renderer.render_step_complete("rinse", Some(12));
+        assert_eq!(renderer.screen().get_line(7), "CLICK or auto in 12s");
+
+        renderer.render_awaiting_jar("rinse", "Move basket to:", None);
+        assert_eq!(renderer.screen().get_line(7), "CLICK when ready");
+    }
+
     #[test]
     fn test_render_error() {
         let mut renderer = Renderer::new();
-        renderer.render_error("OVER TEMP", "Temperature exceeded 55C");
+        renderer.render_error("OVER TEMP", "Temperature exceeded 55C", false);
 
         assert!(renderer.screen().get_line(0).contains("ERROR"));
         assert!(renderer.screen().get_line(2).contains("OVER TEMP"));
+        assert_eq!(renderer.screen().get_line(7), "Power cycle required");
+
+        renderer.render_error("LINK LOST", "Waiting for display", true);
+        assert_eq!(renderer.screen().get_line(7), "Clears on recovery");
+    }
+
+    #[test]
+    fn test_render_recovered() {
+        let mut renderer = Renderer::new();
+        renderer.render_recovered(Some(State::SpinOff), 3);
+
+        assert_eq!(renderer.screen().get_line(0), "Recovered from fault");
+        assert_eq!(renderer.screen().get_line(4), "Was: Spin-off");
+        assert_eq!(renderer.screen().get_line(5), "Step 3");
+
+        // No breadcrumb survived the reset
+        renderer.render_recovered(None, 0);
+        assert_eq!(renderer.screen().get_line(4), "");
+        assert_eq!(renderer.screen().get_line(5), "");
+    }
+
+    #[test]
+    fn test_render_fallback_names_state() {
+        use isochron_core::state::ErrorKind;
+
+        let states = [
+            State::Boot,
+            State::Idle,
+            State::ProgramSelected,
+            State::EditProgram,
+            State::AwaitingJar,
+            State::Running,
+            State::AwaitingSpinOff,
+            State::SpinOff,
+            State::Paused,
+            State::StepComplete,
+            State::ProgramComplete,
+            State::Autotuning,
+            State::Error(ErrorKind::Unknown),
+        ];
+        for state in states {
+            let mut renderer = Renderer::new();
+            renderer.render_fallback(state);
+            let line = renderer.screen().get_line(3);
+            assert!(line.starts_with(state.name()));
+            assert!(line.ends_with("..."));
+        }
+    }
+
+    #[test]
+    fn test_render_autotune_confirm_uncalibrated() {
+        let mut renderer = Renderer::new();
+        renderer.render_autotune_confirm(45, None);
+
+        assert!(renderer.screen().get_line(2).contains("calibrate"));
+        assert!(renderer.screen().get_line(5).contains("45C"));
+    }
+
+    #[test]
+    fn test_render_autotune_confirm_shows_active_pid() {
+        let mut renderer = Renderer::new();
+        renderer.render_autotune_confirm(45, Some((150, 10, 50)));
+
+        assert!(renderer.screen().get_line(2).contains("Active PID"));
+        assert_eq!(renderer.screen().get_line(3), "Kp:1.50 Ki:0.10");
+        assert_eq!(renderer.screen().get_line(4), "Kd:0.50");
+    }
+
+    #[test]
+    fn test_render_autotune_complete_save_status() {
+        let mut renderer = Renderer::new();
+
+        renderer.render_autotune_complete(150, 10, 50, None);
+        assert!(renderer.screen().get_line(6).contains("Saving"));
+
+        renderer.render_autotune_complete(150, 10, 50, Some(true));
+        assert!(renderer.screen().get_line(6).contains("saved"));
+
+        renderer.render_autotune_complete(150, 10, 50, Some(false));
+        assert!(renderer.screen().get_line(6).contains("failed"));
     }
 }
@@ -0,0 +1,438 @@
+//! Screen selection
+//!
+//! Picks the screen for the controller's current state and draws it with
+//! the [`Renderer`]. The controller task copies the result to the display.
+
+use isochron_core::motion::Axis;
+use isochron_core::state::State;
+
+use super::Renderer;
+use crate::controller::Controller;
+
+/// Render the current state
+///
+/// Each arm reports whether it drew a screen; states without one (or
+/// missing the data for it) get a placeholder so the display never shows
+/// a stale screen.
+pub fn render_state(controller: &Controller, renderer: &mut Renderer) {
+    let drawn = match controller.state() {
+        State::Boot => {
+            match controller.manual_homing_axis() {
+                Some(Axis::Z) => renderer.render_manual_homing("Z"),
+                Some(Axis::X) => renderer.render_manual_homing("X"),
+                None => renderer.render_boot(),
+            }
+            true
+        }
+        State::Idle if controller.recovery_notice().is_some() => {
+            let last = controller.recovery_notice().and_then(|n| n.last);
+            renderer.render_recovered(last.map(|c| c.state), last.map(|c| c.step).unwrap_or(0));
+            true
+        }
+        State::Idle if controller.diagnostics_shown() => {
+            renderer.render_diagnostics(controller.sensor_raw());
+            true
+        }
+        State::Idle => {
+            // Collect program labels plus autotune and diagnostics options
+            let extra = if controller.diagnostics_menu() { 2 } else { 1 };
+            let mut labels: heapless::Vec<&str, 8> =
+                controller.program_labels().take(8 - extra).collect();
+            let _ = labels.push("Autotune Heater");
+            let autotune_index = labels.len() - 1;
+            if controller.diagnostics_menu() {
+                let _ = labels.push("Diagnostics");
+            }
+
+            // Determine selected index for display
+            let selected = if controller.is_autotune_selected() {
+                autotune_index
+            } else if controller.is_diagnostics_selected() {
+                labels.len() - 1 // Last item (diagnostics)
+            } else {
+                controller.selected_program() as usize
+            };
+            renderer.set_quiet(controller.quiet_mode());
+            renderer.render_menu(&labels, selected);
+            true
+        }
+        State::ProgramSelected => {
+            if let Some(program) = controller.get_program(controller.selected_program()) {
+                // Build step descriptions
+                let mut steps: heapless::Vec<&str, 8> = heapless::Vec::new();
+                for step in program.steps.iter().take(5) {
+                    let _ = steps.push(step.jar.as_str());
+                }
+
+                // Calculate total time (simplified)
+                let total_time = controller.step_total_s();
+
+                renderer.render_program_detail(
+                    program.label.as_str(),
+                    program.notes.as_deref(),
+                    &steps,
+                    total_time,
+                );
+                true
+            } else {
+                false
+            }
+        }
+        State::Running => {
+            if let (Some(profile), Some(jar)) =
+                (controller.current_profile(), controller.current_jar())
+            {
+                let (temp, target) = if controller.has_heater() {
+                    (controller.current_temp_c(), profile.temperature_c)
+                } else {
+                    (None, None)
+                };
+
+                renderer.render_running(
+                    controller
+                        .get_program(controller.selected_program())
+                        .map(|p| p.label.as_str())
+                        .unwrap_or(""),
+                    controller.current_step_num(),
+                    controller.total_steps(),
+                    jar.name.as_str(),
+                    profile.label.as_str(),
+                    controller.current_iteration(),
+                    controller.motor_command().rpm,
+                    controller.step_elapsed_s(),
+                    controller.step_total_s(),
+                    controller.program_elapsed_s(),
+                    controller.program_total_s(),
+                    temp,
+                    target,
+                );
+                true
+            } else {
+                false
+            }
+        }
+        State::Paused => {
+            let program_name = controller
+                .get_program(controller.selected_program())
+                .map(|p| p.label.as_str())
+                .unwrap_or("");
+            renderer.render_paused(
+                program_name,
+                controller.current_step_num(),
+                controller.total_steps(),
+            );
+            true
+        }
+        State::SpinOff => {
+            // Show spin-off in progress (similar to running but different message)
+            if let Some(jar) = controller.current_jar() {
+                renderer.render_awaiting_jar(jar.name.as_str(), "Spin-off in progress", None);
+                true
+            } else {
+                false
+            }
+        }
+        State::AwaitingJar => {
+            if let Some(jar) = controller.current_jar() {
+                renderer.render_awaiting_jar(
+                    jar.name.as_str(),
+                    "Move basket to:",
+                    controller.auto_advance_remaining_s(),
+                );
+                true
+            } else {
+                false
+            }
+        }
+        State::AwaitingSpinOff => {
+            renderer.render_awaiting_jar("", "Lift basket for spin-off", None);
+            true
+        }
+        State::StepComplete => {
+            if let Some(jar) = controller.current_jar() {
+                renderer
+                    .render_step_complete(jar.name.as_str(), controller.auto_advance_remaining_s());
+                true
+            } else {
+                false
+            }
+        }
+        State::ProgramComplete => {
+            let program_name = controller
+                .get_program(controller.selected_program())
+                .map(|p| p.label.as_str())
+                .unwrap_or("");
+            renderer.render_complete(program_name, controller.step_elapsed_s());
+            true
+        }
+        State::Error(kind) => {
+            let error_type = match kind {
+                isochron_core::state::ErrorKind::ThermistorFault => "SENSOR FAULT",
+                isochron_core::state::ErrorKind::OverTemperature => "OVER TEMP",
+                isochron_core::state::ErrorKind::ThermalRunaway => "THERMAL RUNAWAY",
+                isochron_core::state::ErrorKind::MotorStall => "MOTOR STALL",
+                isochron_core::state::ErrorKind::DriverFault(
+                    isochron_core::state::DriverFaultKind::OverTemperature,
+                ) => "DRIVER OVERTEMP",
+                isochron_core::state::ErrorKind::DriverFault(
+                    isochron_core::state::DriverFaultKind::ShortCircuit,
+                ) => "DRIVER SHORT",
+                isochron_core::state::ErrorKind::LinkLost => "LINK LOST",
+                isochron_core::state::ErrorKind::PositionOutOfBounds => "POSITION FAULT",
+                isochron_core::state::ErrorKind::HomingFailed => "HOMING FAILED",
+                isochron_core::state::ErrorKind::LidOpen => "LID OPEN",
+                isochron_core::state::ErrorKind::Imbalance => "IMBALANCE",
+                isochron_core::state::ErrorKind::ConfigError => "CONFIG ERROR",
+                isochron_core::state::ErrorKind::Unknown => "UNKNOWN ERROR",
+            };
+            let auto_clears = controller.fault_auto_clears();
+            let details = if auto_clears {
+                "Waiting for display"
+            } else if kind == isochron_core::state::ErrorKind::ConfigError {
+                "No valid config"
+            } else {
+                "Power cycle to restart"
+            };
+            renderer.render_error(error_type, details, auto_clears);
+            true
+        }
+        State::EditProgram => {
+            use crate::controller::EditField;
+            match (
+                controller.profile_edit(),
+                controller.get_program(controller.selected_program()),
+            ) {
+                (Some(edit), Some(program)) => {
+                    if let Some(yes) = edit.save_prompt {
+                        renderer.render_save_prompt(edit.profile.label.as_str(), yes);
+                    } else {
+                        renderer.render_profile_edit(
+                            edit.profile.label.as_str(),
+                            edit.step + 1,
+                            program.steps.len() as u8,
+                            edit.profile.rpm,
+                            edit.profile.time_s,
+                            edit.field == EditField::Time,
+                        );
+                    }
+                    true
+                }
+                _ => false,
+            }
+        }
+        State::Autotuning => {
+            use crate::controller::AutotunePhase;
+            match controller.autotune_phase() {
+                AutotunePhase::Confirming => {
+                    // Show confirmation screen
+                    renderer.render_autotune_confirm(
+                        controller.autotune_target_c(),
+                        controller.active_pid(),
+                    );
+                    true
+                }
+                AutotunePhase::ConfirmOverwrite => {
+                    renderer.render_autotune_overwrite();
+                    true
+                }
+                AutotunePhase::Running => {
+                    // Show progress screen
+                    let (peaks, ticks) = controller.autotune_progress();
+                    // Convert ticks to seconds (500ms per tick)
+                    let elapsed_s = (ticks / 2) as u32;
+                    let temp_c = controller.current_temp_c().unwrap_or(0);
+                    let target_c = controller.autotune_target_c();
+                    renderer.render_autotune_progress(peaks, elapsed_s, temp_c, target_c);
+                    true
+                }
+                AutotunePhase::Complete => {
+                    // Show result screen
+                    if let Some((kp, ki, kd)) = controller.autotune_result() {
+                        renderer.render_autotune_complete(
+                            kp,
+                            ki,
+                            kd,
+                            controller.calibration_saved(),
+                        );
+                        true
+                    } else {
+                        false
+                    }
+                }
+                AutotunePhase::Failed => {
+                    // Show failure screen
+                    let reason = controller
+                        .autotune_failure()
+                        .map(|r| r.as_str())
+                        .unwrap_or("Unknown error");
+                    renderer.render_autotune_failed(reason);
+                    true
+                }
+            }
+        }
+    };
+
+    if !drawn {
+        renderer.render_fallback(controller.state());
+    }
+    if controller.ui_locked() {
+        renderer.render_lock_indicator();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heapless::String;
+    use isochron_core::config::{
+        JarConfig, MachineCapabilities, ProfileConfig, ProgramConfig, ProgramStep,
+    };
+    use isochron_core::scheduler::{DirectionMode, SpinOffConfig};
+    use isochron_core::util::TemperatureC10;
+    use isochron_protocol::InputEvent;
+
+    fn label(text: &str) -> String<16> {
+        String::try_from(text).unwrap()
+    }
+
+    /// Manual machine with a two-jar program, the first jar spun off
+    fn manual_controller() -> Controller {
+        let clean = ProfileConfig {
+            label: label("Clean"),
+            time_s: 1,
+            direction: DirectionMode::Clockwise,
+            spinoff: Some(SpinOffConfig {
+                lift_mm: 20,
+                rpm: 150,
+                time_s: 1,
+                pre_spinoff_delay_s: 0,
+            }),
+            ..Default::default()
+        };
+        let rinse = ProfileConfig {
+            label: label("Rinse"),
+            time_s: 1,
+            direction: DirectionMode::Clockwise,
+            ..Default::default()
+        };
+        let jar = |name: &str| JarConfig {
+            name: label(name),
+            ..Default::default()
+        };
+        let step = |jar: &str, profile: &str| ProgramStep {
+            jar: label(jar),
+            profile: label(profile),
+        };
+        let program = ProgramConfig {
+            label: label("Test"),
+            notes: None,
+            steps: heapless::Vec::from_slice(&[step("clean", "Clean"), step("rinse", "Rinse")])
+                .unwrap(),
+        };
+
+        let mut ctrl = Controller::new(MachineCapabilities::default());
+        ctrl.set_prompt_first_jar(true);
+        ctrl.load_config(&[program], &[clean, rinse], &[jar("clean"), jar("rinse")]);
+        ctrl
+    }
+
+    /// Render over a stale screen, returning what was drawn
+    fn rendered(ctrl: &Controller) -> Renderer {
+        let mut renderer = Renderer::new();
+        renderer.render_error("stale", "stale", false);
+        render_state(ctrl, &mut renderer);
+        renderer
+    }
+
+    #[test]
+    fn test_every_state_renders_a_screen() {
+        let mut seen: heapless::Vec<State, 16> = heapless::Vec::new();
+        let mut check = |ctrl: &Controller| {
+            let renderer = rendered(ctrl);
+            let lines = (0..8).map(|row| renderer.screen().get_line(row));
+            assert!(
+                lines.clone().all(|line| !line.contains("stale")),
+                "{:?} left the old screen",
+                ctrl.state()
+            );
+            assert!(
+                lines.clone().any(|line| !line.is_empty()),
+                "{:?} drew nothing",
+                ctrl.state()
+            );
+            if !seen.contains(&ctrl.state()) {
+                seen.push(ctrl.state()).unwrap();
+            }
+        };
+        let mut now_ms = 0;
+        let mut tick = |ctrl: &mut Controller| {
+            now_ms += 1000;
+            ctrl.heartbeat_received();
+            ctrl.tick(now_ms);
+        };
+
+        // Through a program on a manual machine
+        let mut ctrl = manual_controller();
+        check(&ctrl);
+        ctrl.boot_complete();
+        check(&ctrl);
+        for input in [
+            InputEvent::EncoderClick,       // Select
+            InputEvent::EncoderCw,          // Edit
+            InputEvent::EncoderDoubleClick, // Back
+            InputEvent::EncoderClick,       // Start, waits for the first jar
+            InputEvent::EncoderClick,       // Basket in
+            InputEvent::EncoderClick,       // Pause
+            InputEvent::EncoderClick,       // Resume
+        ] {
+            ctrl.process_input(input);
+            check(&ctrl);
+        }
+        tick(&mut ctrl); // Waits for the basket lift
+        check(&ctrl);
+        ctrl.process_input(InputEvent::EncoderClick); // Lifted, spins off
+        check(&ctrl);
+        tick(&mut ctrl); // Step done
+        check(&ctrl);
+        ctrl.process_input(InputEvent::EncoderClick); // On to the next jar
+        check(&ctrl);
+        ctrl.process_input(InputEvent::EncoderClick); // Basket in
+        tick(&mut ctrl);
+        check(&ctrl);
+
+        // Autotune from the idle menu
+        let mut ctrl = manual_controller();
+        ctrl.boot_complete();
+        ctrl.process_input(InputEvent::EncoderCcw); // Wrap to autotune
+        ctrl.process_input(InputEvent::EncoderClick);
+        check(&ctrl);
+
+        // A fault
+        let mut ctrl = manual_controller();
+        ctrl.boot_complete();
+        ctrl.update_temperature_for(0, Some(TemperatureC10::from_whole(150)));
+        ctrl.tick(100);
+        check(&ctrl);
+
+        // Fails to compile when a state is added, as a reminder to reach it
+        // above
+        for state in seen.iter() {
+            match state {
+                State::Boot
+                | State::Idle
+                | State::ProgramSelected
+                | State::EditProgram
+                | State::AwaitingJar
+                | State::Running
+                | State::AwaitingSpinOff
+                | State::SpinOff
+                | State::Paused
+                | State::StepComplete
+                | State::ProgramComplete
+                | State::Autotuning
+                | State::Error(_) => {}
+            }
+        }
+        assert_eq!(seen.len(), 13, "{:?}", seen);
+    }
+}
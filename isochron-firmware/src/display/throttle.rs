@@ -0,0 +1,245 @@
+//! Display render rate limiting
+//!
+//! The controller requests renders on input, on state changes and
+//! periodically while running. Progress-only refreshes are coalesced so the
+//! display is redrawn at most once per `min_interval_ms`, which avoids
+//! flicker and needless UART traffic. State changes always render at once.
+//!
+//! Requests are gathered in a [`RenderPass`] while the controller task
+//! handles a batch of inputs, ticks and status updates. The batch ends in
+//! at most one render, made after everything was applied, so the screen
+//! never shows an intermediate state.
+
+/// Why a render was requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RenderRequest {
+    /// State, menu or screen content changed - render immediately
+    StateChange,
+    /// Only progress values (time, RPM, temperature) changed
+    Progress,
+}
+
+/// Coalesces render requests with a timestamp guard
+#[derive(Debug, Clone)]
+pub struct RenderThrottle {
+    /// Minimum interval between progress renders (ms)
+    min_interval_ms: u32,
+    /// Timestamp of the last render (ms)
+    last_render_ms: Option<u32>,
+    /// A progress render was suppressed and is still owed
+    pending: bool,
+}
+
+impl RenderThrottle {
+    /// Create a throttle with the given minimum interval
+    pub const fn new(min_interval_ms: u16) -> Self {
+        Self {
+            min_interval_ms: min_interval_ms as u32,
+            last_render_ms: None,
+            pending: false,
+        }
+    }
+
+    /// Request a render
+    ///
+    /// Returns true if the caller should render now. Suppressed progress
+    /// requests are remembered and released by [`RenderThrottle::poll`].
+    pub fn request(&mut self, kind: RenderRequest, now_ms: u32) -> bool {
+        if kind == RenderRequest::StateChange || self.interval_elapsed(now_ms) {
+            self.mark_rendered(now_ms);
+            true
+        } else {
+            self.pending = true;
+            false
+        }
+    }
+
+    /// Check for a coalesced render that is now due
+    pub fn poll(&mut self, now_ms: u32) -> bool {
+        if self.pending && self.interval_elapsed(now_ms) {
+            self.mark_rendered(now_ms);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn interval_elapsed(&self, now_ms: u32) -> bool {
+        match self.last_render_ms {
+            Some(last) => now_ms.wrapping_sub(last) >= self.min_interval_ms,
+            None => true,
+        }
+    }
+
+    fn mark_rendered(&mut self, now_ms: u32) {
+        self.last_render_ms = Some(now_ms);
+        self.pending = false;
+    }
+}
+
+/// Render requests gathered while handling one batch of events
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderPass {
+    request: Option<RenderRequest>,
+}
+
+impl RenderPass {
+    /// Start a pass with nothing to render
+    pub const fn new() -> Self {
+        Self { request: None }
+    }
+
+    /// Note that the screen needs redrawing
+    ///
+    /// A state change outranks progress-only requests in the same pass.
+    pub fn request(&mut self, kind: RenderRequest) {
+        if self.request != Some(RenderRequest::StateChange) {
+            self.request = Some(kind);
+        }
+    }
+
+    /// End the pass, returning true if the final state should be rendered
+    ///
+    /// A pass without requests can still release a coalesced progress
+    /// render from an earlier pass.
+    pub fn finish(self, throttle: &mut RenderThrottle, now_ms: u32) -> bool {
+        match self.request {
+            Some(kind) => throttle.request(kind, now_ms),
+            None => throttle.poll(now_ms),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_render_always_allowed() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, 0));
+    }
+
+    #[test]
+    fn test_progress_rate_limited() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, 1000));
+
+        // Ticks every 100ms: only every third one renders
+        assert!(!throttle.request(RenderRequest::Progress, 1100));
+        assert!(!throttle.request(RenderRequest::Progress, 1200));
+        assert!(throttle.request(RenderRequest::Progress, 1300));
+        assert!(!throttle.request(RenderRequest::Progress, 1400));
+    }
+
+    #[test]
+    fn test_state_change_renders_immediately() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, 1000));
+        assert!(throttle.request(RenderRequest::StateChange, 1010));
+        assert!(throttle.request(RenderRequest::StateChange, 1020));
+
+        // State change resets the guard for progress updates
+        assert!(!throttle.request(RenderRequest::Progress, 1100));
+    }
+
+    #[test]
+    fn test_suppressed_progress_is_coalesced() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, 0));
+        assert!(!throttle.request(RenderRequest::Progress, 50));
+        assert!(!throttle.request(RenderRequest::Progress, 100));
+
+        // Not yet due
+        assert!(!throttle.poll(200));
+        // One render covers both suppressed requests
+        assert!(throttle.poll(250));
+        assert!(!throttle.poll(600));
+    }
+
+    #[test]
+    fn test_state_change_clears_pending() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, 0));
+        assert!(!throttle.request(RenderRequest::Progress, 100));
+        assert!(throttle.request(RenderRequest::StateChange, 150));
+        // Nothing owed: the state change render covered it
+        assert!(!throttle.poll(500));
+    }
+
+    #[test]
+    fn test_timestamp_wraparound() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, u32::MAX - 100));
+        assert!(!throttle.request(RenderRequest::Progress, 50));
+        assert!(throttle.request(RenderRequest::Progress, 200));
+    }
+
+    /// Minimal stand-in for the controller task loop
+    ///
+    /// Each batch is a list of (state after the event, render request)
+    /// pairs; returns the states that were actually rendered.
+    fn run_batches(
+        batches: &[&[(u8, RenderRequest)]],
+        throttle: &mut RenderThrottle,
+    ) -> heapless::Vec<u8, 8> {
+        let mut rendered = heapless::Vec::new();
+        let mut state = 0;
+        for (i, batch) in batches.iter().enumerate() {
+            let mut pass = RenderPass::new();
+            for &(next, kind) in batch.iter() {
+                state = next;
+                pass.request(kind);
+            }
+            if pass.finish(throttle, i as u32 * 100) {
+                let _ = rendered.push(state);
+            }
+        }
+        rendered
+    }
+
+    #[test]
+    fn test_pass_renders_final_state_once() {
+        use RenderRequest::*;
+        let mut throttle = RenderThrottle::new(250);
+
+        // Tick, then two inputs queued behind it, then a status update
+        let batch: &[(u8, RenderRequest)] = &[(1, Progress), (2, StateChange), (3, StateChange)];
+        assert_eq!(run_batches(&[batch], &mut throttle), [3]);
+
+        // Input followed by a tick that changes state again
+        let batch: &[(u8, RenderRequest)] = &[(4, StateChange), (5, Progress)];
+        assert_eq!(run_batches(&[batch], &mut throttle), [5]);
+    }
+
+    #[test]
+    fn test_state_change_outranks_progress() {
+        let mut throttle = RenderThrottle::new(250);
+        assert!(throttle.request(RenderRequest::Progress, 0));
+
+        // Progress alone would be throttled this soon...
+        let mut pass = RenderPass::new();
+        pass.request(RenderRequest::Progress);
+        assert!(!pass.finish(&mut throttle, 10));
+
+        // ...but not when an input in the same pass changed the state
+        let mut pass = RenderPass::new();
+        pass.request(RenderRequest::StateChange);
+        pass.request(RenderRequest::Progress);
+        assert!(pass.finish(&mut throttle, 20));
+    }
+
+    #[test]
+    fn test_empty_pass_flushes_coalesced_progress() {
+        use RenderRequest::*;
+        let mut throttle = RenderThrottle::new(250);
+
+        // Progress at 0 renders, at 100 is held back and released by the
+        // empty pass at 300 with the state current at that point
+        let progress: &[(u8, RenderRequest)] = &[(1, Progress)];
+        let later: &[(u8, RenderRequest)] = &[(2, Progress)];
+        let rendered = run_batches(&[progress, later, &[], &[]], &mut throttle);
+        assert_eq!(rendered, [1, 2]);
+    }
+}
@@ -11,33 +11,59 @@
 
 extern crate alloc;
 
+use core::cell::RefCell;
+
 use defmt::*;
 use embassy_executor::Spawner;
-use embassy_rp::adc::{Adc, Channel, InterruptHandler as AdcInterruptHandler};
+use embassy_rp::adc::{Adc, InterruptHandler as AdcInterruptHandler};
 use embassy_rp::bind_interrupts;
-use embassy_rp::gpio::{Input, Level, Output, Pull};
-use embassy_rp::peripherals::{DMA_CH2, FLASH, PIO0, UART0, UART1};
+use embassy_rp::gpio::{AnyPin, Input, Level, Output, OutputOpenDrain, Pull};
+use embassy_rp::i2c::{Config as I2cConfig, I2c};
+use embassy_rp::peripherals::{DMA_CH2, FLASH, I2C1, PIO0, UART0, UART1};
 use embassy_rp::pio::Pio;
 use embassy_rp::pwm::{Config as PwmConfig, Pwm};
 use embassy_rp::uart::{
     BufferedInterruptHandler, Config as UartConfig, InterruptHandler as UartInterruptHandler, Uart,
 };
+use embassy_rp::watchdog::Watchdog;
 use embassy_rp::Peri;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_time::Delay;
 use embedded_alloc::LlffHeap as Heap;
 use static_cell::StaticCell;
 use {defmt_rtt as _, panic_probe as _};
 
+use isochron_hal_rp2040::adc::{AdcPins, SharedAdc};
 use isochron_hal_rp2040::flash::FlashStorage;
-use isochron_hal_rp2040::pio::StepGeneratorConfig;
+use isochron_hal_rp2040::gpio::{RpInput, RpOpenDrain, RpOutput};
+use isochron_hal_rp2040::i2c::RpI2c;
+use isochron_hal_rp2040::pio::{StepGeneratorConfig, DEFAULT_STEP_PULSE_NS};
 use isochron_hal_rp2040::stepper::PioStepper;
+use isochron_hal_rp2040::watchdog::RpWatchdog;
+use isochron_hal_rp2040::WatchdogTrait;
 
+use crate::channels::RECOVERY_NOTICE;
 use crate::config::{parse_config, ConfigPersistence};
+use crate::tasks::watchdog::BREADCRUMB_SCRATCH;
 
 use isochron_core::config::{
+    AdcFilterConfig, ConfigSource, HeaterCalibration, HeaterConfig, HeaterHwConfig, HomingType,
     JarConfig, MachineCapabilities, MachineConfig, MotorType, ProfileConfig, ProgramConfig,
-    ProgramStep,
+    ProgramStep, SensorType, StepperHwConfig, StopBehavior, ThermistorModel, MAX_HEATERS,
 };
+use isochron_core::motion::homing::DEFAULT_HOMING_SPEED;
+use isochron_core::motion::{Axis, Backlash, HomingConfig, RampProfile};
+use isochron_core::safety::{Breadcrumb, RecoveryNotice};
 use isochron_core::scheduler::DirectionMode;
+use isochron_core::traits::TemperatureSensor;
+use isochron_drivers::heater::GpioHeater;
+use isochron_drivers::sensor::{
+    AdcFilter, Ds18b20Sensor, OneWire, Tmp117Sensor, TMP117_DEFAULT_ADDRESS,
+};
+use isochron_drivers::stepper::a4988::MsPin;
+use isochron_drivers::stepper::{A4988Config, A4988Driver};
+
+use crate::tasks::HeaterPin;
 
 // Heap allocator for TOML parsing
 #[global_allocator]
@@ -65,6 +91,14 @@ bind_interrupts!(struct Irqs {
     ADC_IRQ_FIFO => AdcInterruptHandler;
 });
 
+/// GPIOs claimed by the fixed board setup below
+///
+/// A configured heater enable pin is taken by number, so it must not be
+/// one of these. GPIO26-29 are handed to the ADC (or GPIO26/27 to an I2C
+/// temperature sensor), and are reserved either way so a config change
+/// can't make them clash.
+const CLAIMED_PINS: &[u8] = &[0, 1, 8, 9, 10, 11, 12, 17, 23, 26, 27, 28, 29];
+
 // Static cells for UART buffers (must live forever)
 static TX_BUF: StaticCell<[u8; 256]> = StaticCell::new();
 static RX_BUF: StaticCell<[u8; 256]> = StaticCell::new();
@@ -75,6 +109,18 @@ static MACHINE_CONFIG: StaticCell<MachineConfig> = StaticCell::new();
 static PROGRAMS: StaticCell<[ProgramConfig; 8]> = StaticCell::new();
 static PROFILES: StaticCell<[ProfileConfig; 8]> = StaticCell::new();
 static JARS: StaticCell<[JarConfig; 8]> = StaticCell::new();
+static HEATERS: StaticCell<[HeaterConfig; MAX_HEATERS]> = StaticCell::new();
+
+// Static cells for the first heater's temperature sensor (one is used)
+static SHARED_ADC: StaticCell<SharedAdc> = StaticCell::new();
+static THERMISTOR: StaticCell<tasks::ThermistorSensor> = StaticCell::new();
+static I2C_SENSOR: StaticCell<Tmp117Sensor<RpI2c<'static, I2C1>>> = StaticCell::new();
+static ONEWIRE_SENSOR: StaticCell<Ds18b20Sensor<RpOpenDrain<'static>, Delay>> = StaticCell::new();
+static MISSING_SENSOR: StaticCell<tasks::MissingSensor> = StaticCell::new();
+
+// Thermistors of the heaters after the first, in heater order
+static EXTRA_THERMISTORS: [StaticCell<tasks::ThermistorSensor>; MAX_HEATERS - 1] =
+    [const { StaticCell::new() }; MAX_HEATERS - 1];
 
 /// Main entry point
 #[embassy_executor::main]
@@ -90,7 +136,13 @@ async fn main(spawner: Spawner) {
 
     // Load configuration from flash (or use embedded defaults)
     // Also load calibration data and get flash storage back for persistence
-    let (config, calibration, flash_storage) = load_config_from_flash(p.FLASH, p.DMA_CH2).await;
+    let (config, config_source, calibration, flash_storage) =
+        load_config_from_flash(p.FLASH, p.DMA_CH2).await;
+
+    let (watchdog, watchdog_timeout_ms) = init_watchdog(
+        RpWatchdog::new(Watchdog::new(p.WATCHDOG)),
+        config.watchdog_timeout_ms,
+    );
 
     // Get motor type before extracting other config
     let motor_type = config.motor_type;
@@ -105,11 +157,29 @@ async fn main(spawner: Spawner) {
             let gear_num = stepper.gear_ratio_num as u32;
             let gear_den = stepper.gear_ratio_den.max(1) as u32;
             let steps = full_steps * microsteps * gear_num / gear_den;
+            let step_pulse_ns = stepper
+                .step_pulse_ns
+                .map(u32::from)
+                .unwrap_or(DEFAULT_STEP_PULSE_NS);
             info!(
-                "Stepper config: {} steps/rev ({}x{} * {}/{}), enable_inverted={}",
-                steps, full_steps, microsteps, gear_num, gear_den, stepper.enable_pin.inverted
+                "Stepper config: {} steps/rev ({}x{} * {}/{}), enable_inverted={}, pulse={}ns, stop={:?}",
+                steps,
+                full_steps,
+                microsteps,
+                gear_num,
+                gear_den,
+                stepper.enable_pin.inverted,
+                step_pulse_ns,
+                stepper.stop_behavior
             );
-            (steps, stepper.enable_pin.inverted, stepper.microsteps)
+            (
+                steps,
+                stepper.enable_pin.inverted,
+                stepper.microsteps,
+                step_pulse_ns,
+                stepper.stop_behavior,
+                stepper.ramp(),
+            )
         })
     } else {
         None
@@ -155,39 +225,158 @@ async fn main(spawner: Spawner) {
                     tmc.hold_current_ma,
                     tmc.stealthchop,
                     tmc.stall_threshold,
+                    tmc.rsense_mohm,
+                    tmc.spin_hold,
                 )
             })
     } else {
         None
     };
 
-    // Extract heater config values including PID coefficients
-    let heater_config_values = config.find_heater("dryer").map(|heater| {
-        info!(
-            "Heater config: max_temp={}°C, hysteresis={}°C, control={:?}",
-            heater.max_temp, heater.hysteresis, heater.control
-        );
-        if heater.pid_kp_x100.is_some()
-            || heater.pid_ki_x100.is_some()
-            || heater.pid_kd_x100.is_some()
-        {
-            info!(
-                "  PID from TOML: Kp={:?}, Ki={:?}, Kd={:?}",
-                heater.pid_kp_x100, heater.pid_ki_x100, heater.pid_kd_x100
-            );
-        }
-        (
-            heater.max_temp,
-            heater.hysteresis,
-            heater.control,
-            heater.pid_kp_x100,
-            heater.pid_ki_x100,
-            heater.pid_kd_x100,
-        )
-    });
+    // A step/dir basket driver replaces the TMC2209 (stepper only)
+    let a4988_config_values = if motor_type == MotorType::Stepper {
+        config.find_a4988("basket").map(|a4988| {
+            info!("A4988 config: chip={:?}", a4988.chip);
+            (a4988.chip, [a4988.ms1_pin, a4988.ms2_pin, a4988.ms3_pin])
+        })
+    } else {
+        None
+    };
+
+    // Spin-off imbalance detection reads the basket driver's StallGuard
+    let imbalance_threshold = if motor_type == MotorType::Stepper {
+        config
+            .tmc2209s
+            .iter()
+            .find(|t| t.stepper_name.as_str() == "basket")
+            .and_then(|tmc| tmc.imbalance_threshold)
+    } else {
+        None
+    };
+    let stall_settle_ms = config
+        .tmc2209s
+        .iter()
+        .find(|t| t.stepper_name.as_str() == "basket")
+        .and_then(|tmc| tmc.stall_settle_ms);
+
+    // Machines without heater hardware run agitation-only
+    let heater_count = config.heater_hw.len() as u8;
+
+    // Heater output polarity, optional enable relay and temperature sensor
+    // of heater 0, the first [heater] section, on the board's HE0 output.
+    // Without heater hardware the thermistor defaults to the SKR Pico TH0 pin
+    let (
+        heater_inverted,
+        heater_enable,
+        sensor_pin,
+        sensor_type,
+        sensor_address,
+        thermistor_model,
+        adc_filter,
+        onewire_pin,
+    ) = config
+        .heater_hw
+        .first()
+        .map(|hw| {
+            (
+                hw.heater_pin.inverted,
+                hw.enable_pin,
+                hw.sensor_pin,
+                hw.sensor_type,
+                hw.sensor_address,
+                hw.thermistor_model(),
+                hw.adc_filter,
+                hw.onewire_pin,
+            )
+        })
+        .unwrap_or((
+            false,
+            None,
+            27,
+            SensorType::default(),
+            None,
+            ThermistorModel::default(),
+            AdcFilterConfig::default(),
+            None,
+        ));
+    // A 1-Wire sensor's data pin is taken by number like the enable pin
+    let onewire_gpio = onewire_pin
+        .filter(|_| sensor_type.is_onewire())
+        .map(|pin| pin.pin);
+    if sensor_type == SensorType::Ntc10k && thermistor_model == ThermistorModel::Table {
+        warn!("ntc10k sensor without temp_table or thermistor_beta: using the 100K table");
+    }
+
+    // Heater control settings, including PID coefficients
+    let primary_heater = config
+        .heater_hw
+        .first()
+        .and_then(|hw| config.find_heater(&hw.name))
+        .cloned();
+
+    // Heaters after the first, set up once the fixed pins are claimed
+    let extra_heaters: heapless::Vec<(HeaterHwConfig, HeaterConfig), { MAX_HEATERS - 1 }> = config
+        .heater_hw
+        .iter()
+        .skip(1)
+        .map(|hw| {
+            let heater = config
+                .find_heater(&hw.name)
+                .cloned()
+                .unwrap_or_else(|| HeaterConfig {
+                    name: hw.name.clone(),
+                    ..Default::default()
+                });
+            (hw.clone(), heater)
+        })
+        .collect();
 
     // Now we can move config
-    let (programs, profiles, jars) = init_config_from_machine(config);
+    let ui = config.ui.clone();
+    let autostart_program = config.autostart_program.clone();
+    let max_pause_s = config.max_pause_s;
+    let prewarm_next_jar = config.prewarm_next_jar;
+    let spinoff_limits = tasks::SpinOffLimits {
+        max_rpm: config.max_spinoff_rpm,
+        quiet_rpm: config.quiet_spinoff_rpm,
+        imbalance_threshold,
+    };
+    let link = config.link;
+    let lid_config = config.lid;
+    let ultrasonic_config = config.ultrasonic.clone();
+    if config.park_after_program {
+        warn!("park_after_program is set but not supported yet: the basket stays at the last jar");
+    }
+    let park = tasks::ParkSettings {
+        position: config.park_after_program.then_some(config.park_position),
+        // Only a stepper knows the basket's angle
+        angle_deg: config
+            .park_angle_deg
+            .filter(|_| motor_type == MotorType::Stepper),
+        prompt_first_jar: config.prompt_first_jar,
+    };
+    let homing_order = config.homing_order;
+    // Z and X steppers, set up once the fixed pins are claimed
+    let axis_steppers = [
+        config.find_stepper("z").cloned(),
+        config.find_stepper("x").cloned(),
+    ]
+    .map(|stepper| stepper.filter(|_| motor_type == MotorType::Stepper));
+    let protection = tasks::ProtectionSettings {
+        stall_reverse_recovery: config.stall_reverse_recovery,
+        startup_stagger_ms: config.startup_stagger_ms,
+        config_missing: config_source == ConfigSource::Missing,
+        // Set once the motor driver is set up below
+        motor_setup_failed: false,
+        commands_on_change: config.commands_on_change,
+        sensor_fault_policy: primary_heater
+            .as_ref()
+            .map(|heater| heater.fault_policy)
+            .unwrap_or_default(),
+        thermal_runaway: primary_heater.as_ref().and_then(|heater| heater.runaway),
+        balance_rotation: config.balance_rotation,
+    };
+    let (programs, profiles, jars, heaters) = init_config_from_machine(config);
     info!("Configuration loaded");
 
     // Setup UART for display communication
@@ -205,7 +394,7 @@ async fn main(spawner: Spawner) {
     // Motor hardware initialization (conditional based on motor_type)
     // Only one motor type is active at a time - use enum to hold resources
     enum MotorResources {
-        Stepper(PioStepper<'static, PIO0, 0>),
+        Stepper(PioStepper<'static, PIO0, 0>, RampProfile),
         Dc(
             Pwm<'static>,
             Option<Output<'static>>,
@@ -227,10 +416,18 @@ async fn main(spawner: Spawner) {
                 mut common, sm0, ..
             } = Pio::new(p.PIO0, Irqs);
 
-            let (steps_per_rev, enable_inverted, _microsteps) = stepper_config_values
-                .unwrap_or_else(|| {
+            let (steps_per_rev, enable_inverted, _microsteps, step_pulse_ns, _stop_behavior, ramp) =
+                stepper_config_values.unwrap_or_else(|| {
                     warn!("No stepper config found, using defaults");
-                    (3200, false, 16) // 200 steps * 16 microsteps
+                    // 200 steps * 16 microsteps
+                    (
+                        3200,
+                        false,
+                        16,
+                        DEFAULT_STEP_PULSE_NS,
+                        StopBehavior::default(),
+                        StepperHwConfig::default().ramp(),
+                    )
                 });
 
             let stepper_config = StepGeneratorConfig {
@@ -239,6 +436,7 @@ async fn main(spawner: Spawner) {
                 enable_pin: 12,
                 enable_inverted,
                 steps_per_rev,
+                step_pulse_ns,
             };
 
             let stepper = PioStepper::new(
@@ -251,7 +449,7 @@ async fn main(spawner: Spawner) {
             );
 
             info!("PIO stepper initialized");
-            MotorResources::Stepper(stepper)
+            MotorResources::Stepper(stepper, ramp)
         }
         MotorType::Dc => {
             // PWM on GPIO11 (slice 5, channel B)
@@ -310,74 +508,117 @@ async fn main(spawner: Spawner) {
         }
     };
 
-    // Setup ADC for temperature sensing
-    // Pin assignment is board-specific (SKR Pico TH0: GPIO27)
-    let adc = Adc::new(p.ADC, Irqs, embassy_rp::adc::Config::default());
-    let therm_channel = Channel::new_pin(p.PIN_27, embassy_rp::gpio::Pull::None);
-
     // Setup heater output
     // Pin assignment is board-specific (SKR Pico HE0: GPIO23)
-    let heater_pin = Output::new(p.PIN_23, Level::Low);
-
-    // Heater settings from config with calibration fallback
-    // Priority: TOML config > Calibration from flash > Defaults
-    let heater_config = if let Some((max_temp, hysteresis, control, toml_kp, toml_ki, toml_kd)) =
-        heater_config_values
-    {
-        // Get calibration values for heater 0 (dryer) if available
-        let cal = calibration.get(0);
-        let (cal_kp, cal_ki, cal_kd) = if let Some(c) = cal {
-            info!(
-                "Loaded PID calibration from flash: Kp={}.{:02}, Ki={}.{:02}, Kd={}.{:02}",
-                c.kp_x100 / 100,
-                (c.kp_x100 % 100).abs(),
-                c.ki_x100 / 100,
-                (c.ki_x100 % 100).abs(),
-                c.kd_x100 / 100,
-                (c.kd_x100 % 100).abs(),
+    let heater_pin = Output::new(p.PIN_23, Level::from(heater_inverted));
+    let mut heater = GpioHeater::new(HeaterPin(heater_pin), heater_inverted);
+    if let Some(enable) = heater_enable {
+        if enable.pin > 29 || CLAIMED_PINS.contains(&enable.pin) {
+            warn!(
+                "Heater enable pin gpio{} is already in use, not driving it",
+                enable.pin
             );
-            (Some(c.kp_x100), Some(c.ki_x100), Some(c.kd_x100))
         } else {
-            (None, None, None)
-        };
-
-        // TOML values take priority over calibration
-        let pid_kp = toml_kp.or(cal_kp).unwrap_or(0);
-        let pid_ki = toml_ki.or(cal_ki).unwrap_or(0);
-        let pid_kd = toml_kd.or(cal_kd).unwrap_or(0);
-
-        if pid_kp != 0 || pid_ki != 0 || pid_kd != 0 {
-            info!(
-                "Using PID coefficients: Kp={}.{:02}, Ki={}.{:02}, Kd={}.{:02}",
-                pid_kp / 100,
-                (pid_kp % 100).abs(),
-                pid_ki / 100,
-                (pid_ki % 100).abs(),
-                pid_kd / 100,
-                (pid_kd % 100).abs(),
-            );
+            // SAFETY: the pin is a valid GPIO not claimed by any other
+            // peripheral set up in main (checked against CLAIMED_PINS)
+            let pin = unsafe { AnyPin::steal(enable.pin) };
+            let enable_out = Output::new(pin, Level::from(enable.inverted));
+            heater = heater.with_enable_pin(HeaterPin(enable_out), enable.inverted);
+            info!("Heater enable pin: gpio{}", enable.pin);
         }
+    }
 
-        tasks::HeaterConfig {
-            control_mode: control,
-            max_temp_c: max_temp,
-            hysteresis_c: hysteresis,
-            pullup_ohms: 4700, // Standard 4.7K pullup (could be configurable)
-            adc_max: 4096,
-            pid_kp_x100: pid_kp,
-            pid_ki_x100: pid_ki,
-            pid_kd_x100: pid_kd,
-            ..Default::default()
+    // Heater settings from config with calibration fallback
+    let heater_config = match &primary_heater {
+        Some(heater) => heater_task_config(0, heater, calibration.get(0)),
+        None => {
+            warn!("No heater config found, using defaults");
+            tasks::HeaterConfig::default()
         }
-    } else {
-        warn!("No dryer heater config found, using defaults");
-        tasks::HeaterConfig::default()
     };
 
+    // Temperature sensor: thermistor on the ADC, a digital sensor on I2C1,
+    // or a DS18B20 on its own 1-Wire pin
+    // I2C pins are board-specific; thermistors use the configured sensor_pin
+    // (SKR Pico TH0: GPIO27, THB: GPIO26)
+    // Without I2C the ADC is kept for the other heaters' thermistors
+    let (temp_sensor, mut thermistor_adc): (
+        &'static mut dyn TemperatureSensor,
+        Option<(&'static SharedAdc, AdcPins)>,
+    ) =
+        if sensor_type.is_i2c() {
+            let address = sensor_address.unwrap_or(TMP117_DEFAULT_ADDRESS);
+            let i2c = I2c::new_blocking(p.I2C1, p.PIN_27, p.PIN_26, I2cConfig::default());
+            let mut sensor = Tmp117Sensor::new(RpI2c::new(i2c), address);
+            if sensor.probe().is_ok() {
+                info!("TMP117 found at {:#04x}", address);
+            } else {
+                warn!("No TMP117 answering at {:#04x}", address);
+            }
+            (I2C_SENSOR.init(sensor), None)
+        } else {
+            let adc: &'static SharedAdc = SHARED_ADC.init(BlockingMutex::new(RefCell::new(
+                Adc::new(p.ADC, Irqs, embassy_rp::adc::Config::default()),
+            )));
+            let mut adc_pins = AdcPins::new(p.PIN_26, p.PIN_27, p.PIN_28, p.PIN_29);
+            // A DS18B20 that can't be wired up is left absent: the heater
+            // reports a sensor fault rather than the board panicking at boot
+            let sensor: &'static mut dyn TemperatureSensor = if sensor_type.is_onewire() {
+                match onewire_pin {
+                    None => {
+                        warn!("ds18b20 sensor needs an onewire_pin, no temperature sensor");
+                        MISSING_SENSOR.init(tasks::MissingSensor)
+                    }
+                    Some(pin)
+                        if pin.pin > 29
+                            || CLAIMED_PINS.contains(&pin.pin)
+                            || heater_enable.is_some_and(|e| e.pin == pin.pin) =>
+                    {
+                        warn!(
+                            "onewire_pin gpio{} is already in use, no temperature sensor",
+                            pin.pin
+                        );
+                        MISSING_SENSOR.init(tasks::MissingSensor)
+                    }
+                    Some(pin) => {
+                        // SAFETY: the pin is a valid GPIO not claimed by any
+                        // other peripheral set up in main (checked above)
+                        let any_pin = unsafe { AnyPin::steal(pin.pin) };
+                        let mut bus_pin = OutputOpenDrain::new(any_pin, Level::High);
+                        bus_pin.set_pullup(pin.pull_up);
+                        let bus = OneWire::new(RpOpenDrain::new(bus_pin), Delay);
+                        let mut sensor = Ds18b20Sensor::new(bus);
+                        match sensor.probe() {
+                            Ok(_) => info!("DS18B20 found on gpio{}", pin.pin),
+                            Err(e) => warn!("No DS18B20 answering on gpio{}: {:?}", pin.pin, e),
+                        }
+                        ONEWIRE_SENSOR.init(sensor)
+                    }
+                }
+            } else {
+                let therm_channel = adc_pins.channel(sensor_pin).unwrap_or_else(|e| {
+                    defmt::panic!("Thermistor sensor_pin {}: {:?}", sensor_pin, e)
+                });
+                THERMISTOR.init(tasks::ThermistorSensor::new(
+                    adc,
+                    therm_channel,
+                    heater_config.pullup_ohms,
+                    heater_config.adc_max,
+                    thermistor_model,
+                    AdcFilter::new(adc_filter),
+                ))
+            };
+            (sensor, Some((adc, adc_pins)))
+        };
+
+    let stop_behavior = stepper_config_values
+        .map(|(_, _, _, _, stop, _)| stop)
+        .unwrap_or_default();
+
     info!("ADC and heater initialized");
 
-    // TMC2209 setup (only for stepper motor type)
-    let tmc_resources = if motor_type == MotorType::Stepper {
+    // TMC2209 setup (only for stepper motor type without a step/dir driver)
+    let tmc_resources = if motor_type == MotorType::Stepper && a4988_config_values.is_none() {
         // Setup UART1 for TMC2209 communication
         // Pin assignments are board-specific (SKR Pico TMC: GPIO8 TX, GPIO9 RX)
         let tmc_uart_config = {
@@ -394,14 +635,18 @@ async fn main(spawner: Spawner) {
             p.DMA_CH1,
             tmc_uart_config,
         );
-        let (tmc_tx, _tmc_rx) = tmc_uart.split();
+        let (tmc_tx, tmc_rx) = tmc_uart.split();
 
         // Get microsteps from stepper config for TMC
-        let stepper_microsteps = stepper_config_values.map(|(_, _, ms)| ms).unwrap_or(16);
+        let (stepper_steps_per_rev, stepper_microsteps) = stepper_config_values
+            .map(|(steps, _, ms, _, _, _)| (steps, ms))
+            .unwrap_or((3200, 16));
 
         // TMC2209 configuration from config (already extracted above)
         let tmc_config =
-            if let Some((uart_addr, run_ma, hold_ma, stealthchop, sg_thresh)) = tmc_config_values {
+            if let Some((uart_addr, run_ma, hold_ma, stealthchop, sg_thresh, rsense, spin_hold)) =
+                tmc_config_values
+            {
                 isochron_drivers::stepper::tmc2209::Tmc2209Config {
                     uart_address: uart_addr,
                     run_current_ma: run_ma,
@@ -409,6 +654,10 @@ async fn main(spawner: Spawner) {
                     stealthchop,
                     stallguard_threshold: sg_thresh,
                     microsteps: stepper_microsteps.into(), // u8 -> u16 safely
+                    rsense_mohm: rsense
+                        .unwrap_or(isochron_drivers::stepper::tmc2209::DEFAULT_RSENSE_MOHM),
+                    steps_per_rev: stepper_steps_per_rev,
+                    spin_hold,
                 }
             } else {
                 warn!("No TMC2209 config found, using defaults");
@@ -419,6 +668,9 @@ async fn main(spawner: Spawner) {
                     stealthchop: true,
                     stallguard_threshold: 80,
                     microsteps: 16,
+                    rsense_mohm: isochron_drivers::stepper::tmc2209::DEFAULT_RSENSE_MOHM,
+                    steps_per_rev: stepper_steps_per_rev,
+                    spin_hold: false,
                 }
             };
 
@@ -427,38 +679,344 @@ async fn main(spawner: Spawner) {
         // Setup TMC2209 DIAG pin for StallGuard stall detection
         // SKR Pico stepper X DIAG pin is GPIO17
         let diag_pin = Input::new(p.PIN_17, Pull::Down);
-        let stall_config = tasks::StallMonitorConfig::default();
+        let mut stall_config = tasks::StallMonitorConfig::default();
+        if let Some(settle_ms) = stall_settle_ms {
+            stall_config.settle_ms = settle_ms as u32;
+        }
 
         info!("TMC DIAG pin initialized");
 
-        Some((tmc_tx, tmc_config, diag_pin, stall_config))
+        Some((tmc_tx, tmc_rx, tmc_config, diag_pin, stall_config))
     } else {
         None
     };
 
-    // Machine capabilities (manual machine for now - no z/x motors)
+    // Lid interlock switch, taken by number like the heater enable pin
+    let lid = lid_config.and_then(|lid| {
+        let pin = lid.pin.pin;
+        if pin > 29
+            || CLAIMED_PINS.contains(&pin)
+            || heater_enable.is_some_and(|e| e.pin == pin)
+            || onewire_gpio == Some(pin)
+        {
+            warn!("Lid pin gpio{} is already in use, no interlock", pin);
+            return None;
+        }
+        // SAFETY: the pin is a valid GPIO not claimed by any other
+        // peripheral set up in main (checked above)
+        let any_pin = unsafe { AnyPin::steal(pin) };
+        let pull = if lid.pin.pull_up {
+            Pull::Up
+        } else {
+            Pull::None
+        };
+        info!("Lid interlock pin: gpio{}", pin);
+        Some((RpInput::new(Input::new(any_pin, pull)), lid))
+    });
+
+    // GPIOs taken by number below, checked by each later claim
+    let lid_pin = lid.as_ref().map(|(_, lid)| lid.pin.pin);
+    let mut taken: heapless::Vec<u8, 24> = heapless::Vec::new();
+
+    // A4988 MS pins, taken by number like the lid pin. The driver is kept
+    // for the life of the firmware so the pins hold their levels. With the
+    // pins floating the microstep resolution, and so the basket speed, is
+    // unknown, so a failed setup leaves the motor unspawned and faults.
+    let mut motor_setup_failed = false;
+    if let Some((chip, ms_pin_configs)) = a4988_config_values {
+        info!("A4988 driver: no UART setup or stall detection");
+        let ms_pins = ms_pin_configs.map(|ms| {
+            let ms = ms?;
+            let pin = ms.pin;
+            if pin > 29
+                || CLAIMED_PINS.contains(&pin)
+                || heater_enable.is_some_and(|e| e.pin == pin)
+                || lid_pin == Some(pin)
+                || onewire_gpio == Some(pin)
+                || taken.contains(&pin)
+            {
+                warn!("A4988 MS pin gpio{} is already in use", pin);
+                return None;
+            }
+            let _ = taken.push(pin);
+            // SAFETY: the pin is a valid GPIO not claimed by any other
+            // peripheral set up in main (checked above)
+            let any_pin = unsafe { AnyPin::steal(pin) };
+            Some(MsPin {
+                pin: RpOutput::new(Output::new(any_pin, Level::Low)),
+                inverted: ms.inverted,
+            })
+        });
+        let microsteps = stepper_config_values
+            .map(|(_, _, ms, _, _, _)| ms)
+            .unwrap_or(16);
+        let a4988_config = A4988Config {
+            chip,
+            microsteps: microsteps.into(),
+        };
+        match A4988Driver::new(a4988_config, ms_pins) {
+            Ok(driver) => {
+                static A4988: StaticCell<A4988Driver<RpOutput<'static>>> = StaticCell::new();
+                A4988.init(driver);
+                info!("A4988 MS pins set for 1/{} microstepping", microsteps);
+            }
+            Err(e) => {
+                error!(
+                    "A4988 can't be set to 1/{} microstepping: {:?}",
+                    microsteps, e
+                );
+                motor_setup_failed = true;
+            }
+        }
+    }
+
+    // Heaters after the first: output and enable pins taken by number like
+    // the lid pin, thermistors on the shared ADC. One that can't be set up
+    // is left without a task, so its jars never heat.
+    let mut extra_heater_tasks: heapless::Vec<
+        (
+            u8,
+            &'static mut dyn TemperatureSensor,
+            GpioHeater<HeaterPin>,
+            tasks::HeaterConfig,
+        ),
+        { MAX_HEATERS - 1 },
+    > = heapless::Vec::new();
+    for ((index, (hw, control)), sensor_cell) in
+        (1u8..).zip(extra_heaters).zip(EXTRA_THERMISTORS.iter())
+    {
+        let name = hw.name.as_str();
+        if hw.sensor_type.is_i2c() || hw.sensor_type.is_onewire() {
+            warn!(
+                "Heater {} ({}) needs a thermistor sensor, not started",
+                index, name
+            );
+            continue;
+        }
+        let Some((adc, adc_pins)) = thermistor_adc.as_mut() else {
+            warn!(
+                "Heater {} ({}): ADC pins used by I2C, not started",
+                index, name
+            );
+            continue;
+        };
+        let in_use = |pin: u8| {
+            pin > 29
+                || CLAIMED_PINS.contains(&pin)
+                || heater_enable.is_some_and(|e| e.pin == pin)
+                || lid_pin == Some(pin)
+                || onewire_gpio == Some(pin)
+                || taken.contains(&pin)
+        };
+        let pin = hw.heater_pin.pin;
+        if in_use(pin) || hw.enable_pin.is_some_and(|e| e.pin == pin || in_use(e.pin)) {
+            warn!(
+                "Heater {} ({}) pins are already in use, not started",
+                index, name
+            );
+            continue;
+        }
+        let channel = match adc_pins.channel(hw.sensor_pin) {
+            Ok(channel) => channel,
+            Err(e) => {
+                warn!(
+                    "Heater {} ({}) sensor_pin {}: {:?}",
+                    index, name, hw.sensor_pin, e
+                );
+                continue;
+            }
+        };
+        let _ = taken.push(pin);
+
+        // SAFETY: the pin is a valid GPIO not claimed by any other
+        // peripheral set up in main (checked above)
+        let any_pin = unsafe { AnyPin::steal(pin) };
+        let inverted = hw.heater_pin.inverted;
+        let mut output = GpioHeater::new(
+            HeaterPin(Output::new(any_pin, Level::from(inverted))),
+            inverted,
+        );
+        if let Some(enable) = hw.enable_pin {
+            let _ = taken.push(enable.pin);
+            // SAFETY: as above
+            let any_pin = unsafe { AnyPin::steal(enable.pin) };
+            let enable_out = Output::new(any_pin, Level::from(enable.inverted));
+            output = output.with_enable_pin(HeaterPin(enable_out), enable.inverted);
+        }
+
+        let config = heater_task_config(index, &control, calibration.get(index));
+        let sensor = sensor_cell.init(tasks::ThermistorSensor::new(
+            adc,
+            channel,
+            config.pullup_ohms,
+            config.adc_max,
+            hw.thermistor_model(),
+            AdcFilter::new(hw.adc_filter),
+        ));
+        info!(
+            "Heater {} ({}): gpio{}, thermistor on gpio{}",
+            index, name, pin, hw.sensor_pin
+        );
+        let _ = extra_heater_tasks.push((index, sensor, output, config));
+    }
+
+    // Z and X axes: step, dir, enable and endstop pins taken by number like
+    // the heater pins. An endstop-homed axis needs its endstop settings;
+    // one without them is left out, so homing doesn't wait on it. Manually
+    // homed axes and axes without homing need no endstop.
+    let mut axis_tasks: heapless::Vec<(Axis, tasks::AxisPins, tasks::AxisFwConfig), 2> =
+        heapless::Vec::new();
+    let mut axis_homing = [None, None];
+    let mut axis_types = [HomingType::None; 2];
+    for (axis, stepper) in [Axis::Z, Axis::X].into_iter().zip(axis_steppers) {
+        let Some(stepper) = stepper else {
+            continue;
+        };
+        let homing = match stepper.homing_type {
+            HomingType::Endstop => match HomingConfig::from_stepper(&stepper) {
+                Some(homing) => Some(homing),
+                None => {
+                    warn!(
+                        "{:?} axis needs endstop_pin, position_endstop and position_max, not started",
+                        axis
+                    );
+                    continue;
+                }
+            },
+            HomingType::Manual | HomingType::None => None,
+        };
+        let endstop = homing.map(|(_, endstop)| endstop);
+        let mut pins: heapless::Vec<u8, 4> = heapless::Vec::new();
+        let _ = pins.extend_from_slice(&[
+            stepper.step_pin.pin,
+            stepper.dir_pin.pin,
+            stepper.enable_pin.pin,
+        ]);
+        if let Some(endstop) = endstop {
+            let _ = pins.push(endstop.pin());
+        }
+        let in_use = |pin: u8| {
+            pin > 29
+                || CLAIMED_PINS.contains(&pin)
+                || heater_enable.is_some_and(|e| e.pin == pin)
+                || lid_pin == Some(pin)
+                || onewire_gpio == Some(pin)
+                || taken.contains(&pin)
+        };
+        if (0..pins.len()).any(|i| in_use(pins[i]) || pins[..i].contains(&pins[i])) {
+            warn!("{:?} axis pins are already in use, not started", axis);
+            continue;
+        }
+        let _ = taken.extend_from_slice(&pins);
+
+        // SAFETY: the pins are valid GPIOs not claimed by any other
+        // peripheral set up in main (checked above)
+        let output = |pin: u8, level: Level| {
+            RpOutput::new(Output::new(unsafe { AnyPin::steal(pin) }, level))
+        };
+        let axis_pins = tasks::AxisPins {
+            step: output(stepper.step_pin.pin, Level::Low),
+            dir: output(stepper.dir_pin.pin, Level::Low),
+            enable: output(
+                stepper.enable_pin.pin,
+                Level::from(stepper.enable_pin.inverted),
+            ),
+            // SAFETY: as above
+            endstop: endstop.map(|endstop| {
+                let pull = if endstop.pull_up() {
+                    Pull::Up
+                } else {
+                    Pull::None
+                };
+                RpInput::new(Input::new(unsafe { AnyPin::steal(endstop.pin()) }, pull))
+            }),
+        };
+        let axis_config = tasks::AxisFwConfig {
+            dir_pin: stepper.dir_pin,
+            enable_pin: stepper.enable_pin,
+            endstop,
+            backlash: Backlash::from_stepper(&stepper),
+            steps_per_mm_x1000: stepper.steps_per_mm_x1000(),
+            jog_speed: stepper.homing_speed.unwrap_or(DEFAULT_HOMING_SPEED),
+            step_pulse_ns: stepper
+                .step_pulse_ns
+                .map(u32::from)
+                .unwrap_or(DEFAULT_STEP_PULSE_NS),
+        };
+        info!(
+            "{:?} axis: step gpio{}, homing {:?}",
+            axis, stepper.step_pin.pin, stepper.homing_type
+        );
+        axis_homing[axis as usize] = homing;
+        axis_types[axis as usize] = stepper.homing_type;
+        let _ = axis_tasks.push((axis, axis_pins, axis_config));
+    }
+
+    // Ultrasonic module output, taken by number like the axis pins
+    let ultrasonic = ultrasonic_config.and_then(|config| {
+        let pin = config.pin.pin;
+        if pin > 29
+            || CLAIMED_PINS.contains(&pin)
+            || heater_enable.is_some_and(|e| e.pin == pin)
+            || lid_pin == Some(pin)
+            || onewire_gpio == Some(pin)
+            || taken.contains(&pin)
+        {
+            warn!(
+                "Ultrasonic pin gpio{} is already in use, not driving it",
+                pin
+            );
+            return None;
+        }
+        let _ = taken.push(pin);
+        // SAFETY: the pin is a valid GPIO not claimed by any other
+        // peripheral set up in main (checked above)
+        let any_pin = unsafe { AnyPin::steal(pin) };
+        let output = Output::new(any_pin, Level::from(config.pin.inverted));
+        info!("Ultrasonic {} pin: gpio{}", config.name.as_str(), pin);
+        Some((
+            tasks::ModulePin::new(RpOutput::new(output), config.pin),
+            config,
+        ))
+    });
+
+    // Machine capabilities: Z and X home at boot, but moves between jars
+    // aren't implemented yet, so programs run as on a manual machine
+    let has_axis = |axis: Axis| axis_tasks.iter().any(|(a, _, _)| *a == axis);
     let capabilities = MachineCapabilities {
-        has_z: false,
-        has_x: false,
-        has_lid: false,
-        heater_count: 1,
+        has_z: has_axis(Axis::Z),
+        has_x: has_axis(Axis::X),
+        has_lid: lid.is_some(),
+        heater_count,
+        has_heater: heater_count > 0,
         is_automated: false,
     };
 
     // Spawn tasks
     spawner.spawn(tasks::tick_task()).unwrap();
-    spawner.spawn(tasks::display_rx_task(rx)).unwrap();
-    spawner.spawn(tasks::display_tx_task(tx)).unwrap();
+    spawner
+        .spawn(tasks::display_rx_task(rx, link.test_pattern))
+        .unwrap();
+    spawner
+        .spawn(tasks::display_tx_task(
+            tx,
+            link.heartbeat_ms,
+            link.test_pattern,
+        ))
+        .unwrap();
 
     // Motor task - spawn based on motor resources
     match motor_resources {
-        MotorResources::Stepper(stepper) => {
-            spawner.spawn(tasks::stepper_task(stepper)).unwrap();
+        MotorResources::Stepper(..) if motor_setup_failed => {
+            error!("Stepper motor task not spawned: driver setup failed");
+        }
+        MotorResources::Stepper(stepper, ramp) => {
+            spawner.spawn(tasks::stepper_task(stepper, ramp)).unwrap();
             info!("Stepper motor task spawned");
             // TMC2209 and stall monitor tasks (only for stepper)
-            if let Some((tmc_tx, tmc_config, diag_pin, stall_config)) = tmc_resources {
+            if let Some((tmc_tx, tmc_rx, tmc_config, diag_pin, stall_config)) = tmc_resources {
                 spawner
-                    .spawn(tasks::tmc_init_task(tmc_tx, tmc_config))
+                    .spawn(tasks::tmc_task(tmc_tx, tmc_rx, tmc_config))
                     .unwrap();
                 spawner
                     .spawn(tasks::stall_monitor_task(diag_pin, stall_config))
@@ -481,13 +1039,22 @@ async fn main(spawner: Spawner) {
     }
 
     spawner
-        .spawn(tasks::heater_task(
-            adc,
-            therm_channel,
-            heater_pin,
-            heater_config,
-        ))
+        .spawn(tasks::heater_task(0, temp_sensor, heater, heater_config))
         .unwrap();
+    for (index, sensor, output, config) in extra_heater_tasks {
+        spawner
+            .spawn(tasks::heater_task(index, sensor, output, config))
+            .unwrap();
+    }
+    if let Some((pin, config)) = lid {
+        spawner.spawn(tasks::lid_task(pin, config)).unwrap();
+    }
+    if let Some((pin, config)) = ultrasonic {
+        spawner.spawn(tasks::ultrasonic_task(pin, config)).unwrap();
+    }
+    for (axis, pins, config) in axis_tasks {
+        spawner.spawn(tasks::axis_task(axis, pins, config)).unwrap();
+    }
     spawner
         .spawn(tasks::calibration_task(flash_storage))
         .unwrap();
@@ -497,17 +1064,60 @@ async fn main(spawner: Spawner) {
             programs,
             profiles,
             jars,
+            heaters,
+            calibration,
+            tasks::ControllerSettings {
+                stop_behavior,
+                ui,
+                autostart_program,
+                max_pause_s,
+                prewarm_next_jar,
+                link,
+                spinoff_limits,
+                park,
+                homing: tasks::HomingSettings {
+                    order: homing_order,
+                    types: axis_types,
+                    axes: axis_homing,
+                },
+                protection: tasks::ProtectionSettings {
+                    motor_setup_failed,
+                    ..protection
+                },
+            },
         ))
         .unwrap();
 
+    // A task that hangs the executor or stalls the controller starves the
+    // watchdog task, and the watchdog resets the board
+    spawner
+        .spawn(tasks::watchdog_task(watchdog, watchdog_timeout_ms))
+        .unwrap();
+
     info!("All tasks spawned, firmware running");
+}
+
+/// Check why the board last reset, and arm the watchdog
+///
+/// Returns the watchdog and its armed timeout, or None if it is disabled.
+/// A watchdog timeout is reported to the controller along with the
+/// breadcrumb it left.
+fn init_watchdog(mut watchdog: RpWatchdog, timeout_ms: u16) -> (RpWatchdog, Option<u32>) {
+    if watchdog.reset_by_timeout() {
+        let last = Breadcrumb::from_word(watchdog.scratch(BREADCRUMB_SCRATCH));
+        warn!("Reset by watchdog, last state: {:?}", last);
+        RECOVERY_NOTICE.signal(RecoveryNotice { last });
+    }
+    watchdog.set_scratch(BREADCRUMB_SCRATCH, 0);
 
-    // Main task has nothing else to do - all work happens in spawned tasks
-    // We could use this for watchdog or other system monitoring
-    loop {
-        embassy_time::Timer::after_secs(60).await;
-        trace!("Main loop heartbeat");
+    if timeout_ms == 0 {
+        info!("Watchdog disabled");
+        return (watchdog, None);
     }
+    let timeout_ms = watchdog.clamp_timeout_ms(timeout_ms as u32);
+    watchdog.start(timeout_ms);
+    info!("Watchdog armed: {}ms", timeout_ms);
+    (watchdog, Some(timeout_ms))
 }
 
 /// Initialize the heap allocator
@@ -525,47 +1135,135 @@ use isochron_core::config::CalibrationData;
 /// Load configuration from flash storage
 ///
 /// Attempts to load TOML config from flash. If not found or invalid,
-/// returns the embedded default configuration.
+/// returns the embedded default configuration, reported as
+/// [`ConfigSource::Missing`] when its fallback is disabled.
+/// Saved profile edits are merged over the loaded profiles.
 /// Also loads PID calibration data and returns the FlashStorage for future saves.
 async fn load_config_from_flash(
     flash: Peri<'static, FLASH>,
     dma: Peri<'static, DMA_CH2>,
-) -> (MachineConfig, CalibrationData, FlashStorage<'static>) {
+) -> (
+    MachineConfig,
+    ConfigSource,
+    CalibrationData,
+    FlashStorage<'static>,
+) {
     let flash_storage = FlashStorage::new(flash, dma);
     let mut persistence = ConfigPersistence::new(flash_storage);
 
     let default_config = create_default_config();
 
-    let config = match persistence.load().await {
-        Ok(config) => {
-            info!("Loaded configuration from flash");
-            config
+    let loaded = persistence.load().await;
+    if let Err(e) = &loaded {
+        if e.is_read_error() {
+            error!("Flash read failed after retries: {:?}", e);
+        }
+    }
+    let (mut config, source) = MachineConfig::select(loaded.ok(), default_config);
+    match source {
+        ConfigSource::Flash => info!("Loaded configuration from flash"),
+        ConfigSource::Embedded => {
+            info!("No valid configuration in flash, using embedded defaults")
         }
-        Err(_) => {
-            // Flash empty or invalid - use embedded defaults
-            info!("No valid configuration in flash, using embedded defaults");
-            default_config
+        ConfigSource::Missing => {
+            error!("No valid configuration in flash, embedded fallback disabled")
         }
-    };
+    }
 
     // Reclaim the storage to load calibration
     let mut storage = persistence.into_storage();
 
+    // Merge user-saved profile values
+    let overrides = crate::config::load_profile_overrides(&mut storage).await;
+    let applied = overrides.apply(&mut config.profiles);
+    if applied > 0 {
+        info!("Applied {} saved profile edit(s)", applied);
+    }
+
     // Load PID calibration data
     let calibration = crate::config::load_calibration(&mut storage).await;
 
-    (config, calibration, storage)
+    (config, source, calibration, storage)
+}
+
+/// Heater task settings for heater `index`
+///
+/// Priority: TOML config > Calibration from flash > Defaults
+fn heater_task_config(
+    index: u8,
+    heater: &HeaterConfig,
+    calibration: Option<&HeaterCalibration>,
+) -> tasks::HeaterConfig {
+    info!(
+        "Heater {} config: max_temp={}°C, hysteresis={}°C, control={:?}",
+        index, heater.max_temp, heater.hysteresis, heater.control
+    );
+    if heater.pid_kp_x100.is_some() || heater.pid_ki_x100.is_some() || heater.pid_kd_x100.is_some()
+    {
+        info!(
+            "  PID from TOML: Kp={:?}, Ki={:?}, Kd={:?}",
+            heater.pid_kp_x100, heater.pid_ki_x100, heater.pid_kd_x100
+        );
+    }
+
+    let (cal_kp, cal_ki, cal_kd) = if let Some(c) = calibration {
+        info!(
+            "Loaded PID calibration from flash: Kp={}.{:02}, Ki={}.{:02}, Kd={}.{:02}",
+            c.kp_x100 / 100,
+            (c.kp_x100 % 100).abs(),
+            c.ki_x100 / 100,
+            (c.ki_x100 % 100).abs(),
+            c.kd_x100 / 100,
+            (c.kd_x100 % 100).abs(),
+        );
+        (Some(c.kp_x100), Some(c.ki_x100), Some(c.kd_x100))
+    } else {
+        (None, None, None)
+    };
+
+    // TOML values take priority over calibration
+    let pid_kp = heater.pid_kp_x100.or(cal_kp).unwrap_or(0);
+    let pid_ki = heater.pid_ki_x100.or(cal_ki).unwrap_or(0);
+    let pid_kd = heater.pid_kd_x100.or(cal_kd).unwrap_or(0);
+
+    if pid_kp != 0 || pid_ki != 0 || pid_kd != 0 {
+        info!(
+            "Using PID coefficients: Kp={}.{:02}, Ki={}.{:02}, Kd={}.{:02}",
+            pid_kp / 100,
+            (pid_kp % 100).abs(),
+            pid_ki / 100,
+            (pid_ki % 100).abs(),
+            pid_kd / 100,
+            (pid_kd % 100).abs(),
+        );
+    }
+
+    tasks::HeaterConfig {
+        control_mode: heater.control,
+        max_temp_c: heater.max_temp,
+        hysteresis_c: heater.hysteresis,
+        pullup_ohms: 4700, // Standard 4.7K pullup (could be configurable)
+        adc_max: 4096,
+        pid_kp_x100: pid_kp,
+        pid_ki_x100: pid_ki,
+        pid_kd_x100: pid_kd,
+        max_heat_rate_c_per_min: heater.max_heat_rate_c_per_min,
+        ..Default::default()
+    }
 }
 
 /// Convert MachineConfig to static slices for task consumption
 ///
 /// Copies config data into static cells that live for the program duration.
+/// Heaters are returned in `[heater]` section order, so a heater's index
+/// is the same for the scheduler and the heater tasks.
 fn init_config_from_machine(
     config: MachineConfig,
 ) -> (
     &'static [ProgramConfig],
     &'static [ProfileConfig],
     &'static [JarConfig],
+    &'static [HeaterConfig],
 ) {
     // Store full config (for potential future use)
     let stored_config = MACHINE_CONFIG.init(config);
@@ -594,11 +1292,26 @@ fn init_config_from_machine(
     }
     let jars = JARS.init(jars_arr);
 
+    // Copy heater control settings to static array, one per [heater]
+    let mut heaters_arr: [HeaterConfig; MAX_HEATERS] = Default::default();
+    let heater_count = stored_config.heater_hw.len();
+    for (i, hw) in stored_config.heater_hw.iter().enumerate() {
+        heaters_arr[i] = stored_config
+            .find_heater(&hw.name)
+            .cloned()
+            .unwrap_or_else(|| HeaterConfig {
+                name: hw.name.clone(),
+                ..Default::default()
+            });
+    }
+    let heaters = HEATERS.init(heaters_arr);
+
     // Return slices of actual data (not full arrays)
     (
         &programs[..program_count],
         &profiles[..profile_count],
         &jars[..jar_count],
+        &heaters[..heater_count],
     )
 }
 
@@ -673,9 +1386,30 @@ fn create_minimal_fallback_config() -> MachineConfig {
 
     let program = ProgramConfig {
         label: prog_label,
+        notes: None,
         steps,
     };
     let _ = config.programs.push(program);
 
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use isochron_hal_rp2040::adc::AdcChannel;
+
+    #[test]
+    fn test_adc_pins_claimed() {
+        let adc = [
+            AdcChannel::Adc0,
+            AdcChannel::Adc1,
+            AdcChannel::Adc2,
+            AdcChannel::Adc3,
+        ];
+        for channel in adc {
+            let gpio = channel.gpio().unwrap();
+            assert!(CLAIMED_PINS.contains(&gpio), "gpio{} not claimed", gpio);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Z and X axis task
+//!
+//! Steps a position-controlled axis (the basket lift or the jar carousel)
+//! on the controller's behalf and reports its endstop and travel. What to
+//! do next is decided by the controller; this task only moves. A move
+//! stops by itself the moment the endstop triggers, rather than waiting
+//! up to a tick for the controller to react. Jogs for manual homing are
+//! counted out here at the homing speed. After a reversal the axis's
+//! backlash is taken up before any travel is counted.
+
+use defmt::*;
+use embassy_futures::select::{select, Either};
+use embassy_time::{Delay, Duration, Instant, Timer};
+use embedded_hal::delay::DelayNs;
+use isochron_core::config::PinConfig;
+use isochron_core::motion::{Axis, Backlash, Endstop, HomingMove};
+use isochron_hal_rp2040::gpio::{RpInput, RpOutput};
+use isochron_hal_rp2040::{InputPinTrait, OutputPinTrait};
+
+use crate::channels::{AxisCommand, AxisReport, AXIS_CMD, AXIS_REPORT};
+
+/// Interval between reports while the axis is stopped or moving (ms)
+const REPORT_MS: u64 = 20;
+
+/// Step/dir/enable outputs and endstop input of an axis
+pub struct AxisPins {
+    /// Step pulse output
+    pub step: RpOutput<'static>,
+    /// Direction output
+    pub dir: RpOutput<'static>,
+    /// Driver enable output
+    pub enable: RpOutput<'static>,
+    /// Endstop switch input (None = manually homed axis without one)
+    pub endstop: Option<RpInput<'static>>,
+}
+
+/// Axis settings from the stepper configuration
+pub struct AxisFwConfig {
+    /// Direction pin polarity
+    pub dir_pin: PinConfig,
+    /// Enable pin polarity
+    pub enable_pin: PinConfig,
+    /// Endstop switch polarity
+    pub endstop: Option<Endstop>,
+    /// Play taken up when the axis reverses
+    pub backlash: Backlash,
+    /// Steps per mm of travel × 1000
+    pub steps_per_mm_x1000: u32,
+    /// Jog speed (mm/s)
+    pub jog_speed: u16,
+    /// Step pulse width (ns)
+    pub step_pulse_ns: u32,
+}
+
+/// Time between steps at `speed_mm_s` (µs)
+fn step_interval_us(speed_mm_s: u16, steps_per_mm_x1000: u32) -> u64 {
+    let steps_per_s_x1000 = speed_mm_s.max(1) as u64 * steps_per_mm_x1000.max(1) as u64;
+    (1_000_000_000 / steps_per_s_x1000).max(1)
+}
+
+/// Distance covered by `steps` (µm)
+fn steps_to_um(steps: u32, steps_per_mm_x1000: u32) -> u32 {
+    (steps as u64 * 1_000_000 / steps_per_mm_x1000.max(1) as u64).min(u32::MAX as u64) as u32
+}
+
+/// Jog left over from `remaining` signed steps plus a new jog of `jog_mm`
+fn add_jog(remaining: i64, jog_mm: i32, steps_per_mm_x1000: u32) -> i64 {
+    remaining + jog_mm as i64 * steps_per_mm_x1000 as i64 / 1000
+}
+
+/// Axis task
+///
+/// Reports the endstop every `REPORT_MS` and follows the controller's
+/// motion commands. The driver is enabled on the first move and then
+/// left enabled, holding the axis where it stopped.
+#[embassy_executor::task(pool_size = 2)]
+pub async fn axis_task(axis: Axis, mut pins: AxisPins, mut config: AxisFwConfig) {
+    info!("{:?} axis task started", axis);
+    let set = |pin: &mut RpOutput<'static>, polarity: PinConfig, active: bool| {
+        if polarity.is_active(active) {
+            pin.set_high();
+        } else {
+            pin.set_low();
+        }
+    };
+    set(&mut pins.enable, config.enable_pin, false);
+    // Raw endstop level, and whether it reads triggered
+    let read_endstop = |pins: &AxisPins| {
+        let high = pins.endstop.as_ref().is_some_and(|pin| pin.is_high());
+        let triggered = config.endstop.is_some_and(|e| e.is_triggered(high));
+        (high, triggered)
+    };
+
+    let mut motion = HomingMove::Stop;
+    // Signed steps left of a jog (0 = not jogging)
+    let mut jog_steps: i64 = 0;
+    // Steps left taking up backlash after a reversal; they move the
+    // motor but not the axis, so they count as neither travel nor jog
+    let mut slack_steps: u16 = 0;
+    let mut was_triggered = read_endstop(&pins).1;
+    // Steps since the current move started, and the part already reported
+    let mut steps: u32 = 0;
+    let mut reported_um: u32 = 0;
+    let mut last_report = Instant::now();
+
+    loop {
+        let step_us = match motion {
+            HomingMove::Stop => None,
+            HomingMove::Move { speed, .. } => {
+                Some(step_interval_us(speed, config.steps_per_mm_x1000))
+            }
+        };
+        let wait_us = step_us.unwrap_or(REPORT_MS * 1000);
+        match select(
+            AXIS_CMD[axis as usize].wait(),
+            Timer::after(Duration::from_micros(wait_us)),
+        )
+        .await
+        {
+            Either::First(cmd) => {
+                trace!("{:?} axis: {:?}", axis, cmd);
+                let next = match cmd {
+                    AxisCommand::Homing(next) => {
+                        jog_steps = 0;
+                        next
+                    }
+                    AxisCommand::Jog(mm) => {
+                        jog_steps = add_jog(jog_steps, mm, config.steps_per_mm_x1000);
+                        if jog_steps == 0 {
+                            HomingMove::Stop
+                        } else {
+                            HomingMove::Move {
+                                positive: jog_steps > 0,
+                                speed: config.jog_speed,
+                            }
+                        }
+                    }
+                };
+                if let HomingMove::Move { positive, .. } = next {
+                    let take_up = config.backlash.take_up(positive);
+                    if take_up > 0 {
+                        slack_steps = take_up;
+                    }
+                    set(&mut pins.dir, config.dir_pin, positive);
+                    set(&mut pins.enable, config.enable_pin, true);
+                }
+                motion = next;
+            }
+            Either::Second(_) => {
+                if step_us.is_some() {
+                    pins.step.set_high();
+                    Delay.delay_ns(config.step_pulse_ns);
+                    pins.step.set_low();
+                    if slack_steps > 0 {
+                        slack_steps -= 1;
+                    } else {
+                        steps = steps.saturating_add(1);
+                        if jog_steps != 0 {
+                            jog_steps -= jog_steps.signum();
+                            if jog_steps == 0 {
+                                motion = HomingMove::Stop;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let (endstop_high, triggered) = read_endstop(&pins);
+        let hit = triggered && !was_triggered && motion != HomingMove::Stop;
+        was_triggered = triggered;
+        if hit {
+            debug!("{:?} axis endstop triggered", axis);
+            motion = HomingMove::Stop;
+            jog_steps = 0;
+        }
+
+        if hit || last_report.elapsed() >= Duration::from_millis(REPORT_MS) {
+            let moved_um = steps_to_um(steps, config.steps_per_mm_x1000);
+            let report = AxisReport {
+                axis,
+                endstop_high,
+                moved_um: moved_um - reported_um,
+            };
+            // A full channel keeps the travel for the next report
+            if AXIS_REPORT.try_send(report).is_ok() {
+                reported_um = moved_um;
+                if motion == HomingMove::Stop {
+                    steps = 0;
+                    reported_um = 0;
+                }
+            }
+            last_report = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_timing() {
+        // T8 leadscrew at 1/16: 400 steps/mm
+        assert_eq!(step_interval_us(5, 400_000), 500);
+        assert_eq!(steps_to_um(400, 400_000), 1000);
+        assert_eq!(steps_to_um(1, 400_000), 2);
+
+        // Unset values don't divide by zero
+        assert_eq!(step_interval_us(0, 0), 1_000_000);
+        assert_eq!(steps_to_um(10, 0), 10_000_000);
+    }
+
+    #[test]
+    fn test_jogs_add_up() {
+        assert_eq!(add_jog(0, 1, 400_000), 400);
+        assert_eq!(add_jog(150, 1, 400_000), 550);
+        // A reversal cancels what is left before moving back
+        assert_eq!(add_jog(150, -1, 400_000), -250);
+        assert_eq!(add_jog(400, -1, 400_000), 0);
+    }
+}
@@ -1,15 +1,18 @@
 //! Calibration persistence task
 //!
-//! Handles saving and loading PID calibration data to/from flash.
+//! Handles saving and loading PID calibration data to/from flash, and
+//! saves profile edits the user confirmed on the edit screen.
 //! Runs as a background task, listening for save requests.
 
 use defmt::*;
+use embassy_futures::select::{select, Either};
 
 use isochron_core::config::HeaterCalibration;
 use isochron_hal_rp2040::flash::FlashStorage;
 
-use crate::channels::CALIBRATION_SAVE;
+use crate::channels::{CalibrationSaveRequest, CALIBRATION_SAVE, CALIBRATION_SAVED, PROFILE_SAVE};
 use crate::config::calibration::save_heater_calibration;
+use crate::config::profiles::save_profile;
 
 /// Calibration task - handles flash persistence for PID calibration
 ///
@@ -22,35 +25,50 @@ pub async fn calibration_task(mut storage: FlashStorage<'static>) {
 
     loop {
         // Wait for a save request
-        let request = CALIBRATION_SAVE.wait().await;
-
-        info!(
-            "Saving calibration for heater {}: Kp={}.{:02}, Ki={}.{:02}, Kd={}.{:02}",
-            request.heater_index,
-            request.kp_x100 / 100,
-            (request.kp_x100 % 100).abs(),
-            request.ki_x100 / 100,
-            (request.ki_x100 % 100).abs(),
-            request.kd_x100 / 100,
-            (request.kd_x100 % 100).abs(),
-        );
-
-        // Create calibration entry
-        let calibration = HeaterCalibration::new(
-            request.heater_index,
-            request.kp_x100,
-            request.ki_x100,
-            request.kd_x100,
-        );
-
-        // Save to flash
-        match save_heater_calibration(&mut storage, calibration).await {
-            Ok(()) => {
-                info!("Calibration saved successfully");
-            }
-            Err(e) => {
-                error!("Failed to save calibration: {:?}", e);
+        match select(CALIBRATION_SAVE.wait(), PROFILE_SAVE.wait()).await {
+            Either::First(request) => save_calibration(&mut storage, request).await,
+            Either::Second(request) => {
+                info!("Saving profile {}", request.profile_index);
+                if let Err(e) =
+                    save_profile(&mut storage, request.profile_index, &request.profile).await
+                {
+                    error!("Failed to save profile: {:?}", e);
+                }
             }
         }
     }
 }
+
+/// Write an autotune result and report the outcome to the controller
+async fn save_calibration(storage: &mut FlashStorage<'static>, request: CalibrationSaveRequest) {
+    info!(
+        "Saving calibration for heater {}: Kp={}.{:02}, Ki={}.{:02}, Kd={}.{:02}",
+        request.heater_index,
+        request.kp_x100 / 100,
+        (request.kp_x100 % 100).abs(),
+        request.ki_x100 / 100,
+        (request.ki_x100 % 100).abs(),
+        request.kd_x100 / 100,
+        (request.kd_x100 % 100).abs(),
+    );
+
+    // Create calibration entry
+    let calibration = HeaterCalibration::new(
+        request.heater_index,
+        request.kp_x100,
+        request.ki_x100,
+        request.kd_x100,
+    );
+
+    // Save to flash
+    match save_heater_calibration(storage, calibration).await {
+        Ok(()) => {
+            info!("Calibration saved successfully");
+            CALIBRATION_SAVED.signal(true);
+        }
+        Err(e) => {
+            error!("Failed to save calibration: {:?}", e);
+            CALIBRATION_SAVED.signal(false);
+        }
+    }
+}
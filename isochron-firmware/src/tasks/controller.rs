@@ -6,20 +6,113 @@
 
 use defmt::*;
 use embassy_futures::select::{select3, Either3};
+use embassy_time::{Instant, Timer};
 
-use isochron_core::config::{JarConfig, MachineCapabilities, ProfileConfig, ProgramConfig};
-use isochron_core::state::State;
+use heapless::String as HString;
+
+use isochron_core::config::{
+    CalibrationData, HeaterConfig, HomingOrder, HomingType, JarConfig, LinkConfig,
+    MachineCapabilities, ParkPosition, ProfileConfig, ProgramConfig, SensorFaultPolicy,
+    StopBehavior, ThermalRunawayConfig, UiConfig, MAX_HEATERS, MAX_LABEL_LEN,
+};
+use isochron_core::motion::{Axis, Endstop, HomingConfig};
+use isochron_core::scheduler::{BalanceConfig, HeaterCommand, MotorCommand};
+use isochron_core::state::Event;
+use isochron_core::util::TemperatureC10;
+use isochron_protocol::InputEvent;
 
 use crate::channels::{
-    AutotuneCommand, AutotuneStatus, CalibrationSaveRequest, AUTOTUNE_CMD, AUTOTUNE_STATUS,
-    CALIBRATION_SAVE, EVENT_CHANNEL, HEARTBEAT_RECEIVED, HEATER_CMD, INPUT_CHANNEL, MOTOR_CMD,
-    MOTOR_STALL, SCREEN_UPDATE, TEMP_READING,
+    AutotuneCommand, AutotuneStatus, AxisCommand, CalibrationSaveRequest, ProfileSaveRequest,
+    AUTOTUNE_CMD, AUTOTUNE_STATUS, AXIS_CMD, AXIS_REPORT, BREADCRUMB, CALIBRATION_SAVE,
+    CALIBRATION_SAVED, CONTROLLER_TICK, DRIVER_FAULT, EVENT_CHANNEL, HEARTBEAT_RECEIVED,
+    HEATER_CMD, HEATER_OUTPUT, INPUT_CHANNEL, LID_OPEN, MOTOR_CMD, MOTOR_STALL, OPERATION_CANCEL,
+    ORIENT_CMD, ORIENT_DONE, PROFILE_SAVE, QUIET_MODE, RECOVERY_NOTICE, SCHEDULER_STATE,
+    SCHEDULER_STATE_REQUEST, SCREEN_UPDATE, SENSOR_RAW, SOFT_RESET_REQUEST, STALLGUARD_READING,
+    TEMP_READING, ULTRASONIC_CMD,
 };
 use crate::controller::Controller;
-use crate::display::Renderer;
+use crate::display::{render_state, RenderPass, RenderRequest, RenderThrottle, Renderer};
 use crate::tasks::display_tx::SCREEN_BUFFER;
 use crate::tasks::tick::TICK_SIGNAL;
 
+/// Time for the motor and heater tasks to apply the stop before a soft reset
+const SOFT_RESET_DELAY_MS: u64 = 200;
+
+/// Spin-off safety limits
+pub struct SpinOffLimits {
+    /// RPM ceiling applied to profile spin-off speeds
+    pub max_rpm: Option<u16>,
+    /// RPM ceiling applied to spin-off speeds in quiet mode
+    pub quiet_rpm: u16,
+    /// StallGuard spread that faults as an unbalanced load
+    pub imbalance_threshold: Option<u16>,
+}
+
+/// Motor and supply protection settings
+pub struct ProtectionSettings {
+    /// Reverse briefly on a stall before faulting
+    pub stall_reverse_recovery: bool,
+    /// Delay between motor and heater start-up (ms, 0 = off)
+    pub startup_stagger_ms: u16,
+    /// Flash held no valid config and the embedded fallback is disabled
+    pub config_missing: bool,
+    /// The basket driver couldn't be set up, so no motor task is running
+    pub motor_setup_failed: bool,
+    /// Send motor and heater commands only when they change
+    pub commands_on_change: bool,
+    /// Response to a heater temperature sensor fault
+    pub sensor_fault_policy: SensorFaultPolicy,
+    /// Heater thermal runaway detection (None = off)
+    pub thermal_runaway: Option<ThermalRunawayConfig>,
+    /// Spin at the end of a program to cancel its net rotation (None = off)
+    pub balance_rotation: Option<BalanceConfig>,
+}
+
+/// Where the basket rests when it isn't working, and where it starts
+pub struct ParkSettings {
+    /// Position after a program on automated machines (None = stay put)
+    /// Only logged until X/Z position control exists.
+    pub position: Option<ParkPosition>,
+    /// Basket orientation between steps on manual machines (degrees)
+    pub angle_deg: Option<u16>,
+    /// Prompt for the first jar on manual machines
+    pub prompt_first_jar: bool,
+}
+
+/// Axis homing at boot
+pub struct HomingSettings {
+    /// Order in which the axes home
+    pub order: HomingOrder,
+    /// How each axis homes, by `Axis as usize`
+    pub types: [HomingType; 2],
+    /// Endstop homing of each axis, by `Axis as usize` (None = no axis task)
+    pub axes: [Option<(HomingConfig, Endstop)>; 2],
+}
+
+/// Settings the controller task applies at start-up
+pub struct ControllerSettings {
+    /// Behavior when stopping between steps
+    pub stop_behavior: StopBehavior,
+    /// Display and input settings
+    pub ui: UiConfig,
+    /// Program to start automatically once idle
+    pub autostart_program: Option<HString<MAX_LABEL_LEN>>,
+    /// Abort a paused program after this many seconds (0 = never)
+    pub max_pause_s: u16,
+    /// Heat the next jar while the basket is carried over
+    pub prewarm_next_jar: bool,
+    /// Display link timing
+    pub link: LinkConfig,
+    /// Spin-off speed limits
+    pub spinoff_limits: SpinOffLimits,
+    /// Where the basket rests
+    pub park: ParkSettings,
+    /// Axis homing at boot
+    pub homing: HomingSettings,
+    /// Motor and supply protection
+    pub protection: ProtectionSettings,
+}
+
 /// Controller task - main coordination loop
 #[embassy_executor::task]
 pub async fn controller_task(
@@ -27,28 +120,113 @@ pub async fn controller_task(
     programs: &'static [ProgramConfig],
     profiles: &'static [ProfileConfig],
     jars: &'static [JarConfig],
+    heaters: &'static [HeaterConfig],
+    calibration: CalibrationData,
+    settings: ControllerSettings,
 ) {
+    let ControllerSettings {
+        stop_behavior,
+        ui,
+        autostart_program,
+        max_pause_s,
+        prewarm_next_jar,
+        link,
+        spinoff_limits,
+        park,
+        homing,
+        protection,
+    } = settings;
     info!("Controller task started");
 
     // Initialize controller
     let mut controller = Controller::new(capabilities);
     controller.load_config(programs, profiles, jars);
+    controller.load_heaters(heaters);
+    controller.load_calibration(&calibration);
+    controller.set_stop_behavior(stop_behavior);
+    controller.set_max_pause(max_pause_s);
+    controller.set_prewarm(prewarm_next_jar);
+    controller.set_complete_auto_return(ui.complete_auto_return_s);
+    controller.set_auto_advance(ui.auto_advance_s);
+    controller.set_diagnostics_menu(ui.diagnostics_menu);
+    controller.set_lock_during_motion(ui.lock_during_motion);
+    controller.set_keymap(&ui.keymap);
+    controller.set_max_spinoff_rpm(spinoff_limits.max_rpm);
+    controller.set_quiet_spinoff_rpm(spinoff_limits.quiet_rpm);
+    controller.set_imbalance_threshold(spinoff_limits.imbalance_threshold);
+    controller.set_link_config(&link);
+    controller.set_park_position(park.position);
+    controller.set_park_angle(park.angle_deg);
+    controller.set_prompt_first_jar(park.prompt_first_jar);
+    controller.set_homing_order(homing.order);
+    controller.set_homing_types(
+        homing.types[Axis::Z as usize],
+        homing.types[Axis::X as usize],
+    );
+    for (axis, setup) in [Axis::Z, Axis::X].into_iter().zip(homing.axes) {
+        if let Some((config, endstop)) = setup {
+            controller.set_axis_homing(axis, config, endstop);
+        }
+    }
+    controller.set_stall_reverse_recovery(protection.stall_reverse_recovery);
+    controller.set_startup_stagger(protection.startup_stagger_ms);
+    controller.set_commands_on_change(protection.commands_on_change);
+    controller.set_sensor_fault_policy(protection.sensor_fault_policy);
+    controller.set_thermal_runaway(protection.thermal_runaway);
+    controller.set_balance_rotation(protection.balance_rotation);
+    if let Some(name) = autostart_program {
+        if controller.set_autostart_program(name.as_str()) {
+            info!("Autostart program: {}", name.as_str());
+        } else {
+            warn!("Autostart program '{}' not found", name.as_str());
+        }
+    }
 
     // Initialize renderer for building screens
     let mut renderer = Renderer::new();
+    renderer.set_status_header(ui.status_header);
+    renderer.set_overall_progress(ui.show_overall_progress);
+    let mut throttle = RenderThrottle::new(ui.min_render_interval_ms);
 
     // Render boot screen
     renderer.render_boot();
     update_screen_buffer(&renderer).await;
 
-    // Complete boot sequence
-    controller.boot_complete();
-    info!("Boot complete, entering idle state");
+    if let Some(notice) = RECOVERY_NOTICE.try_take() {
+        warn!(
+            "Recovered from watchdog reset, last state: {:?}",
+            notice.last
+        );
+        controller.set_recovery_notice(notice);
+    }
+
+    if protection.config_missing {
+        error!("No valid configuration in flash and fallback disabled");
+        controller.fault_config_error();
+    } else if protection.motor_setup_failed {
+        error!("Basket driver setup failed, motor disabled");
+        controller.fault_config_error();
+    }
+
+    // Home the axes, or complete boot straight away (may autostart a program)
+    if controller.start_homing() {
+        info!("Homing axes");
+        OPERATION_CANCEL.reset();
+        send_axis_commands(&mut controller);
+    } else if let Some(event) = controller.boot_complete() {
+        info!("Autostarted program, event: {:?}", event);
+        let _ = EVENT_CHANNEL.try_send(event);
+        send_commands(&mut controller);
+    } else {
+        info!("Boot complete, entering idle state");
+    }
 
     // Render initial menu
     render_current_state(&controller, &mut renderer).await;
 
     loop {
+        let mut pass = RenderPass::new();
+
         // Wait for either: input event, tick, or safety sensor update
         match select3(
             INPUT_CHANNEL.receive(),
@@ -60,50 +238,15 @@ pub async fn controller_task(
         )
         .await
         {
-            Either3::First(input) => {
-                // Process input event
-                debug!("Input: {:?}", input);
-                if let Some(event) = controller.process_input(input) {
-                    debug!("Event: {:?}", event);
-                    // Log event for debugging
-                    let _ = EVENT_CHANNEL.try_send(event);
-
-                    // Handle autotune start/cancel
-                    use crate::controller::AutotunePhase;
-                    use isochron_core::state::Event;
-                    match event {
-                        Event::StartAutotune => {
-                            // Only send command when actually starting (Running phase)
-                            if controller.autotune_phase() == AutotunePhase::Running {
-                                info!("Starting autotune");
-                                AUTOTUNE_CMD.signal(AutotuneCommand::Start {
-                                    target_x10: controller.autotune_target_x10(),
-                                });
-                            }
-                        }
-                        Event::CancelAutotune => {
-                            // Only send cancel if autotune was actually running
-                            if controller.autotune_phase() == AutotunePhase::Failed {
-                                info!("Canceling autotune");
-                                AUTOTUNE_CMD.signal(AutotuneCommand::Cancel);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-
-                // Update motor/heater commands
-                MOTOR_CMD.signal(controller.motor_command());
-                HEATER_CMD.signal(controller.heater_command());
-
-                // Re-render display
-                render_current_state(&controller, &mut renderer).await;
-            }
+            Either3::First(input) => handle_input(&mut controller, input, &mut pass),
 
             Either3::Second(now_ms) => {
-                // Check for temperature updates from heater task
-                if let Some(temp) = TEMP_READING.try_take() {
-                    controller.update_temperature(temp);
+                // Check for temperature updates from heater tasks
+                poll_heaters(&mut controller);
+
+                // Check for lid interlock updates from lid task
+                if let Some(open) = LID_OPEN.try_take() {
+                    controller.update_lid_open(open);
                 }
 
                 // Check for motor stall updates from TMC task
@@ -111,6 +254,16 @@ pub async fn controller_task(
                     controller.update_motor_stall(stalled);
                 }
 
+                // Check for driver fault updates from TMC task
+                if let Some(fault) = DRIVER_FAULT.try_take() {
+                    controller.update_driver_fault(fault);
+                }
+
+                // Check for StallGuard readings from TMC task
+                if let Some(sg_result) = STALLGUARD_READING.try_take() {
+                    controller.update_stallguard(sg_result);
+                }
+
                 // Check for heartbeat from display
                 if HEARTBEAT_RECEIVED.signaled() {
                     HEARTBEAT_RECEIVED.reset();
@@ -118,30 +271,107 @@ pub async fn controller_task(
                     trace!("Heartbeat received, safety updated");
                 }
 
+                // Endstop reports drive homing at boot
+                while let Ok(report) = AXIS_REPORT.try_receive() {
+                    let event = controller.handle_axis_report(
+                        report.axis,
+                        report.endstop_high,
+                        report.moved_um,
+                    );
+                    if let Some(event) = event {
+                        info!("Homing: {:?}", event);
+                        let _ = EVENT_CHANNEL.try_send(event);
+                        send_commands(&mut controller);
+                        pass.request(RenderRequest::StateChange);
+                    }
+                }
+                if let Some(event) = controller.check_homing_cancel(&OPERATION_CANCEL) {
+                    warn!("Homing cancelled");
+                    let _ = EVENT_CHANNEL.try_send(event);
+                    pass.request(RenderRequest::StateChange);
+                }
+
                 // Periodic tick - update scheduler and safety
                 if let Some(event) = controller.tick(now_ms) {
                     debug!("Tick event: {:?}", event);
                     let _ = EVENT_CHANNEL.try_send(event);
 
-                    // Update motor/heater commands
-                    MOTOR_CMD.signal(controller.motor_command());
-                    HEATER_CMD.signal(controller.heater_command());
+                    if event == Event::Abort {
+                        OPERATION_CANCEL.cancel();
+                    }
 
-                    // Re-render display for state changes
-                    render_current_state(&controller, &mut renderer).await;
-                }
+                    // No X/Z position control yet: the park move is dropped
+                    if let Some(park) = controller.take_park_move() {
+                        warn!(
+                            "Park at X={} Z={} skipped: no X/Z position control",
+                            park.x_pos, park.z_pos
+                        );
+                    }
 
-                // Periodic display refresh for running state (progress bar, time)
-                if controller.state().motor_allowed() {
-                    render_current_state(&controller, &mut renderer).await;
+                    // Update motor/heater commands
+                    send_commands(&mut controller);
+
+                    pass.request(RenderRequest::StateChange);
+                } else if let Some(angle) = controller.take_orient_move() {
+                    // Step done on a manual machine: turn the basket for loading
+                    info!("Turning basket to {} degrees", angle);
+                    ORIENT_CMD.signal(angle);
+                } else if controller.take_command_update() {
+                    // A new segment, stall recovery or the start-up stagger changed a command
+                    send_commands(&mut controller);
+                } else if controller.state().motor_allowed()
+                    || controller.auto_advance_remaining_s().is_some()
+                {
+                    // Periodic display refresh for running state (progress bar,
+                    // time) and the auto-advance countdown
+                    pass.request(RenderRequest::Progress);
                 }
+
+                send_axis_commands(&mut controller);
+
+                // Still ticking: the watchdog task keeps feeding
+                CONTROLLER_TICK.signal(());
             }
 
             Either3::Third(_) => {
+                // Remote soft reset, refused unless idle
+                if SOFT_RESET_REQUEST.signaled() {
+                    SOFT_RESET_REQUEST.reset();
+                    if controller.soft_reset_allowed() {
+                        info!("Soft reset: stopping outputs and restarting");
+                        MOTOR_CMD.signal(MotorCommand::stopped());
+                        for (heater, cmd) in HEATER_CMD.iter().enumerate() {
+                            cmd.signal(HeaterCommand::off().for_heater(heater as u8));
+                        }
+                        Timer::after_millis(SOFT_RESET_DELAY_MS).await;
+                        cortex_m::peripheral::SCB::sys_reset();
+                    } else {
+                        warn!("Soft reset refused in state {:?}", controller.state());
+                    }
+                }
+
+                // Scheduler state for the host, on request or on change
+                if SCHEDULER_STATE_REQUEST.signaled() {
+                    SCHEDULER_STATE_REQUEST.reset();
+                    SCHEDULER_STATE.signal(controller.scheduler_state());
+                } else if let Some(state) = controller.take_scheduler_state_change() {
+                    SCHEDULER_STATE.signal(state);
+                }
+
                 // Periodic safety signal polling (every 100ms)
-                // Check for temperature updates from heater task
-                if let Some(temp) = TEMP_READING.try_take() {
-                    controller.update_temperature(temp);
+                // Check for temperature updates from heater tasks
+                poll_heaters(&mut controller);
+
+                // Raw thermistor values for the diagnostics screen
+                if let Some(raw) = SENSOR_RAW.try_take() {
+                    if controller.update_sensor_raw(raw) {
+                        pass.request(RenderRequest::Progress);
+                    }
+                }
+
+                // Check for lid interlock updates from lid task
+                if let Some(open) = LID_OPEN.try_take() {
+                    controller.update_lid_open(open);
                 }
 
                 // Check for motor stall updates from TMC task
@@ -149,6 +379,16 @@ pub async fn controller_task(
                     controller.update_motor_stall(stalled);
                 }
 
+                // Check for driver fault updates from TMC task
+                if let Some(fault) = DRIVER_FAULT.try_take() {
+                    controller.update_driver_fault(fault);
+                }
+
+                // Check for StallGuard readings from TMC task
+                if let Some(sg_result) = STALLGUARD_READING.try_take() {
+                    controller.update_stallguard(sg_result);
+                }
+
                 // Check for heartbeat from display
                 if HEARTBEAT_RECEIVED.signaled() {
                     HEARTBEAT_RECEIVED.reset();
@@ -186,7 +426,7 @@ pub async fn controller_task(
                             controller.set_autotune_complete(kp_x100, ki_x100, kd_x100);
                             // Request calibration save to flash
                             CALIBRATION_SAVE.signal(CalibrationSaveRequest {
-                                heater_index: 0,
+                                heater_index: controller.autotune_heater_index(),
                                 kp_x100,
                                 ki_x100,
                                 kd_x100,
@@ -207,159 +447,185 @@ pub async fn controller_task(
                             controller.set_autotune_failed(failure_reason);
                         }
                     }
-                    // Re-render display for autotune status changes
-                    render_current_state(&controller, &mut renderer).await;
+                    pass.request(RenderRequest::StateChange);
                 }
-            }
-        }
-    }
-}
 
-/// Render the current state to the screen buffer
-async fn render_current_state(controller: &Controller, renderer: &mut Renderer) {
-    match controller.state() {
-        State::Boot => {
-            renderer.render_boot();
-        }
-        State::Idle => {
-            // Collect program labels plus autotune option
-            let mut labels: heapless::Vec<&str, 8> = controller.program_labels().take(7).collect();
-            let _ = labels.push("Autotune Heater");
-
-            // Determine selected index for display
-            let selected = if controller.is_autotune_selected() {
-                labels.len() - 1 // Last item (autotune)
-            } else {
-                controller.selected_program() as usize
-            };
-            renderer.render_menu(&labels, selected);
-        }
-        State::ProgramSelected => {
-            if let Some(program) = controller.get_program(controller.selected_program()) {
-                // Build step descriptions
-                let mut steps: heapless::Vec<&str, 8> = heapless::Vec::new();
-                for step in program.steps.iter().take(5) {
-                    let _ = steps.push(step.jar.as_str());
+                // Basket turned to its park angle: prompt for the next jar
+                match ORIENT_DONE.try_take() {
+                    Some(true) => {
+                        if let Some(event) = controller.orientation_complete() {
+                            let _ = EVENT_CHANNEL.try_send(event);
+                            if let Some(cmd) = controller.take_motor_command() {
+                                MOTOR_CMD.signal(cmd);
+                            }
+                            pass.request(RenderRequest::StateChange);
+                        }
+                    }
+                    // Stopped short by a motor command: carry on next tick
+                    Some(false) if controller.orientation_interrupted() => {
+                        debug!("Basket turn interrupted, resuming");
+                    }
+                    Some(false) | None => {}
                 }
 
-                // Calculate total time (simplified)
-                let total_time = controller.step_total_s();
-
-                renderer.render_program_detail(program.label.as_str(), &steps, total_time);
+                // Check for calibration save confirmation from flash
+                if let Some(ok) = CALIBRATION_SAVED.try_take() {
+                    controller.set_calibration_saved(ok);
+                    pass.request(RenderRequest::StateChange);
+                }
             }
         }
-        State::Running => {
-            if let (Some(profile), Some(jar)) =
-                (controller.current_profile(), controller.current_jar())
-            {
-                let temp = controller.current_temp_c();
-                let target = profile.temperature_c;
-
-                renderer.render_running(
-                    controller
-                        .get_program(controller.selected_program())
-                        .map(|p| p.label.as_str())
-                        .unwrap_or(""),
-                    controller.current_step_num(),
-                    controller.total_steps(),
-                    jar.name.as_str(),
-                    profile.label.as_str(),
-                    controller.motor_command().rpm,
-                    controller.step_elapsed_s(),
-                    controller.step_total_s(),
-                    temp,
-                    target,
-                );
-            }
+
+        // Apply inputs that queued up meanwhile, so the render below shows
+        // their effect rather than an intermediate state
+        while let Ok(input) = INPUT_CHANNEL.try_receive() {
+            handle_input(&mut controller, input, &mut pass);
         }
-        State::Paused => {
-            let program_name = controller
-                .get_program(controller.selected_program())
-                .map(|p| p.label.as_str())
-                .unwrap_or("");
-            renderer.render_paused(
-                program_name,
-                controller.current_step_num(),
-                controller.total_steps(),
-            );
+
+        // One render of the final state (or a coalesced progress render)
+        if pass.finish(&mut throttle, uptime_ms()) {
+            render_current_state(&controller, &mut renderer).await;
         }
-        State::SpinOff => {
-            // Show spin-off in progress (similar to running but different message)
-            if let Some(jar) = controller.current_jar() {
-                renderer.render_awaiting_jar(jar.name.as_str(), "Spin-off in progress");
-            }
+
+        // Leave a breadcrumb for the recovery screen after a watchdog reset
+        if let Some(crumb) = controller.take_breadcrumb() {
+            BREADCRUMB.signal(crumb);
         }
-        State::AwaitingJar => {
-            if let Some(jar) = controller.current_jar() {
-                renderer.render_awaiting_jar(jar.name.as_str(), "Move basket to:");
+    }
+}
+
+/// Apply one input event from the display
+fn handle_input(controller: &mut Controller, input: InputEvent, pass: &mut RenderPass) {
+    // Process input event
+    debug!("Input: {:?}", input);
+    if let Some(event) = controller.process_input(input) {
+        debug!("Event: {:?}", event);
+        // Log event for debugging
+        let _ = EVENT_CHANNEL.try_send(event);
+
+        // Handle autotune start/cancel and abort
+        use crate::controller::AutotunePhase;
+        match event {
+            Event::StartAutotune => {
+                // Only send command when actually starting (Running phase)
+                if controller.autotune_phase() == AutotunePhase::Running {
+                    info!("Starting autotune");
+                    OPERATION_CANCEL.reset();
+                    AUTOTUNE_CMD.signal(AutotuneCommand::Start {
+                        target_x10: controller.autotune_target_x10(),
+                    });
+                }
             }
-        }
-        State::AwaitingSpinOff => {
-            renderer.render_awaiting_jar("", "Lift basket for spin-off");
-        }
-        State::StepComplete => {
-            if let Some(jar) = controller.current_jar() {
-                renderer.render_step_complete(jar.name.as_str());
+            Event::CancelAutotune => {
+                // Only send cancel if autotune was actually running
+                if controller.autotune_phase() == AutotunePhase::Failed {
+                    info!("Canceling autotune");
+                    OPERATION_CANCEL.cancel();
+                }
             }
+            Event::Abort => OPERATION_CANCEL.cancel(),
+            _ => {}
         }
-        State::ProgramComplete => {
-            let program_name = controller
-                .get_program(controller.selected_program())
-                .map(|p| p.label.as_str())
-                .unwrap_or("");
-            renderer.render_complete(program_name, controller.step_elapsed_s());
+    }
+
+    if let Some(quiet) = controller.take_quiet_change() {
+        info!("Quiet mode {}", if quiet { "on" } else { "off" });
+        QUIET_MODE.signal(quiet);
+    }
+
+    // Persist a profile edit the user chose to save
+    if let Some((profile_index, profile)) = controller.take_profile_save() {
+        PROFILE_SAVE.signal(ProfileSaveRequest {
+            profile_index,
+            profile,
+        });
+    }
+
+    // Update motor/heater commands
+    send_commands(controller);
+    send_axis_commands(controller);
+
+    // Input always gets immediate feedback
+    pass.request(RenderRequest::StateChange);
+}
+
+/// Pass a temperature reading on, warning when a sensor fault suspends heating
+fn update_temperature(controller: &mut Controller, heater: u8, temp: Option<TemperatureC10>) {
+    let suspended = controller.heating_suspended_for(heater);
+    controller.update_temperature_for(heater, temp);
+    match (suspended, controller.heating_suspended_for(heater)) {
+        (false, true) => warn!(
+            "Heater {} sensor fault: heater off, continuing unheated",
+            heater
+        ),
+        (true, false) => info!(
+            "Heater {} sensor readings restored, heating resumed",
+            heater
+        ),
+        _ => {}
+    }
+}
+
+/// Heaters with a running task
+///
+/// The first heater's task runs even without heater hardware, so its
+/// sensor is still watched.
+fn heater_tasks(controller: &Controller) -> u8 {
+    controller.heater_count().clamp(1, MAX_HEATERS as u8)
+}
+
+/// Take temperature and element updates from the heater tasks
+fn poll_heaters(controller: &mut Controller) {
+    for heater in 0..heater_tasks(controller) {
+        if let Some(temp) = TEMP_READING[heater as usize].try_take() {
+            update_temperature(controller, heater, temp);
         }
-        State::Error(kind) => {
-            let error_type = match kind {
-                isochron_core::state::ErrorKind::ThermistorFault => "SENSOR FAULT",
-                isochron_core::state::ErrorKind::OverTemperature => "OVER TEMP",
-                isochron_core::state::ErrorKind::MotorStall => "MOTOR STALL",
-                isochron_core::state::ErrorKind::LinkLost => "LINK LOST",
-                isochron_core::state::ErrorKind::ConfigError => "CONFIG ERROR",
-                isochron_core::state::ErrorKind::Unknown => "UNKNOWN ERROR",
-            };
-            renderer.render_error(error_type, "Power cycle to restart");
+        if let Some(on) = HEATER_OUTPUT[heater as usize].try_take() {
+            controller.update_heater_output_for(heater, on);
         }
-        State::EditProgram => {
-            // Placeholder for edit mode
+    }
+}
+
+/// Signal the motor and heater commands to their tasks
+///
+/// Unchanged commands are skipped when configured to send only on change.
+fn send_commands(controller: &mut Controller) {
+    if let Some(cmd) = controller.take_motor_command() {
+        MOTOR_CMD.signal(cmd);
+    }
+    for heater in 0..heater_tasks(controller) {
+        if let Some(cmd) = controller.take_heater_command(heater) {
+            HEATER_CMD[heater as usize].signal(cmd);
         }
-        State::Autotuning => {
-            use crate::controller::AutotunePhase;
-            match controller.autotune_phase() {
-                AutotunePhase::Confirming => {
-                    // Show confirmation screen
-                    renderer.render_autotune_confirm(controller.autotune_target_c());
-                }
-                AutotunePhase::Running => {
-                    // Show progress screen
-                    let (peaks, ticks) = controller.autotune_progress();
-                    // Convert ticks to seconds (500ms per tick)
-                    let elapsed_s = (ticks / 2) as u32;
-                    let temp_c = controller.current_temp_c().unwrap_or(0);
-                    let target_c = controller.autotune_target_c();
-                    renderer.render_autotune_progress(peaks, elapsed_s, temp_c, target_c);
-                }
-                AutotunePhase::Complete => {
-                    // Show result screen
-                    if let Some((kp, ki, kd)) = controller.autotune_result() {
-                        renderer.render_autotune_complete(kp, ki, kd);
-                    }
-                }
-                AutotunePhase::Failed => {
-                    // Show failure screen
-                    let reason = controller
-                        .autotune_failure()
-                        .map(|r| r.as_str())
-                        .unwrap_or("Unknown error");
-                    renderer.render_autotune_failed(reason);
-                }
-            }
+    }
+    if let Some(cmd) = controller.take_ultrasonic_command() {
+        ULTRASONIC_CMD.signal(cmd);
+    }
+}
+
+/// Signal changed homing motion to the axis tasks
+fn send_axis_commands(controller: &mut Controller) {
+    for axis in [Axis::Z, Axis::X] {
+        if let Some(motion) = controller.take_homing_move(axis) {
+            AXIS_CMD[axis as usize].signal(AxisCommand::Homing(motion));
         }
     }
+    if let Some((axis, mm)) = controller.take_homing_jog() {
+        AXIS_CMD[axis as usize].signal(AxisCommand::Jog(mm));
+    }
+}
 
+/// Render the current state to the screen buffer
+async fn render_current_state(controller: &Controller, renderer: &mut Renderer) {
+    render_state(controller, renderer);
     update_screen_buffer(renderer).await;
 }
 
+/// Current time in ms for render throttling
+fn uptime_ms() -> u32 {
+    Instant::now().as_millis() as u32
+}
+
 /// Copy rendered screen to shared buffer and signal update
 async fn update_screen_buffer(renderer: &Renderer) {
     let mut buffer = SCREEN_BUFFER.lock().await;
@@ -8,6 +8,7 @@ use embassy_rp::gpio::Output;
 use embassy_rp::pwm::{Config as PwmConfig, Pwm};
 use embassy_time::{Duration, Ticker};
 
+use isochron_core::motion::RampProfile;
 use isochron_core::traits::Direction;
 use isochron_core::traits::{DcMotorDriver, MotorDriver};
 use isochron_drivers::motor::dc::{DcMotor, DcMotorConfig};
@@ -53,8 +54,8 @@ pub async fn dc_motor_task(
     // Create the motor driver
     let driver_config = DcMotorConfig {
         min_duty: config.min_duty,
-        soft_start_ms: config.soft_start_ms,
-        soft_stop_ms: config.soft_stop_ms,
+        ramp: RampProfile::from_ramp_time(config.soft_start_ms, 100),
+        stop_ramp: RampProfile::from_ramp_time(config.soft_stop_ms, 100),
         has_direction: dir_pin.is_some(),
     };
     let mut motor = DcMotor::new(driver_config);
@@ -1,25 +1,120 @@
 //! Display UART receive task
 //!
-//! Receives frames from the V0 Display and dispatches events.
+//! Receives frames from the V0 Display and dispatches events. Frames are
+//! routed by channel: a host on the telemetry channel may request the
+//! scheduler state.
+//!
+//! With a link test pattern configured, also checks the display's link
+//! test results and logs the running error rate.
 
 use defmt::*;
 use embassy_rp::uart::BufferedUartRx;
 use embedded_io_async::Read;
 
-use isochron_protocol::{DisplayCommand, FrameParser};
+use isochron_protocol::{
+    ChannelHandler, DisplayCommand, Frame, FrameError, FrameParser, LinkTestPattern,
+    CHANNEL_DISPLAY, CHANNEL_TELEMETRY, LINK_TEST_LEN,
+};
 
-use crate::channels::{HEARTBEAT_RECEIVED, INPUT_CHANNEL};
+use crate::channels::{
+    HEARTBEAT_RECEIVED, INPUT_CHANNEL, SCHEDULER_STATE_REQUEST, SOFT_RESET_REQUEST,
+};
 
 /// Buffer size for UART receive
 const RX_BUF_SIZE: usize = 64;
 
+/// Running tally of link test results
+struct LinkTestLog {
+    /// Pattern the controller sends
+    pattern: Option<LinkTestPattern>,
+    /// Results received
+    tests: u32,
+    /// Results whose checksum did not match the pattern
+    failed: u32,
+    /// Pattern bytes the display reported as wrong
+    bad_bytes: u32,
+}
+
+impl LinkTestLog {
+    fn record(&mut self, checksum: u16, errors: u8) {
+        let Some(pattern) = self.pattern else {
+            warn!("Link test result without a test running");
+            return;
+        };
+        self.tests += 1;
+        self.bad_bytes += errors as u32;
+        if checksum == pattern.checksum() && errors == 0 {
+            debug!("Link test {} passed", self.tests);
+        } else {
+            self.failed += 1;
+            warn!(
+                "Link test {} failed: {} bad bytes, checksum {:#06x}",
+                self.tests, errors, checksum
+            );
+        }
+        info!(
+            "Link test: {}/{} failed, {}/{} bytes bad",
+            self.failed,
+            self.tests,
+            self.bad_bytes,
+            self.tests * LINK_TEST_LEN as u32
+        );
+    }
+}
+
+/// Commands from the display on the display channel
+struct DisplayChannel {
+    link_test: LinkTestLog,
+}
+
+impl ChannelHandler for DisplayChannel {
+    fn channel(&self) -> u8 {
+        CHANNEL_DISPLAY
+    }
+
+    fn handle_frame(&mut self, frame: Frame) {
+        match DisplayCommand::from_frame(&frame) {
+            Ok(cmd) => handle_display_command(cmd, &mut self.link_test),
+            Err(e) => warn!("Failed to parse display command: {:?}", e),
+        }
+    }
+}
+
+/// Host requests passed through the display on the telemetry channel
+struct TelemetryChannel;
+
+impl ChannelHandler for TelemetryChannel {
+    fn channel(&self) -> u8 {
+        CHANNEL_TELEMETRY
+    }
+
+    fn handle_frame(&mut self, frame: Frame) {
+        // The host may only ask for state
+        if let Ok(DisplayCommand::RequestSchedulerState) = DisplayCommand::from_frame(&frame) {
+            trace!("Scheduler state requested");
+            SCHEDULER_STATE_REQUEST.signal(());
+        }
+    }
+}
+
 /// Display RX task - receives and parses frames from V0 Display
 #[embassy_executor::task]
-pub async fn display_rx_task(mut rx: BufferedUartRx) {
+pub async fn display_rx_task(mut rx: BufferedUartRx, test_pattern: Option<LinkTestPattern>) {
     info!("Display RX task started");
 
+    let mut display = DisplayChannel {
+        link_test: LinkTestLog {
+            pattern: test_pattern,
+            tests: 0,
+            failed: 0,
+            bad_bytes: 0,
+        },
+    };
+    let mut telemetry = TelemetryChannel;
     let mut parser = FrameParser::new();
     let mut buf = [0u8; RX_BUF_SIZE];
+    // Warn once about a display on older firmware
+    let mut legacy_reported = false;
 
     loop {
         // Read available bytes
@@ -27,22 +122,20 @@ pub async fn display_rx_task(mut rx: BufferedUartRx) {
             Ok(n) if n > 0 => {
                 trace!("RX: {} bytes", n);
 
-                // Feed bytes to parser
+                // Feed bytes to parser, dispatching frames by channel;
+                // frames on other channels are dropped
                 for &byte in &buf[..n] {
-                    match parser.feed(byte) {
-                        Ok(Some(frame)) => {
-                            // Parse the display command
-                            match DisplayCommand::from_frame(&frame) {
-                                Ok(cmd) => {
-                                    handle_display_command(cmd).await;
-                                }
-                                Err(e) => {
-                                    warn!("Failed to parse display command: {:?}", e);
-                                }
-                            }
+                    match parser.feed_routed(byte, &mut [&mut display, &mut telemetry]) {
+                        Ok(_) => {
+                            // Handled, dropped or need more bytes
                         }
-                        Ok(None) => {
-                            // Need more bytes
+                        Err(FrameError::LegacyFrame) => {
+                            if !legacy_reported {
+                                legacy_reported = true;
+                                error!(
+                                    "Display sends the legacy frame format, update its firmware"
+                                );
+                            }
                         }
                         Err(e) => {
                             warn!("Frame parse error: {:?}", e);
@@ -61,7 +154,7 @@ pub async fn display_rx_task(mut rx: BufferedUartRx) {
 }
 
 /// Handle a parsed display command
-async fn handle_display_command(cmd: DisplayCommand) {
+fn handle_display_command(cmd: DisplayCommand, link_test: &mut LinkTestLog) {
     match cmd {
         DisplayCommand::Ping => {
             trace!("PING received");
@@ -78,5 +171,20 @@ async fn handle_display_command(cmd: DisplayCommand) {
             // ACK received, could use for flow control
             trace!("ACK received");
         }
+        DisplayCommand::Nack { seq } => {
+            // Screen commands are not sequenced yet, so nothing to resend
+            trace!("NACK received for {}", seq);
+        }
+        DisplayCommand::SoftReset => {
+            info!("Soft reset requested");
+            SOFT_RESET_REQUEST.signal(());
+        }
+        DisplayCommand::LinkTestResult { checksum, errors } => {
+            link_test.record(checksum, errors);
+        }
+        DisplayCommand::RequestSchedulerState => {
+            // Only honoured on the telemetry channel
+            trace!("Scheduler state request from display ignored");
+        }
     }
 }
@@ -1,39 +1,87 @@
 //! Display UART transmit task
 //!
-//! Sends screen updates and heartbeat responses to the V0 Display.
+//! Sends screen updates and heartbeat responses to the V0 Display, and
+//! scheduler state to a host on the telemetry channel.
 
 use defmt::*;
 use embassy_rp::uart::BufferedUartTx;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
-use embassy_time::{Duration, Ticker};
+use embassy_time::{Duration, Instant, Ticker, Timer};
 use embedded_io_async::Write;
 
-use crate::channels::{HEARTBEAT_RECEIVED, SCREEN_UPDATE};
+use isochron_core::util::{retry_async, Backoff};
+use isochron_protocol::{LinkTestPattern, PicoMessage};
+
+use crate::channels::{HEARTBEAT_RECEIVED, SCHEDULER_STATE, SCREEN_UPDATE};
 use crate::display::{protocol, Screen};
 
+/// Attempts per frame before giving up on a UART write
+const WRITE_ATTEMPTS: u8 = 3;
+
+/// Backoff between failed frame writes
+const WRITE_BACKOFF: Backoff = Backoff::new(5, 20);
+
+/// Longest time between full screen redraws
+///
+/// Screen updates only carry the rows that changed, so a display that
+/// restarted would otherwise show a partial screen until every row had
+/// changed once.
+const FULL_REDRAW_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Shared screen buffer protected by mutex
 pub static SCREEN_BUFFER: Mutex<CriticalSectionRawMutex, Screen> = Mutex::new(Screen::new());
 
 /// Display TX task - sends frames to V0 Display
+///
+/// Screen updates send only the rows that changed since the last update
+/// the display received; a failed write or `FULL_REDRAW_INTERVAL` without
+/// a full redraw sends the whole screen again. Each PONG is followed by
+/// the configured heartbeat interval and the version info, so a display
+/// that reboots picks them up again on its next heartbeat. With a link
+/// test pattern configured, a test frame follows as well.
 #[embassy_executor::task]
-pub async fn display_tx_task(mut tx: BufferedUartTx) {
+pub async fn display_tx_task(
+    mut tx: BufferedUartTx,
+    heartbeat_ms: u16,
+    test_pattern: Option<LinkTestPattern>,
+) {
     info!("Display TX task started");
 
     // Ticker for checking heartbeat response
     let mut ticker = Ticker::every(Duration::from_millis(50));
 
+    // Screen the display is showing (None = unknown, redraw in full)
+    let mut shown: Option<Screen> = None;
+    let mut last_full_redraw = Instant::now();
+
     loop {
         // Check for pending heartbeat response
         if HEARTBEAT_RECEIVED.signaled() {
             HEARTBEAT_RECEIVED.reset();
             send_pong(&mut tx).await;
+            send_link_config(&mut tx, heartbeat_ms).await;
+            send_version_info(&mut tx).await;
+            if let Some(pattern) = test_pattern {
+                send_link_test(&mut tx, pattern).await;
+            }
         }
 
         // Check for screen update request
         if SCREEN_UPDATE.signaled() {
             SCREEN_UPDATE.reset();
-            send_screen_update(&mut tx).await;
+            if last_full_redraw.elapsed() >= FULL_REDRAW_INTERVAL {
+                shown = None;
+            }
+            if shown.is_none() {
+                last_full_redraw = Instant::now();
+            }
+            send_screen_update(&mut tx, &mut shown).await;
+        }
+
+        // Scheduler state for the host
+        if let Some(state) = SCHEDULER_STATE.try_take() {
+            send_scheduler_state(&mut tx, &state).await;
         }
 
         ticker.next().await;
@@ -45,7 +93,7 @@ async fn send_pong(tx: &mut BufferedUartTx) {
     if let Ok(frame) = protocol::pong_frame() {
         let mut buf = [0u8; 64];
         if let Ok(len) = frame.encode(&mut buf) {
-            if let Err(e) = tx.write_all(&buf[..len]).await {
+            if let Err(e) = write_frame(tx, &buf[..len]).await {
                 warn!("Failed to send PONG: {:?}", e);
             } else {
                 trace!("PONG sent");
@@ -54,20 +102,85 @@ async fn send_pong(tx: &mut BufferedUartTx) {
     }
 }
 
-/// Send current screen content to display
-async fn send_screen_update(tx: &mut BufferedUartTx) {
+/// Send the heartbeat interval to display
+async fn send_link_config(tx: &mut BufferedUartTx, heartbeat_ms: u16) {
+    if let Ok(frame) = protocol::link_config_frame(heartbeat_ms) {
+        let mut buf = [0u8; 64];
+        if let Ok(len) = frame.encode(&mut buf) {
+            if let Err(e) = write_frame(tx, &buf[..len]).await {
+                warn!("Failed to send link config: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Send the supported versions to display
+async fn send_version_info(tx: &mut BufferedUartTx) {
+    if let Ok(frame) = protocol::version_info_frame() {
+        let mut buf = [0u8; 64];
+        if let Ok(len) = frame.encode(&mut buf) {
+            if let Err(e) = write_frame(tx, &buf[..len]).await {
+                warn!("Failed to send version info: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Send a link test pattern to display
+async fn send_link_test(tx: &mut BufferedUartTx, pattern: LinkTestPattern) {
+    if let Ok(frame) = protocol::link_test_frame(pattern) {
+        let mut buf = [0u8; 64];
+        if let Ok(len) = frame.encode(&mut buf) {
+            if let Err(e) = write_frame(tx, &buf[..len]).await {
+                warn!("Failed to send link test: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Send scheduler state to the host
+async fn send_scheduler_state(tx: &mut BufferedUartTx, state: &PicoMessage<'_>) {
+    if let Ok(frame) = protocol::scheduler_state_frame(state) {
+        let mut buf = [0u8; 64];
+        if let Ok(len) = frame.encode(&mut buf) {
+            if let Err(e) = write_frame(tx, &buf[..len]).await {
+                warn!("Failed to send scheduler state: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Send the rows of the current screen that differ from `shown`
+///
+/// `shown` is updated to the screen sent, or cleared if a frame failed so
+/// that the next update redraws in full.
+async fn send_screen_update(tx: &mut BufferedUartTx, shown: &mut Option<Screen>) {
     // Lock screen buffer and encode frames
     let screen = SCREEN_BUFFER.lock().await;
 
-    for frame in protocol::encode_screen(&screen) {
+    let mut sent = true;
+    for frame in protocol::encode_screen_update(&screen, shown.as_ref()) {
         let mut buf = [0u8; 64];
         if let Ok(len) = frame.encode(&mut buf) {
-            if let Err(e) = tx.write_all(&buf[..len]).await {
+            if let Err(e) = write_frame(tx, &buf[..len]).await {
                 warn!("Failed to send screen frame: {:?}", e);
+                sent = false;
                 break;
             }
         }
     }
 
+    *shown = sent.then(|| screen.clone());
     trace!("Screen update sent");
 }
+
+/// Write an encoded frame, retrying transient UART errors
+async fn write_frame(tx: &mut BufferedUartTx, frame: &[u8]) -> Result<(), embassy_rp::uart::Error> {
+    retry_async(
+        WRITE_ATTEMPTS,
+        WRITE_BACKOFF,
+        async |ms| Timer::after_millis(ms as u64).await,
+        async || tx.write_all(frame).await,
+    )
+    .await
+}
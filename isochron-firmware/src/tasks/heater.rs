@@ -4,20 +4,52 @@
 //! - Bang-bang: Simple on/off control with hysteresis
 //! - PID: Time-proportioning PID control
 //!
+//! With a heating rate limit configured, either mode follows a setpoint
+//! that ramps up to the target instead of the target itself.
+//!
 //! Also implements autotune using Åström-Hägglund relay method.
+//!
+//! While autotuning, the autotune relay owns the heater pin exclusively:
+//! `HEATER_CMD` is drained but ignored, and the heater stays off after
+//! autotune ends until the controller sends a fresh command.
 
 use defmt::*;
-use embassy_rp::adc::{Adc, Async, Channel};
+use embassy_rp::adc::Channel;
 use embassy_rp::gpio::Output;
 use embassy_time::{Duration, Ticker};
 
-use isochron_core::config::HeaterControlMode;
-use isochron_drivers::heater::{Fixed32, PidCoefficients};
+use isochron_core::config::{HeaterControlMode, ThermistorModel, MAX_HEATERS};
+use isochron_core::scheduler::HeaterCommand;
+use isochron_core::traits::{HeaterOutput, SensorError, SensorRaw, TemperatureSensor};
+use isochron_core::util::TemperatureC10;
+use isochron_drivers::heater::{
+    ziegler_nichols, Fixed32, GpioHeater, OutputPin, PidCoefficients, SetpointRamp,
+};
+use isochron_drivers::sensor::{thermistor, AdcFilter};
+use isochron_hal_rp2040::adc::SharedAdc;
 
 use crate::channels::{
     AutotuneCommand, AutotuneFailure, AutotuneStatus, AUTOTUNE_CMD, AUTOTUNE_STATUS, HEATER_CMD,
-    TEMP_READING,
+    HEATER_OUTPUT, OPERATION_CANCEL, SENSOR_RAW, TEMP_READING,
 };
+use crate::controller::AUTOTUNE_HEATER_INDEX;
+
+/// GPIO output driving the heater or its enable relay
+pub struct HeaterPin(pub Output<'static>);
+
+impl OutputPin for HeaterPin {
+    fn set_high(&mut self) {
+        self.0.set_high();
+    }
+
+    fn set_low(&mut self) {
+        self.0.set_low();
+    }
+
+    fn is_set_high(&self) -> bool {
+        self.0.is_set_high()
+    }
+}
 
 /// Heater control configuration
 #[derive(Clone)]
@@ -38,6 +70,8 @@ pub struct HeaterConfig {
     pub pid_kd_x100: i16,
     /// PWM period for PID time-proportioning (in ticks)
     pub pwm_period_ticks: u8,
+    /// Maximum heating rate (°C/min, 0 = no limit)
+    pub max_heat_rate_c_per_min: u16,
 }
 
 impl Default for HeaterConfig {
@@ -52,6 +86,7 @@ impl Default for HeaterConfig {
             pid_ki_x100: 0,
             pid_kd_x100: 0,
             pwm_period_ticks: 20, // 10 seconds at 500ms loop
+            max_heat_rate_c_per_min: 0,
         }
     }
 }
@@ -116,6 +151,109 @@ fn resistance_to_temp_x10(resistance: u32) -> Option<i16> {
     None
 }
 
+/// Convert resistance to temperature with the configured model
+fn model_to_temp_x10(model: &ThermistorModel, resistance: u32) -> Option<i16> {
+    match model {
+        ThermistorModel::Table => resistance_to_temp_x10(resistance),
+        model => thermistor::resistance_to_temp_x10(model, resistance).ok(),
+    }
+}
+
+/// Convert an ADC count to a temperature, keeping the intermediate values
+fn convert_adc(
+    adc_value: u16,
+    pullup_ohms: u32,
+    adc_max: u16,
+    model: &ThermistorModel,
+) -> (Result<i16, SensorError>, SensorRaw) {
+    let resistance = adc_to_resistance(adc_value, pullup_ohms, adc_max);
+    let temp_x10 = resistance.and_then(|r| model_to_temp_x10(model, r));
+    let result = match (resistance, temp_x10) {
+        (_, Some(temp_x10)) => Ok(temp_x10),
+        (Some(_), None) => Err(SensorError::OutOfRange),
+        (None, _) if adc_value < 10 => Err(SensorError::ShortCircuit),
+        (None, _) => Err(SensorError::OpenCircuit),
+    };
+    let raw = SensorRaw {
+        adc: adc_value,
+        resistance_ohms: resistance,
+        temp_x10,
+    };
+    (result, raw)
+}
+
+/// NTC thermistor read through the RP2040 ADC
+///
+/// Each thermistor reads its own channel through the shared ADC.
+pub struct ThermistorSensor {
+    adc: &'static SharedAdc,
+    channel: Channel<'static>,
+    pullup_ohms: u32,
+    adc_max: u16,
+    /// Resistance-to-temperature model
+    model: ThermistorModel,
+    /// Spike rejection on each read
+    filter: AdcFilter,
+    /// Raw values behind the last conversion
+    last_raw: Option<SensorRaw>,
+}
+
+impl ThermistorSensor {
+    /// Create a thermistor sensor on an ADC channel
+    pub fn new(
+        adc: &'static SharedAdc,
+        channel: Channel<'static>,
+        pullup_ohms: u32,
+        adc_max: u16,
+        model: ThermistorModel,
+        filter: AdcFilter,
+    ) -> Self {
+        Self {
+            adc,
+            channel,
+            pullup_ohms,
+            adc_max,
+            model,
+            filter,
+            last_raw: None,
+        }
+    }
+}
+
+impl TemperatureSensor for ThermistorSensor {
+    fn read_celsius_x10(&mut self) -> Result<i16, SensorError> {
+        // A conversion takes 2 µs, so even a full burst isn't worth awaiting
+        let channel = &mut self.channel;
+        let filter = &self.filter;
+        let adc_value = self
+            .adc
+            .lock(|adc| {
+                let mut adc = adc.borrow_mut();
+                filter.read(|| adc.blocking_read(channel))
+            })
+            .map_err(|_| SensorError::ConversionError)?;
+        let (result, raw) = convert_adc(adc_value, self.pullup_ohms, self.adc_max, &self.model);
+        self.last_raw = Some(raw);
+        result
+    }
+
+    fn raw_reading(&self) -> Option<SensorRaw> {
+        self.last_raw
+    }
+}
+
+/// Stand-in for a sensor that couldn't be set up
+///
+/// Every read faults, so the heater stays off and the controller raises
+/// a sensor fault instead of the board failing to boot.
+pub struct MissingSensor;
+
+impl TemperatureSensor for MissingSensor {
+    fn read_celsius_x10(&mut self) -> Result<i16, SensorError> {
+        Err(SensorError::OpenCircuit)
+    }
+}
+
 /// Heater task mode
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum TaskMode {
@@ -125,6 +263,47 @@ enum TaskMode {
     Autotuning,
 }
 
+/// Heater ownership and target
+///
+/// In `Normal` mode the controller's `HEATER_CMD` sets the target. In
+/// `Autotuning` mode the relay owns the heater and commands are dropped,
+/// not deferred, so nothing stale is applied once autotune ends.
+struct ControlState {
+    mode: TaskMode,
+    /// Target temperature, None = heater off
+    target: Option<TemperatureC10>,
+}
+
+impl ControlState {
+    fn new() -> Self {
+        Self {
+            mode: TaskMode::Normal,
+            target: None,
+        }
+    }
+
+    /// Apply a controller command, returns false if it was ignored
+    fn apply_command(&mut self, cmd: HeaterCommand) -> bool {
+        if self.mode == TaskMode::Autotuning {
+            return false;
+        }
+        self.target = cmd.target;
+        true
+    }
+
+    /// Hand the heater to the autotune relay
+    fn start_autotune(&mut self) {
+        self.mode = TaskMode::Autotuning;
+        self.target = None;
+    }
+
+    /// Return to normal control with the heater off
+    fn end_autotune(&mut self) {
+        self.mode = TaskMode::Normal;
+        self.target = None;
+    }
+}
+
 /// PID internal state
 struct PidState {
     /// PID coefficients
@@ -369,10 +548,6 @@ impl AutotuneState {
             low_peaks.iter().map(|p| p.0 as i32).sum::<i32>() / low_peaks.len() as i32;
         let amplitude = (avg_high - avg_low) / 2;
 
-        if amplitude < 5 {
-            return None;
-        }
-
         // Calculate average period
         let mut period_sum: u32 = 0;
         let mut period_count: u32 = 0;
@@ -391,54 +566,56 @@ impl AutotuneState {
         }
 
         let tu = period_sum / period_count;
-        if tu < 4 {
-            return None;
-        }
-
-        // Ku = 4 * d / (π * a), d = 255, using π ≈ 314/100
-        let ku_x100: i32 = (4 * 255 * 10000) / (314 * amplitude);
 
-        // Ziegler-Nichols PID:
-        // Kp = 0.6 * Ku
-        // Ki = 1.2 * Ku / Tu
-        // Kd = 0.075 * Ku * Tu
-        let kp_x100 = (60 * ku_x100) / 100;
-        let ki_x100 = (120 * ku_x100) / (100 * tu as i32);
-        let kd_x100 = (75 * ku_x100 * tu as i32) / 10000;
+        // Rejects tiny oscillations and gains that cannot be stored
+        let Some(gains) = ziegler_nichols(255, amplitude, tu) else {
+            warn!("Autotune rejected: amplitude={}, Tu={}", amplitude, tu);
+            return None;
+        };
 
         info!(
             "Autotune complete: Ku={}, Tu={}, Kp={}, Ki={}, Kd={}",
-            ku_x100, tu, kp_x100, ki_x100, kd_x100
+            gains.ku_x100, tu, gains.kp_x100, gains.ki_x100, gains.kd_x100
         );
 
-        Some((
-            kp_x100.clamp(0, i16::MAX as i32) as i16,
-            ki_x100.clamp(0, i16::MAX as i32) as i16,
-            kd_x100.clamp(0, i16::MAX as i32) as i16,
-        ))
+        Some((gains.kp_x100, gains.ki_x100, gains.kd_x100))
     }
 }
 
+/// Control loop period (ms)
+const TICK_MS: u32 = 500;
+
 /// Heater control task
 ///
-/// Reads thermistor via ADC and controls heater GPIO with either
-/// bang-bang or PID control logic.
-#[embassy_executor::task]
+/// Reads the configured temperature sensor (ADC thermistor or I2C) and
+/// controls heater GPIO with either bang-bang or PID control logic. A
+/// configured enable pin is released whenever the heater is disabled or a
+/// fault occurs.
+///
+/// One task runs per heater, talking to the controller on the channels
+/// at `index`. Only the autotune heater takes autotune commands and
+/// publishes raw readings for diagnostics.
+#[embassy_executor::task(pool_size = MAX_HEATERS)]
 pub async fn heater_task(
-    mut adc: Adc<'static, Async>,
-    mut therm_channel: Channel<'static>,
-    mut heater_pin: Output<'static>,
+    index: u8,
+    sensor: &'static mut dyn TemperatureSensor,
+    mut heater: GpioHeater<HeaterPin>,
     config: HeaterConfig,
 ) {
-    info!("Heater task started (mode: {:?})", config.control_mode);
+    info!(
+        "Heater {} task started (mode: {:?})",
+        index, config.control_mode
+    );
+    let autotune_heater = index == AUTOTUNE_HEATER_INDEX;
+    let heater_cmd = &HEATER_CMD[index as usize];
+    let temp_reading = &TEMP_READING[index as usize];
+    let heater_output = &HEATER_OUTPUT[index as usize];
 
     // Start with heater off
-    heater_pin.set_low();
+    heater.shutdown();
 
     // State
-    let mut target_temp_c: Option<i16> = None;
-    let mut heater_on = false;
-    let mut mode = TaskMode::Normal;
+    let mut control = ControlState::new();
 
     // PID state (initialized even for bang-bang, used if autotune completes)
     let mut pid_state = PidState::new(config.pid_kp_x100, config.pid_ki_x100, config.pid_kd_x100);
@@ -448,185 +625,160 @@ pub async fn heater_task(
     let mut autotune_progress_tick: u32 = 0;
 
     // Control loop ticker (update every 500ms)
-    let mut ticker = Ticker::every(Duration::from_millis(500));
+    let mut ticker = Ticker::every(Duration::from_millis(TICK_MS as u64));
+
+    // Heating rate limit ahead of bang-bang/PID
+    let mut ramp = SetpointRamp::new(config.max_heat_rate_c_per_min);
 
     loop {
-        // Check for autotune command (non-blocking)
-        if let Some(cmd) = AUTOTUNE_CMD.try_take() {
+        // Check for autotune command (non-blocking), left for the
+        // autotune heater's task by the others
+        if let Some(cmd) = autotune_heater.then(|| AUTOTUNE_CMD.try_take()).flatten() {
             match cmd {
                 AutotuneCommand::Start { target_x10 } => {
                     info!("Starting autotune at target {}°C", target_x10 / 10);
-                    mode = TaskMode::Autotuning;
+                    control.start_autotune();
+                    pid_state.reset();
+                    ramp.reset();
                     autotune_state = Some(AutotuneState::new(
                         target_x10,
                         config.max_temp_c as i16 * 10,
                     ));
                     autotune_progress_tick = 0;
-                    heater_pin.set_high();
-                    heater_on = true;
+                    heater.set_on(true);
                     AUTOTUNE_STATUS.signal(AutotuneStatus::Started);
                 }
-                AutotuneCommand::Cancel => {
-                    if mode == TaskMode::Autotuning {
-                        info!("Autotune cancelled");
-                        mode = TaskMode::Normal;
-                        autotune_state = None;
-                        heater_pin.set_low();
-                        heater_on = false;
-                        AUTOTUNE_STATUS.signal(AutotuneStatus::Failed(AutotuneFailure::Cancelled));
-                    }
-                }
             }
         }
 
-        // Check for heater command (only in normal mode)
-        if mode == TaskMode::Normal {
-            if let Some(cmd) = HEATER_CMD.try_take() {
-                target_temp_c = cmd.target_temp_c;
-                if target_temp_c.is_none() {
-                    heater_pin.set_low();
-                    heater_on = false;
-                    pid_state.reset();
-                    debug!("Heater disabled");
-                } else {
-                    debug!("Heater target: {}°C", cmd.target_temp_c.unwrap());
-                }
+        // Stop autotune between relay steps if the controller cancelled it
+        if control.mode == TaskMode::Autotuning && OPERATION_CANCEL.is_cancelled() {
+            info!("Autotune cancelled");
+            control.end_autotune();
+            autotune_state = None;
+            heater.shutdown();
+            AUTOTUNE_STATUS.signal(AutotuneStatus::Failed(AutotuneFailure::Cancelled));
+        }
+
+        // Check for heater command (autotune owns the heater while running)
+        if let Some(cmd) = heater_cmd.try_take() {
+            if !control.apply_command(cmd) {
+                debug!("Heater command ignored during autotune");
+            } else if let Some(target) = control.target {
+                debug!("Heater target: {}°C", target);
+            } else {
+                heater.shutdown();
+                pid_state.reset();
+                ramp.reset();
+                debug!("Heater disabled");
             }
         }
 
-        // Read temperature
-        match adc.read(&mut therm_channel).await {
-            Ok(adc_value) => {
-                if let Some(resistance) =
-                    adc_to_resistance(adc_value, config.pullup_ohms, config.adc_max)
-                {
-                    if let Some(temp_x10) = resistance_to_temp_x10(resistance) {
-                        let temp_c = temp_x10 / 10;
-                        trace!("Temperature: {}.{}°C", temp_c, (temp_x10 % 10).abs());
-
-                        // Signal temperature to controller
-                        TEMP_READING.signal(Some(temp_x10));
-
-                        match mode {
-                            TaskMode::Normal => {
-                                if let Some(target) = target_temp_c {
-                                    // Safety check
-                                    if temp_c >= config.max_temp_c {
-                                        if heater_on {
-                                            heater_pin.set_low();
-                                            heater_on = false;
-                                            warn!("Max temperature reached, heater off");
-                                        }
-                                    } else {
-                                        // Apply control based on mode
-                                        let should_be_on = match config.control_mode {
-                                            HeaterControlMode::BangBang => apply_bang_bang(
-                                                temp_c,
-                                                target,
-                                                config.hysteresis_c,
-                                                heater_on,
-                                            ),
-                                            HeaterControlMode::Pid => {
-                                                let target_x10 = target * 10;
-                                                let duty =
-                                                    pid_state.calculate(target_x10, temp_x10);
-                                                pid_state.apply_pwm(duty, config.pwm_period_ticks)
-                                            }
-                                        };
-
-                                        if should_be_on != heater_on {
-                                            if should_be_on {
-                                                heater_pin.set_high();
-                                            } else {
-                                                heater_pin.set_low();
-                                            }
-                                            heater_on = should_be_on;
-                                        }
+        // Read temperature, publishing the raw values for diagnostics
+        let reading = sensor.read_celsius_x10();
+        if let Some(raw) = sensor.raw_reading().filter(|_| autotune_heater) {
+            SENSOR_RAW.signal(raw);
+        }
+        match reading {
+            Ok(temp_x10) => {
+                let temp = TemperatureC10::from_x10(temp_x10);
+                let temp_c = temp.to_whole();
+                trace!("Temperature: {}°C", temp);
+
+                // Signal temperature to controller
+                temp_reading.signal(Some(temp));
+
+                match control.mode {
+                    TaskMode::Normal => {
+                        if let Some(target) = control.target {
+                            // Safety check
+                            if temp_c >= config.max_temp_c {
+                                if heater.is_on() {
+                                    warn!("Max temperature reached, heater off");
+                                }
+                                heater.shutdown();
+                            } else {
+                                let setpoint = TemperatureC10::from_x10(ramp.update(
+                                    target.as_x10(),
+                                    temp.as_x10(),
+                                    TICK_MS,
+                                ));
+
+                                // Apply control based on mode
+                                let should_be_on = match config.control_mode {
+                                    HeaterControlMode::BangBang => apply_bang_bang(
+                                        temp_c,
+                                        setpoint.to_whole(),
+                                        config.hysteresis_c,
+                                        heater.is_on(),
+                                    ),
+                                    HeaterControlMode::Pid => {
+                                        let duty =
+                                            pid_state.calculate(setpoint.as_x10(), temp.as_x10());
+                                        pid_state.apply_pwm(duty, config.pwm_period_ticks)
                                     }
+                                };
+
+                                if should_be_on != heater.is_on() {
+                                    heater.set_on(should_be_on);
                                 }
                             }
-                            TaskMode::Autotuning => {
-                                if let Some(ref mut state) = autotune_state {
-                                    let (should_be_on, result) = state.update(temp_x10);
-
-                                    // Update heater
-                                    if should_be_on != heater_on {
-                                        if should_be_on {
-                                            heater_pin.set_high();
-                                        } else {
-                                            heater_pin.set_low();
-                                        }
-                                        heater_on = should_be_on;
-                                    }
+                        }
+                    }
+                    TaskMode::Autotuning => {
+                        if let Some(ref mut state) = autotune_state {
+                            let (should_be_on, result) = state.update(temp_x10);
 
-                                    // Send progress every 10 ticks
-                                    autotune_progress_tick += 1;
-                                    if autotune_progress_tick >= 10 {
-                                        autotune_progress_tick = 0;
-                                        AUTOTUNE_STATUS.signal(AutotuneStatus::Progress {
-                                            peaks: state.peaks.len() as u8,
-                                            ticks: state.tick_count,
+                            // Update heater
+                            if should_be_on != heater.is_on() {
+                                heater.set_on(should_be_on);
+                            }
+
+                            // Send progress every 10 ticks
+                            autotune_progress_tick += 1;
+                            if autotune_progress_tick >= 10 {
+                                autotune_progress_tick = 0;
+                                AUTOTUNE_STATUS.signal(AutotuneStatus::Progress {
+                                    peaks: state.peaks.len() as u8,
+                                    ticks: state.tick_count,
+                                });
+                            }
+
+                            // Handle completion
+                            if let Some(result) = result {
+                                match result {
+                                    Ok((kp, ki, kd)) => {
+                                        info!("Autotune complete");
+                                        // Update PID state with new coefficients
+                                        pid_state.update_coefficients(kp, ki, kd);
+                                        AUTOTUNE_STATUS.signal(AutotuneStatus::Complete {
+                                            kp_x100: kp,
+                                            ki_x100: ki,
+                                            kd_x100: kd,
                                         });
                                     }
-
-                                    // Handle completion
-                                    if let Some(result) = result {
-                                        match result {
-                                            Ok((kp, ki, kd)) => {
-                                                info!("Autotune complete");
-                                                // Update PID state with new coefficients
-                                                pid_state.update_coefficients(kp, ki, kd);
-                                                AUTOTUNE_STATUS.signal(AutotuneStatus::Complete {
-                                                    kp_x100: kp,
-                                                    ki_x100: ki,
-                                                    kd_x100: kd,
-                                                });
-                                            }
-                                            Err(e) => {
-                                                warn!("Autotune failed: {:?}", e);
-                                                AUTOTUNE_STATUS.signal(AutotuneStatus::Failed(e));
-                                            }
-                                        }
-                                        mode = TaskMode::Normal;
-                                        autotune_state = None;
-                                        heater_pin.set_low();
-                                        heater_on = false;
+                                    Err(e) => {
+                                        warn!("Autotune failed: {:?}", e);
+                                        AUTOTUNE_STATUS.signal(AutotuneStatus::Failed(e));
                                     }
                                 }
+                                control.end_autotune();
+                                autotune_state = None;
+                                pid_state.reset();
+                                heater.shutdown();
                             }
                         }
-                    } else {
-                        warn!("Temperature out of range");
-                        TEMP_READING.signal(None);
-                        handle_sensor_fault(
-                            &mut heater_pin,
-                            &mut heater_on,
-                            &mut mode,
-                            &mut autotune_state,
-                        );
                     }
-                } else {
-                    warn!("Thermistor fault (open/short)");
-                    TEMP_READING.signal(None);
-                    handle_sensor_fault(
-                        &mut heater_pin,
-                        &mut heater_on,
-                        &mut mode,
-                        &mut autotune_state,
-                    );
                 }
             }
-            Err(_) => {
-                warn!("ADC read error");
-                TEMP_READING.signal(None);
-                handle_sensor_fault(
-                    &mut heater_pin,
-                    &mut heater_on,
-                    &mut mode,
-                    &mut autotune_state,
-                );
+            Err(e) => {
+                warn!("Temperature sensor fault: {:?}", e);
+                temp_reading.signal(None);
+                ramp.reset();
+                handle_sensor_fault(&mut heater, &mut control, &mut autotune_state);
             }
         }
+        heater_output.signal(heater.is_on());
 
         ticker.next().await;
     }
@@ -646,21 +798,130 @@ fn apply_bang_bang(temp_c: i16, target: i16, hysteresis: i16, currently_on: bool
     }
 }
 
-/// Handle sensor fault - turn off heater and abort autotune
+/// Handle sensor fault - turn off heater, release enable and abort autotune
 fn handle_sensor_fault(
-    heater_pin: &mut Output<'static>,
-    heater_on: &mut bool,
-    mode: &mut TaskMode,
+    heater: &mut GpioHeater<HeaterPin>,
+    control: &mut ControlState,
     autotune_state: &mut Option<AutotuneState>,
 ) {
-    if *heater_on {
-        heater_pin.set_low();
-        *heater_on = false;
-    }
+    heater.shutdown();
 
-    if *mode == TaskMode::Autotuning {
+    if control.mode == TaskMode::Autotuning {
         AUTOTUNE_STATUS.signal(AutotuneStatus::Failed(AutotuneFailure::SensorFault));
-        *mode = TaskMode::Normal;
+        control.end_autotune();
         *autotune_state = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_reading_matches_conversion() {
+        for adc in [2048, 3800, 3913, 4000] {
+            let (result, raw) = convert_adc(adc, 4700, 4096, &ThermistorModel::Table);
+            let resistance = adc_to_resistance(adc, 4700, 4096);
+            assert_eq!(raw.adc, adc);
+            assert_eq!(raw.resistance_ohms, resistance);
+            assert_eq!(raw.temp_x10, resistance.and_then(resistance_to_temp_x10));
+            assert_eq!(result.ok(), raw.temp_x10);
+        }
+
+        // Near 100K the thermistor reads about 25°C
+        let (result, raw) = convert_adc(3913, 4700, 4096, &ThermistorModel::Table);
+        assert_eq!(raw.resistance_ohms, Some(100_497));
+        assert!(result.is_ok_and(|t| (245..=255).contains(&t)));
+    }
+
+    #[test]
+    fn test_raw_reading_on_faults() {
+        // Shorted and open sensors keep their ADC count for diagnosis
+        let (result, raw) = convert_adc(3, 4700, 4096, &ThermistorModel::Table);
+        assert_eq!(result, Err(SensorError::ShortCircuit));
+        assert_eq!(
+            (raw.adc, raw.resistance_ohms, raw.temp_x10),
+            (3, None, None)
+        );
+
+        let (result, raw) = convert_adc(4095, 4700, 4096, &ThermistorModel::Table);
+        assert_eq!(result, Err(SensorError::OpenCircuit));
+        assert_eq!(raw.resistance_ohms, None);
+
+        // A resistance off the table, e.g. the wrong thermistor type
+        let (result, raw) = convert_adc(100, 4700, 4096, &ThermistorModel::Table);
+        assert_eq!(result, Err(SensorError::OutOfRange));
+        assert_eq!(raw.resistance_ohms, Some(117));
+        assert_eq!(raw.temp_x10, None);
+    }
+
+    #[test]
+    fn test_configured_model() {
+        let beta = ThermistorModel::Beta {
+            beta: 3950,
+            r0_ohms: 100_000,
+            t0_c: 25,
+        };
+        let (result, _) = convert_adc(3913, 4700, 4096, &beta);
+        assert!(result.is_ok_and(|t| (245..=255).contains(&t)));
+
+        // 2K is past the end of the table but on the beta curve (~150°C)
+        let (result, raw) = convert_adc(1222, 4700, 4096, &ThermistorModel::Table);
+        assert_eq!(raw.resistance_ohms, Some(1_998));
+        assert_eq!(result, Err(SensorError::OutOfRange));
+        let (result, _) = convert_adc(1222, 4700, 4096, &beta);
+        assert!(result.is_ok_and(|t| (1490..=1510).contains(&t)));
+    }
+
+    #[test]
+    fn test_command_applied_in_normal_mode() {
+        let mut control = ControlState::new();
+        assert!(control.apply_command(HeaterCommand::heating(TemperatureC10::from_whole(40))));
+        assert_eq!(control.target, Some(TemperatureC10::from_whole(40)));
+
+        assert!(control.apply_command(HeaterCommand::off()));
+        assert_eq!(control.target, None);
+    }
+
+    #[test]
+    fn test_command_ignored_during_autotune() {
+        let mut control = ControlState::new();
+        control.apply_command(HeaterCommand::heating(TemperatureC10::from_whole(40)));
+
+        // Autotune takes over and drops the previous target
+        control.start_autotune();
+        assert_eq!(control.target, None);
+
+        assert!(!control.apply_command(HeaterCommand::heating(TemperatureC10::from_whole(50))));
+        assert_eq!(control.mode, TaskMode::Autotuning);
+        assert_eq!(control.target, None);
+    }
+
+    #[test]
+    fn test_normal_control_resumes_after_autotune() {
+        let mut control = ControlState::new();
+        control.start_autotune();
+        control.apply_command(HeaterCommand::heating(TemperatureC10::from_whole(50)));
+
+        // Commands sent during autotune are not replayed afterwards
+        control.end_autotune();
+        assert_eq!(control.mode, TaskMode::Normal);
+        assert_eq!(control.target, None);
+
+        assert!(control.apply_command(HeaterCommand::heating(TemperatureC10::from_whole(45))));
+        assert_eq!(control.target, Some(TemperatureC10::from_whole(45)));
+    }
+
+    #[test]
+    fn test_watchdog_stall_turns_heater_off() {
+        let mut control = ControlState::new();
+        control.apply_command(HeaterCommand::heating(TemperatureC10::from_whole(45)));
+
+        // The watchdog task saw the controller stall; the heater task
+        // receives its command on the same signal as the controller's
+        crate::tasks::watchdog::disable_outputs();
+        let cmd = HEATER_CMD[0].try_take().unwrap();
+        assert!(control.apply_command(cmd));
+        assert_eq!(control.target, None);
+    }
+}
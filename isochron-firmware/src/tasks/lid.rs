@@ -0,0 +1,103 @@
+//! Lid interlock task
+//!
+//! Polls the lid switch and signals the controller whenever the lid opens
+//! or closes. The switch is debounced so contact bounce as the lid seats
+//! doesn't trip the interlock.
+
+use defmt::*;
+use embassy_time::{Duration, Ticker};
+use isochron_core::config::LidConfig;
+use isochron_hal_rp2040::gpio::RpInput;
+use isochron_hal_rp2040::InputPinTrait;
+
+use crate::channels::LID_OPEN;
+
+/// Switch poll interval (ms)
+const POLL_MS: u32 = 10;
+
+/// Debounced lid state, polled every `POLL_MS`
+#[derive(Debug, Clone)]
+struct LidFilter {
+    /// Polls a new level must hold before it counts
+    debounce_polls: u32,
+    /// Consecutive polls at a level other than `open`
+    counter: u32,
+    /// Lid currently reported open
+    open: bool,
+}
+
+impl LidFilter {
+    fn new(debounce_ms: u16, open: bool) -> Self {
+        Self {
+            debounce_polls: (debounce_ms as u32 / POLL_MS).max(1),
+            counter: 0,
+            open,
+        }
+    }
+
+    /// Poll the switch, returning the new lid state when it changes
+    fn update(&mut self, open: bool) -> Option<bool> {
+        if open == self.open {
+            self.counter = 0;
+            return None;
+        }
+        self.counter += 1;
+        if self.counter < self.debounce_polls {
+            return None;
+        }
+        self.counter = 0;
+        self.open = open;
+        Some(open)
+    }
+}
+
+/// Lid interlock task
+///
+/// Reports the lid state at start-up, then every debounced change.
+#[embassy_executor::task]
+pub async fn lid_task(pin: RpInput<'static>, config: LidConfig) {
+    info!("Lid interlock task started");
+
+    let is_open = |pin: &RpInput<'static>| config.pin.is_active(pin.is_high());
+    let mut filter = LidFilter::new(config.debounce_ms, is_open(&pin));
+    LID_OPEN.signal(filter.open);
+
+    let mut ticker = Ticker::every(Duration::from_millis(POLL_MS as u64));
+    loop {
+        ticker.next().await;
+
+        if let Some(open) = filter.update(is_open(&pin)) {
+            if open {
+                info!("Lid opened");
+            } else {
+                info!("Lid closed");
+            }
+            LID_OPEN.signal(open);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Poll with the lid at `open` for `ms`, returning any change
+    fn poll(filter: &mut LidFilter, open: bool, ms: u32) -> Option<bool> {
+        let mut change = None;
+        for _ in 0..ms / POLL_MS {
+            change = filter.update(open).or(change);
+        }
+        change
+    }
+
+    #[test]
+    fn test_bounce_ignored() {
+        let mut filter = LidFilter::new(50, false);
+        for _ in 0..5 {
+            assert_eq!(poll(&mut filter, true, 30), None);
+            assert_eq!(poll(&mut filter, false, 10), None);
+        }
+        assert_eq!(poll(&mut filter, true, 50), Some(true));
+        assert_eq!(poll(&mut filter, false, 50), Some(false));
+    }
+}
@@ -3,25 +3,36 @@
 //! Each task runs independently and communicates via channels/signals.
 
 pub mod ac_motor;
+pub mod axis;
 pub mod calibration;
 pub mod controller;
 pub mod dc_motor;
 pub mod display_rx;
 pub mod display_tx;
 pub mod heater;
+pub mod lid;
 pub mod stall_monitor;
 pub mod stepper;
 pub mod tick;
 pub mod tmc;
+pub mod ultrasonic;
+pub mod watchdog;
 
 pub use ac_motor::{ac_motor_task, AcMotorFwConfig};
+pub use axis::{axis_task, AxisFwConfig, AxisPins};
 pub use calibration::calibration_task;
-pub use controller::controller_task;
+pub use controller::{
+    controller_task, ControllerSettings, HomingSettings, ParkSettings, ProtectionSettings,
+    SpinOffLimits,
+};
 pub use dc_motor::{dc_motor_task, DcMotorFwConfig};
 pub use display_rx::display_rx_task;
 pub use display_tx::display_tx_task;
-pub use heater::{heater_task, HeaterConfig};
+pub use heater::{heater_task, HeaterConfig, HeaterPin, MissingSensor, ThermistorSensor};
+pub use lid::lid_task;
 pub use stall_monitor::{stall_monitor_task, StallMonitorConfig};
 pub use stepper::stepper_task;
 pub use tick::tick_task;
-pub use tmc::tmc_init_task;
+pub use tmc::tmc_task;
+pub use ultrasonic::{ultrasonic_task, ModulePin};
+pub use watchdog::watchdog_task;
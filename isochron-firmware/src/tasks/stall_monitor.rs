@@ -2,12 +2,23 @@
 //!
 //! Monitors the TMC2209 DIAG pin for StallGuard stall detection.
 //! When a stall is detected (DIAG goes high), signals the controller.
+//!
+//! StallGuard readings are unreliable while the motor accelerates, so
+//! the DIAG pin can false-trigger right after a speed change. Assertions
+//! are ignored for a settle window after each change and only trusted
+//! once the motor is at speed.
 
 use defmt::*;
 use embassy_rp::gpio::Input;
 use embassy_time::{Duration, Ticker};
 
-use crate::channels::MOTOR_STALL;
+use crate::channels::{MOTOR_STALL, STALL_SPEED_CHANGE};
+
+/// DIAG poll interval (ms)
+const POLL_MS: u32 = 20;
+
+/// Default settle window after a speed change (ms)
+pub const DEFAULT_SETTLE_MS: u32 = 500;
 
 /// Stall monitor configuration
 pub struct StallMonitorConfig {
@@ -15,6 +26,8 @@ pub struct StallMonitorConfig {
     pub debounce_ms: u32,
     /// Active level (true = high when stalled)
     pub active_high: bool,
+    /// DIAG is ignored for this long after a speed change (ms)
+    pub settle_ms: u32,
 }
 
 impl Default for StallMonitorConfig {
@@ -22,7 +35,68 @@ impl Default for StallMonitorConfig {
         Self {
             debounce_ms: 50,
             active_high: true, // TMC2209 DIAG is active high
+            settle_ms: DEFAULT_SETTLE_MS,
+        }
+    }
+}
+
+/// Debounced DIAG stall state, polled every `POLL_MS`
+#[derive(Debug, Clone)]
+struct StallFilter {
+    /// Polls DIAG must stay asserted before a stall
+    debounce_polls: u32,
+    /// Polls in the settle window after a speed change
+    settle_polls: u32,
+    /// Polls left in the current settle window
+    settling: u32,
+    /// Debounce counter
+    counter: u32,
+    /// Stall currently reported
+    stalled: bool,
+}
+
+impl StallFilter {
+    fn new(config: &StallMonitorConfig) -> Self {
+        Self {
+            debounce_polls: config.debounce_ms / POLL_MS,
+            settle_polls: config.settle_ms.div_ceil(POLL_MS),
+            settling: 0,
+            counter: 0,
+            stalled: false,
+        }
+    }
+
+    /// Restart the settle window after the motor speed changed
+    fn speed_changed(&mut self) {
+        self.settling = self.settle_polls;
+        self.counter = 0;
+    }
+
+    /// Motor has settled at its commanded speed
+    fn is_at_speed(&self) -> bool {
+        self.settling == 0
+    }
+
+    /// Poll the DIAG level, returning the new stall state when it changes
+    fn update(&mut self, diag_asserted: bool) -> Option<bool> {
+        let trusted = self.is_at_speed();
+        self.settling = self.settling.saturating_sub(1);
+
+        if diag_asserted && trusted {
+            self.counter = self.counter.saturating_add(1);
+            if self.counter >= self.debounce_polls && !self.stalled {
+                self.stalled = true;
+                return Some(true);
+            }
+        } else {
+            let cleared = self.stalled && self.counter == 0;
+            self.counter = self.counter.saturating_sub(1);
+            if cleared {
+                self.stalled = false;
+                return Some(false);
+            }
         }
+        None
     }
 }
 
@@ -34,34 +108,93 @@ impl Default for StallMonitorConfig {
 pub async fn stall_monitor_task(diag_pin: Input<'static>, config: StallMonitorConfig) {
     info!("Stall monitor task started");
 
-    let mut ticker = Ticker::every(Duration::from_millis(20));
-    let mut stalled = false;
-    let mut debounce_counter: u32 = 0;
-    let debounce_threshold = config.debounce_ms / 20; // 20ms tick rate
+    let mut ticker = Ticker::every(Duration::from_millis(POLL_MS as u64));
+    let mut filter = StallFilter::new(&config);
 
     loop {
+        if STALL_SPEED_CHANGE.try_take().is_some() {
+            filter.speed_changed();
+        }
+
         let pin_stalled = if config.active_high {
             diag_pin.is_high()
         } else {
             diag_pin.is_low()
         };
 
-        if pin_stalled {
-            debounce_counter = debounce_counter.saturating_add(1);
-            if debounce_counter >= debounce_threshold && !stalled {
-                stalled = true;
+        match filter.update(pin_stalled) {
+            Some(true) => {
                 warn!("Motor stall detected!");
                 MOTOR_STALL.signal(true);
             }
-        } else {
-            if stalled && debounce_counter == 0 {
-                stalled = false;
+            Some(false) => {
                 info!("Motor stall cleared");
                 MOTOR_STALL.signal(false);
             }
-            debounce_counter = debounce_counter.saturating_sub(1);
+            None => {}
         }
 
         ticker.next().await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> StallFilter {
+        StallFilter::new(&StallMonitorConfig::default())
+    }
+
+    /// Poll with DIAG at `asserted` for `ms`, returning any stall change
+    fn poll(filter: &mut StallFilter, asserted: bool, ms: u32) -> Option<bool> {
+        let mut change = None;
+        for _ in 0..ms / POLL_MS {
+            change = filter.update(asserted).or(change);
+        }
+        change
+    }
+
+    #[test]
+    fn test_diag_ignored_while_settling() {
+        let mut filter = filter();
+        filter.speed_changed();
+        assert!(!filter.is_at_speed());
+
+        // Asserted for most of the window: not a stall
+        assert_eq!(poll(&mut filter, true, 480), None);
+        assert_eq!(poll(&mut filter, false, 20), None);
+        assert!(filter.is_at_speed());
+        assert!(!filter.stalled);
+
+        // A later speed change opens a new window
+        poll(&mut filter, false, 1000);
+        filter.speed_changed();
+        assert_eq!(poll(&mut filter, true, 200), None);
+    }
+
+    #[test]
+    fn test_diag_honoured_at_speed() {
+        let mut filter = filter();
+        filter.speed_changed();
+        poll(&mut filter, false, DEFAULT_SETTLE_MS);
+        assert!(filter.is_at_speed());
+
+        // Still debounced: a single poll is a glitch
+        assert_eq!(poll(&mut filter, true, 20), None);
+        assert_eq!(poll(&mut filter, false, 40), None);
+
+        assert_eq!(poll(&mut filter, true, 60), Some(true));
+        assert_eq!(poll(&mut filter, false, 100), Some(false));
+    }
+
+    #[test]
+    fn test_assertion_spanning_window_end_needs_debounce() {
+        let mut filter = filter();
+        filter.speed_changed();
+        // Asserted throughout: counting only starts once settled
+        assert_eq!(poll(&mut filter, true, DEFAULT_SETTLE_MS), None);
+        assert_eq!(poll(&mut filter, true, 20), None);
+        assert_eq!(poll(&mut filter, true, 40), Some(true));
+    }
+}
@@ -1,68 +1,196 @@
 //! Stepper motor control task
 //!
-//! Receives motor commands from the controller and drives the PIO stepper.
+//! Receives motor commands from the controller and drives the PIO stepper,
+//! ramping between speeds as set by the basket stepper's ramp keys.
+
+use core::future::pending;
 
 use defmt::*;
+use embassy_futures::select::{select, select3, Either, Either3};
 use embassy_rp::peripherals::PIO0;
+use embassy_time::Timer;
 
+use isochron_core::motion::{steps_to_angle, RampProfile, SpeedRamp};
+use isochron_core::scheduler::MotorCommand;
 use isochron_core::traits::Direction;
 use isochron_hal_rp2040::stepper::PioStepper;
 
-use crate::channels::MOTOR_CMD;
+use crate::channels::{MOTOR_CMD, ORIENT_CMD, ORIENT_DONE, STALL_SPEED_CHANGE, STEPPER_RPM};
+
+/// Speed the basket turns at to reach its park angle
+const ORIENT_RPM: u16 = 10;
+
+/// Interval between speed updates while ramping (ms)
+const RAMP_STEP_MS: u32 = 20;
+
+/// Basket stepper following the controller's commands along a speed ramp
+struct BasketMotor {
+    stepper: PioStepper<'static, PIO0, 0>,
+    ramp: SpeedRamp,
+    /// Latest motor command
+    command: MotorCommand,
+    /// Direction the stepper is set to turn
+    direction: Direction,
+    /// Speed the stepper is stepping at
+    rpm: u16,
+    enabled: bool,
+}
+
+impl BasketMotor {
+    /// Follow a new motor command
+    ///
+    /// A reversal while turning ramps down to a stop first.
+    fn command(&mut self, cmd: MotorCommand) {
+        trace!("Motor command: rpm={}, dir={:?}", cmd.rpm, cmd.direction);
+        self.command = cmd;
+        if cmd.direction != self.direction && self.rpm > 0 {
+            debug!("Direction change: stopping for direction reversal");
+            self.ramp.set_target(0);
+        } else {
+            self.set_direction(cmd.direction);
+            self.ramp.set_target(cmd.rpm);
+        }
+        self.advance(0);
+    }
+
+    /// Advance the speed ramp by `delta_ms`
+    fn advance(&mut self, delta_ms: u32) {
+        let rpm = self.ramp.update(delta_ms);
+        self.set_rpm(rpm);
+        if rpm == 0 && self.command.direction != self.direction {
+            // Stopped for a reversal: turn around and ramp back up
+            self.set_direction(self.command.direction);
+            self.ramp.set_target(self.command.rpm);
+            let rpm = self.ramp.update(0);
+            self.set_rpm(rpm);
+        }
+
+        // Brake keeps the driver enabled at hold current; coast releases it.
+        // Checked on every update so a held motor is released when the
+        // program ends even though the RPM stays at zero.
+        if self.ramp.is_done() && self.rpm == 0 && self.enabled != self.command.enable_required() {
+            if self.command.enable_required() {
+                self.stepper.enable();
+            } else {
+                self.stepper.disable();
+            }
+            self.enabled = self.command.enable_required();
+        }
+    }
+
+    /// Change direction; only done while stopped
+    fn set_direction(&mut self, direction: Direction) {
+        if direction != self.direction {
+            self.stepper
+                .set_direction(direction == Direction::Clockwise);
+            self.direction = direction;
+        }
+    }
+
+    /// Step at `rpm` along the ramp
+    fn set_rpm(&mut self, rpm: u16) {
+        if rpm == self.rpm {
+            return;
+        }
+        if rpm == 0 {
+            // Driver enable handled per stop behavior once the ramp ends
+            debug!("Motor stop (hold={})", self.command.hold);
+            self.stepper.stop();
+        } else {
+            if self.rpm == 0 {
+                trace!("Motor start: {} RPM", rpm);
+            }
+            if !self.enabled {
+                self.stepper.enable();
+                self.enabled = true;
+            }
+            self.stepper.set_rpm(rpm);
+        }
+        self.rpm = rpm;
+        STEPPER_RPM.signal(rpm);
+        STALL_SPEED_CHANGE.signal(());
+    }
+}
 
 /// Stepper control task for the basket motor
 ///
-/// Waits for motor commands and controls the PIO stepper accordingly.
+/// Waits for motor commands and controls the PIO stepper accordingly,
+/// changing speed along the configured ramp.
 /// Uses PIO0 state machine 0 for the primary basket motor.
 #[embassy_executor::task]
-pub async fn stepper_task(mut stepper: PioStepper<'static, PIO0, 0>) {
+pub async fn stepper_task(mut stepper: PioStepper<'static, PIO0, 0>, ramp: RampProfile) {
     info!("Stepper task started");
 
     // Start disabled
     stepper.disable();
 
-    // Track last command for change detection
-    let mut last_rpm: u16 = 0;
-    let mut last_direction = Direction::Clockwise;
+    let mut motor = BasketMotor {
+        stepper,
+        ramp: SpeedRamp::new(ramp),
+        command: MotorCommand::stopped(),
+        direction: Direction::Clockwise,
+        rpm: 0,
+        enabled: false,
+    };
 
     loop {
-        // Wait for next motor command
-        let cmd = MOTOR_CMD.wait().await;
-
-        trace!("Motor command: rpm={}, dir={:?}", cmd.rpm, cmd.direction);
-
-        // Handle direction change (must stop first if changing direction)
-        if cmd.direction != last_direction && last_rpm > 0 && cmd.rpm > 0 {
-            // Direction change while running - stop first
-            debug!("Direction change: stopping for direction reversal");
-            stepper.stop();
-            stepper.set_direction(cmd.direction == Direction::Clockwise);
-            last_direction = cmd.direction;
-        } else if cmd.direction != last_direction {
-            // Can change direction while stopped
-            stepper.set_direction(cmd.direction == Direction::Clockwise);
-            last_direction = cmd.direction;
-        }
-
-        // Handle speed change
-        if cmd.rpm != last_rpm {
-            if cmd.rpm == 0 {
-                // Stop motor
-                debug!("Motor stop");
-                stepper.stop();
-                stepper.disable();
+        let ramping = !motor.ramp.is_done();
+        let ramp_step = async {
+            if ramping {
+                Timer::after_millis(RAMP_STEP_MS as u64).await
             } else {
-                // Start or change speed
-                if last_rpm == 0 {
-                    // Starting from stopped
-                    debug!("Motor start: {} RPM", cmd.rpm);
-                    stepper.enable();
-                } else {
-                    debug!("Motor speed change: {} -> {} RPM", last_rpm, cmd.rpm);
+                pending().await
+            }
+        };
+
+        // Wait for next motor command, basket orientation request or ramp step
+        match select3(MOTOR_CMD.wait(), ORIENT_CMD.wait(), ramp_step).await {
+            Either3::First(cmd) => motor.command(cmd),
+            Either3::Second(angle_deg) => {
+                let interrupted = orient(&mut motor.stepper, angle_deg).await;
+                motor.ramp.reset();
+                motor.rpm = 0;
+                motor.direction = Direction::Clockwise;
+                motor.enabled = true;
+                ORIENT_DONE.signal(interrupted.is_none());
+                // Cancelled, e.g. by an abort: apply the new command
+                if let Some(cmd) = interrupted {
+                    motor.command(cmd);
                 }
-                stepper.set_rpm(cmd.rpm);
             }
-            last_rpm = cmd.rpm;
+            Either3::Third(()) => motor.advance(RAMP_STEP_MS),
         }
     }
 }
+
+/// Turn the basket forward to `angle_deg`, then stop with the driver enabled
+///
+/// Returns the motor command that interrupted the move, if any.
+async fn orient(
+    stepper: &mut PioStepper<'static, PIO0, 0>,
+    angle_deg: u16,
+) -> Option<MotorCommand> {
+    stepper.stop();
+    stepper.set_direction(true);
+    stepper.enable();
+
+    let steps = steps_to_angle(stepper.position_steps(), stepper.steps_per_rev(), angle_deg);
+    if steps == 0 {
+        return None;
+    }
+    debug!("Orienting basket: {} steps to {} degrees", steps, angle_deg);
+    stepper.set_rpm(ORIENT_RPM);
+    STEPPER_RPM.signal(ORIENT_RPM);
+    STALL_SPEED_CHANGE.signal(());
+    let freq_hz = stepper.current_freq().max(1) as u64;
+    let duration_us = steps as u64 * 1_000_000 / freq_hz;
+
+    let interrupted = match select(Timer::after_micros(duration_us), MOTOR_CMD.wait()).await {
+        Either::First(_) => None,
+        Either::Second(cmd) => Some(cmd),
+    };
+    stepper.stop();
+    STEPPER_RPM.signal(0);
+    STALL_SPEED_CHANGE.signal(());
+    interrupted
+}
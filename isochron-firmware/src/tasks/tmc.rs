@@ -4,18 +4,57 @@
 //! Uses UART1 on GPIO8 (TX) and GPIO9 (RX) for TMC communication.
 
 use defmt::*;
-use embassy_rp::uart::{Async, UartTx};
-use embassy_time::{Duration, Timer};
+use embassy_rp::uart::{Async, UartRx, UartTx};
+use embassy_time::{with_timeout, Duration, Timer};
 
-use isochron_drivers::stepper::tmc2209::{Tmc2209Config, Tmc2209Driver};
+use isochron_core::state::DriverFaultKind;
+use isochron_core::util::{retry_async, Backoff};
+use isochron_drivers::stepper::tmc2209::{
+    parse_read_response, sg_result_from_register, DrvStatus, Tmc2209Config, Tmc2209Driver,
+    DEFAULT_TPOWERDOWN,
+};
 
-/// TMC2209 initialization task
+use super::tick::TICK_INTERVAL_MS;
+use crate::channels::{DRIVER_FAULT, QUIET_MODE, STALLGUARD_READING, STEPPER_RPM};
+
+/// Attempts per datagram before initialization is abandoned
+const WRITE_ATTEMPTS: u8 = 3;
+
+/// Backoff between failed datagram writes
+const WRITE_BACKOFF: Backoff = Backoff::new(10, 50);
+
+/// Interval between SG_RESULT polls, one per controller tick so every
+/// tick sees a fresh reading
+const SG_POLL_INTERVAL: Duration = Duration::from_millis(TICK_INTERVAL_MS as u64);
+
+/// SG_RESULT polls per DRV_STATUS poll (500ms)
+const STATUS_POLL_EVERY: u8 = 5;
+
+/// Time allowed for a register reply
+const STATUS_REPLY_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// Bytes received per register read: the 4-byte request echoed on the
+/// single-wire bus followed by the 8-byte reply
+const STATUS_RX_LEN: usize = 12;
+
+/// TMC2209 task
 ///
 /// Initializes the TMC2209 driver over UART with the specified configuration.
 /// After initialization, the driver is configured for StealthChop operation
-/// with the specified current settings.
+/// with the specified current settings. SG_RESULT is then read every
+/// controller tick for spin-off imbalance detection; stalls are reported
+/// separately through the DIAG pin (see `stall_monitor_task`). DRV_STATUS
+/// is polled every 500ms so that over-temperature shutdown and phase
+/// shorts fault the controller, and each status poll, with spin hold
+/// enabled, updates TPOWERDOWN for the basket's current speed.
+/// A driver configured for SpreadCycle is switched to StealthChop while
+/// quiet mode is on.
 #[embassy_executor::task]
-pub async fn tmc_init_task(mut tx: UartTx<'static, Async>, config: Tmc2209Config) {
+pub async fn tmc_task(
+    mut tx: UartTx<'static, Async>,
+    mut rx: UartRx<'static, Async>,
+    config: Tmc2209Config,
+) {
     info!("TMC2209 init task starting...");
 
     // Wait for TMC2209 to power up
@@ -35,9 +74,18 @@ pub async fn tmc_init_task(mut tx: UartTx<'static, Async>, config: Tmc2209Config
         // Small delay between writes for TMC to process
         Timer::after(Duration::from_millis(10)).await;
 
-        match tx.write(datagram).await {
+        let result = retry_async(
+            WRITE_ATTEMPTS,
+            WRITE_BACKOFF,
+            async |ms| Timer::after_millis(ms as u64).await,
+            async || tx.write(datagram).await,
+        )
+        .await;
+
+        match result {
             Ok(()) => {
                 trace!("Sent TMC datagram {}/6", i + 1);
+                drop_echo(&mut rx).await;
             }
             Err(e) => {
                 error!("Failed to send TMC datagram {}: {:?}", i + 1, e);
@@ -54,8 +102,124 @@ pub async fn tmc_init_task(mut tx: UartTx<'static, Async>, config: Tmc2209Config
     debug!("  Run current: {}mA", config.run_current_ma);
     debug!("  Hold current: {}mA", config.hold_current_ma);
     debug!("  StealthChop: {}", config.stealthchop);
+    debug!("  Spin hold: {}", config.spin_hold);
+
+    // The stepper task handles step/dir/enable via GPIO; from here on
+    // this task only watches the driver for faults
+    let sg_request = driver.read_sg_result_request();
+    let status_request = driver.read_status_request();
+    let mut until_status = STATUS_POLL_EVERY;
+    let mut reported: Option<DriverFaultKind> = None;
+    let mut spin_rpm = 0;
+    let mut power_down_delay = DEFAULT_TPOWERDOWN;
+    let mut quiet = false;
+    let mut stealth_forced = false;
+
+    loop {
+        Timer::after(SG_POLL_INTERVAL).await;
 
-    // Task complete - TMC2209 is now configured
-    // The stepper task handles step/dir/enable via GPIO
-    // Future: could add periodic stall monitoring here
+        if let Some(value) = read_register(&mut tx, &mut rx, &sg_request).await {
+            STALLGUARD_READING.signal(sg_result_from_register(value));
+        }
+
+        until_status -= 1;
+        if until_status > 0 {
+            continue;
+        }
+        until_status = STATUS_POLL_EVERY;
+
+        // A failed write is retried on the next poll
+        if let Some(rpm) = STEPPER_RPM.try_take() {
+            spin_rpm = rpm;
+        }
+        let delay = driver.power_down_delay(spin_rpm);
+        if delay != power_down_delay {
+            let datagram = driver.power_down_datagram(spin_rpm);
+            match tx.write(&datagram).await {
+                Ok(()) => {
+                    debug!("TPOWERDOWN {} for {} RPM", delay, spin_rpm);
+                    power_down_delay = delay;
+                    drop_echo(&mut rx).await;
+                }
+                Err(e) => warn!("TPOWERDOWN write failed: {:?}", e),
+            }
+        }
+
+        // Only a SpreadCycle driver changes mode; StealthChop is already quiet
+        if let Some(enabled) = QUIET_MODE.try_take() {
+            quiet = enabled;
+        }
+        let force = quiet && !config.stealthchop;
+        if force != stealth_forced {
+            match tx.write(&driver.gconf_datagram(force)).await {
+                Ok(()) => {
+                    debug!("StealthChop forced: {}", force);
+                    stealth_forced = force;
+                    drop_echo(&mut rx).await;
+                }
+                Err(e) => warn!("GCONF write failed: {:?}", e),
+            }
+        }
+
+        let status = match read_register(&mut tx, &mut rx, &status_request).await {
+            Some(value) => DrvStatus::from_register(value),
+            None => continue,
+        };
+
+        let fault = status.fault_kind();
+        if fault != reported {
+            match fault {
+                Some(kind) => error!("TMC2209 driver fault: {:?}", kind),
+                None => info!("TMC2209 driver fault cleared"),
+            }
+            reported = fault;
+            DRIVER_FAULT.signal(fault);
+        } else if status.has_warning() {
+            warn!("TMC2209 warning (overtemp pre-warning or open load)");
+        }
+    }
+}
+
+/// Read back the echo of a written datagram
+///
+/// TX and RX share the single-wire bus, so every datagram written is
+/// received again; dropping it keeps it from being taken for a reply.
+async fn drop_echo(rx: &mut UartRx<'static, Async>) {
+    let mut echo = [0u8; 8];
+    let _ = with_timeout(STATUS_REPLY_TIMEOUT, rx.read(&mut echo)).await;
+}
+
+/// Request and read a register, returning None on any link error
+async fn read_register(
+    tx: &mut UartTx<'static, Async>,
+    rx: &mut UartRx<'static, Async>,
+    request: &[u8; 4],
+) -> Option<u32> {
+    if let Err(e) = tx.write(request).await {
+        warn!("TMC read request failed: {:?}", e);
+        return None;
+    }
+
+    let mut buf = [0u8; STATUS_RX_LEN];
+    match with_timeout(STATUS_REPLY_TIMEOUT, rx.read(&mut buf)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            warn!("TMC read failed: {:?}", e);
+            return None;
+        }
+        Err(_) => {
+            warn!("TMC read timed out");
+            return None;
+        }
+    }
+
+    let mut reply = [0u8; 8];
+    reply.copy_from_slice(&buf[request.len()..]);
+    match parse_read_response(&reply) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("TMC reply invalid: {:?}", e);
+            None
+        }
+    }
 }
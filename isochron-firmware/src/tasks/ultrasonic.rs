@@ -0,0 +1,90 @@
+//! Ultrasonic module task
+//!
+//! Switches the ultrasonic module from the controller's commands, which
+//! turn it off whenever the basket isn't working in a jar that names it.
+//! The driver's on-time limit is checked here every second.
+
+use defmt::*;
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Instant, Ticker};
+use isochron_core::config::{PinConfig, UltrasonicHwConfig};
+use isochron_drivers::accessory::ultrasonic::{TransducerOutput, Ultrasonic, UltrasonicConfig};
+use isochron_hal_rp2040::gpio::RpOutput;
+use isochron_hal_rp2040::OutputPinTrait;
+
+use crate::channels::ULTRASONIC_CMD;
+
+/// On-time limit check interval (ms)
+const CHECK_MS: u64 = 1000;
+
+/// On/off module output honouring the pin's polarity
+pub struct ModulePin {
+    pin: RpOutput<'static>,
+    polarity: PinConfig,
+}
+
+impl ModulePin {
+    /// Wrap the module's output pin
+    pub fn new(pin: RpOutput<'static>, polarity: PinConfig) -> Self {
+        Self { pin, polarity }
+    }
+}
+
+impl TransducerOutput for ModulePin {
+    fn set_duty_percent(&mut self, duty_percent: u8) {
+        self.pin
+            .set_state(self.polarity.is_active(duty_percent > 0));
+    }
+}
+
+/// Driver settings for a module
+fn driver_config(config: &UltrasonicHwConfig) -> UltrasonicConfig {
+    UltrasonicConfig {
+        max_on_ms: config.max_on_s as u32 * 1000,
+        ..Default::default()
+    }
+}
+
+/// Ultrasonic module task
+#[embassy_executor::task]
+pub async fn ultrasonic_task(pin: ModulePin, config: UltrasonicHwConfig) {
+    info!("Ultrasonic task started");
+
+    let mut ultrasonic = Ultrasonic::new(pin, driver_config(&config));
+    let mut ticker = Ticker::every(Duration::from_millis(CHECK_MS));
+    loop {
+        let event = select(ULTRASONIC_CMD.wait(), ticker.next()).await;
+        let now_ms = Instant::now().as_millis() as u32;
+        match event {
+            Either::First(cmd) => {
+                let was_on = ultrasonic.is_on();
+                ultrasonic.apply(cmd, now_ms);
+                if ultrasonic.is_on() != was_on {
+                    debug!("Ultrasonic {}", if cmd.on { "on" } else { "off" });
+                }
+            }
+            Either::Second(_) => {
+                let was_cut_off = ultrasonic.is_cut_off();
+                ultrasonic.update(now_ms);
+                if ultrasonic.is_cut_off() && !was_cut_off {
+                    warn!("Ultrasonic on-time limit reached, switched off");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_driver_config() {
+        let config = UltrasonicHwConfig {
+            max_on_s: 600,
+            ..Default::default()
+        };
+        assert_eq!(driver_config(&config).max_on_ms, 600_000);
+        assert_eq!(driver_config(&config).intensity, 100);
+    }
+}
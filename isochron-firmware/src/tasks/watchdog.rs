@@ -0,0 +1,79 @@
+//! Watchdog task
+//!
+//! Owns the hardware watchdog. It is fed only while the controller keeps
+//! finishing ticks, so a controller stuck on an await resets the board
+//! just like a hung executor does. Before letting the watchdog expire the
+//! task turns the heaters and motor off, since their tasks may still be
+//! running the last command the controller gave them.
+//!
+//! Breadcrumbs from the controller are written to a scratch register here,
+//! where they survive the reset.
+
+use defmt::*;
+use embassy_futures::select::{select3, Either3};
+use embassy_time::{Instant, Timer};
+use isochron_core::safety::{TickWatch, WatchdogAction};
+use isochron_core::scheduler::{HeaterCommand, MotorCommand};
+use isochron_hal_rp2040::watchdog::RpWatchdog;
+use isochron_hal_rp2040::WatchdogTrait;
+
+use crate::channels::{BREADCRUMB, CONTROLLER_TICK, HEATER_CMD, MOTOR_CMD};
+
+/// Watchdog scratch register holding the breadcrumb
+pub const BREADCRUMB_SCRATCH: usize = 0;
+
+/// Wake-up interval while the watchdog is disabled (ms)
+const IDLE_POLL_MS: u32 = 500;
+
+/// Watchdog task - feeds the watchdog while the controller is healthy
+///
+/// `timeout_ms` is the armed timeout, or None if the watchdog is disabled.
+/// The controller counts as stalled after a full timeout without a tick.
+#[embassy_executor::task]
+pub async fn watchdog_task(mut watchdog: RpWatchdog, timeout_ms: Option<u32>) {
+    info!("Watchdog task started");
+
+    let poll_ms = timeout_ms.map_or(IDLE_POLL_MS, |t| watchdog.feed_interval_ms(t));
+    let mut watch = TickWatch::new(timeout_ms.unwrap_or(u32::MAX), uptime_ms());
+
+    loop {
+        match select3(
+            BREADCRUMB.wait(),
+            CONTROLLER_TICK.wait(),
+            Timer::after_millis(poll_ms as u64),
+        )
+        .await
+        {
+            Either3::First(crumb) => watchdog.set_scratch(BREADCRUMB_SCRATCH, crumb.to_word()),
+            Either3::Second(()) => watch.tick(uptime_ms()),
+            Either3::Third(()) => trace!("Watchdog poll"),
+        }
+
+        if timeout_ms.is_none() {
+            continue;
+        }
+        match watch.poll(uptime_ms()) {
+            WatchdogAction::Feed => watchdog.feed(),
+            WatchdogAction::DisableOutputs => {
+                error!("Controller stalled, outputs off until the watchdog resets");
+                disable_outputs();
+            }
+            WatchdogAction::Starve => {}
+        }
+    }
+}
+
+/// Command every heater and the motor off
+///
+/// The output tasks pick these up like any controller command.
+pub fn disable_outputs() {
+    for (heater, cmd) in HEATER_CMD.iter().enumerate() {
+        cmd.signal(HeaterCommand::off().for_heater(heater as u8));
+    }
+    MOTOR_CMD.signal(MotorCommand::stopped());
+}
+
+/// Milliseconds since boot
+fn uptime_ms() -> u32 {
+    Instant::now().as_millis() as u32
+}
@@ -0,0 +1,326 @@
+//! COBS (Consistent Overhead Byte Stuffing) frame mode
+//!
+//! The default framing finds frames by the START byte and the length
+//! field, so a START byte inside a payload can be taken for the start of
+//! a frame when the parser resynchronizes after a dropped byte. COBS
+//! removes every zero byte from the frame, leaving 0x00 free to mark the
+//! end of each frame unambiguously, whatever the payload holds.
+//!
+//! Frame format:
+//! - COBS(CHANNEL, LENGTH, TYPE, PAYLOAD, CHECKSUM): the fields of a
+//!   default frame without START, stuffed so they contain no zeros
+//! - DELIMITER (1 byte): 0x00
+//!
+//! A receiver that loses bytes mid-frame drops everything up to the next
+//! delimiter: the damaged frame fails to decode or fails its checksum,
+//! and the frame after it parses normally.
+
+use heapless::Vec;
+
+use crate::frame::{Frame, FrameError, MAX_PAYLOAD_SIZE};
+
+/// Frame delimiter
+pub const COBS_DELIMITER: u8 = 0x00;
+
+/// Largest frame before stuffing (CHANNEL + LENGTH + TYPE + MAX_PAYLOAD + CHECKSUM)
+const MAX_FRAME_BODY: usize = 3 + MAX_PAYLOAD_SIZE + 1;
+
+/// Maximum complete COBS frame size, including the delimiter
+pub const MAX_COBS_FRAME_SIZE: usize = encoded_len(MAX_FRAME_BODY) + 1;
+
+/// Length of `len` bytes once stuffed (one code byte per 254 bytes)
+const fn encoded_len(len: usize) -> usize {
+    len + len / 254 + 1
+}
+
+/// Stuff `data` into `out`, returning the encoded length
+///
+/// The output holds no zero bytes; the delimiter is not written.
+pub fn encode(data: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    if out.len() < encoded_len(data.len()) {
+        return Err(FrameError::BufferTooSmall);
+    }
+
+    let mut code_index = 0;
+    let mut write = 1;
+    let mut code: u8 = 1;
+    for &byte in data {
+        if byte != 0 {
+            out[write] = byte;
+            write += 1;
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            out[code_index] = code;
+            code_index = write;
+            write += 1;
+            code = 1;
+        }
+    }
+    out[code_index] = code;
+
+    Ok(write)
+}
+
+/// Unstuff `data` (without its delimiter) into `out`, returning the decoded length
+pub fn decode(data: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    let mut read = 0;
+    let mut write = 0;
+    while read < data.len() {
+        let code = data[read] as usize;
+        let end = read + code;
+        if code == 0 || end > data.len() {
+            return Err(FrameError::InvalidFrame);
+        }
+
+        for &byte in &data[read + 1..end] {
+            if byte == 0 {
+                return Err(FrameError::InvalidFrame);
+            }
+            *out.get_mut(write).ok_or(FrameError::BufferTooSmall)? = byte;
+            write += 1;
+        }
+        read = end;
+
+        // A full block carries no zero after it
+        if code != 0xFF && read < data.len() {
+            *out.get_mut(write).ok_or(FrameError::BufferTooSmall)? = 0;
+            write += 1;
+        }
+    }
+
+    Ok(write)
+}
+
+impl Frame {
+    /// Encode this frame into a byte buffer as a COBS frame
+    ///
+    /// Returns the number of bytes written, including the delimiter.
+    pub fn encode_cobs(&self, buffer: &mut [u8]) -> Result<usize, FrameError> {
+        let mut plain = [0u8; MAX_FRAME_BODY + 1];
+        let len = self.encode(&mut plain)?;
+
+        // Stuff everything after START, leaving room for the delimiter
+        let end = buffer.len().saturating_sub(1);
+        let stuffed = encode(&plain[1..len], &mut buffer[..end])?;
+        buffer[stuffed] = COBS_DELIMITER;
+
+        Ok(stuffed + 1)
+    }
+}
+
+/// Parser for COBS frames
+///
+/// Collects bytes up to each delimiter, then unstuffs them and checks
+/// the frame's checksum. Damaged frames are reported as errors, never
+/// returned.
+#[derive(Debug, Clone)]
+pub struct CobsFrameParser {
+    /// Stuffed bytes since the last delimiter
+    buffer: Vec<u8, MAX_COBS_FRAME_SIZE>,
+    /// More bytes arrived than any frame holds
+    overflow: bool,
+}
+
+impl Default for CobsFrameParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CobsFrameParser {
+    /// Create a new COBS frame parser
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            overflow: false,
+        }
+    }
+
+    /// Reset the parser state, dropping any partial frame
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.overflow = false;
+    }
+
+    /// Feed a single byte to the parser
+    ///
+    /// Returns `Ok(Some(frame))` when a delimiter ends a valid frame,
+    /// `Ok(None)` when more bytes are needed, or `Err` when it ends a
+    /// damaged one.
+    pub fn feed(&mut self, byte: u8) -> Result<Option<Frame>, FrameError> {
+        if byte != COBS_DELIMITER {
+            if self.buffer.push(byte).is_err() {
+                self.overflow = true;
+            }
+            return Ok(None);
+        }
+
+        // Back-to-back delimiters carry no frame
+        if self.buffer.is_empty() && !self.overflow {
+            return Ok(None);
+        }
+
+        let result = if self.overflow {
+            Err(FrameError::InvalidFrame)
+        } else {
+            self.decode_frame().map(Some)
+        };
+        self.reset();
+        result
+    }
+
+    /// Feed multiple bytes to the parser
+    ///
+    /// Returns the first complete frame found, if any.
+    /// Remaining bytes after a complete frame are not consumed.
+    pub fn feed_bytes(&mut self, bytes: &[u8]) -> Result<Option<Frame>, FrameError> {
+        for &byte in bytes {
+            if let Some(frame) = self.feed(byte)? {
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Unstuff the buffered bytes and validate them as a frame
+    fn decode_frame(&self) -> Result<Frame, FrameError> {
+        let mut body = [0u8; MAX_FRAME_BODY];
+        let len = decode(&self.buffer, &mut body).map_err(|_| FrameError::InvalidFrame)?;
+        if len < 4 || body[1] as usize != len - 4 {
+            return Err(FrameError::InvalidFrame);
+        }
+
+        let (channel, length, msg_type) = (body[0], body[1], body[2]);
+        let payload = &body[3..len - 1];
+        if Frame::calculate_checksum(channel, length, msg_type, payload) != body[len - 1] {
+            return Err(FrameError::InvalidChecksum);
+        }
+
+        Frame::from_wire(channel, msg_type, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::FRAME_START;
+
+    fn stuff(data: &[u8]) -> Vec<u8, 300> {
+        let mut out = [0u8; 300];
+        let len = encode(data, &mut out).unwrap();
+        Vec::from_slice(&out[..len]).unwrap()
+    }
+
+    fn encode_cobs(frame: &Frame) -> Vec<u8, MAX_COBS_FRAME_SIZE> {
+        let mut buffer = [0u8; MAX_COBS_FRAME_SIZE];
+        let len = frame.encode_cobs(&mut buffer).unwrap();
+        Vec::from_slice(&buffer[..len]).unwrap()
+    }
+
+    /// Feed every byte, collecting frames and errors in order
+    fn feed_all(parser: &mut CobsFrameParser, bytes: &[u8]) -> Vec<Result<Frame, FrameError>, 8> {
+        let mut results = Vec::new();
+        for &byte in bytes {
+            match parser.feed(byte) {
+                Ok(Some(frame)) => results.push(Ok(frame)).unwrap(),
+                Ok(None) => {}
+                Err(e) => results.push(Err(e)).unwrap(),
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn test_cobs_encoding() {
+        assert_eq!(stuff(&[]).as_slice(), &[0x01]);
+        assert_eq!(stuff(&[0x00]).as_slice(), &[0x01, 0x01]);
+        assert_eq!(
+            stuff(&[0x11, 0x22, 0x00, 0x33]).as_slice(),
+            &[0x03, 0x11, 0x22, 0x02, 0x33]
+        );
+
+        // A full block of 254 non-zero bytes needs no zero after it
+        let long = [0x42u8; 254];
+        let stuffed = stuff(&long);
+        assert_eq!(stuffed.len(), 256);
+        assert_eq!((stuffed[0], stuffed[255]), (0xFF, 0x01));
+
+        for data in [&[0x00, 0x00][..], &[0x11, 0x22, 0x00, 0x33], &long] {
+            let mut out = [0u8; 300];
+            let len = decode(&stuff(data), &mut out).unwrap();
+            assert_eq!(&out[..len], data);
+        }
+    }
+
+    #[test]
+    fn test_cobs_frame_roundtrip() {
+        // Payload holding START, delimiter and full-scale bytes
+        let original = Frame::new(0x21, &[FRAME_START, 0x00, 0xFF, 0x00, FRAME_START]).unwrap();
+        let encoded = encode_cobs(&original);
+
+        let (delimiter, stuffed) = encoded.split_last().unwrap();
+        assert_eq!(*delimiter, COBS_DELIMITER);
+        assert!(!stuffed.contains(&COBS_DELIMITER));
+
+        let mut parser = CobsFrameParser::new();
+        let parsed = parser.feed_bytes(&encoded).unwrap().unwrap();
+        assert_eq!(parsed, original);
+
+        // Largest payload fits the frame size
+        let large = Frame::new(0x21, &[0u8; MAX_PAYLOAD_SIZE]).unwrap();
+        let encoded = encode_cobs(&large);
+        assert_eq!(parser.feed_bytes(&encoded).unwrap().unwrap(), large);
+    }
+
+    #[test]
+    fn test_interrupted_frame_recovers_at_delimiter() {
+        let first = encode_cobs(&Frame::new(0x21, &[1, 2, FRAME_START, 4]).unwrap());
+        let second = encode_cobs(&Frame::new(0x21, &[5, 6, 7]).unwrap());
+        let third = encode_cobs(&Frame::empty(0x24));
+
+        // The first frame is cut off and runs into the second
+        let mut stream = Vec::<u8, 64>::new();
+        stream.extend_from_slice(&first[..5]).unwrap();
+        stream.extend_from_slice(&second).unwrap();
+        stream.extend_from_slice(&third).unwrap();
+
+        let mut parser = CobsFrameParser::new();
+        let results = feed_all(&mut parser, &stream);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_err());
+        assert_eq!(results[1].as_ref().unwrap().msg_type, 0x24);
+    }
+
+    #[test]
+    fn test_dropped_byte_never_yields_frame() {
+        let frame = Frame::new(0x21, &[0, 0, 5, b'H', b'e', b'l', b'l', b'o']).unwrap();
+        let encoded = encode_cobs(&frame);
+        let next = encode_cobs(&Frame::empty(0x24));
+
+        for dropped in 0..encoded.len() - 1 {
+            let mut stream = Vec::<u8, 64>::new();
+            stream.extend_from_slice(&encoded[..dropped]).unwrap();
+            stream.extend_from_slice(&encoded[dropped + 1..]).unwrap();
+            stream.extend_from_slice(&next).unwrap();
+
+            let mut parser = CobsFrameParser::new();
+            let results = feed_all(&mut parser, &stream);
+            assert_eq!(results.len(), 2);
+            assert!(results[0].is_err());
+            assert_eq!(results[1].as_ref().unwrap().msg_type, 0x24);
+        }
+    }
+
+    #[test]
+    fn test_overlong_run_dropped() {
+        let mut parser = CobsFrameParser::new();
+        for _ in 0..MAX_COBS_FRAME_SIZE + 10 {
+            assert_eq!(parser.feed(0x55), Ok(None));
+        }
+        assert_eq!(parser.feed(COBS_DELIMITER), Err(FrameError::InvalidFrame));
+
+        let encoded = encode_cobs(&Frame::empty(0x24));
+        assert_eq!(parser.feed_bytes(&encoded).unwrap().unwrap().msg_type, 0x24);
+    }
+}
@@ -8,12 +8,14 @@ pub enum InputEvent {
     EncoderCw,
     /// Encoder rotated counter-clockwise (1 detent)
     EncoderCcw,
-    /// Short press (<500 ms)
+    /// Short press (shorter than the long-press threshold, 500 ms by default)
     EncoderClick,
-    /// Long press (>=500 ms)
+    /// Long press (held for the long-press threshold)
     EncoderLongPress,
     /// Button released (after long press)
     EncoderRelease,
+    /// Two short presses within the double-click window
+    EncoderDoubleClick,
 }
 
 // Wire format values
@@ -22,6 +24,7 @@ const EVENT_ENCODER_CCW: u8 = 0x02;
 const EVENT_ENCODER_CLICK: u8 = 0x10;
 const EVENT_ENCODER_LONG_PRESS: u8 = 0x11;
 const EVENT_ENCODER_RELEASE: u8 = 0x12;
+const EVENT_ENCODER_DOUBLE_CLICK: u8 = 0x13;
 
 impl InputEvent {
     /// Parse an event from its wire format byte
@@ -32,6 +35,7 @@ impl InputEvent {
             EVENT_ENCODER_CLICK => Some(InputEvent::EncoderClick),
             EVENT_ENCODER_LONG_PRESS => Some(InputEvent::EncoderLongPress),
             EVENT_ENCODER_RELEASE => Some(InputEvent::EncoderRelease),
+            EVENT_ENCODER_DOUBLE_CLICK => Some(InputEvent::EncoderDoubleClick),
             _ => None,
         }
     }
@@ -44,6 +48,7 @@ impl InputEvent {
             InputEvent::EncoderClick => EVENT_ENCODER_CLICK,
             InputEvent::EncoderLongPress => EVENT_ENCODER_LONG_PRESS,
             InputEvent::EncoderRelease => EVENT_ENCODER_RELEASE,
+            InputEvent::EncoderDoubleClick => EVENT_ENCODER_DOUBLE_CLICK,
         }
     }
 
@@ -56,7 +61,10 @@ impl InputEvent {
     pub fn is_button(&self) -> bool {
         matches!(
             self,
-            InputEvent::EncoderClick | InputEvent::EncoderLongPress | InputEvent::EncoderRelease
+            InputEvent::EncoderClick
+                | InputEvent::EncoderLongPress
+                | InputEvent::EncoderRelease
+                | InputEvent::EncoderDoubleClick
         )
     }
 
@@ -82,6 +90,7 @@ mod tests {
             InputEvent::EncoderClick,
             InputEvent::EncoderLongPress,
             InputEvent::EncoderRelease,
+            InputEvent::EncoderDoubleClick,
         ];
 
         for event in events {
@@ -110,6 +119,7 @@ mod tests {
         assert!(InputEvent::EncoderClick.is_button());
         assert!(InputEvent::EncoderLongPress.is_button());
         assert!(InputEvent::EncoderRelease.is_button());
+        assert!(InputEvent::EncoderDoubleClick.is_button());
         assert!(!InputEvent::EncoderCw.is_button());
     }
 
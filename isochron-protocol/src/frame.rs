@@ -1,22 +1,76 @@
 //! Frame encoding and decoding for the V0 Display protocol.
 //!
 //! Frame format:
-//! - START (1 byte): 0xAA synchronization byte
+//! - START (1 byte): 0xAB synchronization byte
+//! - CHANNEL (1 byte): logical channel the frame belongs to
 //! - LENGTH (1 byte): payload length (0-250)
 //! - TYPE (1 byte): message type identifier
 //! - PAYLOAD (0-250 bytes): type-specific data
-//! - CHECKSUM (1 byte): XOR of LENGTH, TYPE, and all PAYLOAD bytes
+//! - CHECKSUM (1 byte): XOR of CHANNEL, LENGTH, TYPE, and all PAYLOAD bytes
+//!
+//! Channels let several logical links share one UART, e.g. display
+//! traffic and host telemetry passed through the display. Message types
+//! are scoped to their channel. A receiver dispatches frames by channel
+//! (see [`FrameParser::feed_routed`]) and drops frames on channels it has
+//! no handler for; they are still parsed whole, so framing stays in sync.
+//!
+//! The START byte doubles as the frame format version. Firmware from
+//! before channels existed starts its frames with [`LEGACY_FRAME_START`]
+//! and has no CHANNEL byte. The parser still reads that format far enough
+//! to check it, and reports a valid legacy frame as
+//! [`FrameError::LegacyFrame`], so a controller and display on mismatched
+//! firmware can say so instead of failing every checksum.
+//!
+//! A frame may carry a sequence number for reliable delivery (see
+//! [`crate::reliable`]). It sets [`FRAME_SEQ_FLAG`] in the CHANNEL byte
+//! and puts a SEQ byte first in the payload, counted in LENGTH and the
+//! checksum. [`FrameParser`] always strips the flag and the SEQ byte, so
+//! every receiver gets the frame on its base channel with [`Frame::seq`]
+//! set. One that doesn't use sequencing handles it like any other frame
+//! but never ACKs it, so the sender resends it until it gives up.
+//!
+//! With the `crc16` feature, frames can instead end in a 2-byte
+//! CRC16-CCITT (poly 0x1021, init 0xFFFF, high byte first), which catches
+//! errors the XOR checksum misses, such as the same bit flipped in two
+//! bytes. The XOR checksum stays the default so existing displays keep
+//! working. CRC16 is not negotiated yet: both ends must be built with the
+//! feature and switched over together with [`Frame::encode_crc16`] and
+//! [`FrameParser::with_crc16`], since a peer checking XOR checksums
+//! rejects every CRC16 frame.
 
 use heapless::Vec;
 
-/// Frame synchronization byte
-pub const FRAME_START: u8 = 0xAA;
+/// Frame synchronization byte, which also marks the frame format
+pub const FRAME_START: u8 = 0xAB;
+
+/// START byte of the legacy format (START, LENGTH, TYPE, PAYLOAD, CHECKSUM)
+pub const LEGACY_FRAME_START: u8 = 0xAA;
+
+/// Channel for controller/display traffic
+pub const CHANNEL_DISPLAY: u8 = 0x00;
+
+/// Channel for host telemetry and commands
+pub const CHANNEL_TELEMETRY: u8 = 0x01;
 
 /// Maximum payload size in bytes
 pub const MAX_PAYLOAD_SIZE: usize = 250;
 
-/// Maximum complete frame size (START + LENGTH + TYPE + MAX_PAYLOAD + CHECKSUM)
-pub const MAX_FRAME_SIZE: usize = 1 + 1 + 1 + MAX_PAYLOAD_SIZE + 1;
+/// CHANNEL byte flag: the frame carries a sequence number
+pub const FRAME_SEQ_FLAG: u8 = 0x80;
+
+/// Capability bit: the peer accepts and sends CRC16 frames
+///
+/// Reserved for a capability exchange; no message carries it yet.
+pub const FRAME_CRC16: u8 = 1 << 0;
+
+/// Largest frame check (CHECKSUM, or CRC16 with the `crc16` feature)
+#[cfg(not(feature = "crc16"))]
+const MAX_CHECK_SIZE: usize = 1;
+#[cfg(feature = "crc16")]
+const MAX_CHECK_SIZE: usize = 2;
+
+/// Maximum complete frame size (START + CHANNEL + LENGTH + TYPE + MAX_PAYLOAD + CHECKSUM)
+pub const MAX_FRAME_SIZE: usize = 1 + 1 + 1 + 1 + MAX_PAYLOAD_SIZE + MAX_CHECK_SIZE;
 
 /// Errors that can occur during frame parsing or encoding
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -32,19 +86,25 @@ pub enum FrameError {
     InvalidFrame,
     /// Buffer too small for encoding
     BufferTooSmall,
+    /// Valid frame in the legacy format: the peer runs older firmware
+    LegacyFrame,
 }
 
 /// A parsed or constructed frame
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Frame {
+    /// Logical channel
+    pub channel: u8,
     /// Message type identifier
     pub msg_type: u8,
     /// Payload data
     pub payload: Vec<u8, MAX_PAYLOAD_SIZE>,
+    /// Sequence number for reliable delivery (None = fire-and-forget)
+    pub seq: Option<u8>,
 }
 
 impl Frame {
-    /// Create a new frame with the given message type and payload
+    /// Create a new display channel frame with the given message type and payload
     pub fn new(msg_type: u8, payload: &[u8]) -> Result<Self, FrameError> {
         if payload.len() > MAX_PAYLOAD_SIZE {
             return Err(FrameError::PayloadTooLarge);
@@ -56,47 +116,130 @@ impl Frame {
             .map_err(|_| FrameError::PayloadTooLarge)?;
 
         Ok(Self {
+            channel: CHANNEL_DISPLAY,
             msg_type,
             payload: payload_vec,
+            seq: None,
         })
     }
 
-    /// Create a frame with no payload
+    /// Create a display channel frame with no payload
     pub fn empty(msg_type: u8) -> Self {
         Self {
+            channel: CHANNEL_DISPLAY,
             msg_type,
             payload: Vec::new(),
+            seq: None,
         }
     }
 
+    /// Move this frame to another channel
+    pub fn on_channel(mut self, channel: u8) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Number this frame for reliable delivery
+    ///
+    /// The sequence number takes one byte of the payload, so a sequenced
+    /// frame holds at most `MAX_PAYLOAD_SIZE - 1` payload bytes.
+    pub fn with_seq(mut self, seq: u8) -> Self {
+        self.seq = Some(seq);
+        self
+    }
+
+    /// Build a frame from its wire fields, splitting off any sequence number
+    pub(crate) fn from_wire(channel: u8, msg_type: u8, payload: &[u8]) -> Result<Self, FrameError> {
+        let (seq, payload) = if channel & FRAME_SEQ_FLAG != 0 {
+            let (&seq, rest) = payload.split_first().ok_or(FrameError::InvalidFrame)?;
+            (Some(seq), rest)
+        } else {
+            (None, payload)
+        };
+
+        Ok(Self {
+            channel: channel & !FRAME_SEQ_FLAG,
+            msg_type,
+            payload: Vec::from_slice(payload).map_err(|_| FrameError::InvalidFrame)?,
+            seq,
+        })
+    }
+
     /// Calculate checksum for frame data
-    fn calculate_checksum(length: u8, msg_type: u8, payload: &[u8]) -> u8 {
-        let mut checksum = length ^ msg_type;
+    pub(crate) fn calculate_checksum(channel: u8, length: u8, msg_type: u8, payload: &[u8]) -> u8 {
+        let mut checksum = channel ^ length ^ msg_type;
         for &byte in payload {
             checksum ^= byte;
         }
         checksum
     }
 
+    /// Calculate CRC16-CCITT for frame data
+    #[cfg(feature = "crc16")]
+    fn calculate_crc16(channel: u8, length: u8, msg_type: u8, payload: &[u8]) -> u16 {
+        let mut crc = 0xFFFF;
+        for &byte in [channel, length, msg_type].iter().chain(payload) {
+            crc ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x1021
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    /// Write the frame header and payload, returning the check's offset
+    fn encode_body(&self, buffer: &mut [u8], check_len: usize) -> Result<usize, FrameError> {
+        // SEQ travels as the first payload byte
+        let (channel, header_len) = match self.seq {
+            Some(_) => (self.channel | FRAME_SEQ_FLAG, 5),
+            None => (self.channel, 4),
+        };
+        let length = header_len - 4 + self.payload.len();
+        if length > MAX_PAYLOAD_SIZE {
+            return Err(FrameError::PayloadTooLarge);
+        }
+        // START + CHANNEL + LENGTH + TYPE + payload + check
+        if buffer.len() < 4 + length + check_len {
+            return Err(FrameError::BufferTooSmall);
+        }
+
+        buffer[0] = FRAME_START;
+        buffer[1] = channel;
+        buffer[2] = length as u8;
+        buffer[3] = self.msg_type;
+        if let Some(seq) = self.seq {
+            buffer[4] = seq;
+        }
+        buffer[header_len..4 + length].copy_from_slice(&self.payload);
+
+        Ok(4 + length)
+    }
+
     /// Encode this frame into a byte buffer
     ///
     /// Returns the number of bytes written
     pub fn encode(&self, buffer: &mut [u8]) -> Result<usize, FrameError> {
-        let frame_len = 4 + self.payload.len(); // START + LENGTH + TYPE + payload + CHECKSUM
-        if buffer.len() < frame_len {
-            return Err(FrameError::BufferTooSmall);
-        }
+        let end = self.encode_body(buffer, 1)?;
+        buffer[end] = Self::calculate_checksum(buffer[1], buffer[2], buffer[3], &buffer[4..end]);
 
-        let length = self.payload.len() as u8;
-        let checksum = Self::calculate_checksum(length, self.msg_type, &self.payload);
-
-        buffer[0] = FRAME_START;
-        buffer[1] = length;
-        buffer[2] = self.msg_type;
-        buffer[3..3 + self.payload.len()].copy_from_slice(&self.payload);
-        buffer[3 + self.payload.len()] = checksum;
+        Ok(end + 1)
+    }
 
-        Ok(frame_len)
+    /// Encode this frame into a byte buffer, ending in a CRC16
+    ///
+    /// Only for peers advertising [`FRAME_CRC16`]. Returns the number of
+    /// bytes written.
+    #[cfg(feature = "crc16")]
+    pub fn encode_crc16(&self, buffer: &mut [u8]) -> Result<usize, FrameError> {
+        let end = self.encode_body(buffer, 2)?;
+        let crc = Self::calculate_crc16(buffer[1], buffer[2], buffer[3], &buffer[4..end]);
+        buffer[end..end + 2].copy_from_slice(&crc.to_be_bytes());
+
+        Ok(end + 2)
     }
 
     /// Encode this frame into a heapless Vec
@@ -110,27 +253,50 @@ impl Frame {
     }
 }
 
+/// Receiver for the frames on one channel
+pub trait ChannelHandler {
+    /// Channel this handler receives
+    fn channel(&self) -> u8;
+
+    /// Handle a complete frame on this handler's channel
+    fn handle_frame(&mut self, frame: Frame);
+}
+
 /// State machine for parsing incoming frames
 #[derive(Debug, Clone)]
 pub struct FrameParser {
     state: ParseState,
     buffer: Vec<u8, MAX_PAYLOAD_SIZE>,
+    channel: u8,
     expected_length: u8,
     msg_type: u8,
+    /// Frame being parsed is in the legacy format
+    legacy: bool,
+    /// Expect a CRC16 instead of the XOR checksum
+    #[cfg(feature = "crc16")]
+    crc16: bool,
+    /// High byte of a CRC16 being received
+    #[cfg(feature = "crc16")]
+    crc_high: u8,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ParseState {
     /// Waiting for START byte
     WaitingForStart,
-    /// Got START, waiting for LENGTH
+    /// Got START, waiting for CHANNEL
+    WaitingForChannel,
+    /// Got CHANNEL, waiting for LENGTH
     WaitingForLength,
     /// Got LENGTH, waiting for TYPE
     WaitingForType,
     /// Reading payload bytes
     ReadingPayload,
-    /// Waiting for CHECKSUM
+    /// Waiting for CHECKSUM (or the CRC16 high byte)
     WaitingForChecksum,
+    /// Waiting for the CRC16 low byte
+    #[cfg(feature = "crc16")]
+    WaitingForCrcLow,
 }
 
 impl Default for FrameParser {
@@ -145,8 +311,23 @@ impl FrameParser {
         Self {
             state: ParseState::WaitingForStart,
             buffer: Vec::new(),
+            channel: 0,
             expected_length: 0,
             msg_type: 0,
+            legacy: false,
+            #[cfg(feature = "crc16")]
+            crc16: false,
+            #[cfg(feature = "crc16")]
+            crc_high: 0,
+        }
+    }
+
+    /// Create a frame parser expecting CRC16 frames
+    #[cfg(feature = "crc16")]
+    pub fn with_crc16() -> Self {
+        Self {
+            crc16: true,
+            ..Self::new()
         }
     }
 
@@ -154,8 +335,10 @@ impl FrameParser {
     pub fn reset(&mut self) {
         self.state = ParseState::WaitingForStart;
         self.buffer.clear();
+        self.channel = 0;
         self.expected_length = 0;
         self.msg_type = 0;
+        self.legacy = false;
     }
 
     /// Feed a single byte to the parser
@@ -166,11 +349,20 @@ impl FrameParser {
         match self.state {
             ParseState::WaitingForStart => {
                 if byte == FRAME_START {
+                    self.state = ParseState::WaitingForChannel;
+                } else if byte == LEGACY_FRAME_START {
+                    // No CHANNEL byte; its checksum is one over channel 0
+                    self.legacy = true;
                     self.state = ParseState::WaitingForLength;
                 }
                 // Silently ignore non-START bytes while waiting
                 Ok(None)
             }
+            ParseState::WaitingForChannel => {
+                self.channel = byte;
+                self.state = ParseState::WaitingForLength;
+                Ok(None)
+            }
             ParseState::WaitingForLength => {
                 if byte > MAX_PAYLOAD_SIZE as u8 {
                     self.reset();
@@ -199,25 +391,51 @@ impl FrameParser {
                 Ok(None)
             }
             ParseState::WaitingForChecksum => {
-                let expected_checksum =
-                    Frame::calculate_checksum(self.expected_length, self.msg_type, &self.buffer);
-
-                if byte != expected_checksum {
-                    self.reset();
-                    return Err(FrameError::InvalidChecksum);
+                #[cfg(feature = "crc16")]
+                if self.crc16 && !self.legacy {
+                    self.crc_high = byte;
+                    self.state = ParseState::WaitingForCrcLow;
+                    return Ok(None);
                 }
 
-                let frame = Frame {
-                    msg_type: self.msg_type,
-                    payload: self.buffer.clone(),
-                };
-
-                self.reset();
-                Ok(Some(frame))
+                let expected_checksum = Frame::calculate_checksum(
+                    self.channel,
+                    self.expected_length,
+                    self.msg_type,
+                    &self.buffer,
+                );
+                self.finish(byte == expected_checksum)
+            }
+            #[cfg(feature = "crc16")]
+            ParseState::WaitingForCrcLow => {
+                let expected_crc = Frame::calculate_crc16(
+                    self.channel,
+                    self.expected_length,
+                    self.msg_type,
+                    &self.buffer,
+                );
+                self.finish(u16::from_be_bytes([self.crc_high, byte]) == expected_crc)
             }
         }
     }
 
+    /// Complete the frame being parsed once its check is in
+    fn finish(&mut self, valid: bool) -> Result<Option<Frame>, FrameError> {
+        if !valid {
+            self.reset();
+            return Err(FrameError::InvalidChecksum);
+        }
+        if self.legacy {
+            self.reset();
+            return Err(FrameError::LegacyFrame);
+        }
+
+        let frame = Frame::from_wire(self.channel, self.msg_type, &self.buffer);
+
+        self.reset();
+        frame.map(Some)
+    }
+
     /// Feed multiple bytes to the parser
     ///
     /// Returns the first complete frame found, if any.
@@ -230,6 +448,29 @@ impl FrameParser {
         }
         Ok(None)
     }
+
+    /// Feed a single byte and dispatch a complete frame by channel
+    ///
+    /// The frame goes to the handler for its channel. Frames on a channel
+    /// without a handler are dropped; returns the channel of a frame that
+    /// was handled.
+    pub fn feed_routed(
+        &mut self,
+        byte: u8,
+        handlers: &mut [&mut dyn ChannelHandler],
+    ) -> Result<Option<u8>, FrameError> {
+        let Some(frame) = self.feed(byte)? else {
+            return Ok(None);
+        };
+        let channel = frame.channel;
+        match handlers.iter_mut().find(|h| h.channel() == channel) {
+            Some(handler) => {
+                handler.handle_frame(frame);
+                Ok(Some(channel))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -242,11 +483,12 @@ mod tests {
         let mut buffer = [0u8; 10];
         let len = frame.encode(&mut buffer).unwrap();
 
-        assert_eq!(len, 4);
+        assert_eq!(len, 5);
         assert_eq!(buffer[0], FRAME_START);
-        assert_eq!(buffer[1], 0); // length
-        assert_eq!(buffer[2], 0x20); // type
-        assert_eq!(buffer[3], 0x20); // checksum (0 ^ 0x20 = 0x20)
+        assert_eq!(buffer[1], CHANNEL_DISPLAY); // channel
+        assert_eq!(buffer[2], 0); // length
+        assert_eq!(buffer[3], 0x20); // type
+        assert_eq!(buffer[4], 0x20); // checksum (0 ^ 0 ^ 0x20 = 0x20)
     }
 
     #[test]
@@ -255,14 +497,15 @@ mod tests {
         let mut buffer = [0u8; 20];
         let len = frame.encode(&mut buffer).unwrap();
 
-        assert_eq!(len, 12);
+        assert_eq!(len, 13);
         assert_eq!(buffer[0], FRAME_START);
-        assert_eq!(buffer[1], 8); // length
-        assert_eq!(buffer[2], 0x21); // type
-                                     // payload starts at buffer[3]
-        assert_eq!(buffer[3], 0); // row
-        assert_eq!(buffer[4], 0); // col
-        assert_eq!(buffer[5], 5); // string length
+        assert_eq!(buffer[1], CHANNEL_DISPLAY); // channel
+        assert_eq!(buffer[2], 8); // length
+        assert_eq!(buffer[3], 0x21); // type
+                                     // payload starts at buffer[4]
+        assert_eq!(buffer[4], 0); // row
+        assert_eq!(buffer[5], 0); // col
+        assert_eq!(buffer[6], 5); // string length
     }
 
     #[test]
@@ -306,10 +549,243 @@ mod tests {
         assert_eq!(parsed.msg_type, 0x24);
     }
 
+    #[test]
+    fn test_legacy_frame_reported() {
+        // PONG and TEXT as older firmware sends them, without CHANNEL
+        let pong = [LEGACY_FRAME_START, 0x00, 0x24, 0x24];
+        let text = [
+            LEGACY_FRAME_START,
+            0x02,
+            0x21,
+            0x01,
+            0x02,
+            0x21 ^ 0x02 ^ 0x03,
+        ];
+
+        let mut parser = FrameParser::new();
+        assert_eq!(parser.feed_bytes(&pong), Err(FrameError::LegacyFrame));
+        assert_eq!(parser.feed_bytes(&text), Err(FrameError::LegacyFrame));
+
+        // A damaged one is still just a bad checksum
+        let mut corrupt = text;
+        corrupt[3] ^= 0x10;
+        assert_eq!(
+            parser.feed_bytes(&corrupt),
+            Err(FrameError::InvalidChecksum)
+        );
+
+        // Current frames parse as before once the stream moves on
+        let frame = Frame::empty(0x24).encode_to_vec().unwrap();
+        assert_eq!(parser.feed_bytes(&frame).unwrap().unwrap().msg_type, 0x24);
+    }
+
     #[test]
     fn test_payload_too_large() {
         let large_payload = [0u8; MAX_PAYLOAD_SIZE + 1];
         let result = Frame::new(0x21, &large_payload);
         assert_eq!(result, Err(FrameError::PayloadTooLarge));
+
+        // The sequence number takes a payload byte
+        let full = Frame::new(0x21, &[0u8; MAX_PAYLOAD_SIZE]).unwrap();
+        assert!(full.encode_to_vec().is_ok());
+        assert_eq!(
+            full.with_seq(1).encode_to_vec(),
+            Err(FrameError::PayloadTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_sequenced_frame_roundtrip() {
+        let original = Frame::new(0x21, &[1, 2, 3])
+            .unwrap()
+            .on_channel(CHANNEL_TELEMETRY)
+            .with_seq(42);
+        let encoded = original.encode_to_vec().unwrap();
+        assert_eq!(encoded[1], CHANNEL_TELEMETRY | FRAME_SEQ_FLAG);
+        assert_eq!(encoded[2], 4); // SEQ + payload
+        assert_eq!(encoded[4], 42);
+
+        let mut parser = FrameParser::new();
+        let parsed = parser.feed_bytes(&encoded).unwrap().unwrap();
+        assert_eq!(parsed, original);
+
+        // A flagged frame without room for SEQ is malformed
+        let mut bare = Frame::empty(0x24).encode_to_vec().unwrap();
+        bare[1] |= FRAME_SEQ_FLAG;
+        bare[4] ^= FRAME_SEQ_FLAG;
+        assert_eq!(parser.feed_bytes(&bare), Err(FrameError::InvalidFrame));
+    }
+
+    /// Handler that counts the frames it gets and keeps the last type
+    struct Recorder {
+        channel: u8,
+        frames: usize,
+        last_type: Option<u8>,
+    }
+
+    impl Recorder {
+        fn new(channel: u8) -> Self {
+            Self {
+                channel,
+                frames: 0,
+                last_type: None,
+            }
+        }
+    }
+
+    impl ChannelHandler for Recorder {
+        fn channel(&self) -> u8 {
+            self.channel
+        }
+
+        fn handle_frame(&mut self, frame: Frame) {
+            assert_eq!(frame.channel, self.channel);
+            self.frames += 1;
+            self.last_type = Some(frame.msg_type);
+        }
+    }
+
+    fn feed_all(
+        parser: &mut FrameParser,
+        bytes: &[u8],
+        handlers: &mut [&mut dyn ChannelHandler],
+    ) -> Vec<u8, 8> {
+        let mut routed = Vec::new();
+        for &byte in bytes {
+            if let Some(channel) = parser.feed_routed(byte, handlers).unwrap() {
+                routed.push(channel).unwrap();
+            }
+        }
+        routed
+    }
+
+    #[test]
+    fn test_frames_routed_by_channel() {
+        let display = Frame::empty(0x24).encode_to_vec().unwrap();
+        let telemetry = Frame::new(0x01, &[7, 8])
+            .unwrap()
+            .on_channel(CHANNEL_TELEMETRY)
+            .encode_to_vec()
+            .unwrap();
+
+        let mut display_rx = Recorder::new(CHANNEL_DISPLAY);
+        let mut telemetry_rx = Recorder::new(CHANNEL_TELEMETRY);
+        let mut parser = FrameParser::new();
+
+        let mut stream = Vec::<u8, 32>::new();
+        stream.extend_from_slice(&telemetry).unwrap();
+        stream.extend_from_slice(&display).unwrap();
+        let routed = feed_all(
+            &mut parser,
+            &stream,
+            &mut [&mut display_rx, &mut telemetry_rx],
+        );
+
+        assert_eq!(routed.as_slice(), &[CHANNEL_TELEMETRY, CHANNEL_DISPLAY]);
+        assert_eq!((display_rx.frames, display_rx.last_type), (1, Some(0x24)));
+        assert_eq!(
+            (telemetry_rx.frames, telemetry_rx.last_type),
+            (1, Some(0x01))
+        );
+    }
+
+    #[test]
+    fn test_unknown_channel_ignored() {
+        // A frame on an unknown channel, whose payload holds a START byte
+        let unknown = Frame::new(0x24, &[FRAME_START, 0x00, 0x00])
+            .unwrap()
+            .on_channel(0x7F)
+            .encode_to_vec()
+            .unwrap();
+        let display = Frame::empty(0x24).encode_to_vec().unwrap();
+
+        let mut display_rx = Recorder::new(CHANNEL_DISPLAY);
+        let mut parser = FrameParser::new();
+
+        let mut stream = Vec::<u8, 32>::new();
+        stream.extend_from_slice(&unknown).unwrap();
+        stream.extend_from_slice(&display).unwrap();
+        let routed = feed_all(&mut parser, &stream, &mut [&mut display_rx]);
+
+        // Dropped whole, and the next frame is still found
+        assert_eq!(routed.as_slice(), &[CHANNEL_DISPLAY]);
+        assert_eq!(display_rx.frames, 1);
+    }
+
+    #[cfg(feature = "crc16")]
+    fn encode_crc16(frame: &Frame) -> Vec<u8, MAX_FRAME_SIZE> {
+        let mut buffer = [0u8; MAX_FRAME_SIZE];
+        let len = frame.encode_crc16(&mut buffer).unwrap();
+        Vec::from_slice(&buffer[..len]).unwrap()
+    }
+
+    #[cfg(feature = "crc16")]
+    #[test]
+    fn test_crc16_roundtrip() {
+        let original = Frame::new(0x21, &[1, 2, 3, 4, 5]).unwrap();
+        let encoded = encode_crc16(&original);
+        assert_eq!(encoded.len(), 11);
+
+        // CRC-16/CCITT-FALSE check value
+        assert_eq!(Frame::calculate_crc16(b'1', b'2', b'3', b"456789"), 0x29B1);
+
+        let mut parser = FrameParser::with_crc16();
+        let parsed = parser.feed_bytes(&encoded).unwrap().unwrap();
+        assert_eq!(parsed, original);
+
+        // The modes do not mix
+        let mut parser = FrameParser::new();
+        assert_eq!(
+            parser.feed_bytes(&encoded),
+            Err(FrameError::InvalidChecksum)
+        );
+    }
+
+    #[cfg(feature = "crc16")]
+    #[test]
+    fn test_crc16_catches_single_bit_flips() {
+        let frame = Frame::new(0x21, &[0, 0, 5, b'H', b'e', b'l', b'l', b'o']).unwrap();
+        let encoded = encode_crc16(&frame);
+
+        for index in 4..4 + frame.payload.len() {
+            for bit in 0..8 {
+                let mut corrupt = encoded.clone();
+                corrupt[index] ^= 1 << bit;
+                let mut parser = FrameParser::with_crc16();
+                assert_eq!(
+                    parser.feed_bytes(&corrupt),
+                    Err(FrameError::InvalidChecksum)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "crc16")]
+    #[test]
+    fn test_crc16_catches_errors_checksum_misses() {
+        let frame = Frame::new(0x21, &[0, 0, 5, b'H', b'e', b'l', b'l', b'o']).unwrap();
+        let plain = frame.encode_to_vec().unwrap();
+        let crc = encode_crc16(&frame);
+
+        // The same bit flipped in two payload bytes cancels out of the XOR
+        for bit in 0..8 {
+            for (first, second) in [(4, 5), (6, 9), (7, 11)] {
+                let mut corrupt = plain.clone();
+                corrupt[first] ^= 1 << bit;
+                corrupt[second] ^= 1 << bit;
+                let mut parser = FrameParser::new();
+                let parsed = parser.feed_bytes(&corrupt).unwrap().unwrap();
+                assert_ne!(parsed.payload, frame.payload);
+
+                let mut corrupt = crc.clone();
+                corrupt[first] ^= 1 << bit;
+                corrupt[second] ^= 1 << bit;
+                let mut parser = FrameParser::with_crc16();
+                assert_eq!(
+                    parser.feed_bytes(&corrupt),
+                    Err(FrameError::InvalidChecksum)
+                );
+            }
+        }
     }
 }
@@ -8,22 +8,45 @@
 //!
 //! All messages use a simple binary frame format:
 //! ```text
-//! ┌───────┬────────┬──────┬─────────────┬──────────┐
-//! │ START │ LENGTH │ TYPE │ PAYLOAD     │ CHECKSUM │
-//! │ 1B    │ 1B     │ 1B   │ 0–250B      │ 1B       │
-//! └───────┴────────┴──────┴─────────────┴──────────┘
+//! ┌───────┬─────────┬────────┬──────┬─────────────┬──────────┐
+//! │ START │ CHANNEL │ LENGTH │ TYPE │ PAYLOAD     │ CHECKSUM │
+//! │ 1B    │ 1B      │ 1B     │ 1B   │ 0–250B      │ 1B       │
+//! └───────┴─────────┴────────┴──────┴─────────────┴──────────┘
 //! ```
 //!
+//! The channel byte lets other traffic, such as host telemetry, share the
+//! UART with the display. Display messages use [`CHANNEL_DISPLAY`].
+//! START is 0xAB; frames from older firmware, which start with 0xAA and
+//! have no CHANNEL byte, are reported as [`FrameError::LegacyFrame`].
+//! With the `crc16` feature the checksum can be replaced by a 2-byte CRC16,
+//! for peers built with the same feature (see [`FRAME_CRC16`]).
+//!
+//! Frames can also be sent COBS-encoded (see [`cobs`]), delimited by a
+//! zero byte instead of START, so payloads may hold arbitrary binary data.
+//!
+//! Commands that must not be lost can be numbered and resent until the
+//! display acknowledges them (see [`reliable`]).
+//!
 //! The display acts as a "dumb terminal" — it handles only input capture and
 //! screen rendering. All UI logic remains on the SKR Pico.
 
 #![no_std]
 #![deny(unsafe_code)]
 
+pub mod cobs;
 pub mod events;
 pub mod frame;
 pub mod messages;
+pub mod reliable;
 
+pub use cobs::{CobsFrameParser, COBS_DELIMITER, MAX_COBS_FRAME_SIZE};
 pub use events::InputEvent;
-pub use frame::{Frame, FrameError, FrameParser, FRAME_START, MAX_PAYLOAD_SIZE};
-pub use messages::{ControllerCommand, DisplayCommand, PicoMessage};
+pub use frame::{
+    ChannelHandler, Frame, FrameError, FrameParser, CHANNEL_DISPLAY, CHANNEL_TELEMETRY,
+    FRAME_CRC16, FRAME_SEQ_FLAG, FRAME_START, LEGACY_FRAME_START, MAX_PAYLOAD_SIZE,
+};
+pub use messages::{
+    blit_bitmap, clamp_contrast, ControllerCommand, DisplayCommand, LinkTestPattern, PicoMessage,
+    DEFAULT_CONTRAST, DEFAULT_HEARTBEAT_MS, DISPLAY_WIDTH_PX, LINK_TEST_LEN, MIN_CONTRAST,
+};
+pub use reliable::ReliableSender;
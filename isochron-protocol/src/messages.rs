@@ -3,6 +3,9 @@
 //! Message types are divided into two categories:
 //! - Display → Pico: Input events, heartbeat requests
 //! - Pico → Display: Screen commands, heartbeat responses
+//!
+//! A host on the telemetry channel uses the same types: it sends
+//! `DisplayCommand`s and receives `PicoMessage`s.
 
 use crate::events::InputEvent;
 use crate::frame::{Frame, FrameError, MAX_PAYLOAD_SIZE};
@@ -12,6 +15,10 @@ use heapless::Vec;
 pub const MSG_INPUT: u8 = 0x01;
 pub const MSG_PING: u8 = 0x02;
 pub const MSG_ACK: u8 = 0x03;
+pub const MSG_SOFT_RESET: u8 = 0x04;
+pub const MSG_LINK_TEST_RESULT: u8 = 0x05;
+pub const MSG_NACK: u8 = 0x06;
+pub const MSG_REQUEST_SCHEDULER_STATE: u8 = 0x07;
 
 // Message type IDs: Pico → Display
 pub const MSG_CLEAR: u8 = 0x20;
@@ -19,12 +26,120 @@ pub const MSG_TEXT: u8 = 0x21;
 pub const MSG_INVERT: u8 = 0x22;
 pub const MSG_HLINE: u8 = 0x23;
 pub const MSG_PONG: u8 = 0x24;
+pub const MSG_LINK_CONFIG: u8 = 0x25;
+pub const MSG_VERSION_INFO: u8 = 0x26;
+pub const MSG_LINK_TEST: u8 = 0x27;
+pub const MSG_BLIT: u8 = 0x28;
+pub const MSG_SET_CONTRAST: u8 = 0x29;
+pub const MSG_SCHEDULER_STATE: u8 = 0x2A;
 pub const MSG_RESET: u8 = 0x2F;
 
 /// Display dimensions
 pub const DISPLAY_ROWS: u8 = 8;
 pub const DISPLAY_COLS: u8 = 21;
 
+/// Display width in pixels; each of the `DISPLAY_ROWS` pages is 8 pixels tall
+pub const DISPLAY_WIDTH_PX: usize = 128;
+
+/// Display contrast at boot
+pub const DEFAULT_CONTRAST: u8 = 0xCF;
+
+/// Lowest contrast accepted, so a dimmed display stays readable
+pub const MIN_CONTRAST: u8 = 0x10;
+
+/// Clamp a contrast level to the accepted range
+pub fn clamp_contrast(contrast: u8) -> u8 {
+    contrast.max(MIN_CONTRAST)
+}
+
+/// Default interval between display heartbeats (PING)
+pub const DEFAULT_HEARTBEAT_MS: u16 = 1000;
+
+/// Bytes of test pattern in a link test frame
+pub const LINK_TEST_LEN: usize = 32;
+
+/// Known byte pattern sent to check the display link
+///
+/// The display compares what it received against the pattern and echoes
+/// a checksum of the received bytes, so corruption the frame's XOR
+/// checksum misses still shows up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LinkTestPattern {
+    /// 0x00, 0x01, 0x02, ...
+    Incrementing,
+    /// Alternating 0x55 / 0xAA
+    Checkerboard,
+}
+
+impl LinkTestPattern {
+    /// Convert to wire byte
+    pub fn to_byte(self) -> u8 {
+        match self {
+            LinkTestPattern::Incrementing => 0,
+            LinkTestPattern::Checkerboard => 1,
+        }
+    }
+
+    /// Parse from wire byte
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(LinkTestPattern::Incrementing),
+            1 => Some(LinkTestPattern::Checkerboard),
+            _ => None,
+        }
+    }
+
+    /// The pattern's bytes
+    pub fn bytes(self) -> [u8; LINK_TEST_LEN] {
+        core::array::from_fn(|i| match self {
+            LinkTestPattern::Incrementing => i as u8,
+            LinkTestPattern::Checkerboard if i % 2 == 0 => 0x55,
+            LinkTestPattern::Checkerboard => 0xAA,
+        })
+    }
+
+    /// Checksum a correctly received pattern echoes back
+    pub fn checksum(self) -> u16 {
+        link_test_checksum(&self.bytes())
+    }
+
+    /// Result the display reports for `received`
+    ///
+    /// `errors` counts bytes that differ from the pattern, including
+    /// missing or extra ones.
+    pub fn verify(self, received: &[u8]) -> DisplayCommand {
+        let expected = self.bytes();
+        let mismatched = expected
+            .iter()
+            .zip(received)
+            .filter(|(want, got)| want != got)
+            .count();
+        let errors = mismatched + expected.len().abs_diff(received.len());
+        DisplayCommand::LinkTestResult {
+            checksum: link_test_checksum(received),
+            errors: errors.min(u8::MAX as usize) as u8,
+        }
+    }
+}
+
+/// Scheduler state temperature meaning "heater off"
+const TEMP_OFF_X10: i16 = i16::MIN;
+
+/// Scheduler state payload length
+const SCHEDULER_STATE_LEN: usize = 11;
+
+/// Fletcher-16 checksum of link test bytes
+pub fn link_test_checksum(data: &[u8]) -> u16 {
+    let (mut sum1, mut sum2) = (0u16, 0u16);
+    for &byte in data {
+        sum1 = (sum1 + byte as u16) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+    (sum2 << 8) | sum1
+}
+
 /// Messages from the Pico to the Display
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -39,10 +154,62 @@ pub enum PicoMessage<'a> {
     HLine { row: u8, start_col: u8, end_col: u8 },
     /// Heartbeat response
     Pong,
+    /// Link timing: heartbeat interval the display should use
+    LinkConfig { heartbeat_ms: u16 },
+    /// Versions the controller firmware supports
+    VersionInfo { config_schema: u8 },
+    /// Link diagnostic: a known test pattern for the display to check
+    LinkTest { pattern: LinkTestPattern },
+    /// Write raw SH1106 page bytes (one byte = 8 vertical pixels)
+    /// starting at pixel column `col` of `page`
+    Blit { page: u8, col: u8, data: &'a [u8] },
+    /// Set the display contrast (clamped to at least `MIN_CONTRAST`)
+    SetContrast(u8),
+    /// Scheduler state for a host dashboard
+    SchedulerState {
+        /// Execution phase code
+        phase: u8,
+        /// Current step index
+        step: u8,
+        /// Current segment index within the step
+        segment: u8,
+        /// Commanded motor speed
+        rpm: u16,
+        /// Commanded heater target (0.1°C units, None = off)
+        temp: Option<i16>,
+        /// Time elapsed in the current step (seconds)
+        elapsed_s: u32,
+    },
     /// Reset display to boot state
     Reset,
 }
 
+/// Split a bitmap into Blit messages, one per page it covers
+///
+/// `bitmap` holds `width` bytes for each page from `page` down, in SH1106
+/// page format. A bitmap running past the bottom or right edge yields
+/// messages that fail to encode.
+pub fn blit_bitmap(
+    page: u8,
+    col: u8,
+    width: usize,
+    bitmap: &[u8],
+) -> impl Iterator<Item = PicoMessage<'_>> {
+    bitmap
+        .chunks(width.max(1))
+        .enumerate()
+        .map(move |(i, data)| PicoMessage::Blit {
+            page: page.saturating_add(i.min(u8::MAX as usize) as u8),
+            col,
+            data,
+        })
+}
+
+/// Check a blit lands within the display
+fn blit_in_bounds(page: u8, col: u8, len: usize) -> bool {
+    page < DISPLAY_ROWS && col as usize + len <= DISPLAY_WIDTH_PX
+}
+
 impl<'a> PicoMessage<'a> {
     /// Encode this message into a frame
     pub fn to_frame(&self) -> Result<Frame, FrameError> {
@@ -80,6 +247,49 @@ impl<'a> PicoMessage<'a> {
                 end_col,
             } => Frame::new(MSG_HLINE, &[*row, *start_col, *end_col]),
             PicoMessage::Pong => Ok(Frame::empty(MSG_PONG)),
+            PicoMessage::LinkConfig { heartbeat_ms } => {
+                Frame::new(MSG_LINK_CONFIG, &heartbeat_ms.to_le_bytes())
+            }
+            PicoMessage::VersionInfo { config_schema } => {
+                Frame::new(MSG_VERSION_INFO, &[*config_schema])
+            }
+            PicoMessage::LinkTest { pattern } => {
+                // Payload: [pattern][pattern bytes...]
+                let mut payload = [0u8; 1 + LINK_TEST_LEN];
+                payload[0] = pattern.to_byte();
+                payload[1..].copy_from_slice(&pattern.bytes());
+                Frame::new(MSG_LINK_TEST, &payload)
+            }
+            PicoMessage::Blit { page, col, data } => {
+                // Payload: [page][col][bytes...]
+                if !blit_in_bounds(*page, *col, data.len()) {
+                    return Err(FrameError::InvalidFrame);
+                }
+                let mut payload = [0u8; 2 + DISPLAY_WIDTH_PX];
+                payload[0] = *page;
+                payload[1] = *col;
+                payload[2..2 + data.len()].copy_from_slice(data);
+                Frame::new(MSG_BLIT, &payload[..2 + data.len()])
+            }
+            PicoMessage::SetContrast(contrast) => {
+                Frame::new(MSG_SET_CONTRAST, &[clamp_contrast(*contrast)])
+            }
+            PicoMessage::SchedulerState {
+                phase,
+                step,
+                segment,
+                rpm,
+                temp,
+                elapsed_s,
+            } => {
+                // Payload: [phase][step][segment][rpm:2][temp:2][elapsed:4]
+                let mut payload = [0u8; SCHEDULER_STATE_LEN];
+                payload[..3].copy_from_slice(&[*phase, *step, *segment]);
+                payload[3..5].copy_from_slice(&rpm.to_le_bytes());
+                payload[5..7].copy_from_slice(&temp.unwrap_or(TEMP_OFF_X10).to_le_bytes());
+                payload[7..].copy_from_slice(&elapsed_s.to_le_bytes());
+                Frame::new(MSG_SCHEDULER_STATE, &payload)
+            }
             PicoMessage::Reset => Ok(Frame::empty(MSG_RESET)),
         }
     }
@@ -101,6 +311,32 @@ pub enum ControllerCommand {
     },
     /// Invert a region (for selection highlight)
     Invert { row: u8, start_col: u8, end_col: u8 },
+    /// Set the heartbeat interval
+    LinkConfig { heartbeat_ms: u16 },
+    /// Versions the controller firmware supports
+    VersionInfo { config_schema: u8 },
+    /// Link diagnostic: test pattern and the bytes actually received
+    LinkTest {
+        pattern: LinkTestPattern,
+        received: Vec<u8, LINK_TEST_LEN>,
+    },
+    /// Write raw SH1106 page bytes starting at pixel column `col` of `page`
+    Blit {
+        page: u8,
+        col: u8,
+        data: Vec<u8, DISPLAY_WIDTH_PX>,
+    },
+    /// Set the display contrast (at least `MIN_CONTRAST`)
+    SetContrast(u8),
+    /// Scheduler state (see `PicoMessage::SchedulerState`)
+    SchedulerState {
+        phase: u8,
+        step: u8,
+        segment: u8,
+        rpm: u16,
+        temp: Option<i16>,
+        elapsed_s: u32,
+    },
     /// Reset display to boot state
     Reset,
 }
@@ -139,6 +375,79 @@ impl ControllerCommand {
                     end_col: frame.payload[2],
                 })
             }
+            MSG_LINK_CONFIG => {
+                if frame.payload.len() < 2 {
+                    return Err(FrameError::InvalidFrame);
+                }
+                let heartbeat_ms = u16::from_le_bytes([frame.payload[0], frame.payload[1]]);
+                if heartbeat_ms == 0 {
+                    return Err(FrameError::InvalidFrame);
+                }
+                Ok(ControllerCommand::LinkConfig { heartbeat_ms })
+            }
+            MSG_VERSION_INFO => {
+                if frame.payload.is_empty() {
+                    return Err(FrameError::InvalidFrame);
+                }
+                Ok(ControllerCommand::VersionInfo {
+                    config_schema: frame.payload[0],
+                })
+            }
+            MSG_LINK_TEST => {
+                let (&pattern, data) = frame
+                    .payload
+                    .split_first()
+                    .ok_or(FrameError::InvalidFrame)?;
+                let pattern =
+                    LinkTestPattern::from_byte(pattern).ok_or(FrameError::InvalidFrame)?;
+                // Keep what fits; extra bytes count as errors anyway
+                let mut received = Vec::new();
+                for &byte in data.iter().take(LINK_TEST_LEN) {
+                    let _ = received.push(byte);
+                }
+                Ok(ControllerCommand::LinkTest { pattern, received })
+            }
+            MSG_BLIT => {
+                let [page, col, bytes @ ..] = frame.payload.as_slice() else {
+                    return Err(FrameError::InvalidFrame);
+                };
+                if !blit_in_bounds(*page, *col, bytes.len()) {
+                    return Err(FrameError::InvalidFrame);
+                }
+                let data = Vec::from_slice(bytes).map_err(|_| FrameError::InvalidFrame)?;
+                Ok(ControllerCommand::Blit {
+                    page: *page,
+                    col: *col,
+                    data,
+                })
+            }
+            MSG_SET_CONTRAST => {
+                let [contrast] = frame.payload.as_slice() else {
+                    return Err(FrameError::InvalidFrame);
+                };
+                Ok(ControllerCommand::SetContrast(clamp_contrast(*contrast)))
+            }
+            MSG_SCHEDULER_STATE => {
+                let payload: &[u8; SCHEDULER_STATE_LEN] = frame
+                    .payload
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| FrameError::InvalidFrame)?;
+                let temp = i16::from_le_bytes([payload[5], payload[6]]);
+                Ok(ControllerCommand::SchedulerState {
+                    phase: payload[0],
+                    step: payload[1],
+                    segment: payload[2],
+                    rpm: u16::from_le_bytes([payload[3], payload[4]]),
+                    temp: (temp != TEMP_OFF_X10).then_some(temp),
+                    elapsed_s: u32::from_le_bytes([
+                        payload[7],
+                        payload[8],
+                        payload[9],
+                        payload[10],
+                    ]),
+                })
+            }
             MSG_RESET => Ok(ControllerCommand::Reset),
             _ => Err(FrameError::InvalidFrame),
         }
@@ -155,6 +464,15 @@ pub enum DisplayCommand {
     Ping,
     /// Acknowledgement of a received command
     Ack { seq: u8 },
+    /// A sequenced command was received but rejected; resend it
+    Nack { seq: u8 },
+    /// Request a controlled controller restart (refused unless idle)
+    SoftReset,
+    /// Link diagnostic result: checksum of the received pattern and the
+    /// number of bytes that differed from it
+    LinkTestResult { checksum: u16, errors: u8 },
+    /// Ask for a `PicoMessage::SchedulerState` (sent by a host)
+    RequestSchedulerState,
 }
 
 impl DisplayCommand {
@@ -178,6 +496,25 @@ impl DisplayCommand {
                     seq: frame.payload[0],
                 })
             }
+            MSG_NACK => {
+                if frame.payload.is_empty() {
+                    return Err(FrameError::InvalidFrame);
+                }
+                Ok(DisplayCommand::Nack {
+                    seq: frame.payload[0],
+                })
+            }
+            MSG_SOFT_RESET => Ok(DisplayCommand::SoftReset),
+            MSG_REQUEST_SCHEDULER_STATE => Ok(DisplayCommand::RequestSchedulerState),
+            MSG_LINK_TEST_RESULT => {
+                if frame.payload.len() < 3 {
+                    return Err(FrameError::InvalidFrame);
+                }
+                Ok(DisplayCommand::LinkTestResult {
+                    checksum: u16::from_le_bytes([frame.payload[0], frame.payload[1]]),
+                    errors: frame.payload[2],
+                })
+            }
             _ => Err(FrameError::InvalidFrame),
         }
     }
@@ -188,6 +525,13 @@ impl DisplayCommand {
             DisplayCommand::Input(event) => Frame::new(MSG_INPUT, &[event.to_byte()]),
             DisplayCommand::Ping => Ok(Frame::empty(MSG_PING)),
             DisplayCommand::Ack { seq } => Frame::new(MSG_ACK, &[*seq]),
+            DisplayCommand::Nack { seq } => Frame::new(MSG_NACK, &[*seq]),
+            DisplayCommand::SoftReset => Ok(Frame::empty(MSG_SOFT_RESET)),
+            DisplayCommand::LinkTestResult { checksum, errors } => {
+                let [lo, hi] = checksum.to_le_bytes();
+                Frame::new(MSG_LINK_TEST_RESULT, &[lo, hi, *errors])
+            }
+            DisplayCommand::RequestSchedulerState => Ok(Frame::empty(MSG_REQUEST_SCHEDULER_STATE)),
         }
     }
 }
@@ -233,6 +577,113 @@ mod tests {
         assert_eq!(frame.payload[2], 20);
     }
 
+    #[test]
+    fn test_link_config_roundtrip() {
+        let frame = PicoMessage::LinkConfig { heartbeat_ms: 2500 }
+            .to_frame()
+            .unwrap();
+        assert_eq!(frame.msg_type, MSG_LINK_CONFIG);
+        assert_eq!(
+            ControllerCommand::from_frame(&frame).unwrap(),
+            ControllerCommand::LinkConfig { heartbeat_ms: 2500 }
+        );
+
+        // Zero interval is rejected
+        let frame = Frame::new(MSG_LINK_CONFIG, &[0, 0]).unwrap();
+        assert!(ControllerCommand::from_frame(&frame).is_err());
+    }
+
+    #[test]
+    fn test_version_info_roundtrip() {
+        let frame = PicoMessage::VersionInfo { config_schema: 3 }
+            .to_frame()
+            .unwrap();
+        assert_eq!(frame.msg_type, MSG_VERSION_INFO);
+        assert_eq!(
+            ControllerCommand::from_frame(&frame).unwrap(),
+            ControllerCommand::VersionInfo { config_schema: 3 }
+        );
+
+        let frame = Frame::empty(MSG_VERSION_INFO);
+        assert!(ControllerCommand::from_frame(&frame).is_err());
+    }
+
+    #[test]
+    fn test_soft_reset_roundtrip() {
+        use crate::frame::FrameParser;
+
+        let frame = DisplayCommand::SoftReset.to_frame().unwrap();
+        assert_eq!(frame.msg_type, MSG_SOFT_RESET);
+        assert!(frame.payload.is_empty());
+
+        let mut buf = [0u8; 16];
+        let len = frame.encode(&mut buf).unwrap();
+        let decoded = FrameParser::new().feed_bytes(&buf[..len]).unwrap().unwrap();
+        assert_eq!(
+            DisplayCommand::from_frame(&decoded).unwrap(),
+            DisplayCommand::SoftReset
+        );
+    }
+
+    #[test]
+    fn test_link_test_roundtrip() {
+        for pattern in [LinkTestPattern::Incrementing, LinkTestPattern::Checkerboard] {
+            let frame = PicoMessage::LinkTest { pattern }.to_frame().unwrap();
+            assert_eq!(frame.msg_type, MSG_LINK_TEST);
+            assert_eq!(
+                ControllerCommand::from_frame(&frame).unwrap(),
+                ControllerCommand::LinkTest {
+                    pattern,
+                    received: Vec::from_slice(&pattern.bytes()).unwrap(),
+                }
+            );
+        }
+
+        // Unknown pattern is rejected
+        let frame = Frame::new(MSG_LINK_TEST, &[7, 0, 1]).unwrap();
+        assert!(ControllerCommand::from_frame(&frame).is_err());
+    }
+
+    #[test]
+    fn test_link_test_result_roundtrip() {
+        let original = DisplayCommand::LinkTestResult {
+            checksum: 0xBEEF,
+            errors: 3,
+        };
+        let frame = original.to_frame().unwrap();
+        assert_eq!(frame.msg_type, MSG_LINK_TEST_RESULT);
+        assert_eq!(DisplayCommand::from_frame(&frame).unwrap(), original);
+
+        let frame = Frame::new(MSG_LINK_TEST_RESULT, &[0xEF]).unwrap();
+        assert!(DisplayCommand::from_frame(&frame).is_err());
+    }
+
+    #[test]
+    fn test_link_test_verify() {
+        let pattern = LinkTestPattern::Checkerboard;
+
+        // Intact pattern echoes the expected checksum
+        assert_eq!(
+            pattern.verify(&pattern.bytes()),
+            DisplayCommand::LinkTestResult {
+                checksum: pattern.checksum(),
+                errors: 0,
+            }
+        );
+
+        // Two corrupted bytes and a dropped one
+        let mut corrupted = pattern.bytes();
+        corrupted[3] ^= 0x01;
+        corrupted[10] = 0x00;
+        let DisplayCommand::LinkTestResult { checksum, errors } =
+            pattern.verify(&corrupted[..LINK_TEST_LEN - 1])
+        else {
+            panic!("expected a link test result");
+        };
+        assert_ne!(checksum, pattern.checksum());
+        assert_eq!(errors, 3);
+    }
+
     #[test]
     fn test_display_command_input() {
         let frame = Frame::new(MSG_INPUT, &[0x01]).unwrap(); // ENCODER_CW
@@ -249,9 +700,184 @@ mod tests {
 
     #[test]
     fn test_display_command_roundtrip() {
-        let original = DisplayCommand::Input(InputEvent::EncoderClick);
-        let frame = original.to_frame().unwrap();
-        let parsed = DisplayCommand::from_frame(&frame).unwrap();
-        assert_eq!(original, parsed);
+        for original in [
+            DisplayCommand::Input(InputEvent::EncoderClick),
+            DisplayCommand::Ack { seq: 7 },
+            DisplayCommand::Nack { seq: 200 },
+        ] {
+            let frame = original.to_frame().unwrap();
+            let parsed = DisplayCommand::from_frame(&frame).unwrap();
+            assert_eq!(original, parsed);
+        }
+        assert!(DisplayCommand::from_frame(&Frame::empty(MSG_NACK)).is_err());
+    }
+
+    #[test]
+    fn test_scheduler_state_roundtrip() {
+        for temp in [Some(455), Some(-40), None] {
+            let frame = PicoMessage::SchedulerState {
+                phase: 2,
+                step: 3,
+                segment: 5,
+                rpm: 150,
+                temp,
+                elapsed_s: 70_000,
+            }
+            .to_frame()
+            .unwrap();
+            assert_eq!(frame.msg_type, MSG_SCHEDULER_STATE);
+            assert_eq!(frame.payload.len(), SCHEDULER_STATE_LEN);
+            assert_eq!(
+                ControllerCommand::from_frame(&frame).unwrap(),
+                ControllerCommand::SchedulerState {
+                    phase: 2,
+                    step: 3,
+                    segment: 5,
+                    rpm: 150,
+                    temp,
+                    elapsed_s: 70_000,
+                }
+            );
+        }
+
+        // Truncated state is rejected
+        let frame = Frame::new(MSG_SCHEDULER_STATE, &[1, 0, 0, 150, 0]).unwrap();
+        assert!(ControllerCommand::from_frame(&frame).is_err());
+
+        // The request carries no payload
+        let frame = DisplayCommand::RequestSchedulerState.to_frame().unwrap();
+        assert_eq!(frame.msg_type, MSG_REQUEST_SCHEDULER_STATE);
+        assert!(frame.payload.is_empty());
+        assert_eq!(
+            DisplayCommand::from_frame(&frame).unwrap(),
+            DisplayCommand::RequestSchedulerState
+        );
+    }
+
+    #[test]
+    fn test_set_contrast_roundtrip() {
+        for contrast in [MIN_CONTRAST, 0x80, DEFAULT_CONTRAST, 0xFF] {
+            let frame = PicoMessage::SetContrast(contrast).to_frame().unwrap();
+            assert_eq!(frame.msg_type, MSG_SET_CONTRAST);
+            assert_eq!(
+                ControllerCommand::from_frame(&frame).unwrap(),
+                ControllerCommand::SetContrast(contrast)
+            );
+        }
+        assert!(ControllerCommand::from_frame(&Frame::empty(MSG_SET_CONTRAST)).is_err());
+    }
+
+    #[test]
+    fn test_contrast_clamped() {
+        assert_eq!(clamp_contrast(0), MIN_CONTRAST);
+        assert_eq!(clamp_contrast(MIN_CONTRAST - 1), MIN_CONTRAST);
+        assert_eq!(clamp_contrast(0xFF), 0xFF);
+
+        // Too-dim values are raised when sent...
+        let frame = PicoMessage::SetContrast(0).to_frame().unwrap();
+        assert_eq!(frame.payload.as_slice(), &[MIN_CONTRAST]);
+
+        // ...and when received from a controller that did not clamp them
+        let frame = Frame::new(MSG_SET_CONTRAST, &[0x01]).unwrap();
+        assert_eq!(
+            ControllerCommand::from_frame(&frame).unwrap(),
+            ControllerCommand::SetContrast(MIN_CONTRAST)
+        );
+    }
+
+    #[test]
+    fn test_blit_roundtrip() {
+        let data = [0x00, 0xFF, 0x81, 0x7E];
+        let frame = PicoMessage::Blit {
+            page: 3,
+            col: 60,
+            data: &data,
+        }
+        .to_frame()
+        .unwrap();
+        assert_eq!(frame.msg_type, MSG_BLIT);
+        assert_eq!(frame.payload.as_slice(), &[3, 60, 0x00, 0xFF, 0x81, 0x7E]);
+
+        let ControllerCommand::Blit {
+            page,
+            col,
+            data: decoded,
+        } = ControllerCommand::from_frame(&frame).unwrap()
+        else {
+            panic!("expected a blit");
+        };
+        assert_eq!((page, col), (3, 60));
+        assert_eq!(decoded.as_slice(), &data);
+
+        // A full page row fits in one frame
+        let row = [0xAA; DISPLAY_WIDTH_PX];
+        let msg = PicoMessage::Blit {
+            page: 7,
+            col: 0,
+            data: &row,
+        };
+        assert!(msg.to_frame().is_ok());
+    }
+
+    #[test]
+    fn test_blit_out_of_range_rejected() {
+        let data = [0xFF; 8];
+        for (page, col) in [(DISPLAY_ROWS, 0), (0, 121), (0, 200)] {
+            let msg = PicoMessage::Blit {
+                page,
+                col,
+                data: &data,
+            };
+            assert_eq!(msg.to_frame(), Err(FrameError::InvalidFrame));
+
+            let mut payload = Vec::<u8, 16>::new();
+            payload.extend_from_slice(&[page, col]).unwrap();
+            payload.extend_from_slice(&data).unwrap();
+            let frame = Frame::new(MSG_BLIT, &payload).unwrap();
+            assert_eq!(
+                ControllerCommand::from_frame(&frame),
+                Err(FrameError::InvalidFrame)
+            );
+        }
+
+        // Ending exactly at the right edge is fine; a missing header is not
+        let msg = PicoMessage::Blit {
+            page: 0,
+            col: 120,
+            data: &data,
+        };
+        assert!(msg.to_frame().is_ok());
+        let frame = Frame::new(MSG_BLIT, &[0]).unwrap();
+        assert!(ControllerCommand::from_frame(&frame).is_err());
+    }
+
+    #[test]
+    fn test_blit_bitmap_spans_frames() {
+        // A 16x24 bitmap covers three pages
+        let mut bitmap = [0u8; 48];
+        for (i, byte) in bitmap.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        let mut pages = 0;
+        for (i, msg) in blit_bitmap(2, 100, 16, &bitmap).enumerate() {
+            let frame = msg.to_frame().unwrap();
+            assert!(frame.payload.len() <= MAX_PAYLOAD_SIZE);
+            let ControllerCommand::Blit { page, col, data } =
+                ControllerCommand::from_frame(&frame).unwrap()
+            else {
+                panic!("expected a blit");
+            };
+            assert_eq!((page, col), (2 + i as u8, 100));
+            assert_eq!(data.as_slice(), &bitmap[i * 16..(i + 1) * 16]);
+            pages += 1;
+        }
+        assert_eq!(pages, 3);
+
+        // Running off the bottom of the display fails to encode
+        let mut frames = blit_bitmap(6, 0, 16, &bitmap);
+        assert!(frames.next().unwrap().to_frame().is_ok());
+        assert!(frames.next().unwrap().to_frame().is_ok());
+        assert!(frames.next().unwrap().to_frame().is_err());
     }
 }
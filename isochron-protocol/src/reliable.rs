@@ -0,0 +1,262 @@
+//! Optional reliable delivery for controller commands
+//!
+//! Screen commands are normally fire-and-forget: a dropped `Text` frame
+//! leaves stale content on the display until the next full redraw. With
+//! reliable delivery each frame is numbered (see [`Frame::with_seq`]) and
+//! the display answers every sequenced frame it accepts with
+//! [`DisplayCommand::Ack`](crate::DisplayCommand::Ack). The sender keeps
+//! the frame until then and sends it again if no ACK arrives in time, or
+//! straight away on a NACK.
+//!
+//! One frame is in flight at a time. Unsequenced frames, such as the
+//! heartbeat, are unaffected and can be sent alongside.
+
+use crate::frame::Frame;
+
+/// Default time to wait for an ACK before resending (ms)
+pub const DEFAULT_RETRANSMIT_MS: u32 = 200;
+
+/// Default resends before a frame is given up on
+pub const DEFAULT_MAX_RETRIES: u8 = 3;
+
+/// Frame waiting for its ACK
+#[derive(Debug, Clone)]
+struct Pending {
+    frame: Frame,
+    /// When the frame was last sent (ms)
+    sent_ms: u32,
+    /// Resends so far
+    retries: u8,
+}
+
+/// Sender side of reliable delivery
+///
+/// Numbers frames, holds the last unacknowledged one and hands it back
+/// for resending when its ACK is overdue.
+#[derive(Debug, Clone)]
+pub struct ReliableSender {
+    /// Time to wait for an ACK (ms)
+    timeout_ms: u32,
+    /// Resends before giving up on a frame
+    max_retries: u8,
+    /// Sequence number for the next frame
+    next_seq: u8,
+    /// Frame in flight
+    pending: Option<Pending>,
+    /// Frames given up on
+    dropped: u16,
+}
+
+impl Default for ReliableSender {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETRANSMIT_MS, DEFAULT_MAX_RETRIES)
+    }
+}
+
+impl ReliableSender {
+    /// Create a sender resending after `timeout_ms`, at most `max_retries` times
+    pub fn new(timeout_ms: u32, max_retries: u8) -> Self {
+        Self {
+            timeout_ms,
+            max_retries,
+            next_seq: 0,
+            pending: None,
+            dropped: 0,
+        }
+    }
+
+    /// Number `frame` and hold it until acknowledged
+    ///
+    /// Returns the frame to transmit, or None while the previous frame is
+    /// still waiting for its ACK.
+    pub fn send(&mut self, frame: Frame, now_ms: u32) -> Option<Frame> {
+        if self.pending.is_some() {
+            return None;
+        }
+
+        let frame = frame.with_seq(self.next_seq);
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.pending = Some(Pending {
+            frame: frame.clone(),
+            sent_ms: now_ms,
+            retries: 0,
+        });
+        Some(frame)
+    }
+
+    /// Check if a frame is waiting for its ACK
+    pub fn is_waiting(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Number of frames given up on after running out of retries
+    pub fn dropped(&self) -> u16 {
+        self.dropped
+    }
+
+    /// Handle an ACK; returns true if it completed the frame in flight
+    ///
+    /// ACKs for other sequence numbers, e.g. duplicates of an earlier
+    /// resend, are ignored.
+    pub fn handle_ack(&mut self, seq: u8) -> bool {
+        if self.pending.as_ref().and_then(|p| p.frame.seq) != Some(seq) {
+            return false;
+        }
+        self.pending = None;
+        true
+    }
+
+    /// Handle a NACK, returning the frame to resend straight away
+    pub fn handle_nack(&mut self, seq: u8, now_ms: u32) -> Option<Frame> {
+        if self.pending.as_ref().and_then(|p| p.frame.seq) != Some(seq) {
+            return None;
+        }
+        self.resend(now_ms)
+    }
+
+    /// Check the ACK timeout, returning the frame to resend if it expired
+    pub fn poll(&mut self, now_ms: u32) -> Option<Frame> {
+        let sent_ms = self.pending.as_ref()?.sent_ms;
+        if now_ms.wrapping_sub(sent_ms) < self.timeout_ms {
+            return None;
+        }
+        self.resend(now_ms)
+    }
+
+    /// Resend the frame in flight, or give up once out of retries
+    fn resend(&mut self, now_ms: u32) -> Option<Frame> {
+        let pending = self.pending.as_mut()?;
+        if pending.retries >= self.max_retries {
+            self.pending = None;
+            self.dropped = self.dropped.saturating_add(1);
+            return None;
+        }
+        pending.retries += 1;
+        pending.sent_ms = now_ms;
+        Some(pending.frame.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::{FrameParser, MAX_FRAME_SIZE};
+    use crate::messages::{ControllerCommand, PicoMessage};
+    use heapless::Vec;
+
+    /// Display end of a simulated link: parses frames and ACKs them
+    struct Receiver {
+        parser: FrameParser,
+        accepted: Vec<ControllerCommand, 8>,
+    }
+
+    impl Receiver {
+        fn new() -> Self {
+            Self {
+                parser: FrameParser::new(),
+                accepted: Vec::new(),
+            }
+        }
+
+        fn receive(&mut self, bytes: &[u8]) -> Option<u8> {
+            let frame = self.parser.feed_bytes(bytes).unwrap()?;
+            let cmd = ControllerCommand::from_frame(&frame).unwrap();
+            self.accepted.push(cmd).unwrap();
+            frame.seq
+        }
+    }
+
+    fn text(row: u8, text: &str) -> Frame {
+        PicoMessage::Text { row, col: 0, text }.to_frame().unwrap()
+    }
+
+    fn wire(frame: &Frame) -> Vec<u8, MAX_FRAME_SIZE> {
+        frame.encode_to_vec().unwrap()
+    }
+
+    #[test]
+    fn test_single_drop_retransmitted_once() {
+        let mut sender = ReliableSender::new(100, 3);
+        let mut display = Receiver::new();
+        let mut queue = [text(0, "Clean"), text(1, "Rinse"), text(2, "Dry")].into_iter();
+        let mut transmissions = 0;
+        let mut dropped_one = false;
+
+        let mut next = queue.next();
+        for now_ms in (0..2000).step_by(10) {
+            let outgoing = match next.take() {
+                Some(frame) => match sender.send(frame.clone(), now_ms) {
+                    Some(sent) => {
+                        next = queue.next();
+                        Some(sent)
+                    }
+                    None => {
+                        next = Some(frame);
+                        sender.poll(now_ms)
+                    }
+                },
+                None => sender.poll(now_ms),
+            };
+            let Some(frame) = outgoing else { continue };
+            transmissions += 1;
+
+            // The link loses the first transmission of the second frame
+            if frame.seq == Some(1) && !dropped_one {
+                dropped_one = true;
+                continue;
+            }
+            if let Some(seq) = display.receive(&wire(&frame)) {
+                assert!(sender.handle_ack(seq));
+            }
+        }
+
+        assert_eq!(transmissions, 4);
+        assert!(!sender.is_waiting());
+        assert_eq!(sender.dropped(), 0);
+        let rows: Vec<u8, 8> = display
+            .accepted
+            .iter()
+            .map(|cmd| match cmd {
+                ControllerCommand::Text { row, .. } => *row,
+                _ => panic!("unexpected command"),
+            })
+            .collect();
+        assert_eq!(rows.as_slice(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_waits_for_ack_before_next_frame() {
+        let mut sender = ReliableSender::default();
+        let first = sender.send(text(0, "Clean"), 0).unwrap();
+        assert_eq!(first.seq, Some(0));
+        assert!(sender.send(text(1, "Rinse"), 10).is_none());
+        assert_eq!(sender.poll(DEFAULT_RETRANSMIT_MS - 1), None);
+
+        // A stale ACK doesn't release the frame
+        assert!(!sender.handle_ack(5));
+        assert!(sender.handle_ack(0));
+        assert_eq!(sender.send(text(1, "Rinse"), 20).unwrap().seq, Some(1));
+    }
+
+    #[test]
+    fn test_nack_resends_immediately() {
+        let mut sender = ReliableSender::new(100, 3);
+        let sent = sender.send(text(0, "Clean"), 0).unwrap();
+        assert_eq!(sender.handle_nack(9, 5), None);
+        assert_eq!(sender.handle_nack(0, 5), Some(sent));
+        // The timeout restarts from the resend
+        assert_eq!(sender.poll(100), None);
+        assert!(sender.poll(105).is_some());
+    }
+
+    #[test]
+    fn test_gives_up_after_max_retries() {
+        let mut sender = ReliableSender::new(100, 2);
+        sender.send(text(0, "Clean"), 0).unwrap();
+        assert!(sender.poll(100).is_some());
+        assert!(sender.poll(200).is_some());
+        assert_eq!(sender.poll(300), None);
+        assert!(!sender.is_waiting());
+        assert_eq!(sender.dropped(), 1);
+    }
+}